@@ -0,0 +1,82 @@
+use boon::{Compiler, SchemaIndex, Schemas, ValidationOptions};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use serde_json::{json, Value};
+
+fn compile(schema: Value) -> (Schemas, SchemaIndex) {
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler
+        .add_resource("http://tmp.com/schema.json", schema)
+        .unwrap();
+    let sch = compiler
+        .compile("http://tmp.com/schema.json", &mut schemas)
+        .unwrap();
+    (schemas, sch)
+}
+
+pub fn arrays(c: &mut Criterion) {
+    let mut group = c.benchmark_group("primitiveArrays");
+    let (schemas, sch) = compile(json!({"items": {"type": "number", "minimum": 0}}));
+    let default = ValidationOptions::new();
+    for n in [100, 100_000] {
+        let valid = Value::from((0..n).map(|i| i as f64 / 2.0).collect::<Vec<_>>());
+        group.bench_with_input(BenchmarkId::new("numbers/validate", n), &valid, |b, v| {
+            b.iter(|| schemas.validate(v, sch).is_ok())
+        });
+        group.bench_with_input(BenchmarkId::new("numbers/is_valid", n), &valid, |b, v| {
+            b.iter(|| schemas.is_valid_with(v, sch, &default))
+        });
+
+        // every 100th item fails
+        let invalid = Value::from(
+            (0..n)
+                .map(|i| if i % 100 == 0 { -1 } else { i })
+                .collect::<Vec<_>>(),
+        );
+        group.bench_with_input(BenchmarkId::new("numbers/invalid", n), &invalid, |b, v| {
+            b.iter(|| schemas.validate(v, sch).is_ok())
+        });
+    }
+
+    let (schemas, sch) = compile(json!({"items": {"type": "string", "maxLength": 8}}));
+    let n = 100_000;
+    let strings = Value::from((0..n).map(|i| format!("s{i}")).collect::<Vec<_>>());
+    group.bench_with_input(BenchmarkId::new("strings/validate", n), &strings, |b, v| {
+        b.iter(|| schemas.validate(v, sch).is_ok())
+    });
+    group.finish();
+}
+
+// large objects, which differ only in `id`, next to identical 10-level
+// deep `config`.
+fn config(id: usize) -> Value {
+    let mut v = json!({});
+    for _ in 0..10 {
+        let mut obj = serde_json::Map::new();
+        for k in 0..100 {
+            obj.insert(format!("key{k}"), json!({"enabled": true, "weight": k}));
+        }
+        obj.insert("inner".to_owned(), v);
+        v = Value::Object(obj);
+    }
+    json!({"config": v, "id": id})
+}
+
+pub fn unique_items(c: &mut Criterion) {
+    let mut group = c.benchmark_group("uniqueItems");
+    let (schemas, sch) = compile(json!({"uniqueItems": true}));
+    let hashed = ValidationOptions::new().hash_unique_items(true);
+    for n in [20, 500] {
+        let configs = Value::from((0..n).map(config).collect::<Vec<_>>());
+        group.bench_with_input(BenchmarkId::new("configs/default", n), &configs, |b, v| {
+            b.iter(|| schemas.validate(v, sch).is_ok())
+        });
+        group.bench_with_input(BenchmarkId::new("configs/hashed", n), &configs, |b, v| {
+            b.iter(|| schemas.is_valid_with(v, sch, &hashed))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, arrays, unique_items);
+criterion_main!(benches);
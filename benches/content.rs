@@ -0,0 +1,43 @@
+use base64::Engine;
+use boon::{Compiler, Schemas};
+use criterion::{criterion_group, criterion_main, Criterion};
+use serde_json::{json, Value};
+
+pub fn content(c: &mut Criterion) {
+    // 5 branches, each decoding same payload; only last one matches
+    let branches = (0..5)
+        .map(|i| {
+            json!({
+                "contentEncoding": "base64",
+                "contentMediaType": "application/json",
+                "contentSchema": {"items": {"type": "integer", "minimum": 4 - i}}
+            })
+        })
+        .collect::<Vec<_>>();
+    let schema = json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "anyOf": branches
+    });
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.enable_content_assertions();
+    compiler
+        .add_resource("http://tmp.com/schema.json", schema)
+        .unwrap();
+    let sch = compiler
+        .compile("http://tmp.com/schema.json", &mut schemas)
+        .unwrap();
+
+    // ~1MB of base64
+    let payload = Value::from((0..150_000).map(|i| i % 100).collect::<Vec<_>>()).to_string();
+    let payload = base64::engine::general_purpose::STANDARD.encode(payload);
+    let instance = Value::String(payload);
+    assert!(schemas.validate(&instance, sch).is_ok());
+
+    c.bench_function("content/anyOf_5", |b| {
+        b.iter(|| schemas.validate(&instance, sch).is_ok())
+    });
+}
+
+criterion_group!(benches, content);
+criterion_main!(benches);
@@ -0,0 +1,58 @@
+use boon::{Compiler, SchemaIndex, Schemas};
+use criterion::{criterion_group, criterion_main, Criterion};
+use serde_json::{json, Map, Value};
+
+// generated schemas, like kubernetes CRDs, have many no-op subschemas
+fn schema(depth: usize) -> Value {
+    let any = json!({
+        "type": ["null", "boolean", "object", "array", "number", "string", "integer"]
+    });
+    if depth == 0 {
+        return json!({
+            "description": "leaf field",
+            "allOf": [{}, any],
+            "x-kubernetes-int-or-string": true
+        });
+    }
+    let props = (0..8)
+        .map(|i| (format!("f{i}"), schema(depth - 1)))
+        .collect::<Map<_, _>>();
+    json!({
+        "description": "nested object",
+        "type": "object",
+        "allOf": [{}, any, { "description": "no-op" }],
+        "properties": props,
+        "x-kubernetes-preserve-unknown-fields": true
+    })
+}
+
+fn instance(depth: usize) -> Value {
+    if depth == 0 {
+        return json!("value");
+    }
+    let obj = (0..8)
+        .map(|i| (format!("f{i}"), instance(depth - 1)))
+        .collect::<Map<_, _>>();
+    Value::Object(obj)
+}
+
+fn compile(schema: Value) -> (Schemas, SchemaIndex) {
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler
+        .add_resource("http://tmp.com/crd.json", schema)
+        .unwrap();
+    let sch = compiler
+        .compile("http://tmp.com/crd.json", &mut schemas)
+        .unwrap();
+    (schemas, sch)
+}
+
+pub fn crd(c: &mut Criterion) {
+    let (schemas, sch) = compile(schema(3));
+    let inst = instance(3);
+    c.bench_function("crd", |b| b.iter(|| schemas.validate(&inst, sch).unwrap()));
+}
+
+criterion_group!(benches, crd);
+criterion_main!(benches);
@@ -0,0 +1,57 @@
+use boon::{Compiler, SchemaIndex, Schemas};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use serde_json::{json, Value};
+
+fn compile(schema: Value) -> (Schemas, SchemaIndex) {
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler
+        .add_resource("http://tmp.com/schema.json", schema)
+        .unwrap();
+    let sch = compiler
+        .compile("http://tmp.com/schema.json", &mut schemas)
+        .unwrap();
+    (schemas, sch)
+}
+
+// `depth` levels of objects, using only `properties` and `type`
+fn schema(depth: usize) -> Value {
+    let mut sch = json!({"type": "integer"});
+    for _ in 0..depth {
+        sch = json!({
+            "type": "object",
+            "properties": {
+                "name": {"type": "string"},
+                "size": {"type": "integer"},
+                "next": sch
+            }
+        });
+    }
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "allOf": [sch]
+    })
+}
+
+fn instance(depth: usize) -> Value {
+    let mut v = json!(1);
+    for i in 0..depth {
+        v = json!({"name": format!("n{i}"), "size": i, "next": v});
+    }
+    v
+}
+
+pub fn deep_properties(c: &mut Criterion) {
+    let mut group = c.benchmark_group("deep_properties");
+    for depth in [5, 25] {
+        let (schemas, sch) = compile(schema(depth));
+        let inst = instance(depth);
+        group.bench_with_input(BenchmarkId::from_parameter(depth), &inst, |b, inst| {
+            b.iter(|| schemas.validate(inst, sch).is_ok())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, deep_properties);
+criterion_main!(benches);
@@ -0,0 +1,67 @@
+use boon::{Compiler, Schemas};
+use criterion::{criterion_group, criterion_main, Criterion};
+use serde_json::{json, Value};
+
+// small registry-like document
+fn schema(i: usize) -> Value {
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "type": "object",
+        "required": ["id", "name"],
+        "properties": {
+            "id": { "const": i },
+            "name": { "type": "string", "pattern": format!("^n{i}-[a-z]+$") },
+            "email": { "type": "string", "format": "email" },
+            "tags": { "type": "array", "items": { "$ref": "#/$defs/tag" } },
+            "owner": { "$ref": "#/$defs/person" }
+        },
+        "$defs": {
+            "tag": { "type": "string", "minLength": 1, "maxLength": 32 },
+            "person": {
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string" },
+                    "age": { "type": "integer", "minimum": 0 }
+                }
+            }
+        }
+    })
+}
+
+fn compiler(locs: &[String]) -> Compiler {
+    let mut c = Compiler::new();
+    for (i, loc) in locs.iter().enumerate() {
+        c.add_resource(loc, schema(i)).unwrap();
+    }
+    c
+}
+
+pub fn compile_all(c: &mut Criterion) {
+    let locs = (0..200)
+        .map(|i| format!("http://tmp.com/s{i}.json"))
+        .collect::<Vec<_>>();
+    let mut group = c.benchmark_group("compile-200");
+    group.bench_function("sequential", |b| {
+        b.iter_batched(
+            || compiler(&locs),
+            |mut c| {
+                let mut schemas = Schemas::new();
+                for loc in &locs {
+                    c.compile(loc, &mut schemas).unwrap();
+                }
+            },
+            criterion::BatchSize::LargeInput,
+        )
+    });
+    group.bench_function("parallel", |b| {
+        b.iter_batched(
+            || compiler(&locs),
+            |mut c| c.compile_all_parallel(locs.clone()).unwrap(),
+            criterion::BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, compile_all);
+criterion_main!(benches);
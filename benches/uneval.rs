@@ -0,0 +1,54 @@
+use boon::{Compiler, SchemaIndex, Schemas};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use serde_json::{json, Map, Value};
+
+fn compile(schema: Value) -> (Schemas, SchemaIndex) {
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler
+        .add_resource("http://tmp.com/schema.json", schema)
+        .unwrap();
+    let sch = compiler
+        .compile("http://tmp.com/schema.json", &mut schemas)
+        .unwrap();
+    (schemas, sch)
+}
+
+// anyOf with `n` branches, each evaluating one property
+fn schema(n: usize) -> Value {
+    let any_of = (0..n)
+        .map(|i| json!({"properties": {format!("p{i}"): {"type": "integer"}}}))
+        .collect::<Vec<_>>();
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "anyOf": any_of,
+        "unevaluatedProperties": {"type": "integer"}
+    })
+}
+
+// object with `n` members
+fn instance(n: usize) -> Value {
+    let obj = (0..n)
+        .map(|i| (format!("p{i}"), json!(i)))
+        .collect::<Map<_, _>>();
+    Value::Object(obj)
+}
+
+pub fn uneval(c: &mut Criterion) {
+    let mut group = c.benchmark_group("unevaluatedProperties");
+    for branches in [5, 50] {
+        let (schemas, sch) = compile(schema(branches));
+        for n in [50, 5_000] {
+            let inst = instance(n);
+            group.bench_with_input(
+                BenchmarkId::new(format!("anyOf-{branches}"), n),
+                &inst,
+                |b, inst| b.iter(|| schemas.validate(inst, sch).is_ok()),
+            );
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, uneval);
+criterion_main!(benches);
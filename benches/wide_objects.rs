@@ -0,0 +1,77 @@
+use boon::{Compiler, SchemaIndex, Schemas, ValidationOptions};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use serde_json::{json, Map, Value};
+
+fn compile(schema: Value) -> (Schemas, SchemaIndex) {
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler
+        .add_resource("http://tmp.com/schema.json", schema)
+        .unwrap();
+    let sch = compiler
+        .compile("http://tmp.com/schema.json", &mut schemas)
+        .unwrap();
+    (schemas, sch)
+}
+
+// 20 properties, with additionalProperties false
+fn schema(pattern_properties: bool) -> Value {
+    let props = (0..20)
+        .map(|i| (format!("p{i}"), json!({"type": "integer"})))
+        .collect::<Map<_, _>>();
+    let mut schema = json!({
+        "properties": props,
+        "additionalProperties": false
+    });
+    if pattern_properties {
+        schema["patternProperties"] = json!({
+            "^x-": {"type": "string"},
+            "^[a-z]+_[0-9]+$": true,
+        });
+    }
+    schema
+}
+
+// object with `n` members, all but first 20 are additional
+fn instance(n: usize) -> Value {
+    let obj = (0..n)
+        .map(|i| (format!("p{i}"), json!(i)))
+        .collect::<Map<_, _>>();
+    Value::Object(obj)
+}
+
+pub fn wide_objects(c: &mut Criterion) {
+    let mut group = c.benchmark_group("additionalProperties");
+    for pattern_properties in [false, true] {
+        let (schemas, sch) = compile(schema(pattern_properties));
+        let name = if pattern_properties {
+            "with-patternProperties"
+        } else {
+            "without-patternProperties"
+        };
+        let default = ValidationOptions::new();
+        let fail_fast = ValidationOptions::new().fail_fast(true);
+        for n in [20, 10_000] {
+            let inst = instance(n);
+            group.bench_with_input(
+                BenchmarkId::new(format!("{name}/validate"), n),
+                &inst,
+                |b, inst| b.iter(|| schemas.validate(inst, sch).is_ok()),
+            );
+            group.bench_with_input(
+                BenchmarkId::new(format!("{name}/fail-fast"), n),
+                &inst,
+                |b, inst| b.iter(|| schemas.validate_with(inst, sch, &fail_fast).is_ok()),
+            );
+            group.bench_with_input(
+                BenchmarkId::new(format!("{name}/is_valid"), n),
+                &inst,
+                |b, inst| b.iter(|| schemas.is_valid_with(inst, sch, &default)),
+            );
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, wide_objects);
+criterion_main!(benches);
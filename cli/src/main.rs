@@ -1,7 +1,15 @@
 use core::panic;
-use std::{env, error::Error, fs::File, io::BufReader, process, str::FromStr, sync::Arc};
+use std::{
+    env,
+    error::Error,
+    fs::{self, File},
+    io::{BufReader, Read},
+    process,
+    str::FromStr,
+    sync::Arc,
+};
 
-use boon::{Compiler, Draft, Schemas, SchemeUrlLoader, UrlLoader};
+use boon::{json_from_bytes, Compiler, Draft, Resource, Schemas, SchemeUrlLoader, UrlLoader};
 use getopts::Options;
 use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
 use serde_json::Value;
@@ -209,15 +217,14 @@ impl UrlLoader for FileUrlLoader {
     fn load(&self, url: &str) -> Result<Value, Box<dyn Error>> {
         let url = Url::parse(url)?;
         let path = url.to_file_path().map_err(|_| "invalid file path")?;
-        let file = File::open(&path)?;
         if path
             .extension()
             .filter(|&ext| ext == "yaml" || ext == "yml")
             .is_some()
         {
-            Ok(serde_yaml::from_reader(file)?)
+            Ok(serde_yaml::from_reader(File::open(&path)?)?)
         } else {
-            Ok(serde_json::from_reader(file)?)
+            json_from_bytes(&fs::read(&path)?)
         }
     }
 }
@@ -256,16 +263,28 @@ impl HttpUrlLoader {
 
 impl UrlLoader for HttpUrlLoader {
     fn load(&self, url: &str) -> Result<Value, Box<dyn Error>> {
-        let response = self.0.get(url).call()?;
-        let is_yaml = url.ends_with(".yaml") || url.ends_with(".yml") || {
-            let ctype = response.content_type();
-            ctype.ends_with("/yaml") || ctype.ends_with("-yaml")
-        };
-        if is_yaml {
-            Ok(serde_yaml::from_reader(response.into_reader())?)
+        self.load_resource(url)?.into_json()
+    }
+
+    fn load_resource(&self, url: &str) -> Result<Resource, Box<dyn Error>> {
+        let response = self
+            .0
+            .get(url)
+            .set("Accept-Encoding", "gzip, deflate, br")
+            .call()?;
+        let content_encoding = response.header("Content-Encoding").map(str::to_owned);
+        let content_type = if url.ends_with(".yaml") || url.ends_with(".yml") {
+            "application/yaml".to_owned()
         } else {
-            Ok(serde_json::from_reader(response.into_reader())?)
-        }
+            response.content_type().to_owned()
+        };
+        let mut bytes = Vec::new();
+        response.into_reader().read_to_end(&mut bytes)?;
+        Ok(Resource::Bytes {
+            bytes,
+            content_encoding,
+            content_type: Some(content_type),
+        })
     }
 }
 
@@ -0,0 +1,24 @@
+// validator binary used by scripts/size.sh to compare release binary size
+// across feature sets. usage: size SCHEMA INSTANCE
+
+use std::{env, error::Error, fs::File, process::ExitCode};
+
+use boon::{Compiler, Schemas};
+
+fn main() -> Result<ExitCode, Box<dyn Error>> {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let [schema, instance] = args.as_slice() else {
+        Err("usage: size SCHEMA INSTANCE")?
+    };
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    let sch = compiler.compile(schema, &mut schemas)?;
+    let instance = serde_json::from_reader(File::open(instance)?)?;
+    match schemas.validate(&instance, sch) {
+        Ok(_) => Ok(ExitCode::SUCCESS),
+        Err(e) => {
+            println!("{e:#}");
+            Ok(ExitCode::FAILURE)
+        }
+    }
+}
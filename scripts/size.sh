@@ -0,0 +1,24 @@
+#!/usr/bin/env bash
+# compares release binary size of examples/size.rs with default features
+# against minimal feature set, and checks both binaries validate alike.
+set -euo pipefail
+cd "$(dirname "$0")/.."
+
+build() {
+    cargo build --quiet --release --example size --target-dir "target/size/$1" "${@:2}"
+    echo "target/size/$1/release/examples/size"
+}
+
+default=$(build default)
+minimal=$(build minimal --no-default-features)
+
+for bin in "$default" "$minimal"; do
+    "$bin" tests/examples/schema.json tests/examples/instance.json >/dev/null
+done
+
+size() { wc -c <"$1" | tr -d ' '; }
+printf "%-8s %10s bytes\n" default "$(size "$default")" minimal "$(size "$minimal")"
+if [ "$(size "$minimal")" -ge "$(size "$default")" ]; then
+    echo "minimal build is not smaller" >&2
+    exit 1
+fi
@@ -0,0 +1,203 @@
+/*!
+Conversion of binary instance documents into json [`Value`].
+
+Values which have no json counterpart are mapped as follows:
+- byte strings are converted to standard base64 encoded strings
+- map keys must be strings, otherwise it is an error
+- on duplicate map keys, the last one wins
+- integers must fit in `i64` or `u64`, otherwise it is an error
+- floats must be finite, otherwise it is an error
+- cbor tags are ignored, only the tagged value is used
+- msgpack extension types are an error
+*/
+
+use base64::Engine;
+use serde_json::{Map, Number, Value};
+
+use crate::{InstanceError, InstanceLocation, InstanceToken};
+
+struct Converter {
+    tokens: Vec<InstanceToken<'static>>,
+}
+
+impl Converter {
+    fn unsupported(&self, message: impl Into<String>) -> InstanceError<'static> {
+        InstanceError::Unsupported {
+            instance_location: InstanceLocation {
+                tokens: self.tokens.clone(),
+            },
+            message: message.into(),
+        }
+    }
+
+    fn bytes(&self, b: &[u8]) -> Value {
+        Value::String(base64::engine::general_purpose::STANDARD.encode(b))
+    }
+
+    fn float(&self, f: f64) -> Result<Value, InstanceError<'static>> {
+        match Number::from_f64(f) {
+            Some(n) => Ok(Value::Number(n)),
+            None => Err(self.unsupported(format!("float {f} is not supported"))),
+        }
+    }
+
+    fn array<T>(
+        &mut self,
+        arr: Vec<T>,
+        convert: fn(&mut Self, T) -> Result<Value, InstanceError<'static>>,
+    ) -> Result<Value, InstanceError<'static>> {
+        let mut vec = Vec::with_capacity(arr.len());
+        for (i, item) in arr.into_iter().enumerate() {
+            self.tokens.push(i.into());
+            vec.push(convert(self, item)?);
+            self.tokens.pop();
+        }
+        Ok(Value::Array(vec))
+    }
+
+    fn object<T>(
+        &mut self,
+        entries: Vec<(Option<String>, T)>,
+        convert: fn(&mut Self, T) -> Result<Value, InstanceError<'static>>,
+    ) -> Result<Value, InstanceError<'static>> {
+        let mut obj = Map::new();
+        for (key, value) in entries {
+            let Some(key) = key else {
+                return Err(self.unsupported("non-string map key is not supported"));
+            };
+            self.tokens.push(key.clone().into());
+            let value = convert(self, value)?;
+            self.tokens.pop();
+            obj.insert(key, value);
+        }
+        Ok(Value::Object(obj))
+    }
+}
+
+// cbor --
+
+#[cfg(feature = "cbor")]
+pub(crate) fn cbor_to_json(bytes: &[u8]) -> Result<Value, InstanceError<'static>> {
+    use ciborium::de::Error;
+
+    let mut rd = bytes;
+    let v: ciborium::Value = ciborium::de::from_reader(&mut rd).map_err(|e| {
+        let (offset, message) = match e {
+            Error::Io(e) => (bytes.len(), e.to_string()),
+            Error::Syntax(offset) => (offset, "syntax error".to_owned()),
+            Error::Semantic(offset, msg) => (offset.unwrap_or(bytes.len() - rd.len()), msg),
+            Error::RecursionLimitExceeded => (
+                bytes.len() - rd.len(),
+                "recursion limit exceeded".to_owned(),
+            ),
+        };
+        InstanceError::Decode {
+            format: "cbor",
+            offset,
+            message,
+        }
+    })?;
+    if !rd.is_empty() {
+        return Err(trailing_bytes("cbor", bytes.len() - rd.len()));
+    }
+    Converter { tokens: vec![] }.cbor(v)
+}
+
+#[cfg(feature = "cbor")]
+impl Converter {
+    fn cbor(&mut self, v: ciborium::Value) -> Result<Value, InstanceError<'static>> {
+        use ciborium::Value as CV;
+        match v {
+            CV::Null => Ok(Value::Null),
+            CV::Bool(b) => Ok(Value::Bool(b)),
+            CV::Integer(i) => {
+                let i = i128::from(i);
+                if let Ok(i) = i64::try_from(i) {
+                    Ok(i.into())
+                } else if let Ok(u) = u64::try_from(i) {
+                    Ok(u.into())
+                } else {
+                    Err(self.unsupported(format!("integer {i} is out of range")))
+                }
+            }
+            CV::Float(f) => self.float(f),
+            CV::Text(s) => Ok(Value::String(s)),
+            CV::Bytes(b) => Ok(self.bytes(&b)),
+            CV::Tag(_, v) => self.cbor(*v),
+            CV::Array(arr) => self.array(arr, Self::cbor),
+            CV::Map(entries) => {
+                let entries = entries
+                    .into_iter()
+                    .map(|(k, v)| (k.into_text().ok(), v))
+                    .collect();
+                self.object(entries, Self::cbor)
+            }
+            _ => Err(self.unsupported("unknown cbor value")),
+        }
+    }
+}
+
+// msgpack --
+
+#[cfg(feature = "msgpack")]
+pub(crate) fn msgpack_to_json(bytes: &[u8]) -> Result<Value, InstanceError<'static>> {
+    let mut rd = bytes;
+    let v = rmpv::decode::read_value(&mut rd).map_err(|e| InstanceError::Decode {
+        format: "msgpack",
+        offset: bytes.len() - rd.len(),
+        message: e.to_string(),
+    })?;
+    if !rd.is_empty() {
+        return Err(trailing_bytes("msgpack", bytes.len() - rd.len()));
+    }
+    Converter { tokens: vec![] }.msgpack(v)
+}
+
+#[cfg(feature = "msgpack")]
+impl Converter {
+    fn msgpack(&mut self, v: rmpv::Value) -> Result<Value, InstanceError<'static>> {
+        use rmpv::Value as MV;
+        match v {
+            MV::Nil => Ok(Value::Null),
+            MV::Boolean(b) => Ok(Value::Bool(b)),
+            MV::Integer(i) => {
+                if let Some(i) = i.as_i64() {
+                    Ok(i.into())
+                } else if let Some(u) = i.as_u64() {
+                    Ok(u.into())
+                } else {
+                    Err(self.unsupported(format!("integer {i} is out of range")))
+                }
+            }
+            MV::F32(f) => self.float(f.into()),
+            MV::F64(f) => self.float(f),
+            MV::String(s) => match s.into_str() {
+                Some(s) => Ok(Value::String(s)),
+                None => Err(self.unsupported("string is not valid utf-8")),
+            },
+            MV::Binary(b) => Ok(self.bytes(&b)),
+            MV::Array(arr) => self.array(arr, Self::msgpack),
+            MV::Map(entries) => {
+                let entries = entries
+                    .into_iter()
+                    .map(|(k, v)| match k {
+                        MV::String(s) => (s.into_str(), v),
+                        _ => (None, v),
+                    })
+                    .collect();
+                self.object(entries, Self::msgpack)
+            }
+            MV::Ext(typ, _) => {
+                Err(self.unsupported(format!("extension type {typ} is not supported")))
+            }
+        }
+    }
+}
+
+fn trailing_bytes(format: &'static str, offset: usize) -> InstanceError<'static> {
+    InstanceError::Decode {
+        format,
+        offset,
+        message: "trailing bytes after value".to_owned(),
+    }
+}
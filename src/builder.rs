@@ -0,0 +1,731 @@
+use std::{error::Error, fmt::Display};
+
+use serde_json::{Map, Value};
+
+use crate::{CompileError, Compiler, Draft, SchemaIndex, Schemas};
+
+/**
+Builds schema in code, with keywords checked by the compiler.
+
+Each constructor returns builder for a `type`, which has only the keywords
+applicable to that type, along with keywords common to all types:
+
+```rust
+# use boon::*;
+# use serde_json::json;
+let schema = SchemaBuilder::object()
+    .property("name", SchemaBuilder::string().min_length(1))
+    .property("age", SchemaBuilder::integer().minimum(0))
+    .required(["name"])
+    .additional_properties(false)
+    .build();
+assert_eq!(
+    schema,
+    json!({
+        "type": "object",
+        "properties": {
+            "name": { "type": "string", "minLength": 1 },
+            "age": { "type": "integer", "minimum": 0 }
+        },
+        "required": ["name"],
+        "additionalProperties": false
+    })
+);
+```
+
+so keyword that makes no sense for a type, is rejected:
+
+```compile_fail
+# use boon::*;
+SchemaBuilder::number().min_length(1);
+```
+
+Subschemas are given as anything that converts into [`SchemaBuilder`],
+including `bool` for `true`/`false` schemas. Lists mixing builders of
+different types need conversion:
+
+```rust
+# use boon::*;
+SchemaBuilder::any().one_of([
+    SchemaBuilder::from(SchemaBuilder::string()),
+    SchemaBuilder::integer().into(),
+]);
+```
+*/
+#[derive(Debug, Clone, Default)]
+pub struct SchemaBuilder(Node);
+
+/// Builder for schema with `type` object, see [`SchemaBuilder::object`].
+#[derive(Debug, Clone)]
+pub struct ObjectBuilder(Node);
+
+/// Builder for schema with `type` string, see [`SchemaBuilder::string`].
+#[derive(Debug, Clone)]
+pub struct StringBuilder(Node);
+
+/// Builder for schema with `type` number or integer, see [`SchemaBuilder::number`].
+#[derive(Debug, Clone)]
+pub struct NumberBuilder(Node);
+
+/// Builder for schema with `type` array, see [`SchemaBuilder::array`].
+#[derive(Debug, Clone)]
+pub struct ArrayBuilder(Node);
+
+/// Keyword used in builder has no equivalent in target draft.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsupportedKeyword {
+    pub keyword: &'static str,
+    pub draft: Draft,
+}
+
+impl Error for UnsupportedKeyword {}
+
+impl Display for UnsupportedKeyword {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "keyword {} is not supported in {}",
+            self.keyword,
+            self.draft.internal().url
+        )
+    }
+}
+
+impl SchemaBuilder {
+    /// Schema without any constraints, i.e. `{}`.
+    pub fn any() -> Self {
+        Self::default()
+    }
+
+    pub fn object() -> ObjectBuilder {
+        ObjectBuilder(Node::typed("object"))
+    }
+
+    pub fn string() -> StringBuilder {
+        StringBuilder(Node::typed("string"))
+    }
+
+    pub fn number() -> NumberBuilder {
+        NumberBuilder(Node::typed("number"))
+    }
+
+    pub fn integer() -> NumberBuilder {
+        NumberBuilder(Node::typed("integer"))
+    }
+
+    pub fn array() -> ArrayBuilder {
+        ArrayBuilder(Node::typed("array"))
+    }
+
+    /// Schema with `type` boolean. Use `bool` for `true`/`false` schemas.
+    pub fn boolean() -> Self {
+        Self(Node::typed("boolean"))
+    }
+
+    pub fn null() -> Self {
+        Self(Node::typed("null"))
+    }
+
+    /// Schema with only `$ref` to given `url`.
+    pub fn reference(url: impl Into<String>) -> Self {
+        Self::any().ref_(url)
+    }
+
+    /**
+    Uses `schema` as is, for keywords not covered by builders.
+
+    It is emitted unchanged, whatever the draft built for.
+    */
+    pub fn raw(schema: Value) -> Self {
+        Self(Node {
+            raw: Some(schema),
+            ..Node::default()
+        })
+    }
+}
+
+impl From<bool> for SchemaBuilder {
+    fn from(b: bool) -> Self {
+        Self(Node {
+            boolean: Some(b),
+            ..Node::default()
+        })
+    }
+}
+
+// keywords applicable to all types
+macro_rules! common_methods {
+    ($($t:ty),*) => {$(
+        impl $t {
+            pub fn title(mut self, title: impl Into<String>) -> Self {
+                self.0.set("title", Entry::Value(Value::String(title.into())));
+                self
+            }
+
+            pub fn description(mut self, description: impl Into<String>) -> Self {
+                self.0.set("description", Entry::Value(Value::String(description.into())));
+                self
+            }
+
+            /// Sets `$comment`.
+            pub fn comment(mut self, comment: impl Into<String>) -> Self {
+                self.0.set("$comment", Entry::Value(Value::String(comment.into())));
+                self
+            }
+
+            /// Sets `default`.
+            pub fn default_value(mut self, value: impl Into<Value>) -> Self {
+                self.0.set("default", Entry::Value(value.into()));
+                self
+            }
+
+            /// Appends to `examples`.
+            pub fn example(mut self, value: impl Into<Value>) -> Self {
+                self.0.append("examples", value.into());
+                self
+            }
+
+            /// Sets `enum`.
+            pub fn enum_<V: Into<Value>>(mut self, values: impl IntoIterator<Item = V>) -> Self {
+                let values = values.into_iter().map(Into::into).collect();
+                self.0.set("enum", Entry::Value(Value::Array(values)));
+                self
+            }
+
+            /// Sets `const`.
+            pub fn const_(mut self, value: impl Into<Value>) -> Self {
+                self.0.set("const", Entry::Value(value.into()));
+                self
+            }
+
+            /// Allows `null` in addition to the type of builder.
+            pub fn nullable(mut self) -> Self {
+                if !self.0.types.is_empty() && !self.0.types.contains(&"null") {
+                    self.0.types.push("null");
+                }
+                self
+            }
+
+            /// Sets `$ref`.
+            pub fn ref_(mut self, url: impl Into<String>) -> Self {
+                self.0.set("$ref", Entry::Value(Value::String(url.into())));
+                self
+            }
+
+            /// Adds schema to `$defs`, replacing any with same `name`.
+            pub fn def(mut self, name: impl Into<String>, schema: impl Into<SchemaBuilder>) -> Self {
+                self.0.insert("$defs", name.into(), schema.into().0);
+                self
+            }
+
+            /// Appends to `allOf`.
+            pub fn all_of<S: Into<SchemaBuilder>>(mut self, schemas: impl IntoIterator<Item = S>) -> Self {
+                self.0.extend("allOf", schemas);
+                self
+            }
+
+            /// Appends to `anyOf`.
+            pub fn any_of<S: Into<SchemaBuilder>>(mut self, schemas: impl IntoIterator<Item = S>) -> Self {
+                self.0.extend("anyOf", schemas);
+                self
+            }
+
+            /// Appends to `oneOf`.
+            pub fn one_of<S: Into<SchemaBuilder>>(mut self, schemas: impl IntoIterator<Item = S>) -> Self {
+                self.0.extend("oneOf", schemas);
+                self
+            }
+
+            pub fn not(mut self, schema: impl Into<SchemaBuilder>) -> Self {
+                self.0.set("not", Entry::Schema(schema.into().0));
+                self
+            }
+
+            /// Sets `if`.
+            pub fn if_(mut self, schema: impl Into<SchemaBuilder>) -> Self {
+                self.0.set("if", Entry::Schema(schema.into().0));
+                self
+            }
+
+            pub fn then(mut self, schema: impl Into<SchemaBuilder>) -> Self {
+                self.0.set("then", Entry::Schema(schema.into().0));
+                self
+            }
+
+            /// Sets `else`.
+            pub fn else_(mut self, schema: impl Into<SchemaBuilder>) -> Self {
+                self.0.set("else", Entry::Schema(schema.into().0));
+                self
+            }
+
+            /// Returns schema for draft 2020-12, without `$schema`.
+            pub fn build(&self) -> Value {
+                self.0
+                    .render(Draft::V2020_12)
+                    .expect("all keywords are supported in 2020-12")
+            }
+
+            /**
+            Returns schema for given `draft`, with `$schema` set.
+
+            Keywords renamed or reshaped across drafts are converted, for
+            example `$defs` to `definitions` and `prefixItems` to array
+            form of `items`. Before 2019-09, `$ref` to `#/$defs/..` is
+            rewritten to `#/definitions/..`, and siblings of `$ref` are kept
+            effective by moving `$ref` into `allOf`.
+
+            # Errors
+
+            returns [`UnsupportedKeyword`], if a keyword has no equivalent
+            in `draft`, for example `unevaluatedProperties` in draft-07.
+            */
+            pub fn build_for(&self, draft: Draft) -> Result<Value, UnsupportedKeyword> {
+                let mut schema = self.0.render(draft)?;
+                if let Value::Object(obj) = &mut schema {
+                    let url = Value::String(draft.internal().url.to_owned());
+                    obj.insert("$schema".to_owned(), url);
+                }
+                Ok(schema)
+            }
+
+            /**
+            Adds schema built for 2020-12 as resource at `loc`, and
+            compiles it into `schemas`.
+
+            `$schema` is set, so that default draft of `compiler` does not matter.
+            */
+            pub fn compile(
+                &self,
+                loc: &str,
+                compiler: &mut Compiler,
+                schemas: &mut Schemas,
+            ) -> Result<SchemaIndex, CompileError> {
+                let schema = self
+                    .build_for(Draft::V2020_12)
+                    .expect("all keywords are supported in 2020-12");
+                compiler.add_resource(loc, schema)?;
+                compiler.compile(loc, schemas)
+            }
+        }
+    )*};
+}
+
+common_methods!(
+    SchemaBuilder,
+    ObjectBuilder,
+    StringBuilder,
+    NumberBuilder,
+    ArrayBuilder
+);
+
+macro_rules! into_schema_builder {
+    ($($t:ty),*) => {$(
+        impl From<$t> for SchemaBuilder {
+            fn from(b: $t) -> Self {
+                Self(b.0)
+            }
+        }
+    )*};
+}
+
+into_schema_builder!(ObjectBuilder, StringBuilder, NumberBuilder, ArrayBuilder);
+
+impl ObjectBuilder {
+    /// Adds schema to `properties`, replacing any with same `name`.
+    pub fn property(mut self, name: impl Into<String>, schema: impl Into<SchemaBuilder>) -> Self {
+        self.0.insert("properties", name.into(), schema.into().0);
+        self
+    }
+
+    /// Adds schema to `patternProperties`, replacing any with same `pattern`.
+    pub fn pattern_property(
+        mut self,
+        pattern: impl Into<String>,
+        schema: impl Into<SchemaBuilder>,
+    ) -> Self {
+        self.0
+            .insert("patternProperties", pattern.into(), schema.into().0);
+        self
+    }
+
+    pub fn additional_properties(mut self, schema: impl Into<SchemaBuilder>) -> Self {
+        self.0
+            .set("additionalProperties", Entry::Schema(schema.into().0));
+        self
+    }
+
+    pub fn unevaluated_properties(mut self, schema: impl Into<SchemaBuilder>) -> Self {
+        self.0
+            .set("unevaluatedProperties", Entry::Schema(schema.into().0));
+        self
+    }
+
+    pub fn property_names(mut self, schema: StringBuilder) -> Self {
+        self.0.set("propertyNames", Entry::Schema(schema.0));
+        self
+    }
+
+    /// Appends to `required`, skipping names already present.
+    pub fn required<S: Into<String>>(mut self, names: impl IntoIterator<Item = S>) -> Self {
+        for name in names {
+            let name = Value::String(name.into());
+            if !self.0.contains("required", &name) {
+                self.0.append("required", name);
+            }
+        }
+        self
+    }
+
+    pub fn min_properties(mut self, n: usize) -> Self {
+        self.0.set("minProperties", Entry::Value(n.into()));
+        self
+    }
+
+    pub fn max_properties(mut self, n: usize) -> Self {
+        self.0.set("maxProperties", Entry::Value(n.into()));
+        self
+    }
+
+    /// Properties required, when property `name` is present.
+    pub fn dependent_required<S: Into<String>>(
+        mut self,
+        name: impl Into<String>,
+        names: impl IntoIterator<Item = S>,
+    ) -> Self {
+        let names = names.into_iter().map(|s| Value::String(s.into())).collect();
+        let node = SchemaBuilder::raw(Value::Array(names)).0;
+        self.0.insert("dependentRequired", name.into(), node);
+        self
+    }
+
+    /// Schema applied, when property `name` is present.
+    pub fn dependent_schema(
+        mut self,
+        name: impl Into<String>,
+        schema: impl Into<SchemaBuilder>,
+    ) -> Self {
+        self.0
+            .insert("dependentSchemas", name.into(), schema.into().0);
+        self
+    }
+}
+
+impl StringBuilder {
+    pub fn min_length(mut self, n: usize) -> Self {
+        self.0.set("minLength", Entry::Value(n.into()));
+        self
+    }
+
+    pub fn max_length(mut self, n: usize) -> Self {
+        self.0.set("maxLength", Entry::Value(n.into()));
+        self
+    }
+
+    /// Sets `pattern`, an ECMA-262 regex.
+    pub fn pattern(mut self, regex: impl Into<String>) -> Self {
+        self.0
+            .set("pattern", Entry::Value(Value::String(regex.into())));
+        self
+    }
+
+    pub fn format(mut self, format: impl Into<String>) -> Self {
+        self.0
+            .set("format", Entry::Value(Value::String(format.into())));
+        self
+    }
+
+    pub fn content_encoding(mut self, encoding: impl Into<String>) -> Self {
+        let encoding = Value::String(encoding.into());
+        self.0.set("contentEncoding", Entry::Value(encoding));
+        self
+    }
+
+    pub fn content_media_type(mut self, media_type: impl Into<String>) -> Self {
+        let media_type = Value::String(media_type.into());
+        self.0.set("contentMediaType", Entry::Value(media_type));
+        self
+    }
+
+    pub fn content_schema(mut self, schema: impl Into<SchemaBuilder>) -> Self {
+        self.0.set("contentSchema", Entry::Schema(schema.into().0));
+        self
+    }
+}
+
+impl NumberBuilder {
+    pub fn minimum(mut self, n: impl Into<Value>) -> Self {
+        self.0.set("minimum", Entry::Value(n.into()));
+        self
+    }
+
+    pub fn maximum(mut self, n: impl Into<Value>) -> Self {
+        self.0.set("maximum", Entry::Value(n.into()));
+        self
+    }
+
+    pub fn exclusive_minimum(mut self, n: impl Into<Value>) -> Self {
+        self.0.set("exclusiveMinimum", Entry::Value(n.into()));
+        self
+    }
+
+    pub fn exclusive_maximum(mut self, n: impl Into<Value>) -> Self {
+        self.0.set("exclusiveMaximum", Entry::Value(n.into()));
+        self
+    }
+
+    pub fn multiple_of(mut self, n: impl Into<Value>) -> Self {
+        self.0.set("multipleOf", Entry::Value(n.into()));
+        self
+    }
+}
+
+impl ArrayBuilder {
+    /// Schema for items, after those in `prefixItems`.
+    pub fn items(mut self, schema: impl Into<SchemaBuilder>) -> Self {
+        self.0.set("items", Entry::Schema(schema.into().0));
+        self
+    }
+
+    /// Appends to `prefixItems`.
+    pub fn prefix_items<S: Into<SchemaBuilder>>(
+        mut self,
+        schemas: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.0.extend("prefixItems", schemas);
+        self
+    }
+
+    pub fn unevaluated_items(mut self, schema: impl Into<SchemaBuilder>) -> Self {
+        self.0
+            .set("unevaluatedItems", Entry::Schema(schema.into().0));
+        self
+    }
+
+    pub fn contains(mut self, schema: impl Into<SchemaBuilder>) -> Self {
+        self.0.set("contains", Entry::Schema(schema.into().0));
+        self
+    }
+
+    pub fn min_contains(mut self, n: usize) -> Self {
+        self.0.set("minContains", Entry::Value(n.into()));
+        self
+    }
+
+    pub fn max_contains(mut self, n: usize) -> Self {
+        self.0.set("maxContains", Entry::Value(n.into()));
+        self
+    }
+
+    pub fn min_items(mut self, n: usize) -> Self {
+        self.0.set("minItems", Entry::Value(n.into()));
+        self
+    }
+
+    pub fn max_items(mut self, n: usize) -> Self {
+        self.0.set("maxItems", Entry::Value(n.into()));
+        self
+    }
+
+    pub fn unique_items(mut self, yes: bool) -> Self {
+        self.0.set("uniqueItems", Entry::Value(yes.into()));
+        self
+    }
+}
+
+// --
+
+#[derive(Debug, Clone, Default)]
+struct Node {
+    boolean: Option<bool>,
+    raw: Option<Value>,
+    types: Vec<&'static str>,
+    keywords: Vec<(&'static str, Entry)>, // named as in 2020-12, in insertion order
+}
+
+#[derive(Debug, Clone)]
+enum Entry {
+    Value(Value),
+    Schema(Node),
+    List(Vec<Node>),
+    Map(Vec<(String, Node)>),
+}
+
+impl Node {
+    fn typed(t: &'static str) -> Self {
+        Self {
+            types: vec![t],
+            ..Self::default()
+        }
+    }
+
+    fn get_mut(&mut self, kw: &'static str) -> Option<&mut Entry> {
+        self.keywords
+            .iter_mut()
+            .find_map(|(k, e)| (*k == kw).then_some(e))
+    }
+
+    fn set(&mut self, kw: &'static str, entry: Entry) {
+        match self.get_mut(kw) {
+            Some(e) => *e = entry,
+            None => self.keywords.push((kw, entry)),
+        }
+    }
+
+    fn contains(&self, kw: &'static str, value: &Value) -> bool {
+        self.keywords.iter().any(|(k, e)| match e {
+            Entry::Value(Value::Array(arr)) => *k == kw && arr.contains(value),
+            _ => false,
+        })
+    }
+
+    fn append(&mut self, kw: &'static str, value: Value) {
+        match self.get_mut(kw) {
+            Some(Entry::Value(Value::Array(arr))) => arr.push(value),
+            _ => self.set(kw, Entry::Value(Value::Array(vec![value]))),
+        }
+    }
+
+    fn extend<S: Into<SchemaBuilder>>(
+        &mut self,
+        kw: &'static str,
+        schemas: impl IntoIterator<Item = S>,
+    ) {
+        let schemas = schemas.into_iter().map(|s| s.into().0);
+        match self.get_mut(kw) {
+            Some(Entry::List(list)) => list.extend(schemas),
+            _ => self.set(kw, Entry::List(schemas.collect())),
+        }
+    }
+
+    fn insert(&mut self, kw: &'static str, name: String, node: Node) {
+        let Some(Entry::Map(map)) = self.get_mut(kw) else {
+            self.set(kw, Entry::Map(vec![(name, node)]));
+            return;
+        };
+        match map.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, e)) => *e = node,
+            None => map.push((name, node)),
+        }
+    }
+
+    fn has(&self, kw: &str) -> bool {
+        self.keywords.iter().any(|(k, _)| *k == kw)
+    }
+
+    fn render(&self, draft: Draft) -> Result<Value, UnsupportedKeyword> {
+        if let Some(b) = self.boolean {
+            return Ok(Value::Bool(b));
+        }
+        if let Some(v) = &self.raw {
+            return Ok(v.clone());
+        }
+        let version = draft.internal().version;
+        let unsupported = |keyword| Err(UnsupportedKeyword { keyword, draft });
+
+        let mut obj = Map::new();
+        match self.types.as_slice() {
+            [] => {}
+            [t] => _ = obj.insert("type".to_owned(), (*t).into()),
+            types => _ = obj.insert("type".to_owned(), types.into()),
+        }
+        let mut ref_ = None;
+        for (kw, entry) in &self.keywords {
+            if version < since(kw) {
+                return unsupported(kw);
+            }
+            let mut name = *kw;
+            let mut value = entry.render(draft)?;
+            match name {
+                "$ref" if version < 2019 => {
+                    if let Value::String(url) = &mut value {
+                        if let Some(name) = url.strip_prefix("#/$defs/") {
+                            *url = format!("#/definitions/{name}");
+                        }
+                    }
+                    if self.keywords.len() > 1 || !self.types.is_empty() {
+                        // siblings of $ref are ignored
+                        ref_ = Some(value);
+                        continue;
+                    }
+                }
+                "$defs" if version < 2019 => name = "definitions",
+                "dependentRequired" | "dependentSchemas" if version < 2019 => {
+                    let deps = obj
+                        .entry("dependencies")
+                        .or_insert_with(|| Value::Object(Map::new()));
+                    if let (Value::Object(deps), Value::Object(value)) = (deps, value) {
+                        deps.extend(value);
+                    }
+                    continue;
+                }
+                "prefixItems" if version < 2020 => name = "items",
+                "items" if version < 2020 && self.has("prefixItems") => name = "additionalItems",
+                "const" if version < 6 => {
+                    if self.has("enum") {
+                        return unsupported(kw);
+                    }
+                    name = "enum";
+                    value = Value::Array(vec![value]);
+                }
+                "exclusiveMinimum" | "exclusiveMaximum" if version < 6 => {
+                    // boolean form, modifying minimum/maximum
+                    let bound = if name == "exclusiveMinimum" {
+                        "minimum"
+                    } else {
+                        "maximum"
+                    };
+                    if self.has(bound) {
+                        return unsupported(kw);
+                    }
+                    obj.insert(bound.to_owned(), value);
+                    value = Value::Bool(true);
+                }
+                _ => {}
+            }
+            obj.insert(name.to_owned(), value);
+        }
+        if let Some(ref_) = ref_ {
+            let mut r = Map::new();
+            r.insert("$ref".to_owned(), ref_);
+            let all_of = obj.entry("allOf").or_insert_with(|| Value::Array(vec![]));
+            if let Value::Array(all_of) = all_of {
+                all_of.insert(0, Value::Object(r));
+            }
+        }
+        Ok(Value::Object(obj))
+    }
+}
+
+impl Entry {
+    fn render(&self, draft: Draft) -> Result<Value, UnsupportedKeyword> {
+        Ok(match self {
+            Entry::Value(v) => v.clone(),
+            Entry::Schema(node) => node.render(draft)?,
+            Entry::List(list) => Value::Array(
+                list.iter()
+                    .map(|node| node.render(draft))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Entry::Map(map) => Value::Object(
+                map.iter()
+                    .map(|(name, node)| Ok((name.clone(), node.render(draft)?)))
+                    .collect::<Result<_, _>>()?,
+            ),
+        })
+    }
+}
+
+// draft version, in which `kw` is introduced. keywords that can be
+// converted to earlier drafts are not listed.
+fn since(kw: &str) -> usize {
+    match kw {
+        "contains" | "propertyNames" | "examples" => 6,
+        "if" | "then" | "else" | "$comment" | "contentEncoding" | "contentMediaType" => 7,
+        "minContains"
+        | "maxContains"
+        | "unevaluatedProperties"
+        | "unevaluatedItems"
+        | "contentSchema" => 2019,
+        _ => 4,
+    }
+}
@@ -0,0 +1,240 @@
+use std::fmt::Write;
+
+use serde_json::{Number, Value};
+
+/**
+Serializes `v` into canonical json, such that values which are equal
+as per json-schema serialize identically. This is useful to
+deduplicate or hash instances.
+
+- object members are sorted by name, comparing unicode code points
+- no whitespace is written
+- strings are written as by `serde_json`: only `"`, `\` and control
+  characters are escaped, and they are not normalized otherwise
+- numbers are converted to nearest `f64`, as json-schema equality
+  compares them, and written as by javascript's `Number.prototype.toString`.
+  `-0` is written as `0`, integral values as integers, like `1.0` as `1`,
+  and exponent is used only if it is less than `-6` or more than `20`,
+  like `1e+21` and `1e-7`
+- numbers not representable as finite `f64`, which is possible with
+  `serde_json/arbitrary_precision`, are written as they are
+
+So if two values are equal as per json-schema, their canonical json is
+same. The reverse does not hold for distinct integers beyond `2^53`,
+which are written same if they convert to same `f64`.
+
+```rust
+# use boon::*;
+# use serde_json::json;
+let a = json!({"b": [1.0, -0.0], "a": "x"});
+let b = json!({"a": "x", "b": [1, 0]});
+assert_eq!(canonical_json(&a), r#"{"a":"x","b":[1,0]}"#);
+assert_eq!(canonical_json(&a), canonical_json(&b));
+```
+*/
+pub fn canonical_json(v: &Value) -> String {
+    let mut s = String::new();
+    write_value(&mut s, v);
+    s
+}
+
+/**
+Hash of [`canonical_json`] of `v`, using 64-bit FNV-1a.
+
+It is same across runs and platforms, so it can be persisted. It is not
+cryptographic, so do not rely on it against adversarial collisions.
+*/
+pub fn canonical_hash(v: &Value) -> u64 {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    canonical_json(v)
+        .bytes()
+        .fold(OFFSET, |hash, b| (hash ^ u64::from(b)).wrapping_mul(PRIME))
+}
+
+fn write_value(s: &mut String, v: &Value) {
+    match v {
+        Value::Null => s.push_str("null"),
+        Value::Bool(b) => _ = write!(s, "{b}"),
+        Value::Number(n) => write_number(s, n),
+        Value::String(str) => write_string(s, str),
+        Value::Array(arr) => {
+            s.push('[');
+            for (i, item) in arr.iter().enumerate() {
+                if i > 0 {
+                    s.push(',');
+                }
+                write_value(s, item);
+            }
+            s.push(']');
+        }
+        Value::Object(obj) => {
+            let mut members = obj.iter().collect::<Vec<_>>();
+            members.sort_unstable_by_key(|(name, _)| name.as_str());
+            s.push('{');
+            for (i, (name, value)) in members.into_iter().enumerate() {
+                if i > 0 {
+                    s.push(',');
+                }
+                write_string(s, name);
+                s.push(':');
+                write_value(s, value);
+            }
+            s.push('}');
+        }
+    }
+}
+
+fn write_string(s: &mut String, str: &str) {
+    // serializing string never fails
+    if let Ok(json) = serde_json::to_string(str) {
+        s.push_str(&json);
+    }
+}
+
+fn write_number(s: &mut String, n: &Number) {
+    let Some(f) = n.as_f64() else {
+        _ = write!(s, "{n}");
+        return;
+    };
+    if f == 0.0 {
+        s.push('0');
+        return;
+    }
+    if f < 0.0 {
+        s.push('-');
+    }
+    // shortest digits that round trip, as `d.ddde<exp>`
+    let sci = format!("{:e}", f.abs());
+    let (mantissa, exp) = sci.split_once('e').unwrap_or((&sci, "0"));
+    let digits = mantissa.replace('.', "");
+    let k = digits.len() as i32;
+    let n = exp.parse::<i32>().unwrap_or(0) + 1; // position of decimal point
+    if k <= n && n <= 21 {
+        s.push_str(&digits);
+        s.extend(std::iter::repeat_n('0', (n - k) as usize));
+    } else if 0 < n && n <= 21 {
+        let (int, frac) = digits.split_at(n as usize);
+        _ = write!(s, "{int}.{frac}");
+    } else if -6 < n && n <= 0 {
+        s.push_str("0.");
+        s.extend(std::iter::repeat_n('0', -n as usize));
+        s.push_str(&digits);
+    } else {
+        let (first, rest) = digits.split_at(1);
+        s.push_str(first);
+        if !rest.is_empty() {
+            _ = write!(s, ".{rest}");
+        }
+        let sign = if n > 0 { '+' } else { '-' };
+        _ = write!(s, "e{sign}{}", (n - 1).abs());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::util::equals;
+
+    #[test]
+    fn test_numbers() {
+        let tests = [
+            (json!(0), "0"),
+            (json!(-0.0), "0"),
+            (json!(1.0), "1"),
+            (json!(-12), "-12"),
+            (json!(1.5), "1.5"),
+            (json!(0.000001), "0.000001"),
+            (json!(0.0000001), "1e-7"),
+            (json!(1.25e-10), "1.25e-10"),
+            (json!(123456789012.5), "123456789012.5"),
+            (json!(1e20), "100000000000000000000"),
+            (json!(1e21), "1e+21"),
+            (json!(-1.5e300), "-1.5e+300"),
+            (json!(u64::MAX), "18446744073709552000"),
+            (json!(i64::MIN), "-9223372036854776000"),
+        ];
+        for (v, want) in tests {
+            assert_eq!(canonical_json(&v), want, "{v}");
+        }
+    }
+
+    // xorshift, so that failures are reproducible
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self, n: u64) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0 % n
+        }
+
+        fn value(&mut self, depth: usize) -> Value {
+            let kinds = if depth == 0 { 4 } else { 6 };
+            match self.next(kinds) {
+                0 => Value::Null,
+                1 => Value::Bool(self.next(2) == 0),
+                2 => match self.next(4) {
+                    0 => json!(self.next(5) as f64 - 2.0),
+                    1 => json!(self.next(5) as i64 - 2),
+                    2 => json!((self.next(2000) as f64 - 1000.0) / 8.0),
+                    _ => json!(self.next(3) as f64 * 1e-7),
+                },
+                3 => json!(["a", "b", "é", "\n", "\"", ""][self.next(6) as usize]),
+                4 => (0..self.next(4)).map(|_| self.value(depth - 1)).collect(),
+                _ => (0..self.next(4))
+                    .map(|_| (format!("k{}", self.next(4)), self.value(depth - 1)))
+                    .collect(),
+            }
+        }
+
+        // equal value, written differently
+        fn variant(&mut self, v: &Value) -> Value {
+            match v {
+                Value::Number(n) => match n.as_f64() {
+                    Some(0.0) => [json!(0), json!(0.0), json!(-0.0)][self.next(3) as usize].clone(),
+                    Some(f) if f.fract() == 0.0 && self.next(2) == 0 => match n.is_f64() {
+                        true => json!(f as i64),
+                        false => json!(f),
+                    },
+                    _ => v.clone(),
+                },
+                Value::Array(arr) => arr.iter().map(|item| self.variant(item)).collect(),
+                Value::Object(obj) => {
+                    let mut members = obj.iter().collect::<Vec<_>>();
+                    if self.next(2) == 0 {
+                        members.reverse();
+                    }
+                    members
+                        .into_iter()
+                        .map(|(k, v)| (k.clone(), self.variant(v)))
+                        .collect()
+                }
+                _ => v.clone(),
+            }
+        }
+    }
+
+    #[test]
+    fn test_equal_values_are_canonically_same() {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        for _ in 0..2000 {
+            let (a, b) = (rng.value(3), rng.value(3));
+            let c = rng.variant(&a);
+            assert!(equals(&a, &c), "{a} {c}");
+            assert_eq!(canonical_json(&a), canonical_json(&c), "{a} {c}");
+            assert_eq!(canonical_hash(&a), canonical_hash(&c), "{a} {c}");
+            if equals(&a, &b) {
+                assert_eq!(canonical_json(&a), canonical_json(&b), "{a} {b}");
+            } else {
+                assert_ne!(canonical_json(&a), canonical_json(&b), "{a} {b}");
+            }
+            // canonical json is json, and equal to value
+            let parsed = serde_json::from_str::<Value>(&canonical_json(&a)).unwrap();
+            assert!(equals(&a, &parsed), "{a}");
+        }
+    }
+}
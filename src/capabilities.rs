@@ -0,0 +1,191 @@
+use serde::{ser::SerializeMap, Serialize};
+
+use crate::{
+    compiler::EXTENSIONS,
+    content::{DECODERS, MEDIA_TYPES},
+    formats::FORMATS,
+    Draft,
+};
+
+/**
+Optional behaviors supported by this build of the library, like for
+advertising validation capabilities of a service.
+
+Serializes to json object, with drafts as their metaschema urls.
+see [`capabilities`] and [`Compiler::capabilities`](crate::Compiler::capabilities)
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Capabilities {
+    /// drafts supported, oldest first.
+    pub drafts: Vec<Draft>,
+    /// names of formats validated by `format` keyword, sorted.
+    pub formats: Vec<String>,
+    /// names of encodings decoded by `contentEncoding` keyword, sorted.
+    pub content_encodings: Vec<String>,
+    /// media types checked by `contentMediaType` keyword, sorted.
+    pub content_media_types: Vec<String>,
+    /// uris of vocabularies supported in `$vocabulary`, sorted.
+    pub vocabularies: Vec<String>,
+    /// keywords of custom vocabularies, sorted.
+    pub keywords: Vec<String>,
+    /// extensions supported, sorted.
+    /// see [`Compiler::enable_extension`](crate::Compiler::enable_extension)
+    pub extensions: Vec<String>,
+    /// extensions enabled, sorted. Always empty for [`capabilities`],
+    /// which describes no compiler.
+    pub enabled_extensions: Vec<String>,
+    /// whether regexes support unicode classes like `\p{Lu}`.
+    pub regex_unicode: bool,
+    /// content encodings of loaded documents, sorted.
+    /// see [`Resource::into_json`](crate::Resource::into_json)
+    pub resource_encodings: Vec<String>,
+    /// media types of loaded documents, other than json, sorted.
+    /// see [`Resource::into_json`](crate::Resource::into_json)
+    pub resource_media_types: Vec<String>,
+    /// binary instance formats validated, like `cbor`, sorted.
+    pub instance_formats: Vec<String>,
+    /// cargo features enabled, sorted.
+    pub features: Vec<String>,
+}
+
+/**
+Returns optional behaviors supported by this build of the library,
+as per cargo features enabled.
+
+Lists built-in formats, content encodings and media types, standard
+vocabularies and all supported extensions.
+
+```rust
+# use boon::*;
+let caps = capabilities();
+assert!(caps.drafts.contains(&Draft::V2020_12));
+assert!(caps.formats.iter().any(|f| f == "ipv4"));
+assert_eq!(caps.regex_unicode, cfg!(feature = "regex-unicode"));
+```
+*/
+pub fn capabilities() -> Capabilities {
+    // names of enabled features
+    macro_rules! features {
+        ($($feature:literal),* $(,)?) => {
+            enabled([$(($feature, cfg!(feature = $feature))),*])
+        };
+    }
+    let vocabularies = [Draft::V2019_09, Draft::V2020_12]
+        .iter()
+        .map(Draft::internal)
+        .flat_map(|d| {
+            d.all_vocabs
+                .iter()
+                .map(|v| format!("{}{v}", d.vocab_prefix))
+        })
+        .collect();
+    let mut caps = Capabilities {
+        drafts: vec![
+            Draft::V4,
+            Draft::V6,
+            Draft::V7,
+            Draft::V2019_09,
+            Draft::V2020_12,
+        ],
+        formats: strings(FORMATS.keys().copied()),
+        content_encodings: strings(DECODERS.keys().copied()),
+        content_media_types: strings(MEDIA_TYPES.keys().copied()),
+        vocabularies,
+        keywords: vec![],
+        extensions: strings(EXTENSIONS.iter().copied()),
+        enabled_extensions: vec![],
+        regex_unicode: cfg!(feature = "regex-unicode"),
+        resource_encodings: enabled([
+            ("gzip", true),
+            ("deflate", true),
+            ("identity", true),
+            ("br", cfg!(feature = "brotli")),
+        ]),
+        resource_media_types: enabled([
+            ("application/yaml", cfg!(feature = "yaml")),
+            ("application/json5", cfg!(feature = "json5")),
+        ]),
+        instance_formats: features!("cbor", "msgpack"),
+        features: features!(
+            "url",
+            "formats-net",
+            "formats-time",
+            "regex-unicode",
+            "json5",
+            "cbor",
+            "msgpack",
+            "brotli",
+            "yaml",
+            "arbitrary_precision",
+            "fancy-errors",
+            "codegen",
+            "parallel",
+            "difftest",
+            "ahash",
+            "serde",
+        ),
+    };
+    caps.normalize();
+    caps
+}
+
+fn strings<'a>(it: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    it.into_iter().map(str::to_owned).collect()
+}
+
+fn enabled<'a>(it: impl IntoIterator<Item = (&'a str, bool)>) -> Vec<String> {
+    strings(
+        it.into_iter()
+            .filter_map(|(s, enabled)| enabled.then_some(s)),
+    )
+}
+
+impl Capabilities {
+    // sorts and dedups lists
+    pub(crate) fn normalize(&mut self) {
+        for list in [
+            &mut self.formats,
+            &mut self.content_encodings,
+            &mut self.content_media_types,
+            &mut self.vocabularies,
+            &mut self.keywords,
+            &mut self.extensions,
+            &mut self.enabled_extensions,
+            &mut self.resource_encodings,
+            &mut self.resource_media_types,
+            &mut self.instance_formats,
+            &mut self.features,
+        ] {
+            list.sort();
+            list.dedup();
+        }
+    }
+}
+
+impl Serialize for Capabilities {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let drafts = self
+            .drafts
+            .iter()
+            .map(|d| d.internal().url)
+            .collect::<Vec<_>>();
+        let mut map = serializer.serialize_map(Some(13))?;
+        map.serialize_entry("drafts", &drafts)?;
+        map.serialize_entry("formats", &self.formats)?;
+        map.serialize_entry("content_encodings", &self.content_encodings)?;
+        map.serialize_entry("content_media_types", &self.content_media_types)?;
+        map.serialize_entry("vocabularies", &self.vocabularies)?;
+        map.serialize_entry("keywords", &self.keywords)?;
+        map.serialize_entry("extensions", &self.extensions)?;
+        map.serialize_entry("enabled_extensions", &self.enabled_extensions)?;
+        map.serialize_entry("regex_unicode", &self.regex_unicode)?;
+        map.serialize_entry("resource_encodings", &self.resource_encodings)?;
+        map.serialize_entry("resource_media_types", &self.resource_media_types)?;
+        map.serialize_entry("instance_formats", &self.instance_formats)?;
+        map.serialize_entry("features", &self.features)?;
+        map.end()
+    }
+}
@@ -0,0 +1,215 @@
+/*!
+Embedding schemas into binaries at build time.
+
+[`embed`] (with `codegen` feature) is meant to be called from build script.
+It compiles given schemas, failing the build if any of them is invalid,
+and generates rust source containing every document they refer to,
+along with function `schemas()`:
+
+```rust,ignore
+// build.rs
+fn main() {
+    let out = std::path::Path::new(&std::env::var("OUT_DIR").unwrap()).join("schemas.rs");
+    boon::codegen::embed(&["schemas/person.json"], out).unwrap();
+}
+
+// main.rs
+include!(concat!(env!("OUT_DIR"), "/schemas.rs"));
+
+fn main() {
+    let (schemas, index) = schemas();
+    let sch = index["schemas/person.json"];
+    // ...
+}
+```
+
+On first call, `schemas()` compiles the embedded documents, without
+loading anything, and later calls return the same [`Schemas`].
+*/
+
+use crate::{Compiler, SchemaIndex, Schemas};
+
+/**
+Compiles embedded `docs`, given as `(url, json)` pairs, and returns
+index of each of `roots`. This is used by code generated by [`embed`].
+
+# Panics
+
+Panics if any of `docs` is not valid json, or any of `roots` fails
+to compile. This does not happen with generated code, as they are
+compiled by [`embed`] at build time.
+*/
+pub fn load(docs: &[(&str, &str)], roots: &[&str]) -> (Schemas, Vec<SchemaIndex>) {
+    let mut compiler = Compiler::new();
+    for (url, json) in docs {
+        let json = serde_json::from_str(json)
+            .unwrap_or_else(|e| panic!("embedded document {url} is not valid json: {e}"));
+        if let Err(e) = compiler.add_resource(url, json) {
+            panic!("embedded document {url} cannot be added: {e:#}");
+        }
+    }
+    let mut schemas = Schemas::new();
+    let indexes = roots
+        .iter()
+        .map(|loc| match compiler.compile(loc, &mut schemas) {
+            Ok(sch) => sch,
+            Err(e) => panic!("embedded schema {loc} failed to compile: {e:#}"),
+        })
+        .collect();
+    (schemas, indexes)
+}
+
+#[cfg(feature = "codegen")]
+pub use build::*;
+
+#[cfg(feature = "codegen")]
+mod build {
+    use std::{collections::HashMap, error::Error, fmt::Write, path::Path};
+
+    use crate::{util::UrlFrag, Compiler, SchemaIndex, Schemas};
+
+    /**
+    Compiles schemas at `schema_paths` and writes rust source embedding
+    them into `out_file`. see [module documentation](crate::codegen)
+
+    Schemas are indexed by their path, as given. Paths can have
+    json-pointer fragment, as in [`Compiler::compile`].
+
+    Unknown `format`, `contentEncoding` and `contentMediaType` fail the
+    build, as generated code resolves them against built-in ones only.
+
+    # Errors
+
+    returns error if any schema fails to compile, or `out_file` could
+    not be written.
+    */
+    pub fn embed<P: AsRef<Path>>(
+        schema_paths: &[P],
+        out_file: impl AsRef<Path>,
+    ) -> Result<(), Box<dyn Error>> {
+        let embedded = Embedded::compile(schema_paths)?;
+        if std::env::var_os("OUT_DIR").is_some() {
+            // running in build script
+            for (url, _) in &embedded.docs {
+                if let Some(path) = crate::url::Url::parse(url)
+                    .ok()
+                    .and_then(|u| u.to_file_path().ok())
+                {
+                    println!("cargo:rerun-if-changed={}", path.display());
+                }
+            }
+        }
+        std::fs::write(out_file, embedded.to_rust())?;
+        Ok(())
+    }
+
+    /// Schemas compiled by [`Embedded::compile`], along with documents they refer to.
+    #[derive(Debug, Clone)]
+    pub struct Embedded {
+        /// `(url, json)` of each document, sorted by url.
+        pub docs: Vec<(String, String)>,
+        /// `(path, url)` of each schema, in the order given.
+        pub roots: Vec<(String, String)>,
+    }
+
+    impl Embedded {
+        /**
+        Compiles schemas at `schema_paths`, and collects documents
+        they refer to.
+
+        # Errors
+
+        returns error if any schema fails to compile, or uses unknown
+        `format`, `contentEncoding` or `contentMediaType`.
+        */
+        pub fn compile<P: AsRef<Path>>(schema_paths: &[P]) -> Result<Self, Box<dyn Error>> {
+            let mut compiler = Compiler::new();
+            compiler.enable_format_assertions();
+            compiler.enable_content_assertions();
+            compiler.deny(&[
+                "unknown-format",
+                "unknown-content-encoding",
+                "unknown-content-media-type",
+            ]);
+            let mut schemas = Schemas::new();
+            let mut roots = Vec::with_capacity(schema_paths.len());
+            for path in schema_paths {
+                let path = path.as_ref().to_string_lossy();
+                let url = UrlFrag::absolute(&path)
+                    .map_err(|e| format!("schema {path}: {e:#}"))?
+                    .to_string();
+                compiler
+                    .compile(&url, &mut schemas)
+                    .map_err(|e| format!("schema {path}: {e:#}"))?;
+                roots.push((path.into_owned(), url));
+            }
+            let docs = compiler
+                .documents()
+                .into_iter()
+                .map(|(url, doc)| (url.to_string(), doc.to_string()))
+                .collect();
+            Ok(Self { docs, roots })
+        }
+
+        /// Compiles embedded documents, same as generated `schemas()`.
+        pub fn load(&self) -> (Schemas, HashMap<String, SchemaIndex>) {
+            let docs = self
+                .docs
+                .iter()
+                .map(|(url, json)| (url.as_str(), json.as_str()))
+                .collect::<Vec<_>>();
+            let roots = self
+                .roots
+                .iter()
+                .map(|(_, url)| url.as_str())
+                .collect::<Vec<_>>();
+            let (schemas, indexes) = super::load(&docs, &roots);
+            let names = self
+                .roots
+                .iter()
+                .map(|(path, _)| path.clone())
+                .zip(indexes)
+                .collect();
+            (schemas, names)
+        }
+
+        /// Generates rust source, defining `schemas()`.
+        pub fn to_rust(&self) -> String {
+            let mut out = String::new();
+            out.push_str("// @generated by boon::codegen::embed, do not edit.\n\n");
+            out.push_str("static DOCS: &[(&str, &str)] = &[\n");
+            for (url, json) in &self.docs {
+                _ = writeln!(out, "    ({url:?}, {json:?}),");
+            }
+            out.push_str("];\n\n");
+            out.push_str("static ROOTS: &[(&str, &str)] = &[\n");
+            for (path, url) in &self.roots {
+                _ = writeln!(out, "    ({path:?}, {url:?}),");
+            }
+            out.push_str("];\n\n");
+            out.push_str(SCHEMAS_FN);
+            out
+        }
+    }
+
+    const SCHEMAS_FN: &str = r#"/// Schemas embedded at build time, along with index of each by its path.
+#[allow(dead_code)]
+pub fn schemas() -> (
+    &'static ::boon::Schemas,
+    ::std::collections::HashMap<&'static str, ::boon::SchemaIndex>,
+) {
+    static SCHEMAS: ::std::sync::OnceLock<(::boon::Schemas, ::std::vec::Vec<::boon::SchemaIndex>)> =
+        ::std::sync::OnceLock::new();
+    let (schemas, indexes) = SCHEMAS.get_or_init(|| {
+        let roots = ROOTS.iter().map(|(_, url)| *url).collect::<::std::vec::Vec<_>>();
+        ::boon::codegen::load(DOCS, &roots)
+    });
+    let names = ROOTS
+        .iter()
+        .map(|(path, _)| *path)
+        .zip(indexes.iter().copied())
+        .collect();
+    (schemas, names)
+}
+"#;
+}
@@ -1,10 +1,18 @@
-use std::{cmp::Ordering, collections::HashMap, error::Error, fmt::Display};
-
-use regex::Regex;
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    error::Error,
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+use regex::{Regex, RegexBuilder};
 use serde_json::{Map, Value};
-use url::Url;
 
-use crate::{content::*, draft::*, ecma, formats::*, root::*, roots::*, util::*, *};
+use crate::{
+    content::*, diagnostics::*, draft::*, ecma, formats::*, root::*, roots::*, timerange::*,
+    url::Url, util::*, *,
+};
 
 /// Supported draft versions
 #[non_exhaustive]
@@ -39,16 +47,17 @@ impl Draft {
     ```
     */
     pub fn from_url(url: &str) -> Option<Draft> {
-        match crate::draft::Draft::from_url(url) {
-            Some(draft) => match draft.version {
-                4 => Some(Draft::V4),
-                6 => Some(Draft::V6),
-                7 => Some(Draft::V7),
-                2019 => Some(Draft::V2019_09),
-                2020 => Some(Draft::V2020_12),
-                _ => None,
-            },
-            None => None,
+        crate::draft::Draft::from_url(url).and_then(|draft| Draft::from_version(draft.version))
+    }
+
+    pub(crate) fn from_version(version: usize) -> Option<Draft> {
+        match version {
+            4 => Some(Draft::V4),
+            6 => Some(Draft::V6),
+            7 => Some(Draft::V7),
+            2019 => Some(Draft::V2019_09),
+            2020 => Some(Draft::V2020_12),
+            _ => None,
         }
     }
 
@@ -70,15 +79,47 @@ impl Default for Draft {
     }
 }
 
+// keywords that cannot be disabled, as they affect resolution
+pub(crate) const STRUCTURAL_KEYWORDS: &[&str] = &[
+    "$schema",
+    "$vocabulary",
+    "id",
+    "$id",
+    "$anchor",
+    "$dynamicAnchor",
+    "$recursiveAnchor",
+    "$ref",
+    "$dynamicRef",
+    "$recursiveRef",
+    "$defs",
+    "definitions",
+];
+
+// see `Compiler::enable_extension`
+pub(crate) const EXTENSIONS: &[&str] = &["x-time-range"];
+
 /// JsonSchema compiler.
 #[derive(Default)]
 pub struct Compiler {
     roots: Roots,
     assert_format: bool,
     assert_content: bool,
+    retain_source: bool,
+    anchor_patterns: bool,
+    regex_limits: RegexLimits,
     formats: HashMap<&'static str, Format>,
+    context_formats: HashMap<&'static str, ContextFormat>,
+    format_policies: HashMap<&'static str, FormatPolicy>,
+    vocabularies: Vec<(String, Vec<Keyword>)>, // uri => keywords
     decoders: HashMap<&'static str, Decoder>,
     media_types: HashMap<&'static str, MediaType>,
+    deny: Vec<String>,
+    allow: Vec<String>,
+    disabled_keywords: HashSet<String>,
+    extensions: HashSet<&'static str>,
+    diagnostics: Vec<Diagnostic>,
+    report: CompileReport,
+    consulted: HashSet<Url>, // documents consulted by all compilations
 }
 
 impl Compiler {
@@ -129,14 +170,212 @@ impl Compiler {
         self.assert_content = true;
     }
 
+    /**
+    Compile non-object, non-boolean values as always-valid schema,
+    instead of failing with [`CompileError::NotASchema`]. Values at
+    locations which do not hold schemas are compiled too, instead of
+    failing with [`CompileError::NotASchemaLocation`].
+
+    This is useful for legacy documents which `$ref` values that are
+    not schemas, but it may hide broken json-pointers in references.
+    Such values are not validated against metaschema.
+    */
+    pub fn allow_non_schemas(&mut self) {
+        self.roots.allow_non_schemas = true;
+    }
+
+    /**
+    Retain source json of each compiled schema, for
+    [`Schemas::source`](crate::Schemas::source).
+
+    Source is shared with the document it is part of, so this keeps
+    documents alive as long as the schemas compiled from them.
+    */
+    pub fn enable_source_retention(&mut self) {
+        self.retain_source = true;
+    }
+
+    /**
+    Makes `pattern` and `patternProperties` match whole string, by
+    compiling each regex as `^(?:regex)$`.
+
+    By default patterns match anywhere in the string, as per spec.
+    The compiled regex is reported in [`ErrorKind::Pattern`](crate::ErrorKind::Pattern)
+    and by [`Schemas::pattern`](crate::Schemas::pattern).
+    */
+    pub fn enable_anchored_patterns(&mut self) {
+        self.anchor_patterns = true;
+    }
+
+    /**
+    Sets limits applied when compiling regexes in `pattern` and
+    `patternProperties`.
+
+    Regex exceeding the limits is reported as [`CompileError::InvalidRegex`].
+    This is useful when compiling untrusted schemas.
+    */
+    pub fn set_regex_limits(&mut self, limits: RegexLimits) {
+        self.regex_limits = limits;
+    }
+
+    /**
+    Strips annotation keywords `$comment`, `title` and `description`
+    from documents, as they are added or loaded, so that they are not
+    held in memory.
+
+    Same as [`Compiler::set_stripped_keywords`] with these keywords.
+    */
+    pub fn enable_annotation_stripping(&mut self) {
+        self.set_stripped_keywords(&["$comment", "title", "description"]);
+    }
+
+    /**
+    Strips given `keywords` from documents, as they are added using
+    [`Compiler::add_resource`] or loaded by [`UrlLoader`]. Keyword
+    ending with `*` matches by prefix, for example `x-*` matches vendor
+    extensions like `x-internal`. Replaces keywords given earlier.
+
+    Only keywords of schema objects are stripped; for example property
+    named `title` in `properties`, or in value of `enum`, is retained.
+
+    Keywords affecting validation or resolution, like `type`, `$ref`
+    and `properties`, cannot be stripped; they are silently ignored.
+    `default` and `examples` are retained too, as they are used by
+    [`Schemas::example_for`](crate::Schemas::example_for).
+
+    # Note
+
+    - keywords of vocabularies registered with [`Compiler::register_vocabulary`]
+      must not be listed, as they are stripped like any other keyword
+    - documents added before calling this method are not affected
+    */
+    pub fn set_stripped_keywords(&mut self, keywords: &[&str]) {
+        self.roots.loader.stripped_keywords = keywords.iter().map(|k| k.to_string()).collect();
+    }
+
+    /**
+    Limits size of each document, as it is added or loaded, to `bytes`.
+    Larger document fails with [`CompileError::DocumentTooLarge`].
+
+    Size of document given as bytes or text is their length, whereas
+    size of document given as parsed json is length of its compact
+    serialization. This is useful when compiling untrusted schemas.
+    */
+    pub fn set_max_document_size(&mut self, bytes: usize) {
+        self.roots.loader.max_doc_size = Some(bytes);
+    }
+
+    /**
+    Limits number of custom metaschemas resolved through `$schema`, from
+    a schema document up to a standard metaschema, to `depth`. Longer
+    chain fails with [`CompileError::MetaSchemaChainTooLong`].
+
+    Standard metaschemas do not count. This is useful when compiling
+    untrusted schemas.
+    */
+    pub fn set_max_metaschema_depth(&mut self, depth: usize) {
+        self.roots.loader.max_meta_depth = Some(depth);
+    }
+
+    /**
+    Limits number of distinct custom metaschemas loaded through `$schema`,
+    by one call to [`Compiler::compile`], to `count`. Loading more fails
+    with [`CompileError::TooManyMetaSchemas`].
+
+    Standard metaschemas do not count. Metaschemas are subject to same
+    loader and [`Compiler::set_max_document_size`] as other documents.
+    This is useful when compiling untrusted schemas.
+    */
+    pub fn set_max_metaschema_documents(&mut self, count: usize) {
+        self.roots.loader.max_meta_docs = Some(count);
+    }
+
+    /**
+    Remembers failure to load a url for `ttl`, so that the url is not
+    loaded again meanwhile, by this or later compilations. Instead they
+    fail with [`CompileError::LoadUrlError`], whose source is
+    [`CachedLoadError`](crate::CachedLoadError).
+
+    By default, failed loads are not remembered. With retries enabled
+    using [`Compiler::set_load_retries`], failure is remembered only
+    after retries are exhausted.
+
+    # Note
+
+    on `wasm32-unknown-unknown`, which has no clock, clock must be given
+    using [`Compiler::set_negative_cache_clock`].
+    */
+    pub fn set_negative_cache_ttl(&mut self, ttl: Duration) {
+        self.roots.loader.negative_cache_ttl = Some(ttl);
+    }
+
+    /**
+    Overrides clock used to expire failures remembered as per
+    [`Compiler::set_negative_cache_ttl`]. Defaults to [`Instant::now`].
+
+    ```rust
+    # use std::{cell::Cell, rc::Rc, time::{Duration, Instant}};
+    # use boon::*;
+    let start = Instant::now();
+    let elapsed = Rc::new(Cell::new(Duration::ZERO));
+    let mut compiler = Compiler::new();
+    compiler.set_negative_cache_ttl(Duration::from_secs(60));
+    compiler.set_negative_cache_clock({
+        let elapsed = elapsed.clone();
+        Box::new(move || start + elapsed.get())
+    });
+    elapsed.set(Duration::from_secs(61)); // earlier failures are forgotten
+    ```
+    */
+    pub fn set_negative_cache_clock(&mut self, clock: Box<dyn Fn() -> Instant>) {
+        self.roots.loader.clock = Some(clock);
+    }
+
+    /**
+    Retries loading a url up to `retries` times, if it fails with
+    timeout, like [`LoadError::Timeout`](crate::LoadError::Timeout) or
+    [`std::io::ErrorKind::TimedOut`]. Waits `backoff` before first retry,
+    doubling the wait before each subsequent retry.
+
+    By default, failed loads are not retried.
+
+    # Note
+
+    `backoff` must be zero on `wasm32-unknown-unknown`, which cannot sleep.
+    */
+    pub fn set_load_retries(&mut self, retries: usize, backoff: Duration) {
+        self.roots.loader.retries = retries;
+        self.roots.loader.retry_backoff = backoff;
+    }
+
     /// Overrides default [`UrlLoader`] used to load schema resources
     pub fn use_loader(&mut self, url_loader: Box<dyn UrlLoader>) {
         self.roots.loader.use_loader(url_loader);
     }
 
+    /**
+    Reports progress of compilations to `observer`, like for showing
+    progress of compiling schemas with many remote references.
+
+    Compilations done in other threads by [`Compiler::compile_all`] are
+    not reported.
+    */
+    pub fn set_observer(&mut self, observer: Box<dyn CompileObserver>) {
+        self.roots.loader.observer = Some(observer);
+    }
+
     /**
     Registers custom `format`
 
+    Format is looked up by name, in the following order:
+
+    - format registered last with same name, using either this method
+      or [`Compiler::register_context_format`]
+    - built-in format, same for all drafts
+
+    So registering a built-in format name overrides it, in all schemas
+    compiled afterwards. Use [`Format::builtin`] to extend the built-in one.
+
     # Note
 
     - `regex` format cannot be overridden
@@ -145,10 +384,89 @@ impl Compiler {
     */
     pub fn register_format(&mut self, format: Format) {
         if format.name != "regex" {
+            self.context_formats.remove(format.name);
             self.formats.insert(format.name, format);
         }
     }
 
+    /**
+    Tightens built-in `format` with given `policy`, in all schemas
+    compiled afterwards. Replaces any policy configured earlier.
+
+    Value passing built-in format, but not the policy, fails with
+    [`ErrorKind::Format`], whose error explains the policy violated.
+
+    The policy does not apply, if `format` is overridden using
+    [`Compiler::register_format`] or [`Compiler::register_context_format`].
+
+    # Errors
+
+    returns [`CompileError::UnsupportedFormatPolicy`] if `policy` is not
+    meant for `format`, for example [`DateTimePolicy`] is only meant
+    for `date-time`.
+    */
+    pub fn configure_format(
+        &mut self,
+        format: &str,
+        policy: impl Into<FormatPolicy>,
+    ) -> Result<(), CompileError> {
+        let policy = policy.into();
+        if format != policy.format() {
+            return Err(CompileError::UnsupportedFormatPolicy {
+                format: format.to_owned(),
+                policy_format: policy.format(),
+            });
+        }
+        self.format_policies.insert(policy.format(), policy);
+        Ok(())
+    }
+
+    /**
+    Registers custom `format`, which is given the context of validation.
+    see [`ValidationOptions::context`]
+
+    Replaces any format with same name, registered earlier
+    using [`Compiler::register_format`]. Same notes apply.
+    */
+    pub fn register_context_format(&mut self, format: ContextFormat) {
+        if format.name != "regex" {
+            self.formats.remove(format.name);
+            self.context_formats.insert(format.name, format);
+        }
+    }
+
+    /**
+    Registers custom vocabulary identified by `uri`.
+
+    Keywords of the vocabulary are compiled, only in schemas whose
+    meta-schema lists `uri` in `$vocabulary`, whether required or not.
+    Meta-schemas requiring vocabularies which are not registered, fail
+    with [`CompileError::UnsupportedVocabulary`].
+
+    # Note
+
+    - vocabulary must be registered before compiling schemas, that use it
+    - vocabularies are supported only in draft >= 2019-09
+
+    # Errors
+
+    returns [`CompileError`] if url of meta-schema cannot be parsed.
+    */
+    pub fn register_vocabulary(
+        &mut self,
+        uri: &str,
+        vocabulary: Vocabulary,
+    ) -> Result<(), CompileError> {
+        if let Some((url, doc)) = vocabulary.metaschema {
+            self.add_resource(&url, doc)?;
+        }
+        self.roots.vocabularies.insert(uri.to_owned());
+        self.vocabularies.retain(|(u, _)| u != uri);
+        self.vocabularies
+            .push((uri.to_owned(), vocabulary.keywords));
+        Ok(())
+    }
+
     /**
     Registers custom `contentEncoding`
 
@@ -169,20 +487,329 @@ impl Compiler {
         self.media_types.insert(media_type.name, media_type);
     }
 
+    /**
+    Returns optional behaviors supported by this compiler. Same as
+    [`capabilities`](crate::capabilities()), but also lists formats,
+    content encodings, media types and vocabularies registered, and
+    keywords of registered vocabularies, and extensions enabled in
+    [`Capabilities::enabled_extensions`].
+
+    ```rust
+    # use boon::*;
+    # use serde_json::json;
+    let mut compiler = Compiler::new();
+    compiler.register_format(Format {
+        name: "even",
+        func: |_| Ok(()),
+    });
+    let caps = compiler.capabilities();
+    assert!(caps.formats.iter().any(|f| f == "even"));
+    assert!(caps.enabled_extensions.is_empty());
+    let json = serde_json::to_value(&caps)?;
+    assert_eq!(json["drafts"][0], "http://json-schema.org/draft-04/schema");
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+    */
+    pub fn capabilities(&self) -> Capabilities {
+        let mut caps = capabilities();
+        let formats = self.formats.keys().chain(self.context_formats.keys());
+        caps.formats.extend(formats.map(|name| name.to_string()));
+        let decoders = self.decoders.keys().map(|name| name.to_string());
+        caps.content_encodings.extend(decoders);
+        let media_types = self.media_types.keys().map(|name| name.to_string());
+        caps.content_media_types.extend(media_types);
+        for (uri, keywords) in &self.vocabularies {
+            caps.vocabularies.push(uri.clone());
+            caps.keywords
+                .extend(keywords.iter().map(|kw| kw.name.to_owned()));
+        }
+        caps.enabled_extensions = self.extensions.iter().map(|ext| ext.to_string()).collect();
+        caps.normalize();
+        caps
+    }
+
+    /**
+    Enables extension `name`, in all schemas compiled afterwards.
+    Extension keywords are not part of any draft, so they are ignored
+    like any unknown keyword, unless enabled.
+
+    Supported extensions:
+
+    - `x-time-range`: keywords `x-notBefore` and `x-notAfter` bound
+      strings in `date-time` or `date` format, inclusive. Date is taken
+      as start of day in UTC; other strings are left to `format`. Bound
+      is `"now"`, a duration relative to now like `"P-30D"` or `"-PT1H"`,
+      or a date-time. Current time is given by [`ValidationOptions::clock`].
+      Value out of range fails with [`ErrorKind::Keyword`](crate::ErrorKind::Keyword),
+      whose error is [`TimeRangeError`](crate::TimeRangeError).
+
+    ```rust
+    # use boon::*;
+    # use serde_json::json;
+    # use std::time::{Duration, UNIX_EPOCH};
+    let mut compiler = Compiler::new();
+    compiler.enable_extension("x-time-range");
+    compiler.add_resource("schema.json", json!({
+        "format": "date-time",
+        "x-notBefore": "P-30D",
+        "x-notAfter": "now"
+    }))?;
+    let mut schemas = Schemas::new();
+    let sch_index = compiler.compile("schema.json", &mut schemas)?;
+    let clock = || UNIX_EPOCH + Duration::from_secs(1_700_000_000); // 2023-11-14T22:13:20Z
+    let opts = ValidationOptions::new().clock(&clock);
+    let v = json!("2023-11-01T00:00:00Z");
+    assert!(schemas.validate_with(&v, sch_index, &opts).is_ok());
+    let v = json!("2023-12-01T00:00:00Z");
+    assert!(schemas.validate_with(&v, sch_index, &opts).is_err());
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+
+    # Panics
+
+    Panics if `name` is not a supported extension.
+    */
+    pub fn enable_extension(&mut self, name: &str) {
+        let Some(ext) = EXTENSIONS.iter().find(|ext| **ext == name) else {
+            panic!("Compiler::enable_extension: unsupported extension {name:?}");
+        };
+        self.extensions.insert(ext);
+    }
+
+    /**
+    Ignores keyword `name` while compiling schemas, so that it
+    is treated like an unknown keyword, i.e. an annotation.
+
+    Keywords needed to identify and resolve schema resources, like
+    `$ref`, `$id` and `$schema`, cannot be disabled; they are silently
+    ignored.
+
+    # Note
+
+    This applies to all subsequent compilations with this compiler.
+    Schemas already compiled into [`Schemas`] are not affected.
+    */
+    pub fn disable_keyword(&mut self, name: &str) {
+        if !STRUCTURAL_KEYWORDS.contains(&name) {
+            self.disabled_keywords.insert(name.to_owned());
+        }
+    }
+
+    /**
+    Fail compilation, if any [`Diagnostic`] with given `codes` is reported.
+
+    Such diagnostics are reported with [`Severity::Error`] in
+    [`CompileError::Diagnostics`].
+    */
+    pub fn deny(&mut self, codes: &[&str]) {
+        self.deny.extend(codes.iter().map(|c| c.to_string()));
+    }
+
+    /// Do not report any [`Diagnostic`] with given `codes`, unless
+    /// denied using [`Compiler::deny`].
+    pub fn allow(&mut self, codes: &[&str]) {
+        self.allow.extend(codes.iter().map(|c| c.to_string()));
+    }
+
+    /// Returns diagnostics reported by successful compilations so far,
+    /// and clears them.
+    pub fn take_diagnostics(&mut self) -> Vec<Diagnostic> {
+        std::mem::take(&mut self.diagnostics)
+    }
+
     /**
     Adds schema resource which used later in reference resoltion
     If you do not know which schema resources required, then use [`UrlLoader`].
 
     The argument `loc` can be file path or url. any fragment in `loc` is ignored.
 
+    Adding same document at same url again does nothing. Registration
+    is remembered, see [`Compiler::origin_of`].
+
     # Errors
 
-    returns [`CompileError`] if url parsing failed.
+    returns [`CompileError`] if url parsing failed, or
+    [`CompileError::DocumentTooLarge`] if `json` exceeds the limit
+    set by [`Compiler::set_max_document_size`], or
+    [`CompileError::ConflictingResource`] if different document is
+    already added or loaded at the url.
     */
-    pub fn add_resource(&mut self, loc: &str, json: Value) -> Result<(), CompileError> {
+    pub fn add_resource(&mut self, loc: &str, mut json: Value) -> Result<(), CompileError> {
         let uf = UrlFrag::absolute(loc)?;
-        self.roots.loader.add_doc(uf.url, json);
-        Ok(())
+        let loader = &self.roots.loader;
+        loader.check_size(&uf.url, || json_size(&json))?;
+        loader.strip(&mut json);
+        loader.add_doc(uf.url, json, Origin::Resource(loc.to_owned()))
+    }
+
+    /**
+    Same as [`Compiler::add_resource`], but takes json `bytes` in
+    utf-8 or utf-16, with optional byte order mark.
+
+    # Errors
+
+    returns [`CompileError`] if url parsing failed, or
+    [`CompileError::LoadUrlError`] if `bytes` is not valid json.
+    Encoding problems are reported with [`EncodingError`](crate::EncodingError)
+    as source.
+    */
+    pub fn add_resource_bytes(&mut self, loc: &str, bytes: &[u8]) -> Result<(), CompileError> {
+        let uf = UrlFrag::absolute(loc)?;
+        let loader = &self.roots.loader;
+        loader.check_size(&uf.url, || bytes.len())?;
+        let mut json = json_from_bytes(bytes).map_err(|src| CompileError::LoadUrlError {
+            url: uf.url.as_str().to_owned(),
+            src,
+            referenced_from: vec![],
+        })?;
+        loader.strip(&mut json);
+        loader.add_doc(uf.url, json, Origin::Resource(loc.to_owned()))
+    }
+
+    /**
+    Same as [`Compiler::add_resource`], but takes json5 text, which
+    allows comments, trailing commas etc.
+
+    # Errors
+
+    returns [`CompileError`] if url parsing failed, or
+    [`CompileError::LoadUrlError`] with [`Json5Error`](crate::Json5Error)
+    as source if `json5` is not valid.
+    */
+    #[cfg(feature = "json5")]
+    pub fn add_resource_json5(&mut self, loc: &str, json5: &str) -> Result<(), CompileError> {
+        let uf = UrlFrag::absolute(loc)?;
+        let loader = &self.roots.loader;
+        loader.check_size(&uf.url, || json5.len())?;
+        let mut json =
+            crate::loader::parse_json5(json5).map_err(|e| CompileError::LoadUrlError {
+                url: uf.url.as_str().to_owned(),
+                src: e.into(),
+                referenced_from: vec![],
+            })?;
+        loader.strip(&mut json);
+        loader.add_doc(uf.url, json, Origin::Resource(loc.to_owned()))
+    }
+
+    /**
+    Tells where document at `url` comes from, if it is added or loaded
+    so far. Any fragment in `url` is ignored.
+
+    ```rust
+    # use boon::*;
+    # use serde_json::json;
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://example.com/schema.json", json!({}))?;
+    assert_eq!(
+        compiler.origin_of("http://example.com/schema.json#/a"),
+        Some(&Origin::Resource("http://example.com/schema.json".to_owned()))
+    );
+    assert_eq!(compiler.origin_of("http://example.com/other.json"), None);
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+    */
+    pub fn origin_of(&self, url: &str) -> Option<&Origin> {
+        let uf = UrlFrag::absolute(url).ok()?;
+        self.roots.loader.origin(&uf.url)
+    }
+
+    /// documents added or loaded so far, other than standard metaschemas,
+    /// sorted by url.
+    #[cfg(feature = "codegen")]
+    pub(crate) fn documents(&self) -> Vec<(Url, &Value)> {
+        self.roots.loader.docs()
+    }
+
+    /**
+    Resolves `ref_` found in subschema at json-pointer `ptr` of document `loc`,
+    exactly as compiler resolves `$ref`, honoring embedded `$id`s.
+
+    Returns the location of the referenced schema, as url with
+    json-pointer fragment. Referenced documents are loaded if needed,
+    but nothing is compiled. see [`Schemas::resolve_ref`] to get just
+    the absolute url of `ref_`, without looking it up.
+
+    # Errors
+
+    returns [`CompileError`] if loading documents, or resolving `ref_` failed.
+    */
+    pub fn resolve(&mut self, loc: &str, ptr: &str, ref_: &str) -> Result<String, CompileError> {
+        let uf = UrlFrag::absolute(loc)?;
+        if !ptr.is_empty() && !ptr.starts_with('/') {
+            return Err(CompileError::InvalidJsonPointer {
+                loc: UrlFrag::format(&uf.url, ptr),
+                position: 0,
+                reason: "must start with '/'",
+            });
+        }
+        self.roots.or_load(uf.url.clone())?;
+        let Some(root) = self.roots.get(&uf.url) else {
+            return Err(CompileError::Bug("or_load didn't add".into()));
+        };
+        let base_url = root.base_url(&JsonPointer(ptr.to_owned()));
+        let abs_ref = UrlFrag::join(base_url, ref_)?;
+        if let Some(up) = root.resolve(&abs_ref)? {
+            return Ok(up.to_string());
+        }
+        Ok(self.roots.resolve_fragment(abs_ref)?.to_string())
+    }
+
+    /**
+    Returns report of the last call to [`Compiler::compile`] or its
+    variants, whether it succeeded or not. [`Compiler::compile_all`]
+    reports all its locations together, and so does
+    `Compiler::compile_all_parallel`.
+
+    Schemas compiled earlier into `target` are not compiled again, so
+    documents reached only through them are not listed in
+    [`CompileReport::documents`]. [`CompileReport::unused_resources`]
+    considers all compilations so far, so that resources needed by any
+    of them are not reported.
+
+    ```rust
+    # use boon::*;
+    # use serde_json::json;
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://a.com/main.json", json!({"$ref": "b.json"}))?;
+    compiler.add_resource("http://a.com/b.json", json!({"type": "string"}))?;
+    compiler.add_resource("http://a.com/dead.json", json!({"type": "string"}))?;
+    let mut schemas = Schemas::new();
+    compiler.compile("http://a.com/main.json", &mut schemas)?;
+    let report = compiler.last_compile_report();
+    assert_eq!(report.documents, ["http://a.com/b.json", "http://a.com/main.json"]);
+    assert_eq!(report.unused_resources, ["http://a.com/dead.json"]);
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+    */
+    pub fn last_compile_report(&self) -> &CompileReport {
+        &self.report
+    }
+
+    fn start_report(&mut self) {
+        self.roots.loader.take_usage();
+        self.report = CompileReport::default();
+    }
+
+    // documents already in report, if any, are kept; see
+    // `compile_all_parallel` which adds those of forked compilers.
+    fn finish_report(&mut self) {
+        let usage = self.roots.loader.take_usage();
+        let extend = |list: &mut Vec<String>, urls: &HashSet<Url>| {
+            list.extend(urls.iter().map(Url::to_string));
+            list.sort();
+            list.dedup();
+        };
+        extend(&mut self.report.documents, &usage.docs);
+        extend(&mut self.report.metaschemas, &usage.metas);
+        self.consulted.extend(usage.docs);
+        self.consulted.extend(usage.metas);
+        let mut unused = self.roots.loader.resources();
+        unused.retain(|url| !self.consulted.contains(url));
+        self.report.unused_resources.clear();
+        extend(
+            &mut self.report.unused_resources,
+            &unused.into_iter().collect(),
+        );
     }
 
     /**
@@ -194,12 +821,38 @@ impl Compiler {
               `samples/schema_file.json#defs/address`
 
     if `loc` is already compiled, it simply returns the same [`SchemaIndex`]
+
+    json-pointer fragment is percent-decoded, so `#/foo%20bar` and `#/foo bar`
+    refer to same subschema. Malformed json-pointer fragment is reported
+    as [`CompileError::InvalidJsonPointer`] without loading anything.
+
+    Schemas are added to `target` only if compilation succeeds, so
+    `target` stays usable after an error.
      */
     pub fn compile(
         &mut self,
         loc: &str,
         target: &mut Schemas,
     ) -> Result<SchemaIndex, CompileError> {
+        self.start_report();
+        let result = self.compile_loc(loc, target);
+        self.finish_report();
+        result
+    }
+
+    fn compile_loc(
+        &mut self,
+        loc: &str,
+        target: &mut Schemas,
+    ) -> Result<SchemaIndex, CompileError> {
+        let (_, frag) = split(loc);
+        if let Err((position, reason)) = JsonPointer::check_fragment(frag) {
+            return Err(CompileError::InvalidJsonPointer {
+                loc: loc.to_owned(),
+                position,
+                reason,
+            });
+        }
         let uf = UrlFrag::absolute(loc)?;
         // resolve anchor
         let up = self.roots.resolve_fragment(uf)?;
@@ -211,6 +864,277 @@ impl Compiler {
         result
     }
 
+    /**
+    Same as [`Compiler::compile`], but uses `draft` for the document
+    of `loc`, if it does not declare `$schema`.
+
+    This overrides [`Compiler::set_default_draft`] for that document
+    only. Embedded resources use the draft of their document, and
+    documents referenced from it use the default draft, unless they
+    declare `$schema`.
+
+    Draft of a document is fixed once it is loaded, so `draft` has no
+    effect if the document of `loc` is already loaded by an earlier
+    compilation. [`Schemas::draft_of`] can be used to check the draft used.
+    */
+    pub fn compile_with_draft(
+        &mut self,
+        loc: &str,
+        target: &mut Schemas,
+        draft: Draft,
+    ) -> Result<SchemaIndex, CompileError> {
+        self.start_report();
+        let result = self
+            .load_with_draft(loc, draft)
+            .and_then(|_| self.compile_loc(loc, target));
+        self.finish_report();
+        result
+    }
+
+    fn load_with_draft(&mut self, loc: &str, draft: Draft) -> Result<(), CompileError> {
+        if let Ok(uf) = UrlFrag::absolute(loc) {
+            if self.roots.get(&uf.url).is_none() {
+                let default_draft =
+                    std::mem::replace(&mut self.roots.default_draft, draft.internal());
+                let result = self.roots.or_load(uf.url);
+                self.roots.default_draft = default_draft;
+                result?;
+            }
+        }
+        Ok(())
+    }
+
+    /**
+    Same as [`Compiler::compile`], but also returns the diagnostics
+    reported so far.
+
+    see [`Compiler::take_diagnostics`]
+    */
+    pub fn compile_with_diagnostics(
+        &mut self,
+        loc: &str,
+        target: &mut Schemas,
+    ) -> Result<(SchemaIndex, Vec<Diagnostic>), CompileError> {
+        let sch = self.compile(loc, target)?;
+        Ok((sch, self.take_diagnostics()))
+    }
+
+    /**
+    Compiles each of `locs` into `target`, independently of others.
+
+    Returns result of each compilation, in the order of `locs`. A
+    location that fails to compile does not affect the others; schemas
+    it shares with them are compiled again, by the ones that succeed.
+
+    see [`Compiler::compile`]
+    */
+    pub fn compile_all<S: AsRef<str>>(
+        &mut self,
+        locs: &[S],
+        target: &mut Schemas,
+    ) -> Vec<Result<SchemaIndex, CompileError>> {
+        self.start_report();
+        let results = locs
+            .iter()
+            .map(|loc| self.compile_loc(loc.as_ref(), target))
+            .collect();
+        self.finish_report();
+        results
+    }
+
+    /**
+    Compiles each of `locs` into new [`Schemas`], using multiple threads.
+
+    Documents which do not refer to other documents, i.e. each `$ref`,
+    `$dynamicRef` and `$recursiveRef` in them is a fragment and each
+    `$schema` is a standard metaschema, are compiled in parallel, each
+    into its own staging [`Schemas`], and then merged in the order of
+    `locs`. Remaining documents are compiled sequentially afterwards.
+
+    Returned indexes are in the order of `locs`, and are deterministic
+    for same `locs`.
+
+    # Errors
+
+    returns [`CompileError`] of the first location in `locs` that
+    fails to compile.
+    */
+    #[cfg(feature = "parallel")]
+    pub fn compile_all_parallel(
+        &mut self,
+        locs: Vec<String>,
+    ) -> Result<(Schemas, Vec<SchemaIndex>), CompileError> {
+        self.start_report();
+        let result = self.compile_staged(locs);
+        self.finish_report();
+        result
+    }
+
+    #[cfg(feature = "parallel")]
+    fn compile_staged(
+        &mut self,
+        locs: Vec<String>,
+    ) -> Result<(Schemas, Vec<SchemaIndex>), CompileError> {
+        use std::{
+            sync::{
+                atomic::{AtomicUsize, Ordering},
+                Arc, Mutex,
+            },
+            thread,
+        };
+
+        // documents are loaded upfront, by this thread
+        let mut jobs = vec![]; // (loc-index, url, doc, origin)
+        for (i, loc) in locs.iter().enumerate() {
+            let Ok(uf) = UrlFrag::absolute(loc) else {
+                continue;
+            };
+            // draft of documents used earlier is already fixed
+            if self.roots.get(&uf.url).is_some() || self.roots.loader.load(&uf.url).is_err() {
+                continue;
+            }
+            let Some(doc) = self.roots.loader.get_doc_arc(&uf.url) else {
+                continue;
+            };
+            if is_self_contained(&doc) {
+                let origin = self.roots.loader.origin(&uf.url).cloned();
+                jobs.push((i, uf.url, doc, origin));
+            }
+        }
+
+        let mut staged = (0..locs.len()).map(|_| None).collect::<Vec<_>>();
+        let workers = thread::available_parallelism()
+            .map_or(1, |n| n.get())
+            .min(jobs.len());
+        if !jobs.is_empty() {
+            let fork = self.fork();
+            let next = AtomicUsize::new(0);
+            let results = Mutex::new(vec![]);
+            thread::scope(|s| {
+                for _ in 0..workers {
+                    // where threads are not supported, all are compiled sequentially
+                    _ = thread::Builder::new().spawn_scoped(s, || {
+                        let mut c = fork();
+                        // jobs are picked as workers get free
+                        while let Some((i, url, doc, origin)) =
+                            jobs.get(next.fetch_add(1, Ordering::Relaxed))
+                        {
+                            let origin = origin.clone().unwrap_or(Origin::Loaded(url.to_string()));
+                            _ = c
+                                .roots
+                                .loader
+                                .add_shared_doc(url.clone(), Arc::clone(doc), origin);
+                            let mut staging = Schemas::new();
+                            // errors are reported by sequential compilation
+                            if let Ok(idx) = c.compile(&locs[*i], &mut staging) {
+                                let diagnostics = c.take_diagnostics();
+                                let report = std::mem::take(&mut c.report);
+                                let consulted = std::mem::take(&mut c.consulted);
+                                let staged = (staging, idx, diagnostics, report, consulted);
+                                let mut results = results.lock().unwrap_or_else(|e| e.into_inner());
+                                results.push((*i, staged));
+                            }
+                        }
+                    });
+                }
+            });
+            for (i, result) in results.into_inner().unwrap_or_else(|e| e.into_inner()) {
+                staged[i] = Some(result);
+            }
+        }
+
+        let mut target = Schemas::new();
+        let mut indexes = Vec::with_capacity(locs.len());
+        for (loc, staged) in locs.iter().zip(staged) {
+            let idx = match staged {
+                Some((staging, idx, diagnostics, report, consulted)) => {
+                    self.diagnostics.extend(diagnostics);
+                    // sorted and deduplicated by `finish_report`
+                    self.report.documents.extend(report.documents);
+                    self.report.metaschemas.extend(report.metaschemas);
+                    self.report.subschemas.extend(report.subschemas);
+                    self.consulted.extend(consulted);
+                    target.merge(staging)[idx.0]
+                }
+                None => self.compile_loc(loc, &mut target)?,
+            };
+            indexes.push(idx);
+        }
+        Ok((target, indexes))
+    }
+
+    // returns function creating compilers with same options as this,
+    // which can be called from other threads.
+    #[cfg(feature = "parallel")]
+    fn fork(&self) -> impl Fn() -> Compiler + Sync {
+        // no `..`, so that new fields are not missed
+        let Compiler {
+            roots,
+            assert_format,
+            assert_content,
+            retain_source,
+            anchor_patterns,
+            regex_limits,
+            formats,
+            context_formats,
+            format_policies,
+            vocabularies,
+            decoders,
+            media_types,
+            deny,
+            allow,
+            disabled_keywords,
+            extensions,
+            diagnostics: _,
+            report: _,
+            consulted: _,
+        } = self;
+        let (default_draft, allow_non_schemas) = (roots.default_draft, roots.allow_non_schemas);
+        let registered_vocabs = roots.vocabularies.clone();
+        let stripped_keywords = roots.loader.stripped_keywords.clone();
+        let max_doc_size = roots.loader.max_doc_size;
+        let (max_meta_depth, max_meta_docs) =
+            (roots.loader.max_meta_depth, roots.loader.max_meta_docs);
+        let (assert_format, assert_content) = (*assert_format, *assert_content);
+        let (retain_source, anchor_patterns, regex_limits) =
+            (*retain_source, *anchor_patterns, *regex_limits);
+        let (formats, context_formats) = (formats.clone(), context_formats.clone());
+        let format_policies = format_policies.clone();
+        let vocabularies = vocabularies.clone();
+        let (decoders, media_types) = (decoders.clone(), media_types.clone());
+        let (deny, allow) = (deny.clone(), allow.clone());
+        let disabled_keywords = disabled_keywords.clone();
+        let extensions = extensions.clone();
+        move || {
+            let mut c = Compiler {
+                assert_format,
+                assert_content,
+                retain_source,
+                anchor_patterns,
+                regex_limits,
+                formats: formats.clone(),
+                context_formats: context_formats.clone(),
+                format_policies: format_policies.clone(),
+                vocabularies: vocabularies.clone(),
+                decoders: decoders.clone(),
+                media_types: media_types.clone(),
+                deny: deny.clone(),
+                allow: allow.clone(),
+                disabled_keywords: disabled_keywords.clone(),
+                extensions: extensions.clone(),
+                ..Compiler::default()
+            };
+            c.roots.default_draft = default_draft;
+            c.roots.allow_non_schemas = allow_non_schemas;
+            c.roots.vocabularies = registered_vocabs.clone();
+            c.roots.loader.stripped_keywords = stripped_keywords.clone();
+            c.roots.loader.max_doc_size = max_doc_size;
+            c.roots.loader.max_meta_depth = max_meta_depth;
+            c.roots.loader.max_meta_docs = max_meta_docs;
+            c
+        }
+    }
+
     fn do_compile(
         &mut self,
         up: UrlPtr,
@@ -224,21 +1148,46 @@ impl Compiler {
             // already got compiled
             return Ok(index);
         }
+        if let Some(observer) = &self.roots.loader.observer {
+            observer.on_enqueue(&queue.schemas[0].up.to_string());
+        }
 
         while queue.schemas.len() > compiled.len() {
-            let up = &queue.schemas[compiled.len()];
-            self.roots.ensure_subschema(up)?;
+            let i = compiled.len();
+            let up = &queue.schemas[i].up;
+            self.report.subschemas.push(up.to_string());
+            self.roots
+                .ensure_subschema(up)
+                .map_err(|e| queue.referenced_from(e, i))?;
             let Some(root) = self.roots.get(&up.url) else {
                 return Err(CompileError::Bug("or_load didn't add".into()));
             };
             let doc = self.roots.loader.load(&root.url)?;
-            let v = up.lookup(doc)?;
-            let sch = self.compile_value(target, v, &up.clone(), root, &mut queue)?;
+            let v = up.lookup(doc).map_err(|e| queue.referenced_from(e, i))?;
+            queue.current = Some(i);
+            let len = queue.schemas.len();
+            let sch = self
+                .compile_value(target, v, &up.clone(), root, &mut queue)
+                .map_err(|e| queue.referenced_from(e, i))?;
+            if let Some(observer) = &self.roots.loader.observer {
+                for item in &queue.schemas[len..] {
+                    observer.on_enqueue(&item.up.to_string());
+                }
+                observer.on_schema_compiled(&sch.loc, sch.idx);
+            }
             compiled.push(sch);
             self.roots.insert(&mut queue.roots);
         }
 
-        target.insert(queue.schemas, compiled);
+        let denied = queue.diagnostics.deny(&self.deny);
+        queue.diagnostics.allow(&self.allow);
+        if !denied.is_empty() {
+            return Err(CompileError::Diagnostics(denied));
+        }
+        self.diagnostics.extend(queue.diagnostics.into_vec());
+
+        let locs = queue.schemas.into_iter().map(|item| item.up).collect();
+        target.insert(locs, compiled);
         Ok(index)
     }
 
@@ -252,6 +1201,14 @@ impl Compiler {
     ) -> Result<Schema, CompileError> {
         let mut s = Schema::new(up.to_string());
         s.draft_version = root.draft.version;
+        s.float_integers = s.draft_version >= 6;
+        if self.retain_source {
+            s.source = self
+                .roots
+                .loader
+                .get_doc_arc(&up.url)
+                .map(|doc| (doc, up.ptr.as_str().to_owned()));
+        }
 
         // we know it is already in queue, we just want to get its index
         let len = queue.schemas.len();
@@ -266,18 +1223,27 @@ impl Compiler {
             queue.enqueue_schema(schemas, base)
         };
 
+        if s.idx == s.resource {
+            s.base_url = Some(root.base_url(&up.ptr).clone());
+        }
+
         // if resource, enqueue dynamicAnchors for compilation
         if s.idx == s.resource && root.draft.version >= 2020 {
             let res = root.resource(&up.ptr);
-            for (anchor, anchor_ptr) in &res.anchors {
-                if res.dynamic_anchors.contains(anchor) {
-                    let up = UrlPtr {
-                        url: up.url.clone(),
-                        ptr: anchor_ptr.clone(),
-                    };
-                    let danchor_sch = queue.enqueue_schema(schemas, up);
-                    s.dynamic_anchors.insert(anchor.to_string(), danchor_sch);
-                }
+            // sorted, so that schema indexes do not depend on hash order
+            let mut danchors = res
+                .anchors
+                .iter()
+                .filter(|(anchor, _)| res.dynamic_anchors.contains(*anchor))
+                .collect::<Vec<_>>();
+            danchors.sort_by(|(a1, _), (a2, _)| a1.0.cmp(&a2.0));
+            for (anchor, anchor_ptr) in danchors {
+                let up = UrlPtr {
+                    url: up.url.clone(),
+                    ptr: anchor_ptr.clone(),
+                };
+                let danchor_sch = queue.enqueue_schema(schemas, up);
+                s.dynamic_anchors.insert(anchor.to_string(), danchor_sch);
             }
         }
 
@@ -297,8 +1263,17 @@ impl Compiler {
                     .compile_obj(&mut s)?;
                 }
             }
-            Value::Bool(b) => s.boolean = Some(*b),
-            _ => {}
+            Value::Bool(b) => {
+                s.boolean = Some(*b);
+                s.literal_bool = Some(*b);
+            }
+            _ if self.roots.allow_non_schemas => {}
+            _ => {
+                return Err(CompileError::NotASchema {
+                    loc: up.to_string(),
+                    found_type: Type::of(v),
+                });
+            }
         }
 
         s.all_props_evaluated = s.additional_properties.is_some();
@@ -312,11 +1287,104 @@ impl Compiler {
         } else {
             s.prefix_items.len()
         };
+        s.leaf = s.is_leaf();
+        s.groups = s.keyword_groups();
 
         Ok(s)
     }
 }
 
+/**
+Documents and subschemas consulted by a compilation.
+
+see [`Compiler::last_compile_report`]
+*/
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompileReport {
+    /// urls of documents, whose schemas are compiled or resolved, sorted.
+    pub documents: Vec<String>,
+    /// urls of documents consulted as metaschema through `$schema`, sorted.
+    /// Standard metaschemas are built in, so they are not consulted.
+    pub metaschemas: Vec<String>,
+    /// locations of subschemas compiled, in the order compiled.
+    pub subschemas: Vec<String>,
+    /// urls of documents added with [`Compiler::add_resource`] or its
+    /// variants, which no compilation so far consulted, sorted.
+    pub unused_resources: Vec<String>,
+}
+
+/**
+Observes progress of compilation, see [`Compiler::set_observer`].
+
+All methods do nothing by default. They are called synchronously, so
+they should return quickly.
+
+```rust
+# use std::cell::Cell;
+# use boon::*;
+# use serde_json::json;
+#[derive(Default)]
+struct Progress {
+    compiled: Cell<usize>,
+}
+
+impl CompileObserver for Progress {
+    fn on_schema_compiled(&self, _loc: &str, _sch_index: SchemaIndex) {
+        self.compiled.set(self.compiled.get() + 1);
+    }
+}
+
+let mut compiler = Compiler::new();
+compiler.set_observer(Box::new(Progress::default()));
+compiler.add_resource("http://a.com/schema.json", json!({"items": {"type": "string"}}))?;
+compiler.compile("http://a.com/schema.json", &mut Schemas::new())?;
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+*/
+pub trait CompileObserver {
+    /// Called when schema at `loc` is queued for compilation.
+    fn on_enqueue(&self, _loc: &str) {}
+
+    /// Called before loading document at `url` using [`UrlLoader`].
+    /// Documents added as resources, or already loaded, are not loaded again.
+    fn on_load_start(&self, _url: &str) {}
+
+    /// Called after loading document at `url`, with its outcome.
+    fn on_load_end(&self, _url: &str, _result: Result<(), &CompileError>) {}
+
+    /// Called after schema at `loc` is compiled. Its `sch_index` is valid
+    /// only if the compilation succeeds as a whole.
+    fn on_schema_compiled(&self, _loc: &str, _sch_index: SchemaIndex) {}
+}
+
+/**
+Limits applied when compiling regexes.
+
+see [`Compiler::set_regex_limits`]
+*/
+#[derive(Debug, Clone, Copy)]
+pub struct RegexLimits {
+    /// approximate size limit in bytes of compiled regex.
+    /// see [`RegexBuilder::size_limit`].
+    pub size_limit: usize,
+    /// approximate size limit in bytes of cache used by lazy DFA.
+    /// see [`RegexBuilder::dfa_size_limit`].
+    pub dfa_size_limit: usize,
+    /// maximum length of pattern in bytes. `None` means no limit.
+    pub max_pattern_len: Option<usize>,
+}
+
+impl Default for RegexLimits {
+    /// same as defaults of `regex` crate, with no pattern length limit.
+    fn default() -> Self {
+        Self {
+            size_limit: 10 * (1 << 20),
+            dfa_size_limit: 2 * (1 << 20),
+            max_pattern_len: None,
+        }
+    }
+}
+
 struct ObjCompiler<'c, 'v, 'l, 's, 'r, 'q> {
     c: &'c Compiler,
     obj: &'v Map<String, Value>,
@@ -330,26 +1398,186 @@ struct ObjCompiler<'c, 'v, 'l, 's, 'r, 'q> {
 impl ObjCompiler<'_, '_, '_, '_, '_, '_> {
     fn compile_obj(&mut self, s: &mut Schema) -> Result<(), CompileError> {
         self.compile_draft4(s)?;
-        if self.draft_version() >= 6 {
-            self.compile_draft6(s)?;
+        // siblings of $ref are ignored, see compile_draft4
+        if s.ref_.is_none() || self.draft_version() >= 2019 {
+            if self.draft_version() >= 6 {
+                self.compile_draft6(s)?;
+            }
+            if self.draft_version() >= 7 {
+                self.compile_draft7(s)?;
+            }
+            if self.draft_version() >= 2019 {
+                self.compile_draft2019(s)?;
+            }
+            if self.draft_version() >= 2020 {
+                self.compile_draft2020(s)?;
+            }
+            self.compile_extensions(s)?;
+            self.compile_vocabularies(s)?;
+        }
+        self.collect_flags(s);
+        self.lint_dependencies(s);
+        self.collect_ignored(s);
+        self.fold(s);
+        Ok(())
+    }
+
+    // replaces schema which always passes or always fails, with boolean
+    // schema, so that validator takes its fast path. schema is folded to
+    // `false` only if `enum` or `not` is its only assertion, so that
+    // validator can report same error as without folding.
+    fn fold(&self, s: &mut Schema) {
+        const IDENTITY: [&str; 5] = ["$id", "id", "$anchor", "$dynamicAnchor", "$recursiveAnchor"];
+        if s.idx == s.resource || IDENTITY.iter().any(|kw| self.obj.contains_key(*kw)) {
+            return;
+        }
+        if s.has_no_effect(false) {
+            s.boolean = Some(true);
+            return;
         }
-        if self.draft_version() >= 7 {
-            self.compile_draft7(s)?;
+        let always_fails = match (&s.enum_, s.not) {
+            (Some(e), None) => e.values.is_empty(),
+            (None, Some(_)) => match self.value("not") {
+                Some(Value::Bool(b)) => *b,
+                Some(Value::Object(obj)) => obj.is_empty(),
+                _ => false,
+            },
+            _ => false,
+        };
+        if always_fails && s.has_no_effect(true) {
+            s.boolean = Some(false);
         }
-        if self.draft_version() >= 2019 {
-            self.compile_draft2019(s)?;
+    }
+
+    fn compile_extensions(&self, s: &mut Schema) -> Result<(), CompileError> {
+        if self.c.extensions.contains("x-time-range") {
+            let bound = |kw: &str| {
+                let v = self.value(kw)?;
+                Some(
+                    Bound::compile(v).map_err(|src| CompileError::InvalidKeyword {
+                        url: self.up.format(kw),
+                        src,
+                    }),
+                )
+            };
+            let range = TimeRange {
+                not_before: bound("x-notBefore").transpose()?,
+                not_after: bound("x-notAfter").transpose()?,
+            };
+            if range.not_before.is_some() || range.not_after.is_some() {
+                s.time_range = Some(range);
+            }
         }
-        if self.draft_version() >= 2020 {
-            self.compile_draft2020(s)?;
+        Ok(())
+    }
+
+    // whether `kw` is keyword of enabled extension, or of registered
+    // vocabulary used by this schema
+    fn is_custom_keyword(&self, kw: &str) -> bool {
+        let extension = self.c.extensions.contains("x-time-range")
+            && matches!(kw, "x-notBefore" | "x-notAfter");
+        extension
+            || self.c.vocabularies.iter().any(|(uri, keywords)| {
+                self.root.has_custom_vocab(uri) && keywords.iter().any(|k| k.name == kw)
+            })
+    }
+
+    fn compile_vocabularies(&mut self, s: &mut Schema) -> Result<(), CompileError> {
+        for (uri, keywords) in &self.c.vocabularies {
+            if !self.root.has_custom_vocab(uri) {
+                continue;
+            }
+            for kw in keywords {
+                if let Some(v) = self.value(kw.name) {
+                    let validator =
+                        (kw.compile)(v).map_err(|src| CompileError::InvalidKeyword {
+                            url: self.up.format(kw.name),
+                            src,
+                        })?;
+                    s.keywords.push((kw.name, validator));
+                }
+            }
         }
         Ok(())
     }
 
+    // annotations with value `true`, retained for `Schemas::redact`
+    fn collect_flags(&self, s: &mut Schema) {
+        if s.ref_.is_some() && self.draft_version() < 2019 {
+            return; // siblings of $ref are ignored
+        }
+        for (kw, v) in self.obj {
+            if v == &Value::Bool(true) && is_annotation(kw) && self.value(kw).is_some() {
+                s.flags.push(kw.clone());
+            }
+        }
+    }
+
+    fn collect_ignored(&mut self, s: &mut Schema) {
+        // keywords introduced in later drafts, with their draft
+        const LATER_KEYWORDS: &[(&str, usize)] = &[
+            ("const", 6),
+            ("contains", 6),
+            ("propertyNames", 6),
+            ("if", 7),
+            ("then", 7),
+            ("else", 7),
+            ("dependentRequired", 2019),
+            ("dependentSchemas", 2019),
+            ("minContains", 2019),
+            ("maxContains", 2019),
+            ("unevaluatedProperties", 2019),
+            ("unevaluatedItems", 2019),
+            ("prefixItems", 2020),
+        ];
+        for kw in self.obj.keys() {
+            if self.c.disabled_keywords.contains(kw) {
+                s.ignored.push((kw.clone(), IgnoreReason::Disabled));
+            } else if let Some((_, since)) = LATER_KEYWORDS
+                .iter()
+                .find(|(k, d)| k == kw && *d > self.draft_version())
+            {
+                s.ignored.push((kw.clone(), IgnoreReason::LaterDraft));
+                let loc = self.up.format(kw);
+                self.queue.diagnostics.warn(
+                    "inert-keyword",
+                    loc,
+                    format!(
+                        "{kw} is ignored under {}; it is supported since {}",
+                        draft_name(self.draft_version()),
+                        draft_name(*since)
+                    ),
+                );
+            }
+        }
+    }
+
     fn compile_draft4(&mut self, s: &mut Schema) -> Result<(), CompileError> {
+        // annotations, retained for examples --
+        s.default = self.value("default").cloned();
+
         if self.has_vocab("core") {
             s.ref_ = self.enqueue_ref("$ref")?;
             if s.ref_.is_some() && self.draft_version() < 2019 {
-                // All other properties in a "$ref" object MUST be ignored
+                // All other properties in a "$ref" object MUST be ignored.
+                // warn about those, later drafts would evaluate.
+                let ignored = self
+                    .obj
+                    .keys()
+                    .filter(|k| is_evaluated_since_2019(k) || self.is_custom_keyword(k))
+                    .map(quote)
+                    .collect::<Vec<_>>();
+                if !ignored.is_empty() {
+                    self.warn(
+                        "ref-siblings-ignored",
+                        "$ref",
+                        format!(
+                            "keywords {} next to $ref are ignored under {}; they would apply under 2019-09 and later",
+                            ignored.join(", "),
+                            draft_name(self.draft_version())
+                        ),
+                    );
+                }
                 return Ok(());
             }
         }
@@ -366,7 +1594,17 @@ impl ObjCompiler<'_, '_, '_, '_, '_, '_> {
                         s.items = Some(Items::SchemaRefs(self.enqueue_arr("items")));
                         s.additional_items = self.enquue_additional("additionalItems");
                     }
-                    _ => s.items = self.enqueue_prop("items").map(Items::SchemaRef),
+                    _ => {
+                        s.items = self.enqueue_prop("items").map(Items::SchemaRef);
+                        if self.value("additionalItems").is_some() {
+                            self.warn(
+                                "inert-keyword",
+                                "additionalItems",
+                                "additionalItems has no effect, unless items is an array"
+                                    .to_owned(),
+                            );
+                        }
+                    }
                 }
             }
 
@@ -375,18 +1613,7 @@ impl ObjCompiler<'_, '_, '_, '_, '_, '_> {
                 let mut v = vec![];
                 if let Some(Value::Object(obj)) = self.value("patternProperties") {
                     for pname in obj.keys() {
-                        let ecma =
-                            ecma::convert(pname).map_err(|src| CompileError::InvalidRegex {
-                                url: self.up.format("patternProperties"),
-                                regex: pname.to_owned(),
-                                src,
-                            })?;
-                        let regex =
-                            Regex::new(ecma.as_ref()).map_err(|e| CompileError::InvalidRegex {
-                                url: self.up.format("patternProperties"),
-                                regex: ecma.into_owned(),
-                                src: e.into(),
-                            })?;
+                        let regex = self.regex("patternProperties", pname)?;
                         let ptr = self.up.ptr.append2("patternProperties", pname);
                         let sch = self.enqueue_schema(ptr);
                         v.push((regex, sch));
@@ -434,13 +1661,21 @@ impl ObjCompiler<'_, '_, '_, '_, '_, '_> {
             }
 
             if let Some(Value::Array(e)) = self.value("enum") {
+                if e.is_empty() {
+                    // allowed by metaschema since 2019-09
+                    self.warn(
+                        "empty-enum",
+                        "enum",
+                        "enum has no values, so no instance is valid".to_owned(),
+                    );
+                }
                 let mut types = Types::default();
                 for item in e {
                     types.add(Type::of(item));
                 }
                 s.enum_ = Some(Enum {
                     types,
-                    values: e.clone(),
+                    values: e.as_slice().into(),
                 });
             }
 
@@ -463,13 +1698,37 @@ impl ObjCompiler<'_, '_, '_, '_, '_, '_> {
             } else {
                 s.exclusive_minimum = self.num("exclusiveMinimum");
             }
+            for kw in ["minimum", "maximum", "exclusiveMinimum", "exclusiveMaximum"] {
+                if let Some(Value::Number(n)) = self.value(kw) {
+                    if !n.as_f64().is_some_and(f64::is_finite) {
+                        self.warn(
+                            "non-finite-bound",
+                            kw,
+                            format!("{kw} {n} is not a finite f64, it is compared as decimal"),
+                        );
+                    }
+                }
+            }
 
             s.max_length = self.usize("maxLength");
             s.min_length = self.usize("minLength");
 
             if let Some(Value::String(p)) = self.value("pattern") {
-                let p = ecma::convert(p).map_err(CompileError::Bug)?;
-                s.pattern = Some(Regex::new(p.as_ref()).map_err(|e| CompileError::Bug(e.into()))?);
+                s.pattern = Some(self.regex("pattern", p)?);
+                // author likely expects full match, when length is constrained
+                let constrained = ["minLength", "maxLength", "format"]
+                    .iter()
+                    .any(|kw| self.value(kw).is_some());
+                if constrained && !self.c.anchor_patterns && !is_anchored(p) {
+                    self.warn(
+                        "unanchored-pattern",
+                        "pattern",
+                        format!(
+                            "pattern {} is not anchored with ^ and $, so it matches any string containing a match",
+                            quote(p)
+                        ),
+                    );
+                }
             }
 
             s.max_items = self.usize("maxItems");
@@ -493,12 +1752,23 @@ impl ObjCompiler<'_, '_, '_, '_, '_, '_> {
             })
         {
             if let Some(Value::String(format)) = self.value("format") {
-                s.format = self
-                    .c
-                    .formats
-                    .get(format.as_str())
-                    .or_else(|| FORMATS.get(format.as_str()))
-                    .cloned();
+                s.context_format = self.c.context_formats.get(format.as_str()).cloned();
+                if s.context_format.is_none() {
+                    s.format = self.c.formats.get(format.as_str()).cloned();
+                    if s.format.is_none() {
+                        s.format = FORMATS.get(format.as_str()).cloned();
+                        s.format_policy = self.c.format_policies.get(format.as_str()).copied();
+                    }
+                }
+                if s.format.is_none() && s.context_format.is_none() {
+                    s.ignored
+                        .push(("format".into(), IgnoreReason::UnknownFormat));
+                    self.warn(
+                        "unknown-format",
+                        "format",
+                        format!("format {} is unknown, so it is not asserted", quote(format)),
+                    );
+                }
             }
         }
 
@@ -512,7 +1782,11 @@ impl ObjCompiler<'_, '_, '_, '_, '_, '_> {
         }
 
         if self.has_vocab("validation") {
-            s.constant = self.value("const").cloned();
+            s.constant = self.value("const").cloned().map(Arc::new);
+        }
+
+        if let Some(Value::Array(examples)) = self.value("examples") {
+            s.examples.clone_from(examples);
         }
 
         Ok(())
@@ -528,6 +1802,16 @@ impl ObjCompiler<'_, '_, '_, '_, '_, '_> {
                 if !self.bool_schema("if", true) {
                     s.else_ = self.enqueue_prop("else");
                 }
+            } else {
+                for kw in ["then", "else"] {
+                    if self.value(kw).is_some() {
+                        self.warn(
+                            "inert-keyword",
+                            kw,
+                            format!("{kw} has no effect, without if"),
+                        );
+                    }
+                }
             }
         }
 
@@ -539,6 +1823,16 @@ impl ObjCompiler<'_, '_, '_, '_, '_, '_> {
                     .get(encoding.as_str())
                     .or_else(|| DECODERS.get(encoding.as_str()))
                     .cloned();
+                if s.content_encoding.is_none() {
+                    self.warn(
+                        "unknown-content-encoding",
+                        "contentEncoding",
+                        format!(
+                            "contentEncoding {} is unknown, so it is not asserted",
+                            quote(encoding)
+                        ),
+                    );
+                }
             }
 
             if let Some(Value::String(media_type)) = self.value("contentMediaType") {
@@ -548,6 +1842,16 @@ impl ObjCompiler<'_, '_, '_, '_, '_, '_> {
                     .get(media_type.as_str())
                     .or_else(|| MEDIA_TYPES.get(media_type.as_str()))
                     .cloned();
+                if s.content_media_type.is_none() {
+                    self.warn(
+                        "unknown-content-media-type",
+                        "contentMediaType",
+                        format!(
+                            "contentMediaType {} is unknown, so it is not asserted",
+                            quote(media_type)
+                        ),
+                    );
+                }
             }
         }
 
@@ -564,6 +1868,16 @@ impl ObjCompiler<'_, '_, '_, '_, '_, '_> {
             if s.contains.is_some() {
                 s.max_contains = self.usize("maxContains");
                 s.min_contains = self.usize("minContains");
+            } else {
+                for kw in ["minContains", "maxContains"] {
+                    if self.value(kw).is_some() {
+                        self.warn(
+                            "inert-keyword",
+                            kw,
+                            format!("{kw} has no effect, without contains"),
+                        );
+                    }
+                }
             }
 
             if let Some(Value::Object(dep_req)) = self.value("dependentRequired") {
@@ -638,7 +1952,7 @@ impl ObjCompiler<'_, '_, '_, '_, '_, '_> {
     }
 
     fn enqueue_prop(&mut self, pname: &'static str) -> Option<SchemaIndex> {
-        if self.obj.contains_key(pname) {
+        if self.value(pname).is_some() {
             let ptr = self.up.ptr.append(pname);
             Some(self.enqueue_schema(ptr))
         } else {
@@ -647,7 +1961,7 @@ impl ObjCompiler<'_, '_, '_, '_, '_, '_> {
     }
 
     fn enqueue_arr(&mut self, pname: &'static str) -> Vec<SchemaIndex> {
-        if let Some(Value::Array(arr)) = self.obj.get(pname) {
+        if let Some(Value::Array(arr)) = self.value(pname) {
             (0..arr.len())
                 .map(|i| {
                     let ptr = self.up.ptr.append2(pname, &i.to_string());
@@ -664,7 +1978,7 @@ impl ObjCompiler<'_, '_, '_, '_, '_, '_> {
         T: Default,
         T: FromIterator<(String, SchemaIndex)>,
     {
-        if let Some(Value::Object(obj)) = self.obj.get(pname) {
+        if let Some(Value::Object(obj)) = self.value(pname) {
             obj.keys()
                 .map(|k| {
                     let ptr = self.up.ptr.append2(pname, k);
@@ -677,22 +1991,35 @@ impl ObjCompiler<'_, '_, '_, '_, '_, '_> {
     }
 
     fn enqueue_ref(&mut self, pname: &str) -> Result<Option<SchemaIndex>, CompileError> {
-        let Some(Value::String(ref_)) = self.obj.get(pname) else {
+        let Some(Value::String(ref_)) = self.value(pname) else {
             return Ok(None);
         };
+        let ref_loc = self.up.format(pname);
+        let add_ref_loc = |mut e: CompileError| {
+            if let Some(list) = e.referenced_from_mut() {
+                list.push(ref_loc.clone());
+            }
+            e
+        };
         let base_url = self.root.base_url(&self.up.ptr);
         let abs_ref = UrlFrag::join(base_url, ref_)?;
-        if let Some(resolved_ref) = self.root.resolve(&abs_ref)? {
+        let up = if let Some(resolved_ref) = self.root.resolve(&abs_ref).map_err(add_ref_loc)? {
             // local ref
-            return Ok(Some(self.enqueue_schema(resolved_ref.ptr)));
-        }
-        // remote ref
-        let up = self.queue.resolve_anchor(abs_ref, &self.c.roots)?;
-        Ok(Some(self.queue.enqueue_schema(self.schemas, up)))
+            UrlPtr {
+                url: self.up.url.clone(),
+                ptr: resolved_ref.ptr,
+            }
+        } else {
+            // remote ref
+            self.queue
+                .resolve_anchor(abs_ref, &self.c.roots)
+                .map_err(add_ref_loc)?
+        };
+        Ok(Some(self.queue.enqueue_ref(self.schemas, up, ref_loc)))
     }
 
     fn enquue_additional(&mut self, pname: &'static str) -> Option<Additional> {
-        if let Some(Value::Bool(b)) = self.obj.get(pname) {
+        if let Some(Value::Bool(b)) = self.value(pname) {
             Some(Additional::Bool(*b))
         } else {
             self.enqueue_prop(pname).map(Additional::SchemaRef)
@@ -700,6 +2027,102 @@ impl ObjCompiler<'_, '_, '_, '_, '_, '_> {
     }
 }
 
+// diagnostic helpers
+fn draft_name(version: usize) -> String {
+    match version {
+        2019 => "2019-09".to_owned(),
+        2020 => "2020-12".to_owned(),
+        _ => format!("draft-{version:02}"),
+    }
+}
+
+impl ObjCompiler<'_, '_, '_, '_, '_, '_> {
+    fn warn(&mut self, code: &'static str, kw: &str, message: String) {
+        let loc = self.up.format(kw);
+        self.queue.diagnostics.warn(code, loc, message);
+    }
+}
+
+// lints
+impl ObjCompiler<'_, '_, '_, '_, '_, '_> {
+    // property names used by dependency keywords, but neither listed in
+    // `properties` nor matched by `patternProperties`. names defined
+    // elsewhere, for example in `allOf`, are not known here, so this is
+    // checked only if schema has any of them.
+    fn lint_dependencies(&mut self, s: &Schema) {
+        if s.properties.is_empty() && s.pattern_properties.is_empty() {
+            return;
+        }
+        let known = |name: &str| {
+            s.properties.contains_key(name)
+                || s.pattern_properties.iter().any(|(re, _)| re.is_match(name))
+        };
+        let mut unknown = vec![];
+        for (pname, dep) in &s.dependencies {
+            unknown.push(("dependencies", pname));
+            if let Dependency::Props(required) = dep {
+                unknown.extend(required.iter().map(|p| ("dependencies", p)));
+            }
+        }
+        for (pname, required) in &s.dependent_required {
+            unknown.push(("dependentRequired", pname));
+            unknown.extend(required.iter().map(|p| ("dependentRequired", p)));
+        }
+        for (pname, _) in &s.dependent_schemas {
+            unknown.push(("dependentSchemas", pname));
+        }
+        let mut seen = HashSet::new();
+        unknown.retain(|&(kw, pname)| !known(pname) && seen.insert((kw, pname)));
+        for (kw, pname) in unknown {
+            self.warn(
+                "unknown-dependency-property",
+                kw,
+                format!(
+                    "property {} is neither in properties nor matched by patternProperties",
+                    quote(pname)
+                ),
+            );
+        }
+    }
+}
+
+// regex helpers
+impl ObjCompiler<'_, '_, '_, '_, '_, '_> {
+    fn regex(&self, kw: &str, pattern: &str) -> Result<Regex, CompileError> {
+        let limits = &self.c.regex_limits;
+        let invalid = |src: Box<dyn Error>| CompileError::InvalidRegex {
+            url: self.up.format(kw),
+            regex: pattern.to_owned(),
+            src,
+        };
+        if let Some(max) = limits.max_pattern_len {
+            if pattern.len() > max {
+                let len = pattern.len();
+                return Err(invalid(
+                    format!("pattern length {len} exceeds limit {max}").into(),
+                ));
+            }
+        }
+        let mut ecma = ecma::convert(pattern).map_err(invalid)?;
+        if self.c.anchor_patterns {
+            ecma = format!("^(?:{ecma})$").into();
+        }
+        RegexBuilder::new(ecma.as_ref())
+            .size_limit(limits.size_limit)
+            .dfa_size_limit(limits.dfa_size_limit)
+            .build()
+            .map_err(|e| invalid(e.into()))
+    }
+}
+
+// whether `pattern` can only match whole string
+fn is_anchored(pattern: &str) -> bool {
+    let unescaped_end = pattern
+        .strip_suffix('$')
+        .is_some_and(|p| p.chars().rev().take_while(|c| *c == '\\').count() % 2 == 0);
+    pattern.starts_with('^') && unescaped_end
+}
+
 // query helpers
 impl<'v> ObjCompiler<'_, 'v, '_, '_, '_, '_> {
     fn draft_version(&self) -> usize {
@@ -711,15 +2134,18 @@ impl<'v> ObjCompiler<'_, 'v, '_, '_, '_, '_> {
     }
 
     fn value(&self, pname: &str) -> Option<&'v Value> {
+        if self.c.disabled_keywords.contains(pname) {
+            return None;
+        }
         self.obj.get(pname)
     }
 
     fn bool(&self, pname: &str) -> bool {
-        matches!(self.obj.get(pname), Some(Value::Bool(true)))
+        matches!(self.value(pname), Some(Value::Bool(true)))
     }
 
     fn usize(&self, pname: &str) -> Option<usize> {
-        let Some(Value::Number(n)) = self.obj.get(pname) else {
+        let Some(Value::Number(n)) = self.value(pname) else {
             return None;
         };
         if n.is_u64() {
@@ -732,7 +2158,7 @@ impl<'v> ObjCompiler<'_, 'v, '_, '_, '_, '_> {
     }
 
     fn num(&self, pname: &str) -> Option<Number> {
-        if let Some(Value::Number(n)) = self.obj.get(pname) {
+        if let Some(Value::Number(n)) = self.value(pname) {
             Some(n.clone())
         } else {
             None
@@ -740,7 +2166,7 @@ impl<'v> ObjCompiler<'_, 'v, '_, '_, '_, '_> {
     }
 
     fn bool_schema(&self, pname: &str, b: bool) -> bool {
-        if let Some(Value::Bool(v)) = self.obj.get(pname) {
+        if let Some(Value::Bool(v)) = self.value(pname) {
             return *v == b;
         }
         false
@@ -754,10 +2180,41 @@ pub enum CompileError {
     ParseUrlError { url: String, src: Box<dyn Error> },
 
     /// Failed loading `url`.
-    LoadUrlError { url: String, src: Box<dyn Error> },
+    LoadUrlError {
+        url: String,
+        src: Box<dyn Error>,
+        /// locations of reference keywords leading to `url`,
+        /// up to the compile entry point.
+        referenced_from: Vec<String>,
+    },
 
     /// no [`UrlLoader`] registered for the `url`
-    UnsupportedUrlScheme { url: String },
+    UnsupportedUrlScheme {
+        url: String,
+        /// locations of reference keywords leading to `url`,
+        /// up to the compile entry point.
+        referenced_from: Vec<String>,
+    },
+
+    /// Document at `url` from `conflicting` origin differs from the one
+    /// already known from `existing` origin.
+    /// see [`Compiler::add_resource`]
+    ConflictingResource {
+        url: String,
+        existing: Origin,
+        conflicting: Origin,
+    },
+
+    /// Document at `url` has `size` bytes, exceeding `limit` set by
+    /// [`Compiler::set_max_document_size`].
+    DocumentTooLarge {
+        url: String,
+        size: usize,
+        limit: usize,
+        /// locations of reference keywords leading to `url`,
+        /// up to the compile entry point.
+        referenced_from: Vec<String>,
+    },
 
     /// Error in parsing `$schema` url.
     InvalidMetaSchemaUrl { url: String, src: Box<dyn Error> },
@@ -768,6 +2225,17 @@ pub enum CompileError {
     /// Cycle in resolving `$schema` in `url`.
     MetaSchemaCycle { url: String },
 
+    /// Metaschemas resolved through `$schema` form a `chain` longer than
+    /// `limit` set by [`Compiler::set_max_metaschema_depth`].
+    /// `chain` lists the metaschema urls, in the order resolved.
+    MetaSchemaChainTooLong { chain: Vec<String>, limit: usize },
+
+    /// Resolving `$schema` along `chain` loads more than `limit` distinct
+    /// metaschemas in one compilation, set by
+    /// [`Compiler::set_max_metaschema_documents`].
+    /// `chain` lists the metaschema urls, in the order resolved.
+    TooManyMetaSchemas { chain: Vec<String>, limit: usize },
+
     /// `url` is not valid against metaschema.
     ValidationError {
         url: String,
@@ -796,14 +2264,39 @@ pub enum CompileError {
         ptr2: String,
     },
 
+    /// Anchor `anchor` in `url` at `ptr` does not match anchor syntax
+    /// of its draft.
+    InvalidAnchor {
+        url: String,
+        ptr: String,
+        anchor: String,
+    },
+
     /// Not a valid json pointer.
-    InvalidJsonPointer(String),
+    InvalidJsonPointer {
+        loc: String,
+        /// zero-based character position in fragment of `loc`,
+        /// where json-pointer is invalid.
+        position: usize,
+        reason: &'static str,
+    },
 
-    /// JsonPointer evaluated to nothing.
-    JsonPointerNotFound(String),
+    /// JsonPointer `loc` evaluated to nothing.
+    JsonPointerNotFound {
+        loc: String,
+        /// locations of reference keywords leading to `loc`,
+        /// up to the compile entry point.
+        referenced_from: Vec<String>,
+    },
 
     /// anchor in `reference` not found in `url`.
-    AnchorNotFound { url: String, reference: String },
+    AnchorNotFound {
+        url: String,
+        reference: String,
+        /// locations of reference keywords leading to `reference`,
+        /// up to the compile entry point.
+        referenced_from: Vec<String>,
+    },
 
     /// Unsupported vocabulary `vocabulary` in `url`.
     UnsupportedVocabulary { url: String, vocabulary: String },
@@ -815,17 +2308,76 @@ pub enum CompileError {
         src: Box<dyn Error>,
     },
 
+    /// Value of custom [`Keyword`] at `url` failed to compile.
+    InvalidKeyword { url: String, src: Box<dyn Error> },
+
+    /// Value at `loc` is neither object nor boolean.
+    NotASchema { loc: String, found_type: Type },
+
+    /// Policy meant for format `policy_format` is configured for `format`.
+    /// see [`Compiler::configure_format`].
+    UnsupportedFormatPolicy {
+        format: String,
+        policy_format: &'static str,
+    },
+
+    /// `loc` points into value of keyword `parent_keyword`, at position
+    /// which does not hold schema, like `#/properties/name/type`, rather
+    /// than to a schema like `#/properties/name`.
+    NotASchemaLocation { loc: String, parent_keyword: String },
+
+    /// Diagnostics denied by [`Compiler::deny`] were reported.
+    Diagnostics(Vec<Diagnostic>),
+
     /// Encountered bug in compiler implementation. Please report
     /// this as an issue for this crate.
     Bug(Box<dyn Error>),
 }
 
+impl CompileError {
+    fn referenced_from_mut(&mut self) -> Option<&mut Vec<String>> {
+        match self {
+            Self::LoadUrlError {
+                referenced_from, ..
+            }
+            | Self::JsonPointerNotFound {
+                referenced_from, ..
+            }
+            | Self::AnchorNotFound {
+                referenced_from, ..
+            }
+            | Self::UnsupportedUrlScheme {
+                referenced_from, ..
+            }
+            | Self::DocumentTooLarge {
+                referenced_from, ..
+            } => Some(referenced_from),
+            _ => None,
+        }
+    }
+}
+
+fn write_referenced_from(
+    f: &mut std::fmt::Formatter<'_>,
+    referenced_from: &[String],
+) -> std::fmt::Result {
+    if referenced_from.is_empty() {
+        return Ok(());
+    }
+    write!(
+        f,
+        ", referenced from {}",
+        join_iter(referenced_from, " <- ")
+    )
+}
+
 impl Error for CompileError {
     fn source(&self) -> Option<&(dyn Error + 'static)> {
         match self {
             Self::ParseUrlError { src, .. } => Some(src.as_ref()),
             Self::LoadUrlError { src, .. } => Some(src.as_ref()),
             Self::InvalidMetaSchemaUrl { src, .. } => Some(src.as_ref()),
+            Self::InvalidKeyword { src, .. } => Some(src.as_ref()),
             Self::ValidationError { src, .. } => Some(src),
             Self::Bug(src) => Some(src.as_ref()),
             _ => None,
@@ -843,14 +2395,45 @@ impl Display for CompileError {
                     write!(f, "error parsing {url}")
                 }
             }
-            Self::LoadUrlError { url, src } => {
+            Self::LoadUrlError {
+                url,
+                src,
+                referenced_from,
+            } => {
                 if f.alternate() {
-                    write!(f, "error loading {url}: {src}")
+                    write!(f, "error loading {url}: {src}")?;
                 } else {
-                    write!(f, "error loading {url}")
+                    write!(f, "error loading {url}")?;
                 }
+                write_referenced_from(f, referenced_from)
+            }
+            Self::UnsupportedUrlScheme {
+                url,
+                referenced_from,
+            } => {
+                write!(f, "unsupported scheme in {url}")?;
+                write_referenced_from(f, referenced_from)
+            }
+            Self::ConflictingResource {
+                url,
+                existing,
+                conflicting,
+            } => write!(
+                f,
+                "conflicting documents for {url}: {existing}, and {conflicting}"
+            ),
+            Self::DocumentTooLarge {
+                url,
+                size,
+                limit,
+                referenced_from,
+            } => {
+                write!(
+                    f,
+                    "{url} has {size} bytes, exceeding limit of {limit} bytes"
+                )?;
+                write_referenced_from(f, referenced_from)
             }
-            Self::UnsupportedUrlScheme { url } => write!(f, "unsupported scheme in {url}"),
             Self::InvalidMetaSchemaUrl { url, src } => {
                 if f.alternate() {
                     write!(f, "invalid $schema in {url}: {src}")
@@ -862,6 +2445,20 @@ impl Display for CompileError {
             Self::MetaSchemaCycle { url } => {
                 write!(f, "cycle in resolving $schema in {url}")
             }
+            Self::MetaSchemaChainTooLong { chain, limit } => {
+                write!(
+                    f,
+                    "metaschema chain {} exceeds limit {limit}",
+                    chain.join(" -> ")
+                )
+            }
+            Self::TooManyMetaSchemas { chain, limit } => {
+                write!(
+                    f,
+                    "metaschemas loaded exceed limit {limit}, in resolving {}",
+                    chain.join(" -> ")
+                )
+            }
             Self::ValidationError { url, src } => {
                 if f.alternate() {
                     write!(f, "{url} is not valid against metaschema: {src}")
@@ -888,13 +2485,34 @@ impl Display for CompileError {
                     "duplicate anchor {anchor:?} in {url} at {ptr1:?} and {ptr2:?}"
                 )
             }
-            Self::InvalidJsonPointer(loc) => write!(f, "invalid json-pointer {loc}"),
-            Self::JsonPointerNotFound(loc) => write!(f, "json-pointer in {loc} not found"),
-            Self::AnchorNotFound { url, reference } => {
+            Self::InvalidAnchor { url, ptr, anchor } => {
+                write!(f, "invalid anchor {anchor:?} in {url} at {ptr:?}")
+            }
+            Self::InvalidJsonPointer {
+                loc,
+                position,
+                reason,
+            } => write!(
+                f,
+                "invalid json-pointer {loc}: {reason} at position {position}"
+            ),
+            Self::JsonPointerNotFound {
+                loc,
+                referenced_from,
+            } => {
+                write!(f, "json-pointer in {loc} not found")?;
+                write_referenced_from(f, referenced_from)
+            }
+            Self::AnchorNotFound {
+                url,
+                reference,
+                referenced_from,
+            } => {
                 write!(
                     f,
                     "anchor in reference {reference} is not found in schema {url}"
-                )
+                )?;
+                write_referenced_from(f, referenced_from)
             }
             Self::UnsupportedVocabulary { url, vocabulary } => {
                 write!(f, "unsupported vocabulary {vocabulary} in {url}")
@@ -906,6 +2524,37 @@ impl Display for CompileError {
                     write!(f, "invalid regex {} at {url}", quote(regex))
                 }
             }
+            Self::InvalidKeyword { url, src } => {
+                if f.alternate() {
+                    write!(f, "invalid keyword at {url}: {src}")
+                } else {
+                    write!(f, "invalid keyword at {url}")
+                }
+            }
+            Self::NotASchema { loc, found_type } => {
+                write!(f, "{loc} is not a schema, found {found_type}")
+            }
+            Self::UnsupportedFormatPolicy {
+                format,
+                policy_format,
+            } => {
+                write!(
+                    f,
+                    "policy for format {policy_format:?} cannot be configured for format {format:?}"
+                )
+            }
+            Self::NotASchemaLocation {
+                loc,
+                parent_keyword,
+            } => {
+                write!(
+                    f,
+                    "{loc} is not a schema location: value of keyword {parent_keyword} holds no schema there"
+                )
+            }
+            Self::Diagnostics(list) => {
+                write!(f, "denied diagnostics reported: {}", join_iter(list, "; "))
+            }
             Self::Bug(src) => {
                 write!(
                     f,
@@ -934,19 +2583,70 @@ fn to_strings(v: &Value) -> Vec<String> {
     }
 }
 
+// tells whether `doc` can be compiled without other documents, other
+// than standard metaschemas. values of annotations are also checked,
+// which is conservative.
+#[cfg(feature = "parallel")]
+fn is_self_contained(doc: &Value) -> bool {
+    match doc {
+        Value::Object(obj) => obj.iter().all(|(k, v)| match (k.as_str(), v) {
+            ("$ref" | "$dynamicRef" | "$recursiveRef", Value::String(s)) => s.starts_with('#'),
+            ("$schema", Value::String(s)) => Draft::from_url(s).is_some(),
+            _ => is_self_contained(v),
+        }),
+        Value::Array(arr) => arr.iter().all(is_self_contained),
+        _ => true,
+    }
+}
+
+pub(crate) struct QueueItem {
+    pub(crate) up: UrlPtr,
+    /// queue index of the schema, whose compilation enqueued this.
+    parent: Option<usize>,
+    /// location of the reference keyword, which enqueued this.
+    ref_loc: Option<String>,
+}
+
 pub(crate) struct Queue {
-    pub(crate) schemas: Vec<UrlPtr>,
+    pub(crate) schemas: Vec<QueueItem>,
+    /// queue index of the schema being compiled.
+    pub(crate) current: Option<usize>,
     pub(crate) roots: HashMap<Url, Root>,
+    pub(crate) diagnostics: Diagnostics,
 }
 
 impl Queue {
     fn new() -> Self {
         Self {
             schemas: vec![],
+            current: None,
             roots: HashMap::new(),
+            diagnostics: Diagnostics::default(),
         }
     }
 
+    /// Adds to `e` the locations of reference keywords, through which
+    /// schema at queue index `i` is reached from compile entry point.
+    fn referenced_from(&self, mut e: CompileError, mut i: usize) -> CompileError {
+        let Some(list) = e.referenced_from_mut() else {
+            return e;
+        };
+        loop {
+            let item = &self.schemas[i];
+            if let Some(ref_loc) = &item.ref_loc {
+                list.push(ref_loc.clone());
+            }
+            match item.parent {
+                Some(parent) => i = parent,
+                None => {
+                    list.push(item.up.to_string());
+                    break;
+                }
+            }
+        }
+        e
+    }
+
     pub(crate) fn resolve_anchor(
         &mut self,
         uf: UrlFrag,
@@ -973,13 +2673,26 @@ impl Queue {
             // already got compiled
             return sch.idx;
         }
-        if let Some(qindex) = self.schemas.iter().position(|e| *e == up) {
+        if let Some(qindex) = self.schemas.iter().position(|e| e.up == up) {
             // already queued for compilation
             return SchemaIndex(schemas.size() + qindex);
         }
 
         // new compilation request
-        self.schemas.push(up);
+        self.schemas.push(QueueItem {
+            up,
+            parent: self.current,
+            ref_loc: None,
+        });
         SchemaIndex(schemas.size() + self.schemas.len() - 1)
     }
+
+    fn enqueue_ref(&mut self, schemas: &Schemas, up: UrlPtr, ref_loc: String) -> SchemaIndex {
+        let len = self.schemas.len();
+        let sch = self.enqueue_schema(schemas, up);
+        if self.schemas.len() > len {
+            self.schemas[len].ref_loc = Some(ref_loc);
+        }
+        sch
+    }
 }
@@ -34,6 +34,15 @@ fn decode_base64(s: &str) -> Result<Vec<u8>, Box<dyn Error>> {
     Ok(base64::engine::general_purpose::STANDARD.decode(s)?)
 }
 
+/// size of `s` after decoding, if it can be computed without decoding.
+pub(crate) fn decoded_size(s: &str, decoder: &Decoder) -> Option<usize> {
+    if decoder.name != "base64" {
+        return None;
+    }
+    let n = s.bytes().filter(|b| *b != b'=').count();
+    Some(n / 4 * 3 + (n % 4).saturating_sub(1))
+}
+
 // mediatypes --
 
 /// Defines Mediatype for `contentMediaType`.
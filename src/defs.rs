@@ -0,0 +1,221 @@
+use std::{collections::HashSet, error::Error, fmt::Display};
+
+use serde_json::{Map, Value};
+
+use crate::{
+    draft::{latest, Draft, POS_ITEM, POS_PROP, POS_SELF},
+    url::Url,
+    util::{escape, split},
+};
+
+/// Error returned by [`normalize_defs`], when same name in `definitions`
+/// and `$defs` has different schemas.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DefsCollisionError {
+    /// json-pointer of each colliding entry in `definitions`.
+    pub collisions: Vec<String>,
+}
+
+impl Error for DefsCollisionError {}
+
+impl Display for DefsCollisionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "definitions collide with different schemas in $defs: {}",
+            self.collisions.join(", ")
+        )
+    }
+}
+
+/**
+Merges `definitions` into `$defs`, and rewrites references of the form
+`#/definitions/...` to `#/$defs/...`, to migrate `schema` to draft 2019-09
+or later.
+
+This is done for the document and for each embedded resource, i.e.
+subschema with `$id`. Entries present in both with equal schemas are
+merged. If any entry differs, [`DefsCollisionError`] is returned and
+`schema` is left unchanged.
+
+References resolving to `definitions` of a resource within `schema`,
+including those given as absolute url, are rewritten. References to
+`definitions` of other documents are not rewritten. Values of `enum`,
+`const`, `default` and `examples` keywords are left as they are.
+
+```rust
+# use boon::*;
+# use serde_json::json;
+let mut schema = json!({
+    "properties": { "a": { "$ref": "#/definitions/a" } },
+    "definitions": { "a": { "$ref": "#/$defs/b" } },
+    "$defs": { "b": { "type": "string" } }
+});
+normalize_defs(&mut schema)?;
+assert_eq!(schema, json!({
+    "properties": { "a": { "$ref": "#/$defs/a" } },
+    "$defs": {
+        "a": { "$ref": "#/$defs/b" },
+        "b": { "type": "string" }
+    }
+}));
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+*/
+pub fn normalize_defs(schema: &mut Value) -> Result<(), DefsCollisionError> {
+    let mut normalized = schema.clone();
+    let base = Url::parse(PLACEHOLDER_BASE).expect("placeholder base must be valid url");
+    let mut n = Normalizer {
+        draft: latest(),
+        ids: HashSet::from([base.clone()]),
+        collisions: vec![],
+    };
+    n.collect_ids(&normalized, &base);
+    let root_id = normalized
+        .as_object()
+        .and_then(|obj| resource_id(obj, &base));
+    let base = root_id.unwrap_or(base);
+    n.normalize_resource(&mut normalized, String::new(), &base);
+    if !n.collisions.is_empty() {
+        return Err(DefsCollisionError {
+            collisions: n.collisions,
+        });
+    }
+    *schema = normalized;
+    Ok(())
+}
+
+// base of document without `$id`, to resolve relative references.
+const PLACEHOLDER_BASE: &str = "http://normalize-defs.invalid/";
+
+struct Normalizer {
+    draft: &'static Draft, // latest, whose subschema locations cover earlier drafts
+    ids: HashSet<Url>,     // resources within document
+    collisions: Vec<String>,
+}
+
+impl Normalizer {
+    fn collect_ids(&mut self, v: &Value, base: &Url) {
+        match v {
+            Value::Object(obj) => {
+                let mut base = base;
+                let tmp;
+                if let Some(url) = resource_id(obj, base) {
+                    tmp = url;
+                    base = &tmp;
+                    self.ids.insert(base.clone());
+                }
+                for (kw, v) in obj {
+                    if !is_value_keyword(kw) {
+                        self.collect_ids(v, base);
+                    }
+                }
+            }
+            Value::Array(arr) => arr.iter().for_each(|item| self.collect_ids(item, base)),
+            _ => {}
+        }
+    }
+
+    fn normalize_resource(&mut self, v: &mut Value, ptr: String, base: &Url) {
+        if let Value::Object(obj) = v {
+            if matches!(obj.get("definitions"), Some(Value::Object(_))) {
+                let definitions = obj.remove("definitions");
+                let defs = obj
+                    .entry("$defs")
+                    .or_insert_with(|| Value::Object(Map::new()));
+                match (definitions, defs) {
+                    (Some(Value::Object(definitions)), Value::Object(defs)) => {
+                        for (name, sch) in definitions {
+                            match defs.get(&name) {
+                                Some(existing) if existing != &sch => {
+                                    let ptr = format!("{ptr}/definitions/{}", escape(&name));
+                                    self.collisions.push(ptr);
+                                }
+                                _ => _ = defs.insert(name, sch),
+                            }
+                        }
+                    }
+                    _ => self.collisions.push(format!("{ptr}/$defs")),
+                }
+            }
+        }
+        self.rewrite_refs(v, &ptr, base, true);
+    }
+
+    // rewrites refs within resource, normalizing embedded resources.
+    //
+    // `v` is a subschema, or value of unknown keyword which may contain
+    // subschemas.
+    fn rewrite_refs(&mut self, v: &mut Value, ptr: &str, base: &Url, root: bool) {
+        match v {
+            Value::Object(obj) => {
+                if !root {
+                    if let Some(url) = resource_id(obj, base) {
+                        self.normalize_resource(v, ptr.to_owned(), &url);
+                        return;
+                    }
+                }
+                for (kw, v) in obj.iter_mut() {
+                    let ptr = format!("{ptr}/{}", escape(kw));
+                    if let ("$ref", Value::String(ref_)) = (kw.as_str(), &mut *v) {
+                        self.rewrite_ref(ref_, base);
+                        continue;
+                    }
+                    match (self.draft.subschema_pos(kw), v) {
+                        (Some(pos), Value::Array(arr)) if pos & POS_ITEM != 0 => {
+                            for (i, item) in arr.iter_mut().enumerate() {
+                                self.rewrite_refs(item, &format!("{ptr}/{i}"), base, false);
+                            }
+                        }
+                        (Some(pos), Value::Object(obj)) if pos & POS_PROP != 0 => {
+                            for (pname, sch) in obj.iter_mut() {
+                                let ptr = format!("{ptr}/{}", escape(pname));
+                                self.rewrite_refs(sch, &ptr, base, false);
+                            }
+                        }
+                        (Some(pos), v) if pos & POS_SELF != 0 => {
+                            self.rewrite_refs(v, &ptr, base, false)
+                        }
+                        (Some(_), _) => {}
+                        (None, _) if is_value_keyword(kw) => {}
+                        (None, v) => self.rewrite_refs(v, &ptr, base, false),
+                    }
+                }
+            }
+            Value::Array(arr) => {
+                for (i, item) in arr.iter_mut().enumerate() {
+                    self.rewrite_refs(item, &format!("{ptr}/{i}"), base, false);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn rewrite_ref(&self, ref_: &mut String, base: &Url) {
+        let (url, frag) = split(ref_);
+        let Some(rest) = frag.strip_prefix("/definitions/") else {
+            return;
+        };
+        let same_doc = url.is_empty() || base.join(url).is_ok_and(|url| self.ids.contains(&url));
+        if same_doc {
+            *ref_ = format!("{url}#/$defs/{rest}");
+        }
+    }
+}
+
+// keywords whose values are instance data, rather than schemas.
+fn is_value_keyword(kw: &str) -> bool {
+    matches!(kw, "enum" | "const" | "default" | "examples")
+}
+
+// url of resource, if `obj` has `$id` other than a plain fragment.
+fn resource_id(obj: &Map<String, Value>, base: &Url) -> Option<Url> {
+    let Some(Value::String(id)) = obj.get("$id") else {
+        return None;
+    };
+    let (url, _) = split(id);
+    if url.is_empty() {
+        return None;
+    }
+    base.join(url).ok()
+}
@@ -0,0 +1,110 @@
+use std::fmt::Display;
+
+/// Severity of a [`Diagnostic`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Warning,
+    /// Warning promoted using [`crate::Compiler::deny`].
+    Error,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/**
+Issue found in schema during compilation, which does not prevent
+it from being compiled.
+
+Diagnostics are reported in the order schemas are compiled. They can
+be retrieved using [`crate::Compiler::take_diagnostics`] or
+[`crate::Compiler::compile_with_diagnostics`].
+
+# Codes
+
+- `unknown-format`: `format` is not known, while format assertions are enabled
+- `unknown-content-encoding`, `unknown-content-media-type`: `contentEncoding`
+  or `contentMediaType` is not known, while content assertions are enabled
+- `ref-siblings-ignored`: assertions and applicators next to `$ref` are
+  ignored in draft-07 and earlier, but evaluated since 2019-09. This
+  includes keywords of enabled extensions and registered vocabularies
+- `non-finite-bound`: numeric bound is not representable as finite `f64`,
+  which is possible with `serde_json/arbitrary_precision`
+- `empty-enum`: `enum` has no values, which metaschema allows since 2019-09
+- `unanchored-pattern`: `pattern` without `^` and `$`, next to `minLength`,
+  `maxLength` or `format`, which suggests that full match was expected.
+  see [`crate::Compiler::enable_anchored_patterns`]
+- `inert-keyword`: keyword has no effect, like `then` or `else` without `if`,
+  `additionalItems` without array-form `items`, `minContains` or `maxContains`
+  without `contains`, or keyword introduced in later draft. `definitions`
+  and `$defs` are not reported in any draft, since refs into both resolve
+- `unknown-dependency-property`: property name used by `dependencies`,
+  `dependentRequired` or `dependentSchemas` is neither in `properties` nor
+  matched by `patternProperties`, which suggests it was renamed. Checked
+  only if schema has `properties` or `patternProperties`. Schemas allowing
+  additional properties may do this legitimately, see [`crate::Compiler::allow`]
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// Stable identifier of the kind of issue.
+    pub code: &'static str,
+    /// The absolute location of the keyword causing this diagnostic.
+    pub schema_location: String,
+    pub message: String,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}[{}] at {}: {}",
+            self.severity, self.code, self.schema_location, self.message
+        )
+    }
+}
+
+// collects diagnostics of single compilation.
+#[derive(Default)]
+pub(crate) struct Diagnostics {
+    list: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    pub(crate) fn warn(&mut self, code: &'static str, schema_location: String, message: String) {
+        self.list.push(Diagnostic {
+            severity: Severity::Warning,
+            code,
+            schema_location,
+            message,
+        });
+    }
+
+    /// promotes diagnostics with given `codes` to errors.
+    /// returns the promoted diagnostics, if any.
+    pub(crate) fn deny<T: AsRef<str>>(&mut self, codes: &[T]) -> Vec<Diagnostic> {
+        let mut denied = vec![];
+        for d in &mut self.list {
+            if codes.iter().any(|c| c.as_ref() == d.code) {
+                d.severity = Severity::Error;
+                denied.push(d.clone());
+            }
+        }
+        denied
+    }
+
+    /// drops diagnostics with given `codes`.
+    pub(crate) fn allow<T: AsRef<str>>(&mut self, codes: &[T]) {
+        self.list
+            .retain(|d| !codes.iter().any(|c| c.as_ref() == d.code));
+    }
+
+    pub(crate) fn into_vec(self) -> Vec<Diagnostic> {
+        self.list
+    }
+}
@@ -0,0 +1,323 @@
+/*!
+Differential testing against another validator implementation.
+
+[`DiffTest`] validates each instance of a corpus with boon and with an
+[`OtherValidator`], and reports instances on which they disagree. This
+helps to find divergences in behavior of drafts, before switching from
+one validator to another.
+
+```rust,no_run
+# use boon::*;
+# use boon::difftest::*;
+# use serde_json::json;
+let mut compiler = Compiler::new();
+let test = DiffTest::new(&mut compiler, "schema.json", json!({"type": "integer"}))?;
+let corpus = load_corpus("tests/corpus")?;
+let other = CommandValidator::new("node").arg("validate.js");
+let report = test.run(&other, &corpus);
+assert!(report.is_clean(), "{report}");
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+*/
+
+use std::{
+    error::Error,
+    ffi::{OsStr, OsString},
+    fmt::Display,
+    fs,
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use serde_json::Value;
+
+use crate::{CompileError, Compiler, SchemaIndex, Schemas};
+
+/// Validator implementation, which boon is compared with.
+pub trait OtherValidator {
+    /**
+    Tells whether `instance` is valid against `schema`, the document
+    given to [`DiffTest::new`].
+
+    Error is reported in [`DiffReport::failures`], and the instance
+    is not compared.
+    */
+    fn validate(&self, schema: &Value, instance: &Value) -> Result<bool, Box<dyn Error>>;
+}
+
+impl<F> OtherValidator for F
+where
+    F: Fn(&Value, &Value) -> Result<bool, Box<dyn Error>>,
+{
+    fn validate(&self, schema: &Value, instance: &Value) -> Result<bool, Box<dyn Error>> {
+        self(schema, instance)
+    }
+}
+
+/**
+[`OtherValidator`] backed by a command, run once for each instance.
+
+The command reads schema and instance from stdin, as compact json
+on first and second line. It exits with status `0` if the instance
+is valid, and `1` if it is invalid. Any other outcome is an error,
+which includes what the command printed to stderr.
+*/
+#[derive(Debug, Clone)]
+pub struct CommandValidator {
+    program: OsString,
+    args: Vec<OsString>,
+}
+
+impl CommandValidator {
+    /// Runs `program`, looked up in `PATH` if it is not a path, with no
+    /// arguments. Use [`CommandValidator::arg`] to add arguments.
+    pub fn new(program: impl AsRef<OsStr>) -> Self {
+        Self {
+            program: program.as_ref().to_owned(),
+            args: vec![],
+        }
+    }
+
+    /// Adds argument to pass to the command.
+    pub fn arg(mut self, arg: impl AsRef<OsStr>) -> Self {
+        self.args.push(arg.as_ref().to_owned());
+        self
+    }
+}
+
+impl OtherValidator for CommandValidator {
+    fn validate(&self, schema: &Value, instance: &Value) -> Result<bool, Box<dyn Error>> {
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            let input = format!("{schema}\n{instance}\n");
+            match stdin.write_all(input.as_bytes()) {
+                // command may decide without reading all input
+                Err(e) if e.kind() != ErrorKind::BrokenPipe => return Err(e.into()),
+                _ => {}
+            }
+        }
+        let output = child.wait_with_output()?;
+        match output.status.code() {
+            Some(0) => Ok(true),
+            Some(1) => Ok(false),
+            _ => Err(format!(
+                "{:?} failed with {}: {}",
+                self.program,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )
+            .into()),
+        }
+    }
+}
+
+/// Instance of corpus, see [`load_corpus`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorpusEntry {
+    /// where the instance is loaded from, like `dir/a.json` or `dir/b.jsonl:3`.
+    pub source: String,
+    pub instance: Value,
+}
+
+/// Error returned by [`load_corpus`].
+#[derive(Debug)]
+pub struct CorpusError {
+    pub path: PathBuf,
+    pub src: Box<dyn Error>,
+}
+
+impl Error for CorpusError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(self.src.as_ref())
+    }
+}
+
+impl Display for CorpusError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if f.alternate() {
+            write!(f, "error loading corpus {:?}: {}", self.path, self.src)
+        } else {
+            write!(f, "error loading corpus {:?}", self.path)
+        }
+    }
+}
+
+/**
+Loads instances from `path`.
+
+A `.json` file holds one instance, and a `.jsonl` file holds one instance
+per non-empty line. A directory is searched recursively for such files,
+in order of their names; other files in it are ignored.
+*/
+pub fn load_corpus(path: impl AsRef<Path>) -> Result<Vec<CorpusEntry>, CorpusError> {
+    let mut corpus = vec![];
+    load_path(path.as_ref(), true, &mut corpus)?;
+    Ok(corpus)
+}
+
+fn load_path(path: &Path, given: bool, corpus: &mut Vec<CorpusEntry>) -> Result<(), CorpusError> {
+    let error = |src: Box<dyn Error>| CorpusError {
+        path: path.to_owned(),
+        src,
+    };
+    if path.is_dir() {
+        let mut paths: Vec<PathBuf> = fs::read_dir(path)
+            .and_then(|entries| entries.map(|e| e.map(|e| e.path())).collect())
+            .map_err(|e| error(e.into()))?;
+        paths.sort();
+        for path in paths {
+            load_path(&path, false, corpus)?;
+        }
+        return Ok(());
+    }
+    let jsonl = match path.extension().and_then(OsStr::to_str) {
+        Some("json") => false,
+        Some("jsonl") => true,
+        _ if given => false,
+        _ => return Ok(()),
+    };
+    let text = fs::read_to_string(path).map_err(|e| error(e.into()))?;
+    let source = path.display().to_string();
+    if !jsonl {
+        let instance = serde_json::from_str(&text).map_err(|e| error(e.into()))?;
+        corpus.push(CorpusEntry { source, instance });
+        return Ok(());
+    }
+    for (i, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let instance =
+            serde_json::from_str(line).map_err(|e| error(format!("line {}: {e}", i + 1).into()))?;
+        corpus.push(CorpusEntry {
+            source: format!("{source}:{}", i + 1),
+            instance,
+        });
+    }
+    Ok(())
+}
+
+/// Instance on which boon and [`OtherValidator`] disagree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Disagreement {
+    /// see [`CorpusEntry::source`]
+    pub source: String,
+    pub instance: Value,
+    /// absolute location of schema validated against.
+    pub schema_location: String,
+    /// whether boon found the instance valid. the other validator
+    /// found the opposite.
+    pub boon_valid: bool,
+    /// boon's error in basic output format, if instance is invalid.
+    pub boon_output: Option<Value>,
+}
+
+/// Result of [`DiffTest::run`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DiffReport {
+    /// number of instances compared.
+    pub compared: usize,
+    pub disagreements: Vec<Disagreement>,
+    /// source and error of instances, the other validator failed with.
+    pub failures: Vec<(String, String)>,
+}
+
+impl DiffReport {
+    /// Tells whether there are neither disagreements nor failures.
+    pub fn is_clean(&self) -> bool {
+        self.disagreements.is_empty() && self.failures.is_empty()
+    }
+}
+
+impl Display for DiffReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "compared {} instances: {} disagreements, {} failures",
+            self.compared,
+            self.disagreements.len(),
+            self.failures.len()
+        )?;
+        for d in &self.disagreements {
+            let (boon, other) = match d.boon_valid {
+                true => ("valid", "invalid"),
+                false => ("invalid", "valid"),
+            };
+            write!(
+                f,
+                "\n{}: boon says {boon}, other says {other} against {}",
+                d.source, d.schema_location
+            )?;
+            write!(f, "\n  instance: {}", d.instance)?;
+            if let Some(output) = &d.boon_output {
+                write!(f, "\n  boon: {output}")?;
+            }
+        }
+        for (source, err) in &self.failures {
+            write!(f, "\n{source}: other failed: {err}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Schema to compare validators with, see [module documentation](self).
+pub struct DiffTest {
+    schema: Value,
+    schemas: Schemas,
+    sch_index: SchemaIndex,
+}
+
+impl DiffTest {
+    /**
+    Compiles `schema` with `compiler`, as document at `url`.
+
+    The other validator is given `schema` as is, so the document should
+    not refer to other documents, unless the other validator resolves
+    them the same way.
+    */
+    pub fn new(compiler: &mut Compiler, url: &str, schema: Value) -> Result<Self, CompileError> {
+        compiler.add_resource(url, schema.clone())?;
+        let mut schemas = Schemas::new();
+        let sch_index = compiler.compile(url, &mut schemas)?;
+        Ok(Self {
+            schema,
+            schemas,
+            sch_index,
+        })
+    }
+
+    /// Validates each of `corpus` with boon and `other`, and reports differences.
+    pub fn run(&self, other: &dyn OtherValidator, corpus: &[CorpusEntry]) -> DiffReport {
+        let mut report = DiffReport::default();
+        for entry in corpus {
+            let other_valid = match other.validate(&self.schema, &entry.instance) {
+                Ok(valid) => valid,
+                Err(e) => {
+                    report.failures.push((entry.source.clone(), e.to_string()));
+                    continue;
+                }
+            };
+            report.compared += 1;
+            let result = self.schemas.validate(&entry.instance, self.sch_index);
+            if result.is_ok() == other_valid {
+                continue;
+            }
+            report.disagreements.push(Disagreement {
+                source: entry.source.clone(),
+                instance: entry.instance.clone(),
+                schema_location: self.schemas.get(self.sch_index).loc.clone(),
+                boon_valid: result.is_ok(),
+                boon_output: result
+                    .err()
+                    .and_then(|e| serde_json::to_value(e.basic_output()).ok()),
+            });
+        }
+        report
+    }
+}
@@ -1,17 +1,16 @@
 use std::{
-    collections::{hash_map::Entry, HashMap},
+    collections::{hash_map::Entry, HashMap, HashSet},
     str::FromStr,
 };
 
 use once_cell::sync::Lazy;
 use serde_json::{Map, Value};
-use url::Url;
 
-use crate::{compiler::*, root::Resource, util::*, SchemaIndex, Schemas};
+use crate::{compiler::*, root::Resource, url::Url, util::*, SchemaIndex, Schemas};
 
-const POS_SELF: u8 = 1 << 0;
-const POS_PROP: u8 = 1 << 1;
-const POS_ITEM: u8 = 1 << 2;
+pub(crate) const POS_SELF: u8 = 1 << 0;
+pub(crate) const POS_PROP: u8 = 1 << 1;
+pub(crate) const POS_ITEM: u8 = 1 << 2;
 
 pub(crate) static DRAFT4: Lazy<Draft> = Lazy::new(|| Draft {
     version: 4,
@@ -184,7 +183,7 @@ impl Draft {
             .validate(v, sch)
             .map_err(|src| CompileError::ValidationError {
                 url: up.to_string(),
-                src: src.clone_static(),
+                src: src.into_owned(),
             })
     }
 
@@ -203,6 +202,7 @@ impl Draft {
         &self,
         url: &Url,
         doc: &Value,
+        custom: &HashSet<String>,
     ) -> Result<Option<Vec<String>>, CompileError> {
         if self.version < 2019 {
             return Ok(None);
@@ -217,6 +217,11 @@ impl Draft {
 
         let mut vocabs = vec![];
         for (vocab, reqd) in obj {
+            // custom vocabularies are kept by uri
+            if custom.contains(vocab) {
+                vocabs.push(vocab.to_owned());
+                continue;
+            }
             if let Value::Bool(true) = reqd {
                 let name = vocab
                     .strip_prefix(self.vocab_prefix)
@@ -234,6 +239,24 @@ impl Draft {
         Ok(Some(vocabs))
     }
 
+    // whether `anchor` matches syntax of $anchor/$dynamicAnchor:
+    //   2019-09: [A-Za-z][-A-Za-z0-9.:_]*
+    //   2020-12: [A-Za-z_][-A-Za-z0-9._]*
+    fn is_valid_anchor(&self, anchor: &str) -> bool {
+        let mut chars = anchor.chars();
+        let Some(first) = chars.next() else {
+            return false;
+        };
+        if !(first.is_ascii_alphabetic() || first == '_' && self.version >= 2020) {
+            return false;
+        }
+        chars.all(|c| {
+            c.is_ascii_alphanumeric()
+                || matches!(c, '-' | '.' | '_')
+                || c == ':' && self.version < 2020
+        })
+    }
+
     // collects anchors/dynamic_achors from `sch` into `res`.
     // note this does not collect from subschemas in sch.
     pub(crate) fn collect_anchors(
@@ -282,13 +305,25 @@ impl Draft {
                 return Ok(());
             }
         }
+        let check_anchor = |anchor: &str| {
+            if self.is_valid_anchor(anchor) {
+                return Ok(());
+            }
+            Err(CompileError::InvalidAnchor {
+                url: url.as_str().to_owned(),
+                ptr: sch_ptr.to_string(),
+                anchor: anchor.to_owned(),
+            })
+        };
         if self.version >= 2019 {
             if let Some(Value::String(anchor)) = obj.get("$anchor") {
+                check_anchor(anchor)?;
                 add_anchor(anchor.as_str().into())?;
             }
         }
         if self.version >= 2020 {
             if let Some(Value::String(anchor)) = obj.get("$dynamicAnchor") {
+                check_anchor(anchor)?;
                 add_anchor(anchor.as_str().into())?;
                 res.dynamic_anchors.insert(anchor.as_str().into());
             }
@@ -321,6 +356,17 @@ impl Draft {
             return Ok(());
         };
 
+        // since 2019, plain-name fragments are declared using $anchor;
+        // $id may only have empty fragment
+        if self.version >= 2019 {
+            if let Some(Value::String(id)) = obj.get(self.id) {
+                if !split(id).1.is_empty() {
+                    let loc = UrlFrag::format(url, sch_ptr.as_str());
+                    return Err(CompileError::ParseIdError { loc });
+                }
+            }
+        }
+
         let mut base = base;
         let tmp;
         let res = if let Some(id) = self.get_id(obj) {
@@ -356,8 +402,9 @@ impl Draft {
             debug_assert!(false, "base resource must exist");
         }
 
-        for (&kw, &pos) in &self.subschemas {
-            let Some(v) = obj.get(kw) else {
+        // in document order, so that errors do not depend on hash order
+        for (kw, v) in obj {
+            let Some(&pos) = self.subschemas.get(kw.as_str()) else {
                 continue;
             };
             if pos & POS_SELF != 0 {
@@ -384,21 +431,17 @@ impl Draft {
         Ok(())
     }
 
+    // location of subschemas in value of `kw`, as `POS_*` bits.
+    pub(crate) fn subschema_pos(&self, kw: &str) -> Option<u8> {
+        self.subschemas.get(kw).copied()
+    }
+
     pub(crate) fn is_subschema(&self, ptr: &str) -> bool {
         if ptr.is_empty() {
             return true;
         }
 
-        fn split(mut ptr: &str) -> (&str, &str) {
-            ptr = &ptr[1..]; // rm `/` prefix
-            if let Some(i) = ptr.find('/') {
-                (&ptr[..i], &ptr[i..])
-            } else {
-                (ptr, "")
-            }
-        }
-
-        let (tok, ptr) = split(ptr);
+        let (tok, ptr) = split_ptr(ptr);
 
         if let Some(&pos) = self.subschemas.get(tok) {
             if pos & POS_SELF != 0 && self.is_subschema(ptr) {
@@ -406,13 +449,13 @@ impl Draft {
             }
             if !ptr.is_empty() {
                 if pos & POS_PROP != 0 {
-                    let (_, ptr) = split(ptr);
+                    let (_, ptr) = split_ptr(ptr);
                     if self.is_subschema(ptr) {
                         return true;
                     }
                 }
                 if pos & POS_ITEM != 0 {
-                    let (tok, ptr) = split(ptr);
+                    let (tok, ptr) = split_ptr(ptr);
                     if usize::from_str(tok).is_ok() && self.is_subschema(ptr) {
                         return true;
                     }
@@ -422,6 +465,155 @@ impl Draft {
 
         false
     }
+
+    /// keyword whose value holds `ptr`, relative to schema, at position
+    /// which cannot be a schema. for example `enum` for `/enum/0`, or
+    /// `properties` for `/properties`. `None`, if `ptr` may be a schema,
+    /// like within unknown keyword.
+    pub(crate) fn non_schema_keyword(&self, ptr: &str) -> Option<&'static str> {
+        if ptr.is_empty() {
+            return None;
+        }
+        let (tok, ptr) = split_ptr(ptr);
+        let Some((&kw, &pos)) = self.subschemas.get_key_value(tok) else {
+            // values of annotations like `title` are not checked, so
+            // they are not known to hold no schemas
+            if tok == self.id {
+                return Some(self.id);
+            }
+            return STRUCTURAL_KEYWORDS
+                .iter()
+                .chain(RETAINED_KEYWORDS)
+                .copied()
+                .filter(|kw| !matches!(*kw, "id" | "$defs" | "definitions"))
+                .find(|kw| *kw == tok);
+        };
+
+        // ptr is not schema, only if it is not for any of the positions
+        let mut found = Some(kw);
+        let mut check = |ptr| match self.non_schema_keyword(ptr) {
+            Some(kw) => found = found.and(Some(kw)),
+            None => found = None,
+        };
+        if pos & POS_SELF != 0 {
+            check(ptr);
+        }
+        if !ptr.is_empty() {
+            if pos & POS_PROP != 0 {
+                check(split_ptr(ptr).1);
+            }
+            if pos & POS_ITEM != 0 {
+                let (tok, ptr) = split_ptr(ptr);
+                if usize::from_str(tok).is_ok() {
+                    check(ptr);
+                }
+            }
+        }
+        found
+    }
+}
+
+// splits first token of json-pointer `ptr`, which is not empty
+fn split_ptr(mut ptr: &str) -> (&str, &str) {
+    ptr = &ptr[1..]; // rm `/` prefix
+    if let Some(i) = ptr.find('/') {
+        (&ptr[..i], &ptr[i..])
+    } else {
+        (ptr, "")
+    }
+}
+
+// keywords that cannot be stripped, besides structural keywords and
+// applicators: they affect validation, or are used by `Schemas::example_for`.
+const RETAINED_KEYWORDS: &[&str] = &[
+    "type",
+    "enum",
+    "const",
+    "format",
+    "multipleOf",
+    "maximum",
+    "exclusiveMaximum",
+    "minimum",
+    "exclusiveMinimum",
+    "maxLength",
+    "minLength",
+    "pattern",
+    "maxItems",
+    "minItems",
+    "uniqueItems",
+    "maxContains",
+    "minContains",
+    "maxProperties",
+    "minProperties",
+    "required",
+    "dependentRequired",
+    "contentEncoding",
+    "contentMediaType",
+    "default",
+    "examples",
+];
+
+/// whether `kw` next to `$ref` is evaluated in draft 2019-09 and later,
+/// either as assertion or as applicator.
+pub(crate) fn is_evaluated_since_2019(kw: &str) -> bool {
+    if matches!(kw, "default" | "examples" | "$defs" | "definitions") {
+        return false;
+    }
+    RETAINED_KEYWORDS.contains(&kw)
+        || DRAFT2019.subschemas.contains_key(kw)
+        || DRAFT2020.subschemas.contains_key(kw)
+}
+
+// subschema positions of `kw`, across all drafts.
+fn any_draft_pos(kw: &str) -> u8 {
+    [&*DRAFT4, &*DRAFT6, &*DRAFT7, &*DRAFT2019, &*DRAFT2020]
+        .iter()
+        .filter_map(|d| d.subschemas.get(kw))
+        .fold(0, |acc, pos| acc | pos)
+}
+
+/// whether `kw` neither affects validation nor holds subschemas,
+/// in any draft. for example `title`, `writeOnly` or `x-sensitive`.
+pub(crate) fn is_annotation(kw: &str) -> bool {
+    !STRUCTURAL_KEYWORDS.contains(&kw) && !RETAINED_KEYWORDS.contains(&kw) && any_draft_pos(kw) == 0
+}
+
+fn is_strippable(kw: &str, keywords: &[String]) -> bool {
+    let matched = keywords.iter().any(|k| match k.strip_suffix('*') {
+        Some(prefix) => kw.starts_with(prefix),
+        None => k == kw,
+    });
+    matched && is_annotation(kw)
+}
+
+/// Removes `keywords` from `sch` and its subschemas. Draft of the
+/// document is not known yet, so subschema locations of all drafts
+/// are visited; values of other keywords, like `enum`, are left as is.
+pub(crate) fn strip_keywords(sch: &mut Value, keywords: &[String]) {
+    let Value::Object(obj) = sch else {
+        return;
+    };
+    obj.retain(|kw, _| !is_strippable(kw, keywords));
+    for (kw, v) in obj.iter_mut() {
+        let pos = any_draft_pos(kw);
+        if pos & POS_SELF != 0 {
+            strip_keywords(v, keywords);
+        }
+        if pos & POS_ITEM != 0 {
+            if let Value::Array(arr) = v {
+                for item in arr {
+                    strip_keywords(item, keywords);
+                }
+            }
+        }
+        if pos & POS_PROP != 0 {
+            if let Value::Object(obj) = v {
+                for pvalue in obj.values_mut() {
+                    strip_keywords(pvalue, keywords);
+                }
+            }
+        }
+    }
 }
 
 fn load_std_metaschemas() -> Result<Schemas, CompileError> {
@@ -573,4 +765,29 @@ mod tests {
             assert_eq!(got, test.1, "{}", test.0);
         }
     }
+
+    #[test]
+    fn test_non_schema_keyword() {
+        let tests = vec![
+            ("", None),
+            ("/properties/type", None),
+            ("/properties/a/type", Some("type")),
+            ("/properties", Some("properties")),
+            ("/allOf/0/enum/1/a", Some("enum")),
+            ("/items/0", None), // keyword `0` in schema
+            ("/x-defs/a", None),
+            ("/$defs/a/$id", Some("$id")),
+            ("/id", None),
+        ];
+        for test in tests {
+            let got = DRAFT2020.non_schema_keyword(test.0);
+            assert_eq!(got, test.1, "{}", test.0);
+        }
+        assert_eq!(DRAFT4.non_schema_keyword("/id"), Some("id"));
+        assert_eq!(DRAFT7.non_schema_keyword("/items/1"), None);
+        assert_eq!(
+            DRAFT7.non_schema_keyword("/items/required/0"),
+            Some("required")
+        );
+    }
 }
@@ -5,7 +5,7 @@ use regex_syntax::ast::{self, *};
 
 // covert ecma regex to rust regex if possible
 // see https://262.ecma-international.org/11.0/#sec-regexp-regular-expression-objects
-pub(crate) fn convert(pattern: &str) -> Result<Cow<str>, Box<dyn std::error::Error>> {
+pub(crate) fn convert(pattern: &str) -> Result<Cow<'_, str>, Box<dyn std::error::Error>> {
     let mut pattern = Cow::Borrowed(pattern);
 
     let mut ast = loop {
@@ -77,6 +77,8 @@ handles following translations:
 -  \W should match everything but ascii letters. so replace with [^a-zA-Z0-9_]
 -  \s and \S differences
 -  \a is not an ECMA 262 control escape
+-  \b and \B should use ascii word chars. so replace with (?-u:\b) and (?-u:\B),
+   only without `regex-unicode` feature, as unicode word boundary needs its tables
 */
 struct Translator<'a> {
     pat: &'a str,
@@ -146,6 +148,19 @@ impl Visitor for Translator<'_> {
                     return Err("\\a is not an ECMA 262 control escape");
                 }
             }
+            #[cfg(not(feature = "regex-unicode"))]
+            Ast::Assertion(assertion) => {
+                let (start, end) = (assertion.span.start.offset, assertion.span.end.offset);
+                // already translated
+                if self.pat[..start].ends_with("(?-u:") && self.pat[end..].starts_with(')') {
+                    return Ok(());
+                }
+                match assertion.kind {
+                    AssertionKind::WordBoundary => self.replace(&assertion.span, r"(?-u:\b)"),
+                    AssertionKind::NotWordBoundary => self.replace(&assertion.span, r"(?-u:\B)"),
+                    _ => (),
+                }
+            }
             _ => (),
         }
         Ok(())
@@ -0,0 +1,96 @@
+use std::{borrow::Cow, error::Error, fmt::Display};
+
+/// Error in decoding json text from bytes.
+#[derive(Debug)]
+pub struct EncodingError {
+    /// detected encoding. ex: `utf-8`, `utf-16le`
+    pub encoding: &'static str,
+    /// byte offset at which decoding failed.
+    pub offset: usize,
+    pub message: String,
+}
+
+impl Error for EncodingError {}
+
+impl Display for EncodingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "invalid {} at byte offset {}: {}",
+            self.encoding, self.offset, self.message
+        )
+    }
+}
+
+/**
+Decodes json text from `bytes`.
+
+Byte order mark if any is stripped. Without byte order mark, encoding is
+detected from the pattern of nulls in first four bytes as described in
+[RFC 4627](https://www.rfc-editor.org/rfc/rfc4627#section-3).
+
+utf-8 and utf-16 (LE/BE) are supported.
+*/
+pub(crate) fn decode(bytes: &[u8]) -> Result<Cow<'_, str>, EncodingError> {
+    let (encoding, bom) = match bytes {
+        [0xEF, 0xBB, 0xBF, ..] => ("utf-8", 3),
+        [0x00, 0x00, 0xFE, 0xFF, ..] => ("utf-32be", 4),
+        [0xFF, 0xFE, 0x00, 0x00, ..] => ("utf-32le", 4),
+        [0xFE, 0xFF, ..] => ("utf-16be", 2),
+        [0xFF, 0xFE, ..] => ("utf-16le", 2),
+        [0, 0, 0, _, ..] => ("utf-32be", 0),
+        [_, 0, 0, 0, ..] => ("utf-32le", 0),
+        [0, _, 0, _, ..] | [0, _] => ("utf-16be", 0),
+        [_, 0, _, 0, ..] | [_, 0] => ("utf-16le", 0),
+        _ => ("utf-8", 0),
+    };
+    let content = &bytes[bom..];
+    match encoding {
+        "utf-8" => match std::str::from_utf8(content) {
+            Ok(s) => Ok(Cow::Borrowed(s)),
+            Err(e) => Err(EncodingError {
+                encoding,
+                offset: bom + e.valid_up_to(),
+                message: "invalid byte sequence".to_owned(),
+            }),
+        },
+        "utf-16be" => decode_utf16(content, encoding, bom, u16::from_be_bytes),
+        "utf-16le" => decode_utf16(content, encoding, bom, u16::from_le_bytes),
+        _ => Err(EncodingError {
+            encoding,
+            offset: 0,
+            message: "encoding not supported".to_owned(),
+        }),
+    }
+}
+
+fn decode_utf16(
+    content: &[u8],
+    encoding: &'static str,
+    bom: usize,
+    from_bytes: fn([u8; 2]) -> u16,
+) -> Result<Cow<'static, str>, EncodingError> {
+    let chunks = content.chunks_exact(2);
+    if !chunks.remainder().is_empty() {
+        return Err(EncodingError {
+            encoding,
+            offset: bom + content.len() - 1,
+            message: "odd number of bytes".to_owned(),
+        });
+    }
+    let units = chunks.map(|c| from_bytes([c[0], c[1]]));
+    let mut s = String::with_capacity(content.len() / 2);
+    let mut offset = bom;
+    for ch in char::decode_utf16(units) {
+        let Ok(ch) = ch else {
+            return Err(EncodingError {
+                encoding,
+                offset,
+                message: "unpaired surrogate".to_owned(),
+            });
+        };
+        offset += ch.len_utf16() * 2;
+        s.push(ch);
+    }
+    Ok(Cow::Owned(s))
+}
@@ -1,16 +1,18 @@
 use std::{
+    any::Any,
     collections::HashMap,
     error::Error,
     net::{Ipv4Addr, Ipv6Addr},
 };
 
 use once_cell::sync::Lazy;
-use percent_encoding::percent_decode_str;
 use serde_json::Value;
-use url::Url;
 
 use crate::ecma;
 
+#[cfg(feature = "formats-net")]
+mod net;
+
 /// Defines format for `format` keyword.
 #[derive(Clone, Copy)]
 pub struct Format {
@@ -21,29 +23,204 @@ pub struct Format {
     pub func: fn(v: &Value) -> Result<(), Box<dyn Error>>,
 }
 
+impl Format {
+    /**
+    Returns built-in format with given `name`, if any.
+
+    Useful to extend built-in format, in custom format
+    registered with same name:
+
+    ```rust
+    # use std::error::Error;
+    # use serde_json::Value;
+    # use boon::*;
+    fn email(v: &Value) -> Result<(), Box<dyn Error>> {
+        (Format::builtin("email").unwrap().func)(v)?;
+        if let Value::String(s) = v {
+            if s.ends_with("@mailinator.com") {
+                Err("disposable email domain")?;
+            }
+        }
+        Ok(())
+    }
+
+    let mut compiler = Compiler::new();
+    compiler.register_format(Format {
+        name: "email",
+        func: email,
+    });
+    ```
+    */
+    pub fn builtin(name: &str) -> Option<Format> {
+        FORMATS.get(name).copied()
+    }
+}
+
+/**
+Defines format for `format` keyword, which needs data supplied
+per validation, using [`ValidationOptions::context`](crate::ValidationOptions::context).
+
+```rust
+# use std::{any::Any, error::Error};
+# use serde_json::Value;
+# use boon::*;
+// context is current time in seconds
+fn not_in_past(v: &Value, ctx: Option<&dyn Any>) -> Result<(), Box<dyn Error>> {
+    let (Some(v), Some(now)) = (v.as_u64(), ctx.and_then(|c| c.downcast_ref::<u64>())) else {
+        return Ok(());
+    };
+    if v < *now {
+        Err("timestamp is in the past")?;
+    }
+    Ok(())
+}
+
+let mut compiler = Compiler::new();
+compiler.register_context_format(ContextFormat {
+    name: "not-in-past",
+    func: not_in_past,
+});
+```
+*/
+#[derive(Clone, Copy)]
+pub struct ContextFormat {
+    /// Name of the format
+    pub name: &'static str,
+
+    /// validates given value, using context of validation, if any.
+    #[allow(clippy::type_complexity)]
+    pub func: fn(v: &Value, ctx: Option<&dyn Any>) -> Result<(), Box<dyn Error>>,
+}
+
+/**
+Policy tightening built-in `date-time` format, configured using
+[`Compiler::configure_format`](crate::Compiler::configure_format).
+
+Default policy accepts same values as built-in format.
+
+```rust
+# use boon::*;
+let mut compiler = Compiler::new();
+compiler.configure_format(
+    "date-time",
+    DateTimePolicy {
+        require_utc: true,
+        min_year: Some(1900),
+        max_year: Some(2100),
+        ..Default::default()
+    },
+)?;
+# Ok::<(), CompileError>(())
+```
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateTimePolicy {
+    /// accept only `Z` as offset.
+    pub require_utc: bool,
+    /// minimum year accepted, inclusive.
+    pub min_year: Option<u16>,
+    /// maximum year accepted, inclusive.
+    pub max_year: Option<u16>,
+    /// accept `60` as seconds.
+    pub allow_leap_seconds: bool,
+}
+
+impl Default for DateTimePolicy {
+    fn default() -> Self {
+        Self {
+            require_utc: false,
+            min_year: None,
+            max_year: None,
+            allow_leap_seconds: true,
+        }
+    }
+}
+
+impl DateTimePolicy {
+    // `s` must be valid date-time
+    fn check(&self, s: &str) -> Result<(), Box<dyn Error>> {
+        if self.require_utc && !s.ends_with(['z', 'Z']) {
+            Err("offset must be Z, as UTC is required")?;
+        }
+        let year = s[..4].parse::<u16>()?;
+        if let Some(min) = self.min_year {
+            if year < min {
+                Err(format!("year {year} is before {min}"))?;
+            }
+        }
+        if let Some(max) = self.max_year {
+            if year > max {
+                Err(format!("year {year} is after {max}"))?;
+            }
+        }
+        if !self.allow_leap_seconds && &s[17..19] == "60" {
+            Err("leap second is not allowed")?;
+        }
+        Ok(())
+    }
+}
+
+/// Policy tightening a built-in format, see [`Compiler::configure_format`](crate::Compiler::configure_format).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FormatPolicy {
+    DateTime(DateTimePolicy),
+}
+
+impl FormatPolicy {
+    /// name of the format this policy applies to.
+    pub(crate) fn format(&self) -> &'static str {
+        match self {
+            Self::DateTime(_) => "date-time",
+        }
+    }
+
+    /// called only if `v` is valid in built-in format.
+    pub(crate) fn check(&self, v: &Value) -> Result<(), Box<dyn Error>> {
+        let Value::String(s) = v else {
+            return Ok(());
+        };
+        match self {
+            Self::DateTime(p) => p.check(s),
+        }
+    }
+}
+
+impl From<DateTimePolicy> for FormatPolicy {
+    fn from(p: DateTimePolicy) -> Self {
+        Self::DateTime(p)
+    }
+}
+
 pub(crate) static FORMATS: Lazy<HashMap<&'static str, Format>> = Lazy::new(|| {
     let mut m = HashMap::<&'static str, Format>::new();
     let mut register = |name, func| m.insert(name, Format { name, func });
     register("regex", validate_regex);
     register("ipv4", validate_ipv4);
     register("ipv6", validate_ipv6);
-    register("hostname", validate_hostname);
-    register("idn-hostname", validate_idn_hostname);
-    register("email", validate_email);
-    register("idn-email", validate_idn_email);
-    register("date", validate_date);
-    register("time", validate_time);
-    register("date-time", validate_date_time);
-    register("duration", validate_duration);
-    register("period", validate_period);
     register("json-pointer", validate_json_pointer);
     register("relative-json-pointer", validate_relative_json_pointer);
     register("uuid", validate_uuid);
-    register("uri", validate_uri);
-    register("iri", validate_iri);
-    register("uri-reference", validate_uri_reference);
-    register("iri-reference", validate_iri_reference);
-    register("uri-template", validate_uri_template);
+    #[cfg(feature = "formats-time")]
+    {
+        register("date", validate_date);
+        register("time", validate_time);
+        register("date-time", validate_date_time);
+        register("duration", validate_duration);
+        register("period", validate_period);
+    }
+    #[cfg(feature = "formats-net")]
+    {
+        register("hostname", net::validate_hostname);
+        register("idn-hostname", net::validate_idn_hostname);
+        register("email", net::validate_email);
+        register("idn-email", net::validate_idn_email);
+        register("uri", net::validate_uri);
+        register("iri", net::validate_iri);
+        register("uri-reference", net::validate_uri_reference);
+        register("iri-reference", net::validate_iri_reference);
+        register("uri-template", net::validate_uri_template);
+    }
     m
 });
 
@@ -70,6 +247,7 @@ fn validate_ipv6(v: &Value) -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[cfg(feature = "formats-time")]
 fn validate_date(v: &Value) -> Result<(), Box<dyn Error>> {
     let Value::String(s) = v else {
         return Ok(());
@@ -82,7 +260,7 @@ fn matches_char(s: &str, index: usize, ch: char) -> bool {
 }
 
 // see https://datatracker.ietf.org/doc/html/rfc3339#section-5.6
-fn check_date(s: &str) -> Result<(), Box<dyn Error>> {
+pub(crate) fn check_date(s: &str) -> Result<(), Box<dyn Error>> {
     // yyyy-mm-dd
     if s.len() != 10 {
         Err("must be 10 characters long")?;
@@ -113,16 +291,15 @@ fn check_date(s: &str) -> Result<(), Box<dyn Error>> {
                 Err(format!("february has {feb_days} days only"))?;
             }
         }
-        4 | 6 | 9 | 11 => {
-            if d > 30 {
-                Err("month has 30 days only")?;
-            }
+        4 | 6 | 9 | 11 if d > 30 => {
+            Err("month has 30 days only")?;
         }
         _ => {}
     }
     Ok(())
 }
 
+#[cfg(feature = "formats-time")]
 fn validate_time(v: &Value) -> Result<(), Box<dyn Error>> {
     let Value::String(s) = v else {
         return Ok(());
@@ -203,6 +380,7 @@ fn check_time(mut str: &str) -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[cfg(feature = "formats-time")]
 fn validate_date_time(v: &Value) -> Result<(), Box<dyn Error>> {
     let Value::String(s) = v else {
         return Ok(());
@@ -210,7 +388,7 @@ fn validate_date_time(v: &Value) -> Result<(), Box<dyn Error>> {
     check_date_time(s)
 }
 
-fn check_date_time(s: &str) -> Result<(), Box<dyn Error>> {
+pub(crate) fn check_date_time(s: &str) -> Result<(), Box<dyn Error>> {
     // min: yyyy-mm-ddThh:mm:ssZ
     if s.len() < 20 {
         Err("less than 20 characters long")?;
@@ -227,6 +405,7 @@ fn check_date_time(s: &str) -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[cfg(feature = "formats-time")]
 fn validate_duration(v: &Value) -> Result<(), Box<dyn Error>> {
     let Value::String(s) = v else {
         return Ok(());
@@ -235,6 +414,7 @@ fn validate_duration(v: &Value) -> Result<(), Box<dyn Error>> {
 }
 
 // see https://datatracker.ietf.org/doc/html/rfc3339#appendix-A
+#[cfg(feature = "formats-time")]
 fn check_duration(s: &str) -> Result<(), Box<dyn Error>> {
     // must start with 'P'
     let Some(s) = s.strip_prefix('P') else {
@@ -288,6 +468,7 @@ fn check_duration(s: &str) -> Result<(), Box<dyn Error>> {
 }
 
 // see https://datatracker.ietf.org/doc/html/rfc3339#appendix-A
+#[cfg(feature = "formats-time")]
 fn validate_period(v: &Value) -> Result<(), Box<dyn Error>> {
     let Value::String(s) = v else {
         return Ok(());
@@ -320,360 +501,6 @@ fn validate_period(v: &Value) -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
-fn validate_hostname(v: &Value) -> Result<(), Box<dyn Error>> {
-    let Value::String(s) = v else {
-        return Ok(());
-    };
-    check_hostname(s)
-}
-
-// see https://en.wikipedia.org/wiki/Hostname#Restrictions_on_valid_host_names
-fn check_hostname(mut s: &str) -> Result<(), Box<dyn Error>> {
-    // entire hostname (including the delimiting dots but not a trailing dot) has a maximum of 253 ASCII characters
-    s = s.strip_suffix('.').unwrap_or(s);
-    if s.len() > 253 {
-        Err("more than 253 characters long")?
-    }
-
-    // Hostnames are composed of series of labels concatenated with dots, as are all domain names
-    for label in s.split('.') {
-        // Each label must be from 1 to 63 characters long
-        if !matches!(label.len(), 1..=63) {
-            Err("label must be 1 to 63 characters long")?;
-        }
-
-        // labels must not start or end with a hyphen
-        if label.starts_with('-') {
-            Err("label starts with hyphen")?;
-        }
-
-        if label.ends_with('-') {
-            Err("label ends with hyphen")?;
-        }
-
-        // labels may contain only the ASCII letters 'a' through 'z' (in a case-insensitive manner),
-        // the digits '0' through '9', and the hyphen ('-')
-        if let Some(ch) = label
-            .chars()
-            .find(|c| !matches!(c, 'a'..='z' | 'A'..='Z' | '0'..='9' | '-'))
-        {
-            Err(format!("invalid character {ch:?}"))?;
-        }
-    }
-
-    Ok(())
-}
-
-fn validate_idn_hostname(v: &Value) -> Result<(), Box<dyn Error>> {
-    let Value::String(s) = v else {
-        return Ok(());
-    };
-    check_idn_hostname(s)
-}
-
-fn check_idn_hostname(s: &str) -> Result<(), Box<dyn Error>> {
-    let s = idna::domain_to_ascii_strict(s)?;
-    let unicode = idna::domain_to_unicode(&s).0;
-
-    // see https://www.rfc-editor.org/rfc/rfc5892#section-2.6
-    {
-        static DISALLOWED: [char; 10] = [
-            '\u{0640}', //  ARABIC TATWEEL
-            '\u{07FA}', //  NKO LAJANYALAN
-            '\u{302E}', //  HANGUL SINGLE DOT TONE MARK
-            '\u{302F}', //  HANGUL DOUBLE DOT TONE MARK
-            '\u{3031}', //  VERTICAL KANA REPEAT MARK
-            '\u{3032}', //  VERTICAL KANA REPEAT WITH VOICED SOUND MARK
-            '\u{3033}', //  VERTICAL KANA REPEAT MARK UPPER HALF
-            '\u{3034}', //  VERTICAL KANA REPEAT WITH VOICED SOUND MARK UPPER HA
-            '\u{3035}', //  VERTICAL KANA REPEAT MARK LOWER HALF
-            '\u{303B}', //  VERTICAL IDEOGRAPHIC ITERATION MARK
-        ];
-        if unicode.contains(DISALLOWED) {
-            Err("contains disallowed character")?;
-        }
-    }
-
-    // unicode string must not contain "--" in 3rd and 4th position
-    // and must not start and end with a '-'
-    // see https://www.rfc-editor.org/rfc/rfc5891#section-4.2.3.1
-    {
-        let count: usize = unicode
-            .chars()
-            .skip(2)
-            .take(2)
-            .map(|c| if c == '-' { 1 } else { 0 })
-            .sum();
-        if count == 2 {
-            Err("unicode string must not contain '--' in 3rd and 4th position")?;
-        }
-    }
-
-    // MIDDLE DOT is allowed between 'l' characters only
-    // see https://www.rfc-editor.org/rfc/rfc5892#appendix-A.3
-    {
-        let middle_dot = '\u{00b7}';
-        let mut s = unicode.as_str();
-        while let Some(i) = s.find(middle_dot) {
-            let prefix = &s[..i];
-            let suffix = &s[i + middle_dot.len_utf8()..];
-            if !prefix.ends_with('l') || !suffix.ends_with('l') {
-                Err("MIDDLE DOT is allowed between 'l' characters only")?;
-            }
-            s = suffix;
-        }
-    }
-
-    // Greek KERAIA must be followed by Greek character
-    // see https://www.rfc-editor.org/rfc/rfc5892#appendix-A.4
-    {
-        let keralia = '\u{0375}';
-        let greek = '\u{0370}'..='\u{03FF}';
-        let mut s = unicode.as_str();
-        while let Some(i) = s.find(keralia) {
-            let suffix = &s[i + keralia.len_utf8()..];
-            if !suffix.starts_with(|c| greek.contains(&c)) {
-                Err("Greek KERAIA must be followed by Greek character")?;
-            }
-            s = suffix;
-        }
-    }
-
-    // Hebrew GERESH must be preceded by Hebrew character
-    // see https://www.rfc-editor.org/rfc/rfc5892#appendix-A.5
-    //
-    // Hebrew GERSHAYIM must be preceded by Hebrew character
-    // see https://www.rfc-editor.org/rfc/rfc5892#appendix-A.6
-    {
-        let geresh = '\u{05F3}';
-        let gereshayim = '\u{05F4}';
-        let hebrew = '\u{0590}'..='\u{05FF}';
-        for ch in [geresh, gereshayim] {
-            let mut s = unicode.as_str();
-            while let Some(i) = s.find(ch) {
-                let prefix = &s[..i];
-                let suffix = &s[i + ch.len_utf8()..];
-                if !prefix.ends_with(|c| hebrew.contains(&c)) {
-                    if i == 0 {
-                        Err("Hebrew GERESH must be preceded by Hebrew character")?;
-                    } else {
-                        Err("Hebrew GERESHYIM must be preceded by Hebrew character")?;
-                    }
-                }
-                s = suffix;
-            }
-        }
-    }
-
-    // KATAKANA MIDDLE DOT must be with Hiragana, Katakana, or Han
-    // see https://www.rfc-editor.org/rfc/rfc5892#appendix-A.7
-    {
-        let katakana_middle_dot = '\u{30FB}';
-        let hiragana = '\u{3040}'..='\u{309F}';
-        let katakana = '\u{30A0}'..='\u{30FF}';
-        let han = '\u{4E00}'..='\u{9FFF}'; // https://en.wikipedia.org/wiki/CJK_Unified_Ideographs_(Unicode_block): is this range correct??
-        if unicode.contains(katakana_middle_dot) {
-            if unicode.contains(|c| hiragana.contains(&c))
-                || unicode.contains(|c| c != katakana_middle_dot && katakana.contains(&c))
-                || unicode.contains(|c| han.contains(&c))
-            {
-                // ok
-            } else {
-                Err("KATAKANA MIDDLE DOT must be with Hiragana, Katakana, or Han")?;
-            }
-        }
-    }
-
-    // ARABIC-INDIC DIGITS and Extended Arabic-Indic Digits cannot be mixed
-    // see https://www.rfc-editor.org/rfc/rfc5892#appendix-A.8
-    // see https://www.rfc-editor.org/rfc/rfc5892#appendix-A.9
-    {
-        let arabic_indic_digits = '\u{0660}'..='\u{0669}';
-        let extended_arabic_indic_digits = '\u{06F0}'..='\u{06F9}';
-        if unicode.contains(|c| arabic_indic_digits.contains(&c))
-            && unicode.contains(|c| extended_arabic_indic_digits.contains(&c))
-        {
-            Err("ARABIC-INDIC DIGITS and Extended Arabic-Indic Digits cannot be mixed")?;
-        }
-    }
-
-    // ZERO WIDTH JOINER must be preceded by Virama
-    // see https://www.rfc-editor.org/rfc/rfc5892#appendix-A.2
-    {
-        let zero_width_jointer = '\u{200D}';
-        static VIRAMA: [char; 61] = [
-            '\u{094D}',
-            '\u{09CD}',
-            '\u{0A4D}',
-            '\u{0ACD}',
-            '\u{0B4D}',
-            '\u{0BCD}',
-            '\u{0C4D}',
-            '\u{0CCD}',
-            '\u{0D3B}',
-            '\u{0D3C}',
-            '\u{0D4D}',
-            '\u{0DCA}',
-            '\u{0E3A}',
-            '\u{0EBA}',
-            '\u{0F84}',
-            '\u{1039}',
-            '\u{103A}',
-            '\u{1714}',
-            '\u{1734}',
-            '\u{17D2}',
-            '\u{1A60}',
-            '\u{1B44}',
-            '\u{1BAA}',
-            '\u{1BAB}',
-            '\u{1BF2}',
-            '\u{1BF3}',
-            '\u{2D7F}',
-            '\u{A806}',
-            '\u{A82C}',
-            '\u{A8C4}',
-            '\u{A953}',
-            '\u{A9C0}',
-            '\u{AAF6}',
-            '\u{ABED}',
-            '\u{10A3F}',
-            '\u{11046}',
-            '\u{1107F}',
-            '\u{110B9}',
-            '\u{11133}',
-            '\u{11134}',
-            '\u{111C0}',
-            '\u{11235}',
-            '\u{112EA}',
-            '\u{1134D}',
-            '\u{11442}',
-            '\u{114C2}',
-            '\u{115BF}',
-            '\u{1163F}',
-            '\u{116B6}',
-            '\u{1172B}',
-            '\u{11839}',
-            '\u{1193D}',
-            '\u{1193E}',
-            '\u{119E0}',
-            '\u{11A34}',
-            '\u{11A47}',
-            '\u{11A99}',
-            '\u{11C3F}',
-            '\u{11D44}',
-            '\u{11D45}',
-            '\u{11D97}',
-        ]; // https://www.compart.com/en/unicode/combining/9
-        let mut s = unicode.as_str();
-        while let Some(i) = s.find(zero_width_jointer) {
-            let prefix = &s[..i];
-            let suffix = &s[i + zero_width_jointer.len_utf8()..];
-            if !prefix.ends_with(VIRAMA) {
-                Err("ZERO WIDTH JOINER must be preceded by Virama")?;
-            }
-            s = suffix;
-        }
-    }
-
-    check_hostname(&s)
-}
-
-fn validate_email(v: &Value) -> Result<(), Box<dyn Error>> {
-    let Value::String(s) = v else {
-        return Ok(());
-    };
-    check_email(s)
-}
-
-// see https://en.wikipedia.org/wiki/Email_address
-fn check_email(s: &str) -> Result<(), Box<dyn Error>> {
-    // entire email address to be no more than 254 characters long
-    if s.len() > 254 {
-        Err("more than 254 characters long")?
-    }
-
-    // email address is generally recognized as having two parts joined with an at-sign
-    let Some(at) = s.rfind('@') else {
-        Err("missing @")?
-    };
-    let (local, domain) = (&s[..at], &s[at + 1..]);
-
-    // local part may be up to 64 characters long
-    if local.len() > 64 {
-        Err("local part more than 64 characters long")?
-    }
-
-    if local.len() > 1 && local.starts_with('"') && local.ends_with('"') {
-        // quoted
-        let local = &local[1..local.len() - 1];
-        if local.contains(['\\', '"']) {
-            Err("backslash and quote not allowed within quoted local part")?
-        }
-    } else {
-        // unquoted
-
-        if local.starts_with('.') {
-            Err("starts with dot")?
-        }
-        if local.ends_with('.') {
-            Err("ends with dot")?
-        }
-
-        // consecutive dots not allowed
-        if local.contains("..") {
-            Err("consecutive dots")?
-        }
-
-        // check allowd chars
-        if let Some(ch) = local
-            .chars()
-            .find(|c| !(c.is_ascii_alphanumeric() || ".!#$%&'*+-/=?^_`{|}~".contains(*c)))
-        {
-            Err(format!("invalid character {ch:?}"))?
-        }
-    }
-
-    // domain if enclosed in brackets, must match an IP address
-    if domain.starts_with('[') && domain.ends_with(']') {
-        let s = &domain[1..domain.len() - 1];
-        if let Some(s) = s.strip_prefix("IPv6:") {
-            if let Err(e) = s.parse::<Ipv6Addr>() {
-                Err(format!("invalid ipv6 address: {e}"))?
-            }
-            return Ok(());
-        }
-        if let Err(e) = s.parse::<Ipv4Addr>() {
-            Err(format!("invalid ipv4 address: {e}"))?
-        }
-        return Ok(());
-    }
-
-    // domain must match the requirements for a hostname
-    if let Err(e) = check_hostname(domain) {
-        Err(format!("invalid domain: {e}"))?
-    }
-
-    Ok(())
-}
-
-fn validate_idn_email(v: &Value) -> Result<(), Box<dyn Error>> {
-    let Value::String(s) = v else {
-        return Ok(());
-    };
-
-    let Some(at) = s.rfind('@') else {
-        Err("missing @")?
-    };
-    let (local, domain) = (&s[..at], &s[at + 1..]);
-
-    let local = idna::domain_to_ascii_strict(local)?;
-    let domain = idna::domain_to_ascii_strict(domain)?;
-    if let Err(e) = check_idn_hostname(&domain) {
-        Err(format!("invalid domain: {e}"))?
-    }
-    check_email(&format!("{local}@{domain}"))
-}
-
 fn validate_json_pointer(v: &Value) -> Result<(), Box<dyn Error>> {
     let Value::String(s) = v else {
         return Ok(());
@@ -759,80 +586,3 @@ fn validate_uuid(v: &Value) -> Result<(), Box<dyn Error>> {
     }
     Ok(())
 }
-
-fn validate_uri(v: &Value) -> Result<(), Box<dyn Error>> {
-    let Value::String(s) = v else {
-        return Ok(());
-    };
-    if fluent_uri::Uri::parse(s)?.is_relative() {
-        Err("relative url")?;
-    };
-    Ok(())
-}
-
-fn validate_iri(v: &Value) -> Result<(), Box<dyn Error>> {
-    let Value::String(s) = v else {
-        return Ok(());
-    };
-    match Url::parse(s) {
-        Ok(_) => Ok(()),
-        Err(url::ParseError::RelativeUrlWithoutBase) => Err("relative url")?,
-        Err(e) => Err(e)?,
-    }
-}
-
-static TEMP_URL: Lazy<Url> = Lazy::new(|| Url::parse("http://temp.com").unwrap());
-
-fn parse_uri_reference(s: &str) -> Result<Url, Box<dyn Error>> {
-    if s.contains('\\') {
-        Err("contains \\\\")?;
-    }
-    Ok(TEMP_URL.join(s)?)
-}
-
-fn validate_uri_reference(v: &Value) -> Result<(), Box<dyn Error>> {
-    let Value::String(s) = v else {
-        return Ok(());
-    };
-    fluent_uri::Uri::parse(s)?;
-    Ok(())
-}
-
-fn validate_iri_reference(v: &Value) -> Result<(), Box<dyn Error>> {
-    let Value::String(s) = v else {
-        return Ok(());
-    };
-    parse_uri_reference(s)?;
-    Ok(())
-}
-
-fn validate_uri_template(v: &Value) -> Result<(), Box<dyn Error>> {
-    let Value::String(s) = v else {
-        return Ok(());
-    };
-
-    let url = parse_uri_reference(s)?;
-
-    let path = url.path();
-    // path we got has curly bases percent encoded
-    let path = percent_decode_str(path).decode_utf8()?;
-
-    // ensure curly brackets are not nested and balanced
-    for part in path.as_ref().split('/') {
-        let mut want = true;
-        for got in part
-            .chars()
-            .filter(|c| matches!(c, '{' | '}'))
-            .map(|c| c == '{')
-        {
-            if got != want {
-                Err("nested curly braces")?;
-            }
-            want = !want;
-        }
-        if !want {
-            Err("no matching closing brace")?
-        }
-    }
-    Ok(())
-}
@@ -0,0 +1,179 @@
+use std::{collections::HashMap, fmt::Write};
+
+use crate::{util::split, SchemaIndex, Schemas};
+
+/// Graph of references between schema resources, see [`Schemas::reference_graph`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RefGraph {
+    /// url of each schema resource.
+    pub nodes: Vec<String>,
+    /// references between `nodes`, in the order compiled.
+    pub edges: Vec<RefEdge>,
+}
+
+/// Reference from one schema resource to another, or to itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RefEdge {
+    /// index of referencing resource in [`RefGraph::nodes`].
+    pub from: usize,
+    /// index of referenced resource in [`RefGraph::nodes`].
+    pub to: usize,
+    /// `$ref`, `$dynamicRef` or `$recursiveRef`.
+    pub keyword: &'static str,
+    /// absolute location of the keyword.
+    pub keyword_location: String,
+}
+
+impl RefGraph {
+    /**
+    Returns groups of nodes, which refer to each other directly or
+    indirectly. A node referring to itself forms a group on its own.
+
+    Nodes in each group, and groups, are in ascending order.
+    */
+    pub fn cycles(&self) -> Vec<Vec<usize>> {
+        let mut adj = vec![vec![]; self.nodes.len()];
+        for e in &self.edges {
+            adj[e.from].push(e.to);
+        }
+        let mut tarjan = Tarjan {
+            adj: &adj,
+            index: vec![None; self.nodes.len()],
+            low: vec![0; self.nodes.len()],
+            stack: vec![],
+            on_stack: vec![false; self.nodes.len()],
+            next: 0,
+            sccs: vec![],
+        };
+        for n in 0..self.nodes.len() {
+            if tarjan.index[n].is_none() {
+                tarjan.visit(n);
+            }
+        }
+        let mut cycles = tarjan
+            .sccs
+            .into_iter()
+            .filter(|scc| scc.len() > 1 || adj[scc[0]].contains(&scc[0]))
+            .map(|mut scc| {
+                scc.sort();
+                scc
+            })
+            .collect::<Vec<_>>();
+        cycles.sort();
+        cycles
+    }
+
+    /// Returns true if any node refers to itself, directly or indirectly.
+    pub fn has_cycle(&self) -> bool {
+        !self.cycles().is_empty()
+    }
+
+    /**
+    Renders this graph in Graphviz dot format.
+
+    Nodes are labeled with their url, and edges with the json-pointer of
+    the keyword within referencing resource.
+    */
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph refs {\n");
+        for (i, url) in self.nodes.iter().enumerate() {
+            _ = writeln!(out, "    n{i} [label={}];", dot_quote(url));
+        }
+        for e in &self.edges {
+            let (_, ptr) = split(&e.keyword_location);
+            _ = writeln!(
+                out,
+                "    n{} -> n{} [label={}];",
+                e.from,
+                e.to,
+                dot_quote(ptr)
+            );
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+fn dot_quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// strongly connected components, using Tarjan's algorithm.
+struct Tarjan<'a> {
+    adj: &'a [Vec<usize>],
+    index: Vec<Option<usize>>,
+    low: Vec<usize>,
+    stack: Vec<usize>,
+    on_stack: Vec<bool>,
+    next: usize,
+    sccs: Vec<Vec<usize>>,
+}
+
+impl Tarjan<'_> {
+    fn visit(&mut self, n: usize) {
+        self.index[n] = Some(self.next);
+        self.low[n] = self.next;
+        self.next += 1;
+        self.stack.push(n);
+        self.on_stack[n] = true;
+        for &m in &self.adj[n] {
+            match self.index[m] {
+                None => {
+                    self.visit(m);
+                    self.low[n] = self.low[n].min(self.low[m]);
+                }
+                Some(i) if self.on_stack[m] => self.low[n] = self.low[n].min(i),
+                Some(_) => {}
+            }
+        }
+        if Some(self.low[n]) == self.index[n] {
+            let mut scc = vec![];
+            while let Some(m) = self.stack.pop() {
+                self.on_stack[m] = false;
+                scc.push(m);
+                if m == n {
+                    break;
+                }
+            }
+            self.sccs.push(scc);
+        }
+    }
+}
+
+pub(crate) fn reference_graph(schemas: &Schemas) -> RefGraph {
+    let mut graph = RefGraph::default();
+    let mut nodes = HashMap::new(); // resource => node
+    let mut node = |graph: &mut RefGraph, resource: SchemaIndex| {
+        *nodes.entry(resource).or_insert_with(|| {
+            let sch = schemas.get(resource);
+            let url = match &sch.base_url {
+                Some(url) => url.to_string(),
+                None => sch.loc.clone(),
+            };
+            graph.nodes.push(url);
+            graph.nodes.len() - 1
+        })
+    };
+    for i in 0..schemas.size() {
+        let sch = schemas.get(SchemaIndex(i));
+        let from = node(&mut graph, sch.resource);
+        let refs = [
+            ("$ref", sch.ref_),
+            ("$recursiveRef", sch.recursive_ref),
+            ("$dynamicRef", sch.dynamic_ref.as_ref().map(|dref| dref.sch)),
+        ];
+        for (kw, target) in refs {
+            let Some(target) = target else {
+                continue;
+            };
+            let to = node(&mut graph, schemas.get(target).resource);
+            graph.edges.push(RefEdge {
+                from,
+                to,
+                keyword: kw,
+                keyword_location: format!("{}/{kw}", sch.loc),
+            });
+        }
+    }
+    graph
+}
@@ -102,39 +102,128 @@ println!("{output:#}"); // prints indented json
 
 */
 
+#[cfg(any(feature = "cbor", feature = "msgpack"))]
+mod binary;
+mod builder;
+mod canonical;
+mod capabilities;
+pub mod codegen;
 mod compiler;
 mod content;
+mod defs;
+mod diagnostics;
+#[cfg(feature = "difftest")]
+pub mod difftest;
 mod draft;
 mod ecma;
+mod encoding;
 mod formats;
+mod graph;
 mod loader;
+mod locate;
+mod options;
 mod output;
+#[cfg(feature = "fancy-errors")]
+mod pretty;
+mod query;
+mod redact;
+mod registry;
+mod resource;
 mod root;
 mod roots;
+mod shared;
+mod span;
+mod strict;
+mod summary;
+pub mod text;
+mod timerange;
+#[cfg(feature = "serde")]
+mod typed;
+#[cfg(not(feature = "url"))]
+#[path = "liteurl.rs"]
+mod url;
+// compared against `url` crate in its tests
+#[cfg(all(feature = "url", test))]
+#[allow(dead_code)]
+mod liteurl;
 mod util;
 mod validator;
+mod view;
+mod vocab;
 
 #[cfg(not(target_arch = "wasm32"))]
 pub use loader::FileLoader;
+#[cfg(feature = "json5")]
+pub use loader::Json5Error;
+#[cfg(all(feature = "json5", not(target_arch = "wasm32")))]
+pub use loader::LenientFileLoader;
+#[cfg(feature = "fancy-errors")]
+pub use options::{ColorChoice, PrettyOptions};
+#[cfg(feature = "serde")]
+pub use typed::CombinedError;
 pub use {
-    compiler::{CompileError, Compiler, Draft},
+    builder::{
+        ArrayBuilder, NumberBuilder, ObjectBuilder, SchemaBuilder, StringBuilder,
+        UnsupportedKeyword,
+    },
+    canonical::{canonical_hash, canonical_json},
+    capabilities::{capabilities, Capabilities},
+    compiler::{CompileError, CompileObserver, CompileReport, Compiler, Draft, RegexLimits},
     content::{Decoder, MediaType},
-    formats::Format,
-    loader::{SchemeUrlLoader, UrlLoader},
+    defs::{normalize_defs, DefsCollisionError},
+    diagnostics::{Diagnostic, Severity},
+    encoding::EncodingError,
+    formats::{ContextFormat, DateTimePolicy, Format, FormatPolicy},
+    graph::{RefEdge, RefGraph},
+    loader::{
+        json_from_bytes, CachedLoadError, LoadError, LoaderChainError, Origin, SchemeUrlLoader,
+        UrlLoader,
+    },
+    locate::{AllowResult, SubtreeError, UnevalPolicy},
+    options::{
+        DisplayOptions, InstanceVisitor, MatchedBranches, Metrics, ValidationOptions,
+        ValidationOutput, VisitError,
+    },
     output::{
-        AbsoluteKeywordLocation, FlagOutput, KeywordPath, OutputError, OutputUnit, SchemaToken,
+        AbsoluteKeywordLocation, FlagOutput, KeywordPath, LeafErrorRef, LimitInfo, OutputError,
+        OutputUnit, SchemaToken,
     },
+    query::TriState,
+    redact::RedactBy,
+    registry::{CompiledRegistry, SchemaRegistry},
+    resource::Resource,
+    shared::{OwnedValidationError, SharedSchemas},
+    span::span_of,
+    summary::ErrorSummary,
+    timerange::TimeRangeError,
     validator::{InstanceLocation, InstanceToken},
+    view::{AdditionalView, SchemaKind, SchemaView},
+    vocab::{Keyword, KeywordValidator, Vocabulary},
 };
 
-use std::{borrow::Cow, collections::HashMap, error::Error, fmt::Display};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    error::Error,
+    fmt::Display,
+    sync::{Arc, OnceLock},
+};
 
-use ahash::AHashMap;
+#[cfg(feature = "url")]
+use ::url;
 use regex::Regex;
 use serde_json::{Number, Value};
+use timerange::TimeRange;
+use url::Url;
 use util::*;
 
-/// Identifier to compiled schema.
+/**
+Identifier to compiled schema.
+
+Indexes are deterministic: compiling same documents, in same order and
+with same [`Compiler`] options, assigns same indexes. Validation errors
+are also reported in same order, for same instance.
+*/
 #[derive(Default, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct SchemaIndex(usize);
 
@@ -143,6 +232,7 @@ pub struct SchemaIndex(usize);
 pub struct Schemas {
     list: Vec<Schema>,
     map: HashMap<UrlPtr, usize>, // loc => schema-index
+    arena: ValueArena,
 }
 
 impl Schemas {
@@ -151,13 +241,50 @@ impl Schemas {
     }
 
     fn insert(&mut self, locs: Vec<UrlPtr>, compiled: Vec<Schema>) {
-        for (up, sch) in locs.into_iter().zip(compiled.into_iter()) {
+        for (up, mut sch) in locs.into_iter().zip(compiled) {
+            if let Some(e) = &mut sch.enum_ {
+                e.values = self.arena.enum_values(&e.values);
+            }
+            if let Some(c) = &mut sch.constant {
+                *c = self.arena.constant(c);
+            }
             let i = self.list.len();
             self.list.push(sch);
             self.map.insert(up, i);
         }
     }
 
+    // moves schemas of `other` into this, except those whose location is
+    // already present. returns new index of each schema of `other`.
+    #[cfg(feature = "parallel")]
+    fn merge(&mut self, other: Schemas) -> Vec<SchemaIndex> {
+        let mut locs = other.map.into_iter().collect::<Vec<_>>();
+        locs.sort_by_key(|(_, i)| *i);
+        debug_assert_eq!(locs.len(), other.list.len(), "each schema has one location");
+        let mut next = self.list.len();
+        let remap = locs
+            .iter()
+            .map(|(up, _)| match self.map.get(up) {
+                Some(&i) => SchemaIndex(i),
+                None => {
+                    next += 1;
+                    SchemaIndex(next - 1)
+                }
+            })
+            .collect::<Vec<_>>();
+        let (mut added_locs, mut added) = (vec![], vec![]);
+        for (mut sch, (up, i)) in other.list.into_iter().zip(locs) {
+            if remap[i].0 < self.list.len() {
+                continue; // already present
+            }
+            sch.remap(|i| remap[i.0]);
+            added_locs.push(up);
+            added.push(sch);
+        }
+        self.insert(added_locs, added);
+        remap
+    }
+
     fn get(&self, idx: SchemaIndex) -> &Schema {
         &self.list[idx.0] // todo: return bug
     }
@@ -171,10 +298,64 @@ impl Schemas {
         self.list.get(sch_index.0).is_some()
     }
 
+    /// Same as [`Schemas::len`].
     pub fn size(&self) -> usize {
         self.list.len()
     }
 
+    /// Returns number of schemas compiled, including subschemas.
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /**
+    Returns each compiled schema, with its absolute location.
+
+    Schemas are returned in the order they are compiled. Indexes are dense
+    and start at zero, i.e. `n`th schema returned is the `n`th schema
+    compiled into this instance.
+
+    ```rust
+    # use boon::*;
+    # use serde_json::json;
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://a.com/schema.json", json!({"items": {"type": "string"}}))?;
+    let mut schemas = Schemas::new();
+    compiler.compile("http://a.com/schema.json", &mut schemas)?;
+    let locs = schemas.iter().map(|(_, loc)| loc).collect::<Vec<_>>();
+    assert_eq!(locs, ["http://a.com/schema.json#", "http://a.com/schema.json#/items"]);
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+    */
+    pub fn iter(&self) -> impl Iterator<Item = (SchemaIndex, &str)> + '_ {
+        self.list
+            .iter()
+            .enumerate()
+            .map(|(i, sch)| (SchemaIndex(i), sch.loc.as_str()))
+    }
+
+    /**
+    Returns index of schema compiled at `location`, if any.
+
+    `location` is matched as absolute url with json-pointer fragment, like
+    those returned by [`Schemas::iter`]. Anchors in fragment are not
+    resolved, use [`Compiler::compile`] for them.
+    */
+    pub fn contains_location(&self, location: &str) -> Option<SchemaIndex> {
+        let UrlFrag {
+            url,
+            frag: Fragment::JsonPointer(ptr),
+        } = UrlFrag::absolute(location).ok()?
+        else {
+            return None;
+        };
+        self.map.get(&UrlPtr { url, ptr }).map(|&i| SchemaIndex(i))
+    }
+
     /**
     Validates `v` with schema identified by `sch_index`
 
@@ -188,26 +369,797 @@ impl Schemas {
         v: &'v Value,
         sch_index: SchemaIndex,
     ) -> Result<(), ValidationError<'s, 'v>> {
+        self.validate_with(v, sch_index, &ValidationOptions::default())
+            .map(|_| ())
+    }
+
+    /**
+    Validates `v` with schema identified by `sch_index`, using given `opts`.
+
+    # Panics
+
+    Panics if `sch_index` is not generated for this instance.
+    [`Schemas::contains`] can be used too ensure that it does not panic.
+    */
+    pub fn validate_with<'s, 'v>(
+        &'s self,
+        v: &'v Value,
+        sch_index: SchemaIndex,
+        opts: &ValidationOptions,
+    ) -> Result<ValidationOutput<'s>, ValidationError<'s, 'v>> {
         let Some(sch) = self.list.get(sch_index.0) else {
             panic!("Schemas::validate: schema index out of bounds");
         };
-        validator::validate(v, sch, self)
+        validator::validate(v, sch, self, opts)
+    }
+
+    /**
+    Checks whether `v` is valid against schema identified by `sch_index`,
+    using given `opts`.
+
+    This is faster than [`Schemas::validate_with`], because errors are
+    not constructed.
+
+    # Panics
+
+    Panics if `sch_index` is not generated for this instance.
+    [`Schemas::contains`] can be used too ensure that it does not panic.
+    */
+    pub fn is_valid_with(
+        &self,
+        v: &Value,
+        sch_index: SchemaIndex,
+        opts: &ValidationOptions,
+    ) -> bool {
+        let Some(sch) = self.list.get(sch_index.0) else {
+            panic!("Schemas::is_valid_with: schema index out of bounds");
+        };
+        validator::is_valid(v, sch, self, opts)
+    }
+
+    /**
+    Validates `v` with schema identified by `sch_index`, summarizing
+    errors rather than building the error tree.
+
+    Returns counts of leaf errors in the tree [`Schemas::validate`] would
+    return, along with first 10 of them. Use this for instances with
+    many errors, where the error tree gets large.
+
+    ```rust
+    # use boon::*;
+    # use serde_json::json;
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://a.com/schema.json", json!({"items": {"type": "string"}}))?;
+    let sch_index = compiler.compile("http://a.com/schema.json", &mut schemas)?;
+    let v = json!((0..100).collect::<Vec<_>>());
+    let summary = schemas.validate_summary(&v, sch_index).unwrap_err();
+    assert_eq!(summary.total, 100);
+    assert_eq!(summary.counts["type"], 100);
+    assert_eq!(summary.errors.len(), 10);
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+
+    # Panics
+
+    Panics if `sch_index` is not generated for this instance.
+    [`Schemas::contains`] can be used too ensure that it does not panic.
+    */
+    pub fn validate_summary(
+        &self,
+        v: &Value,
+        sch_index: SchemaIndex,
+    ) -> Result<(), ErrorSummary<'_>> {
+        self.validate_summary_with(v, sch_index, &ValidationOptions::default(), 10)
+    }
+
+    /**
+    Same as [`Schemas::validate_summary`], but using given `opts` and
+    retaining first `retain` leaf errors.
+
+    # Panics
+
+    Panics if `sch_index` is not generated for this instance.
+    [`Schemas::contains`] can be used too ensure that it does not panic.
+    */
+    pub fn validate_summary_with(
+        &self,
+        v: &Value,
+        sch_index: SchemaIndex,
+        opts: &ValidationOptions,
+        retain: usize,
+    ) -> Result<(), ErrorSummary<'_>> {
+        let Some(sch) = self.list.get(sch_index.0) else {
+            panic!("Schemas::validate_summary: schema index out of bounds");
+        };
+        validator::summarize(v, sch, self, opts, retain)
+    }
+
+    /**
+    Parses json `text` and validates it with schema identified by `sch_index`.
+
+    Returns the parsed instance, if it is valid.
+
+    # Panics
+
+    Panics if `sch_index` is not generated for this instance.
+    [`Schemas::contains`] can be used too ensure that it does not panic.
+    */
+    pub fn parse_and_validate(
+        &self,
+        text: &str,
+        sch_index: SchemaIndex,
+    ) -> Result<Value, InstanceError<'_>> {
+        let v: Value = serde_json::from_str(text).map_err(|e| strict::parse_error(text, e))?;
+        self.validate_owned(v, sch_index)
+    }
+
+    /**
+    Same as [`Schemas::parse_and_validate`], but fails with
+    [`InstanceError::DuplicateKey`] if any object in `text` has
+    duplicate keys, rather than keeping the last one.
+
+    # Panics
+
+    Panics if `sch_index` is not generated for this instance.
+    [`Schemas::contains`] can be used too ensure that it does not panic.
+    */
+    pub fn parse_and_validate_strict(
+        &self,
+        text: &str,
+        sch_index: SchemaIndex,
+    ) -> Result<Value, InstanceError<'_>> {
+        strict::check_duplicate_keys(text)?;
+        self.parse_and_validate(text, sch_index)
+    }
+
+    /**
+    Same as [`Schemas::parse_and_validate`], but takes json `bytes`
+    in utf-8 or utf-16, with optional byte order mark.
+
+    Encoding problems are reported as [`InstanceError::Decode`]
+    with detected encoding as `format`.
+    */
+    pub fn parse_and_validate_bytes(
+        &self,
+        bytes: &[u8],
+        sch_index: SchemaIndex,
+    ) -> Result<Value, InstanceError<'_>> {
+        let text = encoding::decode(bytes)?;
+        self.parse_and_validate(&text, sch_index)
+    }
+
+    /**
+    Decodes cbor `bytes` and validates it with schema identified by `sch_index`.
+
+    Returns the decoded instance, if it is valid. Byte strings are
+    converted to base64 strings; see [`InstanceError::Unsupported`]
+    for values that cannot be represented in json.
+
+    # Panics
+
+    Panics if `sch_index` is not generated for this instance.
+    [`Schemas::contains`] can be used too ensure that it does not panic.
+    */
+    #[cfg(feature = "cbor")]
+    pub fn validate_cbor(
+        &self,
+        bytes: &[u8],
+        sch_index: SchemaIndex,
+    ) -> Result<Value, InstanceError<'_>> {
+        let v = binary::cbor_to_json(bytes)?;
+        self.validate_owned(v, sch_index)
+    }
+
+    /**
+    Decodes msgpack `bytes` and validates it with schema identified by `sch_index`.
+
+    Returns the decoded instance, if it is valid. Binary values are
+    converted to base64 strings; see [`InstanceError::Unsupported`]
+    for values that cannot be represented in json.
+
+    # Panics
+
+    Panics if `sch_index` is not generated for this instance.
+    [`Schemas::contains`] can be used too ensure that it does not panic.
+    */
+    #[cfg(feature = "msgpack")]
+    pub fn validate_msgpack(
+        &self,
+        bytes: &[u8],
+        sch_index: SchemaIndex,
+    ) -> Result<Value, InstanceError<'_>> {
+        let v = binary::msgpack_to_json(bytes)?;
+        self.validate_owned(v, sch_index)
+    }
+
+    /**
+    Resolves `ref_` against the base url of schema identified by `from`,
+    honoring `$id` of enclosing resource, as compiler does for `$ref`.
+
+    Returns the absolute url with fragment. Unlike [`Compiler::resolve`],
+    the url is not looked up: anchors are not replaced by json-pointer,
+    and url of a resource embedded using `$id` is not replaced by its
+    location in the document. Resolving the returned url with
+    [`Compiler::resolve`] gives the location of referenced schema.
+
+    # Panics
+
+    Panics if `from` is not generated for this instance.
+    [`Schemas::contains`] can be used too ensure that it does not panic.
+    */
+    pub fn resolve_ref(&self, from: SchemaIndex, ref_: &str) -> Result<String, CompileError> {
+        let Some(sch) = self.list.get(from.0) else {
+            panic!("Schemas::resolve_ref: schema index out of bounds");
+        };
+        let Some(base_url) = &self.get(sch.resource).base_url else {
+            return Err(CompileError::Bug(
+                format!("no base url for resource of {}", sch.loc).into(),
+            ));
+        };
+        Ok(UrlFrag::join(base_url, ref_)?.to_string())
+    }
+
+    /**
+    Returns draft used to compile the schema identified by `sch_index`.
+
+    For schemas using custom metaschema, this is the draft that
+    metaschema is based on.
+
+    Returns `None` if `sch_index` is not generated for this instance.
+    */
+    pub fn draft_of(&self, sch_index: SchemaIndex) -> Option<Draft> {
+        let sch = self.list.get(sch_index.0)?;
+        Draft::from_version(sch.draft_version)
+    }
+
+    /**
+    Returns example of valid value, provided by schema identified by `sch_index`.
+
+    This is first entry of `examples`, falling back to `default`.
+
+    # Panics
+
+    Panics if `sch_index` is not generated for this instance.
+    [`Schemas::contains`] can be used too ensure that it does not panic.
+    */
+    pub fn example_for(&self, sch_index: SchemaIndex) -> Option<&Value> {
+        let Some(sch) = self.list.get(sch_index.0) else {
+            panic!("Schemas::example_for: schema index out of bounds");
+        };
+        sch.example()
+    }
+
+    /**
+    Returns `pattern` of schema identified by `sch_index`, as compiled.
+
+    This is the regex actually used for validation, after conversion from
+    ECMA-262 syntax, and anchoring if [`Compiler::enable_anchored_patterns`]
+    is used.
+
+    # Panics
+
+    Panics if `sch_index` is not generated for this instance.
+    [`Schemas::contains`] can be used too ensure that it does not panic.
+    */
+    pub fn pattern(&self, sch_index: SchemaIndex) -> Option<&str> {
+        let Some(sch) = self.list.get(sch_index.0) else {
+            panic!("Schemas::pattern: schema index out of bounds");
+        };
+        sch.pattern.as_ref().map(Regex::as_str)
+    }
+
+    /**
+    Returns keys of `patternProperties` of schema identified by `sch_index`,
+    as compiled. see [`Schemas::pattern`].
+
+    # Panics
+
+    Panics if `sch_index` is not generated for this instance.
+    [`Schemas::contains`] can be used too ensure that it does not panic.
+    */
+    pub fn pattern_properties(&self, sch_index: SchemaIndex) -> Vec<&str> {
+        let Some(sch) = self.list.get(sch_index.0) else {
+            panic!("Schemas::pattern_properties: schema index out of bounds");
+        };
+        sch.pattern_properties
+            .iter()
+            .map(|(regex, _)| regex.as_str())
+            .collect()
+    }
+
+    /**
+    Returns subschemas of schema identified by `sch_index`, that apply to
+    value at `loc` in instance `v`.
+
+    Subschemas are reached through `properties`, `patternProperties`,
+    `additionalProperties`, item keywords, and references. In-place
+    applicators contribute only if their condition holds for the
+    actual data: `anyOf`/`oneOf` branches that `v` is valid against,
+    `then` or `else` depending on `if`, `dependentSchemas` of present
+    properties. Conditions are checked against each subschema on its
+    own, without annotations from sibling keywords.
+
+    Returns empty list, if `loc` does not exist in `v`. Intended to be
+    used after successful validation; for invalid instance, result may
+    include subschemas that reject the value.
+
+    # Panics
+
+    Panics if `sch_index` is not generated for this instance.
+    [`Schemas::contains`] can be used too ensure that it does not panic.
+    */
+    pub fn schemas_for_location(
+        &self,
+        sch_index: SchemaIndex,
+        v: &Value,
+        loc: &InstanceLocation,
+    ) -> Vec<SchemaIndex> {
+        if !self.contains(sch_index) {
+            panic!("Schemas::schemas_for_location: schema index out of bounds");
+        }
+        locate::schemas_for_location(self, sch_index, v, loc)
+    }
+
+    /**
+    Validates value at `loc` in instance `v`, against subschemas of
+    schema identified by `sch_index` that apply to it, as found by
+    [`Schemas::schemas_for_location`]. Rest of `v` is looked at only
+    to find these subschemas, so this is cheaper than validating `v`
+    for small change to large document.
+
+    `policy` decides how `unevaluatedProperties` and `unevaluatedItems`
+    of ancestors are treated, see [`UnevalPolicy`]. Instance locations
+    in errors are relative to `v`.
+
+    ```rust
+    # use boon::*;
+    # use serde_json::json;
+    # let mut compiler = Compiler::new();
+    let schema = json!({
+        "properties": { "a": { "type": "string" } },
+        "unevaluatedProperties": false
+    });
+    # compiler.add_resource("schema.json", schema)?;
+    # let mut schemas = Schemas::new();
+    # let sch_index = compiler.compile("schema.json", &mut schemas)?;
+    let doc = json!({"a": "x", "b": 1});
+    let loc = InstanceLocation { tokens: vec!["b".into()] };
+    let result = schemas.validate_at(&doc, sch_index, &loc, UnevalPolicy::Forbid);
+    assert!(matches!(result, Err(SubtreeError::Unevaluated { .. })));
+    let result = schemas.validate_at(&doc, sch_index, &loc, UnevalPolicy::Emulate);
+    assert!(matches!(result, Err(SubtreeError::Validation(_))));
+    let result = schemas.validate_at(&doc, sch_index, &loc, UnevalPolicy::Ignore);
+    assert!(result.is_ok());
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+
+    # Panics
+
+    Panics if `sch_index` is not generated for this instance.
+    [`Schemas::contains`] can be used too ensure that it does not panic.
+    */
+    pub fn validate_at<'s, 'v>(
+        &'s self,
+        v: &'v Value,
+        sch_index: SchemaIndex,
+        loc: &InstanceLocation,
+        policy: UnevalPolicy,
+    ) -> Result<(), SubtreeError<'s, 'v>> {
+        if !self.contains(sch_index) {
+            panic!("Schemas::validate_at: schema index out of bounds");
+        }
+        locate::validate_at(self, sch_index, v, loc, policy)
+    }
+
+    /**
+    Tells whether property `candidate` may be added to object at `loc`
+    in instance `v`, as per schema identified by `sch_index`.
+
+    Subschemas governing the object are found as in [`Schemas::schemas_for_location`],
+    so conditionals are resolved against `v` as it is, without `candidate`.
+    Result includes subschemas that would apply to value of `candidate`,
+    which can be used to look further into that value.
+
+    ```rust
+    # use boon::*;
+    # use serde_json::json;
+    # let mut compiler = Compiler::new();
+    let schema = json!({
+        "properties": { "name": { "type": "string" } },
+        "additionalProperties": false
+    });
+    # compiler.add_resource("schema.json", schema)?;
+    # let mut schemas = Schemas::new();
+    # let sch_index = compiler.compile("schema.json", &mut schemas)?;
+    let doc = json!({});
+    let root = InstanceLocation::default();
+    let result = schemas.property_allowed(sch_index, &doc, &root, "name");
+    assert!(matches!(result, AllowResult::Property(_)));
+    let result = schemas.property_allowed(sch_index, &doc, &root, "other");
+    assert_eq!(result, AllowResult::Rejected(sch_index));
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+
+    # Panics
+
+    Panics if `sch_index` is not generated for this instance.
+    [`Schemas::contains`] can be used too ensure that it does not panic.
+    */
+    pub fn property_allowed(
+        &self,
+        sch_index: SchemaIndex,
+        v: &Value,
+        loc: &InstanceLocation,
+        candidate: &str,
+    ) -> AllowResult {
+        if !self.contains(sch_index) {
+            panic!("Schemas::property_allowed: schema index out of bounds");
+        }
+        locate::property_allowed(self, sch_index, v, loc, candidate)
+    }
+
+    /**
+    Tells whether object at `path`, within instances valid against schema
+    identified by `sch_index`, may have properties other than those
+    declared in schema, without looking at any instance.
+
+    `path` is list of property names from the root. Schemas reached
+    through `properties`, `patternProperties`, `additionalProperties`,
+    `$ref` and `allOf` are considered. Returns [`TriState::Unknown`] if
+    answer depends on instance, for example due to `anyOf`, `if` or
+    `patternProperties`.
+
+    ```rust
+    # use boon::*;
+    # use serde_json::json;
+    # let mut compiler = Compiler::new();
+    let schema = json!({
+        "properties": {
+            "headers": { "additionalProperties": false }
+        }
+    });
+    # compiler.add_resource("schema.json", schema)?;
+    # let mut schemas = Schemas::new();
+    # let sch_index = compiler.compile("schema.json", &mut schemas)?;
+    let result = schemas.allows_additional_properties(sch_index, &["headers"]);
+    assert_eq!(result, TriState::No);
+    assert_eq!(schemas.allows_additional_properties(sch_index, &[]), TriState::Yes);
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+
+    # Panics
+
+    Panics if `sch_index` is not generated for this instance.
+    [`Schemas::contains`] can be used too ensure that it does not panic.
+    */
+    pub fn allows_additional_properties(&self, sch_index: SchemaIndex, path: &[&str]) -> TriState {
+        if !self.contains(sch_index) {
+            panic!("Schemas::allows_additional_properties: schema index out of bounds");
+        }
+        query::allows_additional_properties(self, sch_index, path)
+    }
+
+    /**
+    Tells whether property at `path` must be present in its parent
+    object, within instances valid against schema identified by
+    `sch_index`, without looking at any instance.
+
+    Considers same subschemas as [`Schemas::allows_additional_properties`].
+    Returns [`TriState::Unknown`] for empty `path`, or if answer depends
+    on instance, for example due to `dependentRequired`.
+
+    # Panics
+
+    Panics if `sch_index` is not generated for this instance.
+    [`Schemas::contains`] can be used too ensure that it does not panic.
+    */
+    pub fn is_property_required(&self, sch_index: SchemaIndex, path: &[&str]) -> TriState {
+        if !self.contains(sch_index) {
+            panic!("Schemas::is_property_required: schema index out of bounds");
+        }
+        query::is_property_required(self, sch_index, path)
+    }
+
+    /**
+    Returns set of types, which values valid against schema identified
+    by `sch_index` may have, without looking at any instance.
+
+    The answer is conservative: with default [`ValidationOptions`], a
+    value whose type is not in the set is surely invalid, but a value
+    whose type is in the set may still be invalid. Options may accept
+    more, for example with [`ValidationOptions::loose_enum_scalars`] a
+    string may match number in `enum`, though the set has only
+    [`Type::Number`]. `type`, `const` and `enum` narrow the set, which is
+    intersected across `$ref` and `allOf`, and united across `anyOf`,
+    `oneOf` and `then`/`else`. Keywords like `not`, `$dynamicRef` and
+    custom keywords are not analyzed, and recursion through `$ref`
+    yields all types, so they never narrow the set.
+
+    [`Type::Integer`] is present only when [`Type::Number`] is not, that
+    is when only integers are accepted. Empty set means that no value is
+    valid.
+
+    ```rust
+    # use boon::*;
+    # use serde_json::json;
+    # let mut compiler = Compiler::new();
+    let schema = json!({
+        "oneOf": [
+            { "type": "string", "format": "date" },
+            { "type": "object", "properties": { "kind": { "const": "event" } } },
+            { "enum": [1, 2, 3] }
+        ]
+    });
+    # compiler.add_resource("schema.json", schema)?;
+    # let mut schemas = Schemas::new();
+    # let sch_index = compiler.compile("schema.json", &mut schemas)?;
+    let types = schemas.accepted_types(sch_index);
+    assert_eq!(
+        types.iter().collect::<Vec<_>>(),
+        [Type::Number, Type::String, Type::Object]
+    );
+    assert!(!types.contains(Type::Array));
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+
+    # Panics
+
+    Panics if `sch_index` is not generated for this instance.
+    [`Schemas::contains`] can be used too ensure that it does not panic.
+    */
+    pub fn accepted_types(&self, sch_index: SchemaIndex) -> Types {
+        if !self.contains(sch_index) {
+            panic!("Schemas::accepted_types: schema index out of bounds");
+        }
+        query::accepted_types(self, sch_index)
+    }
+
+    /**
+    Checks whether values of type `t` may be valid against schema
+    identified by `sch_index`, like for rejecting request body from its
+    first byte, without parsing it.
+
+    Answer is same as [`Schemas::accepted_types`]. It is computed on
+    first query for the schema and cached, so later calls do not
+    allocate. [`Type::Number`]
+    is accepted if any number is, and [`Type::Integer`] if any integral
+    number is.
+    As with [`Schemas::accepted_types`], `false` means that values of type
+    `t` are surely invalid with default [`ValidationOptions`], but `true`
+    does not mean that they are valid.
+
+    ```rust
+    # use boon::*;
+    # use serde_json::json;
+    # let mut compiler = Compiler::new();
+    let schema = json!({
+        "$ref": "#/$defs/named",
+        "$defs": { "named": { "type": "object", "required": ["name"] } }
+    });
+    # compiler.add_resource("schema.json", schema)?;
+    # let mut schemas = Schemas::new();
+    # let sch_index = compiler.compile("schema.json", &mut schemas)?;
+    assert!(schemas.accepts_type(sch_index, Type::Object));
+    assert!(!schemas.accepts_type(sch_index, Type::Array));
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+
+    # Panics
+
+    Panics if `sch_index` is not generated for this instance.
+    [`Schemas::contains`] can be used too ensure that it does not panic.
+    */
+    pub fn accepts_type(&self, sch_index: SchemaIndex, t: Type) -> bool {
+        if !self.contains(sch_index) {
+            panic!("Schemas::accepts_type: schema index out of bounds");
+        }
+        query::accepts_type(self, sch_index, t)
+    }
+
+    /**
+    Returns graph of `$ref`, `$dynamicRef` and `$recursiveRef` between
+    schema resources compiled so far.
+
+    `$dynamicRef` and `$recursiveRef` are shown with their initial target,
+    as resolution at validation depends on dynamic scope.
+
+    ```
+    # use boon::*;
+    # use serde_json::json;
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://a.com/a.json", json!({"$ref": "b.json"}))?;
+    compiler.add_resource("http://a.com/b.json", json!({"items": {"$ref": "a.json"}}))?;
+    let mut schemas = Schemas::new();
+    compiler.compile("http://a.com/a.json", &mut schemas)?;
+    let graph = schemas.reference_graph();
+    assert_eq!(graph.nodes, ["http://a.com/a.json", "http://a.com/b.json"]);
+    assert_eq!(graph.cycles(), [[0, 1]]);
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+    */
+    pub fn reference_graph(&self) -> RefGraph {
+        graph::reference_graph(self)
+    }
+
+    /**
+    Returns json, schema identified by `sch_index` is compiled from.
+
+    Returns `None` unless [`Compiler::enable_source_retention`] is used.
+
+    ```rust
+    # use boon::*;
+    # use serde_json::json;
+    let mut compiler = Compiler::new();
+    compiler.enable_source_retention();
+    compiler.add_resource("schema.json", json!({"items": {"type": "string"}}))?;
+    let mut schemas = Schemas::new();
+    compiler.compile("schema.json", &mut schemas)?;
+    let sch_index = compiler.compile("schema.json#/items", &mut schemas)?;
+    assert_eq!(schemas.source(sch_index), Some(&json!({"type": "string"})));
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+
+    # Panics
+
+    Panics if `sch_index` is not generated for this instance.
+    */
+    pub fn source(&self, sch_index: SchemaIndex) -> Option<&Value> {
+        let Some(sch) = self.list.get(sch_index.0) else {
+            panic!("Schemas::source: schema index out of bounds");
+        };
+        let (doc, ptr) = sch.source.as_ref()?;
+        doc.pointer(ptr)
+    }
+
+    /**
+    Same as [`Schemas::source`], but rendered as pretty-printed json.
+
+    # Panics
+
+    Panics if `sch_index` is not generated for this instance.
+    */
+    pub fn source_pretty(&self, sch_index: SchemaIndex) -> Option<String> {
+        let v = self.source(sch_index)?;
+        serde_json::to_string_pretty(v).ok()
+    }
+
+    /**
+    Returns view of schema identified by `sch_index`, to introspect
+    its subschemas. see [`SchemaView`] for example.
+
+    # Panics
+
+    Panics if `sch_index` is not generated for this instance.
+    */
+    pub fn view(&self, sch_index: SchemaIndex) -> SchemaView<'_> {
+        if !self.contains(sch_index) {
+            panic!("Schemas::view: schema index out of bounds");
+        }
+        SchemaView::new(self, sch_index)
+    }
+
+    /**
+    Replaces values in `v`, that are marked sensitive by schema
+    identified by `sch_index`, with `"[REDACTED]"`. Returns locations
+    of replaced values.
+
+    Values are marked using `writeOnly` or custom annotation, as selected
+    by `by`. Schema is evaluated against `v` like in [`Schemas::validate`],
+    so that `$ref`, `if`/`then`, `anyOf` etc. are followed as per actual
+    data. Annotations of subschemas that fail are ignored. But if `v` is
+    invalid, they are honored too, so that sensitive values are not missed.
+
+    Locations are returned in ascending order. Values within a redacted
+    value are not reported.
+
+    ```rust
+    # use boon::*;
+    # use serde_json::json;
+    let mut compiler = Compiler::new();
+    compiler.add_resource("schema.json", json!({
+        "properties": { "password": { "writeOnly": true } }
+    }))?;
+    let mut schemas = Schemas::new();
+    let sch_index = compiler.compile("schema.json", &mut schemas)?;
+    let mut v = json!({"user": "alice", "password": "secret"});
+    let redacted = schemas.redact(&mut v, sch_index, RedactBy::WriteOnly);
+    assert_eq!(v, json!({"user": "alice", "password": "[REDACTED]"}));
+    assert_eq!(redacted[0].to_string(), "/password");
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+
+    # Panics
+
+    Panics if `sch_index` is not generated for this instance.
+    */
+    pub fn redact(
+        &self,
+        v: &mut Value,
+        sch_index: SchemaIndex,
+        by: RedactBy,
+    ) -> Vec<InstanceLocation<'static>> {
+        let Some(sch) = self.list.get(sch_index.0) else {
+            panic!("Schemas::redact: schema index out of bounds");
+        };
+        redact::redact(v, sch, self, by)
+    }
+
+    /**
+    Validates `v` with schema identified by `sch_index`, and if it is
+    valid, deserializes it into `T`.
+
+    If `v` is valid, but `T` rejects it, as happens when schema and `T`
+    drift apart, [`CombinedError::Deserialization`] locates the offending
+    value same way as validation errors do.
+
+    ```rust
+    # use boon::*;
+    # use serde_json::json;
+    # let mut compiler = Compiler::new();
+    # compiler.add_resource("schema.json", json!({"items": {"type": "integer"}}))?;
+    # let mut schemas = Schemas::new();
+    # let sch_index = compiler.compile("schema.json", &mut schemas)?;
+    let instance = json!([1, 2]);
+    let list: Vec<u8> = schemas.validate_into(&instance, sch_index).unwrap();
+    assert_eq!(list, [1, 2]);
+    let instance = json!([1, 256]);
+    let result = schemas.validate_into::<Vec<u8>>(&instance, sch_index);
+    let Err(CombinedError::Deserialization { instance_location, .. }) = result else {
+        panic!("256 must not fit in u8");
+    };
+    assert_eq!(instance_location.to_string(), "/1");
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+
+    # Panics
+
+    Panics if `sch_index` is not generated for this instance.
+    */
+    #[cfg(feature = "serde")]
+    pub fn validate_into<'v, T: serde::de::DeserializeOwned>(
+        &self,
+        v: &'v Value,
+        sch_index: SchemaIndex,
+    ) -> Result<T, CombinedError<'_, 'v>> {
+        self.validate(v, sch_index)
+            .map_err(CombinedError::Validation)?;
+        typed::deserialize(v)
+    }
+
+    fn validate_owned(&self, v: Value, sch_index: SchemaIndex) -> Result<Value, InstanceError<'_>> {
+        if let Err(e) = self.validate(&v, sch_index) {
+            return Err(InstanceError::Validation(e.rebind(None)));
+        }
+        Ok(v)
     }
 }
 
 #[derive(Default)]
 struct Schema {
     draft_version: usize,
+    float_integers: bool, // whether 1.0 is integer
     idx: SchemaIndex,
     loc: String,
     resource: SchemaIndex,
-    dynamic_anchors: HashMap<String, SchemaIndex>,
+    base_url: Option<Url>, // only for resource
+    dynamic_anchors: HMap<String, SchemaIndex>,
     all_props_evaluated: bool,
     all_items_evaluated: bool,
     num_items_evaluated: usize,
+    leaf: bool,                           // see `Schema::is_leaf`
+    groups: KeywordGroups,                // see `Schema::keyword_groups`
+    accepted: OnceLock<(Types, bool)>,    // see `query::accepted`
+    source: Option<(Arc<Value>, String)>, // document and json-pointer
+
+    // annotations --
+    default: Option<Value>,
+    examples: Vec<Value>,
+    flags: Vec<String>, // annotations with value `true`, like `writeOnly`
 
     // type agnostic --
-    boolean: Option<bool>, // boolean schema
+    boolean: Option<bool>,      // boolean schema, or folded to it
+    literal_bool: Option<bool>, // authored as boolean schema
     ref_: Option<SchemaIndex>,
     recursive_ref: Option<SchemaIndex>,
     recursive_anchor: bool,
@@ -215,7 +1167,7 @@ struct Schema {
     dynamic_anchor: Option<String>,
     types: Types,
     enum_: Option<Enum>,
-    constant: Option<Value>,
+    constant: Option<Arc<Value>>, // shared, see `ValueArena`
     not: Option<SchemaIndex>,
     all_of: Vec<SchemaIndex>,
     any_of: Vec<SchemaIndex>,
@@ -224,12 +1176,17 @@ struct Schema {
     then: Option<SchemaIndex>,
     else_: Option<SchemaIndex>,
     format: Option<Format>,
+    format_policy: Option<FormatPolicy>, // only with built-in format
+    context_format: Option<ContextFormat>,
+    time_range: Option<TimeRange>, // see `Compiler::enable_extension`
+    keywords: Vec<(&'static str, Box<dyn KeywordValidator>)>, // custom keywords
+    ignored: Vec<(String, IgnoreReason)>, // keywords not enforced
 
     // object --
     min_properties: Option<usize>,
     max_properties: Option<usize>,
     required: Vec<String>,
-    properties: AHashMap<String, SchemaIndex>,
+    properties: HMap<String, SchemaIndex>, // iterated only in sorted order
     pattern_properties: Vec<(Regex, SchemaIndex)>,
     property_names: Option<SchemaIndex>,
     additional_properties: Option<Additional>,
@@ -267,12 +1224,39 @@ struct Schema {
     multiple_of: Option<Number>,
 }
 
+/*
+Set of keyword groups present in schema, so that validator skips
+absent groups with single bit test, rather than checking each keyword.
+*/
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct KeywordGroups(u8);
+
+impl KeywordGroups {
+    const REFS: Self = Self(1 << 0); // $recursiveRef, $dynamicRef
+    const CONDITIONALS: Self = Self(1 << 1); // not, allOf, anyOf, oneOf, if
+    const UNEVAL: Self = Self(1 << 2);
+    const OBJECT: Self = Self(1 << 3);
+    const ARRAY: Self = Self(1 << 4);
+    const STRING: Self = Self(1 << 5);
+    const NUMBER: Self = Self(1 << 6);
+
+    fn contains(self, group: Self) -> bool {
+        self.0 & group.0 != 0
+    }
+
+    fn set(&mut self, group: Self, present: bool) {
+        if present {
+            self.0 |= group.0;
+        }
+    }
+}
+
 #[derive(Debug)]
 struct Enum {
     /// types that occur in enum
     types: Types,
-    /// values in enum
-    values: Vec<Value>,
+    /// values in enum, shared, see `ValueArena`
+    values: Arc<[Value]>,
 }
 
 #[derive(Debug)]
@@ -305,6 +1289,543 @@ impl Schema {
             ..Default::default()
         }
     }
+
+    fn example(&self) -> Option<&Value> {
+        self.examples.first().or(self.default.as_ref())
+    }
+
+    // whether this schema has no assertions or applicators, so that it
+    // behaves same as `true` schema. `enum` and `not` are not considered,
+    // if `but_enum_or_not`.
+    fn has_no_effect(&self, but_enum_or_not: bool) -> bool {
+        // no `..`, so that new fields are not missed
+        let Schema {
+            draft_version: _,
+            float_integers: _,
+            idx: _,
+            loc: _,
+            resource: _,
+            base_url: _,
+            dynamic_anchors,
+            all_props_evaluated: _,
+            all_items_evaluated: _,
+            num_items_evaluated: _,
+            leaf: _,
+            groups: _,
+            accepted: _,
+            source: _,
+            default: _,
+            examples: _,
+            flags: _, // see `Validator::annotate`
+            boolean,
+            literal_bool: _,
+            ref_,
+            recursive_ref,
+            recursive_anchor,
+            dynamic_ref,
+            dynamic_anchor,
+            types,
+            enum_,
+            constant,
+            not,
+            all_of,
+            any_of,
+            one_of,
+            if_,
+            then,
+            else_,
+            format,
+            format_policy: _,
+            context_format,
+            time_range,
+            keywords,
+            ignored,
+            min_properties,
+            max_properties,
+            required,
+            properties,
+            pattern_properties,
+            property_names,
+            additional_properties,
+            dependent_required,
+            dependent_schemas,
+            dependencies,
+            unevaluated_properties,
+            min_items,
+            max_items,
+            unique_items,
+            min_contains,
+            max_contains,
+            contains,
+            items,
+            additional_items,
+            prefix_items,
+            items2020,
+            unevaluated_items,
+            min_length,
+            max_length,
+            pattern,
+            content_encoding,
+            content_media_type,
+            content_schema,
+            minimum,
+            maximum,
+            exclusive_minimum,
+            exclusive_maximum,
+            multiple_of,
+        } = self;
+        dynamic_anchors.is_empty()
+            && boolean.is_none()
+            && ref_.is_none()
+            && recursive_ref.is_none()
+            && !recursive_anchor
+            && dynamic_ref.is_none()
+            && dynamic_anchor.is_none()
+            && (types.is_empty() || types.is_all())
+            && (but_enum_or_not || enum_.is_none())
+            && constant.is_none()
+            && (but_enum_or_not || not.is_none())
+            && all_of.is_empty()
+            && any_of.is_empty()
+            && one_of.is_empty()
+            && if_.is_none()
+            && then.is_none()
+            && else_.is_none()
+            && format.is_none()
+            && context_format.is_none()
+            && time_range.is_none()
+            && keywords.is_empty()
+            && ignored.is_empty()
+            && min_properties.is_none()
+            && max_properties.is_none()
+            && required.is_empty()
+            && properties.is_empty()
+            && pattern_properties.is_empty()
+            && property_names.is_none()
+            && additional_properties.is_none()
+            && dependent_required.is_empty()
+            && dependent_schemas.is_empty()
+            && dependencies.is_empty()
+            && unevaluated_properties.is_none()
+            && min_items.is_none()
+            && max_items.is_none()
+            && !unique_items
+            && min_contains.is_none()
+            && max_contains.is_none()
+            && contains.is_none()
+            && items.is_none()
+            && additional_items.is_none()
+            && prefix_items.is_empty()
+            && items2020.is_none()
+            && unevaluated_items.is_none()
+            && min_length.is_none()
+            && max_length.is_none()
+            && pattern.is_none()
+            && content_encoding.is_none()
+            && content_media_type.is_none()
+            && content_schema.is_none()
+            && minimum.is_none()
+            && maximum.is_none()
+            && exclusive_minimum.is_none()
+            && exclusive_maximum.is_none()
+            && multiple_of.is_none()
+    }
+
+    // whether this schema has only assertions on primitive values, so that
+    // items can be checked by `validator::is_leaf_valid`, without
+    // setting up a validator for each.
+    fn is_leaf(&self) -> bool {
+        if self.boolean.is_some() {
+            return true;
+        }
+        // no `..`, so that new fields are not missed
+        let Schema {
+            draft_version: _,
+            float_integers: _,
+            idx: _,
+            loc: _,
+            resource: _,
+            base_url: _,
+            dynamic_anchors,
+            all_props_evaluated: _,
+            all_items_evaluated: _,
+            num_items_evaluated: _,
+            leaf: _,
+            groups: _,
+            accepted: _,
+            source: _,
+            default: _,
+            examples: _,
+            flags: _, // see `Validator::annotate`
+            boolean: _,
+            literal_bool: _,
+            ref_,
+            recursive_ref,
+            recursive_anchor,
+            dynamic_ref,
+            dynamic_anchor,
+            types: _,
+            enum_: _,
+            constant: _,
+            not,
+            all_of,
+            any_of,
+            one_of,
+            if_,
+            then,
+            else_,
+            format,
+            format_policy: _,
+            context_format,
+            time_range,
+            keywords,
+            ignored,
+            min_properties,
+            max_properties,
+            required,
+            properties,
+            pattern_properties,
+            property_names,
+            additional_properties,
+            dependent_required,
+            dependent_schemas,
+            dependencies,
+            unevaluated_properties,
+            min_items,
+            max_items,
+            unique_items,
+            min_contains,
+            max_contains,
+            contains,
+            items,
+            additional_items,
+            prefix_items,
+            items2020,
+            unevaluated_items,
+            min_length: _,
+            max_length: _,
+            pattern: _,
+            content_encoding,
+            content_media_type,
+            content_schema,
+            minimum: _,
+            maximum: _,
+            exclusive_minimum: _,
+            exclusive_maximum: _,
+            multiple_of: _,
+        } = self;
+        // flagged values must reach the validator, see `Schemas::redact`
+        dynamic_anchors.is_empty()
+            && ref_.is_none()
+            && recursive_ref.is_none()
+            && !recursive_anchor
+            && dynamic_ref.is_none()
+            && dynamic_anchor.is_none()
+            && not.is_none()
+            && all_of.is_empty()
+            && any_of.is_empty()
+            && one_of.is_empty()
+            && if_.is_none()
+            && then.is_none()
+            && else_.is_none()
+            && format.is_none()
+            && context_format.is_none()
+            && time_range.is_none()
+            && keywords.is_empty()
+            && ignored.is_empty()
+            && min_properties.is_none()
+            && max_properties.is_none()
+            && required.is_empty()
+            && properties.is_empty()
+            && pattern_properties.is_empty()
+            && property_names.is_none()
+            && additional_properties.is_none()
+            && dependent_required.is_empty()
+            && dependent_schemas.is_empty()
+            && dependencies.is_empty()
+            && unevaluated_properties.is_none()
+            && min_items.is_none()
+            && max_items.is_none()
+            && !unique_items
+            && min_contains.is_none()
+            && max_contains.is_none()
+            && contains.is_none()
+            && items.is_none()
+            && additional_items.is_none()
+            && prefix_items.is_empty()
+            && items2020.is_none()
+            && unevaluated_items.is_none()
+            && content_encoding.is_none()
+            && content_media_type.is_none()
+            && content_schema.is_none()
+    }
+
+    // keyword groups validated by `Validator`, outside of `Validator::validate`.
+    // must be kept in sync with `refs_validate`, `cond_validate` etc.
+    fn keyword_groups(&self) -> KeywordGroups {
+        // no `..`, so that new fields are not missed
+        let Schema {
+            draft_version: _,
+            float_integers: _,
+            idx: _,
+            loc: _,
+            resource: _,
+            base_url: _,
+            dynamic_anchors: _,
+            all_props_evaluated: _,
+            all_items_evaluated: _,
+            num_items_evaluated: _,
+            leaf: _,
+            groups: _,
+            accepted: _,
+            source: _,
+            default: _,
+            examples: _,
+            flags: _,
+            boolean: _,
+            literal_bool: _,
+            ref_: _,
+            recursive_ref,
+            recursive_anchor: _,
+            dynamic_ref,
+            dynamic_anchor: _,
+            types: _,
+            enum_: _,
+            constant: _,
+            not,
+            all_of,
+            any_of,
+            one_of,
+            if_,
+            then: _, // only with `if_`
+            else_: _,
+            format: _,
+            format_policy: _,
+            context_format: _,
+            time_range: _,
+            keywords: _,
+            ignored: _,
+            min_properties,
+            max_properties,
+            required,
+            properties,
+            pattern_properties,
+            property_names,
+            additional_properties,
+            dependent_required,
+            dependent_schemas,
+            dependencies,
+            unevaluated_properties,
+            min_items,
+            max_items,
+            unique_items,
+            min_contains: _, // only with `contains`
+            max_contains: _,
+            contains,
+            items,
+            additional_items,
+            prefix_items,
+            items2020,
+            unevaluated_items,
+            min_length,
+            max_length,
+            pattern,
+            content_encoding,
+            content_media_type,
+            content_schema: _, // only with `content_media_type`
+            minimum,
+            maximum,
+            exclusive_minimum,
+            exclusive_maximum,
+            multiple_of,
+        } = self;
+        let mut g = KeywordGroups::default();
+        g.set(
+            KeywordGroups::REFS,
+            recursive_ref.is_some() || dynamic_ref.is_some(),
+        );
+        g.set(
+            KeywordGroups::CONDITIONALS,
+            not.is_some()
+                || !all_of.is_empty()
+                || !any_of.is_empty()
+                || !one_of.is_empty()
+                || if_.is_some(),
+        );
+        g.set(
+            KeywordGroups::UNEVAL,
+            unevaluated_properties.is_some() || unevaluated_items.is_some(),
+        );
+        g.set(
+            KeywordGroups::OBJECT,
+            min_properties.is_some()
+                || max_properties.is_some()
+                || !required.is_empty()
+                || !properties.is_empty()
+                || !pattern_properties.is_empty()
+                || property_names.is_some()
+                || additional_properties.is_some()
+                || !dependent_required.is_empty()
+                || !dependent_schemas.is_empty()
+                || !dependencies.is_empty(),
+        );
+        g.set(
+            KeywordGroups::ARRAY,
+            min_items.is_some()
+                || max_items.is_some()
+                || *unique_items
+                || contains.is_some()
+                || items.is_some()
+                || additional_items.is_some()
+                || !prefix_items.is_empty()
+                || items2020.is_some(),
+        );
+        g.set(
+            KeywordGroups::STRING,
+            min_length.is_some()
+                || max_length.is_some()
+                || pattern.is_some()
+                || content_encoding.is_some()
+                || content_media_type.is_some(),
+        );
+        g.set(
+            KeywordGroups::NUMBER,
+            minimum.is_some()
+                || maximum.is_some()
+                || exclusive_minimum.is_some()
+                || exclusive_maximum.is_some()
+                || multiple_of.is_some(),
+        );
+        g
+    }
+
+    // replaces each index referring to other schemas, using `f`.
+    #[cfg(feature = "parallel")]
+    fn remap(&mut self, f: impl Fn(SchemaIndex) -> SchemaIndex) {
+        let remap_all = |list: &mut Vec<SchemaIndex>| list.iter_mut().for_each(|i| *i = f(*i));
+        // no `..`, so that new fields are not missed
+        let Schema {
+            draft_version: _,
+            float_integers: _,
+            idx,
+            loc: _,
+            resource,
+            base_url: _,
+            dynamic_anchors,
+            all_props_evaluated: _,
+            all_items_evaluated: _,
+            num_items_evaluated: _,
+            leaf: _,
+            groups: _,
+            accepted: _,
+            source: _,
+            default: _,
+            examples: _,
+            flags: _,
+            boolean: _,
+            literal_bool: _,
+            ref_,
+            recursive_ref,
+            recursive_anchor: _,
+            dynamic_ref,
+            dynamic_anchor: _,
+            types: _,
+            enum_: _,
+            constant: _,
+            not,
+            all_of,
+            any_of,
+            one_of,
+            if_,
+            then,
+            else_,
+            format: _,
+            format_policy: _,
+            context_format: _,
+            time_range: _,
+            keywords: _,
+            ignored: _,
+            min_properties: _,
+            max_properties: _,
+            required: _,
+            properties,
+            pattern_properties,
+            property_names,
+            additional_properties,
+            dependent_required: _,
+            dependent_schemas,
+            dependencies,
+            unevaluated_properties,
+            min_items: _,
+            max_items: _,
+            unique_items: _,
+            min_contains: _,
+            max_contains: _,
+            contains,
+            items,
+            additional_items,
+            prefix_items,
+            items2020,
+            unevaluated_items,
+            min_length: _,
+            max_length: _,
+            pattern: _,
+            content_encoding: _,
+            content_media_type: _,
+            content_schema,
+            minimum: _,
+            maximum: _,
+            exclusive_minimum: _,
+            exclusive_maximum: _,
+            multiple_of: _,
+        } = self;
+        *idx = f(*idx);
+        *resource = f(*resource);
+        dynamic_anchors.values_mut().for_each(|i| *i = f(*i));
+        if let Some(dref) = dynamic_ref {
+            dref.sch = f(dref.sch);
+        }
+        for i in [
+            ref_,
+            recursive_ref,
+            not,
+            if_,
+            then,
+            else_,
+            property_names,
+            unevaluated_properties,
+            contains,
+            items2020,
+            unevaluated_items,
+            content_schema,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            *i = f(*i);
+        }
+        for list in [all_of, any_of, one_of, prefix_items] {
+            remap_all(list);
+        }
+        properties.values_mut().for_each(|i| *i = f(*i));
+        pattern_properties.iter_mut().for_each(|(_, i)| *i = f(*i));
+        dependent_schemas.iter_mut().for_each(|(_, i)| *i = f(*i));
+        for (_, dep) in dependencies {
+            if let Dependency::SchemaRef(i) = dep {
+                *i = f(*i);
+            }
+        }
+        for additional in [additional_properties, additional_items] {
+            if let Some(Additional::SchemaRef(i)) = additional {
+                *i = f(*i);
+            }
+        }
+        match items {
+            Some(Items::SchemaRef(i)) => *i = f(*i),
+            Some(Items::SchemaRefs(list)) => remap_all(list),
+            None => {}
+        }
+    }
 }
 
 /// JSON data types for JSONSchema
@@ -320,7 +1841,7 @@ pub enum Type {
 }
 
 impl Type {
-    fn of(v: &Value) -> Self {
+    pub(crate) fn of(v: &Value) -> Self {
         match v {
             Value::Null => Type::Null,
             Value::Bool(_) => Type::Boolean,
@@ -368,7 +1889,8 @@ impl Display for Type {
 pub struct Types(u8);
 
 impl Types {
-    fn is_empty(self) -> bool {
+    /// Returns `true` if this set has no types.
+    pub fn is_empty(self) -> bool {
         self.0 == 0
     }
 
@@ -376,6 +1898,19 @@ impl Types {
         self.0 |= t as u8;
     }
 
+    // whether every value matches, noting that integer is a number
+    fn is_all(self) -> bool {
+        let all = [
+            Type::Null,
+            Type::Boolean,
+            Type::Number,
+            Type::String,
+            Type::Array,
+            Type::Object,
+        ];
+        all.into_iter().all(|t| self.contains(t))
+    }
+
     /// Returns `true` if this set contains given type.
     pub fn contains(&self, t: Type) -> bool {
         self.0 & t as u8 != 0
@@ -410,23 +1945,134 @@ impl FromIterator<Type> for Types {
 #[derive(Debug)]
 pub struct ValidationError<'s, 'v> {
     /// The absolute, dereferenced schema location.
-    pub schema_url: &'s str,
+    pub schema_url: Cow<'s, str>,
     /// The location of the JSON value within the instance being validated
     pub instance_location: InstanceLocation<'v>,
     /// kind of error
     pub kind: ErrorKind<'s, 'v>,
+    /// example of valid value, from schema raising this error.
+    /// see [`ValidationOptions::attach_examples`]
+    pub example: Option<Cow<'s, Value>>,
     /// Holds nested errors
     pub causes: Vec<ValidationError<'s, 'v>>,
 }
 
 impl Error for ValidationError<'_, '_> {}
 
-/// A list specifying general categories of validation errors.
+/// Error type for [`Schemas::parse_and_validate`].
+#[derive(Debug)]
+pub enum InstanceError<'s> {
+    /// Instance is not a valid json document.
+    Parse {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    /// Instance bytes are not valid in given `format`.
+    Decode {
+        format: &'static str,
+        offset: usize,
+        message: String,
+    },
+    /// Value at `instance_location` cannot be represented in json.
+    Unsupported {
+        instance_location: InstanceLocation<'static>,
+        message: String,
+    },
+    /// Object at `instance_location` has `key` more than once.
+    /// see [`Schemas::parse_and_validate_strict`]
+    DuplicateKey {
+        instance_location: InstanceLocation<'static>,
+        key: String,
+    },
+    /// String at `instance_location`, or key of object at `instance_location`,
+    /// has `\u` escape of a surrogate, at byte `offset` of text, that does not form a valid pair.
+    ///
+    /// serde_json cannot decode such string, so it is reported along with
+    /// location, rather than as [`InstanceError::Parse`].
+    LoneSurrogate {
+        instance_location: InstanceLocation<'static>,
+        offset: usize,
+    },
+    /// Instance is not valid against schema.
+    Validation(ValidationError<'s, 'static>),
+}
+
+impl Error for InstanceError<'_> {}
+
+impl From<EncodingError> for InstanceError<'_> {
+    fn from(e: EncodingError) -> Self {
+        Self::Decode {
+            format: e.encoding,
+            offset: e.offset,
+            message: e.message,
+        }
+    }
+}
+
+impl From<serde_json::Error> for InstanceError<'_> {
+    fn from(e: serde_json::Error) -> Self {
+        let (line, column) = (e.line(), e.column());
+        let message = e.to_string();
+        let suffix = format!(" at line {line} column {column}");
+        let message = match message.strip_suffix(&suffix) {
+            Some(msg) => msg.to_owned(),
+            None => message,
+        };
+        Self::Parse {
+            line,
+            column,
+            message,
+        }
+    }
+}
+
+/// Why a keyword in schema is not enforced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IgnoreReason {
+    /// disabled using [`Compiler::disable_keyword`].
+    Disabled,
+    /// introduced in a later draft than that of the schema.
+    LaterDraft,
+    /// `format` is unknown, while format assertions are enabled.
+    UnknownFormat,
+}
+
+impl Display for IgnoreReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Disabled => write!(f, "disabled in compiler"),
+            Self::LaterDraft => write!(f, "not supported by schema draft"),
+            Self::UnknownFormat => write!(f, "unknown format"),
+        }
+    }
+}
+
+/// Which limit on instance size is exceeded, see [`ErrorKind::InstanceTooLarge`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstanceLimit {
+    /// see [`ValidationOptions::max_instance_nodes`]
+    Nodes,
+    /// see [`ValidationOptions::max_array_length`]
+    ArrayLength,
+    /// see [`ValidationOptions::max_object_members`]
+    ObjectMembers,
+    /// see [`ValidationOptions::max_string_length`]
+    StringLength,
+}
+
+/**
+A list specifying general categories of validation errors.
+
+New variants may be added in minor releases, so matching must have a
+wildcard arm.
+*/
+#[non_exhaustive]
 #[derive(Debug)]
 pub enum ErrorKind<'s, 'v> {
     Group,
     Schema {
-        url: &'s str,
+        url: Cow<'s, str>,
     },
     ContentSchema,
     PropertyName {
@@ -434,29 +2080,69 @@ pub enum ErrorKind<'s, 'v> {
     },
     Reference {
         kw: &'static str,
-        url: &'s str,
+        url: Cow<'s, str>,
     },
     RefCycle {
-        url: &'s str,
+        url: Cow<'s, str>,
         kw_loc1: String,
         kw_loc2: String,
     },
+    /**
+    Not an error, but a note that `keyword` in schema is not enforced.
+    see [`ValidationOptions::warn_ignored`]
+    */
+    IgnoredKeyword {
+        keyword: Cow<'s, str>,
+        reason: IgnoreReason,
+    },
+    /// validation aborted, after performing `operations`.
+    /// see [`ValidationOptions::max_operations`]
+    BudgetExceeded {
+        operations: usize,
+    },
+    /// validation aborted, as instance value exceeds `limit`.
+    /// see [`ValidationOptions::max_instance_nodes`]
+    InstanceTooLarge {
+        limit: usize,
+        kind: InstanceLimit,
+    },
     FalseSchema,
     Type {
         got: Type,
         want: Types,
+        /// offending value, if it is number or string
+        value: Option<Cow<'v, Value>>,
     },
+    #[non_exhaustive]
     Enum {
-        want: &'s Vec<Value>,
+        want: Cow<'s, [Value]>,
+        /// number, value was also compared as.
+        /// see [`ValidationOptions::loose_enum_scalars`]
+        as_number: Option<Number>,
     },
+    #[non_exhaustive]
     Const {
-        want: &'s Value,
+        want: Cow<'s, Value>,
+        /// number, value was also compared as.
+        /// see [`ValidationOptions::loose_enum_scalars`]
+        as_number: Option<Number>,
     },
     Format {
         got: Cow<'v, Value>,
         want: &'static str,
         err: Box<dyn Error>,
     },
+    /// Custom keyword from [`Vocabulary`] failed.
+    Keyword {
+        keyword: &'static str,
+        err: Box<dyn Error>,
+    },
+    /// Error raised by external code, see [`ValidationError::custom`].
+    Custom {
+        /// machine readable identifier of the check that failed.
+        code: String,
+        message: String,
+    },
     MinProperties {
         got: usize,
         want: usize,
@@ -469,19 +2155,27 @@ pub enum ErrorKind<'s, 'v> {
         got: Vec<Cow<'v, str>>,
     },
     Required {
-        want: Vec<&'s str>,
+        want: Vec<Cow<'s, str>>,
+    },
+    /// Missing property `prop`, at `index` in `required`.
+    ///
+    /// Reported as cause of [`ErrorKind::Required`], only if
+    /// [`ValidationOptions::required_details`] is used.
+    RequiredProperty {
+        prop: Cow<'s, str>,
+        index: usize,
     },
     Dependency {
         /// dependency of prop that failed.
-        prop: &'s str,
+        prop: Cow<'s, str>,
         /// missing props.
-        missing: Vec<&'s str>,
+        missing: Vec<Cow<'s, str>>,
     },
     DependentRequired {
         /// dependency of prop that failed.
-        prop: &'s str,
+        prop: Cow<'s, str>,
         /// missing props.
-        missing: Vec<&'s str>,
+        missing: Vec<Cow<'s, str>>,
     },
     MinItems {
         got: usize,
@@ -516,7 +2210,7 @@ pub enum ErrorKind<'s, 'v> {
     },
     Pattern {
         got: Cow<'v, str>,
-        want: &'s str,
+        want: Cow<'s, str>,
     },
     ContentEncoding {
         want: &'static str,
@@ -527,25 +2221,34 @@ pub enum ErrorKind<'s, 'v> {
         want: &'static str,
         err: Box<dyn Error>,
     },
+    /// content is not decoded, since it is larger than `want` bytes.
+    /// see [`ValidationOptions::max_decoded_content_size`]
+    ContentTooLarge {
+        /// `contentEncoding` or `contentMediaType`.
+        kw: &'static str,
+        /// size of content in bytes.
+        got: usize,
+        want: usize,
+    },
     Minimum {
         got: Cow<'v, Number>,
-        want: &'s Number,
+        want: Cow<'s, Number>,
     },
     Maximum {
         got: Cow<'v, Number>,
-        want: &'s Number,
+        want: Cow<'s, Number>,
     },
     ExclusiveMinimum {
         got: Cow<'v, Number>,
-        want: &'s Number,
+        want: Cow<'s, Number>,
     },
     ExclusiveMaximum {
         got: Cow<'v, Number>,
-        want: &'s Number,
+        want: Cow<'s, Number>,
     },
     MultipleOf {
         got: Cow<'v, Number>,
-        want: &'s Number,
+        want: Cow<'s, Number>,
     },
     Not,
     /// none of the subschemas matched
@@ -559,11 +2262,23 @@ pub enum ErrorKind<'s, 'v> {
 
 impl Display for ErrorKind<'_, '_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_with(f, &DisplayOptions::default())
+    }
+}
+
+impl ErrorKind<'_, '_> {
+    pub(crate) fn fmt_with(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        opts: &DisplayOptions,
+    ) -> std::fmt::Result {
         match self {
             Self::Group => write!(f, "validation failed"),
             Self::Schema { url } => write!(f, "validation failed with {url}"),
             Self::ContentSchema => write!(f, "contentSchema failed"),
-            Self::PropertyName { prop } => write!(f, "invalid property {}", quote(prop)),
+            Self::PropertyName { prop } => {
+                write!(f, "invalid property {}", excerpt_str(prop, opts, true))
+            }
             Self::Reference { .. } => {
                 write!(f, "validation failed")
             }
@@ -577,37 +2292,71 @@ impl Display for ErrorKind<'_, '_> {
                 quote(&kw_loc1.to_string()),
                 quote(&kw_loc2.to_string())
             ),
+            Self::IgnoredKeyword { keyword, reason } => {
+                write!(f, "keyword {} is ignored: {reason}", quote(keyword))
+            }
+            Self::BudgetExceeded { operations } => {
+                write!(f, "validation aborted after {operations} operations")
+            }
+            Self::InstanceTooLarge { limit, kind } => match kind {
+                InstanceLimit::Nodes => write!(f, "instance has more than {limit} values"),
+                InstanceLimit::ArrayLength => write!(f, "array has more than {limit} items"),
+                InstanceLimit::ObjectMembers => {
+                    write!(f, "object has more than {limit} members")
+                }
+                InstanceLimit::StringLength => write!(f, "string has more than {limit} bytes"),
+            },
             Self::FalseSchema => write!(f, "false schema"),
-            Self::Type { got, want } => {
+            Self::Type { got, want, value } => {
                 // todo: why join not working for Type struct ??
-                let want = join_iter(want.iter(), " or ");
-                write!(f, "want {want}, but got {got}",)
+                let want_str = join_iter(want.iter(), " or ");
+                write!(f, "want {want_str}, but got {got}",)?;
+                let Some(value) = value.as_deref().filter(|_| !opts.redact_values) else {
+                    return Ok(());
+                };
+                match value {
+                    Value::Number(n)
+                        if want.contains(Type::Integer) && !want.contains(Type::Number) =>
+                    {
+                        write!(f, " ({})", excerpt_num(n, opts, true))
+                    }
+                    Value::String(s) => match string_hint(s, *want) {
+                        Some(v) => {
+                            let v = excerpt(&v, opts, true);
+                            write!(f, "; did you mean {v} without quotes?")
+                        }
+                        None => Ok(()),
+                    },
+                    _ => Ok(()),
+                }
             }
-            Self::Enum { want } => {
+            Self::Enum { want, as_number } => {
                 if want.iter().all(Type::primitive) {
                     if want.len() == 1 {
-                        write!(f, "value must be ")?;
-                        display(f, &want[0])
+                        write!(f, "value must be {}", excerpt(&want[0], opts, false))?;
                     } else {
-                        let want = join_iter(want.iter().map(string), ", ");
-                        write!(f, "value must be one of {want}")
+                        let want = join_list(want.iter().map(|v| excerpt(v, opts, false)), opts);
+                        write!(f, "value must be one of {want}")?;
                     }
                 } else {
-                    write!(f, "enum failed")
+                    write!(f, "enum failed")?;
                 }
+                write_as_number(f, as_number, opts)
             }
-            Self::Const { want } => {
+            Self::Const { want, as_number } => {
                 if Type::primitive(want) {
-                    write!(f, "value must be ")?;
-                    display(f, want)
+                    write!(f, "value must be {}", excerpt(want, opts, false))?;
                 } else {
-                    write!(f, "const failed")
+                    write!(f, "const failed")?;
                 }
+                write_as_number(f, as_number, opts)
             }
             Self::Format { got, want, err } => {
-                display(f, got)?;
-                write!(f, " is not valid {want}: {err}")
+                let got = excerpt(got, opts, true);
+                write!(f, "{got} is not valid {want}: {err}")
             }
+            Self::Keyword { keyword, err } => write!(f, "{keyword} failed: {err}"),
+            Self::Custom { message, .. } => write!(f, "{message}"),
             Self::MinProperties { got, want } => write!(
                 f,
                 "minimum {want} properties required, but got {got} properties"
@@ -620,26 +2369,27 @@ impl Display for ErrorKind<'_, '_> {
                 write!(
                     f,
                     "additionalProperties {} not allowed",
-                    join_iter(got.iter().map(quote), ", ")
+                    join_list(got.iter().map(|p| excerpt_str(p, opts, true)), opts)
                 )
             }
             Self::Required { want } => write!(
                 f,
                 "missing properties {}",
-                join_iter(want.iter().map(quote), ", ")
+                join_list(want.iter().map(quote), opts)
             ),
+            Self::RequiredProperty { prop, .. } => write!(f, "missing property {}", quote(prop)),
             Self::Dependency { prop, missing } => {
                 write!(
                     f,
                     "properties {} required, if {} property exists",
-                    join_iter(missing.iter().map(quote), ", "),
+                    join_list(missing.iter().map(quote), opts),
                     quote(prop)
                 )
             }
             Self::DependentRequired { prop, missing } => write!(
                 f,
                 "properties {} required, if {} property exists",
-                join_iter(missing.iter().map(quote), ", "),
+                join_list(missing.iter().map(quote), opts),
                 quote(prop)
             ),
             Self::MinItems { got, want } => {
@@ -677,7 +2427,12 @@ impl Display for ErrorKind<'_, '_> {
             Self::MinLength { got, want } => write!(f, "length must be >={want}, but got {got}"),
             Self::MaxLength { got, want } => write!(f, "length must be <={want}, but got {got}"),
             Self::Pattern { got, want } => {
-                write!(f, "{} does not match pattern {}", quote(got), quote(want))
+                write!(
+                    f,
+                    "{} does not match pattern {}",
+                    excerpt_str(got, opts, true),
+                    excerpt_str(want, opts, false)
+                )
             }
             Self::ContentEncoding { want, err } => {
                 write!(f, "value is not {} encoded: {err}", quote(want))
@@ -685,11 +2440,29 @@ impl Display for ErrorKind<'_, '_> {
             Self::ContentMediaType { want, err, .. } => {
                 write!(f, "value is not of mediatype {}: {err}", quote(want))
             }
-            Self::Minimum { got, want } => write!(f, "must be >={want}, but got {got}"),
-            Self::Maximum { got, want } => write!(f, "must be <={want}, but got {got}"),
-            Self::ExclusiveMinimum { got, want } => write!(f, "must be > {want} but got {got}"),
-            Self::ExclusiveMaximum { got, want } => write!(f, "must be < {want} but got {got}"),
-            Self::MultipleOf { got, want } => write!(f, "{got} is not multipleOf {want}"),
+            Self::ContentTooLarge { got, want, .. } => {
+                write!(f, "content of {got} bytes is larger than {want} bytes")
+            }
+            Self::Minimum { got, want } => {
+                let (got, want) = (excerpt_num(got, opts, true), excerpt_num(want, opts, false));
+                write!(f, "must be >={want}, but got {got}")
+            }
+            Self::Maximum { got, want } => {
+                let (got, want) = (excerpt_num(got, opts, true), excerpt_num(want, opts, false));
+                write!(f, "must be <={want}, but got {got}")
+            }
+            Self::ExclusiveMinimum { got, want } => {
+                let (got, want) = (excerpt_num(got, opts, true), excerpt_num(want, opts, false));
+                write!(f, "must be > {want} but got {got}")
+            }
+            Self::ExclusiveMaximum { got, want } => {
+                let (got, want) = (excerpt_num(got, opts, true), excerpt_num(want, opts, false));
+                write!(f, "must be < {want} but got {got}")
+            }
+            Self::MultipleOf { got, want } => {
+                let (got, want) = (excerpt_num(got, opts, true), excerpt_num(want, opts, false));
+                write!(f, "{got} is not multipleOf {want}")
+            }
             Self::Not => write!(f, "not failed"),
             Self::AllOf => write!(f, "allOf failed",),
             Self::AnyOf => write!(f, "anyOf failed"),
@@ -699,18 +2472,107 @@ impl Display for ErrorKind<'_, '_> {
     }
 }
 
-fn display(f: &mut std::fmt::Formatter, v: &Value) -> std::fmt::Result {
+// joins at most `opts.max_list_items` items, followed by count of remaining
+fn join_list<T>(items: impl ExactSizeIterator<Item = T>, opts: &DisplayOptions) -> String
+where
+    T: Display,
+{
+    let len = items.len();
+    let mut s = join_iter(items.take(opts.max_list_items), ", ");
+    if len > opts.max_list_items {
+        s.push_str(&format!(" and {} more", len - opts.max_list_items));
+    }
+    s
+}
+
+// renders arrays and objects as json, unlike `excerpt`
+fn excerpt_json(v: &Value, opts: &DisplayOptions) -> String {
+    match v {
+        Value::Array(_) | Value::Object(_) => {
+            let s = v.to_string();
+            match s.char_indices().nth(opts.max_value_chars) {
+                Some((end, _)) => format!("{}...", &s[..end]),
+                None => s,
+            }
+        }
+        _ => excerpt(v, opts, false),
+    }
+}
+
+// returns primitive value of type in `want`, that string `s` spells
+fn string_hint(s: &str, want: Types) -> Option<Value> {
+    if s.trim() != s {
+        return None;
+    }
+    let v = serde_json::from_str::<Value>(s).ok()?;
+    let matched = match Type::of(&v) {
+        Type::Null | Type::Boolean => want.contains(Type::of(&v)),
+        Type::Number => {
+            want.contains(Type::Number) || want.contains(Type::Integer) && is_integer(&v, false)
+        }
+        _ => false,
+    };
+    matched.then_some(v)
+}
+
+// renders `v` as per `opts`. `instance` tells whether `v` is from instance.
+fn excerpt(v: &Value, opts: &DisplayOptions, instance: bool) -> String {
+    if instance && opts.redact_values {
+        return match v {
+            Value::String(s) => excerpt_str(s, opts, instance),
+            Value::Array(arr) => format!("<array of {} items>", arr.len()),
+            Value::Object(obj) => format!("<object of {} properties>", obj.len()),
+            _ => format!("<{}>", Type::of(v)),
+        };
+    }
     match v {
-        Value::String(s) => write!(f, "{}", quote(s)),
-        Value::Array(_) | Value::Object(_) => write!(f, "value"),
-        _ => write!(f, "{v}"),
+        Value::String(s) => excerpt_str(s, opts, instance),
+        Value::Number(n) => excerpt_num(n, opts, instance),
+        Value::Array(_) | Value::Object(_) => "value".to_owned(),
+        _ => v.to_string(),
     }
 }
 
-fn string(primitive: &Value) -> String {
-    if let Value::String(s) = primitive {
+fn excerpt_str(s: &str, opts: &DisplayOptions, instance: bool) -> String {
+    let len = s.chars().count();
+    if instance && opts.redact_values {
+        format!("<string of {len} chars>")
+    } else if len > opts.max_value_chars {
+        let end = s
+            .char_indices()
+            .nth(opts.max_value_chars)
+            .map_or(s.len(), |(i, _)| i);
+        format!("{}... ({len} chars)", quote(&s[..end]))
+    } else {
         quote(s)
+    }
+}
+
+fn excerpt_num(n: &Number, opts: &DisplayOptions, instance: bool) -> String {
+    if instance && opts.redact_values {
+        return "<number>".to_owned();
+    }
+    let s = n.to_string();
+    if s.len() > opts.max_value_chars {
+        // number string is always ascii
+        format!("{}... ({} chars)", &s[..opts.max_value_chars], s.len())
     } else {
-        format!("{primitive}")
+        s
+    }
+}
+
+// notes number that instance was loosely compared as, in enum and const errors
+fn write_as_number(
+    f: &mut std::fmt::Formatter,
+    as_number: &Option<Number>,
+    opts: &DisplayOptions,
+) -> std::fmt::Result {
+    match as_number {
+        Some(n) => write!(
+            f,
+            " (also compared as number {})",
+            excerpt_num(n, opts, true)
+        ),
+        None => Ok(()),
     }
 }
@@ -0,0 +1,502 @@
+/*!
+Minimal url parser, used instead of `url` crate without `url` feature.
+
+Supports what schema ids and references need: absolute urls with
+scheme, reference resolution as per [RFC 3986], and file paths.
+Compared to `url` crate, it does not
+
+- apply [WHATWG] rules of special schemes, other than default ports and
+  empty paths
+- convert international domain names to ascii
+- validate hosts, ports or percent-encoding
+
+[RFC 3986]: https://www.rfc-editor.org/rfc/rfc3986#section-5.2
+[WHATWG]: https://url.spec.whatwg.org
+*/
+
+use std::{
+    error::Error,
+    fmt::{Debug, Display},
+    hash::Hash,
+    path::{Path, PathBuf},
+};
+
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, CONTROLS};
+
+// see https://url.spec.whatwg.org/#percent-encoded-bytes
+const FRAGMENT: &AsciiSet = &CONTROLS.add(b' ').add(b'"').add(b'<').add(b'>').add(b'`');
+const QUERY: &AsciiSet = &CONTROLS.add(b' ').add(b'"').add(b'#').add(b'<').add(b'>');
+const PATH: &AsciiSet = &QUERY.add(b'?').add(b'`').add(b'{').add(b'}');
+
+// schemes whose empty path is serialized as `/`, along with default port
+const SPECIAL: &[(&str, &str)] = &[
+    ("http", ":80"),
+    ("https", ":443"),
+    ("ws", ":80"),
+    ("wss", ":443"),
+    ("ftp", ":21"),
+    ("file", ""),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ParseError {
+    RelativeUrlWithoutBase,
+    RelativeUrlWithCannotBeABaseBase,
+}
+
+impl Error for ParseError {}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::RelativeUrlWithoutBase => write!(f, "relative URL without a base"),
+            Self::RelativeUrlWithCannotBeABaseBase => {
+                write!(f, "relative URL with a cannot-be-a-base base")
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct Url {
+    serialization: String,
+    scheme_end: usize,
+    host: Option<(usize, usize)>,
+    path_start: usize,
+    query_start: Option<usize>,
+    fragment_start: Option<usize>,
+}
+
+// components of url or relative reference, as written
+#[derive(Clone, Copy)]
+struct Parts<'a> {
+    authority: Option<&'a str>,
+    path: &'a str,
+    query: Option<&'a str>,
+    fragment: Option<&'a str>,
+}
+
+impl<'a> Parts<'a> {
+    fn split(mut s: &'a str) -> Self {
+        let mut fragment = None;
+        if let Some((rest, frag)) = s.split_once('#') {
+            (s, fragment) = (rest, Some(frag));
+        }
+        let mut query = None;
+        if let Some((rest, q)) = s.split_once('?') {
+            (s, query) = (rest, Some(q));
+        }
+        let mut authority = None;
+        if let Some(rest) = s.strip_prefix("//") {
+            let end = rest.find('/').unwrap_or(rest.len());
+            (authority, s) = (Some(&rest[..end]), &rest[end..]);
+        }
+        Self {
+            authority,
+            path: s,
+            query,
+            fragment,
+        }
+    }
+}
+
+impl Url {
+    pub(crate) fn parse(input: &str) -> Result<Self, ParseError> {
+        let Some((scheme, rest)) = split_scheme(input) else {
+            return Err(ParseError::RelativeUrlWithoutBase);
+        };
+        Ok(Self::build(scheme, Parts::split(rest)))
+    }
+
+    pub(crate) fn join(&self, input: &str) -> Result<Self, ParseError> {
+        if let Some((scheme, rest)) = split_scheme(input) {
+            return Ok(Self::build(scheme, Parts::split(rest)));
+        }
+        let r = Parts::split(input);
+        let base = Parts::split(&self.serialization[self.scheme_end + 1..]);
+        let hierarchical = base.authority.is_some() || base.path.starts_with('/');
+        // only fragment can be replaced, like `url` crate does
+        let fragment_only =
+            r.authority.is_none() && r.path.is_empty() && r.query.is_none() && r.fragment.is_some();
+        if !hierarchical && !fragment_only {
+            return Err(ParseError::RelativeUrlWithCannotBeABaseBase);
+        }
+        let merged;
+        let parts = if r.authority.is_some() {
+            r
+        } else if r.path.is_empty() {
+            Parts {
+                query: r.query.or(base.query),
+                ..base
+            }
+        } else {
+            let path = if r.path.starts_with('/') {
+                r.path
+            } else if base.authority.is_some() && base.path.is_empty() {
+                merged = format!("/{}", r.path);
+                &merged
+            } else {
+                let dir = base.path.rfind('/').map_or("", |i| &base.path[..=i]);
+                merged = format!("{dir}{}", r.path);
+                &merged
+            };
+            Parts {
+                path,
+                query: r.query,
+                ..base
+            }
+        };
+        Ok(Self::build(
+            self.scheme(),
+            Parts {
+                fragment: r.fragment,
+                ..parts
+            },
+        ))
+    }
+
+    fn build(scheme: &str, parts: Parts) -> Self {
+        let mut s = scheme.to_ascii_lowercase();
+        let scheme_end = s.len();
+        s.push(':');
+        let special = SPECIAL.iter().find(|(name, _)| *name == &s[..scheme_end]);
+        let mut host = None;
+        if let Some(authority) = parts.authority {
+            s.push_str("//");
+            let (userinfo, host_port) = match authority.rfind('@') {
+                Some(i) => authority.split_at(i + 1),
+                None => ("", authority),
+            };
+            s.push_str(userinfo);
+            let host_len = match host_port.find(']') {
+                Some(i) if host_port.starts_with('[') => i + 1,
+                _ => host_port.find(':').unwrap_or(host_port.len()),
+            };
+            if host_len > 0 {
+                host = Some((s.len(), s.len() + host_len));
+            }
+            let (host, port) = host_port.split_at(host_len);
+            s.push_str(&host.to_ascii_lowercase());
+            if special.is_none_or(|(_, default)| port != *default) {
+                s.push_str(port);
+            }
+        }
+        let path_start = s.len();
+        if parts.authority.is_some() || parts.path.starts_with('/') {
+            let path = remove_dot_segments(parts.path);
+            if path.is_empty() && special.is_some() {
+                s.push('/');
+            }
+            s.extend(utf8_percent_encode(&path, PATH));
+        } else {
+            s.extend(utf8_percent_encode(parts.path, PATH));
+        }
+        let mut query_start = None;
+        if let Some(query) = parts.query {
+            query_start = Some(s.len());
+            s.push('?');
+            s.extend(utf8_percent_encode(query, QUERY));
+        }
+        let mut fragment_start = None;
+        if let Some(fragment) = parts.fragment {
+            fragment_start = Some(s.len());
+            s.push('#');
+            s.extend(utf8_percent_encode(fragment, FRAGMENT));
+        }
+        Self {
+            serialization: s,
+            scheme_end,
+            host,
+            path_start,
+            query_start,
+            fragment_start,
+        }
+    }
+
+    pub(crate) fn from_file_path(path: impl AsRef<Path>) -> Result<Self, ()> {
+        let path = path.as_ref();
+        if !path.is_absolute() {
+            return Err(());
+        }
+        let path = path.to_str().ok_or(())?;
+        let path = if cfg!(windows) {
+            format!("/{}", path.replace('\\', "/"))
+        } else {
+            path.to_owned()
+        };
+        let path = path
+            .replace('%', "%25")
+            .replace('#', "%23")
+            .replace('?', "%3F");
+        Ok(Self::build(
+            "file",
+            Parts {
+                authority: Some(""),
+                path: &path,
+                query: None,
+                fragment: None,
+            },
+        ))
+    }
+
+    pub(crate) fn to_file_path(&self) -> Result<PathBuf, ()> {
+        if self.scheme() != "file" || !matches!(self.host_str(), None | Some("" | "localhost")) {
+            return Err(());
+        }
+        let path = percent_decode_str(self.path())
+            .decode_utf8()
+            .map_err(|_| ())?;
+        if cfg!(windows) {
+            let path = path.strip_prefix('/').unwrap_or(&path);
+            return Ok(PathBuf::from(path.replace('/', "\\")));
+        }
+        Ok(PathBuf::from(path.as_ref()))
+    }
+
+    pub(crate) fn as_str(&self) -> &str {
+        &self.serialization
+    }
+
+    pub(crate) fn scheme(&self) -> &str {
+        &self.serialization[..self.scheme_end]
+    }
+
+    pub(crate) fn host_str(&self) -> Option<&str> {
+        self.host
+            .map(|(start, end)| &self.serialization[start..end])
+    }
+
+    pub(crate) fn path(&self) -> &str {
+        let end = self
+            .query_start
+            .or(self.fragment_start)
+            .unwrap_or(self.serialization.len());
+        &self.serialization[self.path_start..end]
+    }
+
+    pub(crate) fn fragment(&self) -> Option<&str> {
+        self.fragment_start
+            .map(|start| &self.serialization[start + 1..])
+    }
+}
+
+// returns scheme and rest, if `s` starts with scheme.
+fn split_scheme(s: &str) -> Option<(&str, &str)> {
+    let (scheme, rest) = s.split_once(':')?;
+    let mut chars = scheme.chars();
+    let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    valid.then_some((scheme, rest))
+}
+
+// see https://www.rfc-editor.org/rfc/rfc3986#section-5.2.4
+// `path` is empty or absolute, as it follows authority or is absolute.
+fn remove_dot_segments(path: &str) -> String {
+    let Some(path) = path.strip_prefix('/') else {
+        return path.to_owned();
+    };
+    let (mut out, mut trailing) = (vec![], false);
+    for seg in path.split('/') {
+        trailing = matches!(seg, "." | "..");
+        match seg {
+            "." => {}
+            ".." => _ = out.pop(),
+            _ => out.push(seg),
+        }
+    }
+    if trailing {
+        out.push("");
+    }
+    format!("/{}", out.join("/"))
+}
+
+impl PartialEq for Url {
+    fn eq(&self, other: &Self) -> bool {
+        self.serialization == other.serialization
+    }
+}
+
+impl Eq for Url {}
+
+impl Hash for Url {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.serialization.hash(state);
+    }
+}
+
+impl Display for Url {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.serialization)
+    }
+}
+
+impl Debug for Url {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Url({:?})", self.serialization)
+    }
+}
+
+impl From<Url> for String {
+    fn from(url: Url) -> Self {
+        url.serialization
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // url crate is dev-dependency, so behavior is compared against it
+    #[test]
+    fn test_parse() {
+        let tests = [
+            "http://Example.COM/a/../b/./c?q=1#f",
+            "http://example.com",
+            "HTTPS://user@Example.com:8080/x",
+            "http://[::1]:80/x",
+            "file:///tmp/x.json",
+            "urn:uuid:ee564b8a-7a87-4125-8c96-e9f123d6766f#frag",
+            "tag:example.com,2020:schema",
+            "http://example.com/a b/é#/a b",
+            "http://example.com/a%20b",
+            "http://example.com/a/..",
+            "foo://host",
+            "foo://host?q#f",
+            "foo://host/a/./b/../c",
+        ];
+        for input in tests {
+            let want = ::url::Url::parse(input).unwrap();
+            let got = Url::parse(input).unwrap();
+            assert_eq!(got.as_str(), want.as_str(), "{input}");
+            assert_eq!(got.scheme(), want.scheme(), "{input}");
+            assert_eq!(got.host_str(), want.host_str(), "{input}");
+            assert_eq!(got.path(), want.path(), "{input}");
+            assert_eq!(got.fragment(), want.fragment(), "{input}");
+        }
+        assert_eq!(
+            Url::parse("a/b.json"),
+            Err(ParseError::RelativeUrlWithoutBase)
+        );
+    }
+
+    #[test]
+    fn test_join() {
+        let base = "http://a/b/c/d;p?q";
+        let tests = [
+            "g:h",
+            "g",
+            "./g",
+            "g/",
+            "/g",
+            "//g",
+            "?y",
+            "g?y",
+            "#s",
+            "g#s",
+            "g?y#s",
+            ";x",
+            "g;x",
+            "",
+            ".",
+            "./",
+            "..",
+            "../",
+            "../g",
+            "../..",
+            "../../",
+            "../../g",
+            "../../../g",
+            "../../../../g",
+            "/./g",
+            "/../g",
+            "g.",
+            ".g",
+            "g..",
+            "..g",
+            "./../g",
+            "./g/.",
+            "g/./h",
+            "g/../h",
+            "g;x=1/./y",
+            "g;x=1/../y",
+        ];
+        let (want_base, got_base) = (::url::Url::parse(base).unwrap(), Url::parse(base).unwrap());
+        for input in tests {
+            let want = want_base.join(input).unwrap();
+            let got = got_base.join(input).unwrap();
+            assert_eq!(got.as_str(), want.as_str(), "{input}");
+        }
+
+        let base = "http://example.com";
+        let got = Url::parse(base).unwrap().join("schema.json").unwrap();
+        assert_eq!(got.as_str(), "http://example.com/schema.json");
+
+        let base = Url::parse("urn:example:schema").unwrap();
+        assert_eq!(
+            base.join("#/$defs/a").unwrap().as_str(),
+            "urn:example:schema#/$defs/a"
+        );
+        assert_eq!(
+            base.join("other.json"),
+            Err(ParseError::RelativeUrlWithCannotBeABaseBase)
+        );
+    }
+
+    // each reference joined with each base
+    #[test]
+    fn test_join_differential() {
+        let bases = [
+            "http://a/b/c/d;p?q",
+            "http://a",
+            "https://a:443/b/",
+            "file:///tmp/a/b.json",
+            "foo://host",
+            "foo://host/a/b?q",
+            "foo:/a/b",
+            "urn:example:a",
+        ];
+        let refs = [
+            "",
+            ".",
+            "./",
+            "..",
+            "../",
+            "/",
+            "//h",
+            "//h/..",
+            "//h?q",
+            "?q",
+            "#f",
+            "x",
+            "x/",
+            "x/../..",
+            "x/./y/../z",
+            "/a/b/../../..",
+            "/./x/.",
+            "../../../../x",
+            "g:h",
+            "foo:x",
+        ];
+        for base in bases {
+            let (want_base, got_base) =
+                (::url::Url::parse(base).unwrap(), Url::parse(base).unwrap());
+            for r in refs {
+                let want = want_base.join(r).map(|u| u.to_string());
+                let got = got_base.join(r).map(|u| u.to_string());
+                assert_eq!(got.ok(), want.ok(), "{base} joined with {r:?}");
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_file_path() {
+        let path = "/tmp/a b/é#1.json";
+        let want = ::url::Url::from_file_path(path).unwrap();
+        let got = Url::from_file_path(path).unwrap();
+        assert_eq!(got.as_str(), want.as_str());
+        assert_eq!(got.to_file_path(), Ok(PathBuf::from(path)));
+        assert_eq!(Url::from_file_path("tmp/a.json"), Err(()));
+        assert!(Url::parse("http://a/b").unwrap().to_file_path().is_err());
+    }
+}
@@ -2,27 +2,56 @@ use std::{
     cell::RefCell,
     collections::{HashMap, HashSet},
     error::Error,
+    sync::Arc,
+    time::{Duration, Instant},
 };
 
 #[cfg(not(target_arch = "wasm32"))]
-use std::fs::File;
+use std::fs;
 
 use appendlist::AppendList;
 use once_cell::sync::Lazy;
 use serde_json::Value;
-use url::Url;
 
 use crate::{
-    compiler::CompileError,
-    draft::{latest, Draft},
-    util::split,
-    UrlPtr,
+    compiler::{CompileError, CompileObserver},
+    draft::{latest, strip_keywords, Draft},
+    encoding,
+    url::Url,
+    util::{json_size, split},
+    Resource, UrlPtr,
 };
 
 /// A trait for loading json from given `url`
 pub trait UrlLoader {
     /// Loads json from given absolute `url`.
     fn load(&self, url: &str) -> Result<Value, Box<dyn Error>>;
+
+    /**
+    Loads resource from given absolute `url`.
+
+    Override this to return raw bytes along with their content-encoding
+    and content-type, so that decompression and parsing are taken care of
+    by compiler. see [`Resource::into_json`].
+
+    Default implementation returns json loaded by [`UrlLoader::load`].
+    */
+    fn load_resource(&self, url: &str) -> Result<Resource, Box<dyn Error>> {
+        Ok(Resource::Json(self.load(url)?))
+    }
+}
+
+// --
+
+/**
+Parses json `bytes` in utf-8 or utf-16, with optional byte order mark.
+
+Encoding problems are reported as [`EncodingError`](crate::EncodingError).
+This is useful in implementing [`UrlLoader`].
+*/
+pub fn json_from_bytes(bytes: &[u8]) -> Result<Value, Box<dyn Error>> {
+    let text = encoding::decode(bytes)?;
+    Ok(serde_json::from_str(&text)?)
 }
 
 // --
@@ -35,16 +64,257 @@ impl UrlLoader for FileLoader {
     fn load(&self, url: &str) -> Result<Value, Box<dyn Error>> {
         let url = Url::parse(url)?;
         let path = url.to_file_path().map_err(|_| "invalid file path")?;
-        let file = File::open(path)?;
-        Ok(serde_json::from_reader(file)?)
+        let bytes = fs::read(&path)?;
+        #[cfg(feature = "json5")]
+        if path.extension().is_some_and(|ext| ext == "json5") {
+            return Ok(parse_json5(&encoding::decode(&bytes)?)?);
+        }
+        json_from_bytes(&bytes)
+    }
+}
+
+/**
+Same as [`FileLoader`], but also tolerates comments and trailing
+commas in `.json` files, by parsing them as json5.
+
+To use it, register it for `file` scheme:
+```rust,no_run
+# use boon::*;
+let mut loader = SchemeUrlLoader::new();
+loader.register("file", Box::new(LenientFileLoader));
+let mut compiler = Compiler::new();
+compiler.use_loader(Box::new(loader));
+```
+*/
+#[cfg(all(feature = "json5", not(target_arch = "wasm32")))]
+pub struct LenientFileLoader;
+
+#[cfg(all(feature = "json5", not(target_arch = "wasm32")))]
+impl UrlLoader for LenientFileLoader {
+    fn load(&self, url: &str) -> Result<Value, Box<dyn Error>> {
+        let url = Url::parse(url)?;
+        let path = url.to_file_path().map_err(|_| "invalid file path")?;
+        let bytes = fs::read(path)?;
+        Ok(parse_json5(&encoding::decode(&bytes)?)?)
+    }
+}
+
+// --
+
+/// Error in parsing json5 document.
+#[cfg(feature = "json5")]
+#[derive(Debug)]
+pub struct Json5Error {
+    /// one-based line number, `0` if not known.
+    pub line: usize,
+    /// one-based column number, `0` if not known.
+    pub column: usize,
+    pub message: String,
+}
+
+#[cfg(feature = "json5")]
+impl Error for Json5Error {}
+
+#[cfg(feature = "json5")]
+impl std::fmt::Display for Json5Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}", self.message)
+        } else {
+            write!(
+                f,
+                "{} at line {} column {}",
+                self.message, self.line, self.column
+            )
+        }
     }
 }
 
+#[cfg(feature = "json5")]
+pub(crate) fn parse_json5(s: &str) -> Result<Value, Json5Error> {
+    json5::from_str(s).map_err(|e| {
+        let json5::Error::Message { msg, location } = e;
+        let (line, column) = location.map_or((0, 0), |l| (l.line, l.column));
+        // pest renders the offending line as well; keep only the summary
+        let message = msg.lines().last().unwrap_or_default();
+        let message = message.trim_start_matches(['=', ' ']);
+        Json5Error {
+            line,
+            column,
+            message: message.to_owned(),
+        }
+    })
+}
+
 // --
 
+/**
+Error that [`UrlLoader`] can return, so that [`SchemeUrlLoader`] knows
+whether to try the next loader registered for the scheme.
+
+[`std::io::Error`] of kind [`std::io::ErrorKind::NotFound`] is treated
+same as [`LoadError::NotFound`].
+*/
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum LoadError {
+    /// Nothing exists at the url. Next loader is always tried.
+    NotFound,
+    /// Loading timed out.
+    Timeout,
+    /// I/O failure.
+    Io(std::io::Error),
+}
+
+impl Error for LoadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotFound => write!(f, "not found"),
+            Self::Timeout => write!(f, "timed out"),
+            Self::Io(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+fn is_not_found(e: &(dyn Error + 'static)) -> bool {
+    if let Some(e) = e.downcast_ref::<LoadError>() {
+        return match e {
+            LoadError::NotFound => true,
+            LoadError::Io(e) => e.kind() == std::io::ErrorKind::NotFound,
+            _ => false,
+        };
+    }
+    e.downcast_ref::<std::io::Error>()
+        .is_some_and(|e| e.kind() == std::io::ErrorKind::NotFound)
+}
+
+// whether loading may succeed if tried again. for chain of loaders,
+// whether any of them timed out.
+fn is_timeout(e: &(dyn Error + 'static)) -> bool {
+    if let Some(e) = e.downcast_ref::<LoaderChainError>() {
+        return e.errors.iter().any(|e| is_timeout(e.as_ref()));
+    }
+    if let Some(e) = e.downcast_ref::<LoadError>() {
+        return match e {
+            LoadError::Timeout => true,
+            LoadError::Io(e) => e.kind() == std::io::ErrorKind::TimedOut,
+            _ => false,
+        };
+    }
+    e.downcast_ref::<std::io::Error>()
+        .is_some_and(|e| e.kind() == std::io::ErrorKind::TimedOut)
+}
+
+/**
+Failure of an earlier attempt to load same url, reported without trying
+again. see [`Compiler::set_negative_cache_ttl`](crate::Compiler::set_negative_cache_ttl)
+*/
+#[derive(Debug, Clone)]
+pub struct CachedLoadError {
+    /// error of the earlier attempt, along with its source chain.
+    pub error: Arc<dyn Error + Send + Sync>,
+}
+
+impl Error for CachedLoadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.error.source()
+    }
+}
+
+impl std::fmt::Display for CachedLoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (failed earlier, not retried)", self.error)
+    }
+}
+
+// rendered copy of an error and its sources, since loader errors
+// need not be `Send` or `Clone`.
+#[derive(Debug)]
+struct ErrorSnapshot {
+    message: String,
+    source: Option<Box<ErrorSnapshot>>,
+}
+
+impl ErrorSnapshot {
+    fn of(e: &dyn Error) -> Self {
+        Self {
+            message: e.to_string(),
+            source: e.source().map(|src| Box::new(Self::of(src))),
+        }
+    }
+}
+
+impl Error for ErrorSnapshot {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source.as_deref().map(|src| src as _)
+    }
+}
+
+impl std::fmt::Display for ErrorSnapshot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Errors of each loader tried by [`SchemeUrlLoader`], in the order tried.
+#[derive(Debug)]
+pub struct LoaderChainError {
+    pub errors: Vec<Box<dyn Error>>,
+}
+
+impl Error for LoaderChainError {}
+
+impl std::fmt::Display for LoaderChainError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} loaders failed", self.errors.len())?;
+        for (i, e) in self.errors.iter().enumerate() {
+            write!(f, "; [{}] {e}", i + 1)?;
+        }
+        Ok(())
+    }
+}
+
+/**
+[`UrlLoader`] delegating to loaders registered for url scheme.
+
+Multiple loaders can be registered for same scheme. They are tried
+in order, until one succeeds. Next loader is tried, only if previous
+one failed with [`LoadError::NotFound`], unless
+[`SchemeUrlLoader::enable_fallback_on_errors`] is used. If more than
+one loader failed, their errors are reported as [`LoaderChainError`].
+
+```rust,no_run
+# use boon::*;
+# struct CacheLoader;
+# impl UrlLoader for CacheLoader {
+#     fn load(&self, url: &str) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+#         Err(LoadError::NotFound.into())
+#     }
+# }
+# struct HttpLoader;
+# impl UrlLoader for HttpLoader {
+#     fn load(&self, url: &str) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+#         todo!()
+#     }
+# }
+let mut loader = SchemeUrlLoader::new();
+loader.register("https", Box::new(HttpLoader));
+loader.register_front("https", Box::new(CacheLoader)); // tried first
+```
+*/
 #[derive(Default)]
 pub struct SchemeUrlLoader {
-    loaders: HashMap<&'static str, Box<dyn UrlLoader>>,
+    loaders: HashMap<&'static str, Vec<Box<dyn UrlLoader>>>,
+    fallback_on_errors: bool,
 }
 
 impl SchemeUrlLoader {
@@ -52,31 +322,118 @@ impl SchemeUrlLoader {
         Self::default()
     }
 
-    /// Registers [`UrlLoader`] for given url `scheme`
+    /// Registers [`UrlLoader`] for given url `scheme`, to be tried after
+    /// loaders registered earlier for the scheme.
     pub fn register(&mut self, scheme: &'static str, url_loader: Box<dyn UrlLoader>) {
-        self.loaders.insert(scheme, url_loader);
+        self.loaders.entry(scheme).or_default().push(url_loader);
+    }
+
+    /// Registers [`UrlLoader`] for given url `scheme`, to be tried before
+    /// loaders registered earlier for the scheme.
+    pub fn register_front(&mut self, scheme: &'static str, url_loader: Box<dyn UrlLoader>) {
+        self.loaders
+            .entry(scheme)
+            .or_default()
+            .insert(0, url_loader);
+    }
+
+    /// Tries next loader on any error, like [`LoadError::Timeout`], rather
+    /// than only on [`LoadError::NotFound`].
+    pub fn enable_fallback_on_errors(&mut self) {
+        self.fallback_on_errors = true;
     }
 }
 
-impl UrlLoader for SchemeUrlLoader {
-    fn load(&self, url: &str) -> Result<Value, Box<dyn Error>> {
+impl SchemeUrlLoader {
+    fn try_each<T>(
+        &self,
+        url: &str,
+        f: impl Fn(&dyn UrlLoader, &str) -> Result<T, Box<dyn Error>>,
+    ) -> Result<T, Box<dyn Error>> {
         let url = Url::parse(url)?;
-        let Some(loader) = self.loaders.get(url.scheme()) else {
+        let Some(loaders) = self.loaders.get(url.scheme()) else {
             return Err(CompileError::UnsupportedUrlScheme {
                 url: url.as_str().to_owned(),
+                referenced_from: vec![],
             }
             .into());
         };
-        loader.load(url.as_str())
+        let mut errors = vec![];
+        for loader in loaders {
+            match f(loader.as_ref(), url.as_str()) {
+                Ok(v) => return Ok(v),
+                Err(e) => {
+                    let fall_through = self.fallback_on_errors || is_not_found(e.as_ref());
+                    errors.push(e);
+                    if !fall_through {
+                        break;
+                    }
+                }
+            }
+        }
+        match errors.len() {
+            1 => Err(errors.remove(0)),
+            _ => Err(LoaderChainError { errors }.into()),
+        }
+    }
+}
+
+impl UrlLoader for SchemeUrlLoader {
+    fn load(&self, url: &str) -> Result<Value, Box<dyn Error>> {
+        self.try_each(url, |loader, url| loader.load(url))
+    }
+
+    fn load_resource(&self, url: &str) -> Result<Resource, Box<dyn Error>> {
+        self.try_each(url, |loader, url| loader.load_resource(url))
     }
 }
 
 // --
 
+/// Where a document known to compiler comes from, see [`Compiler::origin_of`](crate::Compiler::origin_of).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Origin {
+    /// added using [`Compiler::add_resource`](crate::Compiler::add_resource)
+    /// or its variants, with given `loc`.
+    Resource(String),
+    /// loaded by [`UrlLoader`] from given url.
+    Loaded(String),
+    /// standard metaschema, bundled with this crate.
+    Metaschema,
+}
+
+impl std::fmt::Display for Origin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Resource(loc) => write!(f, "added as {loc}"),
+            Self::Loaded(url) => write!(f, "loaded from {url}"),
+            Self::Metaschema => write!(f, "bundled metaschema"),
+        }
+    }
+}
+
 pub(crate) struct DefaultUrlLoader {
     doc_map: RefCell<HashMap<Url, usize>>,
-    doc_list: AppendList<Value>,
+    doc_list: AppendList<(Arc<Value>, Origin)>, // doc shared with `Schema::source`
     loader: Box<dyn UrlLoader>,
+    pub(crate) stripped_keywords: Vec<String>,
+    pub(crate) max_doc_size: Option<usize>,
+    pub(crate) max_meta_depth: Option<usize>,
+    pub(crate) max_meta_docs: Option<usize>, // per compilation
+    pub(crate) negative_cache_ttl: Option<Duration>,
+    pub(crate) retries: usize,
+    pub(crate) retry_backoff: Duration,
+    failed: RefCell<HashMap<Url, (Instant, CachedLoadError)>>, // negative cache
+    pub(crate) clock: Option<Box<dyn Fn() -> Instant>>,        // for negative cache
+    pub(crate) observer: Option<Box<dyn CompileObserver>>,
+    usage: RefCell<Usage>, // see `CompileReport`
+}
+
+/// urls of documents consulted, since last `take_usage`.
+#[derive(Default)]
+pub(crate) struct Usage {
+    pub(crate) docs: HashSet<Url>,
+    pub(crate) metas: HashSet<Url>, // consulted for `$schema`
 }
 
 impl DefaultUrlLoader {
@@ -89,6 +446,17 @@ impl DefaultUrlLoader {
             doc_map: Default::default(),
             doc_list: AppendList::new(),
             loader: Box::new(loader),
+            stripped_keywords: vec![],
+            max_doc_size: None,
+            max_meta_depth: None,
+            max_meta_docs: None,
+            negative_cache_ttl: None,
+            retries: 0,
+            retry_backoff: Duration::ZERO,
+            failed: Default::default(),
+            clock: None,
+            observer: None,
+            usage: Default::default(),
         }
     }
 
@@ -97,86 +465,309 @@ impl DefaultUrlLoader {
             .borrow()
             .get(url)
             .and_then(|i| self.doc_list.get(*i))
+            .map(|(doc, _)| doc.as_ref())
+    }
+
+    pub(crate) fn origin(&self, url: &Url) -> Option<&Origin> {
+        self.doc_map
+            .borrow()
+            .get(url)
+            .and_then(|i| self.doc_list.get(*i))
+            .map(|(_, origin)| origin)
+    }
+
+    /// same as `get_doc`, but shared.
+    pub(crate) fn get_doc_arc(&self, url: &Url) -> Option<Arc<Value>> {
+        self.doc_map
+            .borrow()
+            .get(url)
+            .and_then(|i| self.doc_list.get(*i))
+            .map(|(doc, _)| Arc::clone(doc))
+    }
+
+    /// documents added or loaded so far, other than standard metaschemas.
+    #[cfg(feature = "codegen")]
+    pub(crate) fn docs(&self) -> Vec<(Url, &Value)> {
+        let mut docs = self
+            .doc_map
+            .borrow()
+            .iter()
+            .filter(|(url, _)| load_std_meta(url.as_str()).is_none())
+            .filter_map(|(url, &i)| Some((url.clone(), self.doc_list.get(i)?.0.as_ref())))
+            .collect::<Vec<_>>();
+        docs.sort_by(|(a, _), (b, _)| a.cmp(b));
+        docs
+    }
+
+    /// urls of documents added as resources, see `Origin::Resource`.
+    pub(crate) fn resources(&self) -> Vec<Url> {
+        self.doc_map
+            .borrow()
+            .iter()
+            .filter(|(_, &i)| matches!(self.doc_list.get(i), Some((_, Origin::Resource(_)))))
+            .map(|(url, _)| url.clone())
+            .collect()
     }
 
-    pub fn add_doc(&self, url: Url, json: Value) {
-        if self.get_doc(&url).is_some() {
-            return;
+    /// adding same document again does nothing. fails, if other
+    /// document is already known at `url`.
+    pub fn add_doc(&self, url: Url, json: Value, origin: Origin) -> Result<(), CompileError> {
+        self.add_shared_doc(url, Arc::new(json), origin)
+    }
+
+    /// same as `add_doc`, but shares `json` with caller.
+    pub(crate) fn add_shared_doc(
+        &self,
+        url: Url,
+        json: Arc<Value>,
+        origin: Origin,
+    ) -> Result<(), CompileError> {
+        if let Some(i) = self.doc_map.borrow().get(&url) {
+            return match self.doc_list.get(*i) {
+                Some((doc, existing)) if *doc != json => Err(CompileError::ConflictingResource {
+                    url: url.to_string(),
+                    existing: existing.clone(),
+                    conflicting: origin,
+                }),
+                _ => Ok(()),
+            };
         }
-        self.doc_list.push(json);
+        self.doc_list.push((json, origin));
         self.doc_map
             .borrow_mut()
             .insert(url, self.doc_list.len() - 1);
+        Ok(())
+    }
+
+    /// fails if document at `url`, whose size is computed by `size`,
+    /// exceeds `max_doc_size`.
+    pub(crate) fn check_size(
+        &self,
+        url: &Url,
+        size: impl FnOnce() -> usize,
+    ) -> Result<(), CompileError> {
+        let Some(limit) = self.max_doc_size else {
+            return Ok(());
+        };
+        let size = size();
+        if size > limit {
+            return Err(CompileError::DocumentTooLarge {
+                url: url.as_str().to_owned(),
+                size,
+                limit,
+                referenced_from: vec![],
+            });
+        }
+        Ok(())
+    }
+
+    /// removes `stripped_keywords` from given document, before it is added.
+    pub(crate) fn strip(&self, doc: &mut Value) {
+        if !self.stripped_keywords.is_empty() {
+            strip_keywords(doc, &self.stripped_keywords);
+        }
     }
 
     pub fn use_loader(&mut self, loader: Box<dyn UrlLoader>) {
         self.loader = loader;
     }
 
+    /// loads resource at `url`, retrying on timeout and remembering
+    /// failure, as configured.
+    fn fetch(&self, url: &Url) -> Result<Resource, Box<dyn Error>> {
+        if let Some(ttl) = self.negative_cache_ttl {
+            if let Some((at, error)) = self.failed.borrow().get(url) {
+                if self.now().saturating_duration_since(*at) < ttl {
+                    return Err(error.clone().into());
+                }
+            }
+        }
+        let mut attempt = 0;
+        let result = loop {
+            match self.loader.load_resource(url.as_str()) {
+                Err(e) if attempt < self.retries && is_timeout(e.as_ref()) => {
+                    // doubles after each attempt
+                    let delay = self
+                        .retry_backoff
+                        .saturating_mul(2u32.saturating_pow(attempt as u32));
+                    if !delay.is_zero() {
+                        std::thread::sleep(delay);
+                    }
+                    attempt += 1;
+                }
+                result => break result,
+            }
+        };
+        if let (Err(e), Some(_)) = (&result, self.negative_cache_ttl) {
+            let error = Arc::new(ErrorSnapshot::of(e.as_ref()));
+            let entry = (self.now(), CachedLoadError { error });
+            self.failed.borrow_mut().insert(url.clone(), entry);
+        }
+        result
+    }
+
+    fn now(&self) -> Instant {
+        self.clock
+            .as_ref()
+            .map_or_else(Instant::now, |clock| clock())
+    }
+
+    /// returns documents consulted since last call, and clears them.
+    pub(crate) fn take_usage(&self) -> Usage {
+        self.usage.take()
+    }
+
     pub(crate) fn load(&self, url: &Url) -> Result<&Value, CompileError> {
+        let doc = self.load_doc(url)?;
+        self.usage.borrow_mut().docs.insert(url.clone());
+        Ok(doc)
+    }
+
+    // same as `load`, but for metaschema
+    fn load_meta(&self, url: &Url) -> Result<&Value, CompileError> {
+        let doc = self.load_doc(url)?;
+        self.usage.borrow_mut().metas.insert(url.clone());
+        Ok(doc)
+    }
+
+    fn load_doc(&self, url: &Url) -> Result<&Value, CompileError> {
         if let Some(doc) = self.get_doc(url) {
             return Ok(doc);
         }
 
         // check in STD_METAFILES
-        let doc = if let Some(content) = load_std_meta(url.as_str()) {
-            serde_json::from_str::<Value>(content).map_err(|e| CompileError::LoadUrlError {
-                url: url.to_string(),
-                src: e.into(),
-            })?
+        let (doc, origin) = if let Some(content) = load_std_meta(url.as_str()) {
+            let doc =
+                serde_json::from_str::<Value>(content).map_err(|e| CompileError::LoadUrlError {
+                    url: url.to_string(),
+                    src: e.into(),
+                    referenced_from: vec![],
+                })?;
+            (doc, Origin::Metaschema)
         } else {
-            self.loader
-                .load(url.as_str())
-                .map_err(|src| CompileError::LoadUrlError {
-                    url: url.as_str().to_owned(),
-                    src,
-                })?
+            if let Some(observer) = &self.observer {
+                observer.on_load_start(url.as_str());
+            }
+            let result = self.fetch_doc(url);
+            if let Some(observer) = &self.observer {
+                observer.on_load_end(url.as_str(), result.as_ref().map(|_| ()));
+            }
+            (result?, Origin::Loaded(url.to_string()))
         };
-        self.add_doc(url.clone(), doc);
+        self.add_doc(url.clone(), doc, origin)?;
         self.get_doc(url)
             .ok_or(CompileError::Bug("doc must exist".into()))
     }
 
+    // fetches document using `loader`, applying limits
+    fn fetch_doc(&self, url: &Url) -> Result<Value, CompileError> {
+        let load_error = |src: Box<dyn Error>| match src.downcast::<CompileError>() {
+            // reported as is, so that referencing chain is added to it
+            Ok(e) if matches!(*e, CompileError::UnsupportedUrlScheme { .. }) => *e,
+            Ok(e) => CompileError::LoadUrlError {
+                url: url.as_str().to_owned(),
+                src: e,
+                referenced_from: vec![],
+            },
+            Err(src) => CompileError::LoadUrlError {
+                url: url.as_str().to_owned(),
+                src,
+                referenced_from: vec![],
+            },
+        };
+        let res = self.fetch(url).map_err(load_error)?;
+        // decoded size is checked, when it is not known upfront
+        let check_decoded = match &res {
+            Resource::Json(_) => true,
+            Resource::Bytes {
+                bytes,
+                content_encoding,
+                ..
+            } => {
+                self.check_size(url, || bytes.len())?;
+                content_encoding.is_some()
+            }
+        };
+        let mut doc = res.into_json().map_err(load_error)?;
+        if check_decoded {
+            self.check_size(url, || json_size(&doc))?;
+        }
+        self.strip(&mut doc);
+        Ok(doc)
+    }
+
     pub(crate) fn get_draft(
         &self,
         up: &UrlPtr,
         doc: &Value,
         default_draft: &'static Draft,
-        mut cycle: HashSet<Url>,
     ) -> Result<&'static Draft, CompileError> {
-        let Value::Object(obj) = &doc else {
-            return Ok(default_draft);
-        };
-        let Some(Value::String(sch)) = obj.get("$schema") else {
-            return Ok(default_draft);
-        };
-        if let Some(draft) = Draft::from_url(sch) {
-            return Ok(draft);
+        let mut chain: Vec<Url> = vec![]; // custom metaschemas, in order
+        let (mut up, mut doc) = (up.clone(), doc);
+        loop {
+            let Value::Object(obj) = &doc else {
+                return Ok(default_draft);
+            };
+            let Some(Value::String(sch)) = obj.get("$schema") else {
+                return Ok(default_draft);
+            };
+            if let Some(draft) = Draft::from_url(sch) {
+                return Ok(draft);
+            }
+            let (sch, _) = split(sch);
+            let sch = Url::parse(sch).map_err(|e| CompileError::InvalidMetaSchemaUrl {
+                url: up.to_string(),
+                src: e.into(),
+            })?;
+            if up.ptr.is_empty() && sch == up.url {
+                return Err(CompileError::UnsupportedDraft { url: sch.into() });
+            }
+            if chain.contains(&sch) {
+                return Err(CompileError::MetaSchemaCycle { url: sch.into() });
+            }
+            chain.push(sch.clone());
+            self.check_meta_limits(&chain)?;
+
+            doc = self.load_meta(&sch)?;
+            up = UrlPtr {
+                url: sch,
+                ptr: "".into(),
+            };
         }
-        let (sch, _) = split(sch);
-        let sch = Url::parse(sch).map_err(|e| CompileError::InvalidMetaSchemaUrl {
-            url: up.to_string(),
-            src: e.into(),
-        })?;
-        if up.ptr.is_empty() && sch == up.url {
-            return Err(CompileError::UnsupportedDraft { url: sch.into() });
+    }
+
+    /// fails, if metaschema `chain` exceeds `max_meta_depth`, or loading its
+    /// last metaschema exceeds `max_meta_docs`.
+    fn check_meta_limits(&self, chain: &[Url]) -> Result<(), CompileError> {
+        let chain_strings = || chain.iter().map(Url::to_string).collect();
+        if let Some(limit) = self.max_meta_depth {
+            if chain.len() > limit {
+                return Err(CompileError::MetaSchemaChainTooLong {
+                    chain: chain_strings(),
+                    limit,
+                });
+            }
         }
-        if !cycle.insert(sch.clone()) {
-            return Err(CompileError::MetaSchemaCycle { url: sch.into() });
+        if let Some(limit) = self.max_meta_docs {
+            let metas = &self.usage.borrow().metas;
+            let url = chain
+                .last()
+                .ok_or(CompileError::Bug("empty chain".into()))?;
+            if !metas.contains(url) && metas.len() >= limit {
+                return Err(CompileError::TooManyMetaSchemas {
+                    chain: chain_strings(),
+                    limit,
+                });
+            }
         }
-
-        let doc = self.load(&sch)?;
-        let up = UrlPtr {
-            url: sch,
-            ptr: "".into(),
-        };
-        self.get_draft(&up, doc, default_draft, cycle)
+        Ok(())
     }
 
     pub(crate) fn get_meta_vocabs(
         &self,
         doc: &Value,
         draft: &'static Draft,
+        custom: &HashSet<String>,
     ) -> Result<Option<Vec<String>>, CompileError> {
         let Value::Object(obj) = &doc else {
             return Ok(None);
@@ -192,8 +783,8 @@ impl DefaultUrlLoader {
             url: sch.to_string(),
             src: e.into(),
         })?;
-        let doc = self.load(&sch)?;
-        draft.get_vocabs(&sch, doc)
+        let doc = self.load_meta(&sch)?;
+        draft.get_vocabs(&sch, doc, custom)
     }
 }
 
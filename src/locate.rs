@@ -0,0 +1,490 @@
+use std::{borrow::Cow, cell::Cell, collections::HashSet, error::Error, fmt::Display};
+
+use serde_json::Value;
+
+use crate::{util::quote, validator, *};
+
+// schema along with its dynamic scope, outermost first
+type Entry = (SchemaIndex, Vec<SchemaIndex>);
+
+pub(crate) fn schemas_for_location(
+    schemas: &Schemas,
+    sch: SchemaIndex,
+    v: &Value,
+    loc: &InstanceLocation,
+) -> Vec<SchemaIndex> {
+    let locator = Locator::new(schemas);
+    let Some((entries, v)) = locator.entries_at(sch, v, loc) else {
+        return vec![];
+    };
+    let mut out = vec![];
+    for (sch, mut scope) in entries {
+        locator.walk(sch, v, &mut scope, None, &mut out, &mut HashSet::new());
+    }
+    dedup(out).into_iter().map(|(sch, _)| sch).collect()
+}
+
+/**
+How [`Schemas::validate_at`](crate::Schemas::validate_at) treats
+`unevaluatedProperties` and `unevaluatedItems` of schemas applying to
+ancestors of the location.
+
+Whether these keywords apply to a value depends on what their sibling
+keywords evaluate, which is decided by the ancestor, not by the value.
+*/
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UnevalPolicy {
+    /// fail with [`SubtreeError::Unevaluated`], if any schema applying
+    /// to an ancestor has these keywords.
+    #[default]
+    Forbid,
+    /// disregard these keywords of ancestors. Value may be reported
+    /// valid, though validating whole document rejects it.
+    Ignore,
+    /// apply these keywords of ancestors, if value is not evaluated by
+    /// their siblings. Evaluation is reconstructed from ancestors, as
+    /// in [`Schemas::schemas_for_location`](crate::Schemas::schemas_for_location),
+    /// so result matches validating whole document, unless ancestors
+    /// are invalid on their own.
+    Emulate,
+}
+
+/// Error returned by [`Schemas::validate_at`](crate::Schemas::validate_at).
+#[derive(Debug)]
+pub enum SubtreeError<'s, 'v> {
+    /// value is not valid against subschemas applying to it.
+    Validation(ValidationError<'s, 'v>),
+    /// schema applying to an ancestor has `unevaluatedProperties` or
+    /// `unevaluatedItems`, with [`UnevalPolicy::Forbid`].
+    Unevaluated {
+        /// absolute location of the schema.
+        schema_url: &'s str,
+    },
+    /// location does not exist in instance.
+    NotFound(InstanceLocation<'static>),
+}
+
+impl Error for SubtreeError<'_, '_> {}
+
+impl Display for SubtreeError<'_, '_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Validation(e) => e.fmt(f),
+            Self::Unevaluated { schema_url } => write!(
+                f,
+                "cannot validate subtree: {} applies unevaluated keywords to its ancestor",
+                quote(schema_url)
+            ),
+            Self::NotFound(loc) => {
+                write!(f, "instance location {} not found", quote(&loc.to_string()))
+            }
+        }
+    }
+}
+
+pub(crate) fn validate_at<'s, 'v>(
+    schemas: &'s Schemas,
+    sch: SchemaIndex,
+    v: &'v Value,
+    loc: &InstanceLocation,
+    policy: UnevalPolicy,
+) -> Result<(), SubtreeError<'s, 'v>> {
+    let locator = Locator {
+        schemas,
+        opts: ValidationOptions::default(),
+        policy,
+        uneval_found: Cell::new(None),
+    };
+    let Some((entries, v)) = locator.entries_at(sch, v, loc) else {
+        return Err(SubtreeError::NotFound(loc.clone().rebind()));
+    };
+    if let Some(sch) = locator.uneval_found.get() {
+        let schema_url = schemas.get(sch).loc.as_str();
+        return Err(SubtreeError::Unevaluated { schema_url });
+    }
+    let mut causes = vec![];
+    for (sch, _) in entries {
+        if let Err(mut e) = validator::validate(v, schemas.get(sch), schemas, &locator.opts) {
+            prefix(&mut e, loc);
+            causes.push(e);
+        }
+    }
+    if causes.is_empty() {
+        return Ok(());
+    }
+    let url = &schemas.get(sch).loc;
+    Err(SubtreeError::Validation(ValidationError {
+        schema_url: Cow::Borrowed(url),
+        instance_location: loc.clone().rebind(),
+        kind: ErrorKind::Schema {
+            url: Cow::Borrowed(url),
+        },
+        example: None,
+        causes,
+    }))
+}
+
+// makes instance locations of `e` relative to document, rather than to value at `loc`
+fn prefix(e: &mut ValidationError, loc: &InstanceLocation) {
+    let tokens = std::mem::take(&mut e.instance_location.tokens);
+    e.instance_location.tokens = loc.clone().rebind().tokens;
+    e.instance_location.tokens.extend(tokens);
+    for cause in &mut e.causes {
+        prefix(cause, loc);
+    }
+}
+
+/**
+Whether property may be added to an object, see
+[`Schemas::property_allowed`](crate::Schemas::property_allowed).
+
+Each list has subschemas that would apply to value of the property.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AllowResult {
+    /// named in `properties`.
+    Property(Vec<SchemaIndex>),
+    /// not named in `properties`, but matches `patternProperties`.
+    PatternProperty(Vec<SchemaIndex>),
+    /// accepted by `additionalProperties` or `unevaluatedProperties`.
+    /// list is empty, if neither keyword is present.
+    Additional(Vec<SchemaIndex>),
+    /// rejected by schema at given index, for example due to
+    /// `additionalProperties`, `propertyNames` or `maxProperties`.
+    Rejected(SchemaIndex),
+    /// location does not exist in instance, or it is not an object.
+    Unknown,
+}
+
+pub(crate) fn property_allowed(
+    schemas: &Schemas,
+    sch: SchemaIndex,
+    v: &Value,
+    loc: &InstanceLocation,
+    candidate: &str,
+) -> AllowResult {
+    let locator = Locator::new(schemas);
+    let Some((entries, v)) = locator.entries_at(sch, v, loc) else {
+        return AllowResult::Unknown;
+    };
+    let Value::Object(obj) = v else {
+        return AllowResult::Unknown;
+    };
+
+    let tok = InstanceToken::from(candidate);
+    let (mut applied, mut value_schs) = (vec![], vec![]);
+    for (sch, scope) in entries {
+        let step = Some((&tok, &Value::Null));
+        let mut scope2 = scope.clone();
+        locator.walk(sch, v, &mut scope2, None, &mut applied, &mut HashSet::new());
+        let mut scope = scope;
+        locator.walk(
+            sch,
+            v,
+            &mut scope,
+            step,
+            &mut value_schs,
+            &mut HashSet::new(),
+        );
+    }
+    let value_schs = dedup(value_schs)
+        .into_iter()
+        .map(|(sch, _)| sch)
+        .collect::<Vec<_>>();
+
+    let (mut named, mut pattern) = (false, false);
+    for (sch, _) in dedup(applied) {
+        let s = schemas.get(sch);
+        let s_named = s.properties.contains_key(candidate);
+        let s_pattern = s
+            .pattern_properties
+            .iter()
+            .any(|(re, _)| re.is_match(candidate));
+        if !s_named && !s_pattern {
+            if let Some(Additional::Bool(false)) = s.additional_properties {
+                return AllowResult::Rejected(sch);
+            }
+        }
+        if let Some(max) = s.max_properties {
+            if obj.len() >= max && !obj.contains_key(candidate) {
+                return AllowResult::Rejected(sch);
+            }
+        }
+        if let Some(names) = s.property_names {
+            if !locator.is_valid(names, &Value::String(candidate.to_owned())) {
+                return AllowResult::Rejected(sch);
+            }
+        }
+        named |= s_named;
+        pattern |= s_pattern;
+    }
+    let rejected = value_schs
+        .iter()
+        .find(|sch| schemas.get(**sch).boolean == Some(false));
+    if let Some(sch) = rejected {
+        return AllowResult::Rejected(*sch);
+    }
+    if named {
+        AllowResult::Property(value_schs)
+    } else if pattern {
+        AllowResult::PatternProperty(value_schs)
+    } else {
+        AllowResult::Additional(value_schs)
+    }
+}
+
+fn dedup(entries: Vec<Entry>) -> Vec<Entry> {
+    let mut seen = HashSet::new();
+    entries
+        .into_iter()
+        .filter(|(sch, _)| seen.insert(*sch))
+        .collect()
+}
+
+struct Locator<'s> {
+    schemas: &'s Schemas,
+    opts: ValidationOptions<'static>,
+    policy: UnevalPolicy,
+    // with UnevalPolicy::Forbid, schema with unevaluated* keywords,
+    // that applies to an ancestor
+    uneval_found: Cell<Option<SchemaIndex>>,
+}
+
+impl<'s> Locator<'s> {
+    fn new(schemas: &'s Schemas) -> Self {
+        Self {
+            schemas,
+            opts: ValidationOptions::default(),
+            policy: UnevalPolicy::Emulate,
+            uneval_found: Cell::new(None),
+        }
+    }
+
+    // entries applicable to value at `loc`, along with that value.
+    // returns None, if `loc` does not exist in `v`.
+    fn entries_at<'v>(
+        &self,
+        sch: SchemaIndex,
+        mut v: &'v Value,
+        loc: &InstanceLocation,
+    ) -> Option<(Vec<Entry>, &'v Value)> {
+        let mut entries: Vec<Entry> = vec![(sch, vec![])];
+        for tok in &loc.tokens {
+            let child = match (tok, v) {
+                (InstanceToken::Prop(p), Value::Object(obj)) => obj.get(p.as_ref()),
+                (InstanceToken::Item(i), Value::Array(arr)) => arr.get(*i),
+                _ => None,
+            }?;
+            let mut next = vec![];
+            for (sch, mut scope) in entries {
+                let step = Some((tok, child));
+                self.walk(sch, v, &mut scope, step, &mut next, &mut HashSet::new());
+            }
+            entries = dedup(next);
+            v = child;
+        }
+        Some((entries, v))
+    }
+
+    // applies `sch` to `v`. if `step` is given, collects subschemas that apply
+    // to that child of `v`, otherwise collects subschemas that apply to `v`.
+    // returns whether the child is evaluated, for unevaluated* keywords.
+    fn walk(
+        &self,
+        sch: SchemaIndex,
+        v: &Value,
+        scope: &mut Vec<SchemaIndex>,
+        step: Option<(&InstanceToken, &Value)>,
+        out: &mut Vec<Entry>,
+        active: &mut HashSet<SchemaIndex>,
+    ) -> bool {
+        if !active.insert(sch) {
+            return false; // cycle of in-place applicators
+        }
+        if step.is_none() {
+            out.push((sch, scope.clone()));
+        }
+        scope.push(sch);
+        let s = self.schemas.get(sch);
+        let ref_only = s.draft_version < 2019 && s.ref_.is_some();
+        let step = step.filter(|_| !ref_only);
+        let uneval = s.draft_version >= 2019 && s.groups.contains(KeywordGroups::UNEVAL);
+        if step.is_some() && uneval && self.policy == UnevalPolicy::Forbid {
+            self.uneval_found.set(self.uneval_found.get().or(Some(sch)));
+        }
+        let mut evaluated = false;
+        if let Some((tok, child)) = step {
+            evaluated = self.children(s, v, tok, child, scope, out);
+        }
+        for t in self.in_place(s, v, scope) {
+            evaluated |= self.walk(t, v, scope, step, out, active);
+        }
+        if let (Some((tok, _)), false, true) =
+            (step, evaluated, self.policy != UnevalPolicy::Ignore)
+        {
+            evaluated = self.unevaluated(s, tok, scope, out);
+        }
+        scope.pop();
+        active.remove(&sch);
+        evaluated
+    }
+
+    // subschemas applied to same instance, which the instance passes
+    fn in_place(&self, s: &Schema, v: &Value, scope: &[SchemaIndex]) -> Vec<SchemaIndex> {
+        let mut list = vec![];
+        if let Some(ref_) = s.ref_ {
+            list.push(ref_);
+            if s.draft_version < 2019 {
+                return list;
+            }
+        }
+        if let Some(mut sch) = s.recursive_ref {
+            if self.schemas.get(sch).recursive_anchor {
+                for &scope_sch in scope.iter().rev() {
+                    let base_sch = self.schemas.get(self.schemas.get(scope_sch).resource);
+                    if base_sch.recursive_anchor {
+                        sch = scope_sch;
+                    }
+                }
+            }
+            list.push(sch);
+        }
+        if let Some(dref) = &s.dynamic_ref {
+            let mut sch = dref.sch;
+            if let Some(anchor) = &dref.anchor {
+                if self.schemas.get(sch).dynamic_anchor == dref.anchor {
+                    for &scope_sch in scope.iter().rev() {
+                        let base_sch = self.schemas.get(self.schemas.get(scope_sch).resource);
+                        if let Some(dsch) = base_sch.dynamic_anchors.get(anchor) {
+                            sch = *dsch;
+                        }
+                    }
+                }
+            }
+            list.push(sch);
+        }
+        if let Value::Object(obj) = v {
+            for (prop, dep) in &s.dependencies {
+                if let (Dependency::SchemaRef(sch), true) = (dep, obj.contains_key(prop)) {
+                    list.push(*sch);
+                }
+            }
+            for (prop, sch) in &s.dependent_schemas {
+                if obj.contains_key(prop) {
+                    list.push(*sch);
+                }
+            }
+        }
+        list.extend(&s.all_of);
+        list.extend(s.any_of.iter().filter(|sch| self.is_valid(**sch, v)));
+        list.extend(s.one_of.iter().filter(|sch| self.is_valid(**sch, v)));
+        if let Some(if_) = s.if_ {
+            if self.is_valid(if_, v) {
+                list.push(if_);
+                list.extend(s.then);
+            } else {
+                list.extend(s.else_);
+            }
+        }
+        list
+    }
+
+    // collects subschemas of `s` that apply to `child` of `v`.
+    // returns whether any of them evaluates the child.
+    fn children(
+        &self,
+        s: &Schema,
+        v: &Value,
+        tok: &InstanceToken,
+        child: &Value,
+        scope: &[SchemaIndex],
+        out: &mut Vec<Entry>,
+    ) -> bool {
+        let mut push = |sch: SchemaIndex| out.push((sch, scope.to_vec()));
+        let mut matched = false;
+        match (tok, v) {
+            (InstanceToken::Prop(pname), Value::Object(_)) => {
+                if let Some(sch) = s.properties.get(pname.as_ref()) {
+                    push(*sch);
+                    matched = true;
+                }
+                for (regex, sch) in &s.pattern_properties {
+                    if regex.is_match(pname) {
+                        push(*sch);
+                        matched = true;
+                    }
+                }
+                if !matched {
+                    if let Some(Additional::SchemaRef(sch)) = &s.additional_properties {
+                        push(*sch);
+                    }
+                    matched = s.additional_properties.is_some();
+                }
+            }
+            (InstanceToken::Item(i), Value::Array(arr)) => {
+                let i = *i;
+                if s.draft_version < 2020 {
+                    let prefix = match &s.items {
+                        Some(Items::SchemaRef(sch)) => {
+                            push(*sch);
+                            arr.len()
+                        }
+                        Some(Items::SchemaRefs(list)) => {
+                            if let Some(sch) = list.get(i) {
+                                push(*sch);
+                            }
+                            list.len()
+                        }
+                        None => 0,
+                    };
+                    matched = i < prefix;
+                    if !matched {
+                        if let Some(Additional::SchemaRef(sch)) = &s.additional_items {
+                            push(*sch);
+                        }
+                        matched = s.additional_items.is_some();
+                    }
+                } else if let Some(sch) = s.prefix_items.get(i) {
+                    push(*sch);
+                    matched = true;
+                } else if let Some(sch) = s.items2020 {
+                    push(sch);
+                    matched = true;
+                }
+                if let Some(sch) = s.contains {
+                    if self.is_valid(sch, child) {
+                        push(sch);
+                        matched |= s.draft_version >= 2020;
+                    }
+                }
+            }
+            _ => {}
+        }
+        matched
+    }
+
+    // collects unevaluated* subschema of `s`, for child not evaluated otherwise
+    fn unevaluated(
+        &self,
+        s: &Schema,
+        tok: &InstanceToken,
+        scope: &[SchemaIndex],
+        out: &mut Vec<Entry>,
+    ) -> bool {
+        if s.draft_version < 2019 {
+            return false;
+        }
+        let sch = match tok {
+            InstanceToken::Prop(_) => s.unevaluated_properties,
+            InstanceToken::Item(_) => s.unevaluated_items,
+        };
+        if let Some(sch) = sch {
+            out.push((sch, scope.to_vec()));
+        }
+        sch.is_some()
+    }
+
+    fn is_valid(&self, sch: SchemaIndex, v: &Value) -> bool {
+        validator::is_valid(v, self.schemas.get(sch), self.schemas, &self.opts)
+    }
+}
@@ -0,0 +1,563 @@
+use std::{any::Any, fmt::Debug, time::SystemTime};
+
+use serde_json::Value;
+
+use crate::{InstanceLocation, SchemaIndex, ValidationError};
+
+/**
+Options controlling a single validation, used with
+[`Schemas::validate_with`](crate::Schemas::validate_with) and
+[`Schemas::is_valid_with`](crate::Schemas::is_valid_with).
+
+Default options give same behavior as [`Schemas::validate`](crate::Schemas::validate).
+
+```rust
+# use boon::*;
+let opts = ValidationOptions::new().fail_fast(true).collect_metrics(true);
+```
+*/
+#[derive(Default, Clone)]
+pub struct ValidationOptions<'c> {
+    pub(crate) fail_fast: bool,
+    pub(crate) collect_metrics: bool,
+    pub(crate) max_operations: Option<usize>,
+    pub(crate) warn_ignored: bool,
+    pub(crate) attach_examples: bool,
+    pub(crate) collect_branches: bool,
+    pub(crate) context: Option<&'c dyn Any>,
+    pub(crate) visitor: Option<&'c dyn InstanceVisitor>,
+    pub(crate) clock: Option<&'c dyn Fn() -> SystemTime>,
+    pub(crate) hash_unique_items: bool,
+    pub(crate) loose_enum_scalars: bool,
+    pub(crate) draft4_float_integers: bool,
+    pub(crate) required_details: bool,
+    pub(crate) max_decoded_content_size: Option<usize>,
+    pub(crate) max_instance_nodes: Option<usize>,
+    pub(crate) max_array_length: Option<usize>,
+    pub(crate) max_object_members: Option<usize>,
+    pub(crate) max_string_length: Option<usize>,
+    // collect locations of values with this flag, see `Schemas::redact`
+    pub(crate) annotation: Option<&'c str>,
+    // collect flags from failed subschemas too
+    pub(crate) keep_failed_annotations: bool,
+}
+
+impl Debug for ValidationOptions<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ValidationOptions")
+            .field("fail_fast", &self.fail_fast)
+            .field("collect_metrics", &self.collect_metrics)
+            .field("max_operations", &self.max_operations)
+            .field("warn_ignored", &self.warn_ignored)
+            .field("attach_examples", &self.attach_examples)
+            .field("collect_branches", &self.collect_branches)
+            .field("context", &self.context.map(|_| ".."))
+            .field("visitor", &self.visitor.map(|_| ".."))
+            .field("clock", &self.clock.map(|_| ".."))
+            .field("hash_unique_items", &self.hash_unique_items)
+            .field("loose_enum_scalars", &self.loose_enum_scalars)
+            .field("draft4_float_integers", &self.draft4_float_integers)
+            .field("required_details", &self.required_details)
+            .field("max_decoded_content_size", &self.max_decoded_content_size)
+            .field("max_instance_nodes", &self.max_instance_nodes)
+            .field("max_array_length", &self.max_array_length)
+            .field("max_object_members", &self.max_object_members)
+            .field("max_string_length", &self.max_string_length)
+            .field("annotation", &self.annotation)
+            .field("keep_failed_annotations", &self.keep_failed_annotations)
+            .finish()
+    }
+}
+
+impl<'c> ValidationOptions<'c> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+    Stop evaluating a schema, as soon as one of its keywords fails.
+
+    The error returned still explains why the instance is invalid,
+    but it may not report all the failures.
+    */
+    pub fn fail_fast(mut self, yes: bool) -> Self {
+        self.fail_fast = yes;
+        self
+    }
+
+    /**
+    Abort validation, once more than `n` operations are performed.
+
+    An operation is evaluation of a subschema against an instance value,
+    as counted by [`Metrics::evaluations`]. On abort, validation fails with
+    [`ErrorKind::BudgetExceeded`](crate::ErrorKind::BudgetExceeded) located
+    at the instance value being evaluated.
+
+    This bounds validation time of untrusted schemas and instances.
+    */
+    pub fn max_operations(mut self, n: usize) -> Self {
+        self.max_operations = Some(n);
+        self
+    }
+
+    /**
+    Report keywords that are present in schema, but not enforced,
+    as [`ValidationOutput::warnings`].
+
+    A warning with [`ErrorKind::IgnoredKeyword`](crate::ErrorKind::IgnoredKeyword)
+    is reported, each time such schema is evaluated against an instance value.
+    This helps to explain why an instance unexpectedly passes validation.
+    */
+    pub fn warn_ignored(mut self, yes: bool) -> Self {
+        self.warn_ignored = yes;
+        self
+    }
+
+    /**
+    Populate [`ValidationError::example`] of errors raised by keywords,
+    with example of valid value from schema containing the keyword.
+
+    see [`Schemas::example_for`](crate::Schemas::example_for)
+    */
+    pub fn attach_examples(mut self, yes: bool) -> Self {
+        self.attach_examples = yes;
+        self
+    }
+
+    /**
+    Supplies data needed by custom formats, registered using
+    [`Compiler::register_context_format`](crate::Compiler::register_context_format).
+
+    Built-in formats and keywords ignore it.
+    */
+    pub fn context(mut self, ctx: &'c dyn Any) -> Self {
+        self.context = Some(ctx);
+        self
+    }
+
+    /**
+    Clock giving current time, for bounds of `x-notBefore` and
+    `x-notAfter` relative to now. see [`Compiler::enable_extension`](crate::Compiler::enable_extension).
+
+    Defaults to system time. Fixed clock makes validation deterministic,
+    for example in tests.
+
+    # Note
+
+    clock must be given on `wasm32-unknown-unknown`, which has no system time.
+    Otherwise bounds relative to now fail validation, rather than panic.
+    */
+    pub fn clock(mut self, clock: &'c dyn Fn() -> SystemTime) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /**
+    For `uniqueItems`, hash each item once and compare only items with
+    matching hashes, regardless of array size.
+
+    By default, this is done only for arrays with more than 20 items;
+    smaller arrays compare each pair of items. This helps with small
+    arrays of large, distinct values, which are otherwise told apart
+    only after comparing much of them. Equality is same either way.
+    */
+    pub fn hash_unique_items(mut self, yes: bool) -> Self {
+        self.hash_unique_items = yes;
+        self
+    }
+
+    /**
+    For `enum` and `const`, also match a string against a number,
+    if the string holds a json number equal to it. For example `"1"`
+    and `"1.0"` match `1`, and `1` matches `"1.0"`.
+
+    **This deviates from the specification**, where a string never
+    equals a number. Only string is read as number, using json number
+    syntax, so `" 1"`, `"+1"` and `"0x1"` match no number. Numbers are
+    compared as usual, so `1` equals `1.0`. Strings are compared with
+    strings as usual, so `"1"` does not match `"1.0"`. Other keywords,
+    like `type` and `minimum`, are not affected.
+
+    Errors of `enum` and `const` report the number compared, in
+    [`ErrorKind::Enum::as_number`](crate::ErrorKind::Enum) and
+    [`ErrorKind::Const::as_number`](crate::ErrorKind::Const).
+    */
+    pub fn loose_enum_scalars(mut self, yes: bool) -> Self {
+        self.loose_enum_scalars = yes;
+        self
+    }
+
+    /**
+    Treat numbers with zero fractional part, like `1.0`, as integers
+    in draft-04 schemas.
+
+    Draft-04 considers them as not integers, whereas later drafts
+    consider them as integers. This makes draft-04 schemas
+    behave like later drafts.
+    */
+    pub fn draft4_float_integers(mut self, yes: bool) -> Self {
+        self.draft4_float_integers = yes;
+        self
+    }
+
+    /**
+    Report each property missing by `required` keyword, as cause of
+    [`ErrorKind::Required`](crate::ErrorKind::Required) with kind
+    [`ErrorKind::RequiredProperty`](crate::ErrorKind::RequiredProperty).
+
+    Each cause is located at the missing member of instance,
+    with keyword path `required/<index>`.
+    */
+    pub fn required_details(mut self, yes: bool) -> Self {
+        self.required_details = yes;
+        self
+    }
+
+    /**
+    Refuse content larger than `n` bytes, in content assertions.
+
+    A string whose `contentEncoding` decodes to more than `n` bytes, or
+    which is passed to `contentMediaType` as is and has more than `n`
+    bytes, fails with [`ErrorKind::ContentTooLarge`](crate::ErrorKind::ContentTooLarge),
+    and its `contentSchema` is not evaluated. Size of `base64` content is
+    computed from the string, so that it is not decoded at all.
+
+    Validation of `contentSchema` counts towards [`Self::max_operations`],
+    same as the rest of the instance. So together these bound the work
+    done for documents embedded in strings of untrusted instances.
+
+    Content assertions are disabled by default,
+    see [`Compiler::enable_content_assertions`](crate::Compiler::enable_content_assertions).
+    */
+    pub fn max_decoded_content_size(mut self, n: usize) -> Self {
+        self.max_decoded_content_size = Some(n);
+        self
+    }
+
+    /**
+    Abort validation, once instance is found to have more than `n`
+    values in total.
+
+    The root counts as one value, and each array or object adds its items
+    or members, when it is first evaluated. On abort, validation fails with
+    [`ErrorKind::InstanceTooLarge`](crate::ErrorKind::InstanceTooLarge)
+    located at the array or object, whose values exceeded the limit.
+
+    Like the other instance limits, this is checked as values are
+    evaluated, so values which no subschema evaluates are not counted.
+    Property names and decoded content are not part of instance, and
+    are not checked.
+    */
+    pub fn max_instance_nodes(mut self, n: usize) -> Self {
+        self.max_instance_nodes = Some(n);
+        self
+    }
+
+    /// Abort validation, if an array with more than `n` items is evaluated.
+    /// see [`Self::max_instance_nodes`]
+    pub fn max_array_length(mut self, n: usize) -> Self {
+        self.max_array_length = Some(n);
+        self
+    }
+
+    /// Abort validation, if an object with more than `n` members is evaluated.
+    /// see [`Self::max_instance_nodes`]
+    pub fn max_object_members(mut self, n: usize) -> Self {
+        self.max_object_members = Some(n);
+        self
+    }
+
+    /**
+    Abort validation, if a string with more than `n` bytes is evaluated.
+
+    Unlike `maxLength`, which counts characters, this counts bytes in
+    utf-8, so that it is checked without scanning the string.
+    see [`Self::max_instance_nodes`]
+    */
+    pub fn max_string_length(mut self, n: usize) -> Self {
+        self.max_string_length = Some(n);
+        self
+    }
+
+    pub(crate) fn limits_instance(&self) -> bool {
+        self.max_instance_nodes.is_some()
+            || self.max_array_length.is_some()
+            || self.max_object_members.is_some()
+            || self.max_string_length.is_some()
+    }
+
+    /// Report [`Metrics`] in [`ValidationOutput`].
+    pub fn collect_metrics(mut self, yes: bool) -> Self {
+        self.collect_metrics = yes;
+        self
+    }
+
+    /**
+    Report which subschemas of `anyOf` and `oneOf` matched,
+    as [`ValidationOutput::branches`].
+
+    With this enabled, all subschemas of `anyOf` are evaluated,
+    even after one matches.
+    */
+    pub fn collect_branches(mut self, yes: bool) -> Self {
+        self.collect_branches = yes;
+        self
+    }
+
+    /**
+    Calls `visitor` for each instance value, along with each subschema
+    evaluated against it. see [`InstanceVisitor`]
+    */
+    pub fn visitor(mut self, visitor: &'c dyn InstanceVisitor) -> Self {
+        self.visitor = Some(visitor);
+        self
+    }
+}
+
+/**
+Checks instance values, as subschemas are evaluated against them,
+so that constraints depending on external data can be enforced in
+same traversal as validation. see [`ValidationOptions::visitor`]
+
+Only subschemas actually evaluated are visited; for example `then`
+is visited only if `if` matched, and `$ref` target only if the
+referring subschema is evaluated. Visitor is not called:
+
+- for boolean schemas
+- if value fails `type`, `const` or `enum` of the subschema, as its
+  evaluation stops there
+- for property names and decoded content, as they are not part of
+  instance
+- for subschemas with `$ref` in draft-07 and earlier, as everything
+  next to `$ref` is ignored there; its target is visited instead
+
+Failure returned is reported as [`ErrorKind::Custom`](crate::ErrorKind::Custom)
+at the value, like any other keyword failure of the subschema. So it
+is discarded along with the subschema, for example in `anyOf` branch
+that did not match.
+
+```rust
+# use boon::*;
+# use serde_json::{json, Value};
+# use std::cell::RefCell;
+struct UserExists(RefCell<Vec<String>>);
+
+impl InstanceVisitor for UserExists {
+    fn visit(&self, loc: &InstanceLocation, v: &Value, _sch: SchemaIndex) -> Result<(), VisitError> {
+        if loc.to_string().ends_with("/user") {
+            self.0.borrow_mut().push(loc.to_string());
+            if v != "alice" {
+                return Err(VisitError::new("user-exists", format!("no user {v}")));
+            }
+        }
+        Ok(())
+    }
+}
+# let mut compiler = Compiler::new();
+# compiler.add_resource("schema.json", json!({"properties": {"user": {"type": "string"}}}))?;
+# let mut schemas = Schemas::new();
+# let sch_index = compiler.compile("schema.json", &mut schemas)?;
+let visitor = UserExists(RefCell::default());
+let opts = ValidationOptions::new().visitor(&visitor);
+let instance = json!({"user": "bob"});
+let result = schemas.validate_with(&instance, sch_index, &opts);
+assert!(result.unwrap_err().to_string().contains("no user \"bob\""));
+assert_eq!(visitor.0.into_inner(), ["/user"]);
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+*/
+pub trait InstanceVisitor {
+    /**
+    Called with value at `loc`, when subschema `sch` is evaluated
+    against it. Use interior mutability, to record state.
+
+    # Errors
+
+    returns [`VisitError`] if value fails the check.
+    */
+    fn visit(&self, loc: &InstanceLocation, v: &Value, sch: SchemaIndex) -> Result<(), VisitError>;
+}
+
+/// Failure reported by [`InstanceVisitor::visit`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VisitError {
+    /// machine readable identifier of the check that failed.
+    pub code: String,
+    pub message: String,
+}
+
+impl VisitError {
+    pub fn new(code: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            code: code.into(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Result of successful validation with [`ValidationOptions`].
+#[derive(Debug, Default)]
+pub struct ValidationOutput<'s> {
+    /// present only if [`ValidationOptions::collect_metrics`] is enabled.
+    pub metrics: Option<Metrics>,
+    /// non-fatal notes, reported only if [`ValidationOptions::warn_ignored`] is enabled.
+    pub warnings: Vec<ValidationError<'s, 'static>>,
+    /// present only if [`ValidationOptions::collect_branches`] is enabled.
+    pub branches: Vec<MatchedBranches<'s>>,
+}
+
+/**
+Subschemas of an `anyOf` or `oneOf` keyword, that matched an instance value.
+
+Only keywords that contributed to successful validation are reported,
+i.e. not those within a failed subschema. Keywords evaluated against
+`propertyNames` and decoded content are not reported.
+*/
+#[derive(Debug, Clone)]
+pub struct MatchedBranches<'s> {
+    /// location of schema containing the keyword.
+    pub schema_url: &'s str,
+    /// either `anyOf` or `oneOf`.
+    pub keyword: &'static str,
+    pub instance_location: InstanceLocation<'static>,
+    /// indexes of matched subschemas, in ascending order.
+    /// has single entry for `oneOf`.
+    pub indexes: Vec<usize>,
+}
+
+/// Statistics collected during validation.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Metrics {
+    /// number of times a subschema is evaluated against an instance value.
+    pub evaluations: usize,
+}
+
+/**
+Options controlling how values are rendered, when displaying errors
+with [`ValidationError::display_with`](crate::ValidationError::display_with).
+
+Default options are used by `Display` implementations.
+*/
+#[derive(Debug, Clone)]
+pub struct DisplayOptions {
+    pub(crate) max_value_chars: usize,
+    pub(crate) max_list_items: usize,
+    pub(crate) redact_values: bool,
+}
+
+impl Default for DisplayOptions {
+    fn default() -> Self {
+        Self {
+            max_value_chars: 64,
+            max_list_items: 10,
+            redact_values: false,
+        }
+    }
+}
+
+impl DisplayOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Truncate rendered strings and numbers to `n` chars. Defaults to `64`.
+    pub fn max_value_chars(mut self, n: usize) -> Self {
+        self.max_value_chars = n;
+        self
+    }
+
+    /**
+    Render at most `n` items of lists, like `enum` values or missing
+    `required` properties, followed by count of remaining items.
+    Defaults to `10`.
+
+    Errors still hold complete lists, for programmatic access.
+    */
+    pub fn max_list_items(mut self, n: usize) -> Self {
+        self.max_list_items = n.max(1);
+        self
+    }
+
+    /**
+    Do not render values from instance, to avoid leaking sensitive data.
+    Instead their type and size are rendered, like `<string of 12 chars>`.
+
+    Values from schema, like `enum` values, are still rendered.
+    */
+    pub fn redact_values(mut self, yes: bool) -> Self {
+        self.redact_values = yes;
+        self
+    }
+}
+
+/// When to use ANSI colors, in [`PrettyOptions`].
+#[cfg(feature = "fancy-errors")]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Use colors if stderr is a terminal and `NO_COLOR` is not set.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/**
+Options for [`ValidationError::render_pretty`](crate::ValidationError::render_pretty).
+
+```rust
+# use boon::*;
+let text = r#"{"name": 1}"#;
+let opts = PrettyOptions::new()
+    .color(ColorChoice::Never)
+    .source(text)
+    .display_options(DisplayOptions::new().max_value_chars(16));
+```
+*/
+#[cfg(feature = "fancy-errors")]
+#[derive(Debug, Default, Clone)]
+pub struct PrettyOptions<'a> {
+    pub(crate) display: DisplayOptions,
+    pub(crate) color: ColorChoice,
+    pub(crate) source: Option<&'a str>,
+}
+
+#[cfg(feature = "fancy-errors")]
+impl<'a> PrettyOptions<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Options to render values in error messages.
+    pub fn display_options(mut self, opts: DisplayOptions) -> Self {
+        self.display = opts;
+        self
+    }
+
+    /// Whether to use ANSI colors. Defaults to [`ColorChoice::Auto`].
+    pub fn color(mut self, color: ColorChoice) -> Self {
+        self.color = color;
+        self
+    }
+
+    /**
+    Original json `text` of the instance, to show the offending source
+    line below each error. see [`span_of`](crate::span_of)
+
+    Not shown, if values are redacted in [`DisplayOptions`].
+    */
+    pub fn source(mut self, text: &'a str) -> Self {
+        self.source = Some(text);
+        self
+    }
+
+    pub(crate) fn use_color(&self) -> bool {
+        match self.color {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                use std::io::IsTerminal;
+                std::env::var_os("NO_COLOR").is_none() && std::io::stderr().is_terminal()
+            }
+        }
+    }
+}
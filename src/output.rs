@@ -1,17 +1,23 @@
 use std::{
     borrow::Cow,
+    collections::{BTreeMap, HashSet},
     fmt::{Display, Formatter, Write},
+    io,
 };
 
 use serde::{
     ser::{SerializeMap, SerializeSeq},
     Serialize,
 };
+use serde_json::Value;
 
-use crate::{util::*, ErrorKind, InstanceLocation, ValidationError};
+use crate::{
+    excerpt_json, util::*, DisplayOptions, ErrorKind, InstanceError, InstanceLocation,
+    ValidationError,
+};
 
-impl<'s> ValidationError<'s, '_> {
-    fn absolute_keyword_location(&self) -> AbsoluteKeywordLocation<'s> {
+impl ValidationError<'_, '_> {
+    fn absolute_keyword_location(&self) -> AbsoluteKeywordLocation<'_> {
         if let ErrorKind::Reference { url, .. } = &self.kind {
             AbsoluteKeywordLocation {
                 schema_url: url,
@@ -19,13 +25,13 @@ impl<'s> ValidationError<'s, '_> {
             }
         } else {
             AbsoluteKeywordLocation {
-                schema_url: self.schema_url,
+                schema_url: &self.schema_url,
                 keyword_path: self.kind.keyword_path(),
             }
         }
     }
 
-    fn skip(&self) -> bool {
+    pub(crate) fn skip(&self) -> bool {
         self.causes.len() == 1 && matches!(self.kind, ErrorKind::Reference { .. })
     }
 
@@ -35,7 +41,7 @@ impl<'s> ValidationError<'s, '_> {
     }
 
     /// The `Basic` structure, a flat list of output units.
-    pub fn basic_output(&self) -> OutputUnit {
+    pub fn basic_output(&self) -> OutputUnit<'_, '_, '_> {
         let mut outputs = vec![];
 
         let mut in_ref = InRef::default();
@@ -59,6 +65,7 @@ impl<'s> ValidationError<'s, '_> {
                         absolute_keyword_location,
                         instance_location: &e.instance_location,
                         error: OutputError::Leaf(&e.kind),
+                        example: e.example.as_deref(),
                     });
                 }
                 DfsItem::Post(e) => {
@@ -82,11 +89,12 @@ impl<'s> ValidationError<'s, '_> {
             absolute_keyword_location: None,
             instance_location: &self.instance_location,
             error,
+            example: self.example.as_deref(),
         }
     }
 
     /// The `Detailed` structure, based on the schema.
-    pub fn detailed_output(&self) -> OutputUnit {
+    pub fn detailed_output(&self) -> OutputUnit<'_, '_, '_> {
         let mut root = None;
         let mut stack: Vec<OutputUnit> = vec![];
 
@@ -111,6 +119,7 @@ impl<'s> ValidationError<'s, '_> {
                         absolute_keyword_location,
                         instance_location: &e.instance_location,
                         error: OutputError::Leaf(&e.kind),
+                        example: e.example.as_deref(),
                     });
                 }
                 DfsItem::Post(e) => {
@@ -142,6 +151,34 @@ impl<'s> ValidationError<'s, '_> {
 impl Display for ValidationError<'_, '_> {
     /// Formats error hierarchy. Use `#` to show the schema location.
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_with(f, &DisplayOptions::default())
+    }
+}
+
+impl ValidationError<'_, '_> {
+    /**
+    Same as `Display`, but renders values in error messages as per `opts`.
+
+    ```rust
+    # use boon::*;
+    # use serde_json::json;
+    # let mut compiler = Compiler::new();
+    # compiler.add_resource("schema.json", json!({"maxLength": 2}))?;
+    # let mut schemas = Schemas::new();
+    # let sch_index = compiler.compile("schema.json", &mut schemas)?;
+    let instance = json!("secret");
+    if let Err(e) = schemas.validate(&instance, sch_index) {
+        let opts = DisplayOptions::new().redact_values(true);
+        println!("{}", e.display_with(&opts));
+    }
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+    */
+    pub fn display_with<'a>(&'a self, opts: &'a DisplayOptions) -> impl Display + 'a {
+        WithOptions { inner: self, opts }
+    }
+
+    fn fmt_with(&self, f: &mut std::fmt::Formatter<'_>, opts: &DisplayOptions) -> std::fmt::Result {
         let mut indent = Indent::default();
         let mut sloc = SchemaLocation::default();
         // let mut kw_loc = KeywordLocation::default();
@@ -165,7 +202,10 @@ impl Display for ValidationError<'_, '_> {
                             // write!(f, " [{}]", kw_loc.get(e))?;
                             // write!(f, " [{}]", e.absolute_keyword_location())?;
                         }
-                        write!(f, ": {}", e.kind)?;
+                        write!(f, ": {}", e.kind.display_with(opts))?;
+                        if let Some(example) = &e.example {
+                            write!(f, ", e.g. {}", excerpt_json(example, opts))?;
+                        }
                     }
                 }
                 DfsItem::Post(e) => {
@@ -182,6 +222,126 @@ impl Display for ValidationError<'_, '_> {
     }
 }
 
+impl ErrorKind<'_, '_> {
+    /// Same as `Display`, but renders values as per `opts`.
+    pub fn display_with<'a>(&'a self, opts: &'a DisplayOptions) -> impl Display + 'a {
+        WithOptions { inner: self, opts }
+    }
+}
+
+struct WithOptions<'a, T> {
+    inner: &'a T,
+    opts: &'a DisplayOptions,
+}
+
+impl Display for WithOptions<'_, ValidationError<'_, '_>> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.inner.fmt_with(f, self.opts)
+    }
+}
+
+impl Display for WithOptions<'_, ErrorKind<'_, '_>> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.inner.fmt_with(f, self.opts)
+    }
+}
+
+impl Display for InstanceError<'_> {
+    /// Use `#` to show the schema location of validation errors.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Parse {
+                line,
+                column,
+                message,
+            } => write!(
+                f,
+                "instance is not valid json at line {line} column {column}: {message}"
+            ),
+            Self::Decode {
+                format,
+                offset,
+                message,
+            } => write!(
+                f,
+                "instance is not valid {format} at byte offset {offset}: {message}"
+            ),
+            Self::Unsupported {
+                instance_location,
+                message,
+            } => write!(
+                f,
+                "instance at {} cannot be converted to json: {message}",
+                quote(&instance_location.to_string())
+            ),
+            Self::DuplicateKey {
+                instance_location,
+                key,
+            } => write!(
+                f,
+                "instance at {} has duplicate key {}",
+                quote(&instance_location.to_string()),
+                quote(key)
+            ),
+            Self::LoneSurrogate {
+                instance_location,
+                offset,
+            } => write!(
+                f,
+                "instance at {} has lone surrogate escape at byte offset {offset}",
+                quote(&instance_location.to_string())
+            ),
+            Self::Validation(e) => e.fmt(f),
+        }
+    }
+}
+
+impl Serialize for InstanceError<'_> {
+    /// Validation errors are serialized in [basic] output format.
+    ///
+    /// [basic]: https://json-schema.org/draft/2020-12/json-schema-core.html#name-basic
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            Self::Parse { line, column, .. } => {
+                let mut map = serializer.serialize_map(Some(5))?;
+                map.serialize_entry("valid", &false)?;
+                map.serialize_entry("instanceLocation", "")?;
+                map.serialize_entry("line", line)?;
+                map.serialize_entry("column", column)?;
+                map.serialize_entry("error", &self.to_string())?;
+                map.end()
+            }
+            Self::Decode { offset, .. } => {
+                let mut map = serializer.serialize_map(Some(4))?;
+                map.serialize_entry("valid", &false)?;
+                map.serialize_entry("instanceLocation", "")?;
+                map.serialize_entry("offset", offset)?;
+                map.serialize_entry("error", &self.to_string())?;
+                map.end()
+            }
+            Self::Unsupported {
+                instance_location, ..
+            }
+            | Self::DuplicateKey {
+                instance_location, ..
+            }
+            | Self::LoneSurrogate {
+                instance_location, ..
+            } => {
+                let mut map = serializer.serialize_map(Some(3))?;
+                map.serialize_entry("valid", &false)?;
+                map.serialize_entry("instanceLocation", &instance_location.to_string())?;
+                map.serialize_entry("error", &self.to_string())?;
+                map.end()
+            }
+            Self::Validation(e) => e.basic_output().serialize(serializer),
+        }
+    }
+}
+
 struct DfsIterator<'a, 'v, 's> {
     root: Option<&'a ValidationError<'v, 's>>,
     stack: Vec<Frame<'a, 'v, 's>>,
@@ -268,16 +428,16 @@ impl Indent {
 // SchemaLocation
 
 #[derive(Default)]
-struct SchemaLocation<'a, 's, 'v> {
+pub(crate) struct SchemaLocation<'a, 's, 'v> {
     stack: Vec<&'a ValidationError<'s, 'v>>,
 }
 
 impl<'a, 's, 'v> SchemaLocation<'a, 's, 'v> {
-    fn pre(&mut self, e: &'a ValidationError<'s, 'v>) {
+    pub(crate) fn pre(&mut self, e: &'a ValidationError<'s, 'v>) {
         self.stack.push(e);
     }
 
-    fn post(&mut self) {
+    pub(crate) fn post(&mut self) {
         self.stack.pop();
     }
 }
@@ -287,8 +447,8 @@ impl Display for SchemaLocation<'_, '_, '_> {
         let mut iter = self.stack.iter().cloned();
         let cur = iter.next_back().unwrap();
         let cur: Cow<str> = match &cur.kind {
-            ErrorKind::Schema { url } => Cow::Borrowed(url),
-            ErrorKind::Reference { url, .. } => Cow::Borrowed(url),
+            ErrorKind::Schema { url } => Cow::Borrowed(url.as_ref()),
+            ErrorKind::Reference { url, .. } => Cow::Borrowed(url.as_ref()),
             _ => Cow::Owned(cur.absolute_keyword_location().to_string()),
         };
 
@@ -306,7 +466,7 @@ impl Display for SchemaLocation<'_, '_, '_> {
                 p
             }
             _ => {
-                let (p, _) = split(prev.schema_url);
+                let (p, _) = split(&prev.schema_url);
                 p
             }
         };
@@ -332,11 +492,13 @@ impl<'a> KeywordLocation<'a> {
         let cur = match &e.kind {
             ErrorKind::Schema { url } => url,
             ErrorKind::Reference { url, .. } => url,
-            _ => e.schema_url,
+            _ => &e.schema_url,
         };
 
         if let Some((prev, _)) = self.stack.last() {
-            self.loc.push_str(&e.schema_url[prev.len()..]); // todo: url-decode
+            self.loc.push_str(&Fragment::decode(
+                e.schema_url.get(prev.len()..).unwrap_or(""),
+            ));
             if let ErrorKind::Reference { kw, .. } = &e.kind {
                 self.loc.push('/');
                 self.loc.push_str(kw);
@@ -417,10 +579,12 @@ pub struct OutputUnit<'e, 's, 'v> {
     pub valid: bool,
     pub keyword_location: String,
     /// The absolute, dereferenced location of the validating keyword
-    pub absolute_keyword_location: Option<AbsoluteKeywordLocation<'s>>,
+    pub absolute_keyword_location: Option<AbsoluteKeywordLocation<'e>>,
     /// The location of the JSON value within the instance being validated
     pub instance_location: &'e InstanceLocation<'v>,
     pub error: OutputError<'e, 's, 'v>,
+    /// see [`ValidationError::example`]
+    pub example: Option<&'e Value>,
 }
 
 impl Serialize for OutputUnit<'_, '_, '_> {
@@ -428,7 +592,14 @@ impl Serialize for OutputUnit<'_, '_, '_> {
     where
         S: serde::Serializer,
     {
-        let n = 4 + self.absolute_keyword_location.as_ref().map_or(0, |_| 1);
+        let limit = match self.error {
+            OutputError::Leaf(kind) => kind.limit(),
+            OutputError::Branch(_) => None,
+        };
+        let n = 4
+            + self.absolute_keyword_location.as_ref().map_or(0, |_| 1)
+            + limit.as_ref().map_or(0, |_| 2)
+            + self.example.map_or(0, |_| 1);
         let mut map = serializer.serialize_map(Some(n))?;
         map.serialize_entry("valid", &self.valid)?;
         map.serialize_entry("keywordLocation", &self.keyword_location.to_string())?;
@@ -441,6 +612,13 @@ impl Serialize for OutputUnit<'_, '_, '_> {
             OutputError::Branch(_) => "errors",
         };
         map.serialize_entry(pname, &self.error)?;
+        if let Some(limit) = &limit {
+            map.serialize_entry("actual", &limit.actual)?;
+            map.serialize_entry("limit", &limit.limit)?;
+        }
+        if let Some(example) = self.example {
+            map.serialize_entry("example", example)?;
+        }
         map.end()
     }
 }
@@ -477,10 +655,252 @@ impl Serialize for OutputError<'_, '_, '_> {
     }
 }
 
+// by_instance_location --
+
+impl<'s, 'v> ValidationError<'s, 'v> {
+    /**
+    Leaf errors grouped by instance location, for consumers like forms,
+    that show messages next to each field rather than a tree.
+
+    Locations are ordered by their tokens, with array indexes compared
+    as numbers. Leaves with same message at same absolute keyword location,
+    as happens when same schema is applied more than once via `allOf`,
+    are reported once.
+
+    ```rust
+    # use boon::*;
+    # use serde_json::json;
+    # let mut compiler = Compiler::new();
+    let schema = json!({"items": {"minimum": 0}});
+    # compiler.add_resource("schema.json", schema)?;
+    # let mut schemas = Schemas::new();
+    # let sch_index = compiler.compile("schema.json", &mut schemas)?;
+    let instance = json!([-1, 1, -2]);
+    let err = schemas.validate(&instance, sch_index).unwrap_err();
+    let groups = err.by_instance_location();
+    let locations = groups.keys().map(|loc| loc.to_string()).collect::<Vec<_>>();
+    assert_eq!(locations, ["/0", "/2"]);
+    assert_eq!(groups.values().next().unwrap()[0].code, "minimum");
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+    */
+    pub fn by_instance_location(
+        &self,
+    ) -> BTreeMap<&InstanceLocation<'v>, Vec<LeafErrorRef<'_, 's, 'v>>> {
+        let mut groups: BTreeMap<_, Vec<_>> = BTreeMap::new();
+        let mut seen = HashSet::new();
+        let mut kw_loc = KeywordLocation::default();
+        for node in DfsIterator::new(self) {
+            match node {
+                DfsItem::Pre(e) => {
+                    kw_loc.pre(e);
+                    if !e.causes.is_empty() {
+                        continue;
+                    }
+                    let key = (
+                        &e.instance_location,
+                        e.absolute_keyword_location().to_string(),
+                        e.kind.to_string(),
+                    );
+                    if !seen.insert(key) {
+                        continue;
+                    }
+                    groups
+                        .entry(&e.instance_location)
+                        .or_default()
+                        .push(LeafErrorRef {
+                            code: e.kind.code(),
+                            keyword_location: kw_loc.get(e),
+                            kind: &e.kind,
+                        });
+                }
+                DfsItem::Post(_) => kw_loc.post(),
+            }
+        }
+        groups
+    }
+}
+
+/// Leaf error, as reported by [`ValidationError::by_instance_location`].
+#[derive(Debug)]
+pub struct LeafErrorRef<'e, 's, 'v> {
+    /// keyword that failed, for example `minLength`. `false` for false
+    /// schema, and [`ErrorKind::Custom`] code for custom errors.
+    pub code: &'e str,
+    /// relative location of failed keyword, as in basic output.
+    pub keyword_location: String,
+    pub kind: &'e ErrorKind<'s, 'v>,
+}
+
+impl ErrorKind<'_, '_> {
+    pub(crate) fn code(&self) -> &str {
+        match self {
+            ErrorKind::Custom { code, .. } => code,
+            ErrorKind::FalseSchema => "false",
+            ErrorKind::RefCycle { .. } => "$ref",
+            ErrorKind::BudgetExceeded { .. } => "budget",
+            ErrorKind::InstanceTooLarge { .. } => "instance-size",
+            _ => match self.keyword_path() {
+                Some(path) => path.keyword,
+                None => "",
+            },
+        }
+    }
+}
+
+// json lines --
+
+impl ValidationError<'_, '_> {
+    /**
+    Writes each leaf error as json object on its own line, for log
+    pipelines consuming [JSON Lines].
+
+    Each line has `schema`, the url of schema validated against, so that
+    lines are self-contained. Then `instanceLocation`, `keywordLocation`,
+    `absoluteKeywordLocation`, `code` as in [`LeafErrorRef::code`], and
+    `error` message. Lines are written in the order of [`ValidationError::causes`],
+    without collecting them first, and their count is [`ValidationError::error_count`].
+
+    ```rust
+    # use boon::*;
+    # use serde_json::json;
+    # let mut compiler = Compiler::new();
+    # compiler.add_resource("schema.json", json!({"items": {"minimum": 0}}))?;
+    # let mut schemas = Schemas::new();
+    # let sch_index = compiler.compile("schema.json", &mut schemas)?;
+    let instance = json!([-1, 1, -2]);
+    let err = schemas.validate(&instance, sch_index).unwrap_err();
+    let mut out = vec![];
+    err.to_json_lines(&mut out)?;
+    assert_eq!(String::from_utf8(out)?.lines().count(), err.error_count());
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+
+    [JSON Lines]: https://jsonlines.org
+    */
+    pub fn to_json_lines(&self, w: &mut impl io::Write) -> io::Result<()> {
+        let mut kw_loc = KeywordLocation::default();
+        for node in DfsIterator::new(self) {
+            match node {
+                DfsItem::Pre(e) => {
+                    kw_loc.pre(e);
+                    if !e.causes.is_empty() {
+                        continue;
+                    }
+                    let line = JsonLine {
+                        schema: &self.schema_url,
+                        keyword_location: kw_loc.get(e),
+                        e,
+                    };
+                    // strings are escaped, so that line has no newline
+                    serde_json::to_writer(&mut *w, &line)?;
+                    w.write_all(b"\n")?;
+                }
+                DfsItem::Post(_) => kw_loc.post(),
+            }
+        }
+        Ok(())
+    }
+
+    /// Number of leaf errors, that is errors without causes.
+    pub fn error_count(&self) -> usize {
+        DfsIterator::new(self)
+            .filter(|node| matches!(node, DfsItem::Pre(e) if e.causes.is_empty()))
+            .count()
+    }
+}
+
+struct JsonLine<'a, 'e, 's, 'v> {
+    schema: &'a str,
+    keyword_location: String,
+    e: &'e ValidationError<'s, 'v>,
+}
+
+impl Serialize for JsonLine<'_, '_, '_, '_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(6))?;
+        map.serialize_entry("schema", self.schema)?;
+        map.serialize_entry("instanceLocation", &self.e.instance_location.to_string())?;
+        map.serialize_entry("keywordLocation", &self.keyword_location)?;
+        map.serialize_entry(
+            "absoluteKeywordLocation",
+            &self.e.absolute_keyword_location().to_string(),
+        )?;
+        map.serialize_entry("code", self.e.kind.code())?;
+        map.serialize_entry("error", &self.e.kind.to_string())?;
+        map.end()
+    }
+}
+
+// LimitInfo --
+
+/**
+Normalized form of bounds-style errors, see [`ErrorKind::limit`].
+
+For `minContains`/`maxContains`, `actual` is number of matched items.
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct LimitInfo {
+    /// keyword that failed.
+    pub keyword: &'static str,
+    /// measured from instance: length, count or the number itself.
+    pub actual: Value,
+    /// bound given in schema.
+    pub limit: Value,
+    /// whether `actual` equal to `limit` fails too.
+    pub exclusive: bool,
+}
+
+impl ErrorKind<'_, '_> {
+    /**
+    Returns `actual` and `limit` of bounds-style error, regardless of
+    keyword.
+
+    These are `minLength`, `maxLength`, `minItems`, `maxItems`,
+    `minProperties`, `maxProperties`, `minContains`, `maxContains`,
+    `minimum`, `maximum`, `exclusiveMinimum`, `exclusiveMaximum` and
+    `multipleOf`. Returns `None` for others.
+    */
+    pub fn limit(&self) -> Option<LimitInfo> {
+        use ErrorKind::*;
+        let (actual, limit, exclusive) = match self {
+            MinLength { got, want }
+            | MaxLength { got, want }
+            | MinItems { got, want }
+            | MaxItems { got, want }
+            | MinProperties { got, want }
+            | MaxProperties { got, want } => (Value::from(*got), Value::from(*want), false),
+            MinContains { got, want } | MaxContains { got, want } => {
+                (Value::from(got.len()), Value::from(*want), false)
+            }
+            Minimum { got, want } | Maximum { got, want } | MultipleOf { got, want } => (
+                Value::Number(got.as_ref().clone()),
+                Value::Number(want.as_ref().clone()),
+                false,
+            ),
+            ExclusiveMinimum { got, want } | ExclusiveMaximum { got, want } => (
+                Value::Number(got.as_ref().clone()),
+                Value::Number(want.as_ref().clone()),
+                true,
+            ),
+            _ => return None,
+        };
+        Some(LimitInfo {
+            keyword: self.keyword_path()?.keyword,
+            actual,
+            limit,
+            exclusive,
+        })
+    }
+}
+
 // AbsoluteKeywordLocation --
 
-impl<'s> ErrorKind<'s, '_> {
-    pub fn keyword_path(&self) -> Option<KeywordPath<'s>> {
+impl ErrorKind<'_, '_> {
+    pub fn keyword_path(&self) -> Option<KeywordPath<'_>> {
         #[inline(always)]
         fn kw(kw: &'static str) -> Option<KeywordPath<'static>> {
             Some(KeywordPath {
@@ -505,15 +925,24 @@ impl<'s> ErrorKind<'s, '_> {
             PropertyName { .. } => kw("propertyNames"),
             Reference { kw: kword, .. } => kw(kword),
             RefCycle { .. } => None,
+            IgnoredKeyword { .. } => None,
+            BudgetExceeded { .. } => None,
+            InstanceTooLarge { .. } => None,
             FalseSchema => None,
             Type { .. } => kw("type"),
             Enum { .. } => kw("enum"),
             Const { .. } => kw("const"),
             Format { .. } => kw("format"),
+            Keyword { keyword, .. } => kw(keyword),
+            Custom { .. } => None,
             MinProperties { .. } => kw("minProperties"),
             MaxProperties { .. } => kw("maxProperties"),
             AdditionalProperties { .. } => kw("additionalProperty"),
             Required { .. } => kw("required"),
+            RequiredProperty { index, .. } => Some(KeywordPath {
+                keyword: "required",
+                token: Some(SchemaToken::Item(*index)),
+            }),
             Dependency { prop, .. } => kw_prop("dependencies", prop),
             DependentRequired { prop, .. } => kw_prop("dependentRequired", prop),
             MinItems { .. } => kw("minItems"),
@@ -528,6 +957,7 @@ impl<'s> ErrorKind<'s, '_> {
             Pattern { .. } => kw("pattern"),
             ContentEncoding { .. } => kw("contentEncoding"),
             ContentMediaType { .. } => kw("contentMediaType"),
+            ContentTooLarge { kw: kword, .. } => kw(kword),
             Minimum { .. } => kw("minimum"),
             Maximum { .. } => kw("maximum"),
             ExclusiveMinimum { .. } => kw("exclusiveMinimum"),
@@ -559,7 +989,7 @@ impl Display for AbsoluteKeywordLocation<'_> {
             if let Some(token) = &path.token {
                 f.write_str("/")?;
                 match token {
-                    SchemaToken::Prop(p) => write!(f, "{}", escape(p))?, // todo: url-encode
+                    SchemaToken::Prop(p) => f.write_str(&Fragment::encode(&escape(p)))?,
                     SchemaToken::Item(i) => write!(f, "{i}")?,
                 }
             }
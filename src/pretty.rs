@@ -0,0 +1,169 @@
+use std::fmt::Write;
+
+use crate::{output::SchemaLocation, util::*, *};
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const RED: &str = "\x1b[31m";
+const CYAN: &str = "\x1b[36m";
+
+// max chars of source line shown around the offending value
+const MAX_LINE_CHARS: usize = 100;
+
+impl ValidationError<'_, '_> {
+    /**
+    Renders error hierarchy as a tree, for showing in terminal.
+
+    Each error shows its instance location and schema location, like
+    alternate `Display`. If `opts` has the instance source, leaf errors
+    also show the source line with the offending value underlined.
+
+    ```rust
+    # use boon::*;
+    # use serde_json::json;
+    # let mut compiler = Compiler::new();
+    # compiler.add_resource("schema.json", json!({"properties": {"name": {"type": "string"}}}))?;
+    # let mut schemas = Schemas::new();
+    # let sch_index = compiler.compile("schema.json", &mut schemas)?;
+    let text = r#"{"name": 1}"#;
+    let instance = serde_json::from_str(text)?;
+    if let Err(e) = schemas.validate(&instance, sch_index) {
+        let opts = PrettyOptions::new().source(text);
+        eprintln!("{}", e.render_pretty(&opts));
+    }
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+    */
+    pub fn render_pretty(&self, opts: &PrettyOptions) -> String {
+        let mut r = Renderer {
+            opts,
+            color: opts.use_color(),
+            out: String::new(),
+            sloc: SchemaLocation::default(),
+        };
+        r.render(self, "", None);
+        r.out.truncate(r.out.trim_end().len());
+        r.out
+    }
+}
+
+struct Renderer<'a, 'o, 's, 'v> {
+    opts: &'o PrettyOptions<'o>,
+    color: bool,
+    out: String,
+    sloc: SchemaLocation<'a, 's, 'v>,
+}
+
+impl<'a, 's, 'v> Renderer<'a, '_, 's, 'v> {
+    // `last` is None for root, otherwise whether `e` is last of its siblings
+    fn render(&mut self, e: &'a ValidationError<'s, 'v>, prefix: &str, last: Option<bool>) {
+        if e.skip() {
+            return self.render(&e.causes[0], prefix, last);
+        }
+        self.sloc.pre(e);
+        let (branch, child_prefix) = match last {
+            None => ("", String::new()),
+            Some(true) => ("└── ", format!("{prefix}    ")),
+            Some(false) => ("├── ", format!("{prefix}│   ")),
+        };
+        self.out.push_str(&self.paint(prefix, DIM));
+        self.out.push_str(&self.paint(branch, DIM));
+
+        let opts = &self.opts.display;
+        if let ErrorKind::Schema { .. } = &e.kind {
+            let line = format!("jsonschema {}", e.kind);
+            self.out.push_str(&self.paint(&line, BOLD));
+        } else {
+            let loc = quote(&e.instance_location.to_string());
+            let sloc = format!("[{}]", self.sloc);
+            let mut msg = e.kind.display_with(opts).to_string();
+            if let Some(example) = &e.example {
+                write!(msg, ", e.g. {}", excerpt_json(example, opts)).ok();
+            }
+            let msg_style = if e.causes.is_empty() { RED } else { "" };
+            let line = format!(
+                "at {} {}: {}",
+                self.paint(&loc, CYAN),
+                self.paint(&sloc, DIM),
+                self.paint(&msg, msg_style)
+            );
+            self.out.push_str(&line);
+        }
+        self.out.push('\n');
+
+        if e.causes.is_empty() {
+            self.snippet(e, &child_prefix);
+        }
+        for (i, cause) in e.causes.iter().enumerate() {
+            self.render(cause, &child_prefix, Some(i + 1 == e.causes.len()));
+        }
+        self.sloc.post();
+    }
+
+    // shows source line of value at instance location of `e`
+    fn snippet(&mut self, e: &ValidationError, prefix: &str) {
+        let Some(text) = self.opts.source else {
+            return;
+        };
+        if self.opts.display.redact_values {
+            return;
+        }
+        let Some(span) = span_of(text, &e.instance_location) else {
+            return;
+        };
+        let line_start = text[..span.start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = text[span.start..]
+            .find('\n')
+            .map_or(text.len(), |i| span.start + i);
+        let line_no = text[..span.start].matches('\n').count() + 1;
+
+        // window of the line, around the value
+        let chars = text[line_start..line_end]
+            .trim_end_matches('\r')
+            .chars()
+            .map(|c| if c == '\t' { ' ' } else { c })
+            .collect::<Vec<_>>();
+        let col = text[line_start..span.start].chars().count();
+        let len = text[span.start..span.end.min(line_end)].chars().count();
+        let from = col.saturating_sub(MAX_LINE_CHARS / 3);
+        let to = chars.len().min(from + MAX_LINE_CHARS);
+        let mut line = chars[from..to].iter().collect::<String>();
+        let mut col = col - from;
+        if from > 0 {
+            line.insert(0, '…');
+            col += 1;
+        }
+        if to < chars.len() {
+            line.push('…');
+        }
+        let len = len.clamp(1, (to - from).saturating_sub(col).max(1));
+
+        let gutter = line_no.to_string();
+        let blank = " ".repeat(gutter.len());
+        let carets = "^".repeat(len);
+        let prefix = self.paint(prefix, DIM);
+        let bar = self.paint("│", DIM);
+        writeln!(
+            self.out,
+            "{prefix}{} {bar} {line}",
+            self.paint(&gutter, DIM)
+        )
+        .ok();
+        writeln!(
+            self.out,
+            "{prefix}{blank} {bar} {}{}",
+            " ".repeat(col),
+            self.paint(&carets, RED)
+        )
+        .ok();
+    }
+
+    fn paint(&self, s: &str, style: &str) -> String {
+        if self.color && !style.is_empty() && !s.is_empty() {
+            format!("{style}{s}{RESET}")
+        } else {
+            s.to_owned()
+        }
+    }
+}
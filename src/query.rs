@@ -0,0 +1,415 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::*;
+
+/// Answer to a static query about schema, made without any instance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriState {
+    Yes,
+    No,
+    /// Answer depends on instance, for example due to `anyOf`, or
+    /// `patternProperties`.
+    Unknown,
+}
+
+pub(crate) fn allows_additional_properties(
+    schemas: &Schemas,
+    sch: SchemaIndex,
+    path: &[&str],
+) -> TriState {
+    let q = Query { schemas };
+    let applied = q.walk(sch, path);
+    if applied.never {
+        return TriState::No;
+    }
+    let mut unknown = applied.partial;
+    for &sch in &applied.list {
+        let s = schemas.get(sch);
+        match &s.additional_properties {
+            Some(Additional::Bool(false)) => {
+                if s.pattern_properties.is_empty() {
+                    return TriState::No;
+                }
+                unknown = true;
+            }
+            Some(Additional::SchemaRef(a)) if q.is_false(*a) => {
+                if s.pattern_properties.is_empty() {
+                    return TriState::No;
+                }
+                unknown = true;
+            }
+            _ => {}
+        }
+        if let Some(u) = s.unevaluated_properties {
+            if q.is_false(u) {
+                match q.evaluates_any(s, &mut HashSet::new()) {
+                    Some(false) => return TriState::No,
+                    Some(true) => {}
+                    None => unknown = true,
+                }
+            }
+        }
+        if s.property_names.is_some() {
+            unknown = true;
+        }
+    }
+    if unknown {
+        TriState::Unknown
+    } else {
+        TriState::Yes
+    }
+}
+
+pub(crate) fn is_property_required(schemas: &Schemas, sch: SchemaIndex, path: &[&str]) -> TriState {
+    let Some((name, parent)) = path.split_last() else {
+        return TriState::Unknown;
+    };
+    let q = Query { schemas };
+    let applied = q.walk(sch, parent);
+    if applied.never {
+        return TriState::Unknown;
+    }
+    let mut unknown = applied.partial;
+    for &sch in &applied.list {
+        let s = schemas.get(sch);
+        if s.required.iter().any(|r| r == name) {
+            return TriState::Yes;
+        }
+        let in_dependency = s
+            .dependent_required
+            .iter()
+            .any(|(_, props)| props.iter().any(|p| p == name))
+            || s.dependencies.iter().any(|(_, dep)| match dep {
+                Dependency::Props(props) => props.iter().any(|p| p == name),
+                Dependency::SchemaRef(_) => true,
+            });
+        if in_dependency {
+            unknown = true;
+        }
+    }
+    if unknown {
+        TriState::Unknown
+    } else {
+        TriState::No
+    }
+}
+
+pub(crate) fn accepted_types(schemas: &Schemas, sch: SchemaIndex) -> Types {
+    let types = accepted(schemas, sch);
+    // integral numbers are numbers
+    if types.contains(Type::Number) {
+        Types(types.0 & !(Type::Integer as u8))
+    } else {
+        types
+    }
+}
+
+pub(crate) fn accepts_type(schemas: &Schemas, sch: SchemaIndex, t: Type) -> bool {
+    let types = accepted(schemas, sch);
+    match t {
+        Type::Number => types.0 & (Type::Number as u8 | Type::Integer as u8) != 0,
+        _ => types.contains(t),
+    }
+}
+
+// returns types accepted by `sch`, computing them on first call.
+//
+// `Schema::accepted` caches answer of each query, and answers not cut
+// short by cycle, of schemas visited meanwhile. Those are marked exact,
+// and only they are reused by other queries, so that answer does not
+// depend on the order of queries.
+fn accepted(schemas: &Schemas, sch: SchemaIndex) -> Types {
+    if let Some((types, _)) = schemas.get(sch).accepted.get() {
+        return *types;
+    }
+    let mut q = TypeQuery {
+        schemas,
+        visiting: HashSet::new(),
+        done: HashMap::new(),
+        cyclic: false,
+    };
+    let types = q.accepted(sch);
+    schemas.get(sch).accepted.get_or_init(|| (types, false)).0
+}
+
+// in `Types` used here, `Type::Number` stands for numbers that are not
+// integers, so that sets intersect and union bitwise.
+const ALL_TYPES: Types = Types(0x7f);
+
+struct TypeQuery<'s> {
+    schemas: &'s Schemas,
+    visiting: HashSet<SchemaIndex>,    // to detect cycles
+    done: HashMap<SchemaIndex, Types>, // answers in current query, cut short by cycle
+    cyclic: bool,                      // whether current answer hit cycle
+}
+
+impl TypeQuery<'_> {
+    // superset of types of values that `sch` accepts
+    fn accepted(&mut self, sch: SchemaIndex) -> Types {
+        let cached = &self.schemas.get(sch).accepted;
+        if let Some((types, true)) = cached.get() {
+            return *types;
+        }
+        if let Some(types) = self.done.get(&sch) {
+            self.cyclic = true;
+            return *types;
+        }
+        if !self.visiting.insert(sch) {
+            self.cyclic = true;
+            return ALL_TYPES; // cycle, cannot conclude
+        }
+        let outer = std::mem::replace(&mut self.cyclic, false);
+        let types = self.accepted_local(sch);
+        self.visiting.remove(&sch);
+        if self.cyclic {
+            self.done.insert(sch, types);
+        } else {
+            _ = cached.set((types, true));
+        }
+        self.cyclic |= outer;
+        types
+    }
+
+    fn accepted_local(&mut self, sch: SchemaIndex) -> Types {
+        let s = self.schemas.get(sch);
+        match s.boolean {
+            Some(false) => return Types::default(),
+            Some(true) => return ALL_TYPES,
+            None => {}
+        }
+        if let Some(ref_) = s.ref_ {
+            if s.draft_version < 2019 {
+                return self.accepted(ref_); // siblings of $ref ignored
+            }
+        }
+        let mut types = ALL_TYPES;
+        if !s.types.is_empty() {
+            let mut declared = s.types;
+            if declared.contains(Type::Number) {
+                declared.add(Type::Integer);
+            }
+            types.0 &= declared.0;
+        }
+        if let Some(c) = &s.constant {
+            types.0 &= value_types(c).0;
+        }
+        if let Some(e) = &s.enum_ {
+            types.0 &= e.values.iter().fold(0, |acc, v| acc | value_types(v).0);
+        }
+        for sch in s.ref_.iter().chain(&s.all_of) {
+            types.0 &= self.accepted(*sch).0;
+        }
+        for list in [&s.any_of, &s.one_of] {
+            if !list.is_empty() {
+                types.0 &= list.iter().fold(0, |acc, sch| acc | self.accepted(*sch).0);
+            }
+        }
+        if let Some(if_) = s.if_ {
+            let branch = |q: &mut Self, sch: Option<SchemaIndex>| {
+                sch.map_or(ALL_TYPES, |sch| q.accepted(sch))
+            };
+            // branch not taken by boolean `if` is not compiled
+            types.0 &= match self.schemas.get(if_).boolean {
+                Some(true) => branch(self, s.then).0,
+                Some(false) => branch(self, s.else_).0,
+                None => branch(self, s.then).0 | branch(self, s.else_).0,
+            };
+        }
+        types
+    }
+}
+
+// types of values equal to `v`
+fn value_types(v: &Value) -> Types {
+    let mut types = Types::default();
+    match v {
+        Value::Number(n) => {
+            if n.as_f64().is_none_or(|f| f.fract() == 0.0) {
+                types.add(Type::Integer);
+            }
+            // equal value may be written with fraction, like 1.0
+            types.add(Type::Number);
+        }
+        _ => types.add(Type::of(v)),
+    }
+    types
+}
+
+// schemas that apply to same value, regardless of instance.
+#[derive(Default)]
+struct Applied {
+    list: Vec<SchemaIndex>,
+    visited: HashSet<SchemaIndex>,
+    /// some subschemas apply depending on instance, so `list` may
+    /// not have all constraints.
+    partial: bool,
+    /// value cannot exist, due to `false` schema.
+    never: bool,
+}
+
+struct Query<'s> {
+    schemas: &'s Schemas,
+}
+
+impl Query<'_> {
+    fn walk(&self, sch: SchemaIndex, path: &[&str]) -> Applied {
+        let mut applied = Applied::default();
+        self.collect(sch, &mut applied);
+        for name in path {
+            applied = self.child(&applied, name);
+        }
+        applied
+    }
+
+    fn is_false(&self, sch: SchemaIndex) -> bool {
+        self.schemas.get(sch).boolean == Some(false)
+    }
+
+    // collects `sch` along with in-place applicators that always apply.
+    fn collect(&self, sch: SchemaIndex, out: &mut Applied) {
+        if !out.visited.insert(sch) {
+            return; // cycle
+        }
+        let s = self.schemas.get(sch);
+        if let Some(ref_) = s.ref_ {
+            self.collect(ref_, out);
+            if s.draft_version < 2019 {
+                return; // siblings of $ref ignored
+            }
+        }
+        out.list.push(sch);
+        if s.boolean == Some(false) {
+            out.never = true;
+        }
+        for &sch in &s.all_of {
+            self.collect(sch, out);
+        }
+        if has_conditionals(s) {
+            out.partial = true;
+        }
+    }
+
+    // schemas that apply to property `name` of value, to which `parent` applies.
+    fn child(&self, parent: &Applied, name: &str) -> Applied {
+        let mut out = Applied {
+            partial: parent.partial,
+            never: parent.never,
+            ..Default::default()
+        };
+        for &sch in &parent.list {
+            let s = self.schemas.get(sch);
+            let mut matched = false;
+            if let Some(&psch) = s.properties.get(name) {
+                matched = true;
+                self.collect(psch, &mut out);
+            }
+            for (regex, psch) in &s.pattern_properties {
+                if regex.is_match(name) {
+                    matched = true;
+                    self.collect(*psch, &mut out);
+                }
+            }
+            if !matched {
+                match &s.additional_properties {
+                    Some(Additional::Bool(false)) => out.never = true,
+                    Some(Additional::SchemaRef(asch)) => self.collect(*asch, &mut out),
+                    _ => {}
+                }
+            }
+            if let Some(usch) = s.unevaluated_properties {
+                if matched || s.additional_properties.is_some() {
+                    continue;
+                }
+                match self.evaluates_sub(s, name, &mut HashSet::new()) {
+                    Some(false) => self.collect(usch, &mut out),
+                    Some(true) => {}
+                    None => out.partial = true,
+                }
+            }
+        }
+        out
+    }
+
+    // whether in-place subschemas of `s` evaluate property `name`.
+    // returns None, if it depends on instance.
+    fn evaluates_sub(
+        &self,
+        s: &Schema,
+        name: &str,
+        visited: &mut HashSet<SchemaIndex>,
+    ) -> Option<bool> {
+        let mut result = Some(false);
+        for sch in s.ref_.iter().chain(&s.all_of) {
+            if !visited.insert(*sch) {
+                continue;
+            }
+            let sub = self.schemas.get(*sch);
+            let local = sub.properties.contains_key(name)
+                || sub
+                    .pattern_properties
+                    .iter()
+                    .any(|(re, _)| re.is_match(name))
+                || sub.additional_properties.is_some()
+                || sub.unevaluated_properties.is_some();
+            if local {
+                return Some(true);
+            }
+            match self.evaluates_sub(sub, name, visited) {
+                Some(true) => return Some(true),
+                Some(false) => {}
+                None => result = None,
+            }
+        }
+        if has_conditionals(s) {
+            result = None;
+        }
+        result
+    }
+
+    // whether in-place subschemas of `s` evaluate any property not
+    // known in advance. returns None, if it depends on property name
+    // or instance.
+    fn evaluates_any(&self, s: &Schema, visited: &mut HashSet<SchemaIndex>) -> Option<bool> {
+        let mut result = if s.pattern_properties.is_empty() {
+            Some(false)
+        } else {
+            None
+        };
+        if s.additional_properties.is_some() {
+            return Some(true);
+        }
+        for sch in s.ref_.iter().chain(&s.all_of) {
+            if !visited.insert(*sch) {
+                continue;
+            }
+            let sub = self.schemas.get(*sch);
+            if sub.unevaluated_properties.is_some() {
+                return Some(true);
+            }
+            match self.evaluates_any(sub, visited) {
+                Some(true) => return Some(true),
+                Some(false) => {}
+                None => result = None,
+            }
+        }
+        if has_conditionals(s) {
+            result = None;
+        }
+        result
+    }
+}
+
+// whether `s` has in-place applicators, whose effect depends on instance.
+fn has_conditionals(s: &Schema) -> bool {
+    !s.any_of.is_empty()
+        || !s.one_of.is_empty()
+        || s.not.is_some()
+        || s.if_.is_some()
+        || !s.dependent_schemas.is_empty()
+        || s.dependencies
+            .iter()
+            .any(|(_, dep)| matches!(dep, Dependency::SchemaRef(_)))
+        || s.recursive_ref.is_some()
+        || s.dynamic_ref.is_some()
+        || !s.keywords.is_empty()
+}
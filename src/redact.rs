@@ -0,0 +1,66 @@
+use std::collections::HashSet;
+
+use serde_json::Value;
+
+use crate::{validator, InstanceLocation, InstanceToken, Schema, Schemas};
+
+const REDACTED: &str = "[REDACTED]";
+
+/// Which values to redact, in [`Schemas::redact`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedactBy<'a> {
+    /// values described by subschema with `"writeOnly": true`.
+    WriteOnly,
+    /// values described by subschema with given annotation set to `true`,
+    /// for example `"x-sensitive": true`.
+    Annotation(&'a str),
+}
+
+impl RedactBy<'_> {
+    fn keyword(&self) -> &str {
+        match self {
+            RedactBy::WriteOnly => "writeOnly",
+            RedactBy::Annotation(kw) => kw,
+        }
+    }
+}
+
+pub(crate) fn redact(
+    v: &mut Value,
+    schema: &Schema,
+    schemas: &Schemas,
+    by: RedactBy,
+) -> Vec<InstanceLocation<'static>> {
+    let locs = validator::annotated(v, schema, schemas, by.keyword());
+    let set = locs.iter().collect::<HashSet<_>>();
+    let mut seen = HashSet::new();
+    let mut redacted = vec![];
+    for loc in &locs {
+        // values within redacted value, are already gone
+        let within = (0..loc.tokens.len()).any(|n| {
+            set.contains(&InstanceLocation {
+                tokens: loc.tokens[..n].to_vec(),
+            })
+        });
+        if within || !seen.insert(loc) {
+            continue;
+        }
+        if let Some(value) = pointer_mut(v, &loc.tokens) {
+            *value = Value::String(REDACTED.to_owned());
+            redacted.push(loc.clone());
+        }
+    }
+    redacted.sort();
+    redacted
+}
+
+fn pointer_mut<'v>(mut v: &'v mut Value, tokens: &[InstanceToken]) -> Option<&'v mut Value> {
+    for tok in tokens {
+        v = match (v, tok) {
+            (Value::Object(obj), InstanceToken::Prop(p)) => obj.get_mut(p.as_ref())?,
+            (Value::Array(arr), InstanceToken::Item(i)) => arr.get_mut(*i)?,
+            _ => return None,
+        };
+    }
+    Some(v)
+}
@@ -0,0 +1,202 @@
+use std::{collections::HashMap, sync::Arc};
+
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use serde_json::Value;
+
+use crate::{CompileError, Compiler, SchemaIndex, Schemas, ValidationError};
+
+// location of schemas inserted as values
+const BASE_URL: &str = "registry:///";
+
+// chars percent-encoded in name, so that it is single path segment
+const NAME: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+enum Entry {
+    Value(Value),
+    Url(String),
+}
+
+/**
+Named schemas, compiled together into [`CompiledRegistry`].
+
+Schemas inserted as values are located at `registry:///<name>`, so that
+they can refer each other by name, for example `{"$ref": "address"}`,
+unless they have `$id`. The name is percent-encoded as single path
+segment, so schema named `a/b` is referred as `a%2Fb`. Names `.` and `..`
+cannot be used for such schemas.
+
+Each [`SchemaRegistry::build`] compiles with a fresh [`Compiler`] and
+returns a new snapshot. This is useful for reloading schemas at runtime:
+swap the `Arc` once build is done, while in-flight validations finish
+with the old snapshot.
+
+```rust
+# use boon::*;
+# use serde_json::json;
+let mut registry = SchemaRegistry::new();
+registry.insert("address", json!({"required": ["city"]}));
+registry.insert("person", json!({"properties": {"home": {"$ref": "address"}}}));
+let (compiled, errors) = registry.build();
+assert!(errors.is_empty());
+
+let instance = json!({"home": {"street": "main"}});
+assert!(compiled.validate("person", &instance).unwrap().is_err());
+```
+*/
+#[derive(Default)]
+pub struct SchemaRegistry {
+    entries: Vec<(String, Entry)>,
+    compiler: Option<Box<dyn Fn() -> Compiler + Send + Sync>>,
+}
+
+impl SchemaRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+    Uses `f` to create the [`Compiler`] for each build, so that it can
+    be configured with formats, loaders, default draft etc.
+
+    By default [`Compiler::new`] is used.
+    */
+    pub fn use_compiler(&mut self, f: impl Fn() -> Compiler + Send + Sync + 'static) {
+        self.compiler = Some(Box::new(f));
+    }
+
+    /// Adds `schema` with given `name`, replacing any existing schema with that name.
+    pub fn insert(&mut self, name: impl Into<String>, schema: Value) {
+        self.put(name.into(), Entry::Value(schema));
+    }
+
+    /**
+    Adds schema at `url` with given `name`, replacing any existing schema
+    with that name.
+
+    The `url` can be file path or url with optional fragment, as in
+    [`Compiler::compile`]. It is loaded only on build.
+    */
+    pub fn insert_url(&mut self, name: impl Into<String>, url: impl Into<String>) {
+        self.put(name.into(), Entry::Url(url.into()));
+    }
+
+    /// Removes schema with given `name`. Returns true if it was present.
+    pub fn remove(&mut self, name: &str) -> bool {
+        let len = self.entries.len();
+        self.entries.retain(|(n, _)| n != name);
+        self.entries.len() != len
+    }
+
+    fn put(&mut self, name: String, entry: Entry) {
+        match self.entries.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, e)) => *e = entry,
+            None => self.entries.push((name, entry)),
+        }
+    }
+
+    /**
+    Compiles all schemas, in the order they were inserted.
+
+    A schema that fails to compile does not fail the others; it is left
+    out of the snapshot and its error is returned along with its name.
+    */
+    pub fn build(&self) -> (Arc<CompiledRegistry>, Vec<(String, CompileError)>) {
+        let mut compiler = match &self.compiler {
+            Some(f) => f(),
+            None => Compiler::new(),
+        };
+        let mut errors = vec![];
+        let mut locs = Vec::with_capacity(self.entries.len());
+        for (name, entry) in &self.entries {
+            let loc = match entry {
+                Entry::Value(schema) => {
+                    let loc = format!("{BASE_URL}{}", utf8_percent_encode(name, NAME));
+                    if name == "." || name == ".." {
+                        errors.push((
+                            name.clone(),
+                            CompileError::ParseUrlError {
+                                url: loc,
+                                src: "schema name must not be dot segment".into(),
+                            },
+                        ));
+                        continue;
+                    }
+                    if let Err(e) = compiler.add_resource(&loc, schema.clone()) {
+                        errors.push((name.clone(), e));
+                        continue;
+                    }
+                    loc
+                }
+                Entry::Url(url) => url.clone(),
+            };
+            locs.push((name, loc));
+        }
+
+        let mut schemas = Schemas::new();
+        let mut names = HashMap::with_capacity(locs.len());
+        let results = compiler.compile_all(
+            &locs.iter().map(|(_, loc)| loc).collect::<Vec<_>>(),
+            &mut schemas,
+        );
+        for ((name, _), result) in locs.into_iter().zip(results) {
+            match result {
+                Ok(sch_index) => _ = names.insert(name.clone(), sch_index),
+                Err(e) => errors.push((name.clone(), e)),
+            }
+        }
+        (Arc::new(CompiledRegistry { schemas, names }), errors)
+    }
+
+    /**
+    Same as [`SchemaRegistry::build`], but fails if any schema fails to
+    compile.
+
+    # Errors
+
+    returns [`CompileError`] of the first schema that failed to compile.
+    */
+    pub fn build_strict(&self) -> Result<Arc<CompiledRegistry>, CompileError> {
+        let (compiled, errors) = self.build();
+        match errors.into_iter().next() {
+            Some((_, e)) => Err(e),
+            None => Ok(compiled),
+        }
+    }
+}
+
+/// Snapshot of schemas compiled by [`SchemaRegistry::build`].
+pub struct CompiledRegistry {
+    schemas: Schemas,
+    names: HashMap<String, SchemaIndex>,
+}
+
+impl CompiledRegistry {
+    /// Returns index of schema with given `name`, if it compiled successfully.
+    pub fn index_of(&self, name: &str) -> Option<SchemaIndex> {
+        self.names.get(name).copied()
+    }
+
+    /// Returns the compiled schemas, for validating with [`CompiledRegistry::index_of`].
+    pub fn schemas(&self) -> &Schemas {
+        &self.schemas
+    }
+
+    /**
+    Validates `v` with schema of given `name`.
+
+    Returns `None` if there is no schema with given `name`, or it failed
+    to compile.
+    */
+    pub fn validate<'s, 'v>(
+        &'s self,
+        name: &str,
+        v: &'v Value,
+    ) -> Option<Result<(), ValidationError<'s, 'v>>> {
+        let sch_index = self.index_of(name)?;
+        Some(self.schemas.validate(v, sch_index))
+    }
+}
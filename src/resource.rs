@@ -0,0 +1,91 @@
+use std::{error::Error, io::Read};
+
+use serde_json::Value;
+
+use crate::loader::json_from_bytes;
+
+/// Resource loaded by [`UrlLoader::load_resource`](crate::UrlLoader::load_resource).
+pub enum Resource {
+    /// Already parsed json.
+    Json(Value),
+    /// Raw bytes, as received from the source.
+    Bytes {
+        bytes: Vec<u8>,
+        /// ex: `gzip`, `br`. Multiple encodings are separated by comma,
+        /// in the order they were applied.
+        content_encoding: Option<String>,
+        /// ex: `application/json`, `application/yaml`
+        content_type: Option<String>,
+    },
+}
+
+impl Resource {
+    /**
+    Decompresses and parses this resource into json.
+
+    Supported content encodings are `gzip`, `deflate`, `identity`
+    and `br` (with `brotli` feature).
+
+    Content types ending with `yaml` are parsed as yaml (with `yaml`
+    feature), `application/json5` is parsed as json5 (with `json5` feature),
+    and anything else is parsed as json.
+    */
+    pub fn into_json(self) -> Result<Value, Box<dyn Error>> {
+        let (mut bytes, content_encoding, content_type) = match self {
+            Resource::Json(v) => return Ok(v),
+            Resource::Bytes {
+                bytes,
+                content_encoding,
+                content_type,
+            } => (bytes, content_encoding, content_type),
+        };
+        if let Some(encodings) = &content_encoding {
+            for encoding in encodings.rsplit(',') {
+                bytes = decompress(encoding.trim(), bytes)?;
+            }
+        }
+        let media_type = content_type
+            .as_deref()
+            .and_then(|ct| ct.split(';').next())
+            .map(|mt| mt.trim().to_ascii_lowercase());
+        match media_type.as_deref() {
+            Some(mt) if mt.ends_with("yaml") => parse_yaml(&bytes),
+            #[cfg(feature = "json5")]
+            Some("application/json5") => {
+                let text = crate::encoding::decode(&bytes)?;
+                Ok(crate::loader::parse_json5(&text)?)
+            }
+            _ => json_from_bytes(&bytes),
+        }
+    }
+}
+
+fn decompress(encoding: &str, bytes: Vec<u8>) -> Result<Vec<u8>, Box<dyn Error>> {
+    fn read_all(mut rdr: impl Read, encoding: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+        let mut buf = Vec::new();
+        rdr.read_to_end(&mut buf)
+            .map_err(|e| format!("{encoding} decompression failed: {e}"))?;
+        Ok(buf)
+    }
+    match encoding.to_ascii_lowercase().as_str() {
+        "" | "identity" => Ok(bytes),
+        "gzip" | "x-gzip" => read_all(flate2::read::GzDecoder::new(bytes.as_slice()), encoding),
+        "deflate" => read_all(flate2::read::ZlibDecoder::new(bytes.as_slice()), encoding),
+        #[cfg(feature = "brotli")]
+        "br" => read_all(
+            brotli_decompressor::Decompressor::new(bytes.as_slice(), 4096),
+            encoding,
+        ),
+        _ => Err(format!("unsupported content encoding {encoding:?}").into()),
+    }
+}
+
+#[cfg(feature = "yaml")]
+fn parse_yaml(bytes: &[u8]) -> Result<Value, Box<dyn Error>> {
+    Ok(serde_yaml::from_slice(bytes)?)
+}
+
+#[cfg(not(feature = "yaml"))]
+fn parse_yaml(_bytes: &[u8]) -> Result<Value, Box<dyn Error>> {
+    Err("yaml content requires yaml feature".into())
+}
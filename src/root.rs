@@ -1,9 +1,8 @@
 use std::collections::{HashMap, HashSet};
 
-use crate::{compiler::CompileError, draft::*, util::*};
-
 use serde_json::Value;
-use url::Url;
+
+use crate::{compiler::CompileError, draft::*, url::Url, util::*};
 
 pub(crate) struct Root {
     pub(crate) draft: &'static Draft,
@@ -23,6 +22,10 @@ impl Root {
         self.draft.default_vocabs.contains(&name)
     }
 
+    pub(crate) fn has_custom_vocab(&self, uri: &str) -> bool {
+        matches!(&self.meta_vocabs, Some(vocabs) if vocabs.iter().any(|s| s == uri))
+    }
+
     fn resolve_fragment_in(&self, frag: &Fragment, res: &Resource) -> Result<UrlPtr, CompileError> {
         let ptr = match frag {
             Fragment::Anchor(anchor) => {
@@ -30,6 +33,7 @@ impl Root {
                     return Err(CompileError::AnchorNotFound {
                         url: self.url.to_string(),
                         reference: UrlFrag::format(&res.id, frag.as_str()),
+                        referenced_from: vec![],
                     });
                 };
                 ptr.clone()
@@ -1,24 +1,29 @@
 use std::collections::{HashMap, HashSet};
 
-use crate::{compiler::CompileError, draft::*, loader::DefaultUrlLoader, root::Root, util::*};
-
 use serde_json::Value;
-use url::Url;
+
+use crate::{
+    compiler::CompileError, draft::*, loader::DefaultUrlLoader, root::Root, url::Url, util::*, Type,
+};
 
 // --
 
 pub(crate) struct Roots {
     pub(crate) default_draft: &'static Draft,
+    pub(crate) allow_non_schemas: bool,
     map: HashMap<Url, Root>,
     pub(crate) loader: DefaultUrlLoader,
+    pub(crate) vocabularies: HashSet<String>, // registered custom vocabularies
 }
 
 impl Roots {
     fn new() -> Self {
         Self {
             default_draft: latest(),
+            allow_non_schemas: false,
             map: Default::default(),
             loader: DefaultUrlLoader::new(),
+            vocabularies: HashSet::new(),
         }
     }
 }
@@ -50,7 +55,22 @@ impl Roots {
         if !root.draft.is_subschema(up.ptr.as_str()) {
             let doc = self.loader.load(&root.url)?;
             let v = up.ptr.lookup(doc, &up.url)?;
-            root.draft.validate(up, v)?;
+            if !self.allow_non_schemas {
+                if let Some(kw) = root.draft.non_schema_keyword(up.ptr.as_str()) {
+                    return Err(CompileError::NotASchemaLocation {
+                        loc: up.to_string(),
+                        parent_keyword: kw.to_owned(),
+                    });
+                }
+            }
+            if matches!(v, Value::Object(_) | Value::Bool(_)) {
+                root.draft.validate(up, v)?;
+            } else if !self.allow_non_schemas {
+                return Err(CompileError::NotASchema {
+                    loc: up.to_string(),
+                    found_type: Type::of(v),
+                });
+            }
             root.add_subschema(doc, &up.ptr)?;
         }
         Ok(())
@@ -73,10 +93,11 @@ impl Roots {
                 url: url.clone(),
                 ptr: "".into(),
             };
-            self.loader
-                .get_draft(&up, doc, self.default_draft, HashSet::new())?
+            self.loader.get_draft(&up, doc, self.default_draft)?
         };
-        let vocabs = self.loader.get_meta_vocabs(doc, draft)?;
+        let vocabs = self
+            .loader
+            .get_meta_vocabs(doc, draft, &self.vocabularies)?;
         let resources = {
             let mut m = HashMap::default();
             draft.collect_resources(doc, &url, "".into(), &url, &mut m)?;
@@ -0,0 +1,148 @@
+use std::{
+    error::Error,
+    fmt::{Debug, Display},
+    ops::Deref,
+    sync::Arc,
+};
+
+use serde_json::Value;
+
+use crate::*;
+
+/**
+[`Schemas`] shared through [`Arc`], for schemas that live long, like in
+a `static`.
+
+Its validation errors own their data, like [`ValidationError::into_owned`],
+and hold the [`Arc`]. So they can be stored for long, and schemas they
+refer to can still be looked up through [`OwnedValidationError::schemas`].
+
+```rust
+# use boon::*;
+# use serde_json::json;
+let mut schemas = Schemas::new();
+let mut compiler = Compiler::new();
+compiler.add_resource("http://a.com/schema.json", json!({"type": "string"}))?;
+let sch_index = compiler.compile("http://a.com/schema.json", &mut schemas)?;
+let shared = SharedSchemas::new(schemas);
+
+let errors: Vec<OwnedValidationError> = [json!(1), json!(true)]
+    .iter()
+    .filter_map(|v| shared.validate(v, sch_index).err())
+    .collect();
+drop(shared); // errors keep schemas alive
+assert_eq!(errors[0].error().schema_url, "http://a.com/schema.json#");
+assert!(errors[1].to_string().contains("want string, but got boolean"));
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+*/
+#[derive(Clone)]
+pub struct SharedSchemas(Arc<Schemas>);
+
+impl SharedSchemas {
+    /// Shares `schemas` through a new [`Arc`].
+    pub fn new(schemas: Schemas) -> Self {
+        Self(Arc::new(schemas))
+    }
+
+    /// Returns the shared schemas.
+    pub fn schemas(&self) -> &Arc<Schemas> {
+        &self.0
+    }
+
+    /**
+    Validates `v` with schema identified by `sch_index`.
+
+    # Panics
+
+    Panics if `sch_index` is not generated for this instance.
+    [`Schemas::contains`] can be used too ensure that it does not panic.
+    */
+    pub fn validate(&self, v: &Value, sch_index: SchemaIndex) -> Result<(), OwnedValidationError> {
+        self.validate_with(v, sch_index, &ValidationOptions::default())?;
+        Ok(())
+    }
+
+    /**
+    Validates `v` with schema identified by `sch_index`, using given `opts`.
+
+    Returns [`ValidationOutput`] as [`Schemas::validate_with`] does, which
+    borrows from the schemas.
+
+    # Panics
+
+    Panics if `sch_index` is not generated for this instance.
+    [`Schemas::contains`] can be used too ensure that it does not panic.
+    */
+    pub fn validate_with(
+        &self,
+        v: &Value,
+        sch_index: SchemaIndex,
+        opts: &ValidationOptions,
+    ) -> Result<ValidationOutput<'_>, OwnedValidationError> {
+        self.0
+            .validate_with(v, sch_index, opts)
+            .map_err(|e| OwnedValidationError {
+                error: e.into_owned(),
+                schemas: Arc::clone(&self.0),
+            })
+    }
+}
+
+impl Deref for SharedSchemas {
+    type Target = Schemas;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<Schemas> for SharedSchemas {
+    fn from(schemas: Schemas) -> Self {
+        Self::new(schemas)
+    }
+}
+
+impl From<Arc<Schemas>> for SharedSchemas {
+    fn from(schemas: Arc<Schemas>) -> Self {
+        Self(schemas)
+    }
+}
+
+/// Error returned by [`SharedSchemas::validate`], which keeps the
+/// schemas alive.
+pub struct OwnedValidationError {
+    error: ValidationError<'static, 'static>,
+    schemas: Arc<Schemas>,
+}
+
+impl OwnedValidationError {
+    /// Returns the validation error.
+    pub fn error(&self) -> &ValidationError<'static, 'static> {
+        &self.error
+    }
+
+    /// Returns the schemas, this error is reported from.
+    pub fn schemas(&self) -> &Arc<Schemas> {
+        &self.schemas
+    }
+
+    /// Converts into [`ValidationError`], releasing the schemas.
+    pub fn into_owned(self) -> ValidationError<'static, 'static> {
+        self.error
+    }
+}
+
+impl Error for OwnedValidationError {}
+
+impl Display for OwnedValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self.error(), f)
+    }
+}
+
+impl Debug for OwnedValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(self.error(), f)
+    }
+}
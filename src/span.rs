@@ -0,0 +1,165 @@
+use std::ops::Range;
+
+use crate::{InstanceLocation, InstanceToken};
+
+/**
+Returns byte range of value at `loc` within json `text`.
+
+Returns `None` if `text` has no value at `loc`, or if it is not valid
+json along the way. Like `serde_json`, the last of duplicate keys wins.
+
+```rust
+# use boon::*;
+let text = r#"{"a": [1, {"b": true}]}"#;
+let loc = InstanceLocation {
+    tokens: vec!["a".into(), 1.into(), "b".into()],
+};
+assert_eq!(span_of(text, &loc).map(|r| &text[r]), Some("true"));
+```
+*/
+pub fn span_of(text: &str, loc: &InstanceLocation) -> Option<Range<usize>> {
+    let b = text.as_bytes();
+    let mut pos = skip_ws(b, 0);
+    for tok in &loc.tokens {
+        pos = match tok {
+            InstanceToken::Prop(prop) => find_member(text, pos, prop)?,
+            InstanceToken::Item(index) => find_item(b, pos, *index)?,
+        };
+    }
+    Some(pos..value_end(b, pos)?)
+}
+
+/// like [`span_of`], but with unescaped json-pointer `tokens`, which
+/// address array items or object members as per the value along the way.
+pub(crate) fn span_of_tokens<'a>(
+    text: &str,
+    tokens: impl IntoIterator<Item = &'a str>,
+) -> Option<Range<usize>> {
+    let b = text.as_bytes();
+    let mut pos = skip_ws(b, 0);
+    for tok in tokens {
+        pos = match b.get(pos)? {
+            b'[' => {
+                // json-pointer index: no sign or leading zeros
+                if tok.starts_with('+') || (tok.len() > 1 && tok.starts_with('0')) {
+                    return None;
+                }
+                find_item(b, pos, tok.parse().ok()?)?
+            }
+            _ => find_member(text, pos, tok)?,
+        };
+    }
+    Some(pos..value_end(b, pos)?)
+}
+
+// returns position of value of member `prop`, in object at `pos`
+fn find_member(text: &str, pos: usize, prop: &str) -> Option<usize> {
+    let b = text.as_bytes();
+    if b.get(pos) != Some(&b'{') {
+        return None;
+    }
+    let mut found = None;
+    let mut pos = skip_ws(b, pos + 1);
+    if b.get(pos) == Some(&b'}') {
+        return None;
+    }
+    loop {
+        let key_end = string_end(b, pos)?;
+        let key = &text[pos..key_end];
+        let matched = if key.contains('\\') {
+            serde_json::from_str::<String>(key).ok()? == prop
+        } else {
+            &key[1..key.len() - 1] == prop
+        };
+        pos = skip_ws(b, key_end);
+        if b.get(pos) != Some(&b':') {
+            return None;
+        }
+        pos = skip_ws(b, pos + 1);
+        if matched {
+            found = Some(pos);
+        }
+        pos = skip_ws(b, value_end(b, pos)?);
+        match b.get(pos)? {
+            b',' => pos = skip_ws(b, pos + 1),
+            b'}' => return found,
+            _ => return None,
+        }
+    }
+}
+
+// returns position of item `index`, in array at `pos`
+fn find_item(b: &[u8], pos: usize, index: usize) -> Option<usize> {
+    if b.get(pos) != Some(&b'[') {
+        return None;
+    }
+    let mut pos = skip_ws(b, pos + 1);
+    if b.get(pos) == Some(&b']') {
+        return None;
+    }
+    for _ in 0..index {
+        pos = skip_ws(b, value_end(b, pos)?);
+        if b.get(pos) != Some(&b',') {
+            return None;
+        }
+        pos = skip_ws(b, pos + 1);
+    }
+    Some(pos)
+}
+
+fn skip_ws(b: &[u8], mut pos: usize) -> usize {
+    while matches!(b.get(pos), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+        pos += 1;
+    }
+    pos
+}
+
+// returns position after the string starting at `pos`
+fn string_end(b: &[u8], pos: usize) -> Option<usize> {
+    if b.get(pos) != Some(&b'"') {
+        return None;
+    }
+    let mut pos = pos + 1;
+    loop {
+        match b.get(pos)? {
+            b'\\' => pos += 2,
+            b'"' => return Some(pos + 1),
+            _ => pos += 1,
+        }
+    }
+}
+
+// returns position after the value starting at `pos`
+fn value_end(b: &[u8], pos: usize) -> Option<usize> {
+    match b.get(pos)? {
+        b'"' => string_end(b, pos),
+        b'{' | b'[' => {
+            let mut depth = 0;
+            let mut pos = pos;
+            loop {
+                match b.get(pos)? {
+                    b'"' => {
+                        pos = string_end(b, pos)?;
+                        continue;
+                    }
+                    b'{' | b'[' => depth += 1,
+                    b'}' | b']' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return Some(pos + 1);
+                        }
+                    }
+                    _ => {}
+                }
+                pos += 1;
+            }
+        }
+        _ => {
+            let len = b[pos..]
+                .iter()
+                .position(|c| matches!(c, b',' | b'}' | b']' | b' ' | b'\t' | b'\n' | b'\r'))
+                .unwrap_or(b.len() - pos);
+            (len > 0).then_some(pos + len)
+        }
+    }
+}
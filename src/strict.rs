@@ -0,0 +1,182 @@
+/*!
+Strict parsing of json instances, rejecting duplicate object keys.
+
+serde_json keeps the last of duplicate keys, so the validated value may
+differ from what other parsers see. Here the text is scanned once before
+parsing, tracking keys of each object along with its location.
+
+The same scan locates `\u` escapes of lone surrogates, which serde_json
+rejects with only line and column.
+*/
+
+use std::{collections::HashSet, fmt};
+
+use serde::de::{DeserializeSeed, Deserializer, Error, MapAccess, SeqAccess, Visitor};
+
+use crate::{InstanceError, InstanceLocation, InstanceToken};
+
+pub(crate) fn check_duplicate_keys(text: &str) -> Result<(), InstanceError<'static>> {
+    let mut state = State {
+        check_duplicates: true,
+        ..State::default()
+    };
+    let mut de = serde_json::Deserializer::from_str(text);
+    let result = Seed(&mut state).deserialize(&mut de).and_then(|_| de.end());
+    match result {
+        Ok(_) => Ok(()),
+        Err(e) => Err(match state.duplicate.take() {
+            Some(dup) => dup,
+            None => lone_surrogate(text, &e, &state.tokens).unwrap_or_else(|| e.into()),
+        }),
+    }
+}
+
+/// converts error of parsing `text`, reporting location of lone surrogate if any.
+pub(crate) fn parse_error(text: &str, e: serde_json::Error) -> InstanceError<'static> {
+    if find_lone_surrogate(text).is_some() {
+        let mut state = State::default();
+        let mut de = serde_json::Deserializer::from_str(text);
+        if Seed(&mut state).deserialize(&mut de).is_err() {
+            if let Some(err) = lone_surrogate(text, &e, &state.tokens) {
+                return err;
+            }
+        }
+    }
+    e.into()
+}
+
+// returns error, if parsing failed due to lone surrogate escape.
+// `tokens` is location of value being parsed, when it failed.
+fn lone_surrogate(
+    text: &str,
+    e: &serde_json::Error,
+    tokens: &[InstanceToken<'static>],
+) -> Option<InstanceError<'static>> {
+    let offset = find_lone_surrogate(text)?;
+    // serde_json fails at first error, so any syntax error before
+    // the escape, would have failed earlier.
+    let line_start: usize = text
+        .split_inclusive('\n')
+        .take(e.line().saturating_sub(1))
+        .map(str::len)
+        .sum();
+    if offset > line_start + e.column() {
+        return None;
+    }
+    Some(InstanceError::LoneSurrogate {
+        instance_location: InstanceLocation {
+            tokens: tokens.to_vec(),
+        },
+        offset,
+    })
+}
+
+// returns byte offset of first `\u` escape of surrogate, not forming a valid pair.
+fn find_lone_surrogate(text: &str) -> Option<usize> {
+    let bytes = text.as_bytes();
+    let escape = |i: usize| -> Option<u16> {
+        let hex = bytes.get(i..i + 6)?.strip_prefix(b"\\u")?;
+        u16::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()
+    };
+    let (mut i, mut in_str) = (0, false);
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' => in_str = !in_str,
+            b'\\' if in_str => match escape(i) {
+                Some(0xD800..=0xDBFF) => match escape(i + 6) {
+                    Some(0xDC00..=0xDFFF) => i += 6,
+                    _ => return Some(i),
+                },
+                Some(0xDC00..=0xDFFF) => return Some(i),
+                _ => i += 1,
+            },
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+#[derive(Default)]
+struct State {
+    check_duplicates: bool,
+    tokens: Vec<InstanceToken<'static>>,
+    duplicate: Option<InstanceError<'static>>,
+}
+
+struct Seed<'a>(&'a mut State);
+
+impl<'de> DeserializeSeed<'de> for Seed<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Seed<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("any json value")
+    }
+
+    fn visit_bool<E>(self, _v: bool) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_i64<E>(self, _v: i64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_u64<E>(self, _v: u64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_f64<E>(self, _v: f64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_str<E>(self, _v: &str) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_unit<E>(self) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        let state = self.0;
+        let mut i = 0;
+        loop {
+            state.tokens.push(i.into());
+            let item = seq.next_element_seed(Seed(state))?;
+            state.tokens.pop();
+            if item.is_none() {
+                return Ok(());
+            }
+            i += 1;
+        }
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        let state = self.0;
+        let mut keys = HashSet::new();
+        while let Some(key) = map.next_key::<String>()? {
+            if state.check_duplicates && keys.contains(&key) {
+                state.duplicate = Some(InstanceError::DuplicateKey {
+                    instance_location: InstanceLocation {
+                        tokens: state.tokens.clone(),
+                    },
+                    key,
+                });
+                return Err(A::Error::custom("duplicate key"));
+            }
+            state.tokens.push(key.clone().into());
+            map.next_value_seed(Seed(state))?;
+            state.tokens.pop();
+            keys.insert(key);
+        }
+        Ok(())
+    }
+}
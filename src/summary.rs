@@ -0,0 +1,125 @@
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fmt::{Debug, Display},
+};
+
+use crate::{util::HMap, ValidationError};
+
+/**
+Error returned by [`Schemas::validate_summary`](crate::Schemas::validate_summary).
+
+Counts leaf errors of the error tree [`Schemas::validate`](crate::Schemas::validate)
+would return, without building the tree. Only the first few leaf errors
+are built.
+*/
+#[derive(Debug)]
+pub struct ErrorSummary<'s> {
+    /// number of leaf errors.
+    pub total: usize,
+    /// number of leaf errors by their code, as in
+    /// [`LeafErrorRef::code`](crate::LeafErrorRef::code).
+    pub counts: BTreeMap<String, usize>,
+    /// first leaf errors, in the order they appear in error tree.
+    pub errors: Vec<ValidationError<'s, 'static>>,
+}
+
+impl Error for ErrorSummary<'_> {}
+
+impl Display for ErrorSummary<'_> {
+    /// Lists leaf errors retained. Use `#` to show their schema location.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "jsonschema validation failed with {} errors", self.total)?;
+        for e in &self.errors {
+            write!(f, "\n  ")?;
+            Display::fmt(e, f)?;
+        }
+        if self.total > self.errors.len() {
+            write!(f, "\n  ... {} more", self.total - self.errors.len())?;
+        }
+        Ok(())
+    }
+}
+
+// leaf errors found by validation, in summary mode.
+pub(crate) struct Summary<'s> {
+    limit: usize,
+    codes: Vec<String>,
+    code_index: HMap<String, usize>,
+    leaves: Vec<usize>, // index of code, of each leaf
+    errors: Vec<ValidationError<'s, 'static>>,
+}
+
+impl<'s> Summary<'s> {
+    pub(crate) fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            codes: vec![],
+            code_index: HMap::default(),
+            leaves: vec![],
+            errors: vec![],
+        }
+    }
+
+    /// whether next leaf error is retained.
+    pub(crate) fn retains_next(&self) -> bool {
+        self.leaves.len() < self.limit
+    }
+
+    pub(crate) fn add(&mut self, code: &str, error: Option<ValidationError<'s, 'static>>) {
+        let index = match self.code_index.get(code) {
+            Some(&index) => index,
+            None => {
+                self.codes.push(code.to_owned());
+                self.code_index
+                    .insert(code.to_owned(), self.codes.len() - 1);
+                self.codes.len() - 1
+            }
+        };
+        if let Some(error) = error {
+            debug_assert_eq!(self.errors.len(), self.leaves.len());
+            self.errors.push(error);
+        }
+        self.leaves.push(index);
+    }
+
+    /// adds leaf errors in the tree of `e`.
+    pub(crate) fn add_leaves(&mut self, e: ValidationError<'s, 'static>) {
+        if e.causes.is_empty() {
+            let code = e.kind.code().to_owned();
+            let error = self.retains_next().then_some(e);
+            self.add(&code, error);
+        } else {
+            for cause in e.causes {
+                self.add_leaves(cause);
+            }
+        }
+    }
+
+    pub(crate) fn mark(&self) -> usize {
+        self.leaves.len()
+    }
+
+    /// discards leaf errors found after `mark`.
+    pub(crate) fn truncate(&mut self, mark: usize) {
+        self.leaves.truncate(mark);
+        self.errors.truncate(mark);
+    }
+
+    pub(crate) fn finish(self) -> ErrorSummary<'s> {
+        let mut counts = vec![0; self.codes.len()];
+        for &index in &self.leaves {
+            counts[index] += 1;
+        }
+        ErrorSummary {
+            total: self.leaves.len(),
+            counts: self
+                .codes
+                .into_iter()
+                .zip(counts)
+                .filter(|(_, n)| *n > 0)
+                .collect(),
+            errors: self.errors,
+        }
+    }
+}
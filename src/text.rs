@@ -0,0 +1,143 @@
+/*!
+Maps instance and schema locations to positions in json source text,
+for editors to point at the offending value.
+
+```rust
+# use boon::*;
+let text = "{\n  \"a\": [1, \"x\"]\n}";
+let loc = InstanceLocation {
+    tokens: vec!["a".into(), 1.into()],
+};
+let span = text::locate(text, &loc).unwrap();
+assert_eq!(&text[span.start.offset..span.end.offset], "\"x\"");
+assert_eq!((span.start.line, span.start.column), (2, 12));
+```
+*/
+
+use std::fmt::Display;
+
+use crate::{
+    span::{span_of, span_of_tokens},
+    util::{split, Fragment, JsonPointer},
+    AbsoluteKeywordLocation, InstanceLocation, SchemaToken,
+};
+
+/// Position in source text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TextPos {
+    /// byte offset from start of text.
+    pub offset: usize,
+    /// line number, starting from 1.
+    pub line: usize,
+    /// column within line in chars, starting from 1.
+    pub column: usize,
+}
+
+impl Default for TextPos {
+    fn default() -> Self {
+        Self {
+            offset: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+}
+
+impl Display for TextPos {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// Span of a json value in source text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TextSpan {
+    /// position of first char of the value.
+    pub start: TextPos,
+    /// position just after the value.
+    pub end: TextPos,
+}
+
+impl Display for TextSpan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}", self.start, self.end)
+    }
+}
+
+/**
+Returns span of value at `loc` within json `text`.
+
+Returns `None` if `text` has no value at `loc`, or if it is not valid
+json along the way. see [`span_of`] for just byte offsets.
+*/
+pub fn locate(text: &str, loc: &InstanceLocation) -> Option<TextSpan> {
+    let range = span_of(text, loc)?;
+    Some(text_span(text, range.start, range.end))
+}
+
+/**
+Returns span of the keyword value at `loc` within json `text` of the
+schema document.
+
+`text` must be the document at `loc.schema_url`, without fragment.
+Returns `None` if fragment of `loc.schema_url` is an anchor rather than
+json-pointer, or if `text` has no value there.
+
+```rust
+# use boon::*;
+let text = r#"{"properties": {"a/b": {"minimum": 5}}}"#;
+let loc = AbsoluteKeywordLocation {
+    schema_url: "http://a.com/schema.json#/properties/a~1b",
+    keyword_path: Some(KeywordPath {
+        keyword: "minimum",
+        token: None,
+    }),
+};
+let span = text::locate_keyword(text, &loc).unwrap();
+assert_eq!(&text[span.start.offset..span.end.offset], "5");
+```
+*/
+pub fn locate_keyword(text: &str, loc: &AbsoluteKeywordLocation) -> Option<TextSpan> {
+    let (_, frag) = split(loc.schema_url);
+    let frag = Fragment::decode(frag);
+    if !frag.is_empty() && !frag.starts_with('/') {
+        return None; // anchor
+    }
+    let mut tokens = vec![];
+    for tok in frag.split('/').skip(1) {
+        tokens.push(JsonPointer::unescape(tok).ok()?.into_owned());
+    }
+    if let Some(path) = &loc.keyword_path {
+        tokens.push(path.keyword.to_owned());
+        match &path.token {
+            Some(SchemaToken::Prop(p)) => tokens.push(p.to_string()),
+            Some(SchemaToken::Item(i)) => tokens.push(i.to_string()),
+            None => {}
+        }
+    }
+    let range = span_of_tokens(text, tokens.iter().map(String::as_str))?;
+    Some(text_span(text, range.start, range.end))
+}
+
+fn text_span(text: &str, start: usize, end: usize) -> TextSpan {
+    let start = text_pos(text, TextPos::default(), start);
+    TextSpan {
+        start,
+        end: text_pos(text, start, end),
+    }
+}
+
+// advances `from` to byte `offset`
+fn text_pos(text: &str, from: TextPos, offset: usize) -> TextPos {
+    let mut pos = from;
+    for c in text[from.offset..offset].chars() {
+        if c == '\n' {
+            pos.line += 1;
+            pos.column = 1;
+        } else {
+            pos.column += 1;
+        }
+    }
+    pos.offset = offset;
+    pos
+}
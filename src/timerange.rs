@@ -0,0 +1,299 @@
+use std::{
+    error::Error,
+    fmt::Display,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde_json::Value;
+
+use crate::formats::{check_date, check_date_time};
+
+// nanoseconds since unix epoch, in UTC. leap seconds are not counted.
+type Instant = i128;
+
+const NANOS_PER_SEC: i128 = 1_000_000_000;
+const NANOS_PER_DAY: i128 = 86_400 * NANOS_PER_SEC;
+
+/**
+Error of extension keyword `x-notBefore` or `x-notAfter`, when string
+is out of range. It is reported as [`ErrorKind::Keyword`](crate::ErrorKind::Keyword).
+
+see [`Compiler::enable_extension`](crate::Compiler::enable_extension).
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeRangeError {
+    /// string validated.
+    pub got: String,
+    /// bound computed for the keyword, as date-time in UTC.
+    pub bound: String,
+    /// whether `got` is before `bound`, rather than after.
+    pub before: bool,
+}
+
+impl Error for TimeRangeError {}
+
+impl Display for TimeRangeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let dir = if self.before { "before" } else { "after" };
+        write!(f, "'{}' is {dir} {}", self.got, self.bound)
+    }
+}
+
+/// compiled `x-notBefore` and `x-notAfter` of extension `x-time-range`.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TimeRange {
+    pub(crate) not_before: Option<Bound>,
+    pub(crate) not_after: Option<Bound>,
+}
+
+impl TimeRange {
+    /// returns failed keyword along with its error, if `s` is out of range.
+    /// strings other than date-time and date are not checked; `format`
+    /// reports them.
+    pub(crate) fn check(
+        &self,
+        s: &str,
+        clock: Option<&dyn Fn() -> SystemTime>,
+    ) -> Result<(), (&'static str, Box<dyn Error>)> {
+        let Some(t) = parse_instant(s) else {
+            return Ok(());
+        };
+        let mut now = None;
+        let bounds = [
+            ("x-notBefore", &self.not_before, true),
+            ("x-notAfter", &self.not_after, false),
+        ];
+        for (kw, bound, before) in bounds {
+            let bound = match bound {
+                None => continue,
+                Some(Bound::At(at)) => *at,
+                Some(Bound::FromNow { months, nanos }) => {
+                    let now = match now {
+                        Some(now) => now,
+                        None => *now.insert(current_time(clock).map_err(|e| (kw, e))?),
+                    };
+                    add_months(now, *months) + nanos
+                }
+            };
+            if (before && t < bound) || (!before && t > bound) {
+                let err = TimeRangeError {
+                    got: s.to_owned(),
+                    bound: format_instant(bound),
+                    before,
+                };
+                return Err((kw, err.into()));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Bound {
+    At(Instant),
+    // months are added first, as their length varies
+    FromNow { months: i64, nanos: i128 },
+}
+
+impl Bound {
+    pub(crate) fn compile(v: &Value) -> Result<Self, Box<dyn Error>> {
+        let Value::String(s) = v else {
+            Err("must be string")?
+        };
+        if s == "now" {
+            return Ok(Self::FromNow {
+                months: 0,
+                nanos: 0,
+            });
+        }
+        if s.starts_with(['P', '-', '+']) {
+            return parse_duration(s).map_err(|e| format!("invalid duration: {e}").into());
+        }
+        match parse_instant(s) {
+            Some(at) => Ok(Self::At(at)),
+            None => Err("must be now, duration or date-time")?,
+        }
+    }
+}
+
+fn current_time(clock: Option<&dyn Fn() -> SystemTime>) -> Result<Instant, Box<dyn Error>> {
+    let now = match clock {
+        Some(clock) => clock(),
+        // SystemTime::now panics there
+        #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+        None => Err("no clock given in ValidationOptions, and no system time on wasm32")?,
+        #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+        None => SystemTime::now(),
+    };
+    Ok(match now.duration_since(UNIX_EPOCH) {
+        Ok(d) => d.as_nanos() as i128,
+        Err(e) => -(e.duration().as_nanos() as i128),
+    })
+}
+
+// parses duration like `P30D`, relative to now. Sign may prefix the
+// duration like `-P30D`, or its components like `P-1Y-6M`.
+fn parse_duration(s: &str) -> Result<Bound, Box<dyn Error>> {
+    let (neg, s) = match s.strip_prefix('-') {
+        Some(s) => (true, s),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let Some(mut s) = s.strip_prefix('P') else {
+        Err("must start with P")?
+    };
+    if s.is_empty() {
+        Err("nothing after P")?
+    }
+    let (mut months, mut nanos) = (0i64, 0i128);
+    let (mut units, mut time) = ("YMWD", false);
+    while !s.is_empty() {
+        if let Some(rest) = s.strip_prefix('T') {
+            if time {
+                Err("more than one T")?
+            }
+            if rest.is_empty() {
+                Err("no time elements")?
+            }
+            (s, units, time) = (rest, "HMS", true);
+            continue;
+        }
+        let sign = match s.strip_prefix('-') {
+            Some(rest) => {
+                s = rest;
+                -1
+            }
+            None => 1,
+        };
+        let len = s
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(s.len());
+        let (num, rest) = s.split_at(len);
+        let Some(unit) = rest.chars().next() else {
+            Err("missing unit")?
+        };
+        s = &rest[unit.len_utf8()..];
+        let Some(j) = units.find(unit) else {
+            Err(format!("invalid unit {unit}, or out of order"))?
+        };
+        units = &units[j + 1..];
+        if num.is_empty() {
+            Err("missing number")?
+        }
+        if time && unit == 'S' {
+            let (secs, frac) = num.split_once('.').unwrap_or((num, ""));
+            if frac.len() > 9 || !frac.bytes().all(|b| b.is_ascii_digit()) {
+                Err("invalid second fraction")?
+            }
+            let frac = format!("{frac:0<9}").parse::<i128>()?;
+            nanos += sign as i128 * (secs.parse::<u32>()? as i128 * NANOS_PER_SEC + frac);
+            continue;
+        }
+        let n = num
+            .parse::<u32>()
+            .map_err(|_| format!("invalid number {num}, fraction is allowed only in seconds"))?;
+        let n = sign * n as i64;
+        match (time, unit) {
+            (false, 'Y') => months += n * 12,
+            (false, 'M') => months += n,
+            (false, 'W') => nanos += n as i128 * 7 * NANOS_PER_DAY,
+            (false, _) => nanos += n as i128 * NANOS_PER_DAY,
+            (true, 'H') => nanos += n as i128 * 3600 * NANOS_PER_SEC,
+            (true, 'M') => nanos += n as i128 * 60 * NANOS_PER_SEC,
+            _ => unreachable!("units are validated"),
+        }
+    }
+    if neg {
+        (months, nanos) = (-months, -nanos);
+    }
+    Ok(Bound::FromNow { months, nanos })
+}
+
+// parses date-time, or date as start of day in UTC.
+fn parse_instant(s: &str) -> Option<Instant> {
+    let num = |r: std::ops::Range<usize>| s[r].parse::<i64>().ok();
+    if s.len() == 10 {
+        check_date(s).ok()?;
+        let days = days_from_civil(num(0..4)?, num(5..7)?, num(8..10)?);
+        return Some(days as i128 * NANOS_PER_DAY);
+    }
+    check_date_time(s).ok()?;
+    let days = days_from_civil(num(0..4)?, num(5..7)?, num(8..10)?);
+    let secs = num(11..13)? * 3600 + num(14..16)? * 60 + num(17..19)?;
+    let mut t = days as i128 * NANOS_PER_DAY + secs as i128 * NANOS_PER_SEC;
+    let mut rest = &s[19..];
+    if let Some(frac) = rest.strip_prefix('.') {
+        let len = frac.bytes().take_while(u8::is_ascii_digit).count();
+        let digits = &frac[..len.min(9)];
+        t += format!("{digits:0<9}").parse::<i128>().ok()?;
+        rest = &frac[len..];
+    }
+    if rest.len() == 6 {
+        let offset = num(s.len() - 5..s.len() - 3)? * 3600 + num(s.len() - 2..s.len())? * 60;
+        let offset = offset as i128 * NANOS_PER_SEC;
+        t += if rest.starts_with('+') {
+            -offset
+        } else {
+            offset
+        };
+    }
+    Some(t)
+}
+
+fn format_instant(t: Instant) -> String {
+    let (days, nanos) = (t.div_euclid(NANOS_PER_DAY), t.rem_euclid(NANOS_PER_DAY));
+    let (y, m, d) = civil_from_days(days as i64);
+    let (secs, frac) = (nanos / NANOS_PER_SEC, nanos % NANOS_PER_SEC);
+    let (h, mi, sec) = (secs / 3600, secs / 60 % 60, secs % 60);
+    let mut s = format!("{y:04}-{m:02}-{d:02}T{h:02}:{mi:02}:{sec:02}");
+    if frac != 0 {
+        let frac = format!("{frac:09}");
+        s.push('.');
+        s.push_str(frac.trim_end_matches('0'));
+    }
+    s.push('Z');
+    s
+}
+
+fn add_months(t: Instant, months: i64) -> Instant {
+    if months == 0 {
+        return t;
+    }
+    let (days, nanos) = (t.div_euclid(NANOS_PER_DAY), t.rem_euclid(NANOS_PER_DAY));
+    let (y, m, d) = civil_from_days(days as i64);
+    let total = y * 12 + (m - 1) + months;
+    let (y, m) = (total.div_euclid(12), total.rem_euclid(12) + 1);
+    let d = d.min(days_in_month(y, m));
+    days_from_civil(y, m, d) as i128 * NANOS_PER_DAY + nanos
+}
+
+fn days_in_month(y: i64, m: i64) -> i64 {
+    match m {
+        2 if y % 4 == 0 && (y % 100 != 0 || y % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// days since unix epoch, see http://howardhinnant.github.io/date_algorithms.html
+fn days_from_civil(y: i64, m: i64, d: i64) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * ((m + 9) % 12) + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400;
+    (if m <= 2 { y + 1 } else { y }, m, d)
+}
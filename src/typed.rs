@@ -0,0 +1,82 @@
+use std::{error::Error, fmt::Display};
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use serde_path_to_error::Segment;
+
+use crate::{util::quote, InstanceLocation, InstanceToken, ValidationError};
+
+/// Error returned by [`Schemas::validate_into`](crate::Schemas::validate_into).
+#[derive(Debug)]
+pub enum CombinedError<'s, 'v> {
+    /// instance is not valid against schema.
+    Validation(ValidationError<'s, 'v>),
+    /// instance is valid, but cannot be deserialized into the type.
+    /// This happens, when schema and type have drifted apart.
+    Deserialization {
+        /// location of the value which failed to deserialize.
+        instance_location: InstanceLocation<'static>,
+        src: serde_json::Error,
+    },
+}
+
+impl Error for CombinedError<'_, '_> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Validation(_) => None,
+            Self::Deserialization { src, .. } => Some(src),
+        }
+    }
+}
+
+impl Display for CombinedError<'_, '_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Validation(e) => e.fmt(f),
+            Self::Deserialization {
+                instance_location,
+                src,
+            } => write!(
+                f,
+                "deserialization failed at {}: {src}",
+                quote(&instance_location.to_string())
+            ),
+        }
+    }
+}
+
+pub(crate) fn deserialize<'s, 'v, T: DeserializeOwned>(
+    v: &'v Value,
+) -> Result<T, CombinedError<'s, 'v>> {
+    serde_path_to_error::deserialize(v).map_err(|e| {
+        let instance_location = instance_location(v, e.path());
+        CombinedError::Deserialization {
+            instance_location,
+            src: e.into_inner(),
+        }
+    })
+}
+
+// maps serde path into instance location, walking `v` along it.
+fn instance_location(mut v: &Value, path: &serde_path_to_error::Path) -> InstanceLocation<'static> {
+    let mut tokens = vec![];
+    for seg in path.iter() {
+        let (tok, next) = match (seg, v) {
+            (Segment::Seq { index }, Value::Array(arr)) => {
+                (InstanceToken::Item(*index), arr.get(*index))
+            }
+            (Segment::Map { key } | Segment::Enum { variant: key }, Value::Object(obj)) => {
+                (InstanceToken::Prop(key.clone().into()), obj.get(key))
+            }
+            // unit variant, given as string
+            (Segment::Enum { .. }, _) => continue,
+            _ => break,
+        };
+        tokens.push(tok);
+        match next {
+            Some(next) => v = next,
+            None => break,
+        }
+    }
+    InstanceLocation { tokens }
+}
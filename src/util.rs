@@ -1,16 +1,45 @@
 use std::{
     borrow::{Borrow, Cow},
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
     fmt::Display,
-    hash::{Hash, Hasher},
+    hash::{BuildHasher, Hash, Hasher},
     str::FromStr,
+    sync::Arc,
 };
 
-use ahash::{AHashMap, AHasher};
+use once_cell::sync::Lazy;
 use percent_encoding::{percent_decode_str, AsciiSet, CONTROLS};
-use serde_json::Value;
-use url::Url;
+use serde_json::{Number, Value};
 
-use crate::CompileError;
+use crate::{url::Url, CompileError};
+
+// hashing --
+
+/*
+Hasher of maps used during validation, and of values hashed for
+`uniqueItems` and for sharing `enum`/`const` values.
+
+By default it is SipHash with random keys, which resists HashDoS from
+untrusted instances. With `ahash` feature it is ahash, which is faster.
+Either way, validation output does not depend on it: maps are used only
+for lookup, or iterated in sorted order.
+*/
+#[cfg(feature = "ahash")]
+pub(crate) type HashState = ahash::RandomState;
+#[cfg(not(feature = "ahash"))]
+pub(crate) type HashState = std::collections::hash_map::RandomState;
+
+pub(crate) type HMap<K, V> = HashMap<K, V, HashState>;
+pub(crate) type HSet<K> = HashSet<K, HashState>;
+
+// keys are chosen once, so that hashes of values can be compared
+// across schemas.
+static VALUE_HASH: Lazy<HashState> = Lazy::new(HashState::default);
+
+pub(crate) fn hash_value(v: &Value) -> u64 {
+    VALUE_HASH.hash_one(HashedValue(v))
+}
 
 // --
 
@@ -18,7 +47,7 @@ use crate::CompileError;
 pub(crate) struct JsonPointer(pub(crate) String);
 
 impl JsonPointer {
-    pub(crate) fn escape(token: &str) -> Cow<str> {
+    pub(crate) fn escape(token: &str) -> Cow<'_, str> {
         const SPECIAL: [char; 2] = ['~', '/'];
         if token.contains(SPECIAL) {
             token.replace('~', "~0").replace('/', "~1").into()
@@ -27,39 +56,98 @@ impl JsonPointer {
         }
     }
 
-    pub(crate) fn unescape(mut tok: &str) -> Result<Cow<str>, ()> {
+    /// on error, returns byte offset of the invalid `~` in `tok`.
+    pub(crate) fn unescape(tok: &str) -> Result<Cow<'_, str>, usize> {
         let Some(mut tilde) = tok.find('~') else {
             return Ok(Cow::Borrowed(tok));
         };
         let mut s = String::with_capacity(tok.len());
+        let mut start = 0;
         loop {
-            s.push_str(&tok[..tilde]);
-            tok = &tok[tilde + 1..];
-            match tok.chars().next() {
+            s.push_str(&tok[start..tilde]);
+            match tok[tilde + 1..].chars().next() {
                 Some('1') => s.push('/'),
                 Some('0') => s.push('~'),
-                _ => return Err(()),
+                _ => return Err(tilde),
             }
-            tok = &tok[1..];
-            let Some(i) = tok.find('~') else {
-                s.push_str(tok);
+            start = tilde + 2;
+            let Some(i) = tok[start..].find('~') else {
+                s.push_str(&tok[start..]);
                 break;
             };
-            tilde = i;
+            tilde = start + i;
         }
         Ok(Cow::Owned(s))
     }
 
+    /**
+    Checks that percent-encoded url fragment `frag` is a valid json-pointer,
+    if it looks like one.
+
+    On error, returns zero-based character position in `frag` and reason.
+    */
+    pub(crate) fn check_fragment(frag: &str) -> Result<(), (usize, &'static str)> {
+        // decoded bytes, along with char position in frag they came from
+        let mut bytes = Vec::with_capacity(frag.len());
+        let mut positions = Vec::with_capacity(frag.len());
+        let mut chars = frag.char_indices().enumerate();
+        while let Some((pos, (i, ch))) = chars.next() {
+            if ch == '%' {
+                let hex = frag
+                    .get(i + 1..i + 3)
+                    .filter(|hex| hex.bytes().all(|b| b.is_ascii_hexdigit()));
+                let Some(hex) = hex else {
+                    return Err((pos, "'%' must be followed by two hex digits"));
+                };
+                let b =
+                    u8::from_str_radix(hex, 16).map_err(|_| (pos, "invalid percent-encoding"))?;
+                bytes.push(b);
+                positions.push(pos);
+                chars.nth(1);
+            } else if ch.is_control() {
+                return Err((pos, "control character must be percent-encoded"));
+            } else {
+                let mut buf = [0; 4];
+                bytes.extend_from_slice(ch.encode_utf8(&mut buf).as_bytes());
+                positions.resize(bytes.len(), pos);
+            }
+        }
+        let decoded = String::from_utf8(bytes).map_err(|e| {
+            let pos = positions[e.utf8_error().valid_up_to()];
+            (pos, "percent-encoding is not valid utf-8")
+        })?;
+        if !decoded.starts_with('/') {
+            return Ok(()); // empty or anchor
+        }
+        let mut offset = 0;
+        for tok in decoded.split('/') {
+            if let Err(tilde) = Self::unescape(tok) {
+                let pos = positions[offset + tilde];
+                return Err((pos, "'~' must be followed by '0' or '1'"));
+            }
+            offset += tok.len() + 1;
+        }
+        Ok(())
+    }
+
     pub(crate) fn lookup<'a>(
         &self,
         mut v: &'a Value,
         v_url: &Url,
     ) -> Result<&'a Value, CompileError> {
+        let mut offset = 0;
         for tok in self.0.split('/').skip(1) {
-            let Ok(tok) = Self::unescape(tok) else {
-                let loc = UrlFrag::format(v_url, self.as_str());
-                return Err(CompileError::InvalidJsonPointer(loc));
-            };
+            offset += 1;
+            let tok_len = tok.len();
+            let tok = Self::unescape(tok).map_err(|tilde| {
+                let position = Fragment::encode(&self.0[..offset + tilde]).chars().count();
+                CompileError::InvalidJsonPointer {
+                    loc: UrlFrag::format(v_url, self.as_str()),
+                    position,
+                    reason: "'~' must be followed by '0' or '1'",
+                }
+            })?;
+            offset += tok_len;
             match v {
                 Value::Object(obj) => {
                     if let Some(pvalue) = obj.get(tok.as_ref()) {
@@ -78,7 +166,10 @@ impl JsonPointer {
                 _ => {}
             }
             let loc = UrlFrag::format(v_url, self.as_str());
-            return Err(CompileError::JsonPointerNotFound(loc));
+            return Err(CompileError::JsonPointerNotFound {
+                loc,
+                referenced_from: vec![],
+            });
         }
         Ok(v)
     }
@@ -187,6 +278,11 @@ impl Fragment {
         percent_encoding::utf8_percent_encode(frag, FRAGMENT).to_string()
     }
 
+    /// reverses [`Fragment::encode`].
+    pub(crate) fn decode(frag: &str) -> Cow<'_, str> {
+        percent_decode_str(frag).decode_utf8_lossy()
+    }
+
     pub(crate) fn as_str(&self) -> &str {
         match self {
             Fragment::Anchor(s) => &s.0,
@@ -218,7 +314,7 @@ impl UrlFrag {
         match Url::parse(u) {
             Ok(url) => Ok(UrlFrag { url, frag }),
             #[cfg(not(target_arch = "wasm32"))]
-            Err(url::ParseError::RelativeUrlWithoutBase) => {
+            Err(crate::url::ParseError::RelativeUrlWithoutBase) => {
                 let p = std::path::absolute(u).map_err(|e| CompileError::ParseUrlError {
                     url: u.to_owned(),
                     src: e.into(),
@@ -297,15 +393,191 @@ impl Display for UrlPtr {
 
 // --
 
-pub(crate) fn is_integer(v: &Value) -> bool {
+/// length of `v` serialized as compact json, without allocating it.
+pub(crate) fn json_size(v: &Value) -> usize {
+    struct Counter(usize);
+    impl std::io::Write for Counter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0 += buf.len();
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+    let mut counter = Counter(0);
+    _ = serde_json::to_writer(&mut counter, v);
+    counter.0
+}
+
+/// if `float_integers` is true, floats with zero fractional part are also integers.
+pub(crate) fn is_integer(v: &Value, float_integers: bool) -> bool {
     match v {
         Value::Number(n) => {
-            n.is_i64() || n.is_u64() || n.as_f64().filter(|n| n.fract() == 0.0).is_some()
+            n.is_i64()
+                || n.is_u64()
+                || (float_integers && n.as_f64().filter(|n| n.fract() == 0.0).is_some())
         }
         _ => false,
     }
 }
 
+/**
+Compares numbers as `f64`. If either of them is not representable as
+finite `f64`, which is possible with `serde_json/arbitrary_precision`,
+they are compared exactly as decimals.
+
+Returns `None` if numbers cannot be compared.
+*/
+pub(crate) fn num_cmp(a: &Number, b: &Number) -> Option<Ordering> {
+    match (a.as_f64(), b.as_f64()) {
+        (Some(a), Some(b)) if a.is_finite() && b.is_finite() => a.partial_cmp(&b),
+        _ => {
+            let (a, b) = (a.to_string(), b.to_string());
+            Some(Decimal::parse(&a)?.cmp(&Decimal::parse(&b)?))
+        }
+    }
+}
+
+/**
+Checks whether `num` is integer multiple of `mul`.
+
+Integers are checked using integer arithmetic, and others exactly
+as decimals, so that `0.0075` is multiple of `0.0001` though their
+`f64` quotient is not integer. Falls back to `f64` division, if `mul`
+has more than 18 significant digits.
+*/
+pub(crate) fn num_multiple_of(num: &Number, mul: &Number) -> bool {
+    let int = |n: &Number| n.as_i64().map(i128::from).or(n.as_u64().map(i128::from));
+    if let (Some(n), Some(m)) = (int(num), int(mul)) {
+        if m != 0 {
+            return n % m == 0;
+        }
+    }
+    let (n, m) = (num.to_string(), mul.to_string());
+    if let (Some(n), Some(m)) = (Decimal::parse(&n), Decimal::parse(&m)) {
+        if let Some(b) = n.is_multiple_of(&m) {
+            return b;
+        }
+    }
+    match (num.as_f64(), mul.as_f64()) {
+        (Some(n), Some(m)) => (n / m).fract() == 0.0,
+        _ => true,
+    }
+}
+
+// decimal number `0.digits * 10^exp`
+struct Decimal<'a> {
+    neg: bool,
+    // without leading and trailing zeros. empty for zero.
+    digits: Cow<'a, str>,
+    exp: i128,
+}
+
+impl<'a> Decimal<'a> {
+    fn parse(s: &'a str) -> Option<Self> {
+        let (neg, s) = match s.strip_prefix('-') {
+            Some(s) => (true, s),
+            None => (false, s),
+        };
+        let (mantissa, exp) = match s.find(['e', 'E']) {
+            Some(i) => (&s[..i], s[i + 1..].parse::<i128>().ok()?),
+            None => (s, 0),
+        };
+        let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+        if int.is_empty() || !is_digits(int) || !is_digits(frac) {
+            return None;
+        }
+        let digits = if frac.is_empty() {
+            Cow::Borrowed(int)
+        } else {
+            Cow::Owned(format!("{int}{frac}"))
+        };
+        let leading = digits.bytes().take_while(|b| *b == b'0').count();
+        let exp = exp + int.len() as i128 - leading as i128;
+        let digits = match digits {
+            Cow::Borrowed(d) => Cow::Borrowed(d[leading..].trim_end_matches('0')),
+            Cow::Owned(d) => Cow::Owned(d[leading..].trim_end_matches('0').to_owned()),
+        };
+        Some(Self { neg, digits, exp })
+    }
+
+    // whether self is integer multiple of `d`. `None` if `d` is zero,
+    // or has more than 18 digits.
+    fn is_multiple_of(&self, d: &Decimal) -> Option<bool> {
+        if d.digits.is_empty() || d.digits.len() > 18 {
+            return None;
+        }
+        if self.digits.is_empty() {
+            return Some(true);
+        }
+        // self is `digits * 10^scale`
+        let scale = |d: &Decimal| d.exp - d.digits.len() as i128;
+        let Ok(k) = u128::try_from(scale(self) - scale(d)) else {
+            // digits of self has no trailing zeros, to divide by 10
+            return Some(false);
+        };
+        // checks `self.digits * 10^k` mod `d.digits`
+        let m = u128::from(d.digits.parse::<u64>().ok()?);
+        let mut r = 0;
+        for b in self.digits.bytes() {
+            r = (r * 10 + u128::from(b - b'0')) % m;
+        }
+        let (mut pow, mut k) = (10 % m, k);
+        while k > 0 {
+            if k & 1 == 1 {
+                r = r * pow % m;
+            }
+            pow = pow * pow % m;
+            k >>= 1;
+        }
+        Some(r == 0)
+    }
+
+    fn sign(&self) -> i8 {
+        match (self.digits.is_empty(), self.neg) {
+            (true, _) => 0,
+            (false, true) => -1,
+            (false, false) => 1,
+        }
+    }
+}
+
+impl Ord for Decimal<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let sign = self.sign();
+        match sign.cmp(&other.sign()) {
+            Ordering::Equal if sign != 0 => {}
+            ord => return ord,
+        }
+        // digits are compared lexicographically, as their exponents are same
+        let ord = self
+            .exp
+            .cmp(&other.exp)
+            .then_with(|| self.digits.cmp(&other.digits));
+        if self.neg {
+            ord.reverse()
+        } else {
+            ord
+        }
+    }
+}
+
+impl PartialOrd for Decimal<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Decimal<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal<'_> {}
+
 #[cfg(not(target_arch = "wasm32"))]
 fn starts_with_windows_drive(p: &str) -> bool {
     p.chars().next().filter(char::is_ascii_uppercase).is_some() && p[1..].starts_with(":\\")
@@ -332,10 +604,37 @@ where
         .join(sep)
 }
 
-pub(crate) fn escape(token: &str) -> Cow<str> {
+pub(crate) fn escape(token: &str) -> Cow<'_, str> {
     JsonPointer::escape(token)
 }
 
+/// property names longer than this many bytes are elided, when rendered
+/// in instance location.
+pub(crate) const MAX_RENDERED_TOKEN: usize = 256;
+
+/// elides middle of `token` longer than [`MAX_RENDERED_TOKEN`],
+/// noting its actual length.
+pub(crate) fn elide(token: &str) -> Cow<'_, str> {
+    if token.len() <= MAX_RENDERED_TOKEN {
+        return token.into();
+    }
+    let mut head = MAX_RENDERED_TOKEN / 2;
+    while !token.is_char_boundary(head) {
+        head -= 1;
+    }
+    let mut tail = token.len() - MAX_RENDERED_TOKEN / 4;
+    while !token.is_char_boundary(tail) {
+        tail += 1;
+    }
+    format!(
+        "{}…({} bytes)…{}",
+        &token[..head],
+        token.len(),
+        &token[tail..]
+    )
+    .into()
+}
+
 pub(crate) fn split(url: &str) -> (&str, &str) {
     if let Some(i) = url.find('#') {
         (&url[..i], &url[i + 1..])
@@ -345,50 +644,94 @@ pub(crate) fn split(url: &str) -> (&str, &str) {
 }
 
 /// serde_json treats 0 and 0.0 not equal. so we cannot simply use v1==v2
+fn num_equals(n1: &Number, n2: &Number) -> bool {
+    if let (Some(n1), Some(n2)) = (n1.as_u64(), n2.as_u64()) {
+        return n1 == n2;
+    }
+    if let (Some(n1), Some(n2)) = (n1.as_i64(), n2.as_i64()) {
+        return n1 == n2;
+    }
+    if let (Some(n1), Some(n2)) = (n1.as_f64(), n2.as_f64()) {
+        return n1 == n2;
+    }
+    false
+}
+
+/// number that `v` is read as, when matched loosely against `enum`
+/// and `const`: number itself, or string holding json number.
+/// see `ValidationOptions::loose_enum_scalars`
+pub(crate) fn loose_number(v: &Value) -> Option<Number> {
+    match v {
+        Value::Number(n) => Some(n.clone()),
+        Value::String(s) => {
+            // parser allows surrounding whitespace, json number does not
+            let trimmed = s.starts_with(|c: char| c == '-' || c.is_ascii_digit())
+                && s.ends_with(|c: char| c.is_ascii_digit());
+            trimmed.then(|| serde_json::from_str::<Number>(s).ok())?
+        }
+        _ => None,
+    }
+}
+
+/// whether `v`, read as number `n`, equals `e` of the other type among
+/// string and number. values of same type are compared by `equals`.
+pub(crate) fn loose_equals(v: &Value, n: &Number, e: &Value) -> bool {
+    match (v, e) {
+        (Value::String(_), Value::Number(e)) => num_equals(n, e),
+        (Value::Number(_), Value::String(_)) => loose_number(e).is_some_and(|e| num_equals(n, &e)),
+        _ => false,
+    }
+}
+
 pub(crate) fn equals(v1: &Value, v2: &Value) -> bool {
     match (v1, v2) {
         (Value::Null, Value::Null) => true,
         (Value::Bool(b1), Value::Bool(b2)) => b1 == b2,
-        (Value::Number(n1), Value::Number(n2)) => {
-            if let (Some(n1), Some(n2)) = (n1.as_u64(), n2.as_u64()) {
-                return n1 == n2;
-            }
-            if let (Some(n1), Some(n2)) = (n1.as_i64(), n2.as_i64()) {
-                return n1 == n2;
-            }
-            if let (Some(n1), Some(n2)) = (n1.as_f64(), n2.as_f64()) {
-                return n1 == n2;
-            }
-            false
-        }
+        (Value::Number(n1), Value::Number(n2)) => num_equals(n1, n2),
         (Value::String(s1), Value::String(s2)) => s1 == s2,
+        // scalars are compared before recursing into containers, so that
+        // distinct values are told apart without deep comparison.
         (Value::Array(arr1), Value::Array(arr2)) => {
             if arr1.len() != arr2.len() {
                 return false;
             }
-            arr1.iter().zip(arr2).all(|(e1, e2)| equals(e1, e2))
+            let pairs = || arr1.iter().zip(arr2);
+            pairs()
+                .filter(|(e1, _)| !is_container(e1))
+                .all(|(e1, e2)| equals(e1, e2))
+                && pairs()
+                    .filter(|(e1, _)| is_container(e1))
+                    .all(|(e1, e2)| equals(e1, e2))
         }
         (Value::Object(obj1), Value::Object(obj2)) => {
             if obj1.len() != obj2.len() {
                 return false;
             }
-            for (k1, v1) in obj1 {
-                if let Some(v2) = obj2.get(k1) {
-                    if !equals(v1, v2) {
-                        return false;
-                    }
-                } else {
-                    return false;
-                }
-            }
-            true
+            let entries = |containers| {
+                obj1.iter()
+                    .filter(move |(_, v1)| is_container(v1) == containers)
+            };
+            entries(false)
+                .chain(entries(true))
+                .all(|(k1, v1)| obj2.get(k1).is_some_and(|v2| equals(v1, v2)))
         }
         _ => false,
     }
 }
 
-pub(crate) fn duplicates(arr: &Vec<Value>) -> Option<(usize, usize)> {
-    match arr.as_slice() {
+fn is_container(v: &Value) -> bool {
+    matches!(v, Value::Array(_) | Value::Object(_))
+}
+
+/// returns indexes of first duplicate pair in `arr`, if any.
+///
+/// with `hashed`, items are compared only if their hashes match, even
+/// for small arrays. Larger arrays are always compared that way.
+pub(crate) fn duplicates(arr: &[Value], hashed: bool) -> Option<(usize, usize)> {
+    if hashed && arr.len() > 2 {
+        return hashed_duplicates(arr);
+    }
+    match arr {
         [e0, e1] => {
             if equals(e0, e1) {
                 return Some((0, 1));
@@ -414,18 +757,32 @@ pub(crate) fn duplicates(arr: &Vec<Value>) -> Option<(usize, usize)> {
                     }
                 }
             } else {
-                let mut seen = AHashMap::with_capacity(len);
-                for (i, item) in arr.iter().enumerate() {
-                    if let Some(j) = seen.insert(HashedValue(item), i) {
-                        return Some((j, i));
-                    }
-                }
+                return hashed_duplicates(arr);
             }
         }
     }
     None
 }
 
+// hash of each item is computed once, and items are compared only if
+// their full hashes match, which is rare for distinct items.
+fn hashed_duplicates(arr: &[Value]) -> Option<(usize, usize)> {
+    let hashes = arr.iter().map(hash_value).collect::<Vec<_>>();
+    // first index with each hash
+    let mut seen = HMap::with_capacity_and_hasher(arr.len(), HashState::default());
+    for (i, &hash) in hashes.iter().enumerate() {
+        let first = *seen.entry(hash).or_insert(i);
+        if first == i {
+            continue;
+        }
+        let dup = (first..i).find(|&j| hashes[j] == hash && equals(&arr[j], &arr[i]));
+        if let Some(j) = dup {
+            return Some((j, i));
+        }
+    }
+    None
+}
+
 // HashedValue --
 
 // Based on implementation proposed by Sven Marnach:
@@ -447,6 +804,8 @@ impl Hash for HashedValue<'_> {
             Value::Bool(ref b) => b.hash(state),
             Value::Number(ref num) => {
                 if let Some(num) = num.as_f64() {
+                    // -0.0 equals 0.0
+                    let num = if num == 0.0 { 0.0 } else { num };
                     num.to_bits().hash(state);
                 } else if let Some(num) = num.as_u64() {
                     num.hash(state);
@@ -464,11 +823,8 @@ impl Hash for HashedValue<'_> {
                 let mut hash = 0;
                 for (pname, pvalue) in obj {
                     // We have no way of building a new hasher of type `H`, so we
-                    // hardcode using the default hasher of a hash map.
-                    let mut hasher = AHasher::default();
-                    pname.hash(&mut hasher);
-                    HashedValue(pvalue).hash(&mut hasher);
-                    hash ^= hasher.finish();
+                    // hardcode using the hasher of values.
+                    hash ^= VALUE_HASH.hash_one((pname, HashedValue(pvalue)));
                 }
                 state.write_u64(hash);
             }
@@ -476,10 +832,45 @@ impl Hash for HashedValue<'_> {
     }
 }
 
+// ValueArena --
+
+/// shares identical `enum` and `const` values across schemas, so that
+/// repetitive schemas store them once. values are shared only if they
+/// are identical, not merely equal as per json-schema, so that errors
+/// still show values as written.
+#[derive(Default)]
+pub(crate) struct ValueArena {
+    enums: HMap<u64, Vec<Arc<[Value]>>>,
+    consts: HMap<u64, Vec<Arc<Value>>>,
+}
+
+impl ValueArena {
+    pub(crate) fn enum_values(&mut self, values: &Arc<[Value]>) -> Arc<[Value]> {
+        let mut hasher = VALUE_HASH.build_hasher();
+        for v in values.iter() {
+            HashedValue(v).hash(&mut hasher);
+        }
+        let list = self.enums.entry(hasher.finish()).or_default();
+        if let Some(shared) = list.iter().find(|shared| shared == &values) {
+            return shared.clone();
+        }
+        list.push(values.clone());
+        values.clone()
+    }
+
+    pub(crate) fn constant(&mut self, value: &Arc<Value>) -> Arc<Value> {
+        let list = self.consts.entry(hash_value(value)).or_default();
+        if let Some(shared) = list.iter().find(|shared| shared == &value) {
+            return shared.clone();
+        }
+        list.push(value.clone());
+        value.clone()
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
-    use ahash::AHashMap;
     use serde_json::json;
 
     use super::*;
@@ -524,6 +915,58 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_decimal_cmp() {
+        let tests = [
+            ("0", "-0.000", Ordering::Equal),
+            ("1e309", "1E+309", Ordering::Equal),
+            ("10e308", "1e309", Ordering::Equal),
+            ("0.001e3", "1", Ordering::Equal),
+            ("1e309", "1e308", Ordering::Greater),
+            ("-1e309", "-1e308", Ordering::Less),
+            ("-1e309", "0", Ordering::Less),
+            ("12", "123e-1", Ordering::Less),
+            ("2", "123e-2", Ordering::Greater),
+            (
+                "123456789012345678901234567890",
+                "123456789012345678901234567891",
+                Ordering::Less,
+            ),
+        ];
+        for (a, b, want) in tests {
+            let got = Decimal::parse(a).unwrap().cmp(&Decimal::parse(b).unwrap());
+            assert_eq!(got, want, "{a} cmp {b}");
+        }
+        assert!(Decimal::parse("1e").is_none());
+        assert!(Decimal::parse(".5").is_none());
+    }
+
+    #[test]
+    fn test_decimal_multiple_of() {
+        let tests = [
+            ("0.0075", "0.0001", true),
+            ("0.00751", "0.0001", false),
+            ("0.3", "0.1", true),
+            ("19.99", "0.01", true),
+            ("1e308", "0.5", true),
+            ("1e308", "0.123456789", false),
+            ("-4.5", "1.5", true),
+            ("0", "0.7", true),
+            ("7", "7e-300", true),
+            ("1", "3", false),
+            ("123456789012345678901234567890", "10", true),
+            ("123456789012345678901234567891", "3", false),
+        ];
+        for (a, b, want) in tests {
+            let got = Decimal::parse(a)
+                .unwrap()
+                .is_multiple_of(&Decimal::parse(b).unwrap());
+            assert_eq!(got, Some(want), "{a} multipleOf {b}");
+        }
+        let big = Decimal::parse("1234567890123456789").unwrap();
+        assert_eq!(Decimal::parse("1").unwrap().is_multiple_of(&big), None);
+    }
+
     #[test]
     fn test_equals() {
         let tests = [["1.0", "1"], ["-1.0", "-1"]];
@@ -536,10 +979,46 @@ mod tests {
 
     #[test]
     fn test_hashed_value() {
-        let mut seen = AHashMap::with_capacity(10);
+        let mut seen = HMap::default();
         let (v1, v2) = (json!(2), json!(2.0));
         assert!(equals(&v1, &v2));
         assert!(seen.insert(HashedValue(&v1), 1).is_none());
         assert!(seen.insert(HashedValue(&v2), 1).is_some());
     }
+
+    #[test]
+    fn test_duplicates() {
+        let large = |i: usize| {
+            let mut obj = json!({"nested": {"deep": [1, {"x": "y"}]}, "id": i});
+            for k in 0..50 {
+                obj[format!("k{k}")] = json!(k);
+            }
+            obj
+        };
+        let tests = [
+            (json!([1, 2, 3]), None),
+            (json!([1, 2, 1.0]), Some((0, 2))),
+            (json!([0, 1, -0.0]), Some((0, 2))),
+            (
+                json!([{"a": [1, 2]}, {"a": [2, 1]}, {"a": [1.0, 2]}]),
+                Some((0, 2)),
+            ),
+            (
+                json!([{"a": 1, "b": {}}, {"b": {}, "a": 2}, {"b": {}}]),
+                None,
+            ),
+            (json!([[1, [2]], [[2], 1], [1, [2.0]]]), Some((0, 2))),
+            (Value::from((0..10).map(large).collect::<Vec<_>>()), None),
+            (Value::from((0..30).map(large).collect::<Vec<_>>()), None),
+            (
+                Value::from((0..30).chain([7]).map(large).collect::<Vec<_>>()),
+                Some((7, 30)),
+            ),
+        ];
+        for (arr, want) in tests {
+            let arr = arr.as_array().unwrap();
+            assert_eq!(duplicates(arr, false), want, "{arr:?}");
+            assert_eq!(duplicates(arr, true), want, "hashed {arr:?}");
+        }
+    }
 }
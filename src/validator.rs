@@ -1,8 +1,13 @@
-use std::{borrow::Cow, cmp::min, collections::HashSet, fmt::Write};
+use std::{
+    borrow::Cow,
+    cmp::{min, Ordering},
+    fmt::Write,
+    rc::Rc,
+};
 
 use serde_json::{Map, Value};
 
-use crate::{util::*, *};
+use crate::{content::decoded_size, summary::Summary, util::*, *};
 
 macro_rules! prop {
     ($prop:expr) => {
@@ -20,6 +25,234 @@ pub(crate) fn validate<'s, 'v>(
     v: &'v Value,
     schema: &'s Schema,
     schemas: &'s Schemas,
+    opts: &ValidationOptions,
+) -> Result<ValidationOutput<'s>, ValidationError<'s, 'v>> {
+    let mut state = State::default();
+    let mut vloc = Vec::with_capacity(8);
+    let result = validate_in(v, schema, schemas, opts, &mut state, &mut vloc, false);
+    if let Some(aborted) = state.aborted {
+        return Err(ValidationError {
+            schema_url: Cow::Borrowed(&schema.loc),
+            instance_location: InstanceLocation::new(),
+            kind: ErrorKind::Schema {
+                url: Cow::Borrowed(&schema.loc),
+            },
+            example: None,
+            causes: vec![aborted],
+        });
+    }
+    result?;
+    Ok(ValidationOutput {
+        metrics: opts.collect_metrics.then_some(state.metrics),
+        warnings: state.warnings,
+        branches: state.branches,
+    })
+}
+
+/// counts leaf errors, without building error tree.
+pub(crate) fn summarize<'s>(
+    v: &Value,
+    schema: &'s Schema,
+    schemas: &'s Schemas,
+    opts: &ValidationOptions,
+    limit: usize,
+) -> Result<(), ErrorSummary<'s>> {
+    let mut state = State {
+        summary: Some(Summary::new(limit)),
+        ..Default::default()
+    };
+    let mut vloc = Vec::with_capacity(8);
+    let result = validate_in(v, schema, schemas, opts, &mut state, &mut vloc, false);
+    if let Some(aborted) = state.aborted {
+        // only error reported, see `validate`
+        let mut summary = Summary::new(limit);
+        let code = aborted.kind.code().to_owned();
+        summary.add(&code, summary.retains_next().then_some(aborted));
+        return Err(summary.finish());
+    }
+    match (result, state.summary) {
+        (Err(_), Some(summary)) => Err(summary.finish()),
+        _ => Ok(()),
+    }
+}
+
+pub(crate) fn is_valid(
+    v: &Value,
+    schema: &Schema,
+    schemas: &Schemas,
+    opts: &ValidationOptions,
+) -> bool {
+    let mut state = State::default();
+    let mut vloc = Vec::with_capacity(8);
+    let result = validate_in(v, schema, schemas, opts, &mut state, &mut vloc, true);
+    result.is_ok() && state.aborted.is_none()
+}
+
+/// locations of values, which subschemas with `flag` are evaluated against.
+/// if `v` is invalid, flags of failed subschemas are included too.
+pub(crate) fn annotated(
+    v: &Value,
+    schema: &Schema,
+    schemas: &Schemas,
+    flag: &str,
+) -> Vec<InstanceLocation<'static>> {
+    let mut opts = ValidationOptions {
+        annotation: Some(flag),
+        ..Default::default()
+    };
+    let mut state = State::default();
+    let result = validate_in(v, schema, schemas, &opts, &mut state, &mut vec![], false);
+    if result.is_ok() {
+        return state.annotated;
+    }
+    opts.keep_failed_annotations = true;
+    let mut state = State::default();
+    _ = validate_in(v, schema, schemas, &opts, &mut state, &mut vec![], false);
+    state.annotated
+}
+
+// shared by all validators of single validation
+#[derive(Default)]
+struct State<'s> {
+    metrics: Metrics,
+    // set once operations budget is exceeded
+    aborted: Option<ValidationError<'s, 'static>>,
+    // see ValidationOptions::warn_ignored
+    warnings: Vec<ValidationError<'s, 'static>>,
+    // see ValidationOptions::collect_branches
+    branches: Vec<MatchedBranches<'s>>,
+    // see ValidationOptions::annotation
+    annotated: Vec<InstanceLocation<'static>>,
+    content: ContentCache,
+    // set while validating values not part of instance, like property names
+    detached: bool,
+    // see ValidationOptions::max_instance_nodes. addresses of arrays and
+    // objects whose values are counted, so that each is counted once.
+    counted: HSet<usize>,
+    nodes: usize,
+    // set by `summarize`. errors returned are then placeholders, like
+    // with `bool_result`, and leaf errors are recorded here.
+    summary: Option<Summary<'s>>,
+}
+
+// content of strings decoded by contentEncoding and contentMediaType,
+// so that subschemas validating same string, like branches of anyOf,
+// decode it once.
+//
+// keyed by content of string along with functions used, rather than by
+// address: strings not part of instance, like property names, may reuse
+// address of another string during validation.
+#[derive(Default)]
+struct ContentCache {
+    // by decoder function
+    decoded: ContentMap<usize, Rc<Vec<u8>>>,
+    // by decoder and media type functions. None, if media type was
+    // checked without deserializing
+    deserialized: ContentMap<(Option<usize>, usize), Option<Rc<Value>>>,
+    entries: usize,
+    bytes: usize,
+}
+
+// values of string, by key
+type ContentMap<K, V> = HMap<Rc<str>, Vec<(K, V)>>;
+
+impl ContentCache {
+    const MAX_ENTRIES: usize = 64;
+    const MAX_BYTES: usize = 16 << 20;
+
+    fn has_room(&self, bytes: usize) -> bool {
+        self.entries < Self::MAX_ENTRIES && self.bytes + bytes <= Self::MAX_BYTES
+    }
+
+    // caches `value` of `bytes` for `str` and `key`, if there is room for it
+    fn insert<K: PartialEq, V>(
+        &mut self,
+        map: fn(&mut Self) -> &mut ContentMap<K, V>,
+        str: &str,
+        key: K,
+        value: V,
+        bytes: usize,
+    ) {
+        let str_bytes = if map(self).contains_key(str) {
+            0
+        } else {
+            str.len()
+        };
+        if !self.has_room(str_bytes + bytes) {
+            return;
+        }
+        self.bytes += str_bytes + bytes;
+        let Some(entries) = map(self).get_mut(str) else {
+            map(self).insert(Rc::from(str), vec![(key, value)]);
+            self.entries += 1;
+            return;
+        };
+        match entries.iter_mut().find(|(k, _)| *k == key) {
+            Some(entry) => entry.1 = value,
+            None => {
+                entries.push((key, value));
+                self.entries += 1;
+            }
+        }
+    }
+
+    fn decode(
+        &mut self,
+        str: &str,
+        decoder: &Decoder,
+    ) -> Result<Rc<Vec<u8>>, Box<dyn std::error::Error>> {
+        let key = decoder.func as usize;
+        let entries = self.decoded.get(str).map_or(&[][..], Vec::as_slice);
+        if let Some((_, bytes)) = entries.iter().find(|(k, _)| *k == key) {
+            return Ok(Rc::clone(bytes));
+        }
+        let bytes = Rc::new((decoder.func)(str)?);
+        let value = Rc::clone(&bytes);
+        self.insert(|c| &mut c.decoded, str, key, value, bytes.len());
+        Ok(bytes)
+    }
+
+    fn deserialize(
+        &mut self,
+        str: &str,
+        decoder: Option<&Decoder>,
+        bytes: &[u8],
+        mt: &MediaType,
+        deserialize: bool,
+    ) -> Result<Option<Rc<Value>>, Box<dyn std::error::Error>> {
+        let key = (decoder.map(|d| d.func as usize), mt.func as usize);
+        let entries = self.deserialized.get(str).map_or(&[][..], Vec::as_slice);
+        match entries.iter().find(|(k, _)| *k == key) {
+            Some((_, Some(v))) => return Ok(Some(Rc::clone(v))),
+            Some((_, None)) if !deserialize => return Ok(None),
+            _ => {}
+        }
+        let v = (mt.func)(bytes, deserialize)?.map(Rc::new);
+        self.insert(|c| &mut c.deserialized, str, key, v.clone(), bytes.len());
+        Ok(v)
+    }
+}
+
+// error for content of `got` bytes, if it exceeds `limit`.
+fn too_large(
+    kw: &'static str,
+    got: usize,
+    limit: Option<usize>,
+) -> Option<ErrorKind<'static, 'static>> {
+    let want = limit.filter(|want| got > *want)?;
+    Some(ErrorKind::ContentTooLarge { kw, got, want })
+}
+
+// validates `v` as a separate instance, using `vloc` as scratch space
+// for instance location.
+fn validate_in<'s, 'v>(
+    v: &'v Value,
+    schema: &'s Schema,
+    schemas: &'s Schemas,
+    opts: &ValidationOptions,
+    state: &mut State<'s>,
+    vloc: &mut Vec<InstanceToken<'v>>,
+    bool_result: bool,
 ) -> Result<(), ValidationError<'s, 'v>> {
     let scope = Scope {
         sch: schema.idx,
@@ -27,24 +260,28 @@ pub(crate) fn validate<'s, 'v>(
         vid: 0,
         parent: None,
     };
-    let mut vloc = Vec::with_capacity(8);
     let result = Validator {
         v,
-        vloc: &mut vloc,
+        vloc,
         schema,
         schemas,
+        opts,
+        state,
         scope,
         uneval: Uneval::from(v, schema, false),
         errors: vec![],
-        bool_result: false,
+        bool_result,
     }
     .validate();
     match result {
         Err(err) => {
             let mut e = ValidationError {
-                schema_url: &schema.loc,
+                schema_url: Cow::Borrowed(&schema.loc),
                 instance_location: InstanceLocation::new(),
-                kind: ErrorKind::Schema { url: &schema.loc },
+                kind: ErrorKind::Schema {
+                    url: Cow::Borrowed(&schema.loc),
+                },
+                example: None,
                 causes: vec![],
             };
             if let ErrorKind::Group = err.kind {
@@ -85,90 +322,209 @@ struct Validator<'v, 's, 'd, 'e> {
     vloc: &'e mut Vec<InstanceToken<'v>>,
     schema: &'s Schema,
     schemas: &'s Schemas,
+    opts: &'e ValidationOptions<'e>,
+    state: &'e mut State<'s>,
     scope: Scope<'d>,
-    uneval: Uneval<'v>,
+    uneval: Uneval,
     errors: Vec<ValidationError<'s, 'v>>,
     bool_result: bool, // is interested to know valid or not (but not actuall error)
 }
 
 impl<'v, 's> Validator<'v, 's, '_, '_> {
-    fn validate(mut self) -> Result<Uneval<'v>, ValidationError<'s, 'v>> {
+    fn validate(mut self) -> Result<Uneval, ValidationError<'s, 'v>> {
         let s = self.schema;
         let v = self.v;
 
+        // budget --
+        if self.state.aborted.is_some() {
+            return Err(self.error(kind!(Group)));
+        }
+        self.state.metrics.evaluations += 1;
+        if let Some(max) = self.opts.max_operations {
+            if self.state.metrics.evaluations > max {
+                self.state.aborted = Some(ValidationError {
+                    schema_url: Cow::Borrowed(&s.loc),
+                    instance_location: self.instance_location().rebind(),
+                    kind: kind!(BudgetExceeded, operations: max),
+                    example: None,
+                    causes: vec![],
+                });
+                return Err(self.error(kind!(Group)));
+            }
+        }
+        if let Some(kind) = self.exceeded_limit() {
+            self.state.aborted = Some(ValidationError {
+                schema_url: Cow::Borrowed(&s.loc),
+                instance_location: self.instance_location().rebind(),
+                kind,
+                example: None,
+                causes: vec![],
+            });
+            return Err(self.error(kind!(Group)));
+        }
+
         // boolean --
         if let Some(b) = s.boolean {
-            return match b {
-                false => Err(self.error(kind!(FalseSchema))),
-                true => Ok(self.uneval),
+            if b {
+                self.annotate(); // schema folded to true may have flags
+                return Ok(self.uneval);
+            }
+            // schema folded from `enum` or `not`, reports same error as without folding
+            let kind = match &s.enum_ {
+                Some(Enum { values, .. }) => ErrorKind::Enum {
+                    want: Cow::Borrowed(values),
+                    as_number: self.loose_number(v),
+                },
+                None if s.not.is_some() => kind!(Not),
+                None => kind!(FalseSchema),
             };
+            return Err(self.leaf(kind));
+        }
+
+        // ignored keywords --
+        if self.opts.warn_ignored && !s.ignored.is_empty() {
+            let instance_location = self.instance_location();
+            for (keyword, reason) in &s.ignored {
+                self.state.warnings.push(ValidationError {
+                    schema_url: Cow::Borrowed(&s.loc),
+                    instance_location: instance_location.clone().rebind(),
+                    kind: ErrorKind::IgnoredKeyword {
+                        keyword: Cow::Borrowed(keyword),
+                        reason: *reason,
+                    },
+                    example: None,
+                    causes: vec![],
+                });
+            }
         }
 
         // check cycle --
         if let Some(scp) = self.scope.check_cycle() {
             let kind = ErrorKind::RefCycle {
-                url: &self.schema.loc,
+                url: Cow::Borrowed(&self.schema.loc),
                 kw_loc1: self.kw_loc(&self.scope),
                 kw_loc2: self.kw_loc(scp),
             };
-            return Err(self.error(kind));
+            return Err(self.leaf(kind));
+        }
+
+        // $ref --
+        if let (Some(ref_), true) = (s.ref_, s.draft_version < 2019) {
+            // siblings are ignored
+            return self.validate_ref(ref_, "$ref").map(|_| self.uneval);
         }
 
         // type --
         if !s.types.is_empty() {
             let v_type = Type::of(v);
-            let matched =
-                s.types.contains(v_type) || (s.types.contains(Type::Integer) && is_integer(v));
+            let matched = s.types.contains(v_type)
+                || (s.types.contains(Type::Integer)
+                    && is_integer(v, s.float_integers || self.opts.draft4_float_integers));
             if !matched {
-                return Err(self.error(kind!(Type, v_type, s.types)));
+                let value = matches!(v, Value::Number(_) | Value::String(_));
+                return Err(self.leaf(ErrorKind::Type {
+                    got: v_type,
+                    want: s.types,
+                    value: value.then_some(Cow::Borrowed(v)),
+                }));
             }
         }
 
         // constant --
         if let Some(c) = &s.constant {
             if !equals(v, c) {
-                return Err(self.error(kind!(Const, want: c)));
+                let as_number = self.loose_number(v);
+                if !as_number.as_ref().is_some_and(|n| loose_equals(v, n, c)) {
+                    return Err(self.leaf(ErrorKind::Const {
+                        want: Cow::Borrowed(c),
+                        as_number,
+                    }));
+                }
             }
         }
 
         // enum --
         if let Some(Enum { types, values }) = &s.enum_ {
             if !types.contains(Type::of(v)) || !values.iter().any(|e| equals(e, v)) {
-                return Err(self.error(kind!(Enum, want: values)));
+                let as_number = self.loose_number(v);
+                let matched = as_number
+                    .as_ref()
+                    .is_some_and(|n| values.iter().any(|e| loose_equals(v, n, e)));
+                if !matched {
+                    return Err(self.leaf(ErrorKind::Enum {
+                        want: Cow::Borrowed(values),
+                        as_number,
+                    }));
+                }
             }
         }
 
+        // visitor --
+        if let (Some(visitor), false) = (self.opts.visitor, self.state.detached) {
+            if let Err(e) = visitor.visit(&self.instance_location(), v, s.idx) {
+                self.add_error(ErrorKind::Custom {
+                    code: e.code,
+                    message: e.message,
+                });
+            }
+        }
+
+        // annotation --
+        self.annotate();
+
         // format --
         if let Some(format) = &s.format {
-            if let Err(e) = (format.func)(v) {
+            let result = match &s.format_policy {
+                Some(policy) => (format.func)(v).and_then(|_| policy.check(v)),
+                None => (format.func)(v),
+            };
+            if let Err(e) = result {
                 self.add_error(kind!(Format, Cow::Borrowed(v), format.name, e));
             }
         }
+        if let Some(format) = &s.context_format {
+            if let Err(e) = (format.func)(v, self.opts.context) {
+                self.add_error(kind!(Format, Cow::Borrowed(v), format.name, e));
+            }
+        }
+        if let (Some(range), Value::String(str)) = (&s.time_range, v) {
+            if let Err((keyword, err)) = range.check(str, self.opts.clock) {
+                self.add_error(ErrorKind::Keyword { keyword, err });
+            }
+        }
+
+        // custom keywords --
+        for (keyword, kw) in &s.keywords {
+            if let Err(err) = kw.validate(v, self.opts.context) {
+                self.add_error(ErrorKind::Keyword { keyword, err });
+            }
+        }
 
         // $ref --
         if let Some(ref_) = s.ref_ {
-            let result = self.validate_ref(ref_, "$ref");
-            if s.draft_version < 2019 {
-                return result.map(|_| self.uneval);
+            if let Err(e) = self.validate_ref(ref_, "$ref") {
+                self.push_error(e);
             }
-            self.errors.extend(result.err());
         }
 
         // type specific validations --
+        let g = s.groups;
         match v {
-            Value::Object(obj) => self.obj_validate(obj),
-            Value::Array(arr) => self.arr_validate(arr),
-            Value::String(str) => self.str_validate(str),
-            Value::Number(num) => self.num_validate(num),
+            Value::Object(obj) if g.contains(KeywordGroups::OBJECT) => self.obj_validate(obj),
+            Value::Array(arr) if g.contains(KeywordGroups::ARRAY) => self.arr_validate(arr),
+            Value::String(str) if g.contains(KeywordGroups::STRING) => self.str_validate(str),
+            Value::Number(num) if g.contains(KeywordGroups::NUMBER) => self.num_validate(num),
             _ => {}
         }
 
-        if self.errors.is_empty() || !self.bool_result {
-            if s.draft_version >= 2019 {
+        if self.errors.is_empty() || !self.stop_early() {
+            if s.draft_version >= 2019 && g.contains(KeywordGroups::REFS) {
                 self.refs_validate();
             }
-            self.cond_validate();
-            if s.draft_version >= 2019 {
+            if g.contains(KeywordGroups::CONDITIONALS) {
+                self.cond_validate();
+            }
+            if s.draft_version >= 2019 && g.contains(KeywordGroups::UNEVAL) {
                 self.uneval_validate();
             }
         }
@@ -192,7 +548,7 @@ impl<'v> Validator<'v, '_, '_, '_> {
         macro_rules! add_err {
             ($result:expr) => {
                 if let Err(e) = $result {
-                    self.errors.push(e);
+                    self.push_error(e);
                 }
             };
         }
@@ -214,11 +570,16 @@ impl<'v> Validator<'v, '_, '_, '_> {
         // required --
         if !s.required.is_empty() {
             if let Some(missing) = self.find_missing(obj, &s.required) {
-                self.add_error(kind!(Required, want: missing));
+                let mut details = vec![];
+                if self.opts.required_details && !self.bool_result {
+                    details = self.required_details(obj, &s.required);
+                    details = self.summarize_errors(details);
+                }
+                self.add_error_with_causes(kind!(Required, want: missing), details);
             }
         }
 
-        if self.bool_result && !self.errors.is_empty() {
+        if self.stop_early() && !self.errors.is_empty() {
             return;
         }
 
@@ -228,7 +589,10 @@ impl<'v> Validator<'v, '_, '_, '_> {
                 match dep {
                     Dependency::Props(required) => {
                         if let Some(missing) = self.find_missing(obj, required) {
-                            self.add_error(ErrorKind::Dependency { prop, missing });
+                            self.add_error(ErrorKind::Dependency {
+                                prop: prop.into(),
+                                missing,
+                            });
                         }
                     }
                     Dependency::SchemaRef(sch) => {
@@ -238,9 +602,23 @@ impl<'v> Validator<'v, '_, '_, '_> {
             }
         }
 
+        // additionalProperties: false, without patternProperties --
+        // any property missing in `properties` fails, so find it by name
+        // alone, before validating property values.
+        if self.stop_early()
+            && s.pattern_properties.is_empty()
+            && matches!(s.additional_properties, Some(Additional::Bool(false)))
+            && obj.len() > s.properties.len()
+        {
+            if let Some(pname) = obj.keys().find(|p| !s.properties.contains_key(*p)) {
+                self.add_error(kind!(AdditionalProperties, got: vec![pname.into()]));
+                return;
+            }
+        }
+
         let mut additional_props = vec![];
-        for (pname, pvalue) in obj {
-            if self.bool_result && !self.errors.is_empty() {
+        for (i, (pname, pvalue)) in obj.iter().enumerate() {
+            if self.stop_early() && !self.errors.is_empty() {
                 return;
             }
             let mut evaluated = false;
@@ -267,6 +645,9 @@ impl<'v> Validator<'v, '_, '_, '_> {
                         Additional::Bool(allowed) => {
                             if !allowed {
                                 additional_props.push(pname.into());
+                                if self.stop_early() {
+                                    break;
+                                }
                             }
                         }
                         Additional::SchemaRef(sch) => {
@@ -277,7 +658,7 @@ impl<'v> Validator<'v, '_, '_, '_> {
             }
 
             if evaluated {
-                self.uneval.props.remove(pname);
+                self.uneval.props.remove(i);
             }
         }
         if !additional_props.is_empty() {
@@ -290,16 +671,52 @@ impl<'v> Validator<'v, '_, '_, '_> {
 
         // propertyNames --
         if let Some(sch) = &s.property_names {
+            // reused for each property name, to avoid allocation per property
+            let mut v = Value::String(String::new());
+            let mut errors = vec![];
+            // name is not part of instance, so its branches are not reported
+            let branches = self.state.branches.len();
+            let detached = std::mem::replace(&mut self.state.detached, true);
+            // errors are reworded below, so summarized after that
+            let summary = self.state.summary.take();
+            let schema = self.schemas.get(*sch);
+            // valid names are checked in place, without copying them into `v`
+            let fast = schema.leaf && self.opts.max_operations.is_none();
             for pname in obj.keys() {
-                let v = Value::String(pname.to_owned());
-                if let Err(mut e) = self.schemas.validate(&v, *sch) {
-                    e.schema_url = &s.loc;
+                if fast && is_leaf_name_valid(schema, pname) {
+                    self.state.metrics.evaluations += 1;
+                    continue;
+                }
+                if let Value::String(name) = &mut v {
+                    name.clear();
+                    name.push_str(pname);
+                }
+                if let Err(e) = validate_in(
+                    &v,
+                    schema,
+                    self.schemas,
+                    self.opts,
+                    self.state,
+                    // never allocates: string instance has no children
+                    &mut Vec::new(),
+                    self.bool_result,
+                ) {
+                    // string instance has no children, so `pname` is the only
+                    // instance data referred by `e`, and is borrowed from it
+                    let mut e = e.rebind(Some(pname));
+                    e.schema_url = Cow::Borrowed(&s.loc);
                     e.kind = ErrorKind::PropertyName {
                         prop: pname.to_owned(),
                     };
-                    self.errors.push(e.clone_static());
+                    errors.push(e);
                 }
             }
+            self.state.summary = summary;
+            for e in self.summarize_errors(errors) {
+                self.push_error(e);
+            }
+            self.state.branches.truncate(branches);
+            self.state.detached = detached;
         }
 
         if s.draft_version == 6 {
@@ -307,9 +724,25 @@ impl<'v> Validator<'v, '_, '_, '_> {
         }
 
         // dependentSchemas --
-        for (pname, sch) in &s.dependent_schemas {
-            if obj.contains_key(pname) {
-                add_err!(self.validate_self(*sch));
+        if !s.dependent_schemas.is_empty() {
+            let (branches, annotated) = (self.state.branches.len(), self.state.annotated.len());
+            let mut evaluated = vec![];
+            let mut failed = false;
+            for (pname, sch) in &s.dependent_schemas {
+                if obj.contains_key(pname) {
+                    match self.validate_branch(*sch, None, false) {
+                        Ok(uneval) => evaluated.push(uneval),
+                        Err(e) => {
+                            self.push_error(e);
+                            failed = true;
+                        }
+                    }
+                }
+            }
+            if failed {
+                self.discard(branches, annotated);
+            } else {
+                self.merge_branches(evaluated);
             }
         }
 
@@ -317,7 +750,10 @@ impl<'v> Validator<'v, '_, '_, '_> {
         for (prop, required) in &s.dependent_required {
             if obj.contains_key(prop) {
                 if let Some(missing) = self.find_missing(obj, required) {
-                    self.add_error(ErrorKind::DependentRequired { prop, missing });
+                    self.add_error(ErrorKind::DependentRequired {
+                        prop: prop.into(),
+                        missing,
+                    });
                 }
             }
         }
@@ -329,7 +765,7 @@ impl<'v> Validator<'v, '_, '_, '_> {
         macro_rules! add_err {
             ($result:expr) => {
                 if let Err(e) = $result {
-                    self.errors.push(e);
+                    self.push_error(e);
                 }
             };
         }
@@ -350,7 +786,7 @@ impl<'v> Validator<'v, '_, '_, '_> {
 
         // uniqueItems --
         if len > 1 && s.unique_items {
-            if let Some((i, j)) = duplicates(arr) {
+            if let Some((i, j)) = duplicates(arr, self.opts.hash_unique_items) {
                 self.add_error(kind!(UniqueItems, got: [i, j]));
             }
         }
@@ -362,9 +798,7 @@ impl<'v> Validator<'v, '_, '_, '_> {
             if let Some(items) = &s.items {
                 match items {
                     Items::SchemaRef(sch) => {
-                        for (i, item) in arr.iter().enumerate() {
-                            add_err!(self.validate_val(*sch, item, item!(i)));
-                        }
+                        self.items_validate(*sch, arr);
                         evaluated = len;
                         debug_assert!(self.uneval.items.is_empty());
                     }
@@ -386,9 +820,7 @@ impl<'v> Validator<'v, '_, '_, '_> {
                         }
                     }
                     Additional::SchemaRef(sch) => {
-                        for (i, item) in arr[evaluated..].iter().enumerate() {
-                            add_err!(self.validate_val(*sch, item, item!(i)));
-                        }
+                        self.items_validate(*sch, &arr[evaluated..]);
                     }
                 }
                 debug_assert!(self.uneval.items.is_empty());
@@ -402,9 +834,7 @@ impl<'v> Validator<'v, '_, '_, '_> {
             // items2020 --
             if let Some(sch) = &s.items2020 {
                 let evaluated = min(s.prefix_items.len(), len);
-                for (i, item) in arr[evaluated..].iter().enumerate() {
-                    add_err!(self.validate_val(*sch, item, item!(i)));
-                }
+                self.items_validate(*sch, &arr[evaluated..]);
                 debug_assert!(self.uneval.items.is_empty());
             }
         }
@@ -413,29 +843,40 @@ impl<'v> Validator<'v, '_, '_, '_> {
         if let Some(sch) = &s.contains {
             let mut matched = vec![];
             let mut errors = vec![];
+            let mark = self.summary_mark();
 
             for (i, item) in arr.iter().enumerate() {
                 if let Err(e) = self.validate_val(*sch, item, item!(i)) {
-                    errors.push(e);
+                    // placeholders in summary mode, so one is enough
+                    if self.state.summary.is_none() || errors.is_empty() {
+                        errors.push(e);
+                    }
                 } else {
                     matched.push(i);
-                    if s.draft_version >= 2020 {
-                        self.uneval.items.remove(&i);
-                    }
                 }
             }
 
             // minContains --
-            if let Some(min) = s.min_contains {
-                if matched.len() < min {
-                    let mut e = self.error(kind!(MinContains, matched.clone(), min));
-                    e.causes = errors;
-                    self.errors.push(e);
+            let enough = match s.min_contains {
+                Some(min) => matched.len() >= min,
+                None => !matched.is_empty(),
+            };
+            if enough {
+                self.summary_truncate(mark);
+            } else if let Some(min) = s.min_contains {
+                let kind = kind!(MinContains, matched.clone(), min);
+                self.add_error_with_causes(kind, errors);
+            } else {
+                self.add_error_with_causes(kind!(Contains), errors);
+            }
+
+            // since 2020-12, matched items are evaluated, if keyword passed.
+            // this reaches parent scopes only if this schema passes, see Uneval
+            let too_many = s.max_contains.is_some_and(|max| matched.len() > max);
+            if enough && !too_many && s.draft_version >= 2020 {
+                for &i in &matched {
+                    self.uneval.items.remove(i);
                 }
-            } else if matched.is_empty() {
-                let mut e = self.error(kind!(Contains));
-                e.causes = errors;
-                self.errors.push(e);
             }
 
             // maxContains --
@@ -470,7 +911,7 @@ impl<'v> Validator<'v, '_, '_, '_> {
         // pattern --
         if let Some(regex) = &s.pattern {
             if !regex.is_match(str) {
-                self.add_error(kind!(Pattern, str.into(), regex.as_str()));
+                self.add_error(kind!(Pattern, str.into(), regex.as_str().into()));
             }
         }
 
@@ -479,37 +920,91 @@ impl<'v> Validator<'v, '_, '_, '_> {
         }
 
         // contentEncoding --
-        let mut decoded = Some(Cow::from(str.as_bytes()));
+        let limit = self.opts.max_decoded_content_size;
+        let mut decoded = None;
         if let Some(decoder) = &s.content_encoding {
-            match (decoder.func)(str) {
-                Ok(bytes) => decoded = Some(Cow::from(bytes)),
+            let size = decoded_size(str, decoder);
+            if let Some(kind) = size.and_then(|n| too_large("contentEncoding", n, limit)) {
+                self.add_error(kind);
+                return;
+            }
+            match self.state.content.decode(str, decoder) {
+                Ok(bytes) => {
+                    if let Some(kind) = too_large("contentEncoding", bytes.len(), limit) {
+                        self.add_error(kind);
+                        return;
+                    }
+                    decoded = Some(bytes);
+                }
                 Err(err) => {
-                    decoded = None;
                     self.add_error(ErrorKind::ContentEncoding {
                         want: decoder.name,
                         err,
-                    })
+                    });
+                    return;
                 }
             }
         }
 
         // contentMediaType --
         let mut deserialized = None;
-        if let (Some(mt), Some(decoded)) = (&s.content_media_type, decoded) {
-            match (mt.func)(decoded.as_ref(), s.content_schema.is_some()) {
+        if let Some(mt) = &s.content_media_type {
+            let bytes = decoded.as_deref().map_or(str.as_bytes(), Vec::as_slice);
+            if let Some(kind) = too_large("contentMediaType", bytes.len(), limit) {
+                self.add_error(kind);
+                return;
+            }
+            let result = self.state.content.deserialize(
+                str,
+                s.content_encoding.as_ref(),
+                bytes,
+                mt,
+                s.content_schema.is_some(),
+            );
+            match result {
                 Ok(des) => deserialized = des,
                 Err(e) => {
-                    self.add_error(kind!(ContentMediaType, decoded.into(), mt.name, e));
+                    self.add_error(kind!(ContentMediaType, bytes.to_vec(), mt.name, e));
                 }
             }
         }
 
         // contentSchema --
         if let (Some(sch), Some(v)) = (s.content_schema, deserialized) {
-            if let Err(mut e) = self.schemas.validate(&v, sch) {
-                e.schema_url = &s.loc;
+            let sch = self.schemas.get(sch);
+            let branches = self.state.branches.len();
+            let aborted = self.state.aborted.is_some();
+            let detached = std::mem::replace(&mut self.state.detached, true);
+            // errors are reworded below, so summarized after that
+            let summary = self.state.summary.take();
+            let result = validate_in(
+                &v,
+                sch,
+                self.schemas,
+                self.opts,
+                self.state,
+                &mut Vec::new(),
+                self.bool_result,
+            );
+            // decoded content is not part of instance, so its branches are not reported
+            self.state.branches.truncate(branches);
+            self.state.detached = detached;
+            self.state.summary = summary;
+            if !aborted && self.state.aborted.is_some() {
+                // budget exceeded within decoded content, is reported at the string
+                let loc = self.instance_location().rebind();
+                if let Some(e) = &mut self.state.aborted {
+                    e.instance_location = loc;
+                }
+            }
+            if let Err(e) = result {
+                // decoded content is not part of instance, so it is copied
+                let mut e = e.rebind(None);
+                e.schema_url = Cow::Borrowed(&s.loc);
                 e.kind = kind!(ContentSchema);
-                self.errors.push(e.clone_static());
+                for e in self.summarize_errors(vec![e]) {
+                    self.push_error(e);
+                }
             }
         }
     }
@@ -519,46 +1014,44 @@ impl<'v> Validator<'v, '_, '_, '_> {
 
         // minimum --
         if let Some(min) = &s.minimum {
-            if let (Some(minf), Some(numf)) = (min.as_f64(), num.as_f64()) {
-                if numf < minf {
-                    self.add_error(kind!(Minimum, Cow::Borrowed(num), min));
-                }
+            if num_cmp(num, min) == Some(Ordering::Less) {
+                self.add_error(kind!(Minimum, Cow::Borrowed(num), Cow::Borrowed(min)));
             }
         }
 
         // maximum --
         if let Some(max) = &s.maximum {
-            if let (Some(maxf), Some(numf)) = (max.as_f64(), num.as_f64()) {
-                if numf > maxf {
-                    self.add_error(kind!(Maximum, Cow::Borrowed(num), max));
-                }
+            if num_cmp(num, max) == Some(Ordering::Greater) {
+                self.add_error(kind!(Maximum, Cow::Borrowed(num), Cow::Borrowed(max)));
             }
         }
 
         // exclusiveMinimum --
         if let Some(ex_min) = &s.exclusive_minimum {
-            if let (Some(ex_minf), Some(numf)) = (ex_min.as_f64(), num.as_f64()) {
-                if numf <= ex_minf {
-                    self.add_error(kind!(ExclusiveMinimum, Cow::Borrowed(num), ex_min));
-                }
+            if num_cmp(num, ex_min).is_some_and(Ordering::is_le) {
+                self.add_error(kind!(
+                    ExclusiveMinimum,
+                    Cow::Borrowed(num),
+                    Cow::Borrowed(ex_min)
+                ));
             }
         }
 
         // exclusiveMaximum --
         if let Some(ex_max) = &s.exclusive_maximum {
-            if let (Some(ex_maxf), Some(numf)) = (ex_max.as_f64(), num.as_f64()) {
-                if numf >= ex_maxf {
-                    self.add_error(kind!(ExclusiveMaximum, Cow::Borrowed(num), ex_max));
-                }
+            if num_cmp(num, ex_max).is_some_and(Ordering::is_ge) {
+                self.add_error(kind!(
+                    ExclusiveMaximum,
+                    Cow::Borrowed(num),
+                    Cow::Borrowed(ex_max)
+                ));
             }
         }
 
         // multipleOf --
         if let Some(mul) = &s.multiple_of {
-            if let (Some(mulf), Some(numf)) = (mul.as_f64(), num.as_f64()) {
-                if (numf / mulf).fract() != 0.0 {
-                    self.add_error(kind!(MultipleOf, Cow::Borrowed(num), mul));
-                }
+            if !num_multiple_of(num, mul) {
+                self.add_error(kind!(MultipleOf, Cow::Borrowed(num), Cow::Borrowed(mul)));
             }
         }
     }
@@ -571,7 +1064,7 @@ impl<'v, 's> Validator<'v, 's, '_, '_> {
         macro_rules! add_err {
             ($result:expr) => {
                 if let Err(e) = $result {
-                    self.errors.push(e);
+                    self.push_error(e);
                 }
             };
         }
@@ -604,7 +1097,7 @@ impl<'v, 's> Validator<'v, 's, '_, '_> {
         kw: &'static str,
     ) -> Result<(), ValidationError<'s, 'v>> {
         if let Err(err) = self._validate_self(sch, kw.into(), false) {
-            let url = &self.schemas.get(sch).loc;
+            let url = Cow::Borrowed(self.schemas.get(sch).loc.as_str());
             let mut ref_err = self.error(ErrorKind::Reference { kw, url });
             if let ErrorKind::Group = err.kind {
                 ref_err.causes = err.causes;
@@ -659,76 +1152,114 @@ impl Validator<'_, '_, '_, '_> {
         macro_rules! add_err {
             ($result:expr) => {
                 if let Err(e) = $result {
-                    self.errors.push(e);
+                    self.push_error(e);
                 }
             };
         }
 
         // not --
+        // annotations are never collected from `not`
         if let Some(not) = s.not {
-            if self._validate_self(not, None, true).is_ok() {
+            let (branches, annotated) = (self.state.branches.len(), self.state.annotated.len());
+            if self.validate_branch(not, None, true).is_ok() {
+                self.discard(branches, annotated);
                 self.add_error(kind!(Not));
             }
         }
 
         // allOf --
+        // what subschemas evaluated counts only if all of them pass
         if !s.all_of.is_empty() {
+            let (branches, annotated) = (self.state.branches.len(), self.state.annotated.len());
+            let mut evaluated = vec![];
             let mut errors = vec![];
             for sch in &s.all_of {
-                if let Err(e) = self.validate_self(*sch) {
-                    errors.push(e);
-                    if self.bool_result {
-                        break;
+                match self.validate_branch(*sch, None, false) {
+                    Ok(uneval) => evaluated.push(uneval),
+                    Err(e) => {
+                        errors.push(e);
+                        if self.stop_early() {
+                            break;
+                        }
                     }
                 }
             }
-            if !errors.is_empty() {
+            if errors.is_empty() {
+                self.merge_branches(evaluated);
+            } else {
+                self.discard(branches, annotated);
                 self.add_errors(errors, kind!(AllOf));
             }
         }
 
         // anyOf --
         if !s.any_of.is_empty() {
-            let mut matched = false;
+            let mut matched = vec![];
             let mut errors = vec![];
-            for sch in &s.any_of {
+            let mark = self.summary_mark();
+            for (i, sch) in s.any_of.iter().enumerate() {
                 match self.validate_self(*sch) {
                     Ok(_) => {
-                        matched = true;
-                        // for uneval, all schemas must be checked
-                        if self.uneval.is_empty() {
+                        matched.push(i);
+                        // for uneval, branches and annotations, all schemas must be checked
+                        if self.uneval.is_empty()
+                            && !self.opts.collect_branches
+                            && self.opts.annotation.is_none()
+                        {
                             break;
                         }
                     }
                     Err(e) => errors.push(e),
                 }
             }
-            if !matched {
+            if matched.is_empty() {
                 self.add_errors(errors, kind!(AnyOf));
+            } else {
+                self.summary_truncate(mark);
+                if self.opts.collect_branches {
+                    self.add_branches("anyOf", matched);
+                }
             }
         }
 
         // oneOf --
         if !s.one_of.is_empty() {
+            let (branches, annotated) = (self.state.branches.len(), self.state.annotated.len());
             let mut matched = None;
             let mut errors = vec![];
+            let mut multiple = false;
+            let mark = self.summary_mark();
             for (i, sch) in s.one_of.iter().enumerate() {
-                if let Err(e) = self._validate_self(*sch, None, matched.is_some()) {
-                    if matched.is_none() {
-                        errors.push(e);
+                match self.validate_branch(*sch, None, matched.is_some()) {
+                    Err(e) => {
+                        if matched.is_none() {
+                            errors.push(e);
+                        }
                     }
-                } else {
-                    match matched {
-                        None => _ = matched.replace(i),
-                        Some(prev) => {
+                    Ok(uneval) => match matched {
+                        None => {
+                            matched = Some((i, uneval));
+                            self.summary_truncate(mark);
+                        }
+                        Some((prev, _)) => {
+                            // neither match contributes, as keyword failed
+                            self.discard(branches, annotated);
                             self.add_error(ErrorKind::OneOf(Some((prev, i))));
+                            multiple = true;
                             break;
                         }
-                    }
+                    },
                 }
             }
-            if matched.is_none() {
-                self.add_errors(errors, ErrorKind::OneOf(None));
+            match matched {
+                _ if multiple => {}
+                None => self.add_errors(errors, ErrorKind::OneOf(None)),
+                Some((i, uneval)) => {
+                    self.uneval.merge(&uneval);
+                    if self.opts.collect_branches {
+                        self.add_branches("oneOf", vec![i]);
+                    }
+                }
             }
         }
 
@@ -753,7 +1284,7 @@ impl Validator<'_, '_, '_, '_> {
         macro_rules! add_err {
             ($result:expr) => {
                 if let Err(e) = $result {
-                    self.errors.push(e);
+                    self.push_error(e);
                 }
             };
         }
@@ -761,8 +1292,8 @@ impl Validator<'_, '_, '_, '_> {
         // unevaluatedProperties --
         if let (Some(sch), Value::Object(obj)) = (s.unevaluated_properties, v) {
             let uneval = std::mem::take(&mut self.uneval);
-            for pname in &uneval.props {
-                if let Some(pvalue) = obj.get(*pname) {
+            for (i, (pname, pvalue)) in obj.iter().enumerate() {
+                if uneval.props.contains(i) {
                     add_err!(self.validate_val(sch, pvalue, prop!(pname)));
                 }
             }
@@ -772,9 +1303,9 @@ impl Validator<'_, '_, '_, '_> {
         // unevaluatedItems --
         if let (Some(sch), Value::Array(arr)) = (s.unevaluated_items, v) {
             let uneval = std::mem::take(&mut self.uneval);
-            for i in &uneval.items {
-                if let Some(pvalue) = arr.get(*i) {
-                    add_err!(self.validate_val(sch, pvalue, item!(*i)));
+            for i in uneval.items.iter() {
+                if let Some(pvalue) = arr.get(i) {
+                    add_err!(self.validate_val(sch, pvalue, item!(i)));
                 }
             }
             self.uneval.items.clear();
@@ -797,18 +1328,44 @@ impl<'v, 's> Validator<'v, 's, '_, '_> {
         }
         let scope = self.scope.child(sch, None, self.scope.vid + 1);
         let schema = &self.schemas.get(sch);
-        Validator {
+        let (branches, annotated) = (self.state.branches.len(), self.state.annotated.len());
+        let result = Validator {
             v,
             vloc: self.vloc,
             schema,
             schemas: self.schemas,
+            opts: self.opts,
+            state: self.state,
             scope,
             uneval: Uneval::from(v, schema, false),
             errors: vec![],
             bool_result: self.bool_result,
         }
-        .validate()
-        .map(|_| ())
+        .validate();
+        if result.is_err() {
+            self.discard(branches, annotated);
+        }
+        result.map(|_| ())
+    }
+
+    // validates each of `items` with `sch`. items of leaf schema are
+    // checked in place; only failing ones get a validator, to report errors.
+    fn items_validate(&mut self, sch: SchemaIndex, items: &'v [Value]) {
+        let schema = self.schemas.get(sch);
+        let fast = schema.leaf
+            && self.opts.max_operations.is_none()
+            && self.opts.visitor.is_none()
+            && self.opts.annotation.is_none()
+            && !self.opts.limits_instance();
+        for (i, item) in items.iter().enumerate() {
+            if fast && is_leaf_valid(schema, item, self.opts.draft4_float_integers) {
+                self.state.metrics.evaluations += 1;
+                continue;
+            }
+            if let Err(e) = self.validate_val(sch, item, item!(i)) {
+                self.push_error(e);
+            }
+        }
     }
 
     fn _validate_self(
@@ -817,23 +1374,54 @@ impl<'v, 's> Validator<'v, 's, '_, '_> {
         ref_kw: Option<&'static str>,
         bool_result: bool,
     ) -> Result<(), ValidationError<'s, 'v>> {
+        let evaluated = self.validate_branch(sch, ref_kw, bool_result)?;
+        self.uneval.merge(&evaluated);
+        Ok(())
+    }
+
+    // same as `_validate_self`, but returns what subschema evaluated rather
+    // than merging it, for keywords which pass only if other subschemas do
+    fn validate_branch(
+        &mut self,
+        sch: SchemaIndex,
+        ref_kw: Option<&'static str>,
+        bool_result: bool,
+    ) -> Result<Uneval, ValidationError<'s, 'v>> {
         let scope = self.scope.child(sch, ref_kw, self.scope.vid);
         let schema = &self.schemas.get(sch);
+        let (branches, annotated) = (self.state.branches.len(), self.state.annotated.len());
         let result = Validator {
             v: self.v,
             vloc: self.vloc,
             schema,
             schemas: self.schemas,
+            opts: self.opts,
+            state: self.state,
             scope,
             uneval: Uneval::from(self.v, schema, !self.uneval.is_empty()),
             errors: vec![],
             bool_result: self.bool_result || bool_result,
         }
         .validate();
-        if let Ok(reply) = &result {
-            self.uneval.merge(reply);
+        if result.is_err() {
+            self.discard(branches, annotated);
+        }
+        result
+    }
+
+    // merges what subschemas of passed keyword evaluated
+    fn merge_branches(&mut self, evaluated: impl IntoIterator<Item = Uneval>) {
+        for uneval in evaluated {
+            self.uneval.merge(&uneval);
+        }
+    }
+
+    // discards what failed subschema collected
+    fn discard(&mut self, branches: usize, annotated: usize) {
+        self.state.branches.truncate(branches);
+        if !self.opts.keep_failed_annotations {
+            self.state.annotated.truncate(annotated);
         }
-        result.map(|_| ())
     }
 
     #[inline(always)]
@@ -844,37 +1432,170 @@ impl<'v, 's> Validator<'v, 's, '_, '_> {
 
 // error helpers
 impl<'v, 's> Validator<'v, 's, '_, '_> {
+    /// whether to skip remaining keywords once an error is found.
+    #[inline(always)]
+    fn stop_early(&self) -> bool {
+        self.bool_result || self.opts.fail_fast
+    }
+
     #[inline(always)]
     fn error(&self, kind: ErrorKind<'s, 'v>) -> ValidationError<'s, 'v> {
-        if self.bool_result {
+        if self.bool_result || self.state.summary.is_some() {
             return ValidationError {
-                schema_url: &self.schema.loc,
+                schema_url: Cow::Borrowed(&self.schema.loc),
                 instance_location: InstanceLocation::new(),
                 kind: ErrorKind::Group,
+                example: None,
                 causes: vec![],
             };
         }
+        self.full_error(kind, false)
+    }
+
+    // examples are attached only to errors without causes, if `leaf`
+    fn full_error(&self, kind: ErrorKind<'s, 'v>, leaf: bool) -> ValidationError<'s, 'v> {
+        let example = match leaf && self.opts.attach_examples {
+            true => self.schema.example().map(Cow::Borrowed),
+            false => None,
+        };
         ValidationError {
-            schema_url: &self.schema.loc,
+            schema_url: Cow::Borrowed(&self.schema.loc),
             instance_location: self.instance_location(),
             kind,
+            example,
             causes: vec![],
         }
     }
 
-    #[inline(always)]
+    // records location of value, if schema has flag `ValidationOptions::annotation`.
+    // leaf and no-op schemas may have flags, so this is called for
+    // schemas folded to `true` too.
+    fn annotate(&mut self) {
+        if let (Some(flag), false) = (self.opts.annotation, self.state.detached) {
+            if self.schema.flags.iter().any(|f| f == flag) {
+                let loc = self.instance_location().rebind();
+                self.state.annotated.push(loc);
+            }
+        }
+    }
+
+    fn add_branches(&mut self, keyword: &'static str, indexes: Vec<usize>) {
+        self.state.branches.push(MatchedBranches {
+            schema_url: &self.schema.loc,
+            keyword,
+            instance_location: self.instance_location().rebind(),
+            indexes,
+        });
+    }
+
+    /// same as `error`, but for error without causes.
+    /// in summary mode, it is recorded in summary.
+    #[inline]
+    fn leaf(&mut self, kind: ErrorKind<'s, 'v>) -> ValidationError<'s, 'v> {
+        if self.bool_result {
+            return self.error(kind);
+        }
+        if self.state.summary.is_none() {
+            return self.full_error(kind, true);
+        }
+        self.add_leaf(kind)
+    }
+
+    // kept out of line, so that callers of `leaf` do not grow their stack frames
+    #[inline(never)]
+    fn add_leaf(&mut self, kind: ErrorKind<'s, 'v>) -> ValidationError<'s, 'v> {
+        let retains = self
+            .state
+            .summary
+            .as_ref()
+            .is_some_and(Summary::retains_next);
+        let code = kind.code().to_owned();
+        let error = retains.then(|| self.full_error(kind, true).rebind(None));
+        if let Some(summary) = &mut self.state.summary {
+            summary.add(&code, error);
+        }
+        self.error(kind!(Group))
+    }
+
+    /// in summary mode, records leaf errors in the trees of `errors`
+    /// in summary, and returns placeholder for them.
+    fn summarize_errors(
+        &mut self,
+        errors: Vec<ValidationError<'s, 'v>>,
+    ) -> Vec<ValidationError<'s, 'v>> {
+        let Some(summary) = &mut self.state.summary else {
+            return errors;
+        };
+        if errors.is_empty() {
+            return errors;
+        }
+        for e in errors {
+            summary.add_leaves(e.rebind(None));
+        }
+        vec![self.error(kind!(Group))]
+    }
+
+    #[inline]
+    fn push_error(&mut self, e: ValidationError<'s, 'v>) {
+        // placeholders in summary mode, so one is enough
+        if self.state.summary.is_none() || self.errors.is_empty() {
+            self.errors.push(e);
+        }
+    }
+
+    #[inline]
     fn add_error(&mut self, kind: ErrorKind<'s, 'v>) {
-        self.errors.push(self.error(kind));
+        let e = self.leaf(kind);
+        self.push_error(e);
     }
 
-    #[inline(always)]
-    fn add_errors(&mut self, errors: Vec<ValidationError<'s, 'v>>, kind: ErrorKind<'s, 'v>) {
+    #[inline]
+    fn add_errors(&mut self, mut errors: Vec<ValidationError<'s, 'v>>, kind: ErrorKind<'s, 'v>) {
         if errors.len() == 1 {
-            self.errors.extend(errors);
+            self.push_error(errors.remove(0));
         } else {
             let mut err = self.error(kind);
             err.causes = errors;
-            self.errors.push(err);
+            self.push_error(err);
+        }
+    }
+
+    /// adds error with `causes`, which is leaf if there are no causes.
+    fn add_error_with_causes(
+        &mut self,
+        kind: ErrorKind<'s, 'v>,
+        causes: Vec<ValidationError<'s, 'v>>,
+    ) {
+        if causes.is_empty() {
+            self.add_error(kind);
+        } else {
+            let mut err = self.error(kind);
+            err.causes = causes;
+            self.push_error(err);
+        }
+    }
+
+    // see `Summary::mark`
+    #[inline(always)]
+    fn summary_mark(&self) -> usize {
+        self.state.summary.as_ref().map_or(0, Summary::mark)
+    }
+
+    /// discards leaf errors recorded in summary after `mark`,
+    /// as their errors are discarded.
+    #[inline(always)]
+    fn summary_truncate(&mut self, mark: usize) {
+        if let Some(summary) = &mut self.state.summary {
+            summary.truncate(mark);
+        }
+    }
+
+    // see ValidationOptions::loose_enum_scalars
+    fn loose_number(&self, v: &Value) -> Option<Number> {
+        if self.opts.loose_enum_scalars {
+            loose_number(v)
+        } else {
+            None
         }
     }
 
@@ -887,22 +1608,50 @@ impl<'v, 's> Validator<'v, 's, '_, '_> {
             } else {
                 let cur = &self.schemas.get(scope.sch).loc;
                 let parent = &self.schemas.get(parent.sch).loc;
-                loc.insert_str(0, &cur[parent.len()..]);
+                loc.insert_str(0, &Fragment::decode(&cur[parent.len()..]));
             }
             scope = parent;
         }
         loc
     }
 
+    /// one error per missing property, located at the would-be member.
+    fn required_details(
+        &self,
+        obj: &'v Map<String, Value>,
+        required: &'s [String],
+    ) -> Vec<ValidationError<'s, 'v>> {
+        let vloc = self.instance_location();
+        required
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| !obj.contains_key(p.as_str()))
+            .map(|(index, prop)| {
+                let mut instance_location = vloc.clone();
+                instance_location.tokens.push(prop.clone().into());
+                ValidationError {
+                    schema_url: Cow::Borrowed(&self.schema.loc),
+                    instance_location,
+                    kind: ErrorKind::RequiredProperty {
+                        prop: Cow::Borrowed(prop),
+                        index,
+                    },
+                    example: None,
+                    causes: vec![],
+                }
+            })
+            .collect()
+    }
+
     fn find_missing(
         &self,
         obj: &'v Map<String, Value>,
         required: &'s [String],
-    ) -> Option<Vec<&'s str>> {
+    ) -> Option<Vec<Cow<'s, str>>> {
         let mut missing = required
             .iter()
             .filter(|p| !obj.contains_key(p.as_str()))
-            .map(|p| p.as_str());
+            .map(|p| Cow::Borrowed(p.as_str()));
         if self.bool_result {
             missing.next().map(|_| Vec::new())
         } else {
@@ -923,38 +1672,168 @@ impl<'v, 's> Validator<'v, 's, '_, '_> {
         }
         InstanceLocation { tokens }
     }
+
+    // checks limits on instance size, see ValidationOptions::max_instance_nodes
+    fn exceeded_limit(&mut self) -> Option<ErrorKind<'s, 'static>> {
+        if self.state.detached || !self.opts.limits_instance() {
+            return None;
+        }
+        let exceeds = |max: Option<usize>, got: usize, kind| match max {
+            Some(limit) if got > limit => Some(ErrorKind::InstanceTooLarge { limit, kind }),
+            _ => None,
+        };
+        let opts = self.opts;
+        let (max, len, kind) = match self.v {
+            Value::String(s) => (opts.max_string_length, s.len(), InstanceLimit::StringLength),
+            Value::Array(arr) => (opts.max_array_length, arr.len(), InstanceLimit::ArrayLength),
+            Value::Object(obj) => (
+                opts.max_object_members,
+                obj.len(),
+                InstanceLimit::ObjectMembers,
+            ),
+            _ => return None,
+        };
+        if let Some(kind) = exceeds(max, len, kind) {
+            return Some(kind);
+        }
+        if kind == InstanceLimit::StringLength {
+            return None;
+        }
+        let max = opts.max_instance_nodes?;
+        if self.state.counted.is_empty() {
+            self.state.nodes = 1; // root
+        }
+        if self.state.counted.insert(self.v as *const Value as usize) {
+            self.state.nodes += len;
+        }
+        exceeds(Some(max), self.state.nodes, InstanceLimit::Nodes)
+    }
+}
+
+/// checks `v` against leaf schema `s`, same as `Validator::validate`.
+/// see `Schema::is_leaf`. `float_integers` is `ValidationOptions::draft4_float_integers`.
+fn is_leaf_valid(s: &Schema, v: &Value, float_integers: bool) -> bool {
+    if let Some(b) = s.boolean {
+        return b;
+    }
+    let type_matched = s.types.is_empty()
+        || s.types.contains(Type::of(v))
+        || (s.types.contains(Type::Integer) && is_integer(v, s.float_integers || float_integers));
+    if !type_matched {
+        return false;
+    }
+    if let Some(c) = &s.constant {
+        if !equals(v, c) {
+            return false;
+        }
+    }
+    if let Some(Enum { types, values }) = &s.enum_ {
+        if !types.contains(Type::of(v)) || !values.iter().any(|e| equals(e, v)) {
+            return false;
+        }
+    }
+    match v {
+        Value::String(str) => is_leaf_str_valid(s, str),
+        Value::Number(num) => {
+            let cmp = |bound: &Option<Number>, ok: fn(Ordering) -> bool| match bound {
+                Some(bound) => num_cmp(num, bound).is_none_or(ok),
+                None => true,
+            };
+            let multiple = match &s.multiple_of {
+                Some(mul) => num_multiple_of(num, mul),
+                None => true,
+            };
+            cmp(&s.minimum, Ordering::is_ge)
+                && cmp(&s.maximum, Ordering::is_le)
+                && cmp(&s.exclusive_minimum, Ordering::is_gt)
+                && cmp(&s.exclusive_maximum, Ordering::is_lt)
+                && multiple
+        }
+        _ => true,
+    }
+}
+
+/// checks property name `name` against leaf schema `s`, same as
+/// `is_leaf_valid` with string value.
+fn is_leaf_name_valid(s: &Schema, name: &str) -> bool {
+    if let Some(b) = s.boolean {
+        return b;
+    }
+    if !s.types.is_empty() && !s.types.contains(Type::String) {
+        return false;
+    }
+    if let Some(c) = &s.constant {
+        if c.as_str() != Some(name) {
+            return false;
+        }
+    }
+    if let Some(Enum { types, values }) = &s.enum_ {
+        if !types.contains(Type::String) || !values.iter().any(|e| e.as_str() == Some(name)) {
+            return false;
+        }
+    }
+    is_leaf_str_valid(s, name)
+}
+
+fn is_leaf_str_valid(s: &Schema, str: &str) -> bool {
+    if s.min_length.is_some() || s.max_length.is_some() {
+        let len = str.chars().count();
+        if s.min_length.is_some_and(|min| len < min) || s.max_length.is_some_and(|max| len > max) {
+            return false;
+        }
+    }
+    s.pattern.as_ref().is_none_or(|regex| regex.is_match(str))
 }
 
 // Uneval --
 
 #[derive(Default)]
-struct Uneval<'v> {
-    props: HashSet<&'v String>,
-    items: HashSet<usize>,
+/*
+Tracks properties/items of the instance value, not yet evaluated by
+the schema being validated, for `unevaluatedProperties`/`unevaluatedItems`.
+
+Evaluation flows as follows:
+- keywords like `properties`, `items` and `contains` (since 2020-12)
+  remove what they evaluate from the local set.
+- subschemas applied to same instance value (`allOf`, `anyOf`, `oneOf`,
+  `if`/`then`/`else`, `$ref` etc) are validated using `_validate_self`.
+  only if subschema passes, its set is merged into ours, so that a failed
+  `anyOf` branch, failed `if` or `not` does not evaluate anything.
+  note that `not` never contributes, since its subschema passing means
+  we fail anyway.
+- subschemas applied to child values (`validate_val`) never contribute,
+  since they evaluate a different instance value.
+- `unevaluatedProperties`/`unevaluatedItems` validate the remaining set
+  and then clear it, since they evaluate everything.
+
+properties are tracked by their position in the object, so that sets of
+wide objects are cheap to create and merge across many subschemas.
+*/
+struct Uneval {
+    props: BitSet,
+    items: BitSet,
 }
 
-impl<'v> Uneval<'v> {
+impl Uneval {
     fn is_empty(&self) -> bool {
         self.props.is_empty() && self.items.is_empty()
     }
 
-    fn from(v: &'v Value, sch: &Schema, caller_needs: bool) -> Self {
+    fn from(v: &Value, sch: &Schema, caller_needs: bool) -> Self {
         let mut uneval = Self::default();
         match v {
-            Value::Object(obj) => {
+            Value::Object(obj)
                 if !sch.all_props_evaluated
-                    && (caller_needs || sch.unevaluated_properties.is_some())
-                {
-                    uneval.props = obj.keys().collect();
-                }
+                    && (caller_needs || sch.unevaluated_properties.is_some()) =>
+            {
+                uneval.props = BitSet::range(0, obj.len());
             }
-            Value::Array(arr) => {
+            Value::Array(arr)
                 if !sch.all_items_evaluated
                     && (caller_needs || sch.unevaluated_items.is_some())
-                    && sch.num_items_evaluated < arr.len()
-                {
-                    uneval.items = (sch.num_items_evaluated..arr.len()).collect();
-                }
+                    && sch.num_items_evaluated < arr.len() =>
+            {
+                uneval.items = BitSet::range(sch.num_items_evaluated, arr.len());
             }
             _ => (),
         }
@@ -962,8 +1841,59 @@ impl<'v> Uneval<'v> {
     }
 
     fn merge(&mut self, other: &Uneval) {
-        self.props.retain(|p| other.props.contains(p));
-        self.items.retain(|i| other.items.contains(i));
+        self.props.intersect(&other.props);
+        self.items.intersect(&other.items);
+    }
+}
+
+/// set of small indexes, one bit per index.
+#[derive(Default)]
+struct BitSet {
+    words: Vec<u64>,
+}
+
+impl BitSet {
+    /// set with indexes in `from..to`.
+    fn range(from: usize, to: usize) -> Self {
+        let mut words = vec![u64::MAX; to.div_ceil(64)];
+        for i in 0..from {
+            words[i / 64] &= !(1 << (i % 64));
+        }
+        if to % 64 != 0 {
+            words[to / 64] &= (1 << (to % 64)) - 1;
+        }
+        Self { words }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.words.iter().all(|w| *w == 0)
+    }
+
+    fn contains(&self, i: usize) -> bool {
+        self.words
+            .get(i / 64)
+            .is_some_and(|w| w & (1 << (i % 64)) != 0)
+    }
+
+    fn remove(&mut self, i: usize) {
+        if let Some(w) = self.words.get_mut(i / 64) {
+            *w &= !(1 << (i % 64));
+        }
+    }
+
+    fn clear(&mut self) {
+        self.words.clear();
+    }
+
+    fn intersect(&mut self, other: &BitSet) {
+        self.words.truncate(other.words.len());
+        for (w, o) in self.words.iter_mut().zip(&other.words) {
+            *w &= o;
+        }
+    }
+
+    fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.words.len() * 64).filter(|i| self.contains(*i))
     }
 }
 
@@ -996,7 +1926,7 @@ impl Scope<'_> {
         }
     }
 
-    fn check_cycle(&self) -> Option<&Scope> {
+    fn check_cycle(&self) -> Option<&Scope<'_>> {
         let mut scope = self.parent;
         while let Some(scp) = scope {
             if scp.vid != self.vid {
@@ -1012,7 +1942,10 @@ impl Scope<'_> {
 }
 
 /// Token in InstanceLocation json-pointer.
-#[derive(Debug, Clone)]
+///
+/// Ordered by property name or array index, so that `/items/2` comes
+/// before `/items/10`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum InstanceToken<'v> {
     /// Token for property.
     Prop(Cow<'v, str>),
@@ -1038,8 +1971,13 @@ impl From<usize> for InstanceToken<'_> {
     }
 }
 
-/// The location of the JSON value within the instance being validated
-#[derive(Debug, Default)]
+/**
+The location of the JSON value within the instance being validated
+
+When displayed, property names longer than 256 bytes are elided in the
+middle, noting their actual length. `tokens` hold them in full.
+*/
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct InstanceLocation<'v> {
     pub tokens: Vec<InstanceToken<'v>>,
 }
@@ -1049,15 +1987,16 @@ impl InstanceLocation<'_> {
         Self::default()
     }
 
-    fn clone_static(self) -> InstanceLocation<'static> {
-        let mut tokens = Vec::with_capacity(self.tokens.len());
-        for tok in self.tokens {
-            let tok = match tok {
+    pub(crate) fn rebind<'x>(self) -> InstanceLocation<'x> {
+        // collected in place, reusing allocation of `self.tokens`
+        let tokens = self
+            .tokens
+            .into_iter()
+            .map(|tok| match tok {
                 InstanceToken::Prop(p) => InstanceToken::Prop(p.into_owned().into()),
                 InstanceToken::Item(i) => InstanceToken::Item(i),
-            };
-            tokens.push(tok);
-        }
+            })
+            .collect();
         InstanceLocation { tokens }
     }
 }
@@ -1067,7 +2006,7 @@ impl Display for InstanceLocation<'_> {
         for tok in &self.tokens {
             f.write_char('/')?;
             match tok {
-                InstanceToken::Prop(s) => f.write_str(&escape(s))?,
+                InstanceToken::Prop(s) => f.write_str(&escape(&elide(s)))?,
                 InstanceToken::Item(i) => write!(f, "{i}")?,
             }
         }
@@ -1075,15 +2014,111 @@ impl Display for InstanceLocation<'_> {
     }
 }
 
+impl<'s, 'v> ValidationError<'s, 'v> {
+    /**
+    Creates [`ErrorKind::Custom`] error, for checks performed outside
+    of the schema.
+
+    The error has no schema location of its own; once nested using
+    [`Self::with_causes`], it reports the schema location of its parent.
+
+    ```rust
+    # use boon::*;
+    # use serde_json::json;
+    # let mut compiler = Compiler::new();
+    # compiler.add_resource("schema.json", json!({"type": "object"}))?;
+    # let mut schemas = Schemas::new();
+    # let sch_index = compiler.compile("schema.json", &mut schemas)?;
+    let instance = json!({"start": 5, "end": 2});
+    let mut errors = vec![];
+    if let Err(e) = schemas.validate(&instance, sch_index) {
+        errors.extend(e.causes);
+    }
+    if instance["start"].as_i64() > instance["end"].as_i64() {
+        let loc = InstanceLocation { tokens: vec!["end".into()] };
+        errors.push(ValidationError::custom(loc, "range", "end must not be before start"));
+    }
+    let e = ValidationError::custom(InstanceLocation::default(), "order", "invalid order")
+        .with_causes(errors);
+    assert_eq!(
+        e.to_string(),
+        "at '': invalid order\n- at '/end': end must not be before start"
+    );
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+    */
+    pub fn custom(
+        instance_location: InstanceLocation<'v>,
+        code: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        ValidationError {
+            schema_url: Cow::Borrowed(""),
+            instance_location,
+            kind: ErrorKind::Custom {
+                code: code.into(),
+                message: message.into(),
+            },
+            example: None,
+            causes: vec![],
+        }
+    }
+
+    /// Adds `causes` as nested errors of this error.
+    ///
+    /// Causes without schema location, such as those created by
+    /// [`Self::custom`], inherit the schema location of this error.
+    pub fn with_causes(mut self, causes: impl IntoIterator<Item = Self>) -> Self {
+        fn inherit<'s>(e: &mut ValidationError<'s, '_>, url: Cow<'s, str>) {
+            if e.schema_url.is_empty() {
+                for cause in &mut e.causes {
+                    inherit(cause, url.clone());
+                }
+                e.schema_url = url;
+            }
+        }
+        for mut cause in causes {
+            inherit(&mut cause, self.schema_url.clone());
+            self.causes.push(cause);
+        }
+        self
+    }
+}
+
 impl<'s> ValidationError<'s, '_> {
-    pub(crate) fn clone_static(self) -> ValidationError<'s, 'static> {
+    /// Converts this error into one that owns all its data, so that it can
+    /// outlive both [`Schemas`] and the instance being validated.
+    pub fn into_owned(self) -> ValidationError<'static, 'static> {
         let mut causes = Vec::with_capacity(self.causes.len());
         for cause in self.causes {
-            causes.push(cause.clone_static());
+            causes.push(cause.into_owned());
         }
         ValidationError {
-            instance_location: self.instance_location.clone_static(),
-            kind: self.kind.clone_static(),
+            schema_url: Cow::Owned(self.schema_url.into_owned()),
+            instance_location: self.instance_location.rebind(),
+            kind: self.kind.into_owned(),
+            example: self.example.map(|v| Cow::Owned(v.into_owned())),
+            causes,
+        }
+    }
+
+    /// Moves this error into instance lifetime `'x`, copying data borrowed
+    /// from the instance. The error tree itself is not copied: vectors of
+    /// causes and location tokens are reused.
+    ///
+    /// If `str` is given, it must be the string instance this error is
+    /// reported for; string data is borrowed from it instead of copied.
+    /// Kinds holding a [`Value`], like [`ErrorKind::Format`], still copy it.
+    pub(crate) fn rebind<'x>(self, str: Option<&'x str>) -> ValidationError<'s, 'x> {
+        // collected in place, reusing allocation of `self.causes`
+        let causes = self
+            .causes
+            .into_iter()
+            .map(|cause| cause.rebind(str))
+            .collect();
+        ValidationError {
+            instance_location: self.instance_location.rebind(),
+            kind: self.kind.rebind(str),
             causes,
             ..self
         }
@@ -1091,7 +2126,112 @@ impl<'s> ValidationError<'s, '_> {
 }
 
 impl<'s> ErrorKind<'s, '_> {
-    fn clone_static(self) -> ErrorKind<'s, 'static> {
+    /// Converts this kind into one that owns all its data.
+    pub fn into_owned(self) -> ErrorKind<'static, 'static> {
+        fn own<T: ToOwned + ?Sized>(c: Cow<T>) -> Cow<'static, T> {
+            Cow::Owned(c.into_owned())
+        }
+        fn own_all(v: Vec<Cow<str>>) -> Vec<Cow<'static, str>> {
+            v.into_iter().map(own).collect()
+        }
+
+        use ErrorKind::*;
+        match self.rebind(None) {
+            Schema { url } => Schema { url: own(url) },
+            Reference { kw, url } => Reference { kw, url: own(url) },
+            IgnoredKeyword { keyword, reason } => IgnoredKeyword {
+                keyword: own(keyword),
+                reason,
+            },
+            RefCycle {
+                url,
+                kw_loc1,
+                kw_loc2,
+            } => RefCycle {
+                url: own(url),
+                kw_loc1,
+                kw_loc2,
+            },
+            Enum { want, as_number } => Enum {
+                want: own(want),
+                as_number,
+            },
+            Const { want, as_number } => Const {
+                want: own(want),
+                as_number,
+            },
+            Required { want } => Required {
+                want: own_all(want),
+            },
+            RequiredProperty { prop, index } => RequiredProperty {
+                prop: own(prop),
+                index,
+            },
+            Dependency { prop, missing } => Dependency {
+                prop: own(prop),
+                missing: own_all(missing),
+            },
+            DependentRequired { prop, missing } => DependentRequired {
+                prop: own(prop),
+                missing: own_all(missing),
+            },
+            Pattern { got, want } => Pattern {
+                got,
+                want: own(want),
+            },
+            Minimum { got, want } => Minimum {
+                got,
+                want: own(want),
+            },
+            Maximum { got, want } => Maximum {
+                got,
+                want: own(want),
+            },
+            ExclusiveMinimum { got, want } => ExclusiveMinimum {
+                got,
+                want: own(want),
+            },
+            ExclusiveMaximum { got, want } => ExclusiveMaximum {
+                got,
+                want: own(want),
+            },
+            MultipleOf { got, want } => MultipleOf {
+                got,
+                want: own(want),
+            },
+            Group => Group,
+            ContentSchema => ContentSchema,
+            PropertyName { prop } => PropertyName { prop },
+            BudgetExceeded { operations } => BudgetExceeded { operations },
+            InstanceTooLarge { limit, kind } => InstanceTooLarge { limit, kind },
+            FalseSchema => FalseSchema,
+            Type { got, want, value } => Type { got, want, value },
+            Format { got, want, err } => Format { got, want, err },
+            Keyword { keyword, err } => Keyword { keyword, err },
+            Custom { code, message } => Custom { code, message },
+            MinProperties { got, want } => MinProperties { got, want },
+            MaxProperties { got, want } => MaxProperties { got, want },
+            AdditionalProperties { got } => AdditionalProperties { got },
+            MinItems { got, want } => MinItems { got, want },
+            MaxItems { got, want } => MaxItems { got, want },
+            Contains => Contains,
+            MinContains { got, want } => MinContains { got, want },
+            MaxContains { got, want } => MaxContains { got, want },
+            UniqueItems { got } => UniqueItems { got },
+            AdditionalItems { got } => AdditionalItems { got },
+            MinLength { got, want } => MinLength { got, want },
+            MaxLength { got, want } => MaxLength { got, want },
+            ContentEncoding { want, err } => ContentEncoding { want, err },
+            ContentMediaType { got, want, err } => ContentMediaType { got, want, err },
+            ContentTooLarge { kw, got, want } => ContentTooLarge { kw, got, want },
+            Not => Not,
+            AllOf => AllOf,
+            AnyOf => AnyOf,
+            OneOf(opt) => OneOf(opt),
+        }
+    }
+
+    fn rebind<'x>(self, str: Option<&'x str>) -> ErrorKind<'s, 'x> {
         use ErrorKind::*;
         match self {
             AdditionalProperties { got } => AdditionalProperties {
@@ -1102,8 +2242,13 @@ impl<'s> ErrorKind<'s, '_> {
                 want,
                 err,
             },
+            Keyword { keyword, err } => Keyword { keyword, err },
+            Custom { code, message } => Custom { code, message },
             Pattern { got, want } => Pattern {
-                got: got.into_owned().into(),
+                got: match str {
+                    Some(str) => Cow::Borrowed(str),
+                    None => got.into_owned().into(),
+                },
                 want,
             },
             Minimum { got, want } => Minimum {
@@ -1142,13 +2287,21 @@ impl<'s> ErrorKind<'s, '_> {
                 kw_loc1,
                 kw_loc2,
             },
+            IgnoredKeyword { keyword, reason } => IgnoredKeyword { keyword, reason },
+            BudgetExceeded { operations } => BudgetExceeded { operations },
+            InstanceTooLarge { limit, kind } => InstanceTooLarge { limit, kind },
             FalseSchema => FalseSchema,
-            Type { got, want } => Type { got, want },
-            Enum { want } => Enum { want },
-            Const { want } => Const { want },
+            Type { got, want, value } => Type {
+                got,
+                want,
+                value: value.map(|v| Cow::Owned(v.into_owned())),
+            },
+            Enum { want, as_number } => Enum { want, as_number },
+            Const { want, as_number } => Const { want, as_number },
             MinProperties { got, want } => MinProperties { got, want },
             MaxProperties { got, want } => MaxProperties { got, want },
             Required { want } => Required { want },
+            RequiredProperty { prop, index } => RequiredProperty { prop, index },
             Dependency { prop, missing } => Dependency { prop, missing },
             DependentRequired { prop, missing } => DependentRequired { prop, missing },
             MinItems { got, want } => MinItems { got, want },
@@ -1162,6 +2315,7 @@ impl<'s> ErrorKind<'s, '_> {
             MaxLength { got, want } => MaxLength { got, want },
             ContentEncoding { want, err } => ContentEncoding { want, err },
             ContentMediaType { got, want, err } => ContentMediaType { got, want, err },
+            ContentTooLarge { kw, got, want } => ContentTooLarge { kw, got, want },
             Not => Not,
             AllOf => AllOf,
             AnyOf => AnyOf,
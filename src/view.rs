@@ -0,0 +1,179 @@
+use std::fmt::Debug;
+
+use serde_json::Value;
+
+use crate::*;
+
+/// How a subschema is authored, see [`SchemaView::kind`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaKind {
+    /// boolean schema `true` or `false`.
+    Boolean(bool),
+    /// schema object, including `{}`.
+    Object,
+}
+
+/**
+Value of `additionalProperties` or `additionalItems`.
+
+Boolean value of these keywords is not compiled as a subschema, so it
+does not have [`SchemaIndex`].
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub enum AdditionalView<'s> {
+    /// keyword value is boolean.
+    Bool {
+        /// whether additional properties/items are allowed.
+        value: bool,
+        /// absolute location of the keyword.
+        location: String,
+    },
+    /// keyword value is a schema.
+    Schema(SchemaView<'s>),
+}
+
+/**
+Read-only view of compiled schema, for introspection.
+
+Subschemas which always pass or always fail are compiled as boolean
+schemas, so [`SchemaView::kind`] is the only way to tell whether a
+schema is authored as `true`/`false`.
+
+```rust
+# use boon::*;
+# use serde_json::json;
+let mut compiler = Compiler::new();
+compiler.add_resource("schema.json", json!({
+    "properties": { "a": true, "b": {} },
+    "additionalProperties": false
+}))?;
+let mut schemas = Schemas::new();
+let sch_index = compiler.compile("schema.json", &mut schemas)?;
+let view = schemas.view(sch_index);
+let props = view.properties();
+assert_eq!(props[0].1.kind(), SchemaKind::Boolean(true));
+assert_eq!(props[1].1.kind(), SchemaKind::Object);
+assert!(matches!(
+    view.additional_properties(),
+    Some(AdditionalView::Bool { value: false, .. })
+));
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+*/
+#[derive(Clone, Copy)]
+pub struct SchemaView<'s> {
+    schemas: &'s Schemas,
+    idx: SchemaIndex,
+}
+
+impl<'s> SchemaView<'s> {
+    pub(crate) fn new(schemas: &'s Schemas, idx: SchemaIndex) -> Self {
+        Self { schemas, idx }
+    }
+
+    fn schema(&self) -> &'s Schema {
+        self.schemas.get(self.idx)
+    }
+
+    fn view(&self, idx: SchemaIndex) -> SchemaView<'s> {
+        Self::new(self.schemas, idx)
+    }
+
+    /// Index of this schema, for use with [`Schemas`] methods.
+    pub fn index(&self) -> SchemaIndex {
+        self.idx
+    }
+
+    /// Absolute location of this schema.
+    pub fn location(&self) -> &'s str {
+        &self.schema().loc
+    }
+
+    /// Tells whether this schema is authored as boolean schema.
+    pub fn kind(&self) -> SchemaKind {
+        match self.schema().literal_bool {
+            Some(b) => SchemaKind::Boolean(b),
+            None => SchemaKind::Object,
+        }
+    }
+
+    /// see [`Schemas::source`].
+    pub fn source(&self) -> Option<&'s Value> {
+        self.schemas.source(self.idx)
+    }
+
+    /// Subschemas of `properties`, sorted by property name.
+    pub fn properties(&self) -> Vec<(&'s str, SchemaView<'s>)> {
+        let mut props = self
+            .schema()
+            .properties
+            .iter()
+            .map(|(name, &sch)| (name.as_str(), self.view(sch)))
+            .collect::<Vec<_>>();
+        props.sort_by_key(|(name, _)| *name);
+        props
+    }
+
+    /// Value of `additionalProperties`, if present.
+    pub fn additional_properties(&self) -> Option<AdditionalView<'s>> {
+        self.additional(&self.schema().additional_properties, "additionalProperties")
+    }
+
+    /// Subschema of `items` which applies to all items, or to items after
+    /// [`SchemaView::prefix_items`] in draft 2020-12.
+    pub fn items(&self) -> Option<SchemaView<'s>> {
+        let s = self.schema();
+        match (&s.items, &s.items2020) {
+            (Some(Items::SchemaRef(sch)), _) | (_, Some(sch)) => Some(self.view(*sch)),
+            _ => None,
+        }
+    }
+
+    /// Subschemas of `prefixItems`, or of `items` given as array before
+    /// draft 2020-12.
+    pub fn prefix_items(&self) -> Vec<SchemaView<'s>> {
+        let s = self.schema();
+        let list = match &s.items {
+            Some(Items::SchemaRefs(list)) => list,
+            _ => &s.prefix_items,
+        };
+        list.iter().map(|&sch| self.view(sch)).collect()
+    }
+
+    /// Value of `additionalItems`, if present along with `items` array.
+    /// Never present in draft 2020-12, which uses [`SchemaView::items`]
+    /// instead.
+    pub fn additional_items(&self) -> Option<AdditionalView<'s>> {
+        self.additional(&self.schema().additional_items, "additionalItems")
+    }
+
+    /// Subschema of `not`, if present.
+    pub fn not(&self) -> Option<SchemaView<'s>> {
+        self.schema().not.map(|sch| self.view(sch))
+    }
+
+    fn additional(&self, a: &Option<Additional>, kw: &str) -> Option<AdditionalView<'s>> {
+        match a.as_ref()? {
+            Additional::Bool(value) => Some(AdditionalView::Bool {
+                value: *value,
+                location: format!("{}/{kw}", self.location()),
+            }),
+            Additional::SchemaRef(sch) => Some(AdditionalView::Schema(self.view(*sch))),
+        }
+    }
+}
+
+impl Debug for SchemaView<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SchemaView")
+            .field("index", &self.idx)
+            .field("location", &self.location())
+            .finish()
+    }
+}
+
+impl PartialEq for SchemaView<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self.schemas, other.schemas) && self.idx == other.idx
+    }
+}
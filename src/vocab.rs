@@ -0,0 +1,48 @@
+use std::{any::Any, error::Error};
+
+use serde_json::Value;
+
+/**
+Custom vocabulary, registered using
+[`Compiler::register_vocabulary`](crate::Compiler::register_vocabulary).
+
+Its keywords are compiled only in schemas, whose meta-schema lists
+the vocabulary in `$vocabulary`. Otherwise they are ignored like any
+unknown keyword.
+*/
+#[derive(Clone, Default)]
+pub struct Vocabulary {
+    /// Keywords defined by this vocabulary.
+    pub keywords: Vec<Keyword>,
+
+    /// Meta-schema `(url, document)` listing this vocabulary, if any.
+    /// It is added as resource, when vocabulary is registered.
+    pub metaschema: Option<(String, Value)>,
+}
+
+/// Defines a custom keyword of [`Vocabulary`].
+#[derive(Clone, Copy)]
+pub struct Keyword {
+    /// Name of the keyword
+    pub name: &'static str,
+
+    /**
+    Compiles keyword value found in schema.
+
+    Returned error is reported as [`CompileError::InvalidKeyword`](crate::CompileError::InvalidKeyword).
+    Note that keyword value is not compiled as subschema.
+    */
+    #[allow(clippy::type_complexity)]
+    pub compile: fn(v: &Value) -> Result<Box<dyn KeywordValidator>, Box<dyn Error>>,
+}
+
+/// Validates instance values against compiled custom [`Keyword`].
+pub trait KeywordValidator: Send + Sync {
+    /**
+    Validates `v`, using context of validation, if any.
+    see [`ValidationOptions::context`](crate::ValidationOptions::context)
+
+    Returned error is reported as [`ErrorKind::Keyword`](crate::ErrorKind::Keyword).
+    */
+    fn validate(&self, v: &Value, ctx: Option<&dyn Any>) -> Result<(), Box<dyn Error>>;
+}
@@ -0,0 +1,192 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    error::Error,
+};
+
+use boon::{Compiler, SchemaIndex, Schemas};
+use serde_json::{json, Map, Value};
+
+struct CountingAllocator;
+
+thread_local! {
+    // allocations ever made by current thread, unaffected by other tests.
+    static THREAD_ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    // bytes ever allocated by current thread, unaffected by other tests.
+    static THREAD_ALLOCATED: Cell<usize> = const { Cell::new(0) };
+    // bytes allocated by current thread, and not yet freed by it.
+    static THREAD_LIVE: Cell<isize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        _ = THREAD_ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+        _ = THREAD_ALLOCATED.try_with(|n| n.set(n.get() + layout.size()));
+        _ = THREAD_LIVE.try_with(|n| n.set(n.get() + layout.size() as isize));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        _ = THREAD_LIVE.try_with(|n| n.set(n.get() - layout.size() as isize));
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+#[test]
+fn test_property_names_allocations() -> Result<(), Box<dyn Error>> {
+    let schema = json!({
+        "propertyNames": {
+            "maxLength": 10,
+            "pattern": "^[a-z0-9]+$"
+        }
+    });
+    let instance = Value::Object(
+        (0..1000)
+            .map(|i| (format!("prop{i}"), json!(i)))
+            .collect::<Map<_, _>>(),
+    );
+
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.add_resource("schema.json", schema)?;
+    let sch_index = compiler.compile("schema.json", &mut schemas)?;
+    assert!(schemas.validate(&instance, sch_index).is_ok()); // warm up regex cache
+
+    let before = THREAD_ALLOCATIONS.with(Cell::get);
+    assert!(schemas.validate(&instance, sch_index).is_ok());
+    let allocations = THREAD_ALLOCATIONS.with(Cell::get) - before;
+    assert!(
+        allocations < 16,
+        "{allocations} allocations for 1000 property names"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_stripped_keywords_memory() -> Result<(), Box<dyn Error>> {
+    // schema with large annotations, as received from untrusted source
+    let properties = (0..100)
+        .map(|i| {
+            let sch = json!({
+                "type": "string",
+                "description": "d".repeat(10_000),
+                "$comment": "c".repeat(10_000),
+                "x-vendor": {"blob": "x".repeat(10_000)}
+            });
+            (format!("prop{i}"), sch)
+        })
+        .collect::<Map<_, _>>();
+    let schema = json!({"properties": properties}).to_string();
+
+    let retained = |strip: bool| -> Result<usize, Box<dyn Error>> {
+        let before = THREAD_LIVE.with(Cell::get);
+        let mut compiler = Compiler::new();
+        if strip {
+            compiler.set_stripped_keywords(&["$comment", "description", "x-*"]);
+        }
+        compiler.add_resource_bytes("schema.json", schema.as_bytes())?;
+        let retained = (THREAD_LIVE.with(Cell::get) - before).max(0) as usize;
+        drop(compiler);
+        Ok(retained)
+    };
+    let full = retained(false)?;
+    let stripped = retained(true)?;
+    assert!(full > 3_000_000, "{full} bytes retained without stripping");
+    assert!(
+        stripped * 10 < full,
+        "{stripped} bytes retained with stripping, {full} without"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_long_property_names_allocations() -> Result<(), Box<dyn Error>> {
+    let schema = json!({
+        "propertyNames": {"pattern": "^a"},
+        "additionalProperties": {"type": "string"},
+        "unevaluatedProperties": false
+    });
+    let instance = json!({ format!("a/{}", "x".repeat(4 << 20)): "value" });
+
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.add_resource("schema.json", schema)?;
+    let sch_index = compiler.compile("schema.json", &mut schemas)?;
+    assert!(schemas.validate(&instance, sch_index).is_ok()); // warm up regex cache
+
+    let before = THREAD_ALLOCATED.with(Cell::get);
+    assert!(schemas.validate(&instance, sch_index).is_ok());
+    let allocated = THREAD_ALLOCATED.with(Cell::get) - before;
+    assert!(
+        allocated < 64 << 10,
+        "{allocated} bytes allocated for valid instance"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_enum_interning() -> Result<(), Box<dyn Error>> {
+    // bytes retained by schemas compiled from 1000 identical subschemas
+    fn retained(subschema: Value) -> Result<(Schemas, SchemaIndex, isize), Box<dyn Error>> {
+        let before = THREAD_LIVE.with(Cell::get);
+        let mut schemas = Schemas::new();
+        let mut compiler = Compiler::new();
+        let props = (0..1000)
+            .map(|i| (format!("p{i}"), subschema.clone()))
+            .collect::<Map<_, _>>();
+        let schema = Map::from_iter([("properties".to_owned(), Value::Object(props))]);
+        compiler.add_resource("schema.json", Value::Object(schema))?;
+        compiler.compile("schema.json", &mut schemas)?;
+        let sch_index = compiler.compile("schema.json#/properties/p999", &mut schemas)?;
+        drop(compiler);
+        Ok((schemas, sch_index, THREAD_LIVE.with(Cell::get) - before))
+    }
+
+    let codes = (0..250).map(|i| format!("C{i:03}")).collect::<Vec<_>>();
+    let (_, _, plain) = retained(json!({"type": "string"}))?;
+    let (schemas, sch_index, with_enum) = retained(json!({"type": "string", "enum": codes}))?;
+    let (_, _, with_const) = retained(json!({"type": "array", "const": codes}))?;
+
+    // one copy of enum is ~10KB, so 1000 copies would be ~10MB
+    for extra in [with_enum - plain, with_const - plain] {
+        assert!(extra < 32 << 10, "{extra} bytes retained by enum/const");
+    }
+
+    // validation is unaffected
+    assert!(schemas.validate(&json!("C249"), sch_index).is_ok());
+    assert!(schemas.validate(&json!("C250"), sch_index).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_invalid_property_names_allocations() -> Result<(), Box<dyn Error>> {
+    let schema = json!({"propertyNames": {"pattern": "^[a-z]+$"}});
+    let instance = Value::Object(
+        (0..1000)
+            .map(|i| (format!("PROP{i}"), json!(i)))
+            .collect::<Map<_, _>>(),
+    );
+
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.add_resource("schema.json", schema)?;
+    let sch_index = compiler.compile("schema.json", &mut schemas)?;
+    assert!(schemas.validate(&instance, sch_index).is_err()); // warm up regex cache
+
+    let before = THREAD_ALLOCATIONS.with(Cell::get);
+    let err = schemas.validate(&instance, sch_index).unwrap_err();
+    let allocations = THREAD_ALLOCATIONS.with(Cell::get) - before;
+    assert_eq!(err.causes.len(), 1000);
+    // per name: its copy in PropertyName, and vectors of causes of the
+    // error tree, which are moved rather than copied into the result
+    assert!(
+        allocations < 3 * 1000 + 100,
+        "{allocations} allocations for 1000 invalid property names"
+    );
+    Ok(())
+}
@@ -0,0 +1,196 @@
+// boon does not collect annotations yet. these tests pin down how
+// `format`, `content*` and applicator keywords interact with the evaluated
+// properties/items that `unevaluated*` keywords depend on.
+
+use std::error::Error;
+
+use boon::{Compiler, Schemas};
+use serde_json::{json, Value};
+
+fn validate(schema: Value, instance: Value, assert: bool) -> Result<bool, Box<dyn Error>> {
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    if assert {
+        compiler.enable_format_assertions();
+        compiler.enable_content_assertions();
+    }
+    compiler.add_resource("http://tmp.com/schema.json", schema)?;
+    let sch_index = compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+    let valid = schemas.validate(&instance, sch_index).is_ok();
+    Ok(valid)
+}
+
+// leaf errors, as `instanceLocation keywordLocation`
+fn leaf_errors(schema: Value, instance: Value) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://tmp.com/schema.json", schema)?;
+    let sch_index = compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+    let Err(e) = schemas.validate(&instance, sch_index) else {
+        return Ok(vec![]);
+    };
+    let leaves = e
+        .by_instance_location()
+        .into_iter()
+        .flat_map(|(loc, leaves)| {
+            leaves
+                .into_iter()
+                .map(move |leaf| format!("{loc} {}", leaf.keyword_location))
+        })
+        .collect();
+    Ok(leaves)
+}
+
+#[test]
+fn test_format_does_not_evaluate_properties() -> Result<(), Box<dyn Error>> {
+    let schema = json!({
+        "allOf": [{ "format": "email" }],
+        "unevaluatedProperties": false
+    });
+    for assert in [false, true] {
+        assert!(validate(schema.clone(), json!({}), assert)?);
+        assert!(!validate(schema.clone(), json!({"a": 1}), assert)?);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_properties_evaluated_in_format_subschema() -> Result<(), Box<dyn Error>> {
+    let schema = json!({
+        "allOf": [{
+            "format": "email",
+            "contentEncoding": "base64",
+            "contentMediaType": "application/json",
+            "properties": { "a": true },
+            "prefixItems": [true]
+        }],
+        "unevaluatedProperties": false,
+        "unevaluatedItems": false
+    });
+    for assert in [false, true] {
+        assert!(validate(schema.clone(), json!({"a": 1}), assert)?);
+        assert!(!validate(schema.clone(), json!({"a": 1, "b": 2}), assert)?);
+        assert!(validate(schema.clone(), json!([1]), assert)?);
+        assert!(!validate(schema.clone(), json!([1, 2]), assert)?);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_format_failure_discards_evaluated() -> Result<(), Box<dyn Error>> {
+    // properties evaluated by failed subschema must not flow to parent
+    let schema = json!({
+        "anyOf": [
+            { "properties": { "a": true }, "format": "email", "type": "string" },
+            true
+        ],
+        "unevaluatedProperties": false
+    });
+    for assert in [false, true] {
+        assert!(!validate(schema.clone(), json!({"a": 1}), assert)?);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_failed_keyword_evaluates_nothing() -> Result<(), Box<dyn Error>> {
+    // only subschemas that passed, of keywords that passed, evaluate
+    let tests = [
+        (
+            json!({
+                "oneOf": [{ "properties": { "a": true } }, { "properties": { "a": true } }],
+                "unevaluatedProperties": false
+            }),
+            json!({"a": 1}),
+            vec![" /oneOf", "/a /unevaluatedProperties"],
+        ),
+        (
+            json!({
+                "allOf": [{ "properties": { "a": true } }, { "required": ["b"] }],
+                "unevaluatedProperties": false
+            }),
+            json!({"a": 1}),
+            vec![" /allOf/1/required", "/a /unevaluatedProperties"],
+        ),
+        (
+            json!({
+                "anyOf": [
+                    { "properties": { "a": true } },
+                    { "properties": { "b": true }, "required": ["c"] }
+                ],
+                "unevaluatedProperties": false
+            }),
+            json!({"a": 1, "b": 2}),
+            vec!["/b /unevaluatedProperties"],
+        ),
+        (
+            json!({
+                "not": { "properties": { "a": true } },
+                "unevaluatedProperties": false
+            }),
+            json!({"a": 1}),
+            vec![" /not", "/a /unevaluatedProperties"],
+        ),
+        (
+            json!({
+                "dependentSchemas": {
+                    "a": { "properties": { "a": true } },
+                    "b": { "required": ["c"] }
+                },
+                "unevaluatedProperties": false
+            }),
+            json!({"a": 1, "b": 2}),
+            vec![
+                " /dependentSchemas/b/required",
+                "/a /unevaluatedProperties",
+                "/b /unevaluatedProperties",
+            ],
+        ),
+        (
+            json!({
+                "contains": { "type": "string" },
+                "maxContains": 1,
+                "unevaluatedItems": false
+            }),
+            json!(["x", "y"]),
+            vec![
+                " /maxContains",
+                "/0 /unevaluatedItems",
+                "/1 /unevaluatedItems",
+            ],
+        ),
+    ];
+    for (schema, instance, want) in tests {
+        assert_eq!(leaf_errors(schema.clone(), instance)?, want, "{schema}");
+    }
+    Ok(())
+}
+
+#[test]
+fn test_passed_keyword_evaluates() -> Result<(), Box<dyn Error>> {
+    let schema = json!({
+        "oneOf": [
+            { "properties": { "a": true }, "required": ["a"] },
+            { "properties": { "b": true }, "required": ["b"] }
+        ],
+        "dependentSchemas": { "c": { "properties": { "c": true } } },
+        "unevaluatedProperties": false
+    });
+    assert!(validate(schema.clone(), json!({"a": 1, "c": 1}), false)?);
+    assert_eq!(
+        leaf_errors(schema, json!({"a": 1, "b": 2}))?,
+        [
+            " /oneOf",
+            "/a /unevaluatedProperties",
+            "/b /unevaluatedProperties"
+        ]
+    );
+
+    let schema = json!({
+        "contains": { "type": "string" },
+        "maxContains": 2,
+        "unevaluatedItems": { "type": "integer" }
+    });
+    assert!(validate(schema.clone(), json!(["x", 1, "y"]), false)?);
+    Ok(())
+}
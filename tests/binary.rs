@@ -0,0 +1,111 @@
+#![cfg(any(feature = "cbor", feature = "msgpack"))]
+
+use boon::{Compiler, InstanceError, SchemaIndex, Schemas};
+use serde_json::json;
+
+fn compile(schemas: &mut Schemas) -> SchemaIndex {
+    let schema = json!({
+        "properties": {
+            "name": {"type": "string"},
+            "data": {"contentEncoding": "base64"}
+        }
+    });
+    let mut compiler = Compiler::new();
+    compiler.enable_content_assertions();
+    compiler
+        .add_resource("http://tmp.com/schema.json", schema)
+        .unwrap();
+    compiler
+        .compile("http://tmp.com/schema.json", schemas)
+        .unwrap()
+}
+
+#[test]
+#[cfg(feature = "cbor")]
+fn test_cbor() {
+    let mut schemas = Schemas::new();
+    let sch_index = compile(&mut schemas);
+
+    // {"name": "x", "data": h'010203'}
+    let bytes = b"\xa2\x64name\x61x\x64data\x43\x01\x02\x03";
+    let v = schemas.validate_cbor(bytes, sch_index).unwrap();
+    assert_eq!(v, json!({"name": "x", "data": "AQID"}));
+
+    // {"name": 1}
+    let bytes = b"\xa1\x64name\x01";
+    let result = schemas.validate_cbor(bytes, sch_index);
+    assert!(matches!(result, Err(InstanceError::Validation(_))));
+
+    // {"name": {1: 2}}
+    let bytes = b"\xa1\x64name\xa1\x01\x02";
+    let Err(InstanceError::Unsupported {
+        instance_location, ..
+    }) = schemas.validate_cbor(bytes, sch_index)
+    else {
+        panic!("want unsupported error");
+    };
+    assert_eq!(instance_location.to_string(), "/name");
+
+    // null followed by trailing byte
+    let result = schemas.validate_cbor(b"\xf6\x00", sch_index);
+    assert!(matches!(
+        result,
+        Err(InstanceError::Decode {
+            format: "cbor",
+            offset: 1,
+            ..
+        })
+    ));
+
+    // truncated
+    let result = schemas.validate_cbor(b"\xa1\x64na", sch_index);
+    assert!(matches!(
+        result,
+        Err(InstanceError::Decode { offset: 4, .. })
+    ));
+}
+
+#[test]
+#[cfg(feature = "msgpack")]
+fn test_msgpack() {
+    let mut schemas = Schemas::new();
+    let sch_index = compile(&mut schemas);
+
+    // {"name": "x", "data": bin(010203)}
+    let bytes = b"\x82\xa4name\xa1x\xa4data\xc4\x03\x01\x02\x03";
+    let v = schemas.validate_msgpack(bytes, sch_index).unwrap();
+    assert_eq!(v, json!({"name": "x", "data": "AQID"}));
+
+    // {"name": 1}
+    let bytes = b"\x81\xa4name\x01";
+    let result = schemas.validate_msgpack(bytes, sch_index);
+    assert!(matches!(result, Err(InstanceError::Validation(_))));
+
+    // {"name": [{1: 2}]}
+    let bytes = b"\x81\xa4name\x91\x81\x01\x02";
+    let Err(InstanceError::Unsupported {
+        instance_location, ..
+    }) = schemas.validate_msgpack(bytes, sch_index)
+    else {
+        panic!("want unsupported error");
+    };
+    assert_eq!(instance_location.to_string(), "/name/0");
+
+    // nil followed by trailing byte
+    let result = schemas.validate_msgpack(b"\xc0\x00", sch_index);
+    assert!(matches!(
+        result,
+        Err(InstanceError::Decode {
+            format: "msgpack",
+            offset: 1,
+            ..
+        })
+    ));
+
+    // truncated
+    let result = schemas.validate_msgpack(b"\x81\xa4na", sch_index);
+    assert!(matches!(
+        result,
+        Err(InstanceError::Decode { offset: 4, .. })
+    ));
+}
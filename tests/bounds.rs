@@ -0,0 +1,45 @@
+#![cfg(feature = "arbitrary_precision")]
+
+use std::error::Error;
+
+use boon::{Compiler, Schemas};
+use serde_json::Value;
+
+const LONG_INT: &str = "123456789012345678901234567890123456789012345678901234567890";
+
+#[test]
+fn test_non_finite_bounds() -> Result<(), Box<dyn Error>> {
+    let tests = [
+        // (schema, instance, valid)
+        (r#"{"maximum": 1e309}"#, "1e308", true),
+        (r#"{"maximum": 1e309}"#, "1e310", false),
+        (r#"{"maximum": 1e309}"#, "10e308", true),
+        (r#"{"exclusiveMaximum": 1e309}"#, "10e308", false),
+        (r#"{"minimum": -1e309}"#, "-1e308", true),
+        (r#"{"minimum": -1e309}"#, "-1e310", false),
+        (r#"{"exclusiveMinimum": -1e309}"#, "-1e309", false),
+        (r#"{"maximum": 1}"#, "1e309", false),
+        (r#"{"minimum": 1}"#, "-1e309", false),
+        (r#"{"maximum": 1}"#, "-1e309", true),
+        (&format!(r#"{{"maximum": {LONG_INT}e300}}"#), "1e358", true),
+        (&format!(r#"{{"maximum": {LONG_INT}e300}}"#), "1e360", false),
+        (r#"{"maximum": 1e309}"#, &format!("{LONG_INT}e249"), true),
+        (r#"{"maximum": 1e309}"#, &format!("{LONG_INT}e250"), false),
+    ];
+    for (schema, instance, valid) in tests {
+        let schema: Value = serde_json::from_str(schema)?;
+        let instance: Value = serde_json::from_str(instance)?;
+        let mut schemas = Schemas::new();
+        let mut compiler = Compiler::new();
+        compiler.add_resource("http://tmp.com/schema.json", schema.clone())?;
+        let (sch_index, diagnostics) =
+            compiler.compile_with_diagnostics("http://tmp.com/schema.json", &mut schemas)?;
+        let result = schemas.validate(&instance, sch_index);
+        assert_eq!(result.is_ok(), valid, "{schema} {instance}");
+
+        let non_finite = diagnostics.iter().any(|d| d.code == "non-finite-bound");
+        let want = schema.to_string().contains("e309") || schema.to_string().contains("e300");
+        assert_eq!(non_finite, want, "{schema}");
+    }
+    Ok(())
+}
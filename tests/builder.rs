@@ -0,0 +1,198 @@
+use std::error::Error;
+
+use boon::{Compiler, Draft, SchemaBuilder, Schemas, UnsupportedKeyword};
+use serde_json::{json, Value};
+
+fn compile(schema: Value) -> Result<(Schemas, boon::SchemaIndex), Box<dyn Error>> {
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://tmp.com/schema.json", schema)?;
+    let sch_index = compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+    Ok((schemas, sch_index))
+}
+
+#[test]
+fn test_build_object() -> Result<(), Box<dyn Error>> {
+    let builder = SchemaBuilder::object()
+        .title("person")
+        .property("name", SchemaBuilder::string().min_length(1).max_length(10))
+        .property("age", SchemaBuilder::integer().minimum(0).nullable())
+        .property(
+            "tags",
+            SchemaBuilder::array().items(SchemaBuilder::string()),
+        )
+        .pattern_property("^x-", true)
+        .property_names(SchemaBuilder::string().pattern("^[a-z-]+$"))
+        .required(["name"])
+        .required(["name", "age"])
+        .additional_properties(false);
+    assert_eq!(
+        builder.build(),
+        json!({
+            "type": "object",
+            "title": "person",
+            "properties": {
+                "name": { "type": "string", "minLength": 1, "maxLength": 10 },
+                "age": { "type": ["integer", "null"], "minimum": 0 },
+                "tags": { "type": "array", "items": { "type": "string" } }
+            },
+            "patternProperties": { "^x-": true },
+            "propertyNames": { "type": "string", "pattern": "^[a-z-]+$" },
+            "required": ["name", "age"],
+            "additionalProperties": false
+        })
+    );
+
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.set_default_draft(Draft::V4);
+    let sch = builder.compile("http://tmp.com/person.json", &mut compiler, &mut schemas)?;
+    let tests = [
+        (json!({"name": "a", "age": null}), true),
+        (
+            json!({"name": "a", "age": 1, "tags": ["x"], "x-y": 1}),
+            true,
+        ),
+        (json!({"name": "a"}), false),
+        (json!({"name": "", "age": 1}), false),
+        (json!({"name": "a", "age": -1}), false),
+        (json!({"name": "a", "age": 1, "other": 1}), false),
+        (json!({"name": "a", "age": 1, "tags": [1]}), false),
+    ];
+    for (v, valid) in tests {
+        assert_eq!(schemas.validate(&v, sch).is_ok(), valid, "{v}");
+    }
+    Ok(())
+}
+
+#[test]
+fn test_build_applicators() -> Result<(), Box<dyn Error>> {
+    let builder = SchemaBuilder::any()
+        .def("pos", SchemaBuilder::integer().exclusive_minimum(0))
+        .one_of([
+            SchemaBuilder::reference("#/$defs/pos"),
+            SchemaBuilder::string().format("email").into(),
+        ])
+        .not(SchemaBuilder::any().const_(7))
+        .if_(SchemaBuilder::integer())
+        .then(SchemaBuilder::integer().multiple_of(2))
+        .else_(SchemaBuilder::any().enum_(["a@b.com", "c@d.com"]));
+    let (schemas, sch) = compile(builder.build())?;
+    let tests = [
+        (json!(2), true),
+        (json!("a@b.com"), true),
+        (json!(3), false),
+        (json!(-2), false),
+        (json!("x@y.com"), false),
+        (json!(null), false),
+    ];
+    for (v, valid) in tests {
+        assert_eq!(schemas.validate(&v, sch).is_ok(), valid, "{v}");
+    }
+    Ok(())
+}
+
+#[test]
+fn test_build_for_drafts() -> Result<(), Box<dyn Error>> {
+    let builder = SchemaBuilder::array()
+        .prefix_items([
+            SchemaBuilder::from(SchemaBuilder::string()),
+            SchemaBuilder::number().into(),
+        ])
+        .items(false)
+        .min_items(1);
+    assert_eq!(
+        builder.build_for(Draft::V7)?,
+        json!({
+            "$schema": "http://json-schema.org/draft-07/schema",
+            "type": "array",
+            "items": [{ "type": "string" }, { "type": "number" }],
+            "additionalItems": false,
+            "minItems": 1
+        })
+    );
+    assert_eq!(
+        builder.build_for(Draft::V2020_12)?,
+        json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "type": "array",
+            "prefixItems": [{ "type": "string" }, { "type": "number" }],
+            "items": false,
+            "minItems": 1
+        })
+    );
+
+    let builder = SchemaBuilder::object()
+        .def("pos", SchemaBuilder::number().exclusive_minimum(0))
+        .property("a", SchemaBuilder::reference("#/$defs/pos").title("a"))
+        .dependent_required("a", ["b"])
+        .dependent_schema("b", SchemaBuilder::object().required(["c"]));
+    assert_eq!(
+        builder.build_for(Draft::V4)?,
+        json!({
+            "$schema": "http://json-schema.org/draft-04/schema",
+            "type": "object",
+            "definitions": {
+                "pos": { "type": "number", "minimum": 0, "exclusiveMinimum": true }
+            },
+            "properties": {
+                "a": { "title": "a", "allOf": [{ "$ref": "#/definitions/pos" }] }
+            },
+            "dependencies": {
+                "a": ["b"],
+                "b": { "type": "object", "required": ["c"] }
+            }
+        })
+    );
+
+    // each draft gives same results
+    let tests = [
+        (json!({"a": 1, "b": 1, "c": 1}), true),
+        (json!({"a": 0, "b": 1, "c": 1}), false),
+        (json!({"a": 1}), false),
+        (json!({"b": 1}), false),
+        (json!({}), true),
+    ];
+    for draft in [
+        Draft::V4,
+        Draft::V6,
+        Draft::V7,
+        Draft::V2019_09,
+        Draft::V2020_12,
+    ] {
+        let (schemas, sch) = compile(builder.build_for(draft)?)?;
+        for (v, valid) in &tests {
+            let got = schemas.validate(v, sch).is_ok();
+            assert_eq!(got, *valid, "{draft:?} {v}");
+        }
+    }
+
+    // const in draft-04
+    let builder = SchemaBuilder::any().const_("x");
+    assert_eq!(builder.build_for(Draft::V4)?["enum"], json!(["x"]));
+    Ok(())
+}
+
+#[test]
+fn test_build_unsupported() {
+    let builder = SchemaBuilder::object()
+        .property("a", SchemaBuilder::object().unevaluated_properties(false));
+    let err = builder.build_for(Draft::V7).unwrap_err();
+    assert_eq!(
+        err,
+        UnsupportedKeyword {
+            keyword: "unevaluatedProperties",
+            draft: Draft::V7
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "keyword unevaluatedProperties is not supported in http://json-schema.org/draft-07/schema"
+    );
+    assert!(builder.build_for(Draft::V2019_09).is_ok());
+
+    // no equivalent, if both given
+    let builder = SchemaBuilder::number().minimum(1).exclusive_minimum(0);
+    assert!(builder.build_for(Draft::V4).is_err());
+    assert!(builder.build_for(Draft::V6).is_ok());
+}
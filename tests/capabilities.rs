@@ -0,0 +1,158 @@
+use std::error::Error;
+
+use boon::{capabilities, Compiler, Decoder, Draft, Format, Keyword, Vocabulary};
+use serde_json::json;
+
+fn has(list: &[String], item: &str) -> bool {
+    list.iter().any(|s| s == item)
+}
+
+// names in `[features]` of Cargo.toml, other than `default`
+fn declared_features() -> Vec<&'static str> {
+    let manifest = include_str!("../Cargo.toml");
+    let mut lines = manifest
+        .lines()
+        .skip_while(|line| line.trim() != "[features]");
+    lines.next();
+    let mut names = lines
+        .map(str::trim)
+        .take_while(|line| !line.starts_with('['))
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| Some(line.split_once('=')?.0.trim()))
+        .filter(|name| *name != "default")
+        .collect::<Vec<_>>();
+    names.sort();
+    names
+}
+
+#[test]
+fn test_features() {
+    let caps = capabilities();
+    let features = [
+        ("url", cfg!(feature = "url")),
+        ("formats-net", cfg!(feature = "formats-net")),
+        ("formats-time", cfg!(feature = "formats-time")),
+        ("regex-unicode", cfg!(feature = "regex-unicode")),
+        ("json5", cfg!(feature = "json5")),
+        ("cbor", cfg!(feature = "cbor")),
+        ("msgpack", cfg!(feature = "msgpack")),
+        ("brotli", cfg!(feature = "brotli")),
+        ("yaml", cfg!(feature = "yaml")),
+        ("arbitrary_precision", cfg!(feature = "arbitrary_precision")),
+        ("fancy-errors", cfg!(feature = "fancy-errors")),
+        ("codegen", cfg!(feature = "codegen")),
+        ("parallel", cfg!(feature = "parallel")),
+        ("difftest", cfg!(feature = "difftest")),
+        ("ahash", cfg!(feature = "ahash")),
+        ("serde", cfg!(feature = "serde")),
+    ];
+    let mut names = features.iter().map(|f| f.0).collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(names, declared_features(), "features must match Cargo.toml");
+    for (feature, enabled) in features {
+        assert_eq!(has(&caps.features, feature), enabled, "{feature}");
+    }
+    assert_eq!(caps.features.len(), features.iter().filter(|f| f.1).count());
+
+    // fields toggled by features
+    let fields = [
+        (has(&caps.formats, "email"), cfg!(feature = "formats-net")),
+        (
+            has(&caps.formats, "idn-hostname"),
+            cfg!(feature = "formats-net"),
+        ),
+        (
+            has(&caps.formats, "date-time"),
+            cfg!(feature = "formats-time"),
+        ),
+        (caps.regex_unicode, cfg!(feature = "regex-unicode")),
+        (
+            has(&caps.resource_encodings, "br"),
+            cfg!(feature = "brotli"),
+        ),
+        (
+            has(&caps.resource_media_types, "application/yaml"),
+            cfg!(feature = "yaml"),
+        ),
+        (
+            has(&caps.resource_media_types, "application/json5"),
+            cfg!(feature = "json5"),
+        ),
+        (has(&caps.instance_formats, "cbor"), cfg!(feature = "cbor")),
+        (
+            has(&caps.instance_formats, "msgpack"),
+            cfg!(feature = "msgpack"),
+        ),
+    ];
+    for (i, (got, want)) in fields.into_iter().enumerate() {
+        assert_eq!(got, want, "field #{i}");
+    }
+}
+
+#[test]
+fn test_builtin() {
+    let caps = capabilities();
+    assert_eq!(caps.drafts.first(), Some(&Draft::V4));
+    assert_eq!(caps.drafts.last(), Some(&Draft::default()));
+    assert!(has(&caps.formats, "ipv4"));
+    assert!(!has(&caps.formats, "unknown"));
+    assert_eq!(caps.content_encodings, ["base64"]);
+    assert_eq!(caps.content_media_types, ["application/json"]);
+    assert!(has(
+        &caps.vocabularies,
+        "https://json-schema.org/draft/2020-12/vocab/unevaluated"
+    ));
+    assert!(caps.keywords.is_empty());
+    assert_eq!(caps.extensions, ["x-time-range"]);
+    assert!(caps.enabled_extensions.is_empty());
+    let mut sorted = caps.formats.clone();
+    sorted.sort();
+    assert_eq!(caps.formats, sorted);
+}
+
+#[test]
+fn test_compiler() -> Result<(), Box<dyn Error>> {
+    let mut compiler = Compiler::new();
+    assert_eq!(compiler.capabilities(), capabilities());
+
+    compiler.register_format(Format {
+        name: "ipv4",
+        func: |_| Ok(()),
+    });
+    compiler.register_format(Format {
+        name: "even",
+        func: |_| Ok(()),
+    });
+    compiler.register_content_encoding(Decoder {
+        name: "hex",
+        func: |_| Ok(vec![]),
+    });
+    compiler.register_vocabulary(
+        "http://a.com/vocab/words",
+        Vocabulary {
+            keywords: vec![Keyword {
+                name: "maxWords",
+                compile: |_| Err("unused".into()),
+            }],
+            metaschema: None,
+        },
+    )?;
+    compiler.enable_extension("x-time-range");
+    let caps = compiler.capabilities();
+    assert_eq!(caps.formats.iter().filter(|f| *f == "ipv4").count(), 1);
+    assert!(has(&caps.formats, "even"));
+    assert_eq!(caps.content_encodings, ["base64", "hex"]);
+    assert!(has(&caps.vocabularies, "http://a.com/vocab/words"));
+    assert_eq!(caps.keywords, ["maxWords"]);
+    assert_eq!(caps.extensions, ["x-time-range"]);
+    assert_eq!(caps.enabled_extensions, ["x-time-range"]);
+
+    let json = serde_json::to_value(&caps)?;
+    assert_eq!(json["keywords"], json!(["maxWords"]));
+    assert_eq!(
+        json["drafts"][4],
+        json!("https://json-schema.org/draft/2020-12/schema")
+    );
+    assert_eq!(json.as_object().map(|obj| obj.len()), Some(13));
+    Ok(())
+}
@@ -0,0 +1,90 @@
+#![cfg(feature = "codegen")]
+
+use std::{error::Error, fs, path::PathBuf};
+
+use boon::{codegen, Compiler, Schemas};
+use serde_json::json;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("boon-codegen-{name}-{}", std::process::id()));
+    fs::create_dir_all(dir.join("defs")).unwrap();
+    dir
+}
+
+#[test]
+fn test_embed_round_trip() -> Result<(), Box<dyn Error>> {
+    let dir = temp_dir("round-trip");
+    let person = dir.join("person.json");
+    fs::write(
+        &person,
+        json!({
+            "properties": {
+                "name": {"$ref": "defs/common.json#/$defs/name"},
+                "email": {"type": "string", "format": "email"},
+                "photo": {"contentEncoding": "base64"}
+            },
+            "required": ["name"]
+        })
+        .to_string(),
+    )?;
+    fs::write(
+        dir.join("defs/common.json"),
+        json!({"$defs": {"name": {"type": "string", "minLength": 1}}}).to_string(),
+    )?;
+    let paths = [
+        person.to_string_lossy().into_owned(),
+        format!("{}#/$defs/name", dir.join("defs/common.json").display()),
+    ];
+
+    // runtime compiled
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    let want = paths
+        .iter()
+        .map(|p| compiler.compile(p, &mut schemas))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let embedded = codegen::Embedded::compile(&paths)?;
+    assert_eq!(embedded.docs.len(), 2);
+    let out = dir.join("schemas.rs");
+    codegen::embed(&paths, &out)?;
+    let src = fs::read_to_string(&out)?;
+    assert!(src.contains("pub fn schemas()"), "{src}");
+    assert!(src.contains("defs/common.json"), "{src}");
+
+    // documents are not needed anymore
+    fs::remove_dir_all(&dir)?;
+    let (embedded_schemas, index) = embedded.load();
+
+    let instances = [
+        json!({"name": "x", "email": "a@b.com"}),
+        json!({"name": "", "email": "a@b.com"}),
+        json!({"email": "not email"}),
+        json!({"name": "x", "photo": "!!"}),
+        json!("x"),
+        json!(""),
+    ];
+    for (path, sch) in paths.iter().zip(want) {
+        let got = index[path];
+        for v in &instances {
+            let want = schemas.validate(v, sch).map_err(|e| format!("{e:#}"));
+            let got = embedded_schemas
+                .validate(v, got)
+                .map_err(|e| format!("{e:#}"));
+            assert_eq!(got, want, "{path}: {v}");
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_embed_unknown_format() -> Result<(), Box<dyn Error>> {
+    let dir = temp_dir("unknown-format");
+    let schema = dir.join("schema.json");
+    fs::write(&schema, json!({"format": "customer-id"}).to_string())?;
+    let result = codegen::embed(&[&schema], dir.join("schemas.rs"));
+    fs::remove_dir_all(&dir)?;
+    let err = result.unwrap_err().to_string();
+    assert!(err.contains("format 'customer-id' is unknown"), "{err}");
+    Ok(())
+}
@@ -1,7 +1,14 @@
 use std::error::Error;
 
-use boon::{Compiler, Schemas};
-use serde_json::json;
+#[cfg(feature = "formats-time")]
+use boon::DateTimePolicy;
+#[cfg(feature = "formats-net")]
+use boon::Format;
+use boon::{
+    CompileError, Compiler, Draft, Origin, RegexLimits, Schemas, Severity, UrlLoader,
+    ValidationOptions,
+};
+use serde_json::{json, Value};
 
 #[test]
 fn test_metaschema_resource() -> Result<(), Box<dyn Error>> {
@@ -85,3 +92,1702 @@ fn test_compile_nonstd() -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+#[test]
+#[cfg(feature = "formats-net")]
+fn test_diagnostics() -> Result<(), Box<dyn Error>> {
+    let schema = json!({
+        "$schema": "http://json-schema.org/draft-07/schema",
+        "properties": {
+            "a": { "format": "palindrome" },
+            "b": { "$ref": "#/definitions/c", "type": "string", "title": "b" }
+        },
+        "definitions": {
+            "c": { "format": "email" }
+        }
+    });
+
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://tmp.com/schema.json", schema)?;
+    let (_, diagnostics) =
+        compiler.compile_with_diagnostics("http://tmp.com/schema.json", &mut schemas)?;
+    let got = diagnostics
+        .iter()
+        .map(|d| (d.severity, d.code, d.schema_location.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        got,
+        [
+            (
+                Severity::Warning,
+                "unknown-format",
+                "http://tmp.com/schema.json#/properties/a/format"
+            ),
+            (
+                Severity::Warning,
+                "ref-siblings-ignored",
+                "http://tmp.com/schema.json#/properties/b/$ref"
+            ),
+        ]
+    );
+    assert!(compiler.take_diagnostics().is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_diagnostics_deny() -> Result<(), Box<dyn Error>> {
+    let schema = json!({
+        "$schema": "http://json-schema.org/draft-07/schema",
+        "properties": {
+            "a": { "format": "palindrome" },
+            "b": { "$ref": "#/definitions/c", "type": "string" }
+        },
+        "definitions": {
+            "c": { "format": "email" }
+        }
+    });
+
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.deny(&["ref-siblings-ignored"]);
+    compiler.add_resource("http://tmp.com/schema.json", schema)?;
+    let Err(CompileError::Diagnostics(denied)) =
+        compiler.compile("http://tmp.com/schema.json", &mut schemas)
+    else {
+        panic!("want CompileError::Diagnostics");
+    };
+    assert_eq!(denied.len(), 1);
+    assert_eq!(denied[0].severity, Severity::Error);
+    assert_eq!(denied[0].code, "ref-siblings-ignored");
+    assert_eq!(schemas.size(), 0);
+
+    Ok(())
+}
+
+#[test]
+fn test_empty_enum() -> Result<(), Box<dyn Error>> {
+    let schema = json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "properties": {
+            "a": { "enum": [] }
+        }
+    });
+
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://tmp.com/schema.json", schema.clone())?;
+    let (sch, diagnostics) =
+        compiler.compile_with_diagnostics("http://tmp.com/schema.json", &mut schemas)?;
+    let got = diagnostics
+        .iter()
+        .map(|d| (d.code, d.schema_location.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        got,
+        [(
+            "empty-enum",
+            "http://tmp.com/schema.json#/properties/a/enum"
+        )]
+    );
+    assert!(schemas.validate(&json!({"a": 1}), sch).is_err());
+
+    // fails when denied
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.deny(&["empty-enum"]);
+    compiler.add_resource("http://tmp.com/schema.json", schema)?;
+    let result = compiler.compile("http://tmp.com/schema.json", &mut schemas);
+    assert!(
+        matches!(&result, Err(CompileError::Diagnostics(d)) if d[0].code == "empty-enum"),
+        "{result:?}"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_allow_non_schemas() -> Result<(), Box<dyn Error>> {
+    let schema = json!({
+        "title": "person",
+        "$ref": "#/title"
+    });
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://tmp.com/schema.json", schema)?;
+    let result = compiler.compile("http://tmp.com/schema.json", &mut schemas);
+    assert!(
+        matches!(result, Err(CompileError::NotASchema { .. })),
+        "{result:?}"
+    );
+
+    compiler.allow_non_schemas();
+    let sch_index = compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+    assert!(schemas.validate(&json!(1), sch_index).is_ok());
+    Ok(())
+}
+
+#[test]
+fn test_draft4_float_integers() -> Result<(), Box<dyn Error>> {
+    let schema = json!({
+        "$schema": "http://json-schema.org/draft-04/schema#",
+        "type": "integer"
+    });
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://tmp.com/strict.json", schema.clone())?;
+    let sch = compiler.compile("http://tmp.com/strict.json", &mut schemas)?;
+    assert!(schemas.validate(&json!(1.0), sch).is_err());
+    assert!(schemas.validate(&json!(1), sch).is_ok());
+
+    let opts = ValidationOptions::new().draft4_float_integers(true);
+    assert!(schemas.validate_with(&json!(1.0), sch, &opts).is_ok());
+    Ok(())
+}
+
+#[test]
+fn test_resolve_ref() -> Result<(), Box<dyn Error>> {
+    let schema = json!({
+        "$id": "http://a.com/root.json",
+        "$defs": {
+            "B": {
+                "$id": "b/",
+                "$defs": {
+                    "C": { "$id": "c.json" },
+                    "D": { "$anchor": "foo" }
+                }
+            },
+            "E": { "$id": "urn:uuid:deadbeef-1234-ffff-ffff-4321feebdaed" }
+        }
+    });
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://a.com/root.json", schema)?;
+    compiler.add_resource("http://remote.com/x.json", json!({"$anchor": "bar"}))?;
+
+    let tests = [
+        ("", "#/$defs/B", "http://a.com/root.json#/$defs/B"),
+        (
+            "/$defs/B",
+            "c.json",
+            "http://a.com/root.json#/$defs/B/$defs/C",
+        ),
+        (
+            "/$defs/B/$defs/D",
+            "#foo",
+            "http://a.com/root.json#/$defs/B/$defs/D",
+        ),
+        (
+            "/$defs/B",
+            "#foo",
+            "http://a.com/root.json#/$defs/B/$defs/D",
+        ),
+        ("", "b/c.json", "http://a.com/root.json#/$defs/B/$defs/C"),
+        (
+            "",
+            "urn:uuid:deadbeef-1234-ffff-ffff-4321feebdaed",
+            "http://a.com/root.json#/$defs/E",
+        ),
+        (
+            "/$defs/B",
+            "http://remote.com/x.json#bar",
+            "http://remote.com/x.json#",
+        ),
+    ];
+    for (ptr, ref_, want) in tests {
+        let got = compiler.resolve("http://a.com/root.json", ptr, ref_)?;
+        assert_eq!(got, want, "resolving {ref_:?} at {ptr:?}");
+    }
+
+    // anchor foo is not in resource c.json
+    let result = compiler.resolve("http://a.com/root.json", "/$defs/B/$defs/C", "#foo");
+    assert!(matches!(result, Err(CompileError::AnchorNotFound { .. })));
+
+    // Schemas::resolve_ref gives absolute url, which resolves to same location
+    let mut schemas = Schemas::new();
+    for (ptr, ref_, want) in tests {
+        let sch_index = compiler.compile(&format!("http://a.com/root.json#{ptr}"), &mut schemas)?;
+        let url = schemas.resolve_ref(sch_index, ref_)?;
+        let got = compiler.resolve("http://a.com/root.json", "", &url)?;
+        assert_eq!(got, want, "resolving {url:?} of {ref_:?} at {ptr:?}");
+    }
+
+    let sch_index = compiler.compile("http://a.com/root.json#/$defs/B/$defs/D", &mut schemas)?;
+    assert_eq!(
+        schemas.resolve_ref(sch_index, "c.json#/x")?,
+        "http://a.com/b/c.json#/x"
+    );
+    let sch_index = compiler.compile("http://a.com/root.json", &mut schemas)?;
+    assert_eq!(
+        schemas.resolve_ref(sch_index, "b/c.json")?,
+        "http://a.com/b/c.json#"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_compile_json_pointer() -> Result<(), Box<dyn Error>> {
+    let schema = json!({
+        "$defs": {
+            "a/b": { "type": "string" },
+            "m~n": { "type": "number" },
+            "foo bar": { "type": "boolean" },
+            "café": { "type": "null" }
+        }
+    });
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://tmp.com/schema.json", schema)?;
+
+    let same = [
+        ("#/$defs/a~1b", "#/$defs/a%7E1b"),
+        ("#/$defs/m~0n", "#/%24defs/m%7e0n"),
+        ("#/$defs/foo bar", "#/$defs/foo%20bar"),
+        ("#/$defs/café", "#/$defs/caf%C3%A9"),
+    ];
+    for (raw, encoded) in same {
+        let raw = compiler.compile(&format!("http://tmp.com/schema.json{raw}"), &mut schemas)?;
+        let encoded = compiler.compile(
+            &format!("http://tmp.com/schema.json{encoded}"),
+            &mut schemas,
+        )?;
+        assert_eq!(raw, encoded, "{encoded:?}");
+    }
+
+    let invalid = [
+        ("#/$defs/a~2b", 8),
+        ("#/$defs/m~", 8),
+        ("#/$defs/m%7E", 8),
+        ("#/$defs/foo%2", 10),
+        ("#/$defs/foo%zzbar", 10),
+        ("#/$defs/foo\tbar", 10),
+        ("#/$defs/caf%C3", 10),
+    ];
+    for (frag, want) in invalid {
+        let loc = format!("http://tmp.com/schema.json{frag}");
+        let result = compiler.compile(&loc, &mut schemas);
+        let Err(CompileError::InvalidJsonPointer { position, .. }) = result else {
+            panic!("{frag:?}: want InvalidJsonPointer, got {result:?}");
+        };
+        assert_eq!(position, want, "{frag:?}");
+    }
+
+    // empty token is valid json-pointer
+    let result = compiler.compile("http://tmp.com/schema.json#/$defs//a", &mut schemas);
+    assert!(
+        matches!(result, Err(CompileError::JsonPointerNotFound { .. })),
+        "{result:?}"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_regex_limits() -> Result<(), Box<dyn Error>> {
+    let tests = [
+        json!({"pattern": "^[^a]{100}$"}),
+        json!({"patternProperties": {"^[^a]{100}$": true}}),
+    ];
+    for schema in tests {
+        let mut schemas = Schemas::new();
+        let mut compiler = Compiler::new();
+        compiler.add_resource("http://tmp.com/schema.json", schema.clone())?;
+        compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+
+        for limits in [
+            RegexLimits {
+                size_limit: 10_000,
+                ..Default::default()
+            },
+            RegexLimits {
+                max_pattern_len: Some(8),
+                ..Default::default()
+            },
+        ] {
+            let mut schemas = Schemas::new();
+            let mut compiler = Compiler::new();
+            compiler.set_regex_limits(limits);
+            compiler.add_resource("http://tmp.com/schema.json", schema.clone())?;
+            let result = compiler.compile("http://tmp.com/schema.json", &mut schemas);
+            assert!(
+                matches!(result, Err(CompileError::InvalidRegex { .. })),
+                "{schema} {limits:?}: {result:?}"
+            );
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_boolean_roots() -> Result<(), Box<dyn Error>> {
+    for b in [true, false] {
+        let mut schemas = Schemas::new();
+        let mut compiler = Compiler::new();
+        compiler.add_resource("http://tmp.com/bool.json", json!(b))?;
+
+        // top-level boolean root
+        for loc in ["http://tmp.com/bool.json", "http://tmp.com/bool.json#"] {
+            let sch_index = compiler.compile(loc, &mut schemas)?;
+            assert_eq!(schemas.validate(&json!(1), sch_index).is_ok(), b, "{loc}");
+        }
+        let result = compiler.compile("http://tmp.com/bool.json#/x", &mut schemas);
+        assert!(
+            matches!(result, Err(CompileError::JsonPointerNotFound { .. })),
+            "{result:?}"
+        );
+
+        // $ref into boolean root from another document, with different draft
+        let schema = json!({
+            "$schema": "http://json-schema.org/draft-04/schema#",
+            "properties": { "x": { "$ref": "bool.json" } }
+        });
+        compiler.add_resource("http://tmp.com/ref.json", schema)?;
+        let sch_index = compiler.compile("http://tmp.com/ref.json", &mut schemas)?;
+        assert_eq!(schemas.validate(&json!({"x": 1}), sch_index).is_ok(), b);
+        assert!(schemas.validate(&json!({"y": 1}), sch_index).is_ok());
+
+        // boolean root has no $schema, so default draft applies
+        let mut compiler = Compiler::new();
+        compiler.set_default_draft(Draft::V7);
+        compiler.add_resource("http://tmp.com/bool.json", json!(b))?;
+        let sch_index = compiler.compile("http://tmp.com/bool.json", &mut schemas)?;
+        assert_eq!(schemas.validate(&json!(1), sch_index).is_ok(), b);
+
+        let mut compiler = Compiler::new();
+        compiler.set_default_draft(Draft::V4);
+        compiler.add_resource("http://tmp.com/bool4.json", json!(b))?;
+        let result = compiler.compile("http://tmp.com/bool4.json", &mut schemas);
+        assert!(
+            matches!(result, Err(CompileError::ValidationError { .. })),
+            "draft-04 has no boolean schemas: {result:?}"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn test_disable_keyword() -> Result<(), Box<dyn Error>> {
+    let tests = [
+        ("minLength", json!({"minLength": 3}), json!("ab")),
+        (
+            "unevaluatedProperties",
+            json!({"properties": {"a": true}, "unevaluatedProperties": false}),
+            json!({"a": 1, "b": 2}),
+        ),
+        (
+            "items",
+            json!({"items": {"type": "string"}}),
+            json!(["a", 1]),
+        ),
+    ];
+    for (kw, schema, instance) in tests {
+        for disable in [false, true] {
+            let mut schemas = Schemas::new();
+            let mut compiler = Compiler::new();
+            if disable {
+                compiler.disable_keyword(kw);
+            }
+            compiler.add_resource("http://a.com/schema.json", schema.clone())?;
+            let sch = compiler.compile("http://a.com/schema.json", &mut schemas)?;
+            let result = schemas.validate(&instance, sch);
+            assert_eq!(result.is_ok(), disable, "{kw} disabled={disable}");
+        }
+    }
+
+    // structural keywords cannot be disabled
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.disable_keyword("$ref");
+    compiler.add_resource(
+        "http://a.com/schema.json",
+        json!({"$ref": "#/$defs/s", "$defs": {"s": {"type": "string"}}}),
+    )?;
+    let sch = compiler.compile("http://a.com/schema.json", &mut schemas)?;
+    assert!(schemas.validate(&json!(1), sch).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_deterministic_indexes() -> Result<(), Box<dyn Error>> {
+    let mut defs = serde_json::Map::new();
+    for i in 0..20 {
+        defs.insert(
+            format!("d{i}"),
+            json!({ "$dynamicAnchor": format!("a{i}"), "type": "string" }),
+        );
+    }
+    let schema = json!({
+        "$id": "http://a.com/schema.json",
+        "properties": {
+            "x": { "$dynamicRef": "#a3" },
+            "y": { "$id": "y.json", "items": { "$ref": "schema.json#/$defs/d7" } }
+        },
+        "$defs": defs
+    });
+    let locs = (0..20)
+        .map(|i| format!("http://a.com/schema.json#/$defs/d{i}"))
+        .chain(["http://a.com/schema.json#/properties/y".to_owned()])
+        .collect::<Vec<_>>();
+
+    let mut want = None;
+    for _ in 0..50 {
+        let mut schemas = Schemas::new();
+        let mut compiler = Compiler::new();
+        compiler.add_resource("http://a.com/schema.json", schema.clone())?;
+        compiler.compile("http://a.com/schema.json", &mut schemas)?;
+        let mut got = vec![];
+        for loc in &locs {
+            got.push(compiler.compile(loc, &mut schemas)?);
+        }
+        assert_eq!(
+            schemas.size(),
+            want.as_ref().map_or(schemas.size(), |(n, _)| *n)
+        );
+        match &want {
+            None => want = Some((schemas.size(), got)),
+            Some((_, want)) => assert_eq!(&got, want),
+        }
+    }
+    Ok(())
+}
+
+// same output with and without `ahash` feature
+#[test]
+fn test_output_independent_of_hasher() -> Result<(), Box<dyn Error>> {
+    let props = (0..20)
+        .map(|i| (format!("p{i}"), json!({ "enum": [i, {"k": i}, [i]] })))
+        .collect::<serde_json::Map<_, _>>();
+    let schema = json!({
+        "properties": props,
+        "patternProperties": { "^x": { "uniqueItems": true } },
+        "additionalProperties": { "const": { "a": 1, "b": [1, 2] } }
+    });
+    let instance = json!({
+        "p3": {"k": 4},
+        "p11": [11],
+        "p17": "17",
+        "x1": [{"a": 1, "b": 2}, [1], {"b": 2, "a": 1.0}],
+        "y": { "b": [1, 2], "a": 1.0 },
+        "z": { "a": 1 }
+    });
+    let mut want = None;
+    for _ in 0..5 {
+        let mut schemas = Schemas::new();
+        let mut compiler = Compiler::new();
+        compiler.add_resource("http://a.com/schema.json", schema.clone())?;
+        let sch = compiler.compile("http://a.com/schema.json", &mut schemas)?;
+        let opts = ValidationOptions::new().hash_unique_items(true);
+        let Err(e) = schemas.validate_with(&instance, sch, &opts) else {
+            panic!("validation must fail");
+        };
+        let got = serde_json::to_string_pretty(&e.basic_output())?;
+        match &want {
+            None => want = Some(got),
+            Some(want) => assert_eq!(&got, want),
+        }
+    }
+    let want_output = r#"{
+  "valid": false,
+  "keywordLocation": "",
+  "instanceLocation": "",
+  "errors": [
+    {
+      "valid": false,
+      "keywordLocation": "/properties/p17/enum",
+      "instanceLocation": "/p17",
+      "error": "enum failed"
+    },
+    {
+      "valid": false,
+      "keywordLocation": "/properties/p3/enum",
+      "instanceLocation": "/p3",
+      "error": "enum failed"
+    },
+    {
+      "valid": false,
+      "keywordLocation": "/patternProperties/^x/uniqueItems",
+      "instanceLocation": "/x1",
+      "error": "items at 0 and 2 are equal"
+    },
+    {
+      "valid": false,
+      "keywordLocation": "/additionalProperties/const",
+      "instanceLocation": "/z",
+      "error": "const failed"
+    }
+  ]
+}"#;
+    assert_eq!(want.as_deref(), Some(want_output));
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "formats-net")]
+fn test_override_builtin_format() -> Result<(), Box<dyn Error>> {
+    fn email(v: &Value) -> Result<(), Box<dyn Error>> {
+        (Format::builtin("email").unwrap().func)(v)?;
+        if let Value::String(s) = v {
+            if s.ends_with("@disposable.com") {
+                Err("disposable email domain")?;
+            }
+        }
+        Ok(())
+    }
+
+    let schema = json!({"format": "email"});
+    let tests = [
+        ("a@example.com", true, true),
+        ("a@disposable.com", true, false),
+        ("not-an-email", false, false),
+    ];
+    for email_override in [false, true] {
+        let mut schemas = Schemas::new();
+        let mut compiler = Compiler::new();
+        compiler.enable_format_assertions();
+        if email_override {
+            compiler.register_format(Format {
+                name: "email",
+                func: email,
+            });
+        }
+        compiler.add_resource("http://a.com/schema.json", schema.clone())?;
+        let sch = compiler.compile("http://a.com/schema.json", &mut schemas)?;
+        for (v, builtin_valid, override_valid) in tests {
+            let want = if email_override {
+                override_valid
+            } else {
+                builtin_valid
+            };
+            let got = schemas.validate(&json!(v), sch).is_ok();
+            assert_eq!(got, want, "override={email_override} {v}");
+        }
+    }
+
+    // regex cannot be overridden, but builtin is available
+    assert!(Format::builtin("regex").is_some());
+    assert!(Format::builtin("dummy").is_none());
+    Ok(())
+}
+
+#[test]
+fn test_constant_folding() -> Result<(), Box<dyn Error>> {
+    let schema = json!({
+        "properties": {
+            "not": { "not": {} },
+            "enum": { "enum": [] },
+            "both": { "not": true, "type": "string" },
+            "anchor": { "$anchor": "a", "not": {} },
+            "noop": { "description": "anything", "examples": [1] },
+            "uneval": {
+                "allOf": [
+                    {},
+                    { "type": ["null", "boolean", "number", "string", "array", "object"] }
+                ],
+                "unevaluatedProperties": false
+            }
+        }
+    });
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://tmp.com/schema.json", schema)?;
+    let sch_index = compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+
+    assert!(schemas
+        .validate(&json!({ "noop": [1, "x"], "uneval": {} }), sch_index)
+        .is_ok());
+    let instance = json!({ "not": 1, "enum": 1, "both": 1, "anchor": 1, "uneval": { "x": 1 } });
+    let Err(e) = schemas.validate(&instance, sch_index) else {
+        panic!("validation must fail");
+    };
+    let mut got = e
+        .causes
+        .iter()
+        .map(|c| {
+            let kw = c.kind.keyword_path().map(|p| p.to_string());
+            (c.instance_location.to_string(), kw, c.kind.to_string())
+        })
+        .collect::<Vec<_>>();
+    got.sort();
+    let want = [
+        ("/anchor", Some("not"), "not failed"),
+        ("/both", Some("type"), "want string, but got number"),
+        ("/enum", Some("enum"), "value must be one of "),
+        ("/not", Some("not"), "not failed"),
+        ("/uneval/x", None, "false schema"),
+    ];
+    let want = want.map(|(loc, kw, msg)| (loc.to_owned(), kw.map(str::to_owned), msg.to_owned()));
+    assert_eq!(got, want);
+    Ok(())
+}
+
+#[test]
+fn test_unanchored_pattern() -> Result<(), Box<dyn Error>> {
+    let schema = json!({
+        "properties": {
+            "a": { "pattern": "[a-z]+", "maxLength": 5 },
+            "b": { "pattern": "^[a-z]+$", "maxLength": 5 },
+            "c": { "pattern": "[a-z]+" },
+            "d": { "pattern": "^[a-z]+\\$", "minLength": 1 },
+            "e": { "pattern": "[0-9]", "format": "date" }
+        }
+    });
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://tmp.com/schema.json", schema.clone())?;
+    let (_, diagnostics) =
+        compiler.compile_with_diagnostics("http://tmp.com/schema.json", &mut schemas)?;
+    let got = diagnostics
+        .iter()
+        .map(|d| (d.code, d.schema_location.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        got,
+        [
+            (
+                "unanchored-pattern",
+                "http://tmp.com/schema.json#/properties/a/pattern"
+            ),
+            (
+                "unanchored-pattern",
+                "http://tmp.com/schema.json#/properties/d/pattern"
+            ),
+            (
+                "unanchored-pattern",
+                "http://tmp.com/schema.json#/properties/e/pattern"
+            ),
+        ]
+    );
+
+    // fails when denied
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.deny(&["unanchored-pattern"]);
+    compiler.add_resource("http://tmp.com/schema.json", schema)?;
+    let result = compiler.compile("http://tmp.com/schema.json", &mut schemas);
+    assert!(
+        matches!(&result, Err(CompileError::Diagnostics(d)) if d.len() == 3),
+        "{result:?}"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_anchored_patterns() -> Result<(), Box<dyn Error>> {
+    let schema = json!({
+        "pattern": "[a-z]+",
+        "maxLength": 10,
+        "properties": {
+            "obj": {
+                "patternProperties": { "x|y": true },
+                "additionalProperties": false
+            }
+        }
+    });
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.enable_anchored_patterns();
+    compiler.add_resource("http://tmp.com/schema.json", schema)?;
+    let (sch, diagnostics) =
+        compiler.compile_with_diagnostics("http://tmp.com/schema.json", &mut schemas)?;
+    assert!(diagnostics.is_empty(), "{diagnostics:?}");
+
+    assert!(schemas.validate(&json!("abc"), sch).is_ok());
+    let instance = json!("ABCdef!");
+    let Err(e) = schemas.validate(&instance, sch) else {
+        panic!("want error");
+    };
+    assert_eq!(
+        e.causes[0].kind.to_string(),
+        "'ABCdef!' does not match pattern '^(?:[a-z]+)$'"
+    );
+    assert!(schemas
+        .validate(&json!({"obj": {"x": 1, "y": 2}}), sch)
+        .is_ok());
+    assert!(schemas.validate(&json!({"obj": {"xy": 1}}), sch).is_err());
+
+    // introspection
+    assert_eq!(schemas.pattern(sch), Some("^(?:[a-z]+)$"));
+    let obj = compiler.compile("http://tmp.com/schema.json#/properties/obj", &mut schemas)?;
+    assert_eq!(schemas.pattern_properties(obj), ["^(?:x|y)$"]);
+    assert_eq!(schemas.pattern(obj), None);
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "formats-time")]
+fn test_date_time_policy() -> Result<(), Box<dyn Error>> {
+    let utc = DateTimePolicy {
+        require_utc: true,
+        ..Default::default()
+    };
+    let years = DateTimePolicy {
+        min_year: Some(1900),
+        max_year: Some(2100),
+        ..Default::default()
+    };
+    let no_leap = DateTimePolicy {
+        allow_leap_seconds: false,
+        ..Default::default()
+    };
+    let tests = [
+        (DateTimePolicy::default(), "0000-01-01T00:00:00+05:30", None),
+        (utc, "2020-01-01T00:00:00Z", None),
+        (utc, "2020-01-01T00:00:00z", None),
+        (
+            utc,
+            "2020-01-01T00:00:00+00:00",
+            Some("offset must be Z, as UTC is required"),
+        ),
+        (years, "1900-01-01T00:00:00Z", None),
+        (years, "2100-12-31T23:59:59Z", None),
+        (
+            years,
+            "1899-12-31T23:59:59Z",
+            Some("year 1899 is before 1900"),
+        ),
+        (
+            years,
+            "2101-01-01T00:00:00Z",
+            Some("year 2101 is after 2100"),
+        ),
+        (no_leap, "1998-12-31T23:59:59Z", None),
+        (
+            no_leap,
+            "1998-12-31T23:59:60Z",
+            Some("leap second is not allowed"),
+        ),
+    ];
+    for (policy, v, want) in tests {
+        let mut schemas = Schemas::new();
+        let mut compiler = Compiler::new();
+        compiler.enable_format_assertions();
+        compiler.configure_format("date-time", policy)?;
+        compiler.add_resource("http://a.com/schema.json", json!({"format": "date-time"}))?;
+        let sch = compiler.compile("http://a.com/schema.json", &mut schemas)?;
+        let got = schemas
+            .validate(&json!(v), sch)
+            .err()
+            .map(|e| e.to_string());
+        match (got, want) {
+            (None, None) => {}
+            (Some(got), Some(want)) => assert!(got.contains(want), "{v}: {got}"),
+            (got, _) => panic!("{policy:?} {v}: got {got:?}, want {want:?}"),
+        }
+    }
+
+    // applies only to schemas compiled afterwards
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.enable_format_assertions();
+    compiler.add_resource("http://a.com/before.json", json!({"format": "date-time"}))?;
+    compiler.add_resource("http://a.com/after.json", json!({"format": "date-time"}))?;
+    let before = compiler.compile("http://a.com/before.json", &mut schemas)?;
+    compiler.configure_format("date-time", utc)?;
+    let after = compiler.compile("http://a.com/after.json", &mut schemas)?;
+    let v = json!("2020-01-01T00:00:00+01:00");
+    assert!(schemas.validate(&v, before).is_ok());
+    assert!(schemas.validate(&v, after).is_err());
+
+    // still invalid as per built-in format
+    let v = json!("2020-13-01T00:00:00Z");
+    assert!(schemas.validate(&v, after).is_err());
+
+    // policy not meant for format
+    let result = compiler.configure_format("date", utc);
+    assert!(
+        matches!(&result, Err(CompileError::UnsupportedFormatPolicy { format, policy_format: "date-time" }) if format == "date"),
+        "{result:?}"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_stripped_keywords() -> Result<(), Box<dyn Error>> {
+    let schema = json!({
+        "$comment": "internal notes",
+        "title": "person",
+        "x-vendor": {"blob": "..."},
+        "default": {"title": "mr"},
+        "required": ["title"],
+        "properties": {
+            "title": {"description": "honorific", "enum": [{"description": "x"}]}
+        }
+    });
+    let mut compiler = Compiler::new();
+    compiler.set_stripped_keywords(&["$comment", "title", "description", "x-*", "default", "$ref"]);
+    compiler.add_resource("http://a.com/schema.json", schema)?;
+    compiler.add_resource(
+        "http://a.com/ref.json",
+        json!({"$ref": "schema.json#/properties/title/description"}),
+    )?;
+
+    // annotations are gone
+    let result = compiler.compile("http://a.com/ref.json", &mut Schemas::new());
+    assert!(
+        matches!(result, Err(CompileError::JsonPointerNotFound { .. })),
+        "{result:?}"
+    );
+
+    // validation and default are intact
+    let mut schemas = Schemas::new();
+    let sch = compiler.compile("http://a.com/schema.json#/properties/title", &mut schemas)?;
+    assert!(schemas.validate(&json!({"description": "x"}), sch).is_ok());
+    assert!(schemas.validate(&json!({}), sch).is_err());
+    let sch = compiler.compile("http://a.com/schema.json", &mut schemas)?;
+    assert_eq!(schemas.example_for(sch), Some(&json!({"title": "mr"})));
+    assert!(schemas.validate(&json!({"title": 1}), sch).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_max_document_size() -> Result<(), Box<dyn Error>> {
+    let mut compiler = Compiler::new();
+    compiler.set_max_document_size(20);
+    compiler.add_resource("http://a.com/small.json", json!({"type": "string"}))?;
+    let result = compiler.add_resource("http://a.com/large.json", json!({"maxLength": 123456789}));
+    let Err(CompileError::DocumentTooLarge { size, limit, .. }) = result else {
+        panic!("{result:?}");
+    };
+    assert_eq!((size, limit), (23, 20));
+
+    let result =
+        compiler.add_resource_bytes("http://a.com/large.json", br#"{"minLength": 1000000}"#);
+    assert!(
+        matches!(result, Err(CompileError::DocumentTooLarge { size: 22, .. })),
+        "{result:?}"
+    );
+
+    // loaded documents
+    struct Loader;
+    impl UrlLoader for Loader {
+        fn load(&self, _url: &str) -> Result<Value, Box<dyn Error>> {
+            Ok(json!({"description": "d".repeat(100)}))
+        }
+    }
+    compiler.use_loader(Box::new(Loader));
+    compiler.add_resource("http://a.com/ref.json", json!({"$ref": "b.json"}))?;
+    let result = compiler.compile("http://a.com/ref.json", &mut Schemas::new());
+    let Err(CompileError::DocumentTooLarge {
+        url,
+        referenced_from,
+        ..
+    }) = result
+    else {
+        panic!("{result:?}");
+    };
+    assert_eq!(url, "http://a.com/b.json");
+    assert_eq!(
+        referenced_from,
+        ["http://a.com/ref.json#/$ref", "http://a.com/ref.json#"]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_compile_all() -> Result<(), Box<dyn Error>> {
+    let mut compiler = Compiler::new();
+    compiler.add_resource(
+        "http://a.com/common.json",
+        json!({"$defs": {"name": {"type": "string"}}}),
+    )?;
+    // refers to common.json, before failing
+    compiler.add_resource(
+        "http://a.com/bad.json",
+        json!({"properties": {
+            "name": {"$ref": "common.json#/$defs/name"},
+            "age": {"$ref": "common.json#/$defs/missing"}
+        }}),
+    )?;
+    compiler.add_resource(
+        "http://a.com/good.json",
+        json!({"properties": {"name": {"$ref": "common.json#/$defs/name"}}}),
+    )?;
+
+    let mut schemas = Schemas::new();
+    let results = compiler.compile_all(
+        &["http://a.com/bad.json", "http://a.com/good.json"],
+        &mut schemas,
+    );
+    let [bad, good] = results.try_into().unwrap();
+    assert!(
+        matches!(bad, Err(CompileError::JsonPointerNotFound { .. })),
+        "{bad:?}"
+    );
+    let good = good?;
+    assert!(schemas.contains(good));
+    assert!(schemas.validate(&json!({"name": "x"}), good).is_ok());
+    assert!(schemas.validate(&json!({"name": 1}), good).is_err());
+
+    // failed compilation adds nothing
+    let size = schemas.size();
+    assert!(compiler
+        .compile("http://a.com/bad.json", &mut schemas)
+        .is_err());
+    assert_eq!(schemas.size(), size);
+    Ok(())
+}
+
+#[test]
+fn test_compile_with_draft() -> Result<(), Box<dyn Error>> {
+    let mut compiler = Compiler::new();
+    // draft-04 style exclusiveMaximum, in embedded resource
+    let undeclared = json!({
+        "$defs": {
+            "inner": {"id": "inner.json", "maximum": 10, "exclusiveMaximum": true}
+        },
+        "properties": {
+            "n": {"$ref": "#/$defs/inner"},
+            "other": {"$ref": "other.json"}
+        }
+    });
+    compiler.add_resource("http://a.com/undeclared.json", undeclared.clone())?;
+    compiler.add_resource("http://a.com/latest.json", undeclared)?;
+    compiler.add_resource(
+        "http://a.com/declared.json",
+        json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$defs": {"inner": {"$id": "inner.json", "exclusiveMaximum": 10}},
+            "$ref": "#/$defs/inner"
+        }),
+    )?;
+    compiler.add_resource("http://a.com/other.json", json!({"type": "string"}))?;
+
+    let mut schemas = Schemas::new();
+    let sch =
+        compiler.compile_with_draft("http://a.com/undeclared.json", &mut schemas, Draft::V4)?;
+    assert_eq!(schemas.draft_of(sch), Some(Draft::V4));
+    assert!(schemas.validate(&json!({"n": 9}), sch).is_ok());
+    assert!(schemas.validate(&json!({"n": 10}), sch).is_err());
+    // embedded resource uses draft of document
+    let inner = compiler.compile("http://a.com/undeclared.json#/$defs/inner", &mut schemas)?;
+    assert_eq!(schemas.draft_of(inner), Some(Draft::V4));
+    // referenced document uses default draft
+    let other = compiler.compile("http://a.com/other.json", &mut schemas)?;
+    assert_eq!(schemas.draft_of(other), Some(Draft::V2020_12));
+
+    // explicit $schema is never overridden, including by embedded resources
+    let sch = compiler.compile_with_draft("http://a.com/declared.json", &mut schemas, Draft::V4)?;
+    assert_eq!(schemas.draft_of(sch), Some(Draft::V2020_12));
+    let inner = compiler.compile("http://a.com/declared.json#/$defs/inner", &mut schemas)?;
+    assert_eq!(schemas.draft_of(inner), Some(Draft::V2020_12));
+    assert!(schemas.validate(&json!(10), sch).is_err());
+
+    // default draft rejects draft-04 style exclusiveMaximum
+    assert!(compiler
+        .compile("http://a.com/latest.json", &mut schemas)
+        .is_err());
+    // document already loaded, keeps its draft
+    let other = compiler.compile_with_draft("http://a.com/other.json", &mut schemas, Draft::V4)?;
+    assert_eq!(schemas.draft_of(other), Some(Draft::V2020_12));
+    assert_eq!(Schemas::new().draft_of(other), None);
+    Ok(())
+}
+
+#[test]
+fn test_unknown_dependency_property() -> Result<(), Box<dyn Error>> {
+    let schema = json!({
+        "properties": {
+            "paymentMethod": {},
+            "cardNum": {}
+        },
+        "patternProperties": {"^x-": {}},
+        "dependentRequired": {
+            "paymentMethod": ["cardNumber", "x-card"],
+            "billing": ["cardNumber"]
+        },
+        "dependentSchemas": {
+            "x-debug": {},
+            "shipping": {}
+        },
+        "$defs": {
+            // names are not known, without properties
+            "open": {"dependentRequired": {"a": ["b"]}}
+        }
+    });
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://tmp.com/schema.json", schema)?;
+    compiler.add_resource(
+        "http://tmp.com/draft7.json",
+        json!({
+            "$schema": "http://json-schema.org/draft-07/schema",
+            "properties": {"a": {}},
+            "dependencies": {"a": ["b"], "c": {}}
+        }),
+    )?;
+    let mut schemas = Schemas::new();
+    let (_, diagnostics) =
+        compiler.compile_with_diagnostics("http://tmp.com/schema.json", &mut schemas)?;
+    let (_, draft7) =
+        compiler.compile_with_diagnostics("http://tmp.com/draft7.json", &mut schemas)?;
+    let got = diagnostics
+        .iter()
+        .chain(&draft7)
+        .map(|d| d.to_string())
+        .collect::<Vec<_>>();
+    let prefix = "warning[unknown-dependency-property] at";
+    let suffix = "is neither in properties nor matched by patternProperties";
+    assert_eq!(
+        got,
+        [
+            format!("{prefix} http://tmp.com/schema.json#/dependentRequired: property 'billing' {suffix}"),
+            format!("{prefix} http://tmp.com/schema.json#/dependentRequired: property 'cardNumber' {suffix}"),
+            format!("{prefix} http://tmp.com/schema.json#/dependentSchemas: property 'shipping' {suffix}"),
+            format!("{prefix} http://tmp.com/draft7.json#/dependencies: property 'b' {suffix}"),
+            format!("{prefix} http://tmp.com/draft7.json#/dependencies: property 'c' {suffix}"),
+        ]
+    );
+
+    // can be suppressed, or promoted to error
+    compiler.allow(&["unknown-dependency-property"]);
+    compiler.add_resource(
+        "http://tmp.com/allow.json",
+        json!({
+            "properties": {"a": {}},
+            "dependentRequired": {"a": ["b"]}
+        }),
+    )?;
+    let (_, diagnostics) =
+        compiler.compile_with_diagnostics("http://tmp.com/allow.json", &mut schemas)?;
+    assert!(diagnostics.is_empty(), "{diagnostics:?}");
+
+    compiler.deny(&["unknown-dependency-property"]);
+    compiler.add_resource(
+        "http://tmp.com/deny.json",
+        json!({
+            "properties": {"a": {}},
+            "dependentRequired": {"a": ["b"]}
+        }),
+    )?;
+    let result = compiler.compile("http://tmp.com/deny.json", &mut schemas);
+    assert!(
+        matches!(result, Err(CompileError::Diagnostics(_))),
+        "{result:?}"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_ref_siblings_by_draft() -> Result<(), Box<dyn Error>> {
+    let schema = json!({
+        "definitions": {"str": {"type": "string"}, "any": {}},
+        "properties": {
+            "a": {
+                "$ref": "#/definitions/str",
+                "description": "not evaluated in any draft",
+                "x-extension": 1,
+                "maxLength": 3,
+                "not": {"const": "bad"}
+            },
+            // keywords of draft-06 and draft-07
+            "b": {"$ref": "#/definitions/any", "const": 1},
+            "c": {"$ref": "#/definitions/any", "contains": {"const": 1}},
+            "d": {"$ref": "#/definitions/any", "propertyNames": {"maxLength": 1}},
+            "e": {
+                "$ref": "#/definitions/any",
+                "if": {"type": "string"},
+                "then": {"minLength": 2},
+                "else": false
+            }
+        }
+    });
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://tmp.com/draft7.json", schema.clone())?;
+    compiler.add_resource("http://tmp.com/latest.json", schema)?;
+    let mut schemas = Schemas::new();
+    let draft7 =
+        compiler.compile_with_draft("http://tmp.com/draft7.json", &mut schemas, Draft::V7)?;
+    let draft7_diagnostics = compiler.take_diagnostics();
+    let latest = compiler.compile("http://tmp.com/latest.json", &mut schemas)?;
+    let latest_diagnostics = compiler.take_diagnostics();
+
+    // siblings are ignored under draft-07, and evaluated since 2019-09
+    for (v, draft7_valid, latest_valid) in [
+        (json!({"a": "abc"}), true, true),
+        (json!({"a": "abcd"}), true, false),
+        (json!({"a": "bad"}), true, false),
+        (json!({"a": 1}), false, false),
+        (json!({"b": 1}), true, true),
+        (json!({"b": 2}), true, false),
+        (json!({"c": [1, 2]}), true, true),
+        (json!({"c": [2]}), true, false),
+        (json!({"d": {"x": 1}}), true, true),
+        (json!({"d": {"xy": 1}}), true, false),
+        (json!({"e": "xy"}), true, true),
+        (json!({"e": "x"}), true, false),
+        (json!({"e": 1}), true, false),
+    ] {
+        assert_eq!(schemas.validate(&v, draft7).is_ok(), draft7_valid, "{v}");
+        assert_eq!(schemas.validate(&v, latest).is_ok(), latest_valid, "{v}");
+    }
+
+    let got = draft7_diagnostics
+        .iter()
+        .map(|d| d.to_string())
+        .collect::<Vec<_>>();
+    let warning = |prop: &str, keywords: &str| {
+        format!("warning[ref-siblings-ignored] at http://tmp.com/draft7.json#/properties/{prop}/$ref: keywords {keywords} next to $ref are ignored under draft-07; they would apply under 2019-09 and later")
+    };
+    assert_eq!(
+        got,
+        [
+            warning("a", "'maxLength', 'not'"),
+            warning("b", "'const'"),
+            warning("c", "'contains'"),
+            warning("d", "'propertyNames'"),
+            warning("e", "'else', 'if', 'then'"),
+        ]
+    );
+    assert!(latest_diagnostics.is_empty(), "{latest_diagnostics:?}");
+
+    // keywords of extensions too
+    let mut compiler = Compiler::new();
+    compiler.enable_extension("x-time-range");
+    let schema = json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "definitions": {"any": {}},
+        "$ref": "#/definitions/any",
+        "x-notAfter": "2000-01-01T00:00:00Z"
+    });
+    compiler.add_resource("http://tmp.com/ext.json", schema)?;
+    let (sch, diagnostics) =
+        compiler.compile_with_diagnostics("http://tmp.com/ext.json", &mut schemas)?;
+    assert!(schemas
+        .validate(&json!("2020-01-01T00:00:00Z"), sch)
+        .is_ok());
+    let got = diagnostics
+        .iter()
+        .map(|d| d.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(got, ["warning[ref-siblings-ignored] at http://tmp.com/ext.json#/$ref: keywords 'x-notAfter' next to $ref are ignored under draft-07; they would apply under 2019-09 and later"]);
+    Ok(())
+}
+
+#[test]
+fn test_inert_keywords() -> Result<(), Box<dyn Error>> {
+    let tests = [
+        (
+            json!({"then": {}, "else": {}}),
+            vec![
+                ("then", "then has no effect, without if"),
+                ("else", "else has no effect, without if"),
+            ],
+        ),
+        (json!({"if": {}, "then": {}, "else": {}}), vec![]),
+        (
+            json!({"$schema": "http://json-schema.org/draft-07/schema", "items": {}, "additionalItems": false}),
+            vec![(
+                "additionalItems",
+                "additionalItems has no effect, unless items is an array",
+            )],
+        ),
+        (
+            json!({"$schema": "http://json-schema.org/draft-07/schema", "additionalItems": false}),
+            vec![(
+                "additionalItems",
+                "additionalItems has no effect, unless items is an array",
+            )],
+        ),
+        (
+            json!({"$schema": "http://json-schema.org/draft-07/schema", "items": [{}], "additionalItems": false}),
+            vec![],
+        ),
+        (
+            json!({"minContains": 1, "maxContains": 2}),
+            vec![
+                ("minContains", "minContains has no effect, without contains"),
+                ("maxContains", "maxContains has no effect, without contains"),
+            ],
+        ),
+        (
+            json!({"contains": {}, "minContains": 1, "maxContains": 2}),
+            vec![],
+        ),
+        (
+            json!({"$schema": "http://json-schema.org/draft-07/schema", "unevaluatedItems": false}),
+            vec![(
+                "unevaluatedItems",
+                "unevaluatedItems is ignored under draft-07; it is supported since 2019-09",
+            )],
+        ),
+        (json!({"unevaluatedItems": false}), vec![]),
+    ];
+    for (i, (schema, want)) in tests.into_iter().enumerate() {
+        let url = format!("http://tmp.com/schema{i}.json");
+        let mut compiler = Compiler::new();
+        compiler.add_resource(&url, schema.clone())?;
+        let (_, diagnostics) = compiler.compile_with_diagnostics(&url, &mut Schemas::new())?;
+        let got = diagnostics
+            .iter()
+            .map(|d| (d.code, d.schema_location.clone(), d.message.clone()))
+            .collect::<Vec<_>>();
+        let want = want
+            .into_iter()
+            .map(|(kw, msg)| ("inert-keyword", format!("{url}#/{kw}"), msg.to_owned()))
+            .collect::<Vec<_>>();
+        assert_eq!(got, want, "{schema}");
+    }
+
+    // can be promoted to errors
+    let mut compiler = Compiler::new();
+    compiler.deny(&["inert-keyword"]);
+    compiler.add_resource("http://tmp.com/deny.json", json!({"else": {}}))?;
+    let result = compiler.compile("http://tmp.com/deny.json", &mut Schemas::new());
+    assert!(
+        matches!(result, Err(CompileError::Diagnostics(_))),
+        "{result:?}"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_source_retention() -> Result<(), Box<dyn Error>> {
+    struct RemoteLoader;
+    impl UrlLoader for RemoteLoader {
+        fn load(&self, _url: &str) -> Result<Value, Box<dyn Error>> {
+            Ok(json!({"$defs": {"id": {"type": "integer", "minimum": 1}}}))
+        }
+    }
+    let schema = json!({
+        "$defs": {
+            "name": {"type": "string", "x-note": "kept as is"}
+        },
+        "properties": {
+            "name": {"$ref": "#/$defs/name"},
+            "id": {"$ref": "http://remote.com/common.json#/$defs/id"},
+            "tags": {"items": {"enum": ["a", "b"]}},
+            "any": true
+        }
+    });
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.enable_source_retention();
+    compiler.use_loader(Box::new(RemoteLoader));
+    compiler.add_resource("http://tmp.com/schema.json", schema.clone())?;
+    let root = compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+    assert_eq!(schemas.source(root), Some(&schema));
+
+    let cases = [
+        (
+            "http://tmp.com/schema.json#/$defs/name",
+            &schema["$defs"]["name"],
+        ),
+        (
+            "http://tmp.com/schema.json#/properties/tags/items",
+            &schema["properties"]["tags"]["items"],
+        ),
+        ("http://tmp.com/schema.json#/properties/any", &json!(true)),
+        (
+            "http://remote.com/common.json#/$defs/id",
+            &json!({"type": "integer", "minimum": 1}),
+        ),
+    ];
+    for (loc, want) in cases {
+        let sch = compiler.compile(loc, &mut schemas)?;
+        assert_eq!(schemas.source(sch), Some(want), "{loc}");
+    }
+
+    let sch = compiler.compile("http://tmp.com/schema.json#/$defs/name", &mut schemas)?;
+    assert_eq!(
+        schemas.source_pretty(sch).unwrap(),
+        "{\n  \"type\": \"string\",\n  \"x-note\": \"kept as is\"\n}"
+    );
+
+    // not retained by default
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.use_loader(Box::new(RemoteLoader));
+    compiler.add_resource("http://tmp.com/schema.json", schema)?;
+    let root = compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+    assert_eq!(schemas.source(root), None);
+    assert_eq!(schemas.source_pretty(root), None);
+    Ok(())
+}
+
+#[test]
+fn test_schemas_iter() -> Result<(), Box<dyn Error>> {
+    let mut compiler = Compiler::new();
+    compiler.add_resource(
+        "http://a.com/schema.json",
+        json!({
+            "properties": {
+                "a": { "items": { "$ref": "#/$defs/s" } },
+                "b~/c": true
+            },
+            "$defs": {
+                "s": { "type": "string" },
+                "unused": { "type": "number" }
+            }
+        }),
+    )?;
+    let mut schemas = Schemas::new();
+    assert!(schemas.is_empty());
+    let sch = compiler.compile("http://a.com/schema.json", &mut schemas)?;
+    let mut locs = schemas.iter().map(|(_, loc)| loc).collect::<Vec<_>>();
+    locs.sort();
+    assert_eq!(
+        locs,
+        [
+            "http://a.com/schema.json#",
+            "http://a.com/schema.json#/$defs/s",
+            "http://a.com/schema.json#/properties/a",
+            "http://a.com/schema.json#/properties/a/items",
+            "http://a.com/schema.json#/properties/b~0~1c",
+        ]
+    );
+    assert_eq!(schemas.len(), 5);
+    assert_eq!(
+        schemas.iter().next(),
+        Some((sch, "http://a.com/schema.json#"))
+    );
+    for (i, (idx, loc)) in schemas.iter().enumerate() {
+        assert_eq!(schemas.contains_location(loc), Some(idx));
+        assert_eq!(schemas.iter().nth(i), Some((idx, loc)));
+    }
+    assert_eq!(
+        schemas.contains_location("http://a.com/schema.json"),
+        Some(sch)
+    );
+    assert_eq!(
+        schemas.contains_location("http://a.com/schema.json#/$defs/unused"),
+        None
+    );
+
+    // compiled later, is appended
+    let unused = compiler.compile("http://a.com/schema.json#/$defs/unused", &mut schemas)?;
+    assert_eq!(schemas.len(), 6);
+    assert_eq!(schemas.iter().last().map(|(idx, _)| idx), Some(unused));
+    assert_eq!(
+        schemas.contains_location("http://a.com/schema.json#/$defs/unused"),
+        Some(unused)
+    );
+    Ok(())
+}
+
+#[test]
+fn test_conflicting_resources() -> Result<(), Box<dyn Error>> {
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://tmp.com/a.json", json!({"type": "string"}))?;
+    // same document again is fine
+    compiler.add_resource("http://tmp.com/a.json#", json!({"type": "string"}))?;
+    let err = compiler
+        .add_resource("http://tmp.com/a.json#/x", json!({"type": "integer"}))
+        .unwrap_err();
+    assert!(
+        matches!(&err, CompileError::ConflictingResource { url, existing: Origin::Resource(loc), conflicting: Origin::Resource(_) }
+            if url == "http://tmp.com/a.json" && loc == "http://tmp.com/a.json"),
+        "{err:?}"
+    );
+    let msg = err.to_string();
+    assert!(msg.contains("added as http://tmp.com/a.json,"), "{msg}");
+    assert!(msg.contains("added as http://tmp.com/a.json#/x"), "{msg}");
+    assert_eq!(
+        compiler.origin_of("http://tmp.com/a.json"),
+        Some(&Origin::Resource("http://tmp.com/a.json".to_owned()))
+    );
+
+    // loaded, then added
+    struct Loader;
+    impl UrlLoader for Loader {
+        fn load(&self, _url: &str) -> Result<Value, Box<dyn Error>> {
+            Ok(json!({"type": "number"}))
+        }
+    }
+    compiler.use_loader(Box::new(Loader));
+    compiler.add_resource("http://tmp.com/b.json", json!({"$ref": "c.json"}))?;
+    compiler.compile("http://tmp.com/b.json", &mut Schemas::new())?;
+    assert_eq!(
+        compiler.origin_of("http://tmp.com/c.json"),
+        Some(&Origin::Loaded("http://tmp.com/c.json".to_owned()))
+    );
+    let err = compiler
+        .add_resource("http://tmp.com/c.json", json!({"type": "string"}))
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "conflicting documents for http://tmp.com/c.json: loaded from http://tmp.com/c.json, and added as http://tmp.com/c.json"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_compile_report() -> Result<(), Box<dyn Error>> {
+    let mut compiler = Compiler::new();
+    compiler.add_resource(
+        "http://tmp.com/main.json",
+        json!({
+            "$schema": "http://tmp.com/meta.json",
+            "properties": { "a": { "$ref": "a.json" } }
+        }),
+    )?;
+    compiler.add_resource(
+        "http://tmp.com/meta.json",
+        json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$ref": "https://json-schema.org/draft/2020-12/schema"
+        }),
+    )?;
+    compiler.add_resource("http://tmp.com/a.json", json!({"type": "string"}))?;
+    compiler.add_resource("http://tmp.com/b.json", json!({"type": "number"}))?;
+    compiler.add_resource("http://tmp.com/c.json", json!({"$ref": "b.json"}))?;
+    let mut schemas = Schemas::new();
+    compiler.compile("http://tmp.com/main.json", &mut schemas)?;
+    let report = compiler.last_compile_report();
+    assert_eq!(
+        report.documents,
+        ["http://tmp.com/a.json", "http://tmp.com/main.json"]
+    );
+    assert_eq!(report.metaschemas, ["http://tmp.com/meta.json"]);
+    assert_eq!(
+        report.subschemas,
+        [
+            "http://tmp.com/main.json#",
+            "http://tmp.com/main.json#/properties/a",
+            "http://tmp.com/a.json#"
+        ]
+    );
+    assert_eq!(
+        report.unused_resources,
+        ["http://tmp.com/b.json", "http://tmp.com/c.json"]
+    );
+
+    // schemas compiled earlier are not compiled again
+    compiler.compile("http://tmp.com/c.json", &mut schemas)?;
+    let report = compiler.last_compile_report();
+    assert_eq!(
+        report.documents,
+        ["http://tmp.com/b.json", "http://tmp.com/c.json"]
+    );
+    assert!(report.metaschemas.is_empty());
+    assert_eq!(
+        report.subschemas,
+        ["http://tmp.com/c.json#", "http://tmp.com/b.json#"]
+    );
+    assert!(report.unused_resources.is_empty());
+
+    // failed compilation is reported
+    compiler.add_resource("http://tmp.com/d.json", json!({"$ref": "missing.json"}))?;
+    assert!(compiler
+        .compile("http://tmp.com/d.json", &mut schemas)
+        .is_err());
+    let report = compiler.last_compile_report();
+    assert_eq!(report.documents, ["http://tmp.com/d.json"]);
+    Ok(())
+}
+
+#[test]
+fn test_compile_report_batch() -> Result<(), Box<dyn Error>> {
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://tmp.com/a.json", json!({"$ref": "c.json"}))?;
+    compiler.add_resource("http://tmp.com/b.json", json!({"$ref": "missing.json"}))?;
+    compiler.add_resource("http://tmp.com/c.json", json!({"type": "string"}))?;
+    compiler.add_resource("http://tmp.com/d.json", json!({"type": "number"}))?;
+    let locs = ["http://tmp.com/a.json", "http://tmp.com/b.json"];
+    let results = compiler.compile_all(&locs, &mut Schemas::new());
+    assert!(results[0].is_ok() && results[1].is_err());
+
+    // report covers all locations, not just the last
+    let report = compiler.last_compile_report();
+    assert_eq!(
+        report.documents,
+        [
+            "http://tmp.com/a.json",
+            "http://tmp.com/b.json",
+            "http://tmp.com/c.json"
+        ]
+    );
+    assert_eq!(
+        report.subschemas,
+        [
+            "http://tmp.com/a.json#",
+            "http://tmp.com/c.json#",
+            "http://tmp.com/b.json#",
+            "http://tmp.com/missing.json#"
+        ]
+    );
+    assert_eq!(report.unused_resources, ["http://tmp.com/d.json"]);
+    Ok(())
+}
+
+#[test]
+fn test_metaschema_limits() -> Result<(), Box<dyn Error>> {
+    // schema.json -> m1.json -> ... -> m5.json -> 2020-12
+    fn chain_compiler() -> Result<Compiler, Box<dyn Error>> {
+        let mut compiler = Compiler::new();
+        compiler.add_resource(
+            "http://a.com/schema.json",
+            json!({"$schema": "http://a.com/m1.json"}),
+        )?;
+        for i in 1..=5 {
+            let sch = match i {
+                5 => "https://json-schema.org/draft/2020-12/schema".to_owned(),
+                _ => format!("http://a.com/m{}.json", i + 1),
+            };
+            compiler.add_resource(&format!("http://a.com/m{i}.json"), json!({"$schema": sch}))?;
+        }
+        Ok(compiler)
+    }
+
+    let mut compiler = chain_compiler()?;
+    compiler.set_max_metaschema_depth(5);
+    compiler.set_max_metaschema_documents(5);
+    compiler.compile("http://a.com/schema.json", &mut Schemas::new())?;
+
+    let mut compiler = chain_compiler()?;
+    compiler.set_max_metaschema_depth(3);
+    let result = compiler.compile("http://a.com/schema.json", &mut Schemas::new());
+    let Err(CompileError::MetaSchemaChainTooLong { chain, limit }) = result else {
+        panic!("{result:?}");
+    };
+    assert_eq!(limit, 3);
+    assert_eq!(
+        chain,
+        [
+            "http://a.com/m1.json",
+            "http://a.com/m2.json",
+            "http://a.com/m3.json",
+            "http://a.com/m4.json"
+        ]
+    );
+
+    // counted across documents of one compilation
+    let mut compiler = Compiler::new();
+    compiler.set_max_metaschema_documents(2);
+    compiler.add_resource(
+        "http://a.com/schema.json",
+        json!({"$schema": "http://a.com/m1.json", "$ref": "other.json"}),
+    )?;
+    compiler.add_resource(
+        "http://a.com/other.json",
+        json!({"$schema": "http://a.com/m3.json"}),
+    )?;
+    let draft = "https://json-schema.org/draft/2020-12/schema";
+    for (url, sch) in [
+        ("http://a.com/m1.json", "http://a.com/m2.json"),
+        ("http://a.com/m2.json", draft),
+        ("http://a.com/m3.json", draft),
+    ] {
+        compiler.add_resource(url, json!({"$schema": sch}))?;
+    }
+    let result = compiler.compile("http://a.com/schema.json", &mut Schemas::new());
+    let Err(CompileError::TooManyMetaSchemas { chain, limit }) = result else {
+        panic!("{result:?}");
+    };
+    assert_eq!((chain, limit), (vec!["http://a.com/m3.json".to_owned()], 2));
+
+    // counted afresh for each compilation
+    compiler.compile("http://a.com/other.json", &mut Schemas::new())?;
+    Ok(())
+}
+
+#[test]
+fn test_metaschema_loading_policies() -> Result<(), Box<dyn Error>> {
+    // no loader for remote urls by default
+    let mut compiler = Compiler::new();
+    compiler.add_resource(
+        "http://a.com/schema.json",
+        json!({"$schema": "http://evil.com/meta.json"}),
+    )?;
+    let result = compiler.compile("http://a.com/schema.json", &mut Schemas::new());
+    let Err(CompileError::UnsupportedUrlScheme { url, .. }) = result else {
+        panic!("{result:?}");
+    };
+    assert_eq!(url, "http://evil.com/meta.json");
+
+    // size limit applies to loaded metaschema
+    struct Loader;
+    impl UrlLoader for Loader {
+        fn load(&self, _url: &str) -> Result<Value, Box<dyn Error>> {
+            Ok(json!({"description": "d".repeat(100)}))
+        }
+    }
+    compiler.use_loader(Box::new(Loader));
+    compiler.set_max_document_size(50);
+    let result = compiler.compile("http://a.com/schema.json", &mut Schemas::new());
+    let Err(CompileError::DocumentTooLarge { url, .. }) = result else {
+        panic!("{result:?}");
+    };
+    assert_eq!(url, "http://evil.com/meta.json");
+    Ok(())
+}
+
+#[test]
+fn test_not_a_schema_location() -> Result<(), Box<dyn Error>> {
+    let schema = json!({
+        "properties": {
+            "name": { "type": "string", "enum": [{ "type": "string" }, "x"] },
+            "type": { "required": ["a"] }
+        },
+        "$defs": { "a": { "$ref": "#/properties/name/type" } },
+        "x-defs": { "b": { "type": "integer" } },
+        "prefixItems": [true]
+    });
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://tmp.com/schema.json", schema)?;
+    let mut schemas = Schemas::new();
+    let not_schemas = [
+        ("#/properties/name/type", "type"),
+        ("#/properties/name/enum/0", "enum"),
+        ("#/properties/type/required/0", "required"),
+        ("#/properties", "properties"),
+        ("#/$defs/a", "type"), // through $ref
+    ];
+    for (frag, want) in not_schemas {
+        let loc = format!("http://tmp.com/schema.json{frag}");
+        match compiler.compile(&loc, &mut schemas) {
+            Err(CompileError::NotASchemaLocation { parent_keyword, .. }) => {
+                assert_eq!(parent_keyword, want, "{frag}");
+            }
+            result => panic!("{frag}: {result:?}"),
+        }
+    }
+    let err = compiler
+        .compile(
+            "http://tmp.com/schema.json#/properties/name/type",
+            &mut schemas,
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "http://tmp.com/schema.json#/properties/name/type is not a schema location: value of keyword type holds no schema there"
+    );
+
+    for frag in [
+        "#/properties/name",
+        "#/properties/type",
+        "#/x-defs/b",
+        "#/prefixItems/0",
+    ] {
+        let loc = format!("http://tmp.com/schema.json{frag}");
+        compiler.compile(&loc, &mut schemas)?;
+    }
+
+    compiler.allow_non_schemas();
+    compiler.compile(
+        "http://tmp.com/schema.json#/properties/name/enum/0",
+        &mut schemas,
+    )?;
+    Ok(())
+}
+
+#[test]
+fn test_unsupported_scheme_referenced_from() -> Result<(), Box<dyn Error>> {
+    let mut compiler = Compiler::new();
+    compiler.add_resource(
+        "http://a.com/a.json",
+        json!({"properties": {"b": {"$ref": "ftp://mars.com/schema.json"}}}),
+    )?;
+    compiler.add_resource(
+        "http://a.com/schema.json",
+        json!({"items": {"$ref": "a.json"}}),
+    )?;
+    let result = compiler.compile("http://a.com/schema.json", &mut Schemas::new());
+    let Err(e @ CompileError::UnsupportedUrlScheme { .. }) = result else {
+        panic!("{result:?}");
+    };
+    assert_eq!(
+        e.to_string(),
+        "unsupported scheme in ftp://mars.com/schema.json, referenced from \
+         http://a.com/a.json#/properties/b/$ref <- http://a.com/schema.json#/items/$ref \
+         <- http://a.com/schema.json#"
+    );
+    Ok(())
+}
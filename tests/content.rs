@@ -0,0 +1,213 @@
+use std::{
+    error::Error,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use boon::{Compiler, Decoder, ErrorKind, MediaType, Schemas, ValidationOptions};
+use serde_json::{json, Value};
+
+static DECODED: AtomicUsize = AtomicUsize::new(0);
+static DESERIALIZED: AtomicUsize = AtomicUsize::new(0);
+
+fn decode_hex(s: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    DECODED.fetch_add(1, Ordering::SeqCst);
+    (0..s.len())
+        .step_by(2)
+        .map(|i| {
+            Ok(u8::from_str_radix(
+                s.get(i..i + 2).ok_or("odd length")?,
+                16,
+            )?)
+        })
+        .collect()
+}
+
+fn check_json(bytes: &[u8], deserialize: bool) -> Result<Option<Value>, Box<dyn Error>> {
+    DESERIALIZED.fetch_add(1, Ordering::SeqCst);
+    let v: Value = serde_json::from_slice(bytes)?;
+    Ok(deserialize.then_some(v))
+}
+
+fn encode_hex(v: &Value) -> String {
+    v.to_string().bytes().map(|b| format!("{b:02x}")).collect()
+}
+
+#[test]
+fn test_content_decoded_once() -> Result<(), Box<dyn Error>> {
+    let branch = |i: usize| {
+        json!({
+            "contentEncoding": "hex",
+            "contentMediaType": "application/x-json",
+            "contentSchema": {
+                "properties": {
+                    "branch": {"const": i},
+                    "nested": {"$ref": "#"}
+                }
+            }
+        })
+    };
+    let schema = json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "anyOf": (0..5).map(branch).collect::<Vec<_>>()
+    });
+
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.enable_content_assertions();
+    compiler.register_content_encoding(Decoder {
+        name: "hex",
+        func: decode_hex,
+    });
+    compiler.register_content_media_type(MediaType {
+        name: "application/x-json",
+        json_compatible: true,
+        func: check_json,
+    });
+    compiler.add_resource("http://a.com/schema.json", schema)?;
+    let sch = compiler.compile("http://a.com/schema.json", &mut schemas)?;
+
+    let run = |v: &Value| {
+        let (decoded, deserialized) = (
+            DECODED.load(Ordering::SeqCst),
+            DESERIALIZED.load(Ordering::SeqCst),
+        );
+        let valid = schemas.validate(v, sch).is_ok();
+        let decoded = DECODED.load(Ordering::SeqCst) - decoded;
+        let deserialized = DESERIALIZED.load(Ordering::SeqCst) - deserialized;
+        (valid, decoded, deserialized)
+    };
+
+    // only last branch matches
+    let v = Value::String(encode_hex(&json!({"branch": 4})));
+    assert_eq!(run(&v), (true, 1, 1));
+
+    // no branch matches
+    let v = Value::String(encode_hex(&json!({"branch": 5})));
+    assert_eq!(run(&v), (false, 1, 1));
+
+    // nested content, decoded from cached content
+    let nested = Value::String(encode_hex(&json!({"branch": 4})));
+    let v = Value::String(encode_hex(&json!({"branch": 2, "nested": nested})));
+    assert_eq!(run(&v), (true, 2, 2));
+
+    // decoding errors are not cached
+    let v = json!("xyz");
+    assert_eq!(run(&v), (false, 5, 0));
+    let err = schemas.validate(&v, sch).unwrap_err();
+    assert_eq!(err.causes[0].causes.len(), 5, "{err:#}");
+    Ok(())
+}
+
+#[test]
+fn test_content_property_names() -> Result<(), Box<dyn Error>> {
+    // property names are validated in a reused buffer
+    let schema = json!({"propertyNames": {"contentEncoding": "base64"}});
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.enable_content_assertions();
+    compiler.add_resource("http://a.com/schema.json", schema)?;
+    let sch = compiler.compile("http://a.com/schema.json", &mut schemas)?;
+
+    assert!(schemas
+        .validate(&json!({"YWJj": 1, "ZGVm": 2}), sch)
+        .is_ok());
+    for v in [json!({"~~~~": 2}), json!({"YWJj": 1, "~~~~": 2})] {
+        let err = schemas.validate(&v, sch).unwrap_err();
+        assert_eq!(err.causes.len(), 1, "{v}: {err:#}");
+        assert!(
+            matches!(&err.causes[0].kind, ErrorKind::PropertyName { prop } if prop == "~~~~"),
+            "{v}: {err:#}"
+        );
+    }
+    Ok(())
+}
+
+fn decode_identity(s: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    Ok(s.as_bytes().to_vec())
+}
+
+#[test]
+fn test_content_limits() -> Result<(), Box<dyn Error>> {
+    use base64::Engine;
+
+    let schema = json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "properties": {
+            "payload": {
+                "contentEncoding": "base64",
+                "contentMediaType": "application/json",
+                "contentSchema": { "$ref": "#/$defs/node" }
+            },
+            "raw": {
+                "contentMediaType": "application/json",
+                "contentSchema": { "$ref": "#/$defs/node" }
+            },
+            "identity": {
+                "contentEncoding": "identity",
+                "contentMediaType": "application/json"
+            }
+        },
+        "$defs": {
+            "node": { "properties": { "child": { "$ref": "#/$defs/node" } } }
+        }
+    });
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.enable_content_assertions();
+    compiler.register_content_encoding(Decoder {
+        name: "identity",
+        func: decode_identity,
+    });
+    compiler.add_resource("http://a.com/schema.json", schema)?;
+    let sch = compiler.compile("http://a.com/schema.json", &mut schemas)?;
+
+    // deeply nested document, embedded in string
+    let mut nested = json!({});
+    for _ in 0..30 {
+        nested = json!({ "child": nested });
+    }
+    let text = nested.to_string();
+    let encoded = base64::engine::general_purpose::STANDARD.encode(&text);
+    let v = json!({ "payload": encoded });
+    assert!(schemas.validate(&v, sch).is_ok());
+
+    // budget applies within contentSchema
+    let opts = ValidationOptions::new().max_operations(50);
+    let Err(e) = schemas.validate_with(&v, sch, &opts) else {
+        panic!("budget must be exceeded");
+    };
+    let cause = &e.causes[0];
+    assert!(
+        matches!(cause.kind, ErrorKind::BudgetExceeded { operations: 50 }),
+        "{e:#}"
+    );
+    assert_eq!(cause.instance_location.to_string(), "/payload");
+    let opts = ValidationOptions::new().max_operations(500);
+    assert!(schemas.validate_with(&v, sch, &opts).is_ok());
+
+    // size limit, computed without decoding base64
+    let check_too_large = |v: &Value, limit: usize, want_kw: &str| {
+        let opts = ValidationOptions::new().max_decoded_content_size(limit);
+        let Err(e) = schemas.validate_with(v, sch, &opts) else {
+            panic!("{v} must be too large");
+        };
+        let leaf = &e.causes[0];
+        let ErrorKind::ContentTooLarge { kw, got, want } = &leaf.kind else {
+            panic!("want ContentTooLarge, got {e:#}");
+        };
+        assert_eq!((*kw, *got, *want), (want_kw, text.len(), limit));
+        assert_eq!(
+            leaf.kind.to_string(),
+            format!(
+                "content of {} bytes is larger than {limit} bytes",
+                text.len()
+            )
+        );
+        let opts = ValidationOptions::new().max_decoded_content_size(text.len());
+        assert!(schemas.validate_with(v, sch, &opts).is_ok());
+    };
+    check_too_large(&v, 100, "contentEncoding");
+    check_too_large(&json!({ "raw": text }), 100, "contentMediaType");
+    check_too_large(&json!({ "identity": text }), 100, "contentEncoding");
+    Ok(())
+}
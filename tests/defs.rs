@@ -0,0 +1,206 @@
+use std::error::Error;
+
+use boon::{normalize_defs, Compiler, DefsCollisionError, Draft, Schemas};
+use serde_json::{json, Value};
+
+// `definitions` and `$defs` referring to each other
+fn mixed_schema() -> Value {
+    json!({
+        "properties": {
+            "user": { "$ref": "#/definitions/user" },
+            "tag": { "$ref": "#/$defs/tag" }
+        },
+        "definitions": {
+            "user": {
+                "properties": {
+                    "name": { "type": "string" },
+                    "tags": { "items": { "$ref": "#/$defs/tag" } }
+                }
+            },
+            "id": { "type": "integer" }
+        },
+        "$defs": {
+            "tag": { "type": "string", "maxLength": 3 },
+            "owner": { "$ref": "#/definitions/user" }
+        }
+    })
+}
+
+fn check_validation(schemas: &Schemas, sch: boon::SchemaIndex) {
+    let valid = json!({"user": {"name": "a", "tags": ["x"]}, "tag": "y"});
+    assert!(schemas.validate(&valid, sch).is_ok());
+    let invalid = [
+        json!({"user": {"name": 1}}),
+        json!({"user": {"tags": ["long"]}}),
+        json!({"tag": "long"}),
+    ];
+    for v in invalid {
+        assert!(schemas.validate(&v, sch).is_err(), "{v} must be invalid");
+    }
+}
+
+#[test]
+fn test_definitions_and_defs() -> Result<(), Box<dyn Error>> {
+    let drafts = [
+        Draft::V4,
+        Draft::V6,
+        Draft::V7,
+        Draft::V2019_09,
+        Draft::V2020_12,
+    ];
+    for draft in drafts {
+        let mut compiler = Compiler::new();
+        compiler.set_default_draft(draft);
+        compiler.add_resource("http://tmp.com/schema.json", mixed_schema())?;
+        let mut schemas = Schemas::new();
+        let sch = compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+        check_validation(&schemas, sch);
+        let owner = compiler.compile("http://tmp.com/schema.json#/$defs/owner", &mut schemas)?;
+        assert!(schemas.validate(&json!({"name": 1}), owner).is_err());
+        assert!(
+            compiler.take_diagnostics().is_empty(),
+            "no diagnostics for {draft:?}"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn test_normalize_defs() -> Result<(), Box<dyn Error>> {
+    let mut schema = mixed_schema();
+    normalize_defs(&mut schema)?;
+    assert_eq!(schema.get("definitions"), None);
+    assert_eq!(schema["properties"]["user"]["$ref"], "#/$defs/user");
+    assert_eq!(schema["$defs"]["owner"]["$ref"], "#/$defs/user");
+    assert_eq!(
+        schema["$defs"]["user"]["properties"]["tags"]["items"]["$ref"],
+        "#/$defs/tag"
+    );
+    assert_eq!(schema["$defs"]["id"], json!({"type": "integer"}));
+
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://tmp.com/schema.json", schema)?;
+    let mut schemas = Schemas::new();
+    let sch = compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+    check_validation(&schemas, sch);
+    Ok(())
+}
+
+#[test]
+fn test_normalize_defs_embedded() -> Result<(), Box<dyn Error>> {
+    let mut schema = json!({
+        "items": { "$ref": "#/definitions/a" },
+        "definitions": {
+            "a": { "type": "string" },
+            "b": {
+                "$id": "b.json",
+                "items": { "$ref": "#/definitions/a" },
+                "definitions": { "a": { "type": "integer" } }
+            }
+        },
+        "const": { "$ref": "#/definitions/a" }
+    });
+    normalize_defs(&mut schema)?;
+    assert_eq!(
+        schema,
+        json!({
+            "items": { "$ref": "#/$defs/a" },
+            "$defs": {
+                "a": { "type": "string" },
+                "b": {
+                    "$id": "b.json",
+                    "items": { "$ref": "#/$defs/a" },
+                    "$defs": { "a": { "type": "integer" } }
+                }
+            },
+            "const": { "$ref": "#/definitions/a" }
+        })
+    );
+    Ok(())
+}
+
+#[test]
+fn test_normalize_defs_collision() {
+    let mut schema = json!({
+        "definitions": {
+            "same": { "type": "string" },
+            "a/b": { "type": "string" },
+            "c": { "type": "string" }
+        },
+        "$defs": {
+            "same": { "type": "string" },
+            "a/b": { "type": "integer" },
+            "c": { "type": "integer" }
+        }
+    });
+    let before = schema.clone();
+    let err = normalize_defs(&mut schema).unwrap_err();
+    assert_eq!(
+        err,
+        DefsCollisionError {
+            collisions: vec!["/definitions/a~1b".to_owned(), "/definitions/c".to_owned()]
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "definitions collide with different schemas in $defs: /definitions/a~1b, /definitions/c"
+    );
+    assert_eq!(schema, before, "schema must be unchanged");
+}
+
+#[test]
+fn test_normalize_defs_value_keyword_names() -> Result<(), Box<dyn Error>> {
+    // `default` is a property name here, not a keyword
+    let mut schema = json!({
+        "properties": {
+            "default": { "$ref": "#/definitions/a" },
+            "enum": { "items": [{ "$ref": "#/definitions/a" }] }
+        },
+        "definitions": { "a": { "type": "string" } },
+        "default": { "$ref": "#/definitions/a" }
+    });
+    normalize_defs(&mut schema)?;
+    assert_eq!(schema["properties"]["default"]["$ref"], "#/$defs/a");
+    assert_eq!(
+        schema["properties"]["enum"]["items"][0]["$ref"],
+        "#/$defs/a"
+    );
+    assert_eq!(schema["default"]["$ref"], "#/definitions/a");
+    Ok(())
+}
+
+#[test]
+fn test_normalize_defs_absolute_refs() -> Result<(), Box<dyn Error>> {
+    let mut schema = json!({
+        "$id": "http://a.com/root.json",
+        "properties": {
+            "a": { "$ref": "http://a.com/root.json#/definitions/a" },
+            "b": { "$ref": "root.json#/definitions/a" },
+            "c": { "$ref": "http://a.com/other.json#/definitions/a" }
+        },
+        "definitions": {
+            "a": { "type": "string" },
+            "b": {
+                "$id": "b.json",
+                "items": { "$ref": "http://a.com/root.json#/definitions/a" },
+                "definitions": { "a": { "type": "integer" } }
+            }
+        },
+        "items": { "$ref": "b.json#/definitions/a" }
+    });
+    normalize_defs(&mut schema)?;
+    assert_eq!(
+        schema["properties"],
+        json!({
+            "a": { "$ref": "http://a.com/root.json#/$defs/a" },
+            "b": { "$ref": "root.json#/$defs/a" },
+            "c": { "$ref": "http://a.com/other.json#/definitions/a" }
+        })
+    );
+    assert_eq!(
+        schema["$defs"]["b"]["items"]["$ref"],
+        "http://a.com/root.json#/$defs/a"
+    );
+    assert_eq!(schema["items"]["$ref"], "b.json#/$defs/a");
+    Ok(())
+}
@@ -0,0 +1,114 @@
+#![cfg(feature = "difftest")]
+
+use std::{error::Error, fs};
+
+use boon::{
+    difftest::{load_corpus, CommandValidator, CorpusEntry, DiffTest},
+    Compiler,
+};
+use serde_json::{json, Value};
+
+fn corpus(instances: &[Value]) -> Vec<CorpusEntry> {
+    instances
+        .iter()
+        .enumerate()
+        .map(|(i, v)| CorpusEntry {
+            source: format!("#{i}"),
+            instance: v.clone(),
+        })
+        .collect()
+}
+
+#[test]
+fn test_difftest() -> Result<(), Box<dyn Error>> {
+    let mut compiler = Compiler::new();
+    let schema = json!({"type": "integer", "minimum": 0});
+    let test = DiffTest::new(&mut compiler, "http://tmp.com/schema.json", schema.clone())?;
+
+    // treats 1.0 as non-integer, like some older validators
+    let other = |sch: &Value, v: &Value| -> Result<bool, Box<dyn Error>> {
+        assert_eq!(sch, &schema);
+        match v {
+            Value::Null => Err("null not supported".into()),
+            Value::Number(n) => Ok(n.as_u64().is_some()),
+            _ => Ok(false),
+        }
+    };
+    let report = test.run(
+        &other,
+        &corpus(&[json!(1), json!(1.0), json!(-1), json!(null)]),
+    );
+    assert_eq!(report.compared, 3);
+    assert_eq!(
+        report.failures,
+        [("#3".to_owned(), "null not supported".to_owned())]
+    );
+    assert_eq!(report.disagreements.len(), 1);
+    let d = &report.disagreements[0];
+    assert_eq!(d.source, "#1");
+    assert_eq!(d.instance, json!(1.0));
+    assert_eq!(d.schema_location, "http://tmp.com/schema.json#");
+    assert!(d.boon_valid);
+    assert_eq!(d.boon_output, None);
+    assert!(!report.is_clean());
+
+    // boon's error is reported, when boon says invalid
+    let other = |_: &Value, _: &Value| -> Result<bool, Box<dyn Error>> { Ok(true) };
+    let report = test.run(&other, &corpus(&[json!(-1)]));
+    let output = report.disagreements[0].boon_output.as_ref().unwrap();
+    assert_eq!(output["valid"], false);
+    assert!(report
+        .to_string()
+        .contains("#0: boon says invalid, other says valid"));
+    Ok(())
+}
+
+#[test]
+fn test_load_corpus() -> Result<(), Box<dyn Error>> {
+    let dir = std::env::temp_dir().join(format!("boon-difftest-{}", std::process::id()));
+    fs::create_dir_all(dir.join("sub"))?;
+    fs::write(dir.join("b.json"), "[1, 2]")?;
+    fs::write(dir.join("a.jsonl"), "1\n\n\"x\"\n")?;
+    fs::write(dir.join("sub/c.json"), "{}")?;
+    fs::write(dir.join("readme.txt"), "not json")?;
+    let corpus = load_corpus(&dir);
+    let bad = fs::write(dir.join("d.jsonl"), "1\n{").map(|_| load_corpus(&dir));
+    fs::remove_dir_all(&dir)?;
+
+    let corpus = corpus?;
+    let instances: Vec<_> = corpus.iter().map(|e| &e.instance).collect();
+    assert_eq!(
+        instances,
+        [&json!(1), &json!("x"), &json!([1, 2]), &json!({})]
+    );
+    assert!(corpus[1].source.ends_with("a.jsonl:3"));
+    let err = bad?.unwrap_err();
+    assert!(err.path.ends_with("d.jsonl"));
+    assert!(format!("{err:#}").contains("line 2"), "{err:#}");
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn test_command_validator() -> Result<(), Box<dyn Error>> {
+    let mut compiler = Compiler::new();
+    let test = DiffTest::new(
+        &mut compiler,
+        "http://tmp.com/schema.json",
+        json!({"type": "string"}),
+    )?;
+
+    // agrees with boon, except for "b"
+    let script = r#"read s; read i; case "$i" in 1) exit 1;; \"b\") exit 1;; 2) echo oops >&2; exit 3;; esac"#;
+    let other = CommandValidator::new("sh").arg("-c").arg(script);
+    let report = test.run(
+        &other,
+        &corpus(&[json!("a"), json!(1), json!("b"), json!(2)]),
+    );
+    assert_eq!(report.compared, 3);
+    assert_eq!(report.disagreements.len(), 1);
+    assert_eq!(report.disagreements[0].instance, json!("b"));
+    assert_eq!(report.failures.len(), 1);
+    assert!(report.failures[0].1.contains("oops"), "{report}");
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use std::error::Error;
+
+use boon::{CompileError, Compiler, EncodingError, InstanceError, Schemas};
+use serde_json::json;
+
+fn utf16(s: &str, be: bool) -> Vec<u8> {
+    s.encode_utf16()
+        .flat_map(|u| if be { u.to_be_bytes() } else { u.to_le_bytes() })
+        .collect()
+}
+
+#[test]
+fn test_add_resource_bytes() -> Result<(), Box<dyn Error>> {
+    let schema = r#"{"type": "string", "minLength": 2}"#;
+    let inputs = [
+        [b"\xEF\xBB\xBF".as_slice(), schema.as_bytes()].concat(),
+        [b"\xFF\xFE".as_slice(), &utf16(schema, false)].concat(),
+        [b"\xFE\xFF".as_slice(), &utf16(schema, true)].concat(),
+        utf16(schema, false),
+        utf16(schema, true),
+    ];
+    for (i, bytes) in inputs.iter().enumerate() {
+        let url = format!("http://tmp.com/schema{i}.json");
+        let mut schemas = Schemas::new();
+        let mut compiler = Compiler::new();
+        compiler.add_resource_bytes(&url, bytes)?;
+        let sch_index = compiler.compile(&url, &mut schemas)?;
+        assert!(schemas.validate(&json!("ab"), sch_index).is_ok());
+        assert!(schemas.validate(&json!("a"), sch_index).is_err());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_add_resource_bytes_error() {
+    let mut compiler = Compiler::new();
+    // unpaired surrogate
+    let bytes = [b"\xFF\xFE".as_slice(), &utf16("{}", false), b"\x00\xD8"].concat();
+    let result = compiler.add_resource_bytes("http://tmp.com/schema.json", &bytes);
+    let Err(CompileError::LoadUrlError { src, .. }) = result else {
+        panic!("want LoadUrlError, got {result:?}");
+    };
+    let err = src.downcast_ref::<EncodingError>().unwrap();
+    assert_eq!((err.encoding, err.offset), ("utf-16le", 6));
+
+    let result = compiler.add_resource_bytes("http://tmp.com/schema.json", b"\x00\x00\x00{");
+    let Err(CompileError::LoadUrlError { src, .. }) = result else {
+        panic!("want LoadUrlError, got {result:?}");
+    };
+    assert_eq!(
+        src.to_string(),
+        "invalid utf-32be at byte offset 0: encoding not supported"
+    );
+}
+
+#[test]
+fn test_file_with_bom() -> Result<(), Box<dyn Error>> {
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    let sch_index = compiler.compile("tests/examples/schema-utf16le-bom.json", &mut schemas)?;
+    assert!(schemas.validate(&json!("x"), sch_index).is_ok());
+    assert!(schemas.validate(&json!(1), sch_index).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_parse_and_validate_bytes() -> Result<(), Box<dyn Error>> {
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://tmp.com/schema.json", json!({"type": "object"}))?;
+    let sch_index = compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+
+    let v = schemas
+        .parse_and_validate_bytes(b"\xEF\xBB\xBF{\"a\": 1}", sch_index)
+        .map_err(|e| e.to_string())?;
+    assert_eq!(v, json!({"a": 1}));
+
+    let result = schemas.parse_and_validate_bytes(b"{\"a\": \"\xFF\"}", sch_index);
+    assert!(matches!(
+        result,
+        Err(InstanceError::Decode {
+            format: "utf-8",
+            offset: 7,
+            ..
+        })
+    ));
+    Ok(())
+}
@@ -0,0 +1,1037 @@
+use std::{borrow::Cow, error::Error};
+
+use boon::{
+    Compiler, DisplayOptions, ErrorKind, InstanceError, InstanceLocation, LimitInfo, Metrics,
+    Schemas, ValidationError, ValidationOptions,
+};
+use serde_json::{json, Number, Value};
+
+#[test]
+fn test_into_owned() -> Result<(), Box<dyn Error>> {
+    let (want, err): (String, ValidationError<'static, 'static>) = {
+        let schema = json!({
+            "properties": {
+                "name": { "pattern": "^[a-z]+$" },
+                "age": { "minimum": 18 },
+                "kind": { "enum": ["cat", "dog"] }
+            },
+            "required": ["name", "owner"]
+        });
+        let instance = json!({"name": "Tom1", "age": 5, "kind": "cow"});
+
+        let mut schemas = Schemas::new();
+        let mut compiler = Compiler::new();
+        compiler.add_resource("http://tmp.com/schema.json", schema)?;
+        let sch_index = compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+        let err = schemas.validate(&instance, sch_index).unwrap_err();
+        (format!("{err:#}"), err.into_owned())
+    };
+    // schemas and instance are dropped by now
+    assert_eq!(format!("{err:#}"), want);
+    assert_eq!(err.schema_url, "http://tmp.com/schema.json#");
+
+    let kinds = err.causes.iter().map(|e| &e.kind).collect::<Vec<_>>();
+    assert!(kinds
+        .iter()
+        .any(|k| matches!(k, ErrorKind::Required { want } if want == &["owner"])));
+    assert!(kinds.iter().any(
+        |k| matches!(k, ErrorKind::Pattern { got, want } if got == "Tom1" && want == "^[a-z]+$")
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_parse_and_validate() -> Result<(), Box<dyn Error>> {
+    let schema = json!({"required": ["name"]});
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://tmp.com/schema.json", schema)?;
+    let sch_index = compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+
+    let v = schemas
+        .parse_and_validate(r#"{"name": "x"}"#, sch_index)
+        .map_err(|e| e.to_string())?;
+    assert_eq!(v, json!({"name": "x"}));
+
+    let err = schemas
+        .parse_and_validate("{\n  \"name\": }", sch_index)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        InstanceError::Parse {
+            line: 2,
+            column: 11,
+            ..
+        }
+    ));
+    assert_eq!(
+        err.to_string(),
+        "instance is not valid json at line 2 column 11: expected value"
+    );
+    let output = serde_json::to_value(&err)?;
+    assert_eq!(output["valid"], json!(false));
+    assert_eq!(output["line"], json!(2));
+
+    let err = schemas.parse_and_validate("{}", sch_index).unwrap_err();
+    let InstanceError::Validation(e) = &err else {
+        panic!("want validation error, got {err:?}");
+    };
+    assert_eq!(err.to_string(), e.to_string());
+    assert_eq!(
+        serde_json::to_value(&err)?,
+        serde_json::to_value(e.basic_output())?
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_required_details() -> Result<(), Box<dyn Error>> {
+    let schema = json!({
+        "properties": {
+            "person": { "required": ["name", "age", "email"] }
+        }
+    });
+    let instance = json!({"person": {"age": 1}});
+
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://tmp.com/schema.json", schema)?;
+    let sch_index = compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+    let err = schemas.validate(&instance, sch_index).unwrap_err();
+    let err = &err.causes[0];
+    assert!(matches!(err.kind, ErrorKind::Required { .. }));
+    assert!(err.causes.is_empty());
+
+    let opts = ValidationOptions::new().required_details(true);
+    let err = schemas
+        .validate_with(&instance, sch_index, &opts)
+        .unwrap_err();
+    let err = &err.causes[0];
+    assert!(matches!(&err.kind, ErrorKind::Required { want } if want == &["name", "email"]));
+    let details = err
+        .causes
+        .iter()
+        .map(|e| {
+            let kw_path = e.kind.keyword_path().map(|p| p.to_string());
+            (e.instance_location.to_string(), kw_path)
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        details,
+        [
+            ("/person/name".to_owned(), Some("required/0".to_owned())),
+            ("/person/email".to_owned(), Some("required/2".to_owned())),
+        ]
+    );
+    assert_eq!(err.causes[1].kind.to_string(), "missing property 'email'");
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "formats-net")]
+fn test_display_options() -> Result<(), Box<dyn Error>> {
+    let schema = json!({
+        "properties": {
+            "s": { "pattern": "^[a-z]*$", "format": "email" },
+            "n": { "maximum": 3 },
+            "e": { "enum": ["aaaaaaaaaaaa", "b"] }
+        },
+        "propertyNames": { "maxLength": 12 }
+    });
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.enable_format_assertions();
+    compiler.add_resource("http://tmp.com/schema.json", schema)?;
+    let sch_index = compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+    let long = "X".repeat(100);
+    let instance = json!({ "s": long, "n": 12345, "e": 1, "secret-property": 1 });
+    let Err(e) = schemas.validate(&instance, sch_index) else {
+        panic!("validation must fail");
+    };
+    let messages = |opts: &DisplayOptions| {
+        let mut v = vec![];
+        let mut stack = vec![&e];
+        while let Some(e) = stack.pop() {
+            if !matches!(e.kind, ErrorKind::Schema { .. } | ErrorKind::Group) {
+                v.push(e.kind.display_with(opts).to_string());
+            }
+            stack.extend(e.causes.iter());
+        }
+        v.sort();
+        v
+    };
+
+    let truncated = format!("'{}'... (100 chars)", "X".repeat(64));
+    let mut want = [
+        "invalid property 'secret-property'".to_owned(),
+        "length must be <=12, but got 15".to_owned(),
+        "must be <=3, but got 12345".to_owned(),
+        format!("{truncated} does not match pattern '^[a-z]*$'"),
+        format!("{truncated} is not valid email: missing @"),
+        "value must be one of 'aaaaaaaaaaaa', 'b'".to_owned(),
+    ];
+    want.sort();
+    assert_eq!(messages(&DisplayOptions::default()), want);
+    assert!(e.to_string().contains(&truncated), "{e}");
+
+    let opts = DisplayOptions::new().max_value_chars(4);
+    let mut want = [
+        "invalid property 'secr'... (15 chars)",
+        "length must be <=12, but got 15",
+        "must be <=3, but got 1234... (5 chars)",
+        "'XXXX'... (100 chars) does not match pattern '^[a-'... (8 chars)",
+        "'XXXX'... (100 chars) is not valid email: missing @",
+        "value must be one of 'aaaa'... (12 chars), 'b'",
+    ];
+    want.sort();
+    assert_eq!(messages(&opts), want);
+
+    let opts = DisplayOptions::new().redact_values(true);
+    let mut want = [
+        "<string of 100 chars> does not match pattern '^[a-z]*$'",
+        "length must be <=12, but got 15",
+        "<string of 100 chars> is not valid email: missing @",
+        "invalid property <string of 15 chars>",
+        "must be <=3, but got <number>",
+        "value must be one of 'aaaaaaaaaaaa', 'b'",
+    ];
+    want.sort();
+    assert_eq!(messages(&opts), want);
+    let display = e.display_with(&opts).to_string();
+    assert!(
+        !display.contains("XXXX") && !display.contains("12345"),
+        "{display}"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_display_long_lists() -> Result<(), Box<dyn Error>> {
+    let names = (0..5000).map(|i| format!("p{i}")).collect::<Vec<_>>();
+    let schema = json!({
+        "properties": {
+            "e": { "enum": names },
+            "r": { "required": names }
+        }
+    });
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://tmp.com/schema.json", schema)?;
+    let sch_index = compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+    let instance = json!({ "e": "x", "r": { "p1": 1 } });
+    let Err(e) = schemas.validate(&instance, sch_index) else {
+        panic!("validation must fail");
+    };
+    let mut kinds = e.causes.iter().map(|c| &c.kind).collect::<Vec<_>>();
+    kinds.sort_by_key(|k| k.to_string());
+    let [required, enum_] = kinds[..] else {
+        panic!("want two errors: {e:#}");
+    };
+
+    // full list is still available
+    let ErrorKind::Enum { want, .. } = enum_ else {
+        panic!("want enum error, got {enum_:?}");
+    };
+    assert_eq!(want.len(), 5000);
+    let ErrorKind::Required { want } = required else {
+        panic!("want required error, got {required:?}");
+    };
+    assert_eq!(want.len(), 4999);
+
+    let first = |n: usize, skip: usize| {
+        (0..5000)
+            .filter(|i| *i != skip)
+            .take(n)
+            .map(|i| format!("'p{i}'"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    assert_eq!(
+        required.to_string(),
+        format!("missing properties {} and 4989 more", first(10, 1))
+    );
+    assert_eq!(
+        enum_.to_string(),
+        format!("value must be one of {} and 4990 more", first(10, 5000))
+    );
+    let opts = DisplayOptions::new().max_list_items(2);
+    assert_eq!(
+        enum_.display_with(&opts).to_string(),
+        "value must be one of 'p0', 'p1' and 4998 more"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_attach_examples() -> Result<(), Box<dyn Error>> {
+    let schema = json!({
+        "properties": {
+            "a": { "type": "integer", "examples": [42, 7], "default": 0 },
+            "b": { "type": "integer", "default": 0 },
+            "c": { "type": "object", "examples": [{"x": 1}] },
+            "d": { "type": "integer" }
+        }
+    });
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://tmp.com/schema.json", schema)?;
+    let sch_index = compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+    let instance = json!({ "a": "x", "b": "x", "c": 1, "d": "x" });
+
+    // disabled by default
+    let Err(e) = schemas.validate(&instance, sch_index) else {
+        panic!("validation must fail");
+    };
+    assert!(e.causes.iter().all(|c| c.example.is_none()));
+
+    let opts = ValidationOptions::new().attach_examples(true);
+    let Err(e) = schemas.validate_with(&instance, sch_index, &opts) else {
+        panic!("validation must fail");
+    };
+    let mut got = e
+        .causes
+        .iter()
+        .map(|c| {
+            let example = c.example.as_deref().cloned();
+            (c.instance_location.to_string(), example)
+        })
+        .collect::<Vec<_>>();
+    got.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(
+        got,
+        [
+            ("/a".to_owned(), Some(json!(42))),
+            ("/b".to_owned(), Some(json!(0))),
+            ("/c".to_owned(), Some(json!({"x": 1}))),
+            ("/d".to_owned(), None),
+        ]
+    );
+    let display = e.to_string();
+    assert!(
+        display.contains("at '/a': want integer, but got string, e.g. 42"),
+        "{display}"
+    );
+    assert!(display.contains(r#"e.g. {"x":1}"#), "{display}");
+
+    let output = e.basic_output().to_string();
+    assert!(output.contains(r#""example":42"#), "{output}");
+
+    let a = compiler.compile("http://tmp.com/schema.json#/properties/a", &mut schemas)?;
+    assert_eq!(schemas.example_for(a), Some(&json!(42)));
+    assert_eq!(schemas.example_for(sch_index), None);
+
+    // errors with causes get no example
+    let schema = json!({
+        "examples": [true],
+        "allOf": [{ "type": "string" }, { "type": "boolean", "examples": [false] }]
+    });
+    compiler.add_resource("http://tmp.com/branch.json", schema)?;
+    let sch_index = compiler.compile("http://tmp.com/branch.json", &mut schemas)?;
+    let instance = json!(1);
+    let Err(e) = schemas.validate_with(&instance, sch_index, &opts) else {
+        panic!("validation must fail");
+    };
+    assert!(matches!(e.kind, ErrorKind::Schema { .. }));
+    let e = &e.causes[0];
+    assert!(matches!(e.kind, ErrorKind::AllOf));
+    assert_eq!(e.example, None);
+    let examples = e
+        .causes
+        .iter()
+        .map(|c| c.example.as_deref())
+        .collect::<Vec<_>>();
+    assert_eq!(examples, [None, Some(&json!(false))]);
+    Ok(())
+}
+
+#[test]
+fn test_type_error_details() -> Result<(), Box<dyn Error>> {
+    let tests = [
+        (
+            json!({"type": "integer"}),
+            json!(3.5),
+            "want integer, but got number (3.5)",
+        ),
+        (
+            json!({"type": "number"}),
+            json!("3.5"),
+            "want number, but got string; did you mean 3.5 without quotes?",
+        ),
+        (
+            json!({"type": "integer"}),
+            json!("3"),
+            "want integer, but got string; did you mean 3 without quotes?",
+        ),
+        (
+            json!({"type": "integer"}),
+            json!("3.5"),
+            "want integer, but got string",
+        ),
+        (
+            json!({"type": ["boolean", "null"]}),
+            json!("true"),
+            "want null or boolean, but got string; did you mean true without quotes?",
+        ),
+        (
+            json!({"type": "boolean"}),
+            json!(" true"),
+            "want boolean, but got string",
+        ),
+        (
+            json!({"type": "object"}),
+            json!("{}"),
+            "want object, but got string",
+        ),
+        (
+            json!({"type": "string"}),
+            json!(3),
+            "want string, but got number",
+        ),
+    ];
+    for (schema, instance, want) in tests {
+        let mut schemas = Schemas::new();
+        let mut compiler = Compiler::new();
+        compiler.add_resource("http://tmp.com/schema.json", schema)?;
+        let sch_index = compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+        let e = schemas.validate(&instance, sch_index).unwrap_err();
+        let kind = &e.causes[0].kind;
+        assert_eq!(kind.to_string(), want);
+        if let ErrorKind::Type { value, .. } = kind {
+            assert_eq!(value.as_deref(), Some(&instance));
+        } else {
+            panic!("want type error, got {kind:?}");
+        }
+        let opts = DisplayOptions::new().redact_values(true);
+        let redacted = kind.display_with(&opts).to_string();
+        assert!(
+            !redacted.contains("(") && !redacted.contains("mean"),
+            "{redacted}"
+        );
+    }
+
+    // hint is shortened like other values
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://tmp.com/schema.json", json!({"type": "integer"}))?;
+    let sch_index = compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+    let instance = json!("123456789");
+    let e = schemas.validate(&instance, sch_index).unwrap_err();
+    let opts = DisplayOptions::new().max_value_chars(5);
+    assert_eq!(
+        e.causes[0].kind.display_with(&opts).to_string(),
+        "want integer, but got string; did you mean 12345... (9 chars) without quotes?"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_special_chars_in_locations() -> Result<(), Box<dyn Error>> {
+    let schema = json!({
+        "$ref": "#/$defs/obj",
+        "$defs": {
+            "obj": {
+                "properties": { "a/b~c%d e": { "type": "string" } },
+                "patternProperties": { "^/api/.*$": { "type": "integer" } },
+                "dependentRequired": { "x/y": ["z"] }
+            },
+            "refs": {
+                "properties": {
+                    "prop": { "$ref": "#/$defs/obj/properties/a~1b~0c%25d%20e" },
+                    "pattern": { "$ref": "#/$defs/obj/patternProperties/%5E~1api~1.*$" }
+                }
+            }
+        }
+    });
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://tmp.com/schema.json", schema)?;
+    let sch_index = compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+
+    // $refs to those locations resolve
+    let refs = compiler.compile("http://tmp.com/schema.json#/$defs/refs", &mut schemas)?;
+    assert!(schemas
+        .validate(&json!({"prop": "x", "pattern": 1}), refs)
+        .is_ok());
+    assert!(schemas.validate(&json!({"prop": 1}), refs).is_err());
+    assert!(schemas.validate(&json!({"pattern": "x"}), refs).is_err());
+
+    let instance = json!({ "a/b~c%d e": 1, "/api/x": "s", "x/y": 1 });
+    let e = schemas.validate(&instance, sch_index).unwrap_err();
+    let output = serde_json::to_value(e.basic_output())?;
+    let mut got = vec![];
+    for unit in output["errors"].as_array().unwrap() {
+        let Some(abs) = unit["absoluteKeywordLocation"].as_str() else {
+            continue;
+        };
+        let kw_loc = unit["keywordLocation"].as_str().unwrap();
+        // absolute location is valid url, whose fragment is valid json-pointer
+        let url = url::Url::parse(abs)?;
+        let frag = percent_encoding::percent_decode_str(url.fragment().unwrap()).decode_utf8()?;
+        assert_eq!(format!("/$ref{}", &frag["/$defs/obj".len()..]), kw_loc);
+        let tokens = frag
+            .split('/')
+            .skip(3)
+            .map(|t| t.replace("~1", "/").replace("~0", "~"))
+            .collect::<Vec<_>>();
+        if !tokens.is_empty() {
+            got.push(tokens);
+        }
+    }
+    got.sort();
+    assert_eq!(
+        got,
+        [
+            vec!["dependentRequired", "x/y"],
+            vec!["patternProperties", "^/api/.*$", "type"],
+            vec!["properties", "a/b~c%d e", "type"],
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_parse_and_validate_strict() -> Result<(), Box<dyn Error>> {
+    let schema = json!({"required": ["name"]});
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://tmp.com/schema.json", schema)?;
+    let sch_index = compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+
+    let text = r#"{"name": "x", "list": [{"a": 1, "b": {"a": 1}}, {"c": 1}]}"#;
+    let v = schemas
+        .parse_and_validate_strict(text, sch_index)
+        .map_err(|e| e.to_string())?;
+    assert_eq!(v, serde_json::from_str::<serde_json::Value>(text)?);
+
+    let tests = [
+        (r#"{"name": "x", "name": 1}"#, "", "name"),
+        (
+            r#"{"name": "x", "a": {"b": {"c": 1, "c": 2}}}"#,
+            "/a/b",
+            "c",
+        ),
+        (
+            r#"{"name": "x", "l": [1, {"a~/b": 1, "a~/b": 1}]}"#,
+            "/l/1",
+            "a~/b",
+        ),
+    ];
+    for (text, loc, dup) in tests {
+        // non-strict keeps last value
+        assert!(schemas.parse_and_validate(text, sch_index).is_ok());
+
+        let err = schemas
+            .parse_and_validate_strict(text, sch_index)
+            .unwrap_err();
+        let InstanceError::DuplicateKey {
+            instance_location,
+            key,
+        } = &err
+        else {
+            panic!("want DuplicateKey, got {err:?}");
+        };
+        assert_eq!(instance_location.to_string(), loc);
+        assert_eq!(key, dup);
+        let output = serde_json::to_value(&err)?;
+        assert_eq!(output["valid"], json!(false));
+        assert_eq!(output["instanceLocation"], json!(loc));
+    }
+    let err = schemas
+        .parse_and_validate_strict(r#"{"a": {"b": 1, "b": 2}}"#, sch_index)
+        .unwrap_err();
+    assert_eq!(err.to_string(), "instance at '/a' has duplicate key 'b'");
+
+    // syntax errors and validation errors are reported as before
+    let err = schemas
+        .parse_and_validate_strict("{\n  \"name\": }", sch_index)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        InstanceError::Parse {
+            line: 2,
+            column: 11,
+            ..
+        }
+    ));
+    let err = schemas
+        .parse_and_validate_strict("{} []", sch_index)
+        .unwrap_err();
+    assert!(matches!(err, InstanceError::Parse { .. }), "{err:?}");
+    let err = schemas
+        .parse_and_validate_strict("{}", sch_index)
+        .unwrap_err();
+    assert!(matches!(err, InstanceError::Validation(_)));
+    Ok(())
+}
+
+#[test]
+fn test_lone_surrogate() -> Result<(), Box<dyn Error>> {
+    let schema = json!({"required": ["name"]});
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://tmp.com/schema.json", schema)?;
+    let sch_index = compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+
+    // valid pairs and escaped backslashes
+    for text in [
+        r#"{"name": "\ud83d\ude00"}"#,
+        r#"{"name": "\\ud800"}"#,
+        r#"{"name": "\"\\\ud83d\ude00"}"#,
+    ] {
+        assert!(
+            schemas.parse_and_validate(text, sch_index).is_ok(),
+            "{text}"
+        );
+        assert!(
+            schemas.parse_and_validate_strict(text, sch_index).is_ok(),
+            "{text}"
+        );
+    }
+
+    let tests = [
+        (r#"{"name": "x\ud800y"}"#, "/name", 11),
+        (r#"{"name": "\udc00"}"#, "/name", 10),
+        (r#"{"name": "\ud800\u0041"}"#, "/name", 10),
+        (r#"{"name": "\ud800\ud800"}"#, "/name", 10),
+        (r#"{"name": 1, "l": [1, "\\", "\"\ud800"]}"#, "/l/2", 30),
+        (
+            r#"{"name": 1,
+ "o": {"\udfff": 1}}"#,
+            "/o",
+            20,
+        ),
+    ];
+    for (text, loc, want_offset) in tests {
+        for strict in [false, true] {
+            let err = if strict {
+                schemas.parse_and_validate_strict(text, sch_index)
+            } else {
+                schemas.parse_and_validate(text, sch_index)
+            }
+            .unwrap_err();
+            let InstanceError::LoneSurrogate {
+                instance_location,
+                offset,
+            } = &err
+            else {
+                panic!("want LoneSurrogate for {text}, got {err:?}");
+            };
+            assert_eq!(instance_location.to_string(), loc, "{text}");
+            assert_eq!(*offset, want_offset, "{text}");
+            assert_eq!(&text[*offset..*offset + 2], "\\u");
+        }
+    }
+    let err = schemas
+        .parse_and_validate(r#"{"a": ["\ud800"]}"#, sch_index)
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "instance at '/a/0' has lone surrogate escape at byte offset 8"
+    );
+    assert_eq!(
+        serde_json::to_value(&err)?["instanceLocation"],
+        json!("/a/0")
+    );
+
+    // syntax error before the escape is reported as is
+    let err = schemas
+        .parse_and_validate(r#"{"name" 1, "a": "\ud800"}"#, sch_index)
+        .unwrap_err();
+    assert!(matches!(err, InstanceError::Parse { .. }), "{err:?}");
+    Ok(())
+}
+
+#[test]
+fn test_custom_errors() -> Result<(), Box<dyn Error>> {
+    let schema = json!({"properties": {"age": {"minimum": 0}}});
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://tmp.com/schema.json", schema)?;
+    let sch_index = compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+    let instance = json!({"age": -1, "name": "x"});
+
+    // business rule nested in the error tree of schema validation
+    let e = schemas.validate(&instance, sch_index).unwrap_err();
+    let loc = InstanceLocation {
+        tokens: vec!["name".into()],
+    };
+    let custom = ValidationError::custom(loc, "name-length", "name too short");
+    let e = e.with_causes([custom]);
+    let ErrorKind::Custom { code, message } = &e.causes[1].kind else {
+        panic!("want Custom, got {:?}", e.causes[1].kind);
+    };
+    assert_eq!(
+        (code.as_str(), message.as_str()),
+        ("name-length", "name too short")
+    );
+    let want = [
+        "jsonschema validation failed with http://tmp.com/schema.json#",
+        "- at '/age' [S#/properties/age/minimum]: must be >=0, but got -1",
+        "- at '/name' [S#]: name too short",
+    ];
+    assert_eq!(format!("{e:#}"), want.join("\n"));
+
+    // serialized like native errors
+    let output = serde_json::to_value(e.basic_output())?;
+    assert_eq!(output["errors"][1]["keywordLocation"], "");
+    assert_eq!(output["errors"][1]["instanceLocation"], "/name");
+    assert_eq!(output["errors"][1]["error"], "name too short");
+
+    // survives into_owned
+    let e = e.into_owned();
+    assert!(matches!(e.causes[1].kind, ErrorKind::Custom { .. }));
+
+    // custom error as root
+    let e = ValidationError::custom(InstanceLocation::default(), "order", "invalid order")
+        .with_causes([ValidationError::custom(
+            InstanceLocation::default(),
+            "x",
+            "nested",
+        )]);
+    assert_eq!(e.to_string(), "at '': invalid order\n- at '': nested");
+    Ok(())
+}
+
+#[test]
+fn test_leaf_items_errors() -> Result<(), Box<dyn Error>> {
+    let items = json!({
+        "type": ["number", "string"],
+        "minimum": 0,
+        "exclusiveMaximum": 100,
+        "multipleOf": 0.5,
+        "maxLength": 3,
+        "pattern": "^[a-z]"
+    });
+    // allOf makes items schema not leaf, so that it takes the usual path
+    let mut not_leaf = items.clone();
+    not_leaf["allOf"] = json!([true]);
+    let instance = json!([1, -1, 100, 0.25, "ab", "abcd", "1", null, true, 2.5, [1]]);
+
+    let mut outputs = vec![];
+    for (draft, items) in [
+        ("http://json-schema.org/draft-07/schema", &items),
+        ("http://json-schema.org/draft-07/schema", &not_leaf),
+        ("https://json-schema.org/draft/2020-12/schema", &items),
+        ("https://json-schema.org/draft/2020-12/schema", &not_leaf),
+    ] {
+        let schema = json!({"$schema": draft, "items": items});
+        let mut schemas = Schemas::new();
+        let mut compiler = Compiler::new();
+        compiler.add_resource("http://tmp.com/schema.json", schema)?;
+        let sch_index = compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+        let opts = ValidationOptions::new().collect_metrics(true);
+        let e = schemas
+            .validate_with(&instance, sch_index, &opts)
+            .unwrap_err();
+        let valid = json!([1, 2, "a"]);
+        let metrics = match schemas.validate_with(&valid, sch_index, &opts) {
+            Ok(out) => out.metrics,
+            Err(e) => panic!("{e}"),
+        };
+        outputs.push((
+            format!("{e:#}"),
+            serde_json::to_value(e.detailed_output())?,
+            metrics,
+        ));
+    }
+    for pair in outputs.chunks(2) {
+        assert_eq!(pair[0].0, pair[1].0);
+        assert_eq!(pair[0].1, pair[1].1);
+        // each item still counts as evaluation
+        assert_eq!(pair[0].2, Some(Metrics { evaluations: 4 }));
+    }
+    assert_eq!(outputs[0].0.lines().count(), 9, "{}", outputs[0].0);
+    Ok(())
+}
+
+#[test]
+fn test_by_instance_location() -> Result<(), Box<dyn Error>> {
+    let schema = json!({
+        "$defs": { "pos": { "minimum": 0 } },
+        "properties": {
+            "name": { "type": "string", "minLength": 2 },
+            "items": {
+                "items": {
+                    "allOf": [{ "$ref": "#/$defs/pos" }, { "$ref": "#/$defs/pos" }],
+                    "multipleOf": 2
+                },
+                "maxItems": 11
+            },
+            "tags": { "items": { "maxLength": 3 } }
+        },
+        "required": ["name", "id"]
+    });
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://tmp.com/schema.json", schema)?;
+    let sch_index = compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+    let instance = json!({
+        "name": "a",
+        "items": [0, 2, -3, 4, 6, 8, 10, 12, 14, 16, -12, 1],
+        "tags": ["ok", "toolong"]
+    });
+    let err = schemas.validate(&instance, sch_index).unwrap_err();
+
+    let got = err
+        .by_instance_location()
+        .into_iter()
+        .map(|(loc, leaves)| {
+            let leaves = leaves
+                .into_iter()
+                .map(|leaf| format!("{} {}", leaf.code, leaf.keyword_location))
+                .collect::<Vec<_>>();
+            (loc.to_string(), leaves)
+        })
+        .collect::<Vec<_>>();
+    let want = [
+        ("", vec!["required /required"]),
+        ("/items", vec!["maxItems /properties/items/maxItems"]),
+        (
+            "/items/2",
+            vec![
+                "multipleOf /properties/items/items/multipleOf",
+                "minimum /properties/items/items/allOf/0/$ref/minimum",
+            ],
+        ),
+        (
+            "/items/10",
+            vec!["minimum /properties/items/items/allOf/0/$ref/minimum"],
+        ),
+        (
+            "/items/11",
+            vec!["multipleOf /properties/items/items/multipleOf"],
+        ),
+        ("/name", vec!["minLength /properties/name/minLength"]),
+        (
+            "/tags/1",
+            vec!["maxLength /properties/tags/items/maxLength"],
+        ),
+    ]
+    .map(|(loc, leaves)| {
+        let leaves = leaves.into_iter().map(str::to_owned).collect::<Vec<_>>();
+        (loc.to_owned(), leaves)
+    });
+    assert_eq!(got, want);
+
+    // messages are available from kind
+    let groups = err.by_instance_location();
+    let (_, leaves) = groups.iter().next().unwrap();
+    assert_eq!(leaves[0].kind.to_string(), "missing properties 'id'");
+    Ok(())
+}
+
+#[test]
+fn test_json_lines() -> Result<(), Box<dyn Error>> {
+    let schema = json!({
+        "$defs": { "pos": { "minimum": 0 } },
+        "properties": {
+            "name": { "type": "string", "minLength": 2 },
+            "items": { "items": { "$ref": "#/$defs/pos" } }
+        },
+        "additionalProperties": { "type": "string" },
+        "required": ["name", "id"]
+    });
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://tmp.com/schema.json", schema)?;
+    let sch_index = compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+    let instance = json!({"name": "a", "items": [1, -1, -2], "line\nbreak": 1});
+    let err = schemas.validate(&instance, sch_index).unwrap_err();
+
+    let mut out = vec![];
+    err.to_json_lines(&mut out)?;
+    let out = String::from_utf8(out)?;
+    assert!(out.ends_with('\n'));
+    let lines = out
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<Result<Vec<Value>, _>>()?;
+    assert_eq!(lines.len(), err.error_count());
+    assert_eq!(lines.len(), 5, "{out}");
+    for line in &lines {
+        assert_eq!(line["schema"], "http://tmp.com/schema.json#");
+        assert_eq!(line.as_object().map(|obj| obj.len()), Some(6), "{line}");
+    }
+    let mut got = lines
+        .iter()
+        .map(|line| format!("{} {}", line["instanceLocation"], line["code"]))
+        .collect::<Vec<_>>();
+    got.sort();
+    assert_eq!(
+        got,
+        [
+            r#""" "required""#,
+            r#""/items/1" "minimum""#,
+            r#""/items/2" "minimum""#,
+            r#""/line\nbreak" "type""#,
+            r#""/name" "minLength""#,
+        ]
+    );
+    let minimum = lines.iter().find(|l| l["instanceLocation"] == "/items/1");
+    let minimum = minimum.unwrap();
+    assert_eq!(
+        minimum["keywordLocation"],
+        "/properties/items/items/$ref/minimum"
+    );
+    assert_eq!(
+        minimum["absoluteKeywordLocation"],
+        "http://tmp.com/schema.json#/$defs/pos/minimum"
+    );
+    assert!(lines
+        .iter()
+        .any(|l| l["instanceLocation"] == "/line\nbreak"));
+    Ok(())
+}
+
+#[test]
+fn test_long_property_names() -> Result<(), Box<dyn Error>> {
+    let schema = json!({"additionalProperties": {"type": "string"}});
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://tmp.com/schema.json", schema)?;
+    let sch_index = compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+
+    let key = format!("a/{}z", "x".repeat(4 << 20));
+    let instance = json!({ key.clone(): 1 });
+    let err = schemas.validate(&instance, sch_index).unwrap_err();
+
+    // structured tokens keep full name
+    let cause = &err.causes[0];
+    assert_eq!(cause.instance_location.tokens, vec![key.as_str().into()]);
+
+    // rendered name is elided, noting actual length
+    let loc = cause.instance_location.to_string();
+    assert!(loc.starts_with("/a~1xxx"), "{loc}");
+    assert!(loc.ends_with("xxz"), "{loc}");
+    assert!(loc.contains(&format!("({} bytes)", key.len())), "{loc}");
+    assert!(loc.len() < 512, "{} bytes", loc.len());
+
+    let output = format!("{err:#}");
+    assert!(output.len() < 2048, "{} bytes", output.len());
+    let output = serde_json::to_string(&err.detailed_output())?;
+    assert!(output.len() < 2048, "{} bytes", output.len());
+    Ok(())
+}
+
+#[test]
+fn test_limit() {
+    let num = |n: f64| Cow::Owned(Number::from_f64(n).unwrap());
+    let info = |keyword, actual, limit, exclusive| {
+        Some(LimitInfo {
+            keyword,
+            actual,
+            limit,
+            exclusive,
+        })
+    };
+    let tests = [
+        (
+            ErrorKind::MinLength { got: 1, want: 2 },
+            info("minLength", json!(1), json!(2), false),
+        ),
+        (
+            ErrorKind::MaxLength { got: 3, want: 2 },
+            info("maxLength", json!(3), json!(2), false),
+        ),
+        (
+            ErrorKind::MinItems { got: 1, want: 2 },
+            info("minItems", json!(1), json!(2), false),
+        ),
+        (
+            ErrorKind::MaxItems { got: 3, want: 2 },
+            info("maxItems", json!(3), json!(2), false),
+        ),
+        (
+            ErrorKind::MinProperties { got: 1, want: 2 },
+            info("minProperties", json!(1), json!(2), false),
+        ),
+        (
+            ErrorKind::MaxProperties { got: 3, want: 2 },
+            info("maxProperties", json!(3), json!(2), false),
+        ),
+        (
+            ErrorKind::MinContains {
+                got: vec![4],
+                want: 2,
+            },
+            info("minContains", json!(1), json!(2), false),
+        ),
+        (
+            ErrorKind::MaxContains {
+                got: vec![0, 3, 5],
+                want: 2,
+            },
+            info("maxContains", json!(3), json!(2), false),
+        ),
+        (
+            ErrorKind::Minimum {
+                got: num(1.5),
+                want: num(2.0),
+            },
+            info("minimum", json!(1.5), json!(2.0), false),
+        ),
+        (
+            ErrorKind::Maximum {
+                got: num(2.5),
+                want: num(2.0),
+            },
+            info("maximum", json!(2.5), json!(2.0), false),
+        ),
+        (
+            ErrorKind::ExclusiveMinimum {
+                got: num(2.0),
+                want: num(2.0),
+            },
+            info("exclusiveMinimum", json!(2.0), json!(2.0), true),
+        ),
+        (
+            ErrorKind::ExclusiveMaximum {
+                got: num(2.0),
+                want: num(2.0),
+            },
+            info("exclusiveMaximum", json!(2.0), json!(2.0), true),
+        ),
+        (
+            ErrorKind::MultipleOf {
+                got: num(2.5),
+                want: num(2.0),
+            },
+            info("multipleOf", json!(2.5), json!(2.0), false),
+        ),
+        (ErrorKind::Contains, None),
+        (ErrorKind::AdditionalItems { got: 3 }, None),
+    ];
+    for (kind, want) in tests {
+        assert_eq!(kind.limit(), want, "{kind}");
+    }
+}
+
+#[test]
+fn test_limit_output() -> Result<(), Box<dyn Error>> {
+    let schema = json!({
+        "properties": {
+            "name": {"maxLength": 3},
+            "age": {"exclusiveMinimum": 0},
+            "tags": {"type": "array"}
+        }
+    });
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://tmp.com/schema.json", schema)?;
+    let sch_index = compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+
+    let instance = json!({"name": "john", "age": 0, "tags": 1});
+    let err = schemas.validate(&instance, sch_index).unwrap_err();
+    let output = serde_json::to_value(err.basic_output())?;
+    let units = output["errors"].as_array().unwrap();
+    let unit = |loc: &str| units.iter().find(|u| u["instanceLocation"] == loc).unwrap();
+    assert_eq!(unit("/name")["actual"], json!(4));
+    assert_eq!(unit("/name")["limit"], json!(3));
+    assert_eq!(unit("/age")["actual"], json!(0));
+    assert_eq!(unit("/age")["limit"], json!(0));
+    assert!(unit("/tags").get("actual").is_none());
+    assert!(unit("/tags").get("limit").is_none());
+    Ok(())
+}
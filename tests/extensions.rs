@@ -0,0 +1,122 @@
+use std::{
+    error::Error,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use boon::{CompileError, Compiler, ErrorKind, Schemas, TimeRangeError, ValidationOptions};
+use serde_json::{json, Value};
+
+// 2024-02-29T12:00:00Z
+fn clock() -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(1_709_208_000)
+}
+
+fn compile(schema: Value) -> Result<(Schemas, boon::SchemaIndex), CompileError> {
+    let mut compiler = Compiler::new();
+    compiler.enable_extension("x-time-range");
+    compiler.add_resource("schema.json", schema)?;
+    let mut schemas = Schemas::new();
+    let sch = compiler.compile("schema.json", &mut schemas)?;
+    Ok((schemas, sch))
+}
+
+// returns keyword and bound of time range error, if any
+fn check(schemas: &Schemas, sch: boon::SchemaIndex, v: &str) -> Option<(String, String)> {
+    let v = Value::String(v.to_owned());
+    let opts = ValidationOptions::new().clock(&clock);
+    let err = schemas.validate_with(&v, sch, &opts).err()?;
+    match &err.causes[0].kind {
+        ErrorKind::Keyword { keyword, err } => {
+            let err = err.downcast_ref::<TimeRangeError>().unwrap();
+            assert_eq!(err.got, v);
+            assert_eq!(err.before, *keyword == "x-notBefore");
+            Some((keyword.to_string(), err.bound.clone()))
+        }
+        kind => panic!("unexpected error {kind:?}"),
+    }
+}
+
+#[test]
+fn test_time_range() -> Result<(), Box<dyn Error>> {
+    let (schemas, sch) = compile(json!({
+        "format": "date-time",
+        "x-notBefore": "P-1Y",
+        "x-notAfter": "now"
+    }))?;
+    let after = Some(("x-notAfter".to_owned(), "2024-02-29T12:00:00Z".to_owned()));
+    // 2023 has no february 29th
+    let before = Some(("x-notBefore".to_owned(), "2023-02-28T12:00:00Z".to_owned()));
+    let tests = [
+        ("2024-02-29T12:00:00Z", None),
+        ("2024-02-29T13:00:00+01:00", None),
+        ("2023-02-28T12:00:00Z", None),
+        ("2024-01-01", None),
+        ("2024-02-29T12:00:00.001Z", after.clone()),
+        ("2024-02-29T12:00:00-00:01", after),
+        ("2023-02-28T11:59:59Z", before.clone()),
+        ("2023-02-28", before),
+        ("not a date-time", None),
+    ];
+    for (v, want) in tests {
+        assert_eq!(check(&schemas, sch, v), want, "{v}");
+    }
+
+    let (schemas, sch) = compile(json!({
+        "x-notBefore": "2000-01-01T00:00:00+02:00",
+        "x-notAfter": "-PT0.5S"
+    }))?;
+    assert_eq!(check(&schemas, sch, "1999-12-31T22:00:00Z"), None);
+    assert_eq!(
+        check(&schemas, sch, "1999-12-31T21:59:59.5Z"),
+        Some(("x-notBefore".to_owned(), "1999-12-31T22:00:00Z".to_owned()))
+    );
+    assert_eq!(
+        check(&schemas, sch, "2024-02-29T12:00:00Z"),
+        Some(("x-notAfter".to_owned(), "2024-02-29T11:59:59.5Z".to_owned()))
+    );
+
+    // error message has timestamp and bound
+    let v = json!("2030-01-01T00:00:00Z");
+    let opts = ValidationOptions::new().clock(&clock);
+    let err = schemas.validate_with(&v, sch, &opts).unwrap_err();
+    assert_eq!(
+        err.causes[0].kind.to_string(),
+        "x-notAfter failed: '2030-01-01T00:00:00Z' is after 2024-02-29T11:59:59.5Z"
+    );
+    let path = err.causes[0].kind.keyword_path();
+    assert_eq!(path.map(|p| p.keyword), Some("x-notAfter"));
+    Ok(())
+}
+
+#[test]
+fn test_time_range_disabled() -> Result<(), Box<dyn Error>> {
+    let mut compiler = Compiler::new();
+    compiler.add_resource("schema.json", json!({"x-notAfter": "now"}))?;
+    let mut schemas = Schemas::new();
+    let sch = compiler.compile("schema.json", &mut schemas)?;
+    assert!(schemas.validate(&json!("9999-01-01"), sch).is_ok());
+    Ok(())
+}
+
+#[test]
+fn test_time_range_invalid_bound() {
+    for bound in [
+        json!(5),
+        json!("P1X"),
+        json!("PT"),
+        json!("P1.5D"),
+        json!("yesterday"),
+    ] {
+        let result = compile(json!({ "x-notAfter": bound }));
+        assert!(
+            matches!(result, Err(CompileError::InvalidKeyword { .. })),
+            "{bound} must be invalid"
+        );
+    }
+}
+
+#[test]
+#[should_panic(expected = "unsupported extension")]
+fn test_unsupported_extension() {
+    Compiler::new().enable_extension("x-unknown");
+}
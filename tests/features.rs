@@ -0,0 +1,76 @@
+// behavior when optional default features are disabled
+
+use std::error::Error;
+
+use boon::{CompileError, Compiler, Format, Schemas};
+use serde_json::{json, Value};
+
+fn compile(schema: Value) -> Result<(Schemas, boon::SchemaIndex), CompileError> {
+    let mut compiler = Compiler::new();
+    compiler.enable_format_assertions();
+    compiler.add_resource("http://example.com/schema.json", schema)?;
+    let mut schemas = Schemas::new();
+    let sch = compiler.compile("http://example.com/schema.json", &mut schemas)?;
+    Ok((schemas, sch))
+}
+
+#[test]
+fn test_formats() -> Result<(), Box<dyn Error>> {
+    let formats = [
+        ("email", cfg!(feature = "formats-net")),
+        ("hostname", cfg!(feature = "formats-net")),
+        ("uri", cfg!(feature = "formats-net")),
+        ("date-time", cfg!(feature = "formats-time")),
+        ("duration", cfg!(feature = "formats-time")),
+        ("ipv4", true),
+        ("regex", true),
+    ];
+    for (name, enabled) in formats {
+        assert_eq!(Format::builtin(name).is_some(), enabled, "{name}");
+        // unknown formats are ignored
+        let (schemas, sch) = compile(json!({"format": name}))?;
+        let invalid = json!("(#");
+        assert_eq!(schemas.validate(&invalid, sch).is_err(), enabled, "{name}");
+    }
+    Ok(())
+}
+
+#[test]
+fn test_refs() -> Result<(), Box<dyn Error>> {
+    let mut compiler = Compiler::new();
+    compiler.add_resource(
+        "http://Example.com:80/a/b/schema.json",
+        json!({"$ref": "../c/./d.json#/$defs/positive"}),
+    )?;
+    compiler.add_resource(
+        "http://example.com/a/c/d.json",
+        json!({"$defs": {"positive": {"exclusiveMinimum": 0}}}),
+    )?;
+    let mut schemas = Schemas::new();
+    let sch = compiler.compile("http://example.com/a/b/schema.json", &mut schemas)?;
+    assert!(schemas.validate(&json!(1), sch).is_ok());
+    let zero = json!(0);
+    let err = schemas.validate(&zero, sch).unwrap_err();
+    assert_eq!(
+        err.causes[0].causes[0].schema_url,
+        "http://example.com/a/c/d.json#/$defs/positive"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_regex() -> Result<(), Box<dyn Error>> {
+    // ascii classes do not need unicode tables
+    let (schemas, sch) = compile(json!({"pattern": r"^\w+\b-\d\s$"}))?;
+    assert!(schemas.validate(&json!("abc-1 "), sch).is_ok());
+    assert!(schemas.validate(&json!("é-1 "), sch).is_err());
+
+    let result = compile(json!({"pattern": r"^\p{Lu}"}));
+    if cfg!(feature = "regex-unicode") {
+        let (schemas, sch) = result?;
+        assert!(schemas.validate(&json!("É"), sch).is_ok());
+    } else {
+        assert!(matches!(result, Err(CompileError::InvalidRegex { .. })));
+    }
+    Ok(())
+}
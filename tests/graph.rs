@@ -0,0 +1,158 @@
+use std::error::Error;
+
+use boon::{Compiler, RefEdge, Schemas};
+use serde_json::json;
+
+#[test]
+fn test_reference_graph() -> Result<(), Box<dyn Error>> {
+    let mut compiler = Compiler::new();
+    compiler.add_resource(
+        "http://a.com/order.json",
+        json!({
+            "properties": {
+                "customer": {"$ref": "customer.json"},
+                "items": {"items": {"$ref": "#/$defs/item"}}
+            },
+            "$defs": {
+                "item": {
+                    "$id": "item.json",
+                    "properties": {"price": {"$ref": "money.json"}}
+                }
+            }
+        }),
+    )?;
+    compiler.add_resource(
+        "http://a.com/customer.json",
+        json!({"properties": {"orders": {"items": {"$ref": "order.json"}}}}),
+    )?;
+    compiler.add_resource(
+        "http://a.com/money.json",
+        json!({
+            "properties": {"currency": {"$ref": "#/$defs/code"}},
+            "$defs": {"code": {"type": "string"}}
+        }),
+    )?;
+    compiler.add_resource(
+        "http://a.com/tree.json",
+        json!({
+            "$dynamicAnchor": "node",
+            "items": {"$dynamicRef": "#node"}
+        }),
+    )?;
+
+    let mut schemas = Schemas::new();
+    compiler.compile("http://a.com/order.json", &mut schemas)?;
+    compiler.compile("http://a.com/customer.json", &mut schemas)?; // already compiled
+    compiler.compile("http://a.com/tree.json", &mut schemas)?;
+    let graph = schemas.reference_graph();
+
+    let node = |url: &str| graph.nodes.iter().position(|n| n == url).unwrap();
+    let mut nodes = graph.nodes.clone();
+    nodes.sort();
+    assert_eq!(
+        nodes,
+        [
+            "http://a.com/customer.json",
+            "http://a.com/item.json",
+            "http://a.com/money.json",
+            "http://a.com/order.json",
+            "http://a.com/tree.json",
+        ]
+    );
+
+    let (order, customer, item, money, tree) = (
+        node("http://a.com/order.json"),
+        node("http://a.com/customer.json"),
+        node("http://a.com/item.json"),
+        node("http://a.com/money.json"),
+        node("http://a.com/tree.json"),
+    );
+    let edge = |from, to, keyword, keyword_location: &str| RefEdge {
+        from,
+        to,
+        keyword,
+        keyword_location: keyword_location.to_owned(),
+    };
+    let mut edges = graph.edges.clone();
+    edges.sort_by(|a, b| a.keyword_location.cmp(&b.keyword_location));
+    assert_eq!(
+        edges,
+        [
+            edge(
+                customer,
+                order,
+                "$ref",
+                "http://a.com/customer.json#/properties/orders/items/$ref"
+            ),
+            edge(
+                money,
+                money,
+                "$ref",
+                "http://a.com/money.json#/properties/currency/$ref"
+            ),
+            edge(
+                item,
+                money,
+                "$ref",
+                "http://a.com/order.json#/$defs/item/properties/price/$ref"
+            ),
+            edge(
+                order,
+                customer,
+                "$ref",
+                "http://a.com/order.json#/properties/customer/$ref"
+            ),
+            edge(
+                order,
+                item,
+                "$ref",
+                "http://a.com/order.json#/properties/items/items/$ref"
+            ),
+            edge(
+                tree,
+                tree,
+                "$dynamicRef",
+                "http://a.com/tree.json#/items/$dynamicRef"
+            ),
+        ]
+    );
+
+    assert!(graph.has_cycle());
+    let mut want = vec![vec![order, customer], vec![money], vec![tree]];
+    for scc in &mut want {
+        scc.sort();
+    }
+    want.sort();
+    assert_eq!(graph.cycles(), want);
+
+    let dot = graph.to_dot();
+    assert!(dot.starts_with("digraph refs {\n"), "{dot}");
+    assert!(dot.ends_with("}\n"), "{dot}");
+    assert!(
+        dot.contains(&format!(
+            "    n{order} [label=\"http://a.com/order.json\"];\n"
+        )),
+        "{dot}"
+    );
+    assert!(
+        dot.contains(&format!(
+            "    n{customer} -> n{order} [label=\"/properties/orders/items/$ref\"];\n"
+        )),
+        "{dot}"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_reference_graph_acyclic() -> Result<(), Box<dyn Error>> {
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://a.com/a.json", json!({"$ref": "b.json"}))?;
+    compiler.add_resource("http://a.com/b.json", json!({"type": "string"}))?;
+    let mut schemas = Schemas::new();
+    compiler.compile("http://a.com/a.json", &mut schemas)?;
+    let graph = schemas.reference_graph();
+    assert_eq!(graph.edges.len(), 1);
+    assert!(!graph.has_cycle());
+    assert!(graph.cycles().is_empty());
+    Ok(())
+}
@@ -0,0 +1,65 @@
+#![cfg(feature = "json5")]
+
+use std::error::Error;
+
+use boon::{CompileError, Compiler, Json5Error, LenientFileLoader, Schemas, SchemeUrlLoader};
+use serde_json::json;
+
+#[test]
+fn test_add_resource_json5() -> Result<(), Box<dyn Error>> {
+    let schema = r#"{
+        // comments are allowed
+        type: 'object',
+        required: ['name',],
+    }"#;
+
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.add_resource_json5("http://tmp.com/schema.json5", schema)?;
+    let sch_index = compiler.compile("http://tmp.com/schema.json5", &mut schemas)?;
+    assert!(schemas.validate(&json!({"name": "x"}), sch_index).is_ok());
+    assert!(schemas.validate(&json!({}), sch_index).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_add_resource_json5_error() {
+    let schema = "{\n  type: 'object',\n  required: [,]\n}";
+
+    let mut compiler = Compiler::new();
+    let result = compiler.add_resource_json5("http://tmp.com/schema.json5", schema);
+    let Err(CompileError::LoadUrlError { url, src, .. }) = result else {
+        panic!("want LoadUrlError, got {result:?}");
+    };
+    assert_eq!(url, "http://tmp.com/schema.json5");
+    let err = src.downcast_ref::<Json5Error>().unwrap();
+    assert_eq!((err.line, err.column), (3, 14));
+}
+
+#[test]
+fn test_json5_file() -> Result<(), Box<dyn Error>> {
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    let sch_index = compiler.compile("tests/examples/schema.json5", &mut schemas)?;
+    assert!(schemas.validate(&json!({"name": "x"}), sch_index).is_ok());
+    assert!(schemas.validate(&json!({"name": 1}), sch_index).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_lenient_json_file() -> Result<(), Box<dyn Error>> {
+    // comments in .json files are not allowed by default
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    let result = compiler.compile("tests/examples/lenient.json", &mut schemas);
+    assert!(matches!(result, Err(CompileError::LoadUrlError { .. })));
+
+    let mut loader = SchemeUrlLoader::new();
+    loader.register("file", Box::new(LenientFileLoader));
+    let mut compiler = Compiler::new();
+    compiler.use_loader(Box::new(loader));
+    let sch_index = compiler.compile("tests/examples/lenient.json", &mut schemas)?;
+    assert!(schemas.validate(&json!({"name": "x"}), sch_index).is_ok());
+    assert!(schemas.validate(&json!({"name": 1}), sch_index).is_err());
+    Ok(())
+}
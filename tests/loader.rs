@@ -0,0 +1,258 @@
+use std::{
+    cell::Cell,
+    error::Error,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use boon::{
+    CachedLoadError, CompileError, Compiler, LoadError, LoaderChainError, Schemas, SchemeUrlLoader,
+    UrlLoader,
+};
+use serde_json::{json, Value};
+
+// serves `schema` for `url`, and fails with `error` for others.
+struct TestLoader {
+    url: &'static str,
+    schema: Value,
+    error: fn() -> LoadError,
+}
+
+impl UrlLoader for TestLoader {
+    fn load(&self, url: &str) -> Result<Value, Box<dyn Error>> {
+        if url == self.url {
+            Ok(self.schema.clone())
+        } else {
+            Err((self.error)().into())
+        }
+    }
+}
+
+fn cache(error: fn() -> LoadError) -> Box<dyn UrlLoader> {
+    Box::new(TestLoader {
+        url: "https://a.com/cached.json",
+        schema: json!({"type": "string"}),
+        error,
+    })
+}
+
+fn network() -> Box<dyn UrlLoader> {
+    Box::new(TestLoader {
+        url: "https://a.com/remote.json",
+        schema: json!({"type": "number"}),
+        error: || LoadError::Timeout,
+    })
+}
+
+fn compile(
+    loader: SchemeUrlLoader,
+    loc: &str,
+) -> Result<(Schemas, boon::SchemaIndex), CompileError> {
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.use_loader(Box::new(loader));
+    let sch = compiler.compile(loc, &mut schemas)?;
+    Ok((schemas, sch))
+}
+
+#[test]
+fn test_loader_chain() -> Result<(), Box<dyn Error>> {
+    let chain = |error| {
+        let mut loader = SchemeUrlLoader::new();
+        loader.register("https", network());
+        loader.register_front("https", cache(error));
+        loader
+    };
+
+    // hit in first loader
+    let (schemas, sch) = compile(chain(|| LoadError::NotFound), "https://a.com/cached.json")?;
+    assert!(schemas.validate(&json!("x"), sch).is_ok());
+
+    // falls through to second loader
+    let (schemas, sch) = compile(chain(|| LoadError::NotFound), "https://a.com/remote.json")?;
+    assert!(schemas.validate(&json!(1), sch).is_ok());
+
+    // every loader fails
+    let Err(CompileError::LoadUrlError { src, .. }) =
+        compile(chain(|| LoadError::NotFound), "https://a.com/missing.json")
+    else {
+        panic!("must fail");
+    };
+    let chain_err = src.downcast_ref::<LoaderChainError>().unwrap();
+    assert_eq!(chain_err.errors.len(), 2);
+    assert_eq!(
+        src.to_string(),
+        "2 loaders failed; [1] not found; [2] timed out"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_loader_chain_errors() -> Result<(), Box<dyn Error>> {
+    // timeout aborts by default
+    let mut loader = SchemeUrlLoader::new();
+    loader.register("https", cache(|| LoadError::Timeout));
+    loader.register("https", network());
+    let Err(CompileError::LoadUrlError { src, .. }) = compile(loader, "https://a.com/remote.json")
+    else {
+        panic!("must fail");
+    };
+    assert!(
+        matches!(src.downcast_ref(), Some(LoadError::Timeout)),
+        "{src}"
+    );
+
+    // unless fallback on errors is enabled
+    let mut loader = SchemeUrlLoader::new();
+    loader.register("https", cache(|| LoadError::Timeout));
+    loader.register("https", network());
+    loader.enable_fallback_on_errors();
+    let (schemas, sch) = compile(loader, "https://a.com/remote.json")?;
+    assert!(schemas.validate(&json!(1), sch).is_ok());
+
+    // io not-found falls through
+    let mut loader = SchemeUrlLoader::new();
+    loader.register(
+        "https",
+        cache(|| LoadError::Io(std::io::ErrorKind::NotFound.into())),
+    );
+    loader.register("https", network());
+    let (schemas, sch) = compile(loader, "https://a.com/remote.json")?;
+    assert!(schemas.validate(&json!(1), sch).is_ok());
+    Ok(())
+}
+
+// counts attempts to load `https://a.com/flaky.json`, which times out
+// for first `failures` attempts; other urls are served by `schema`.
+struct FlakyLoader {
+    attempts: Rc<Cell<usize>>,
+    failures: usize,
+}
+
+impl UrlLoader for FlakyLoader {
+    fn load(&self, url: &str) -> Result<Value, Box<dyn Error>> {
+        if url != "https://a.com/flaky.json" {
+            return Ok(json!({"$ref": "https://a.com/flaky.json"}));
+        }
+        self.attempts.set(self.attempts.get() + 1);
+        if self.attempts.get() <= self.failures {
+            return Err(LoadError::Timeout.into());
+        }
+        Ok(json!({"type": "string"}))
+    }
+}
+
+fn flaky_compiler(failures: usize) -> (Compiler, Rc<Cell<usize>>) {
+    let attempts = Rc::new(Cell::new(0));
+    let mut compiler = Compiler::new();
+    compiler.use_loader(Box::new(FlakyLoader {
+        attempts: attempts.clone(),
+        failures,
+    }));
+    (compiler, attempts)
+}
+
+const REFERRERS: [&str; 3] = [
+    "https://a.com/1.json",
+    "https://a.com/2.json",
+    "https://a.com/3.json",
+];
+
+#[test]
+fn test_negative_cache() {
+    // default: each compilation loads again
+    let (mut compiler, attempts) = flaky_compiler(usize::MAX);
+    let results = compiler.compile_all(&REFERRERS, &mut Schemas::new());
+    assert!(results.iter().all(Result::is_err));
+    assert_eq!(attempts.get(), 3);
+
+    // failure is remembered across compilations
+    let (mut compiler, attempts) = flaky_compiler(usize::MAX);
+    compiler.set_negative_cache_ttl(Duration::from_secs(3600));
+    let results = compiler.compile_all(&REFERRERS, &mut Schemas::new());
+    assert_eq!(attempts.get(), 1);
+    let Err(CompileError::LoadUrlError { url, src, .. }) = &results[2] else {
+        panic!("must fail with LoadUrlError");
+    };
+    assert_eq!(url, "https://a.com/flaky.json");
+    let cached = src.downcast_ref::<CachedLoadError>().unwrap();
+    assert_eq!(cached.error.to_string(), "timed out");
+    assert_eq!(src.to_string(), "timed out (failed earlier, not retried)");
+
+    // failure is forgotten after ttl
+    let (mut compiler, attempts) = flaky_compiler(usize::MAX);
+    let (start, elapsed) = (Instant::now(), Rc::new(Cell::new(Duration::ZERO)));
+    compiler.set_negative_cache_ttl(Duration::from_secs(60));
+    compiler.set_negative_cache_clock({
+        let elapsed = elapsed.clone();
+        Box::new(move || start + elapsed.get())
+    });
+    assert!(compiler.compile(REFERRERS[0], &mut Schemas::new()).is_err());
+    elapsed.set(Duration::from_secs(59));
+    assert!(compiler.compile(REFERRERS[1], &mut Schemas::new()).is_err());
+    assert_eq!(attempts.get(), 1);
+    elapsed.set(Duration::from_secs(60));
+    assert!(compiler.compile(REFERRERS[2], &mut Schemas::new()).is_err());
+    assert_eq!(attempts.get(), 2);
+}
+
+#[test]
+fn test_negative_cache_source_chain() {
+    struct IoLoader;
+    impl UrlLoader for IoLoader {
+        fn load(&self, url: &str) -> Result<Value, Box<dyn Error>> {
+            if url != "https://a.com/broken.json" {
+                return Ok(json!({"$ref": "https://a.com/broken.json"}));
+            }
+            Err(LoadError::Io(std::io::Error::other("reset")).into())
+        }
+    }
+    let mut compiler = Compiler::new();
+    compiler.use_loader(Box::new(IoLoader));
+    compiler.set_negative_cache_ttl(Duration::from_secs(3600));
+    let results = compiler.compile_all(&REFERRERS[..2], &mut Schemas::new());
+    let Err(CompileError::LoadUrlError { src, .. }) = &results[1] else {
+        panic!("must fail with LoadUrlError");
+    };
+    let cached = src.downcast_ref::<CachedLoadError>().unwrap();
+    let first = results[0].as_ref().unwrap_err().source().unwrap();
+    assert_eq!(cached.error.to_string(), first.to_string());
+    let io = cached.source().expect("source chain must be kept");
+    assert_eq!(io.to_string(), "reset");
+}
+
+#[test]
+fn test_load_retries() {
+    // succeeds on third attempt
+    let (mut compiler, attempts) = flaky_compiler(2);
+    compiler.set_load_retries(2, Duration::ZERO);
+    assert!(compiler.compile(REFERRERS[0], &mut Schemas::new()).is_ok());
+    assert_eq!(attempts.get(), 3);
+
+    // retries exhausted, then failure is remembered
+    let (mut compiler, attempts) = flaky_compiler(usize::MAX);
+    compiler.set_load_retries(2, Duration::from_millis(1));
+    compiler.set_negative_cache_ttl(Duration::from_secs(3600));
+    let results = compiler.compile_all(&REFERRERS, &mut Schemas::new());
+    assert!(results.iter().all(Result::is_err));
+    assert_eq!(attempts.get(), 3);
+}
+
+#[test]
+fn test_load_retries_only_on_timeout() {
+    let attempts = Rc::new(Cell::new(0));
+    struct NotFound(Rc<Cell<usize>>);
+    impl UrlLoader for NotFound {
+        fn load(&self, _url: &str) -> Result<Value, Box<dyn Error>> {
+            self.0.set(self.0.get() + 1);
+            Err(LoadError::NotFound.into())
+        }
+    }
+    let mut compiler = Compiler::new();
+    compiler.use_loader(Box::new(NotFound(attempts.clone())));
+    compiler.set_load_retries(3, Duration::ZERO);
+    assert!(compiler
+        .compile("https://a.com/missing.json", &mut Schemas::new())
+        .is_err());
+    assert_eq!(attempts.get(), 1);
+}
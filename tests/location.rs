@@ -0,0 +1,416 @@
+use std::error::Error;
+
+use boon::{
+    AllowResult, Compiler, InstanceLocation, InstanceToken, SchemaIndex, Schemas, SubtreeError,
+    UnevalPolicy, ValidationError,
+};
+use serde_json::{json, Value};
+
+fn locations(
+    schema: Value,
+    instance: &Value,
+    loc: &[InstanceToken<'static>],
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://tmp.com/schema.json", schema)?;
+    let sch_index = compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+    assert!(schemas.validate(instance, sch_index).is_ok());
+
+    let loc = InstanceLocation {
+        tokens: loc.to_vec(),
+    };
+    let found = schemas.schemas_for_location(sch_index, instance, &loc);
+
+    // map indexes back to locations, by compiling candidate locations
+    let mut got = vec![];
+    for sch in found {
+        let name = candidates(&mut compiler, &mut schemas, sch)?;
+        got.push(name);
+    }
+    Ok(got)
+}
+
+fn candidates(
+    compiler: &mut Compiler,
+    schemas: &mut Schemas,
+    sch: SchemaIndex,
+) -> Result<String, Box<dyn Error>> {
+    const PTRS: &[&str] = &[
+        "",
+        "/$defs/spec",
+        "/$defs/spec/allOf/0",
+        "/$defs/spec/allOf/1",
+        "/$defs/spec/allOf/0/properties/containers",
+        "/$defs/spec/allOf/0/properties/containers/items",
+        "/$defs/spec/allOf/1/patternProperties/^c",
+        "/$defs/spec/allOf/1/patternProperties/^c/items",
+        "/$defs/container",
+        "/$defs/container/properties/image",
+        "/$defs/container/oneOf/0",
+        "/$defs/container/oneOf/1",
+        "/$defs/container/oneOf/0/properties/image",
+        "/$defs/container/oneOf/1/properties/image",
+        "/$defs/container/if",
+        "/$defs/container/then",
+        "/$defs/container/else",
+        "/$defs/container/then/properties/image",
+        "/$defs/container/else/properties/image",
+        "/properties/spec",
+    ];
+    for ptr in PTRS {
+        let url = format!("http://tmp.com/schema.json#{ptr}");
+        if compiler.compile(&url, schemas)? == sch {
+            return Ok(ptr.to_string());
+        }
+    }
+    Err(format!("unexpected schema {sch:?}"))?
+}
+
+#[test]
+fn test_schemas_for_location() -> Result<(), Box<dyn Error>> {
+    let schema = json!({
+        "properties": {
+            "spec": { "$ref": "#/$defs/spec" }
+        },
+        "$defs": {
+            "spec": {
+                "allOf": [
+                    {
+                        "properties": {
+                            "containers": { "items": { "$ref": "#/$defs/container" } }
+                        }
+                    },
+                    {
+                        "patternProperties": {
+                            "^c": { "items": { "required": ["image"] } }
+                        }
+                    }
+                ]
+            },
+            "container": {
+                "properties": {
+                    "image": { "type": "string" }
+                },
+                "oneOf": [
+                    { "properties": { "image": { "pattern": "^docker.io/" } } },
+                    { "properties": { "image": { "pattern": "^quay.io/" } } }
+                ],
+                "if": { "required": ["tag"] },
+                "then": { "properties": { "image": { "maxLength": 20 } } },
+                "else": { "properties": { "image": { "minLength": 3 } } }
+            }
+        }
+    });
+    let instance = json!({
+        "spec": {
+            "containers": [
+                { "image": "quay.io/x" },
+                { "image": "docker.io/a", "tag": "v1" }
+            ]
+        }
+    });
+    let tokens = |i: usize| -> Vec<InstanceToken> {
+        vec!["spec".into(), "containers".into(), i.into(), "image".into()]
+    };
+
+    let got = locations(schema.clone(), &instance, &tokens(0))?;
+    assert_eq!(
+        got,
+        [
+            "/$defs/container/properties/image",
+            "/$defs/container/oneOf/1/properties/image",
+            "/$defs/container/else/properties/image",
+        ]
+    );
+
+    let got = locations(schema.clone(), &instance, &tokens(1))?;
+    assert_eq!(
+        got,
+        [
+            "/$defs/container/properties/image",
+            "/$defs/container/oneOf/0/properties/image",
+            "/$defs/container/then/properties/image",
+        ]
+    );
+
+    // through $ref, allOf and patternProperties
+    let got = locations(schema.clone(), &instance, &tokens(1)[..3])?;
+    assert_eq!(
+        got,
+        [
+            "/$defs/spec/allOf/0/properties/containers/items",
+            "/$defs/container",
+            "/$defs/container/oneOf/0",
+            "/$defs/container/if",
+            "/$defs/container/then",
+            "/$defs/spec/allOf/1/patternProperties/^c/items",
+        ]
+    );
+
+    let got = locations(schema.clone(), &instance, &tokens(1)[..1])?;
+    assert_eq!(
+        got,
+        [
+            "/properties/spec",
+            "/$defs/spec",
+            "/$defs/spec/allOf/0",
+            "/$defs/spec/allOf/1",
+        ]
+    );
+
+    // missing in instance
+    let got = locations(schema, &instance, &tokens(2))?;
+    assert!(got.is_empty(), "{got:?}");
+    Ok(())
+}
+
+#[test]
+fn test_schemas_for_location_unevaluated() -> Result<(), Box<dyn Error>> {
+    let schema = json!({
+        "$defs": {
+            "a": { "properties": { "a": true } },
+            "rest": { "type": "number" }
+        },
+        "allOf": [{ "$ref": "#/$defs/a" }],
+        "unevaluatedProperties": { "$ref": "#/$defs/rest" }
+    });
+    let instance = json!({ "a": "x", "b": 1 });
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://tmp.com/schema.json", schema)?;
+    let sch_index = compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+    let a = compiler.compile(
+        "http://tmp.com/schema.json#/$defs/a/properties/a",
+        &mut schemas,
+    )?;
+    let uneval = compiler.compile(
+        "http://tmp.com/schema.json#/unevaluatedProperties",
+        &mut schemas,
+    )?;
+    let rest = compiler.compile("http://tmp.com/schema.json#/$defs/rest", &mut schemas)?;
+
+    let loc = |p: &'static str| InstanceLocation {
+        tokens: vec![p.into()],
+    };
+    assert_eq!(
+        schemas.schemas_for_location(sch_index, &instance, &loc("a")),
+        [a]
+    );
+    assert_eq!(
+        schemas.schemas_for_location(sch_index, &instance, &loc("b")),
+        [uneval, rest]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_property_allowed() -> Result<(), Box<dyn Error>> {
+    let schema = json!({
+        "$defs": { "tag": { "type": "string" } },
+        "properties": {
+            "meta": {
+                "properties": { "name": { "type": "string" } },
+                "patternProperties": { "^x-": { "type": "integer" } },
+                "additionalProperties": false,
+                "maxProperties": 3
+            },
+            "labels": {
+                "additionalProperties": { "$ref": "#/$defs/tag" },
+                "propertyNames": { "maxLength": 5 }
+            },
+            "spec": {
+                "oneOf": [
+                    {
+                        "required": ["a"],
+                        "properties": { "a": true, "b": { "type": "number" } },
+                        "unevaluatedProperties": false
+                    },
+                    { "required": ["c"], "properties": { "c": true } }
+                ]
+            }
+        }
+    });
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://tmp.com/schema.json", schema)?;
+    let mut idx = |ptr: &str| {
+        let url = format!("http://tmp.com/schema.json#{ptr}");
+        compiler.compile(&url, &mut schemas)
+    };
+    let root = idx("")?;
+    let meta = idx("/properties/meta")?;
+    let name = idx("/properties/meta/properties/name")?;
+    let x = idx("/properties/meta/patternProperties/^x-")?;
+    let labels = idx("/properties/labels")?;
+    let label = idx("/properties/labels/additionalProperties")?;
+    let b = idx("/properties/spec/oneOf/0/properties/b")?;
+    let uneval = idx("/properties/spec/oneOf/0/unevaluatedProperties")?;
+
+    let tests = [
+        (
+            json!({"meta": {}}),
+            "meta",
+            "name",
+            AllowResult::Property(vec![name]),
+        ),
+        (
+            json!({"meta": {}}),
+            "meta",
+            "x-a",
+            AllowResult::PatternProperty(vec![x]),
+        ),
+        (
+            json!({"meta": {}}),
+            "meta",
+            "other",
+            AllowResult::Rejected(meta),
+        ),
+        (
+            json!({"meta": {"name": "x", "x-a": 1, "x-b": 2}}),
+            "meta",
+            "x-c",
+            AllowResult::Rejected(meta),
+        ),
+        (
+            json!({"meta": {"name": "x", "x-a": 1, "x-b": 2}}),
+            "meta",
+            "name",
+            AllowResult::Property(vec![name]),
+        ),
+        (
+            json!({"labels": {}}),
+            "labels",
+            "abc",
+            AllowResult::Additional(vec![label]),
+        ),
+        (
+            json!({"labels": {}}),
+            "labels",
+            "toolong",
+            AllowResult::Rejected(labels),
+        ),
+        // conditionals resolved against instance
+        (
+            json!({"spec": {"a": 1}}),
+            "spec",
+            "b",
+            AllowResult::Property(vec![b]),
+        ),
+        (
+            json!({"spec": {"a": 1}}),
+            "spec",
+            "z",
+            AllowResult::Rejected(uneval),
+        ),
+        (
+            json!({"spec": {"c": 1}}),
+            "spec",
+            "z",
+            AllowResult::Additional(vec![]),
+        ),
+        // not an object, or missing
+        (json!({"meta": 1}), "meta", "name", AllowResult::Unknown),
+        (json!({}), "meta", "name", AllowResult::Unknown),
+    ];
+    for (doc, prop, candidate, want) in tests {
+        let loc = InstanceLocation {
+            tokens: vec![prop.into()],
+        };
+        let got = schemas.property_allowed(root, &doc, &loc, candidate);
+        assert_eq!(got, want, "{doc} {candidate}");
+    }
+
+    // root
+    let loc = InstanceLocation::default();
+    let got = schemas.property_allowed(root, &json!({}), &loc, "meta");
+    assert_eq!(got, AllowResult::Property(vec![meta]));
+    Ok(())
+}
+
+#[test]
+fn test_validate_at() -> Result<(), Box<dyn Error>> {
+    fn leaf_locations(e: &ValidationError, out: &mut Vec<String>) {
+        if e.causes.is_empty() {
+            out.push(e.instance_location.to_string());
+        }
+        for cause in &e.causes {
+            leaf_locations(cause, out);
+        }
+    }
+    let schema = json!({
+        "allOf": [{ "properties": { "a": true } }],
+        "properties": {
+            "list": { "items": { "type": "integer" } }
+        },
+        "unevaluatedProperties": { "type": "string" }
+    });
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://tmp.com/schema.json", schema)?;
+    let sch = compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+    let list = compiler.compile("http://tmp.com/schema.json#/properties/list", &mut schemas)?;
+    let doc = json!({"a": 1, "b": 2, "list": [1, "x"]});
+    let at = |tokens: Vec<InstanceToken<'static>>| InstanceLocation { tokens };
+    let (b, a, item) = (
+        at(vec!["b".into()]),
+        at(vec!["a".into()]),
+        at(vec!["list".into(), 1.into()]),
+    );
+
+    // /b is not evaluated by siblings, so unevaluatedProperties applies to it
+    let result = schemas.validate_at(&doc, sch, &b, UnevalPolicy::Forbid);
+    let Err(SubtreeError::Unevaluated { schema_url }) = result else {
+        panic!("forbid must reject: {result:?}");
+    };
+    assert_eq!(schema_url, "http://tmp.com/schema.json#");
+    let Err(SubtreeError::Validation(e)) =
+        schemas.validate_at(&doc, sch, &b, UnevalPolicy::Emulate)
+    else {
+        panic!("emulate must find /b invalid");
+    };
+    let mut got = vec![];
+    leaf_locations(&e, &mut got);
+    assert_eq!(got, ["/b"]);
+    assert!(schemas
+        .validate_at(&doc, sch, &b, UnevalPolicy::Ignore)
+        .is_ok());
+
+    // /a is evaluated by allOf, so unevaluatedProperties does not apply
+    assert!(schemas
+        .validate_at(&doc, sch, &a, UnevalPolicy::Emulate)
+        .is_ok());
+
+    // locations are relative to document
+    let Err(SubtreeError::Validation(e)) =
+        schemas.validate_at(&doc, sch, &item, UnevalPolicy::Ignore)
+    else {
+        panic!("/list/1 must be invalid");
+    };
+    let mut got = vec![];
+    leaf_locations(&e, &mut got);
+    assert_eq!(got, ["/list/1"]);
+
+    // no unevaluated keywords in ancestors
+    let result = schemas.validate_at(
+        &doc["list"],
+        list,
+        &at(vec![1.into()]),
+        UnevalPolicy::Forbid,
+    );
+    assert!(matches!(result, Err(SubtreeError::Validation(_))));
+    let result = schemas.validate_at(
+        &doc["list"],
+        list,
+        &at(vec![0.into()]),
+        UnevalPolicy::Forbid,
+    );
+    assert!(result.is_ok());
+
+    let result = schemas.validate_at(&doc, sch, &at(vec!["c".into()]), UnevalPolicy::Ignore);
+    let Err(SubtreeError::NotFound(loc)) = result else {
+        panic!("/c must not be found: {result:?}");
+    };
+    assert_eq!(loc.to_string(), "/c");
+    Ok(())
+}
@@ -0,0 +1,128 @@
+use std::{cell::RefCell, error::Error, rc::Rc};
+
+use boon::{CompileError, CompileObserver, Compiler, SchemaIndex, Schemas, UrlLoader};
+use serde_json::{json, Value};
+
+#[derive(Debug, PartialEq)]
+enum Event {
+    Enqueue(String),
+    LoadStart(String),
+    LoadEnd(String, bool),
+    Compiled(String, SchemaIndex),
+}
+
+type Events = Rc<RefCell<Vec<Event>>>;
+
+#[derive(Default)]
+struct Recorder(Events);
+
+impl CompileObserver for Recorder {
+    fn on_enqueue(&self, loc: &str) {
+        self.0.borrow_mut().push(Event::Enqueue(loc.into()));
+    }
+
+    fn on_load_start(&self, url: &str) {
+        self.0.borrow_mut().push(Event::LoadStart(url.into()));
+    }
+
+    fn on_load_end(&self, url: &str, result: Result<(), &CompileError>) {
+        self.0
+            .borrow_mut()
+            .push(Event::LoadEnd(url.into(), result.is_ok()));
+    }
+
+    fn on_schema_compiled(&self, loc: &str, sch_index: SchemaIndex) {
+        self.0
+            .borrow_mut()
+            .push(Event::Compiled(loc.into(), sch_index));
+    }
+}
+
+struct Loader;
+
+impl UrlLoader for Loader {
+    fn load(&self, url: &str) -> Result<Value, Box<dyn Error>> {
+        match url {
+            "http://a.com/b.json" => Ok(json!({"items": {"$ref": "c.json"}})),
+            "http://a.com/c.json" => Ok(json!({"type": "string"})),
+            _ => Err("not found".into()),
+        }
+    }
+}
+
+fn compiler() -> Result<(Compiler, Events), Box<dyn Error>> {
+    let mut compiler = Compiler::new();
+    compiler.use_loader(Box::new(Loader));
+    let recorder = Recorder::default();
+    let events = Rc::clone(&recorder.0);
+    compiler.set_observer(Box::new(recorder));
+    compiler.add_resource(
+        "http://a.com/main.json",
+        json!({"properties": {"b": {"$ref": "b.json"}}}),
+    )?;
+    Ok((compiler, events))
+}
+
+#[test]
+fn test_event_order() -> Result<(), Box<dyn Error>> {
+    let (mut compiler, events) = compiler()?;
+    let mut schemas = Schemas::new();
+    compiler.compile("http://a.com/main.json", &mut schemas)?;
+    let events = events.take();
+
+    let pos = |want: &Event| events.iter().position(|e| e == want).unwrap();
+    let compiled = events
+        .iter()
+        .filter_map(|e| match e {
+            Event::Compiled(loc, idx) => Some((loc, *idx)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(compiled.len(), 5);
+    for (loc, idx) in compiled {
+        let compiled_at = pos(&Event::Compiled(loc.clone(), idx));
+        assert!(pos(&Event::Enqueue(loc.clone())) < compiled_at, "{loc}");
+        assert_eq!(schemas.contains_location(loc), Some(idx));
+        let (url, _) = loc.split_once('#').unwrap();
+        if url != "http://a.com/main.json" {
+            let load_end = pos(&Event::LoadEnd(url.into(), true));
+            assert_eq!(pos(&Event::LoadStart(url.into())) + 1, load_end);
+            assert!(load_end < compiled_at, "{loc}");
+        }
+    }
+    // each document is loaded once
+    let loads = events
+        .iter()
+        .filter(|e| matches!(e, Event::LoadStart(_)))
+        .count();
+    assert_eq!(loads, 2);
+
+    // compiled schemas are not compiled again
+    let (mut compiler, events) = (compiler, Rc::new(RefCell::new(vec![])));
+    compiler.set_observer(Box::new(Recorder(Rc::clone(&events))));
+    compiler.compile("http://a.com/c.json", &mut schemas)?;
+    assert!(events.borrow().is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_load_failure() -> Result<(), Box<dyn Error>> {
+    let (mut compiler, events) = compiler()?;
+    compiler.add_resource("http://a.com/bad.json", json!({"$ref": "missing.json"}))?;
+    let result = compiler.compile("http://a.com/bad.json", &mut Schemas::new());
+    assert!(result.is_err());
+    let mut events = events.take();
+    assert!(
+        matches!(&events.remove(2), Event::Compiled(loc, _) if loc == "http://a.com/bad.json#")
+    );
+    assert_eq!(
+        events,
+        [
+            Event::Enqueue("http://a.com/bad.json#".into()),
+            Event::Enqueue("http://a.com/missing.json#".into()),
+            Event::LoadStart("http://a.com/missing.json".into()),
+            Event::LoadEnd("http://a.com/missing.json".into(), false),
+        ]
+    );
+    Ok(())
+}
@@ -0,0 +1,582 @@
+use std::{any::Any, cell::RefCell, error::Error};
+
+use boon::{
+    Compiler, ContextFormat, ErrorKind, IgnoreReason, InstanceLimit, InstanceLocation,
+    InstanceVisitor, Metrics, SchemaIndex, Schemas, ValidationOptions, VisitError,
+};
+use serde_json::{json, Value};
+
+fn compile(schema: Value) -> Result<(Schemas, SchemaIndex), Box<dyn Error>> {
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://tmp.com/schema.json", schema)?;
+    let sch_index = compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+    Ok((schemas, sch_index))
+}
+
+#[test]
+fn test_default_options() -> Result<(), Box<dyn Error>> {
+    let (schemas, sch_index) = compile(json!({
+        "properties": { "a": { "type": "string" } },
+        "required": ["a"]
+    }))?;
+    let opts = ValidationOptions::default();
+    for v in [json!({"a": "x"}), json!({"a": 1}), json!({}), json!(1)] {
+        let want = schemas.validate(&v, sch_index).map_err(|e| e.to_string());
+        let got = schemas.validate_with(&v, sch_index, &opts);
+        let got = got.map(|out| out.metrics).map_err(|e| e.to_string());
+        assert_eq!(schemas.is_valid_with(&v, sch_index, &opts), want.is_ok());
+        assert_eq!(got, want.map(|_| None), "{v}");
+    }
+    Ok(())
+}
+
+#[test]
+fn test_fail_fast() -> Result<(), Box<dyn Error>> {
+    let (schemas, sch_index) = compile(json!({
+        "required": ["a"],
+        "properties": { "b": { "type": "string" } },
+        "allOf": [{ "minProperties": 3 }, { "maxProperties": 0 }]
+    }))?;
+    let v = json!({"b": 1});
+
+    let Err(e) = schemas.validate(&v, sch_index) else {
+        panic!("validation must fail");
+    };
+    assert_eq!(e.causes.len(), 3);
+
+    let opts = ValidationOptions::new().fail_fast(true);
+    let Err(e) = schemas.validate_with(&v, sch_index, &opts) else {
+        panic!("validation must fail");
+    };
+    assert_eq!(e.causes.len(), 1, "{e:#}");
+    assert!(!schemas.is_valid_with(&v, sch_index, &opts));
+    Ok(())
+}
+
+#[test]
+fn test_collect_metrics() -> Result<(), Box<dyn Error>> {
+    let (schemas, sch_index) = compile(json!({
+        "properties": { "a": { "type": "string" } },
+        "items": { "$ref": "#/properties/a" }
+    }))?;
+    let opts = ValidationOptions::new().collect_metrics(true);
+    let tests = [
+        (json!({"a": "x"}), 2),
+        // each item evaluates items schema and its $ref target
+        (json!(["x", "y", "z"]), 7),
+        (json!(1), 1),
+    ];
+    for (v, evaluations) in tests {
+        let out = schemas
+            .validate_with(&v, sch_index, &opts)
+            .map_err(|e| e.to_string())?;
+        assert_eq!(out.metrics, Some(Metrics { evaluations }), "{v}");
+    }
+    Ok(())
+}
+
+#[test]
+fn test_max_operations() -> Result<(), Box<dyn Error>> {
+    // each level doubles the work, when instance is invalid
+    let mut defs = serde_json::Map::new();
+    for i in 0..40 {
+        let next = json!({ "$ref": format!("#/$defs/d{}", i + 1) });
+        defs.insert(format!("d{i}"), json!({ "anyOf": [next, next] }));
+    }
+    defs.insert("d40".into(), json!({ "type": "string" }));
+    let (schemas, sch_index) = compile(json!({
+        "properties": { "a": { "$ref": "#/$defs/d0" } },
+        "$defs": defs
+    }))?;
+    let v = json!({"a": 1});
+
+    let opts = ValidationOptions::new().max_operations(1000);
+    assert!(!schemas.is_valid_with(&v, sch_index, &opts));
+    let Err(e) = schemas.validate_with(&v, sch_index, &opts) else {
+        panic!("validation must fail");
+    };
+    assert_eq!(e.causes.len(), 1);
+    let cause = &e.causes[0];
+    assert!(
+        matches!(cause.kind, ErrorKind::BudgetExceeded { operations: 1000 }),
+        "{e:#}"
+    );
+    assert_eq!(cause.instance_location.to_string(), "/a");
+
+    // within budget
+    let v = json!({"b": 1});
+    let out = schemas
+        .validate_with(&v, sch_index, &opts.collect_metrics(true))
+        .map_err(|e| e.to_string())?;
+    assert_eq!(out.metrics, Some(Metrics { evaluations: 1 }));
+    Ok(())
+}
+
+#[test]
+fn test_instance_limits() -> Result<(), Box<dyn Error>> {
+    let (schemas, sch_index) = compile(json!({
+        "properties": {
+            "list": { "items": { "type": "string" } },
+            "map": { "additionalProperties": { "type": "integer" } }
+        }
+    }))?;
+    let v = json!({
+        "list": ["a", "bb", "ccc€"],
+        "map": { "x": 1, "y": 2, "z": 3, "w": 4 }
+    });
+    assert!(schemas.validate(&v, sch_index).is_ok());
+
+    let cases = [
+        (
+            ValidationOptions::new().max_instance_nodes(9),
+            "/map",
+            InstanceLimit::Nodes,
+            9,
+        ),
+        (
+            ValidationOptions::new().max_array_length(2),
+            "/list",
+            InstanceLimit::ArrayLength,
+            2,
+        ),
+        (
+            ValidationOptions::new().max_object_members(3),
+            "/map",
+            InstanceLimit::ObjectMembers,
+            3,
+        ),
+        (
+            ValidationOptions::new().max_string_length(5),
+            "/list/2",
+            InstanceLimit::StringLength,
+            5,
+        ),
+    ];
+    for (opts, loc, want_kind, want_limit) in cases {
+        assert!(!schemas.is_valid_with(&v, sch_index, &opts));
+        let Err(e) = schemas.validate_with(&v, sch_index, &opts) else {
+            panic!("{want_kind:?} must be exceeded");
+        };
+        assert_eq!(e.causes.len(), 1);
+        let cause = &e.causes[0];
+        assert!(
+            matches!(cause.kind, ErrorKind::InstanceTooLarge { limit, kind } if limit == want_limit && kind == want_kind),
+            "{e:#}"
+        );
+        assert_eq!(cause.instance_location.to_string(), loc);
+    }
+
+    // exactly at limits
+    let opts = ValidationOptions::new()
+        .max_instance_nodes(10)
+        .max_array_length(3)
+        .max_object_members(4)
+        .max_string_length(6);
+    assert!(schemas.validate_with(&v, sch_index, &opts).is_ok());
+    Ok(())
+}
+
+#[test]
+fn test_warn_ignored() -> Result<(), Box<dyn Error>> {
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.enable_format_assertions();
+    compiler.disable_keyword("maxLength");
+    compiler.add_resource(
+        "http://tmp.com/schema.json",
+        json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "properties": {
+                "a": { "maxLength": 1 },
+                "b": { "format": "dummy" }
+            },
+            "items": { "unevaluatedProperties": false }
+        }),
+    )?;
+    let sch_index = compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+    let v = json!({"a": "xyz", "b": "xyz", "c": 1});
+
+    // disabled by default
+    let out = schemas
+        .validate_with(&v, sch_index, &ValidationOptions::new())
+        .map_err(|e| e.to_string())?;
+    assert!(out.warnings.is_empty());
+
+    let opts = ValidationOptions::new().warn_ignored(true);
+    let out = schemas
+        .validate_with(&v, sch_index, &opts)
+        .map_err(|e| e.to_string())?;
+    let got = out
+        .warnings
+        .iter()
+        .map(|w| {
+            let ErrorKind::IgnoredKeyword { keyword, reason } = &w.kind else {
+                panic!("unexpected warning {w}");
+            };
+            (
+                w.instance_location.to_string(),
+                keyword.to_string(),
+                *reason,
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        got,
+        [
+            (
+                "/a".to_owned(),
+                "maxLength".to_owned(),
+                IgnoreReason::Disabled
+            ),
+            (
+                "/b".to_owned(),
+                "format".to_owned(),
+                IgnoreReason::UnknownFormat
+            ),
+        ]
+    );
+    assert_eq!(
+        out.warnings[0].kind.to_string(),
+        "keyword 'maxLength' is ignored: disabled in compiler"
+    );
+
+    // schema not evaluated, reports nothing
+    let v = json!({"c": 1});
+    let out = schemas
+        .validate_with(&v, sch_index, &opts)
+        .map_err(|e| e.to_string())?;
+    assert!(out.warnings.is_empty());
+
+    let v = json!([{}]);
+    let out = schemas
+        .validate_with(&v, sch_index, &opts)
+        .map_err(|e| e.to_string())?;
+    assert_eq!(out.warnings.len(), 1);
+    assert!(matches!(
+        out.warnings[0].kind,
+        ErrorKind::IgnoredKeyword {
+            reason: IgnoreReason::LaterDraft,
+            ..
+        }
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_fail_fast_additional_properties() -> Result<(), Box<dyn Error>> {
+    let (schemas, sch_index) = compile(json!({
+        "properties": { "a": { "type": "string" }, "b": true },
+        "additionalProperties": false
+    }))?;
+    let v = json!({"a": 1, "x": 1, "y": 1});
+
+    let Err(e) = schemas.validate(&v, sch_index) else {
+        panic!("validation must fail");
+    };
+    assert_eq!(e.causes.len(), 2, "{e:#}");
+
+    // offending property is reported, without validating property values
+    let opts = ValidationOptions::new()
+        .fail_fast(true)
+        .collect_metrics(true);
+    let Err(e) = schemas.validate_with(&v, sch_index, &opts) else {
+        panic!("validation must fail");
+    };
+    assert_eq!(e.causes.len(), 1, "{e:#}");
+    let ErrorKind::AdditionalProperties { got } = &e.causes[0].kind else {
+        panic!("want additionalProperties error: {e:#}");
+    };
+    assert_eq!(got, &["x"]);
+    assert!(!schemas.is_valid_with(&v, sch_index, &opts));
+    Ok(())
+}
+
+#[test]
+fn test_context_format() -> Result<(), Box<dyn Error>> {
+    struct Allowlist(Vec<&'static str>);
+
+    fn allowed(v: &Value, ctx: Option<&dyn Any>) -> Result<(), Box<dyn Error>> {
+        let Value::String(s) = v else {
+            return Ok(());
+        };
+        match ctx.and_then(|ctx| ctx.downcast_ref::<Allowlist>()) {
+            Some(list) if list.0.contains(&s.as_str()) => Ok(()),
+            Some(_) => Err(format!("{s} is not allowed"))?,
+            None => Err("no allowlist")?,
+        }
+    }
+
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.enable_format_assertions();
+    compiler.register_context_format(ContextFormat {
+        name: "allowed",
+        func: allowed,
+    });
+    compiler.add_resource("http://tmp.com/schema.json", json!({"format": "allowed"}))?;
+    let sch_index = compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+
+    let v = json!("acme");
+    let tenant1 = Allowlist(vec!["acme"]);
+    let tenant2 = Allowlist(vec!["globex"]);
+    let opts1 = ValidationOptions::new().context(&tenant1);
+    let opts2 = ValidationOptions::new().context(&tenant2);
+    assert!(schemas.validate_with(&v, sch_index, &opts1).is_ok());
+    assert!(schemas.is_valid_with(&v, sch_index, &opts1));
+    let Err(e) = schemas.validate_with(&v, sch_index, &opts2) else {
+        panic!("validation must fail");
+    };
+    assert!(e.to_string().contains("acme is not allowed"), "{e}");
+    assert!(!schemas.is_valid_with(&v, sch_index, &opts2));
+
+    // without context
+    assert!(schemas.validate(&v, sch_index).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_collect_branches() -> Result<(), Box<dyn Error>> {
+    let (schemas, sch_index) = compile(json!({
+        "properties": {
+            "a": {
+                "oneOf": [
+                    { "type": "string" },
+                    {
+                        "oneOf": [
+                            { "type": "integer", "minimum": 10 },
+                            { "type": "integer", "maximum": 5 }
+                        ]
+                    }
+                ]
+            },
+            "b": {
+                "anyOf": [
+                    { "type": "number" },
+                    { "minimum": 0 },
+                    { "type": "string" }
+                ]
+            },
+            "c": {
+                "anyOf": [
+                    { "allOf": [{ "oneOf": [true] }, false] },
+                    true
+                ]
+            }
+        }
+    }))?;
+    let v = json!({"a": 3, "b": 2, "c": null});
+
+    // disabled by default
+    let out = schemas
+        .validate_with(&v, sch_index, &ValidationOptions::new())
+        .map_err(|e| e.to_string())?;
+    assert!(out.branches.is_empty());
+
+    let opts = ValidationOptions::new().collect_branches(true);
+    let out = schemas
+        .validate_with(&v, sch_index, &opts)
+        .map_err(|e| e.to_string())?;
+    let mut got = out
+        .branches
+        .iter()
+        .map(|b| {
+            (
+                b.schema_url
+                    .trim_start_matches("http://tmp.com/schema.json#"),
+                b.keyword,
+                b.instance_location.to_string(),
+                b.indexes.clone(),
+            )
+        })
+        .collect::<Vec<_>>();
+    got.sort();
+    assert_eq!(
+        got,
+        [
+            ("/properties/a", "oneOf", "/a".to_owned(), vec![1]),
+            ("/properties/a/oneOf/1", "oneOf", "/a".to_owned(), vec![1]),
+            ("/properties/b", "anyOf", "/b".to_owned(), vec![0, 1]),
+            // oneOf within failed subschema is not reported
+            ("/properties/c", "anyOf", "/c".to_owned(), vec![1]),
+        ]
+    );
+
+    // nothing reported on failure
+    let v = json!({"a": 7});
+    assert!(schemas.validate_with(&v, sch_index, &opts).is_err());
+    Ok(())
+}
+
+// records locations where `id` schema is evaluated, and rejects unknown ids
+struct IdVisitor {
+    id: SchemaIndex,
+    visited: RefCell<Vec<String>>,
+}
+
+impl InstanceVisitor for IdVisitor {
+    fn visit(&self, loc: &InstanceLocation, v: &Value, sch: SchemaIndex) -> Result<(), VisitError> {
+        if sch != self.id {
+            return Ok(());
+        }
+        self.visited.borrow_mut().push(loc.to_string());
+        match v.as_str() {
+            Some(id) if id.starts_with('u') => Ok(()),
+            _ => Err(VisitError::new("id-exists", format!("unknown id {v}"))),
+        }
+    }
+}
+
+#[test]
+fn test_visitor() -> Result<(), Box<dyn Error>> {
+    let schema = json!({
+        "$defs": {"id": {"type": "string"}},
+        "properties": {
+            "owner": {"$ref": "#/$defs/id"},
+            "kind": {"enum": ["a", "b"]}
+        },
+        "propertyNames": {"$ref": "#/$defs/id"},
+        "if": {"properties": {"kind": {"const": "a"}}},
+        "then": {"properties": {"extra": {"$ref": "#/$defs/id"}}},
+        "else": {"properties": {"other": {"$ref": "#/$defs/id"}}}
+    });
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.add_resource("schema.json", schema)?;
+    let sch = compiler.compile("schema.json", &mut schemas)?;
+    let id = compiler.compile("schema.json#/$defs/id", &mut schemas)?;
+
+    let visit = |v: &Value| {
+        let visitor = IdVisitor {
+            id,
+            visited: RefCell::default(),
+        };
+        let opts = ValidationOptions::new().visitor(&visitor);
+        let result = schemas.validate_with(v, sch, &opts).map(|_| ());
+        let errors = result.err().map(|e| e.to_string());
+        (visitor.visited.into_inner(), errors)
+    };
+
+    // only branch that applied is visited
+    let v = json!({"owner": "u1", "kind": "a", "extra": "u2", "other": "u3"});
+    assert_eq!(visit(&v), (vec!["/owner".into(), "/extra".into()], None));
+    let v = json!({"owner": "u1", "kind": "b", "extra": "u2", "other": "u3"});
+    assert_eq!(visit(&v), (vec!["/owner".into(), "/other".into()], None));
+
+    // failure is reported at the value
+    let v = json!({"owner": "x1", "kind": "a", "extra": 1});
+    let (visited, errors) = visit(&v);
+    assert_eq!(visited, ["/owner"]); // `extra` fails type
+    let errors = errors.unwrap();
+    assert!(
+        errors.contains("at '/owner': unknown id \"x1\""),
+        "{errors}"
+    );
+    assert!(errors.contains("at '/extra': want string"), "{errors}");
+
+    // items checked in place are visited too
+    compiler.add_resource("ids.json", json!({"items": {"type": "string"}}))?;
+    let sch = compiler.compile("ids.json", &mut schemas)?;
+    let visitor = IdVisitor {
+        id: compiler.compile("ids.json#/items", &mut schemas)?,
+        visited: RefCell::default(),
+    };
+    let opts = ValidationOptions::new().visitor(&visitor);
+    let v = json!(["u1", "x2"]);
+    let result = schemas.validate_with(&v, sch, &opts);
+    assert_eq!(*visitor.visited.borrow(), ["/0", "/1"]);
+    assert!(result.is_err());
+
+    // schema with $ref is not visited, before 2019-09
+    let schema = json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "definitions": {"id": {"type": "string"}},
+        "properties": {"owner": {"$ref": "#/definitions/id"}}
+    });
+    compiler.add_resource("draft7.json", schema)?;
+    let sch = compiler.compile("draft7.json", &mut schemas)?;
+    let visitor = IdVisitor {
+        id: compiler.compile("draft7.json#/properties/owner", &mut schemas)?,
+        visited: RefCell::default(),
+    };
+    let opts = ValidationOptions::new().visitor(&visitor);
+    let v = json!({"owner": "x1"});
+    assert!(schemas.validate_with(&v, sch, &opts).is_ok());
+    assert!(visitor.visited.borrow().is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_loose_enum_scalars() -> Result<(), Box<dyn Error>> {
+    let (schemas, sch) = compile(json!({
+        "properties": {
+            "e": { "enum": [1, "2", 3.5, "x"] },
+            "c": { "const": "1.0" }
+        }
+    }))?;
+    let strict = ValidationOptions::new();
+    let loose = ValidationOptions::new().loose_enum_scalars(true);
+    let valid = |v: Value, opts: &ValidationOptions| schemas.is_valid_with(&v, sch, opts);
+
+    // (instance, valid in strict mode, valid in loose mode)
+    let cases = [
+        (json!({"e": 1}), true, true),
+        (json!({"e": 1.0}), true, true),
+        (json!({"e": "1"}), false, true),
+        (json!({"e": "1.0"}), false, true),
+        (json!({"e": "1e0"}), false, true),
+        (json!({"e": 2}), false, true),
+        (json!({"e": 2.0}), false, true),
+        (json!({"e": "3.50"}), false, true),
+        (json!({"e": "2.0"}), false, false), // strings compared as strings
+        (json!({"e": " 1"}), false, false),  // not json number
+        (json!({"e": "+1"}), false, false),
+        (json!({"e": true}), false, false),
+        (json!({"c": "1.0"}), true, true),
+        (json!({"c": 1}), false, true),
+        (json!({"c": "1"}), false, false),
+    ];
+    for (instance, want_strict, want_loose) in cases {
+        assert_eq!(valid(instance.clone(), &strict), want_strict, "{instance}");
+        assert_eq!(valid(instance.clone(), &loose), want_loose, "{instance}");
+    }
+
+    // error reports number compared
+    let instance = json!({"e": "4", "c": 2});
+    let e = schemas
+        .validate_with(&instance, sch, &loose)
+        .expect_err("must fail");
+    let mut kinds = e.causes.iter().map(|c| &c.kind).collect::<Vec<_>>();
+    kinds.sort_by_key(|k| k.to_string());
+    let [ErrorKind::Const {
+        as_number: c_num, ..
+    }, ErrorKind::Enum {
+        as_number: e_num, ..
+    }] = kinds[..]
+    else {
+        panic!("want enum and const errors: {e:#}");
+    };
+    assert_eq!(
+        e_num.as_ref().map(ToString::to_string).as_deref(),
+        Some("4")
+    );
+    assert_eq!(
+        c_num.as_ref().map(ToString::to_string).as_deref(),
+        Some("2")
+    );
+    assert_eq!(
+        kinds[0].to_string(),
+        "value must be '1.0' (also compared as number 2)"
+    );
+    assert_eq!(
+        kinds[1].to_string(),
+        "value must be one of 1, '2', 3.5, 'x' (also compared as number 4)"
+    );
+
+    // strict mode reports no number
+    let e = schemas.validate(&instance, sch).expect_err("must fail");
+    for cause in &e.causes {
+        assert!(!cause.kind.to_string().contains("compared as number"));
+    }
+    Ok(())
+}
@@ -0,0 +1,115 @@
+#![cfg(feature = "parallel")]
+
+use std::error::Error;
+
+use boon::{CompileError, Compiler, Schemas};
+use serde_json::{json, Value};
+
+fn compiler(n: usize) -> Result<(Compiler, Vec<String>), Box<dyn Error>> {
+    let mut c = Compiler::new();
+    let mut locs = vec![];
+    for i in 0..n {
+        let loc = format!("http://tmp.com/s{i}.json");
+        c.add_resource(
+            &loc,
+            json!({
+                "type": "object",
+                "properties": {
+                    "id": { "const": i },
+                    "tags": { "items": { "$ref": "#/$defs/tag" } }
+                },
+                "$defs": { "tag": { "type": "string", "minLength": 1 } }
+            }),
+        )?;
+        locs.push(loc);
+    }
+    Ok((c, locs))
+}
+
+#[test]
+fn test_compile_all_parallel() -> Result<(), Box<dyn Error>> {
+    let (mut c, locs) = compiler(50)?;
+    let (schemas, indexes) = c.compile_all_parallel(locs.clone())?;
+    assert_eq!(indexes.len(), locs.len());
+    for (i, &sch) in indexes.iter().enumerate() {
+        assert!(schemas
+            .validate(&json!({"id": i, "tags": ["a"]}), sch)
+            .is_ok());
+        assert!(schemas
+            .validate(&json!({"id": i, "tags": [""]}), sch)
+            .is_err());
+        assert!(schemas.validate(&json!({"id": i + 1}), sch).is_err());
+    }
+
+    // same as sequential compilation
+    let (mut c, locs) = compiler(50)?;
+    let mut sequential = Schemas::new();
+    for (loc, &sch) in locs.iter().zip(&indexes) {
+        assert_eq!(c.compile(loc, &mut sequential)?, sch);
+    }
+    assert_eq!(sequential.size(), schemas.size());
+
+    // deterministic
+    let (mut c, locs) = compiler(50)?;
+    let (again, again_indexes) = c.compile_all_parallel(locs)?;
+    assert_eq!(again_indexes, indexes);
+    assert_eq!(again.size(), schemas.size());
+    Ok(())
+}
+
+#[test]
+fn test_compile_all_parallel_cross_references() -> Result<(), Box<dyn Error>> {
+    let (mut c, mut locs) = compiler(10)?;
+    c.add_resource(
+        "http://tmp.com/all.json",
+        json!({ "anyOf": [{ "$ref": "s1.json" }, { "$ref": "s2.json" }] }),
+    )?;
+    locs.insert(0, "http://tmp.com/all.json".to_owned());
+    locs.push("http://tmp.com/s1.json#/$defs/tag".to_owned());
+    let (schemas, indexes) = c.compile_all_parallel(locs)?;
+    let all = indexes[0];
+    assert!(schemas.validate(&json!({"id": 1}), all).is_ok());
+    assert!(schemas.validate(&json!({"id": 3}), all).is_err());
+    assert!(schemas.validate(&Value::from("a"), indexes[11]).is_ok());
+    assert!(schemas.validate(&Value::from(""), indexes[11]).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_compile_all_parallel_error() -> Result<(), Box<dyn Error>> {
+    let (mut c, mut locs) = compiler(10)?;
+    c.add_resource("http://tmp.com/bad1.json", json!({"minLength": "1"}))?;
+    c.add_resource("http://tmp.com/bad2.json", json!({"maxLength": "1"}))?;
+    locs.insert(3, "http://tmp.com/bad2.json".to_owned());
+    locs.insert(7, "http://tmp.com/bad1.json".to_owned());
+    let Err(e) = c.compile_all_parallel(locs) else {
+        panic!("must fail");
+    };
+    assert!(
+        matches!(&e, CompileError::ValidationError { url, .. } if url.contains("bad2.json")),
+        "{e:?}"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_compile_all_parallel_report() -> Result<(), Box<dyn Error>> {
+    let (mut c, mut locs) = compiler(3)?;
+    // compiled sequentially, as it refers to other document
+    c.add_resource("http://tmp.com/r.json", json!({"$ref": "s0.json"}))?;
+    c.add_resource("http://tmp.com/unused.json", json!({}))?;
+    locs.push("http://tmp.com/r.json".to_owned());
+    c.compile_all_parallel(locs.clone())?;
+
+    let report = c.last_compile_report();
+    locs.sort();
+    assert_eq!(report.documents, locs);
+    let roots = report
+        .subschemas
+        .iter()
+        .filter(|loc| loc.ends_with(".json#"))
+        .collect::<Vec<_>>();
+    assert_eq!(roots.len(), locs.len());
+    assert_eq!(report.unused_resources, ["http://tmp.com/unused.json"]);
+    Ok(())
+}
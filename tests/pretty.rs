@@ -0,0 +1,103 @@
+use boon::{span_of, InstanceLocation, InstanceToken};
+
+fn span<'t>(text: &'t str, tokens: Vec<InstanceToken>) -> Option<&'t str> {
+    let loc = InstanceLocation { tokens };
+    span_of(text, &loc).map(|r| &text[r])
+}
+
+#[test]
+fn test_span_of() {
+    let text = r#" {
+        "a": [1, "x,]}", {"b": null}],
+        "esc\"aped/key": {"c": -1.5e3 },
+        "dup": 1,
+        "dup": [ ]
+    } "#;
+    assert_eq!(span(text, vec![]), Some(&text[1..text.len() - 1]));
+    assert_eq!(
+        span(text, vec!["a".into()]),
+        Some(r#"[1, "x,]}", {"b": null}]"#)
+    );
+    assert_eq!(span(text, vec!["a".into(), 0.into()]), Some("1"));
+    assert_eq!(span(text, vec!["a".into(), 1.into()]), Some(r#""x,]}""#));
+    assert_eq!(
+        span(text, vec!["a".into(), 2.into(), "b".into()]),
+        Some("null")
+    );
+    assert_eq!(
+        span(text, vec!["esc\"aped/key".into(), "c".into()]),
+        Some("-1.5e3")
+    );
+    assert_eq!(span(text, vec!["dup".into()]), Some("[ ]"));
+
+    // not found
+    assert_eq!(span(text, vec!["a".into(), 3.into()]), None);
+    assert_eq!(span(text, vec!["x".into()]), None);
+    assert_eq!(span(text, vec![0.into()]), None);
+    assert_eq!(span(text, vec!["dup".into(), 0.into()]), None);
+    assert_eq!(span("{\"a\": [1, 2", vec!["a".into(), 5.into()]), None);
+}
+
+#[cfg(feature = "fancy-errors")]
+#[test]
+fn test_render_pretty() -> Result<(), Box<dyn std::error::Error>> {
+    use boon::{ColorChoice, Compiler, DisplayOptions, PrettyOptions, Schemas};
+    use serde_json::{json, Value};
+
+    let schema = json!({
+        "properties": {
+            "name": { "type": "string" },
+            "tags": { "items": { "maxLength": 3 } }
+        }
+    });
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://tmp.com/schema.json", schema)?;
+    let sch_index = compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+    let text = "{\n  \"name\": 12,\n  \"tags\": [\"ok\", \"toolong\"]\n}";
+    let instance: Value = serde_json::from_str(text)?;
+    let e = schemas.validate(&instance, sch_index).unwrap_err();
+
+    let opts = PrettyOptions::new().color(ColorChoice::Never).source(text);
+    let want = [
+        "jsonschema validation failed with http://tmp.com/schema.json#",
+        "├── at '/name' [S#/properties/name/type]: want string, but got number",
+        "│   2 │   \"name\": 12,",
+        "│     │           ^^",
+        "└── at '/tags/1' [S#/properties/tags/items/maxLength]: length must be <=3, but got 7",
+        "    3 │   \"tags\": [\"ok\", \"toolong\"]",
+        "      │                  ^^^^^^^^^",
+    ];
+    assert_eq!(e.render_pretty(&opts), want.join("\n"));
+
+    // long lines are shown around the value
+    let tags = (0..100).map(|i| format!("t{i:02}")).collect::<Vec<_>>();
+    let text = serde_json::to_string(&json!({ "name": "x", "tags": tags }))?;
+    let text = text.replace("\"t50\"", "\"toolong\"");
+    let instance: Value = serde_json::from_str(&text)?;
+    let e2 = schemas.validate(&instance, sch_index).unwrap_err();
+    let opts2 = PrettyOptions::new().color(ColorChoice::Never).source(&text);
+    let lines = e2.render_pretty(&opts2);
+    let lines = lines.lines().collect::<Vec<_>>();
+    let [_, _, source, carets] = lines[..] else {
+        panic!("{lines:?}");
+    };
+    assert!(
+        source.starts_with("    1 │ …") && source.ends_with('…'),
+        "{source}"
+    );
+    let col = carets.chars().position(|c| c == '^').unwrap();
+    let value = source.chars().skip(col).take(9).collect::<String>();
+    assert_eq!(value, "\"toolong\"", "{source}\n{carets}");
+
+    // colors
+    let colored = e.render_pretty(&opts.clone().color(ColorChoice::Always));
+    assert!(colored.contains("\x1b[31m"), "{colored}");
+    assert!(!e.render_pretty(&opts).contains('\x1b'));
+
+    // source is not shown, if values are redacted
+    let opts = opts.display_options(DisplayOptions::new().redact_values(true));
+    let redacted = e.render_pretty(&opts);
+    assert!(!redacted.contains("toolong"), "{redacted}");
+    Ok(())
+}
@@ -0,0 +1,563 @@
+use std::error::Error;
+
+use boon::{AdditionalView, Compiler, SchemaIndex, SchemaKind, Schemas, TriState, Type};
+use serde_json::{json, Value};
+
+fn compile(schema: Value) -> Result<(Schemas, SchemaIndex), Box<dyn Error>> {
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://tmp.com/schema.json", schema)?;
+    let sch_index = compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+    Ok((schemas, sch_index))
+}
+
+#[test]
+fn test_allows_additional_properties() -> Result<(), Box<dyn Error>> {
+    let (schemas, sch) = compile(json!({
+        "properties": {
+            "closed": { "additionalProperties": false },
+            "open": { "properties": { "a": true } },
+            "typed": { "additionalProperties": { "type": "string" } },
+            "patterns": {
+                "patternProperties": { "^x-": true },
+                "additionalProperties": false
+            },
+            "names": { "propertyNames": { "maxLength": 3 } },
+            "anyOf": {
+                "anyOf": [{ "required": ["a"] }, { "required": ["b"] }]
+            },
+            "anyOfClosed": {
+                "additionalProperties": false,
+                "anyOf": [{ "required": ["a"] }, { "required": ["b"] }]
+            },
+            "never": false,
+            "ref": { "$ref": "#/$defs/closed" }
+        },
+        "patternProperties": {
+            "^p-": { "additionalProperties": false }
+        },
+        "additionalProperties": { "properties": { "deep": { "additionalProperties": false } } },
+        "$defs": {
+            "closed": { "additionalProperties": false }
+        }
+    }))?;
+    let cases = [
+        (vec![], TriState::Yes),
+        (vec!["closed"], TriState::No),
+        (vec!["open"], TriState::Yes),
+        (vec!["typed"], TriState::Yes),
+        (vec!["patterns"], TriState::Unknown),
+        (vec!["names"], TriState::Unknown),
+        (vec!["anyOf"], TriState::Unknown),
+        (vec!["anyOfClosed"], TriState::No),
+        (vec!["never"], TriState::No),
+        (vec!["ref"], TriState::No),
+        (vec!["p-1"], TriState::No),
+        (vec!["other"], TriState::Yes),
+        (vec!["other", "deep"], TriState::No),
+        (vec!["closed", "x", "y"], TriState::No),
+    ];
+    for (path, want) in cases {
+        let got = schemas.allows_additional_properties(sch, &path);
+        assert_eq!(got, want, "path {path:?}");
+    }
+    Ok(())
+}
+
+#[test]
+fn test_allows_additional_properties_allof() -> Result<(), Box<dyn Error>> {
+    let (schemas, sch) = compile(json!({
+        "$defs": {
+            "base": {
+                "properties": { "id": { "type": "string" } },
+                "required": ["id"]
+            },
+            "closed": {
+                "allOf": [{ "$ref": "#/$defs/base" }],
+                "properties": { "name": true },
+                "unevaluatedProperties": false
+            },
+            "extensible": {
+                "allOf": [
+                    { "$ref": "#/$defs/base" },
+                    { "additionalProperties": true }
+                ],
+                "unevaluatedProperties": false
+            },
+            "conditional": {
+                "allOf": [{ "$ref": "#/$defs/base" }],
+                "if": { "required": ["kind"] },
+                "then": { "properties": { "kind": true } },
+                "unevaluatedProperties": false
+            }
+        },
+        "properties": {
+            "closed": { "$ref": "#/$defs/closed" },
+            "extensible": { "$ref": "#/$defs/extensible" },
+            "conditional": { "$ref": "#/$defs/conditional" },
+            "both": {
+                "allOf": [
+                    { "properties": { "a": true } },
+                    { "properties": { "b": true }, "additionalProperties": false }
+                ]
+            }
+        }
+    }))?;
+    let cases = [
+        (vec!["closed"], TriState::No),
+        (vec!["extensible"], TriState::Yes),
+        (vec!["conditional"], TriState::Unknown),
+        (vec!["both"], TriState::No),
+        // evaluated by allOf, so unevaluatedProperties does not apply
+        (vec!["closed", "id"], TriState::Yes),
+        (vec!["closed", "other"], TriState::No),
+    ];
+    for (path, want) in cases {
+        let got = schemas.allows_additional_properties(sch, &path);
+        assert_eq!(got, want, "path {path:?}");
+    }
+    Ok(())
+}
+
+#[test]
+fn test_query_cycles() -> Result<(), Box<dyn Error>> {
+    let (schemas, sch) = compile(json!({
+        "$ref": "#/$defs/node",
+        "$defs": {
+            "node": {
+                "allOf": [{ "$ref": "#/$defs/node" }],
+                "properties": {
+                    "value": true,
+                    "children": { "additionalProperties": { "$ref": "#/$defs/node" } }
+                },
+                "required": ["value"],
+                "additionalProperties": false
+            }
+        }
+    }))?;
+    let path = ["children", "x", "children", "y"];
+    assert_eq!(
+        schemas.allows_additional_properties(sch, &path),
+        TriState::No
+    );
+    assert_eq!(
+        schemas.is_property_required(sch, &["children", "x", "value"]),
+        TriState::Yes
+    );
+    assert_eq!(
+        schemas.is_property_required(sch, &["children", "x", "children"]),
+        TriState::No
+    );
+    Ok(())
+}
+
+#[test]
+fn test_draft7_ref_siblings() -> Result<(), Box<dyn Error>> {
+    let (schemas, sch) = compile(json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "definitions": { "open": { "required": ["a"] } },
+        "$ref": "#/definitions/open",
+        "additionalProperties": false,
+        "required": ["b"]
+    }))?;
+    assert_eq!(
+        schemas.allows_additional_properties(sch, &[]),
+        TriState::Yes
+    );
+    assert_eq!(schemas.is_property_required(sch, &["a"]), TriState::Yes);
+    assert_eq!(schemas.is_property_required(sch, &["b"]), TriState::No);
+    Ok(())
+}
+
+#[test]
+fn test_is_property_required() -> Result<(), Box<dyn Error>> {
+    let (schemas, sch) = compile(json!({
+        "allOf": [{ "required": ["a"] }],
+        "required": ["b"],
+        "properties": {
+            "obj": {
+                "required": ["x"],
+                "dependentRequired": { "x": ["y"] }
+            },
+            "any": {
+                "anyOf": [{ "required": ["x"] }, true]
+            }
+        }
+    }))?;
+    let cases = [
+        (vec![], TriState::Unknown),
+        (vec!["a"], TriState::Yes),
+        (vec!["b"], TriState::Yes),
+        (vec!["obj"], TriState::No),
+        (vec!["obj", "x"], TriState::Yes),
+        (vec!["obj", "y"], TriState::Unknown),
+        (vec!["obj", "z"], TriState::No),
+        (vec!["any", "x"], TriState::Unknown),
+    ];
+    for (path, want) in cases {
+        let got = schemas.is_property_required(sch, &path);
+        assert_eq!(got, want, "path {path:?}");
+    }
+    Ok(())
+}
+
+// checks accepted types of each schema in `$defs`
+fn check_accepted_types(defs: Value, want: &[(&str, Vec<Type>)]) -> Result<(), Box<dyn Error>> {
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://tmp.com/schema.json", json!({ "$defs": defs }))?;
+    for (name, types) in want {
+        let loc = format!("http://tmp.com/schema.json#/$defs/{name}");
+        let sch = compiler.compile(&loc, &mut schemas)?;
+        let got = schemas.accepted_types(sch).iter().collect::<Vec<_>>();
+        assert_eq!(&got, types, "{name}");
+    }
+    Ok(())
+}
+
+#[test]
+fn test_accepted_types() -> Result<(), Box<dyn Error>> {
+    use Type::*;
+    let defs = json!({
+        "any": {},
+        "never": false,
+        "type": { "type": ["string", "null"] },
+        "integer": { "type": "integer", "minimum": 0 },
+        "const": { "const": { "a": 1 } },
+        "constMismatch": { "type": "string", "const": 1 },
+        "enum": { "enum": ["a", null, 1.5] },
+        "enumIntegers": { "type": "integer", "enum": [1, 2.0, "x"] },
+        "allOf": { "allOf": [{ "type": ["string", "array"] }, { "type": ["array", "object"] }] },
+        "anyOf": { "anyOf": [{ "type": "string" }, { "type": "boolean" }] },
+        "oneOfWithAny": { "oneOf": [{ "type": "string" }, { "minimum": 1 }] },
+        "numberAndInteger": { "allOf": [{ "type": "number" }, { "type": "integer" }] },
+        "ref": { "$ref": "#/$defs/anyOf", "type": ["boolean", "null"] },
+        "not": { "type": "string", "not": { "type": "string" } }
+    });
+    let want = [
+        ("any", vec![Null, Boolean, Number, String, Array, Object]),
+        ("never", vec![]),
+        ("type", vec![Null, String]),
+        ("integer", vec![Integer]),
+        ("const", vec![Object]),
+        ("constMismatch", vec![]),
+        ("enum", vec![Null, Number, String]),
+        ("enumIntegers", vec![Integer]),
+        ("allOf", vec![Array]),
+        ("anyOf", vec![Boolean, String]),
+        (
+            "oneOfWithAny",
+            vec![Null, Boolean, Number, String, Array, Object],
+        ),
+        ("numberAndInteger", vec![Integer]),
+        ("ref", vec![Boolean]),
+        // not is not analyzed
+        ("not", vec![String]),
+    ];
+    check_accepted_types(defs, &want)
+}
+
+#[test]
+fn test_accepted_types_recursive() -> Result<(), Box<dyn Error>> {
+    use Type::*;
+    let defs = json!({
+        // cycle through allOf cannot narrow, but type at root does
+        "node": {
+            "type": "object",
+            "allOf": [{ "$ref": "#/$defs/node" }],
+            "properties": { "children": { "items": { "$ref": "#/$defs/node" } } }
+        },
+        "selfRef": { "anyOf": [{ "$ref": "#/$defs/selfRef" }, { "type": "string" }] },
+        "mutual": { "oneOf": [{ "$ref": "#/$defs/mutual2" }, { "type": "null" }] },
+        "mutual2": { "anyOf": [{ "$ref": "#/$defs/mutual" }, { "type": "array" }] },
+        "dynamic": { "$dynamicRef": "#/$defs/node", "type": ["object", "array"] }
+    });
+    let want = [
+        ("node", vec![Object]),
+        (
+            "selfRef",
+            vec![Null, Boolean, Number, String, Array, Object],
+        ),
+        ("mutual", vec![Null, Boolean, Number, String, Array, Object]),
+        (
+            "mutual2",
+            vec![Null, Boolean, Number, String, Array, Object],
+        ),
+        ("dynamic", vec![Array, Object]),
+    ];
+    check_accepted_types(defs, &want)
+}
+
+#[test]
+fn test_accepted_types_conditionals() -> Result<(), Box<dyn Error>> {
+    use Type::*;
+    let defs = json!({
+        "thenElse": {
+            "if": { "type": "string" },
+            "then": { "type": "string", "minLength": 1 },
+            "else": { "type": "integer" }
+        },
+        // missing else accepts anything failing if
+        "thenOnly": {
+            "if": { "type": "string" },
+            "then": { "type": "string" }
+        },
+        "elseOnly": {
+            "type": ["string", "number"],
+            "if": { "minLength": 3 },
+            "else": { "type": "null" }
+        },
+        "dependent": {
+            "type": ["object", "boolean"],
+            "dependentSchemas": { "a": { "type": "string" } }
+        },
+        "nested": {
+            "anyOf": [
+                { "if": true, "then": { "type": "array" }, "else": false },
+                { "allOf": [{ "oneOf": [{ "const": true }, { "const": "x" }] }, { "type": "boolean" }] }
+            ]
+        }
+    });
+    let want = [
+        ("thenElse", vec![Integer, String]),
+        (
+            "thenOnly",
+            vec![Null, Boolean, Number, String, Array, Object],
+        ),
+        ("elseOnly", vec![Number, String]),
+        ("dependent", vec![Boolean, Object]),
+        ("nested", vec![Boolean, Array]),
+    ];
+    check_accepted_types(defs, &want)
+}
+
+#[test]
+fn test_view_boolean_subschemas() -> Result<(), Box<dyn Error>> {
+    let mut compiler = Compiler::new();
+    compiler.enable_source_retention();
+    compiler.add_resource(
+        "http://tmp.com/schema.json",
+        json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "properties": {
+                "yes": true,
+                "no": false,
+                "empty": {},
+                "title": { "title": "no constraints" },
+                "list": {
+                    "items": false,
+                    "additionalProperties": { "type": "string" }
+                },
+                "tuple": {
+                    "items": [true, { "type": "string" }],
+                    "additionalItems": false
+                }
+            },
+            "not": true,
+            "additionalProperties": false
+        }),
+    )?;
+    let mut schemas = Schemas::new();
+    let sch = compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+    let view = schemas.view(sch);
+    assert_eq!(view.kind(), SchemaKind::Object);
+
+    let props = view
+        .properties()
+        .into_iter()
+        .map(|(name, v)| (name, v.kind(), v.location(), v.source()))
+        .collect::<Vec<_>>();
+    let loc = |p: &str| format!("http://tmp.com/schema.json#/properties/{p}");
+    assert_eq!(props.len(), 6);
+    assert_eq!(props[0].0, "empty");
+    assert_eq!(props[0].1, SchemaKind::Object);
+    assert_eq!(props[0].3, Some(&json!({})));
+    assert_eq!(props[2].0, "no");
+    assert_eq!(props[2].1, SchemaKind::Boolean(false));
+    assert_eq!(props[2].2, loc("no"));
+    assert_eq!(props[2].3, Some(&json!(false)));
+    assert_eq!(props[3].0, "title");
+    assert_eq!(props[3].1, SchemaKind::Object);
+    assert_eq!(props[5].0, "yes");
+    assert_eq!(props[5].1, SchemaKind::Boolean(true));
+    assert_eq!(props[5].2, loc("yes"));
+    assert_eq!(props[5].3, Some(&json!(true)));
+
+    let not = view.not().ok_or("must have not")?;
+    assert_eq!(not.kind(), SchemaKind::Boolean(true));
+    assert_eq!(not.location(), "http://tmp.com/schema.json#/not");
+    assert_eq!(
+        view.additional_properties(),
+        Some(AdditionalView::Bool {
+            value: false,
+            location: "http://tmp.com/schema.json#/additionalProperties".to_owned()
+        })
+    );
+
+    let list = props_view(&schemas, sch, "list");
+    let items = list.items().ok_or("must have items")?;
+    assert_eq!(items.kind(), SchemaKind::Boolean(false));
+    assert_eq!(items.location(), format!("{}/items", loc("list")));
+    assert_eq!(items.source(), Some(&json!(false)));
+    let Some(AdditionalView::Schema(additional)) = list.additional_properties() else {
+        panic!("additionalProperties must be subschema");
+    };
+    assert_eq!(additional.kind(), SchemaKind::Object);
+
+    let tuple = props_view(&schemas, sch, "tuple");
+    assert!(tuple.items().is_none());
+    let kinds = tuple
+        .prefix_items()
+        .iter()
+        .map(|v| v.kind())
+        .collect::<Vec<_>>();
+    assert_eq!(kinds, [SchemaKind::Boolean(true), SchemaKind::Object]);
+    assert_eq!(
+        tuple.prefix_items()[0].location(),
+        format!("{}/items/0", loc("tuple"))
+    );
+    assert!(matches!(
+        tuple.additional_items(),
+        Some(AdditionalView::Bool { value: false, .. })
+    ));
+    Ok(())
+}
+
+fn props_view<'s>(schemas: &'s Schemas, sch: SchemaIndex, name: &str) -> boon::SchemaView<'s> {
+    let view = schemas.view(sch);
+    let (_, v) = view
+        .properties()
+        .into_iter()
+        .find(|(n, _)| *n == name)
+        .expect("property must exist");
+    v
+}
+
+#[test]
+fn test_accepts_type() -> Result<(), Box<dyn Error>> {
+    use Type::*;
+    let (schemas, sch) = compile(json!({
+        "properties": {
+            "ref": { "$ref": "#/$defs/object" },
+            "refChain": { "$ref": "#/$defs/ref" },
+            "allOf": { "allOf": [{ "type": ["array", "object"] }, { "$ref": "#/$defs/object" }] },
+            "enum": { "enum": [1.5, "x"] },
+            "integers": { "enum": [1, 2] },
+            "not": { "not": { "type": "object" } },
+            "recursive": { "anyOf": [{ "$ref": "#/properties/recursive" }, { "type": "null" }] }
+        },
+        "$defs": {
+            "object": { "type": "object", "required": ["a"] },
+            "ref": { "$ref": "#/$defs/object", "properties": { "b": { "type": "string" } } }
+        }
+    }))?;
+    let accepted = |name: &str| -> Vec<Type> {
+        let loc = format!("http://tmp.com/schema.json#/properties/{name}");
+        let sch = schemas.contains_location(&loc).unwrap();
+        [Null, Boolean, Number, Integer, String, Array, Object]
+            .into_iter()
+            .filter(|t| schemas.accepts_type(sch, *t))
+            .collect()
+    };
+    assert_eq!(accepted("ref"), [Object]);
+    assert_eq!(accepted("refChain"), [Object]);
+    assert_eq!(accepted("allOf"), [Object]);
+    assert_eq!(accepted("enum"), [Number, String]);
+    assert_eq!(accepted("integers"), [Number, Integer]);
+    // not analyzed, or recursive, so anything is accepted
+    let all = [Null, Boolean, Number, Integer, String, Array, Object];
+    assert_eq!(accepted("not"), all);
+    assert_eq!(accepted("recursive"), all);
+
+    // root, as object or array from first byte
+    assert!(schemas.accepts_type(sch, Object));
+    assert!(schemas.accepts_type(sch, Array));
+    Ok(())
+}
+
+#[test]
+fn test_accepts_type_as_accepted_types() -> Result<(), Box<dyn Error>> {
+    // cached answers must match accepted_types, whichever schema is compiled first
+    let schema = json!({
+        "$defs": {
+            "a": { "type": "object", "allOf": [{ "$ref": "#/$defs/b" }] },
+            "b": { "allOf": [{ "$ref": "#/$defs/a" }], "type": ["object", "array"] },
+            "c": { "anyOf": [{ "$ref": "#/$defs/b" }, { "type": "integer" }] }
+        }
+    });
+    for first in ["a", "b", "c"] {
+        let mut schemas = Schemas::new();
+        let mut compiler = Compiler::new();
+        compiler.add_resource("http://tmp.com/schema.json", schema.clone())?;
+        let mut want = vec![];
+        for name in [first, "a", "b", "c"] {
+            let loc = format!("http://tmp.com/schema.json#/$defs/{name}");
+            let sch = compiler.compile(&loc, &mut schemas)?;
+            let types = schemas.accepted_types(sch).iter().collect::<Vec<_>>();
+            want.push((name, types));
+        }
+        for (name, types) in &want {
+            let loc = format!("http://tmp.com/schema.json#/$defs/{name}");
+            let sch = schemas.contains_location(&loc).unwrap();
+            for t in [
+                Type::Null,
+                Type::Boolean,
+                Type::String,
+                Type::Array,
+                Type::Object,
+            ] {
+                assert_eq!(
+                    schemas.accepts_type(sch, t),
+                    types.contains(&t),
+                    "{name} {t:?}"
+                );
+            }
+        }
+        let got = want.iter().map(|(_, t)| t.clone()).collect::<Vec<_>>();
+        assert_eq!(
+            got[1..],
+            [
+                vec![Type::Object],
+                vec![Type::Object],
+                vec![Type::Integer, Type::Object]
+            ],
+            "{first}"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn test_accepts_type_query_order() -> Result<(), Box<dyn Error>> {
+    // ring of refs: each def refers to next, last to first
+    let n = 500;
+    let defs = (0..n)
+        .map(|i| {
+            let next = format!("#/$defs/d{}", (i + 1) % n);
+            let sch =
+                json!({ "anyOf": [{ "$ref": next }, { "type": "string" }], "type": "string" });
+            (format!("d{i}"), sch)
+        })
+        .collect::<serde_json::Map<_, _>>();
+    let schema = json!({ "$defs": defs, "$ref": "#/$defs/d0" });
+
+    // answers do not depend on which schema is queried first
+    let mut answers = vec![];
+    for first in [0, n / 2] {
+        let (schemas, root) = compile(schema.clone())?;
+        let sch = |i: usize| {
+            let loc = format!("http://tmp.com/schema.json#/$defs/d{i}");
+            schemas.contains_location(&loc).unwrap()
+        };
+        assert!(schemas.accepts_type(sch(first), Type::String));
+        let got = (0..n)
+            .map(sch)
+            .chain([root])
+            .map(|sch| schemas.accepted_types(sch).iter().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert!(got.iter().all(|types| *types == [Type::String]));
+        answers.push(got);
+    }
+    assert_eq!(answers[0], answers[1]);
+    Ok(())
+}
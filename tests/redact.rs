@@ -0,0 +1,159 @@
+use std::error::Error;
+
+use boon::{Compiler, RedactBy, SchemaIndex, Schemas};
+use serde_json::{json, Value};
+
+fn compile(schema: Value) -> Result<(Schemas, SchemaIndex), Box<dyn Error>> {
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://tmp.com/schema.json", schema)?;
+    let sch_index = compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+    Ok((schemas, sch_index))
+}
+
+fn redact(schemas: &Schemas, sch: SchemaIndex, mut v: Value, by: RedactBy) -> (Value, Vec<String>) {
+    let locs = schemas.redact(&mut v, sch, by);
+    (v, locs.iter().map(|loc| loc.to_string()).collect())
+}
+
+#[test]
+fn test_redact_via_ref_and_items() -> Result<(), Box<dyn Error>> {
+    let (schemas, sch) = compile(json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "properties": {
+            "owner": { "$ref": "#/$defs/user" },
+            "members": { "items": { "$ref": "#/$defs/user" } }
+        },
+        "$defs": {
+            "user": {
+                "properties": {
+                    "name": { "type": "string" },
+                    "password": { "type": "string", "writeOnly": true },
+                    "token": { "x-sensitive": true }
+                }
+            }
+        }
+    }))?;
+    let instance = json!({
+        "owner": {"name": "alice", "password": "p1"},
+        "members": [
+            {"name": "bob", "password": "p2", "token": "t2"},
+            {"name": "carol"}
+        ]
+    });
+
+    let (v, locs) = redact(&schemas, sch, instance.clone(), RedactBy::WriteOnly);
+    assert_eq!(locs, ["/members/0/password", "/owner/password"]);
+    assert_eq!(
+        v,
+        json!({
+            "owner": {"name": "alice", "password": "[REDACTED]"},
+            "members": [
+                {"name": "bob", "password": "[REDACTED]", "token": "t2"},
+                {"name": "carol"}
+            ]
+        })
+    );
+
+    let (v, locs) = redact(&schemas, sch, instance, RedactBy::Annotation("x-sensitive"));
+    assert_eq!(locs, ["/members/0/token"]);
+    assert_eq!(v["members"][0]["token"], "[REDACTED]");
+    assert_eq!(v["owner"]["password"], "p1");
+    Ok(())
+}
+
+#[test]
+fn test_redact_conditionals() -> Result<(), Box<dyn Error>> {
+    let (schemas, sch) = compile(json!({
+        "if": { "properties": { "kind": { "const": "card" } } },
+        "then": { "properties": { "number": { "writeOnly": true } } },
+        "anyOf": [
+            { "required": ["pin"], "properties": { "pin": { "writeOnly": true } } },
+            { "properties": { "pin": { "type": "string" } } }
+        ]
+    }))?;
+
+    let instance = json!({"kind": "card", "number": "4111", "pin": 1234});
+    let (v, locs) = redact(&schemas, sch, instance, RedactBy::WriteOnly);
+    assert_eq!(locs, ["/number", "/pin"]);
+    assert_eq!(
+        v,
+        json!({"kind": "card", "number": "[REDACTED]", "pin": "[REDACTED]"})
+    );
+
+    // then does not apply; first anyOf branch fails
+    let instance = json!({"kind": "bank", "number": "4111"});
+    let (v, locs) = redact(&schemas, sch, instance.clone(), RedactBy::WriteOnly);
+    assert!(locs.is_empty());
+    assert_eq!(v, instance);
+    Ok(())
+}
+
+#[test]
+fn test_redact_invalid_instance() -> Result<(), Box<dyn Error>> {
+    let (schemas, sch) = compile(json!({
+        "properties": {
+            "name": { "minLength": 3 },
+            "password": { "writeOnly": true }
+        }
+    }))?;
+    let instance = json!({"name": "al", "password": "secret"});
+    assert!(schemas.validate(&instance, sch).is_err());
+    let (v, locs) = redact(&schemas, sch, instance, RedactBy::WriteOnly);
+    assert_eq!(locs, ["/password"]);
+    assert_eq!(v, json!({"name": "al", "password": "[REDACTED]"}));
+    Ok(())
+}
+
+#[test]
+fn test_redact_nested() -> Result<(), Box<dyn Error>> {
+    let (schemas, sch) = compile(json!({
+        "properties": {
+            "secrets": {
+                "writeOnly": true,
+                "properties": { "key": { "writeOnly": true } }
+            }
+        }
+    }))?;
+    let instance = json!({"secrets": {"key": "k"}});
+    let (v, locs) = redact(&schemas, sch, instance, RedactBy::WriteOnly);
+    assert_eq!(locs, ["/secrets"]);
+    assert_eq!(v, json!({"secrets": "[REDACTED]"}));
+    Ok(())
+}
+
+#[test]
+fn test_redact_ref_siblings_draft7() -> Result<(), Box<dyn Error>> {
+    // siblings of $ref are ignored, before 2019-09
+    let (schemas, sch) = compile(json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "properties": {
+            "a": { "$ref": "#/definitions/s", "writeOnly": true },
+            "b": { "allOf": [{ "$ref": "#/definitions/s" }], "writeOnly": true }
+        },
+        "definitions": { "s": { "type": "integer" } }
+    }))?;
+    let instance = json!({"a": 1, "b": 2});
+    let (_, locs) = redact(&schemas, sch, instance, RedactBy::WriteOnly);
+    assert_eq!(locs, ["/b"]);
+    Ok(())
+}
+
+#[test]
+fn test_redact_leaf_and_noop_schemas() -> Result<(), Box<dyn Error>> {
+    // flags do not stop such schemas from taking fast paths of validator
+    let (schemas, sch) = compile(json!({
+        "properties": {
+            "pins": { "items": { "type": "string", "writeOnly": true } },
+            "note": { "writeOnly": true, "description": "free text" }
+        }
+    }))?;
+    let instance = json!({"pins": ["1", "2"], "note": "n"});
+    let (v, locs) = redact(&schemas, sch, instance, RedactBy::WriteOnly);
+    assert_eq!(locs, ["/note", "/pins/0", "/pins/1"]);
+    assert_eq!(
+        v,
+        json!({"pins": ["[REDACTED]", "[REDACTED]"], "note": "[REDACTED]"})
+    );
+    Ok(())
+}
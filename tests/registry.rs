@@ -0,0 +1,122 @@
+use std::{
+    error::Error,
+    sync::{Arc, RwLock},
+    thread,
+};
+
+use boon::{CompileError, CompiledRegistry, Compiler, Draft, SchemaRegistry};
+use serde_json::json;
+
+#[test]
+fn test_registry() -> Result<(), Box<dyn Error>> {
+    let mut registry = SchemaRegistry::new();
+    registry.insert("address", json!({"required": ["city"]}));
+    registry.insert(
+        "person",
+        json!({"properties": {"home": {"$ref": "address"}}}),
+    );
+    registry.insert_url("dog", "tests/examples/dog.json");
+    let compiled = registry.build_strict()?;
+
+    assert!(compiled.index_of("address").is_some());
+    assert!(compiled.index_of("unknown").is_none());
+    assert_eq!(
+        compiled
+            .validate("person", &json!({"home": {"city": "x"}}))
+            .map(|r| r.is_ok()),
+        Some(true)
+    );
+    let instance = json!({"home": {}});
+    let e = compiled.validate("person", &instance).unwrap().unwrap_err();
+    assert_eq!(e.causes[0].instance_location.to_string(), "/home");
+    let valid = |name, v| compiled.validate(name, &v).map(|r| r.is_ok());
+    assert_eq!(valid("dog", json!({"speak": "bow"})), Some(true));
+    assert_eq!(valid("dog", json!({"speak": "meow"})), Some(false));
+    assert_eq!(valid("unknown", json!({})), None);
+
+    // validate using schemas
+    let sch = compiled.index_of("address").unwrap();
+    assert!(compiled.schemas().validate(&json!({}), sch).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_registry_errors() {
+    let mut registry = SchemaRegistry::new();
+    registry.insert("ok", json!({"type": "string"}));
+    registry.insert("bad", json!({"type": 1}));
+    registry.insert("uses-bad", json!({"$ref": "bad"}));
+    registry.insert_url("missing", "tests/examples/missing.json");
+
+    // other schemas still compile
+    let (compiled, errors) = registry.build();
+    let names = errors.iter().map(|(n, _)| n.as_str()).collect::<Vec<_>>();
+    assert_eq!(names, ["bad", "uses-bad", "missing"]);
+    assert!(compiled.validate("ok", &json!("x")).unwrap().is_ok());
+    assert!(compiled.index_of("bad").is_none());
+    assert!(compiled.validate("bad", &json!("x")).is_none());
+
+    let result = registry.build_strict();
+    assert!(
+        matches!(result, Err(CompileError::ValidationError { .. })),
+        "{:?}",
+        result.err()
+    );
+
+    // replaced
+    registry.insert("bad", json!({"type": "integer"}));
+    assert!(registry.remove("missing"));
+    assert!(!registry.remove("missing"));
+    let compiled = registry.build_strict().unwrap();
+    assert!(compiled.validate("uses-bad", &json!(1)).unwrap().is_ok());
+}
+
+#[test]
+fn test_registry_names() -> Result<(), Box<dyn Error>> {
+    let mut registry = SchemaRegistry::new();
+    registry.insert("a/b", json!({"type": "string"}));
+    registry.insert("a b#c?d", json!({"type": "integer"}));
+    registry.insert(
+        "refs",
+        json!({"prefixItems": [{"$ref": "a%2Fb"}, {"$ref": "a%20b%23c%3Fd"}]}),
+    );
+    let compiled = registry.build_strict()?;
+    let valid = |name, v| compiled.validate(name, &v).map(|r| r.is_ok());
+    assert_eq!(valid("a/b", json!("x")), Some(true));
+    assert_eq!(valid("a/b", json!(true)), Some(false));
+    assert_eq!(valid("a b#c?d", json!(1)), Some(true));
+    assert_eq!(valid("refs", json!(["x", 1])), Some(true));
+    assert_eq!(valid("refs", json!([1, "x"])), Some(false));
+
+    registry.insert("..", json!({}));
+    let (_, errors) = registry.build();
+    assert!(
+        matches!(&errors[..], [(name, CompileError::ParseUrlError { .. })] if name == ".."),
+        "{errors:?}"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_registry_reload() -> Result<(), Box<dyn Error>> {
+    let mut registry = SchemaRegistry::new();
+    registry.use_compiler(|| {
+        let mut c = Compiler::new();
+        c.set_default_draft(Draft::V4);
+        c
+    });
+    registry.insert("num", json!({"maximum": 5, "exclusiveMaximum": true}));
+    let current: RwLock<Arc<CompiledRegistry>> = RwLock::new(registry.build_strict()?);
+
+    let old = Arc::clone(&current.read().unwrap());
+    registry.insert("num", json!({"maximum": 10}));
+    *current.write().unwrap() = registry.build_strict()?;
+
+    // in-flight validation still uses old snapshot
+    let instance = json!(7);
+    let handle = thread::spawn(move || old.validate("num", &instance).unwrap().is_err());
+    assert!(handle.join().unwrap());
+    let current = current.read().unwrap();
+    assert!(current.validate("num", &json!(7)).unwrap().is_ok());
+    Ok(())
+}
@@ -0,0 +1,96 @@
+use std::{error::Error, io::Write};
+
+use boon::{CompileError, Compiler, Resource, Schemas, UrlLoader};
+use flate2::{write::GzEncoder, Compression};
+use serde_json::{json, Value};
+
+struct BytesLoader {
+    bytes: Vec<u8>,
+    content_encoding: Option<&'static str>,
+    content_type: Option<&'static str>,
+}
+
+impl UrlLoader for BytesLoader {
+    fn load(&self, url: &str) -> Result<Value, Box<dyn Error>> {
+        self.load_resource(url)?.into_json()
+    }
+
+    fn load_resource(&self, _url: &str) -> Result<Resource, Box<dyn Error>> {
+        Ok(Resource::Bytes {
+            bytes: self.bytes.clone(),
+            content_encoding: self.content_encoding.map(str::to_owned),
+            content_type: self.content_type.map(str::to_owned),
+        })
+    }
+}
+
+fn gzip(bytes: &[u8]) -> Vec<u8> {
+    let mut enc = GzEncoder::new(Vec::new(), Compression::default());
+    enc.write_all(bytes).unwrap();
+    enc.finish().unwrap()
+}
+
+fn compile(loader: BytesLoader) -> Result<(Schemas, boon::SchemaIndex), CompileError> {
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.use_loader(Box::new(loader));
+    let sch_index = compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+    Ok((schemas, sch_index))
+}
+
+#[test]
+fn test_gzip() -> Result<(), Box<dyn Error>> {
+    let (schemas, sch_index) = compile(BytesLoader {
+        bytes: gzip(br#"{"type": "string"}"#),
+        content_encoding: Some("gzip"),
+        content_type: Some("application/schema+json; charset=utf-8"),
+    })?;
+    assert!(schemas.validate(&json!("x"), sch_index).is_ok());
+    assert!(schemas.validate(&json!(1), sch_index).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_corrupt_gzip() {
+    let mut bytes = gzip(br#"{"type": "string"}"#);
+    bytes.truncate(bytes.len() / 2);
+    let result = compile(BytesLoader {
+        bytes,
+        content_encoding: Some("gzip"),
+        content_type: None,
+    });
+    let Err(e @ CompileError::LoadUrlError { .. }) = result else {
+        panic!("want LoadUrlError");
+    };
+    let msg = format!("{e:#}");
+    assert!(
+        msg.starts_with("error loading http://tmp.com/schema.json: gzip decompression failed"),
+        "{msg}"
+    );
+}
+
+#[test]
+fn test_unsupported_encoding() {
+    let result = compile(BytesLoader {
+        bytes: vec![],
+        content_encoding: Some("compress"),
+        content_type: None,
+    });
+    let Err(e) = result else {
+        panic!("want error");
+    };
+    assert!(format!("{e:#}").ends_with("unsupported content encoding \"compress\""));
+}
+
+#[test]
+#[cfg(feature = "yaml")]
+fn test_yaml() -> Result<(), Box<dyn Error>> {
+    let (schemas, sch_index) = compile(BytesLoader {
+        bytes: gzip(b"type: string\n"),
+        content_encoding: Some("gzip"),
+        content_type: Some("application/yaml"),
+    })?;
+    assert!(schemas.validate(&json!("x"), sch_index).is_ok());
+    assert!(schemas.validate(&json!(1), sch_index).is_err());
+    Ok(())
+}
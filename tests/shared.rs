@@ -0,0 +1,121 @@
+use std::{error::Error, sync::Arc};
+
+use boon::{
+    Compiler, ErrorKind, OwnedValidationError, SchemaIndex, Schemas, SharedSchemas,
+    ValidationOptions,
+};
+use serde_json::json;
+
+fn compile() -> Result<(Schemas, SchemaIndex), Box<dyn Error>> {
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.add_resource(
+        "http://tmp.com/schema.json",
+        json!({
+            "properties": {
+                "name": { "pattern": "^[a-z]+$" },
+                "tags": { "items": { "enum": ["a", "b"] } }
+            }
+        }),
+    )?;
+    let sch = compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+    Ok((schemas, sch))
+}
+
+#[test]
+fn test_arc_retained() -> Result<(), Box<dyn Error>> {
+    let (schemas, sch) = compile()?;
+    let schemas = Arc::new(schemas);
+    let shared = SharedSchemas::from(Arc::clone(&schemas));
+    assert_eq!(Arc::strong_count(&schemas), 2);
+
+    let errors = (0..3)
+        .filter_map(|i| {
+            // instance is dropped before error
+            let v = json!({"name": format!("X{i}"), "tags": ["c"]});
+            shared.validate(&v, sch).err()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(Arc::strong_count(&schemas), 5);
+    assert!(Arc::ptr_eq(errors[0].schemas(), &schemas));
+
+    drop(shared);
+    drop(schemas);
+    let e = errors[2].error();
+    assert_eq!(e.schema_url, "http://tmp.com/schema.json#");
+    let causes = e
+        .causes
+        .iter()
+        .map(|c| c.schema_url.as_ref())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        causes,
+        [
+            "http://tmp.com/schema.json#/properties/name",
+            "http://tmp.com/schema.json#/properties/tags/items"
+        ]
+    );
+    assert!(errors[2]
+        .to_string()
+        .contains("'X2' does not match pattern"));
+    assert_eq!(Arc::strong_count(errors[0].schemas()), 3);
+    Ok(())
+}
+
+#[test]
+fn test_into_owned() -> Result<(), Box<dyn Error>> {
+    let (schemas, sch) = compile()?;
+    let shared = SharedSchemas::new(schemas);
+    let e = shared.validate(&json!({"name": "X"}), sch).unwrap_err();
+    let want = format!("{e:#}");
+    let owned = e.into_owned();
+    assert_eq!(Arc::strong_count(shared.schemas()), 1);
+    assert_eq!(format!("{owned:#}"), want);
+    Ok(())
+}
+
+#[test]
+fn test_static_error() -> Result<(), Box<dyn Error>> {
+    fn validate(shared: &SharedSchemas, sch: SchemaIndex) -> Result<(), Box<dyn Error>> {
+        let v = json!({"tags": [1]});
+        shared.validate(&v, sch)?;
+        Ok(())
+    }
+    let (schemas, sch) = compile()?;
+    let shared = SharedSchemas::new(schemas);
+    let e = validate(&shared, sch).unwrap_err();
+    drop(shared);
+    let e = e.downcast::<OwnedValidationError>().unwrap();
+    assert_eq!(
+        e.error().causes[0].schema_url,
+        "http://tmp.com/schema.json#/properties/tags/items"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_validate_with_output() -> Result<(), Box<dyn Error>> {
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.disable_keyword("pattern");
+    compiler.add_resource(
+        "http://tmp.com/schema.json",
+        json!({"properties": {"name": {"pattern": "^[a-z]+$"}}}),
+    )?;
+    let sch = compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+    let shared = SharedSchemas::new(schemas);
+
+    let opts = ValidationOptions::new().warn_ignored(true);
+    let out = shared.validate_with(&json!({"name": "X"}), sch, &opts)?;
+    assert_eq!(out.warnings.len(), 1);
+    let warning = &out.warnings[0];
+    let ErrorKind::IgnoredKeyword { keyword, .. } = &warning.kind else {
+        panic!("unexpected warning {warning}");
+    };
+    assert_eq!(keyword, "pattern");
+    assert_eq!(
+        warning.schema_url,
+        "http://tmp.com/schema.json#/properties/name"
+    );
+    Ok(())
+}
@@ -0,0 +1,193 @@
+use std::{collections::BTreeMap, error::Error};
+
+use boon::{Compiler, ErrorSummary, SchemaIndex, Schemas, ValidationError, ValidationOptions};
+use serde_json::{json, Value};
+
+fn compile(schema: Value) -> Result<(Schemas, SchemaIndex), Box<dyn Error>> {
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://tmp.com/schema.json", schema)?;
+    let sch = compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+    Ok((schemas, sch))
+}
+
+fn leaves<'a, 's, 'v>(e: &'a ValidationError<'s, 'v>, out: &mut Vec<&'a ValidationError<'s, 'v>>) {
+    if e.causes.is_empty() {
+        out.push(e);
+    }
+    for cause in &e.causes {
+        leaves(cause, out);
+    }
+}
+
+// checks summary against the full error tree
+fn check(schema: Value, instances: &[Value], required_details: bool) -> Result<(), Box<dyn Error>> {
+    let (schemas, sch) = compile(schema)?;
+    let opts = ValidationOptions::new().required_details(required_details);
+    for v in instances {
+        let full = schemas.validate_with(v, sch, &opts);
+        let summary = schemas.validate_summary_with(v, sch, &opts, 3);
+        let (e, summary) = match (full, summary) {
+            (Ok(_), Ok(_)) => continue,
+            (Err(e), Err(summary)) => (e, summary),
+            (full, summary) => panic!("{v}: validate {full:?}, validate_summary {summary:?}"),
+        };
+
+        let mut want = vec![];
+        leaves(&e, &mut want);
+        assert_eq!(summary.total, want.len(), "{v}: {e:#}");
+
+        // instances here have no duplicate leaves, which are merged
+        let mut counts = BTreeMap::new();
+        for leaf in e.by_instance_location().values().flatten() {
+            *counts.entry(leaf.code.to_owned()).or_insert(0) += 1;
+        }
+        assert_eq!(summary.counts, counts, "{v}: {e:#}");
+
+        let want = want
+            .iter()
+            .take(3)
+            .map(|e| format!("{e:#}"))
+            .collect::<Vec<_>>();
+        let got = summary
+            .errors
+            .iter()
+            .map(|e| format!("{e:#}"))
+            .collect::<Vec<_>>();
+        assert_eq!(got, want, "{v}");
+    }
+    Ok(())
+}
+
+#[test]
+fn test_items() -> Result<(), Box<dyn Error>> {
+    let schema = json!({"items": {"type": "integer", "minimum": 0}});
+    let (schemas, sch) = compile(schema.clone())?;
+    let v = json!((0..1000)
+        .map(|i| if i % 2 == 0 {
+            json!(-i - 1)
+        } else {
+            json!("x")
+        })
+        .collect::<Vec<_>>());
+    let summary = schemas.validate_summary(&v, sch).unwrap_err();
+    assert_eq!(summary.total, 1000);
+    assert_eq!(summary.counts["minimum"], 500);
+    assert_eq!(summary.counts["type"], 500);
+    assert_eq!(summary.errors.len(), 10);
+    assert_eq!(summary.errors[1].instance_location.to_string(), "/1");
+    assert!(summary.to_string().ends_with("... 990 more"));
+    check(schema, &[v, json!([1, 2]), json!([-1])], false)
+}
+
+#[test]
+fn test_any_of() -> Result<(), Box<dyn Error>> {
+    let schema = json!({
+        "items": {
+            "anyOf": [
+                {"type": "string", "minLength": 2},
+                {"type": "integer"},
+                {"items": {"type": "null"}}
+            ]
+        }
+    });
+    let instances = [
+        json!(["a", 1, "ab", [null, 1, 2], 1.5]),
+        json!(["ab", 1, [null]]),
+    ];
+    check(schema, &instances, false)
+}
+
+#[test]
+fn test_one_of() -> Result<(), Box<dyn Error>> {
+    let schema = json!({
+        "items": {
+            "oneOf": [
+                {"type": "string", "maxLength": 2},
+                {"type": "string", "minLength": 2},
+                {"type": "integer", "maximum": 3}
+            ]
+        }
+    });
+    check(schema, &[json!(["abc", "a", "ab", 1, 5, true])], false)
+}
+
+#[test]
+fn test_contains() -> Result<(), Box<dyn Error>> {
+    let schema = json!({
+        "properties": {
+            "a": {"contains": {"type": "string"}},
+            "b": {"contains": {"type": "string"}, "minContains": 2, "maxContains": 3},
+        }
+    });
+    let instances = [
+        json!({"a": [1, 2], "b": [1, "x"]}),
+        json!({"a": [1, "x"], "b": ["x", "y", "z", "w", 1]}),
+        json!({"a": [], "b": []}),
+    ];
+    check(schema, &instances, false)
+}
+
+#[test]
+fn test_required_details() -> Result<(), Box<dyn Error>> {
+    let schema = json!({
+        "items": {"required": ["a", "b", "c"], "properties": {"a": {"type": "string"}}}
+    });
+    let instances = [json!([{}, {"a": 1, "b": 2}, {"a": "x", "b": 1, "c": 2}])];
+    check(schema.clone(), &instances, true)?;
+    check(schema, &instances, false)
+}
+
+#[test]
+fn test_property_names() -> Result<(), Box<dyn Error>> {
+    let schema = json!({
+        "propertyNames": {"pattern": "^[a-z]+$", "maxLength": 3},
+        "additionalProperties": {"type": "string"}
+    });
+    check(
+        schema,
+        &[json!({"A": 1, "abcde": "x", "ABCD": 2, "ok": "y"})],
+        false,
+    )
+}
+
+#[test]
+fn test_ref_siblings() -> Result<(), Box<dyn Error>> {
+    let schema = json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "definitions": {"s": {"type": "string"}},
+        "items": {"$ref": "#/definitions/s", "format": "email", "minLength": 10}
+    });
+    check(schema, &[json!([1, "x", "a@b.com"])], false)
+}
+
+#[test]
+fn test_valid() -> Result<(), Box<dyn Error>> {
+    let (schemas, sch) = compile(json!({"type": "array"}))?;
+    assert!(schemas.validate_summary(&json!([]), sch).is_ok());
+    let summary: ErrorSummary = schemas.validate_summary(&json!(1), sch).unwrap_err();
+    assert_eq!(summary.total, 1);
+    assert_eq!(
+        summary.errors[0].kind.to_string(),
+        "want array, but got number"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_uneval() -> Result<(), Box<dyn Error>> {
+    let schema = json!({
+        "$defs": {"n": {"type": "integer", "minimum": 5}},
+        "properties": {"a": {"$ref": "#/$defs/n"}},
+        "if": {"required": ["b"]},
+        "then": {"properties": {"b": {"type": "string"}}},
+        "else": {"properties": {"c": {"$ref": "#/$defs/n"}}},
+        "dependentSchemas": {"d": {"required": ["e"]}},
+        "unevaluatedProperties": {"type": "boolean"}
+    });
+    let instances = [
+        json!({"a": 1, "b": 2, "x": 3, "y": true}),
+        json!({"a": "x", "c": 1, "d": 1, "z": "w"}),
+    ];
+    check(schema, &instances, false)
+}
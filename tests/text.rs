@@ -0,0 +1,155 @@
+use std::error::Error;
+
+use boon::{
+    text::{locate, locate_keyword, TextPos, TextSpan},
+    AbsoluteKeywordLocation, Compiler, InstanceLocation, KeywordPath, OutputError, SchemaToken,
+    Schemas,
+};
+use serde_json::Value;
+
+fn loc(tokens: Vec<boon::InstanceToken>) -> InstanceLocation {
+    InstanceLocation { tokens }
+}
+
+fn pos(offset: usize, line: usize, column: usize) -> TextPos {
+    TextPos {
+        offset,
+        line,
+        column,
+    }
+}
+
+#[test]
+fn test_locate() {
+    let text = "{\n  \"a\": {\n    \"b\": [true, null]\n  }\n}";
+    let span = locate(text, &loc(vec!["a".into(), "b".into(), 1.into()])).unwrap();
+    assert_eq!(
+        span,
+        TextSpan {
+            start: pos(27, 3, 17),
+            end: pos(31, 3, 21),
+        }
+    );
+    assert_eq!(span.to_string(), "3:17-3:21");
+
+    // multiline value
+    let span = locate(text, &loc(vec!["a".into()])).unwrap();
+    assert_eq!((span.start.line, span.start.column), (2, 8));
+    assert_eq!((span.end.line, span.end.column), (4, 4));
+
+    // root
+    let span = locate(text, &loc(vec![])).unwrap();
+    assert_eq!(span.start, pos(0, 1, 1));
+    assert_eq!(span.end, pos(text.len(), 5, 2));
+
+    assert_eq!(locate(text, &loc(vec!["x".into()])), None);
+    assert_eq!(locate(text, &loc(vec!["a".into(), 0.into()])), None);
+}
+
+#[test]
+fn test_locate_after_escaped_strings() {
+    let text = r#"{"q\"\\": "a\"b", "k1": [1, "\"]"], "x": 2}"#;
+    let span = locate(text, &loc(vec!["x".into()])).unwrap();
+    assert_eq!(&text[span.start.offset..span.end.offset], "2");
+    assert_eq!(span.start.column, span.start.offset + 1);
+
+    let span = locate(text, &loc(vec!["k1".into(), 1.into()])).unwrap();
+    assert_eq!(&text[span.start.offset..span.end.offset], r#""\"]""#);
+
+    let span = locate(text, &loc(vec!["q\"\\".into()])).unwrap();
+    assert_eq!(&text[span.start.offset..span.end.offset], r#""a\"b""#);
+}
+
+#[test]
+fn test_locate_multibyte() {
+    let text = "{\"naïve\": \"日本\", \"x\": [\"€\", 1]}";
+    let span = locate(text, &loc(vec!["x".into(), 1.into()])).unwrap();
+    assert_eq!(&text[span.start.offset..span.end.offset], "1");
+    // ï is 2 bytes, 日 and 本 are 3 bytes each, € is 3 bytes
+    assert_eq!(span.start, pos(34, 1, 28));
+    assert_eq!(span.end, pos(35, 1, 29));
+
+    let span = locate(text, &loc(vec!["naïve".into()])).unwrap();
+    assert_eq!(span.start, pos(11, 1, 11));
+    assert_eq!(span.end, pos(19, 1, 15));
+}
+
+#[test]
+fn test_locate_keyword() -> Result<(), Box<dyn Error>> {
+    let text = r##"{
+  "$defs": {
+    "a b": {"enum": ["x", "y"]},
+    "r": {"required": ["p", "q"]}
+  },
+  "properties": {
+    "a/é": {"$ref": "#/$defs/a%20b"},
+    "n": {"$ref": "#/$defs/r"}
+  }
+}"##;
+    let url = "http://tmp.com/schema.json";
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.add_resource(url, serde_json::from_str::<Value>(text)?)?;
+    let sch = compiler.compile(url, &mut schemas)?;
+
+    let v = serde_json::json!({"a/é": "z", "n": {"p": 1}});
+    let e = schemas.validate(&v, sch).unwrap_err();
+    let mut got = vec![];
+    let OutputError::Branch(units) = e.basic_output().error else {
+        panic!("want branch");
+    };
+    for unit in units {
+        let Some(kw_loc) = &unit.absolute_keyword_location else {
+            continue;
+        };
+        let span = locate_keyword(text, kw_loc).unwrap();
+        got.push((
+            span.start.to_string(),
+            text[span.start.offset..span.end.offset].to_owned(),
+        ));
+    }
+    assert_eq!(
+        got,
+        [
+            ("3:21".to_owned(), r#"["x", "y"]"#.to_owned()),
+            ("4:23".to_owned(), r#"["p", "q"]"#.to_owned()),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_locate_keyword_escaped() {
+    let text = r#"{"properties": {"a~b/c": {"minimum": 1}}, "required": ["x"]}"#;
+    let kw_loc = AbsoluteKeywordLocation {
+        schema_url: "http://a.com/s.json#/properties/a~0b~1c",
+        keyword_path: Some(KeywordPath {
+            keyword: "minimum",
+            token: None,
+        }),
+    };
+    let span = locate_keyword(text, &kw_loc).unwrap();
+    assert_eq!(&text[span.start.offset..span.end.offset], "1");
+
+    let kw_loc = AbsoluteKeywordLocation {
+        schema_url: "http://a.com/s.json#",
+        keyword_path: Some(KeywordPath {
+            keyword: "required",
+            token: Some(SchemaToken::Item(0)),
+        }),
+    };
+    let span = locate_keyword(text, &kw_loc).unwrap();
+    assert_eq!(&text[span.start.offset..span.end.offset], r#""x""#);
+
+    // anchors and bad indexes are not located
+    let kw_loc = AbsoluteKeywordLocation {
+        schema_url: "http://a.com/s.json#foo",
+        keyword_path: None,
+    };
+    assert_eq!(locate_keyword(text, &kw_loc), None);
+    let kw_loc = AbsoluteKeywordLocation {
+        schema_url: "http://a.com/s.json#/required/00",
+        keyword_path: None,
+    };
+    assert_eq!(locate_keyword(text, &kw_loc), None);
+}
@@ -0,0 +1,118 @@
+#![cfg(feature = "serde")]
+
+use std::error::Error;
+
+use boon::{CombinedError, Compiler, SchemaIndex, Schemas};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Order {
+    id: u32,
+    items: Vec<Item>,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Item {
+    name: String,
+    qty: u8,
+    kind: Kind,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+enum Kind {
+    Plain,
+    Gift { note: String },
+}
+
+fn compile(schema: Value) -> Result<(Schemas, SchemaIndex), Box<dyn Error>> {
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.add_resource("http://tmp.com/schema.json", schema)?;
+    let sch_index = compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+    Ok((schemas, sch_index))
+}
+
+// drifted from Order: qty is not bounded, note is not required
+fn schema() -> Value {
+    json!({
+        "required": ["id", "items"],
+        "properties": {
+            "id": { "type": "integer", "minimum": 1 },
+            "items": {
+                "items": {
+                    "required": ["name", "qty", "kind"],
+                    "properties": {
+                        "name": { "type": "string" },
+                        "qty": { "type": "integer" }
+                    }
+                }
+            }
+        }
+    })
+}
+
+fn deserialization_location(result: Result<Order, CombinedError>) -> String {
+    match result {
+        Err(CombinedError::Deserialization {
+            instance_location, ..
+        }) => instance_location.to_string(),
+        other => panic!("want deserialization error, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_validate_into() -> Result<(), Box<dyn Error>> {
+    let (schemas, sch) = compile(schema())?;
+    let v = json!({
+        "id": 7,
+        "items": [
+            {"name": "a", "qty": 1, "kind": "Plain"},
+            {"name": "b", "qty": 2, "kind": {"Gift": {"note": "hi"}}}
+        ]
+    });
+    let order: Order = schemas.validate_into(&v, sch).map_err(|e| e.to_string())?;
+    assert_eq!(order.id, 7);
+    assert_eq!(
+        order.items[1].kind,
+        Kind::Gift {
+            note: "hi".to_owned()
+        }
+    );
+    Ok(())
+}
+
+#[test]
+fn test_validate_into_invalid() -> Result<(), Box<dyn Error>> {
+    let (schemas, sch) = compile(schema())?;
+    let v = json!({"id": 0, "items": []});
+    let Err(CombinedError::Validation(e)) = schemas.validate_into::<Order>(&v, sch) else {
+        panic!("validation must fail");
+    };
+    assert_eq!(e.causes[0].instance_location.to_string(), "/id");
+    Ok(())
+}
+
+#[test]
+fn test_validate_into_drifted() -> Result<(), Box<dyn Error>> {
+    let (schemas, sch) = compile(schema())?;
+    let item = |qty: Value, kind: Value| json!({"name": "a", "qty": qty, "kind": kind});
+
+    let v = json!({"id": 1, "items": [item(json!(1), json!("Plain")), item(json!(300), json!("Plain"))]});
+    let result = schemas.validate_into(&v, sch);
+    assert_eq!(deserialization_location(result), "/items/1/qty");
+
+    let v = json!({"id": 1, "items": [item(json!(1), json!({"Gift": {}}))]});
+    let result = schemas.validate_into(&v, sch);
+    let err = result.as_ref().unwrap_err().to_string();
+    assert!(
+        err.starts_with("deserialization failed at '/items/0/kind/Gift': missing field `note`"),
+        "{err}"
+    );
+    assert_eq!(deserialization_location(result), "/items/0/kind/Gift");
+
+    let v = json!({"id": 1, "items": [item(json!(1), json!("Other"))]});
+    let result = schemas.validate_into(&v, sch);
+    assert_eq!(deserialization_location(result), "/items/0/kind");
+    Ok(())
+}
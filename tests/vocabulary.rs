@@ -0,0 +1,125 @@
+use std::{any::Any, error::Error};
+
+use boon::{CompileError, Compiler, Keyword, KeywordValidator, Schemas, Vocabulary};
+use serde_json::{json, Value};
+
+const VOCAB: &str = "http://tmp.com/vocab/words";
+
+struct MaxWords(usize);
+
+impl KeywordValidator for MaxWords {
+    fn validate(&self, v: &Value, _ctx: Option<&dyn Any>) -> Result<(), Box<dyn Error>> {
+        let Value::String(s) = v else {
+            return Ok(());
+        };
+        let words = s.split_whitespace().count();
+        if words > self.0 {
+            Err(format!("want at most {} words, but got {words}", self.0))?;
+        }
+        Ok(())
+    }
+}
+
+fn compile_max_words(v: &Value) -> Result<Box<dyn KeywordValidator>, Box<dyn Error>> {
+    let Some(n) = v.as_u64() else {
+        Err("maxWords must be non-negative integer")?
+    };
+    Ok(Box::new(MaxWords(n as usize)))
+}
+
+fn metaschema(vocabs: Value) -> Value {
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "$vocabulary": vocabs,
+    })
+}
+
+fn new_compiler(metaschema: Value) -> Result<Compiler, Box<dyn Error>> {
+    let mut compiler = Compiler::new();
+    compiler.register_vocabulary(
+        VOCAB,
+        Vocabulary {
+            keywords: vec![Keyword {
+                name: "maxWords",
+                compile: compile_max_words,
+            }],
+            metaschema: Some(("http://tmp.com/meta.json".into(), metaschema)),
+        },
+    )?;
+    compiler.add_resource(
+        "http://tmp.com/schema.json",
+        json!({
+            "$schema": "http://tmp.com/meta.json",
+            "type": "string",
+            "maxWords": 2
+        }),
+    )?;
+    Ok(compiler)
+}
+
+#[test]
+fn test_vocabulary_activation() -> Result<(), Box<dyn Error>> {
+    let core = "https://json-schema.org/draft/2020-12/vocab/core";
+    let validation = "https://json-schema.org/draft/2020-12/vocab/validation";
+    let tests = [
+        (json!({ core: true, validation: true, VOCAB: true }), true),
+        (json!({ core: true, validation: true, VOCAB: false }), true),
+        (json!({ core: true, validation: true }), false),
+    ];
+    for (vocabs, active) in tests {
+        let mut compiler = new_compiler(metaschema(vocabs.clone()))?;
+        let mut schemas = Schemas::new();
+        let sch_index = compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+        assert!(schemas.validate(&json!("a b"), sch_index).is_ok());
+        assert!(schemas.validate(&json!(1), sch_index).is_err());
+        let v = json!("a b c");
+        let result = schemas.validate(&v, sch_index);
+        assert_eq!(result.is_err(), active, "{vocabs}");
+        if let Err(e) = result {
+            assert!(
+                e.to_string()
+                    .contains("maxWords failed: want at most 2 words, but got 3"),
+                "{e}"
+            );
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_unregistered_vocabulary() -> Result<(), Box<dyn Error>> {
+    let core = "https://json-schema.org/draft/2020-12/vocab/core";
+    let other = "http://tmp.com/vocab/other";
+
+    // optional, so ignored
+    let mut compiler = new_compiler(metaschema(json!({ core: true, other: false })))?;
+    let mut schemas = Schemas::new();
+    compiler.compile("http://tmp.com/schema.json", &mut schemas)?;
+
+    // required
+    let mut compiler = new_compiler(metaschema(json!({ core: true, other: true })))?;
+    let mut schemas = Schemas::new();
+    let result = compiler.compile("http://tmp.com/schema.json", &mut schemas);
+    assert!(
+        matches!(&result, Err(CompileError::UnsupportedVocabulary { vocabulary, .. }) if vocabulary == other),
+        "{result:?}"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_invalid_keyword() -> Result<(), Box<dyn Error>> {
+    let core = "https://json-schema.org/draft/2020-12/vocab/core";
+    let mut compiler = new_compiler(metaschema(json!({ core: true, VOCAB: true })))?;
+    compiler.add_resource(
+        "http://tmp.com/invalid.json",
+        json!({ "$schema": "http://tmp.com/meta.json", "maxWords": "two" }),
+    )?;
+    let mut schemas = Schemas::new();
+    let result = compiler.compile("http://tmp.com/invalid.json", &mut schemas);
+    let Err(CompileError::InvalidKeyword { url, .. }) = result else {
+        panic!("want InvalidKeyword, got {result:?}");
+    };
+    assert_eq!(url, "http://tmp.com/invalid.json#/maxWords");
+    Ok(())
+}
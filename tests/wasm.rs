@@ -0,0 +1,55 @@
+#![cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+
+// run with: cargo test --target wasm32-unknown-unknown --test wasm
+// needs wasm-bindgen-test-runner from wasm-bindgen-cli
+
+use boon::{CompileError, Compiler, Schemas};
+use serde_json::json;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+#[wasm_bindgen_test]
+fn test_compile_and_validate() {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "name": { "$ref": "http://tmp.com/name.json" },
+            "born": { "type": "string", "format": "date-time" },
+            "email": { "type": "string", "pattern": "^\\S+@\\S+$" }
+        },
+        "required": ["name"]
+    });
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    compiler.enable_format_assertions();
+    compiler
+        .add_resource(
+            "http://tmp.com/name.json",
+            json!({"type": "string", "minLength": 1}),
+        )
+        .unwrap();
+    compiler
+        .add_resource("http://tmp.com/schema.json", schema)
+        .unwrap();
+    let sch_index = compiler
+        .compile("http://tmp.com/schema.json", &mut schemas)
+        .unwrap();
+
+    let valid = json!({"name": "x", "born": "1990-12-31T23:59:59Z", "email": "a@b"});
+    assert!(schemas.validate(&valid, sch_index).is_ok());
+    let invalid = json!({"name": "", "born": "yesterday", "email": "a b"});
+    let Err(e) = schemas.validate(&invalid, sch_index) else {
+        panic!("validation must fail");
+    };
+    assert_eq!(e.causes.len(), 3);
+}
+
+#[wasm_bindgen_test]
+fn test_no_file_loader() {
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    let result = compiler.compile("file:///tmp/schema.json", &mut schemas);
+    assert!(
+        matches!(result, Err(CompileError::UnsupportedUrlScheme { .. })),
+        "{result:?}"
+    );
+}